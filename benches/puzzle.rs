@@ -0,0 +1,150 @@
+//! Benchmarks for puzzle geometry generation, twist application, and depth
+//! sorting.
+//!
+//! Hyperspeedcube is a binary crate, so this file mirrors the module tree from
+//! `src/main.rs` to get access to the internals. Every native module declared
+//! in `src/main.rs` must also be declared here.
+
+#![allow(dead_code, unused_imports, unused_macros)]
+
+#[macro_use]
+extern crate ambassador;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate strum;
+
+#[macro_use]
+#[path = "../src/debug.rs"]
+mod debug;
+#[macro_use]
+#[path = "../src/i18n.rs"]
+mod i18n;
+#[path = "../src/app.rs"]
+mod app;
+#[path = "../src/audio.rs"]
+mod audio;
+#[path = "../src/camera.rs"]
+mod camera;
+#[path = "../src/cli.rs"]
+mod cli;
+#[path = "../src/commands.rs"]
+mod commands;
+#[path = "../src/crash.rs"]
+mod crash;
+#[path = "../src/detached.rs"]
+mod detached;
+#[path = "../src/gui/mod.rs"]
+mod gui;
+#[path = "../src/history/mod.rs"]
+mod history;
+#[path = "../src/hotkeys.rs"]
+mod hotkeys;
+#[path = "../src/icon.rs"]
+mod icon;
+#[path = "../src/log_buffer.rs"]
+mod log_buffer;
+#[path = "../src/logfile/mod.rs"]
+mod logfile;
+#[path = "../src/net/mod.rs"]
+mod net;
+#[path = "../src/overlay.rs"]
+mod overlay;
+#[path = "../src/preferences/mod.rs"]
+mod preferences;
+#[path = "../src/puzzle/mod.rs"]
+mod puzzle;
+#[path = "../src/render/mod.rs"]
+mod render;
+#[path = "../src/scripting.rs"]
+mod scripting;
+#[path = "../src/serde_impl/mod.rs"]
+mod serde_impl;
+#[path = "../src/util.rs"]
+mod util;
+
+const TITLE: &str = "Hyperspeedcube";
+
+use cgmath::{point2, Point2};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+use preferences::{Preferences, DEFAULT_PREFS};
+use puzzle::traits::*;
+use puzzle::{geometry, Puzzle, PuzzleController, PuzzleTypeEnum, Twist};
+
+/// Number of random twists applied per iteration of the twist benchmark.
+const TWIST_COUNT: usize = 100;
+
+fn puzzle_types() -> Vec<PuzzleTypeEnum> {
+    itertools::chain!(
+        [3, 5, 7, 9].map(|layer_count| PuzzleTypeEnum::Rubiks3D { layer_count }),
+        [3, 5, 7, 9].map(|layer_count| PuzzleTypeEnum::Rubiks4D { layer_count }),
+    )
+    .collect()
+}
+
+fn prefs() -> Preferences {
+    DEFAULT_PREFS.clone()
+}
+
+/// Returns a visible region that is large enough to contain the whole puzzle.
+fn visible_bounds() -> (Point2<f32>, Point2<f32>) {
+    (point2(-100.0, -100.0), point2(100.0, 100.0))
+}
+
+fn bench_geometry(c: &mut Criterion) {
+    let prefs = prefs();
+    let mut group = c.benchmark_group("geometry");
+    for ty in puzzle_types() {
+        group.bench_with_input(BenchmarkId::from_parameter(ty.name()), &ty, |b, &ty| {
+            b.iter_batched(
+                || PuzzleController::new(ty),
+                |mut puzzle| puzzle.geometry(&prefs, visible_bounds()),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_twist(c: &mut Criterion) {
+    let mut group = c.benchmark_group("twist");
+    for ty in puzzle_types() {
+        let twists: Vec<Twist> = (0..TWIST_COUNT).map(|_| Twist::from_rng(ty)).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(ty.name()), &ty, |b, &ty| {
+            b.iter_batched(
+                || Puzzle::new(ty),
+                |mut puzzle| {
+                    for &twist in &twists {
+                        puzzle.twist(twist).expect("error applying twist");
+                    }
+                    puzzle
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_depth_sort(c: &mut Criterion) {
+    let prefs = prefs();
+    let mut group = c.benchmark_group("depth_sort");
+    for ty in puzzle_types() {
+        let mut puzzle = PuzzleController::new(ty);
+        let mut stickers = (*puzzle.geometry(&prefs, visible_bounds())).clone();
+        // Start from the worst case: front to back.
+        stickers.reverse();
+        group.bench_with_input(BenchmarkId::from_parameter(ty.name()), &ty, |b, _| {
+            b.iter_batched(
+                || stickers.clone(),
+                |mut stickers| geometry::sort_by_depth(&mut stickers),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_geometry, bench_twist, bench_depth_sort);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+//! Puzzle model for Hyperspeedcube, without any GUI or rendering dependencies.
+//!
+//! This includes puzzle types and states, twists and notation, scrambling,
+//! sticker geometry, undo history, and log file formats. Projects such as bots, solvers, and web
+//! tools can depend on this crate to work with puzzles directly.
+
+#![warn(clippy::if_then_some_else_none)]
+#![allow(
+    clippy::collapsible_match,
+    clippy::match_like_matches_macro,
+    clippy::single_match,
+    clippy::useless_format
+)]
+
+#[macro_use]
+extern crate ambassador;
+#[macro_use]
+extern crate enum_dispatch;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate strum;
+
+pub mod logfile;
+pub mod puzzle;
+pub mod serde_impl;
+pub mod util;
+mod view;
+
+pub use view::ViewPreferences;
@@ -0,0 +1,181 @@
+//! Log file formats for saving and loading puzzle states.
+//!
+//! These types only describe what is stored in a log file. Turning a log file
+//! into a puzzle with undo history is up to the application.
+
+use bitvec::vec::BitVec;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+pub mod mc4d_compat;
+
+use crate::puzzle::*;
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LogFileFormat {
+    #[default]
+    Hsc,
+    Mc4d,
+}
+impl LogFileFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            LogFileFormat::Hsc => "hsc",
+            LogFileFormat::Mc4d => "log",
+        }
+    }
+}
+
+/// Hyperspeedcube log file.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LogFile {
+    pub version: usize,
+    #[serde(default)]
+    pub puzzle: Option<PuzzleTypeEnum>,
+    #[serde(default)]
+    pub state: u8,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_impl::hex_bitvec::opt"
+    )]
+    pub visible_pieces: Option<BitVec>,
+    #[serde(
+        default,
+        skip_serializing_if = "cgmath::Zero::is_zero",
+        skip_deserializing
+    )]
+    pub scramble_length: usize,
+    #[serde(default, skip_deserializing)]
+    pub twist_count: BTreeMap<TwistMetric, usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub progress: Vec<ProgressSnapshot>,
+    /// Comments on states in the solve, keyed by the number of twists applied
+    /// since the scramble.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<usize, String>,
+    /// Branches of the undo history that are not on the path to the saved
+    /// state.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<LogBranch>,
+    /// Whether `twists` were made on the inverse of the scramble.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inverse: bool,
+    /// Twists made on the other side of the scramble, which are applied as
+    /// premoves.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub premoves: String,
+    #[serde(default, skip_serializing)] // manually serialized
+    pub scramble: String,
+    #[serde(default, skip_serializing)] // manually serialized
+    pub twists: String,
+}
+impl fmt::Display for LogFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", Self::COMMENT_STRING)?;
+        write!(
+            f,
+            "{}",
+            serde_yaml::to_string(self).map_err(|_| fmt::Error)?,
+        )?;
+        if !self.scramble.is_empty() {
+            writeln!(f, "scramble: >")?;
+            for line in self.scramble.lines() {
+                writeln!(f, "  {line}")?;
+            }
+        }
+        if !self.twists.is_empty() {
+            writeln!(f, "twists: >")?;
+            for line in self.twists.lines() {
+                writeln!(f, "  {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+impl LogFile {
+    pub const COMMENT_STRING: &'static str = "# Hyperspeedcube puzzle log";
+    pub const VERSION: usize = 1;
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(puzzle_ty) = self.puzzle {
+            puzzle_ty.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn scramble(&self) -> (Vec<Twist>, Vec<TwistParseError<'_>>) {
+        let mut ret_twists = vec![];
+        let mut ret_errors = vec![];
+        for twist_str in self.scramble.split_whitespace() {
+            match twist_str.parse() {
+                Ok(twist) => ret_twists.push(twist),
+                Err(()) => ret_errors.push(TwistParseError {
+                    twist_str,
+                    error_msg: "invalid twist".to_string(),
+                }),
+            }
+        }
+        (ret_twists, ret_errors)
+    }
+
+    pub fn twists(&self, puzzle_type: &dyn PuzzleType) -> (Vec<Twist>, Vec<TwistParseError<'_>>) {
+        Self::parse_twists(&self.twists, puzzle_type)
+    }
+
+    pub fn premoves(&self, puzzle_type: &dyn PuzzleType) -> (Vec<Twist>, Vec<TwistParseError<'_>>) {
+        Self::parse_twists(&self.premoves, puzzle_type)
+    }
+
+    pub fn parse_twists<'a>(
+        s: &'a str,
+        puzzle_type: &dyn PuzzleType,
+    ) -> (Vec<Twist>, Vec<TwistParseError<'a>>) {
+        let mut ret_twists = vec![];
+        let mut ret_errors = vec![];
+        for twist_str in s.split_whitespace() {
+            match puzzle_type.notation_scheme().parse_twist(twist_str) {
+                Ok(twist) => ret_twists.push(twist),
+                Err(error_msg) => ret_errors.push(TwistParseError {
+                    twist_str,
+                    error_msg,
+                }),
+            }
+        }
+        (ret_twists, ret_errors)
+    }
+}
+
+/// Branch of the undo history that leaves from the twists that were saved or
+/// from an earlier branch.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct LogBranch {
+    /// Branch that this one leaves from: 0 for `twists` or `i + 1` for the
+    /// `i`th branch.
+    pub from_branch: usize,
+    /// Number of twists since the scramble where this branch leaves.
+    pub from_twist: usize,
+    pub twists: String,
+    /// Comments on states along the branch, keyed by the number of twists
+    /// since the scramble.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<usize, String>,
+}
+
+#[derive(Debug)]
+pub struct TwistParseError<'a> {
+    pub twist_str: &'a str,
+    pub error_msg: String,
+}
+impl fmt::Display for TwistParseError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Error parsing twist {:?}: {}",
+            self.twist_str, self.error_msg,
+        )
+    }
+}
+impl Error for TwistParseError<'_> {}
@@ -0,0 +1,670 @@
+//! Rubik's Clock.
+//!
+//! The clock has nine dials on each side, arranged in a 3x3 grid, and four
+//! pins between them. Each pin that is pushed up on one side is down on the
+//! other. Turning a wheel turns every dial next to a pin that is up on the side
+//! being turned. The corner dials on each side share a gear with the corner
+//! dial behind them, so they always turn together.
+//!
+//! Each twist axis is a configuration of pins, named as in WCA scrambles, and
+//! the layer mask selects which side's wheel is turned: layer 1 is the side
+//! facing the front and layer 2 is the side facing the back. `y2` flips the
+//! whole puzzle over.
+
+use cgmath::*;
+use itertools::Itertools;
+use rand::{Rng, RngCore};
+use smallvec::smallvec;
+
+use super::*;
+
+const DIALS_PER_SIDE: usize = 9;
+const DIAL_COUNT: usize = 2 * DIALS_PER_SIDE;
+const PIN_COUNT: usize = 4;
+/// Number of hours on each dial.
+const HOURS: i32 = 12;
+
+const FACES: [(&str, &str); 5] = [
+    ("F", "Front"),
+    ("B", "Back"),
+    ("FH", "Front hands"),
+    ("BH", "Back hands"),
+    ("P", "Pins"),
+];
+const PIECE_TYPE_NAMES: [&str; 4] = ["Corner", "Edge", "Center", "Pin"];
+
+/// Name of each twist axis and the pins that it pushes up, as a bitmask of
+/// the UR, DR, DL, and UL pins. The last axis flips the puzzle instead.
+const PIN_AXES: [(&str, u8); 9] = [
+    ("ALL", 0b1111),
+    ("UR", 0b0001),
+    ("DR", 0b0010),
+    ("DL", 0b0100),
+    ("UL", 0b1000),
+    ("U", 0b1001),
+    ("R", 0b0011),
+    ("D", 0b0110),
+    ("L", 0b1100),
+];
+const FLIP_AXIS: TwistAxis = TwistAxis(PIN_AXES.len() as u8);
+/// Index into [`PIN_AXES`] for the twist when clicking the dial at each
+/// position.
+const DIAL_CLICK_AXES: [u8; DIALS_PER_SIDE] = [4, 5, 1, 8, 0, 6, 3, 7, 2];
+/// Dial positions next to the UR, DR, DL, and UL pins. Dial positions are in
+/// reading order, as seen from the side that the dial is on.
+const PIN_DIALS: [[usize; 4]; PIN_COUNT] = [[1, 2, 4, 5], [4, 5, 7, 8], [3, 4, 6, 7], [0, 1, 3, 4]];
+/// Offset of the UR, DR, DL, and UL pins from the center dial, in units of
+/// the space between dials.
+const PIN_OFFSETS: [(f32, f32); PIN_COUNT] = [(0.5, 0.5), (0.5, -0.5), (-0.5, -0.5), (-0.5, 0.5)];
+
+const FRONT: LayerMask = LayerMask(1);
+const BACK: LayerMask = LayerMask(2);
+
+/// Distance from the center of the view to the center of each side.
+const SIDE_OFFSET: f32 = 0.5;
+/// Distance between the centers of adjacent dials.
+const DIAL_SPACING: f32 = 0.3;
+const DIAL_RADIUS: f32 = 0.11;
+const DIAL_POLYGON_SIDES: usize = 24;
+const HAND_LENGTH: f32 = 0.1;
+const HAND_WIDTH: f32 = 0.025;
+const PIN_RADIUS: f32 = 0.035;
+const PIN_POLYGON_SIDES: usize = 8;
+/// Depth of hands, which are drawn in front of their dials.
+const HAND_Z: f32 = 0.01;
+
+pub(super) fn puzzle_type() -> &'static dyn PuzzleType {
+    &*DESCRIPTION
+}
+
+lazy_static! {
+    static ref DESCRIPTION: ClockDescription = ClockDescription::new();
+}
+
+/// Returns the position of the dial behind the dial at `pos`, as seen from its
+/// own side.
+fn mirror(pos: usize) -> usize {
+    pos + 2 - 2 * (pos % 3)
+}
+/// Returns the index of a pin as seen from the other side.
+fn mirror_pin(pin: usize) -> usize {
+    PIN_COUNT - 1 - pin
+}
+fn is_corner(pos: usize) -> bool {
+    pos != 4 && pos % 2 == 0
+}
+
+/// Returns the signed number of hours that a twist direction turns clockwise.
+fn direction_hours(direction: TwistDirection) -> Option<i32> {
+    let d = direction.0 as i32;
+    if d >= HOURS {
+        return None;
+    }
+    let hours = d / 2 + 1;
+    Some(if d % 2 == 0 { hours } else { -hours })
+}
+/// Returns the twist direction that turns a signed number of hours clockwise,
+/// which must be between -6 and 6 and not zero.
+fn direction_from_hours(hours: i32) -> TwistDirection {
+    TwistDirection(((hours.abs() - 1) * 2 + (hours < 0) as i32) as u8)
+}
+
+#[derive(Debug, Clone)]
+struct ClockDescription {
+    faces: Vec<FaceInfo>,
+    pieces: Vec<PieceInfo>,
+    stickers: Vec<StickerInfo>,
+    twist_axes: Vec<TwistAxisInfo>,
+    twist_directions: Vec<TwistDirectionInfo>,
+    piece_types: Vec<PieceTypeInfo>,
+    notation: NotationScheme,
+}
+impl ClockDescription {
+    fn new() -> Self {
+        let mut pieces = vec![];
+        let mut stickers = vec![];
+
+        // Each dial has a sticker for its face and one for its hand.
+        for side in 0..2 {
+            for pos in 0..DIALS_PER_SIDE {
+                let piece = Piece(pieces.len() as _);
+                let piece_type = match pos {
+                    4 => PieceType(2),
+                    _ if is_corner(pos) => PieceType(0),
+                    _ => PieceType(1),
+                };
+                pieces.push(PieceInfo {
+                    stickers: smallvec![
+                        Sticker(stickers.len() as _),
+                        Sticker(stickers.len() as u16 + 1),
+                    ],
+                    piece_type,
+                });
+                stickers.push(StickerInfo {
+                    piece,
+                    color: Face(side),
+                });
+                stickers.push(StickerInfo {
+                    piece,
+                    color: Face(2 + side),
+                });
+            }
+        }
+        // Each pin has a sticker on each side, which is only shown when the
+        // pin is up on that side.
+        for _ in 0..PIN_COUNT {
+            let piece = Piece(pieces.len() as _);
+            pieces.push(PieceInfo {
+                stickers: smallvec![
+                    Sticker(stickers.len() as _),
+                    Sticker(stickers.len() as u16 + 1),
+                ],
+                piece_type: PieceType(3),
+            });
+            for _ in 0..2 {
+                stickers.push(StickerInfo {
+                    piece,
+                    color: Face(4),
+                });
+            }
+        }
+
+        let twist_directions = (1..=HOURS / 2)
+            .flat_map(|hours| [hours, -hours])
+            .map(|hours| {
+                let symbol = match hours.signum() {
+                    1 => format!("{hours}+"),
+                    _ => format!("{}-", -hours),
+                };
+                let symbol: &'static str = Box::leak(symbol.into_boxed_str());
+                TwistDirectionInfo::new(symbol, symbol)
+            })
+            .collect_vec();
+
+        let notation = NotationScheme {
+            axis_names: PIN_AXES
+                .iter()
+                .map(|&(name, _)| name.to_string())
+                .chain(["y".to_string()])
+                .collect(),
+            direction_names: twist_directions
+                .iter()
+                .map(|dir| TwistDirectionName::Same(dir.symbol.to_string()))
+                .collect(),
+            block_suffix: None,
+            aliases: vec![(
+                "y2".to_string(),
+                Alias::EntireTwist(Twist {
+                    axis: FLIP_AXIS,
+                    direction: direction_from_hours(HOURS / 2),
+                    layers: FRONT | BACK,
+                }),
+            )],
+        };
+
+        Self {
+            faces: FACES
+                .iter()
+                .map(|&(symbol, name)| FaceInfo::new(symbol, name))
+                .collect(),
+            pieces,
+            stickers,
+            twist_axes: PIN_AXES
+                .iter()
+                .map(|&(name, _)| TwistAxisInfo { name })
+                .chain([TwistAxisInfo { name: "y" }])
+                .collect(),
+            twist_directions,
+            piece_types: PIECE_TYPE_NAMES
+                .iter()
+                .map(|&name| PieceTypeInfo::new(name.to_string()))
+                .collect(),
+            notation,
+        }
+    }
+}
+impl PuzzleType for ClockDescription {
+    fn ty(&self) -> PuzzleTypeEnum {
+        PuzzleTypeEnum::Clock
+    }
+    fn name(&self) -> &str {
+        "Clock"
+    }
+    fn family_display_name(&self) -> &'static str {
+        "Clock"
+    }
+    fn family_internal_name(&self) -> &'static str {
+        "Clock"
+    }
+    fn projection_type(&self) -> ProjectionType {
+        ProjectionType::_3D
+    }
+
+    fn layer_count(&self) -> u8 {
+        2
+    }
+    fn family_max_layer_count(&self) -> u8 {
+        2
+    }
+    fn projection_radius_3d(&self, _p: StickerGeometryParams) -> f32 {
+        1.0
+    }
+    fn scramble_moves_count(&self) -> usize {
+        14
+    }
+    fn random_scramble(&self, rng: &mut dyn RngCore) -> Option<Vec<Twist>> {
+        // Follow the WCA scramble format, which turns each pin configuration
+        // on the front, flips the puzzle, and then turns some of them again.
+        // Twists of zero hours are left out.
+        let mut random_twist = |axis: u8| {
+            let hours = rng.gen_range(-HOURS / 2 + 1..=HOURS / 2);
+            (hours != 0).then(|| Twist {
+                axis: TwistAxis(axis),
+                direction: direction_from_hours(hours),
+                layers: FRONT,
+            })
+        };
+        let mut ret = [1, 2, 3, 4, 5, 6, 7, 8, 0]
+            .into_iter()
+            .filter_map(&mut random_twist)
+            .collect_vec();
+        ret.push(Twist {
+            axis: FLIP_AXIS,
+            direction: direction_from_hours(HOURS / 2),
+            layers: FRONT | BACK,
+        });
+        ret.extend([5, 6, 7, 8, 0].into_iter().filter_map(random_twist));
+        Some(ret)
+    }
+
+    fn faces(&self) -> &[FaceInfo] {
+        &self.faces
+    }
+    fn pieces(&self) -> &[PieceInfo] {
+        &self.pieces
+    }
+    fn stickers(&self) -> &[StickerInfo] {
+        &self.stickers
+    }
+    fn twist_axes(&self) -> &[TwistAxisInfo] {
+        &self.twist_axes
+    }
+    fn twist_directions(&self) -> &[TwistDirectionInfo] {
+        &self.twist_directions
+    }
+    fn piece_types(&self) -> &[PieceTypeInfo] {
+        &self.piece_types
+    }
+
+    fn opposite_twist_axis(&self, _twist_axis: TwistAxis) -> Option<TwistAxis> {
+        None
+    }
+    fn count_quarter_turns(&self, twist: Twist) -> usize {
+        direction_hours(twist.direction).map_or(0, |hours| hours.unsigned_abs() as usize)
+    }
+
+    fn make_recenter_twist(&self, _axis: TwistAxis) -> Result<Twist, String> {
+        Err("recentering is not supported for this puzzle".to_string())
+    }
+
+    fn canonicalize_twist(&self, mut twist: Twist) -> Twist {
+        // The puzzle can only be flipped over one way.
+        if twist.axis == FLIP_AXIS {
+            return Twist {
+                axis: FLIP_AXIS,
+                direction: direction_from_hours(HOURS / 2),
+                layers: self.all_layers(),
+            };
+        }
+        // Six hours in either direction is the same.
+        if direction_hours(twist.direction) == Some(-HOURS / 2) {
+            twist.direction = direction_from_hours(HOURS / 2);
+        }
+        twist
+    }
+
+    fn reverse_twist_direction(&self, direction: TwistDirection) -> TwistDirection {
+        TwistDirection(direction.0 ^ 1)
+    }
+    fn chain_twist_directions(
+        &self,
+        _axis: TwistAxis,
+        dirs: &[TwistDirection],
+    ) -> Option<TwistDirection> {
+        let total = dirs
+            .iter()
+            .map(|&dir| direction_hours(dir))
+            .sum::<Option<i32>>()?;
+        let mut hours = total.rem_euclid(HOURS);
+        if hours > HOURS / 2 {
+            hours -= HOURS;
+        }
+        (hours != 0).then(|| direction_from_hours(hours))
+    }
+
+    fn notation_scheme(&self) -> &NotationScheme {
+        &self.notation
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Clock {
+    desc: &'static ClockDescription,
+    /// Hour shown on each dial as seen from its own side, where 0 is 12
+    /// o'clock. Dials `0..9` are on the original front and dials `9..18` are
+    /// on the original back, each in reading order.
+    hours: [u8; DIAL_COUNT],
+    /// Whether each pin (UR, DR, DL, and UL) is up on the original front.
+    pins: [bool; PIN_COUNT],
+    /// Whether the puzzle has been flipped so that the original back faces
+    /// the front.
+    flipped: bool,
+}
+impl Eq for Clock {}
+impl PartialEq for Clock {
+    fn eq(&self, other: &Self) -> bool {
+        self.hours == other.hours && self.pins == other.pins && self.flipped == other.flipped
+    }
+}
+impl PuzzleState for Clock {
+    fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
+        let hours = direction_hours(twist.direction).ok_or("invalid twist direction")?;
+        if twist.axis.0 as usize > PIN_AXES.len() {
+            return Err("invalid twist axis");
+        }
+        if !self.is_twist_legal(twist) {
+            return Err(BLOCKED_TWIST_ERROR);
+        }
+
+        if twist.axis == FLIP_AXIS {
+            self.flipped ^= true;
+            return Ok(());
+        }
+
+        let (_, pins) = PIN_AXES[twist.axis.0 as usize];
+        let side = self.side_turned_by(twist);
+        for pin in 0..PIN_COUNT {
+            let is_up = pins & (1 << pin) != 0;
+            match side {
+                0 => self.pins[pin] = is_up,
+                _ => self.pins[mirror_pin(pin)] = !is_up,
+            }
+        }
+        for (dial, sign) in self.turned_dials(pins, side) {
+            let new_hours = self.hours[dial] as i32 + hours * sign;
+            self.hours[dial] = new_hours.rem_euclid(HOURS) as u8;
+        }
+        Ok(())
+    }
+    fn is_piece_affected_by_twist(&self, twist: Twist, piece: Piece) -> bool {
+        if twist.axis == FLIP_AXIS {
+            return true;
+        }
+        let Some(&(_, pins)) = PIN_AXES.get(twist.axis.0 as usize) else {
+            return false;
+        };
+        self.turned_dials(pins, self.side_turned_by(twist))
+            .iter()
+            .any(|&(dial, _)| dial == piece.0 as usize)
+    }
+    /// Returns 0 for dials turned by the front wheel, 1 for dials turned by
+    /// the back wheel, and 2 for anything else. Corner dials turned by both
+    /// wheels count as turned by the front wheel.
+    fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8 {
+        [FRONT, BACK]
+            .into_iter()
+            .position(|layers| {
+                let twist = Twist {
+                    axis: twist_axis,
+                    direction: TwistDirection(0),
+                    layers,
+                };
+                self.is_piece_affected_by_twist(twist, piece)
+            })
+            .unwrap_or(2) as u8
+    }
+    fn is_twist_legal(&self, twist: Twist) -> bool {
+        if twist.axis == FLIP_AXIS {
+            twist.layers == self.all_layers()
+                && direction_hours(twist.direction).map(i32::abs) == Some(HOURS / 2)
+        } else {
+            twist.layers == FRONT || twist.layers == BACK
+        }
+    }
+
+    fn rotation_candidates(&self) -> Vec<(Vec<Twist>, Quaternion<f32>)> {
+        vec![] // The puzzle is always viewed flat.
+    }
+
+    fn sticker_geometry(
+        &self,
+        sticker: Sticker,
+        p: StickerGeometryParams,
+    ) -> Option<StickerGeometry> {
+        let piece = self.info(sticker).piece;
+        let is_second_sticker = self.info(piece).stickers[1] == sticker;
+        let piece = piece.0 as usize;
+
+        if piece >= DIAL_COUNT {
+            // Only show the pin on the side where it is up.
+            let pin = piece - DIAL_COUNT;
+            let side = is_second_sticker as usize;
+            let (pin, is_up) = match side {
+                0 => (pin, self.pins[pin]),
+                _ => (mirror_pin(pin), !self.pins[pin]),
+            };
+            if !is_up {
+                return None;
+            }
+            let (x, y) = PIN_OFFSETS[pin];
+            let center = self.side_center(side) + vec3(x, y, 0.0) * DIAL_SPACING;
+            return Some(StickerGeometry::new_double_polygon(
+                regular_polygon(center, PIN_RADIUS, PIN_POLYGON_SIDES),
+                ClickTwists::default(),
+                true,
+                false,
+            ));
+        }
+
+        let side = piece / DIALS_PER_SIDE;
+        let pos = piece % DIALS_PER_SIDE;
+        let (col, row) = ((pos % 3) as f32, (pos / 3) as f32);
+        let center = self.side_center(side) + vec3(col - 1.0, 1.0 - row, 0.0) * DIAL_SPACING;
+
+        // Clicking a dial turns the wheel on its side with the pins closest
+        // to it pushed up.
+        let cw_twist = Twist {
+            axis: TwistAxis(DIAL_CLICK_AXES[pos]),
+            direction: direction_from_hours(1),
+            layers: if (side == 1) == self.flipped {
+                FRONT
+            } else {
+                BACK
+            },
+        };
+        let twists = ClickTwists {
+            cw: Some(cw_twist),
+            ccw: Some(self.reverse_twist(cw_twist)),
+            recenter: None,
+        };
+
+        if !is_second_sticker {
+            return Some(StickerGeometry::new_double_polygon(
+                regular_polygon(center, DIAL_RADIUS, DIAL_POLYGON_SIDES),
+                twists,
+                true,
+                false,
+            ));
+        }
+
+        let mut hours = self.hours[piece] as f32;
+        if let Some((twist, progress)) = p.twist_animation {
+            if let Some(&(_, pins)) = PIN_AXES.get(twist.axis.0 as usize) {
+                let twist_hours = direction_hours(twist.direction).unwrap_or(0) as f32;
+                for (dial, sign) in self.turned_dials(pins, self.side_turned_by(twist)) {
+                    if dial == piece {
+                        hours += twist_hours * sign as f32 * progress;
+                    }
+                }
+            }
+        }
+
+        // Hours go clockwise from the top.
+        let angle = Rad::turn_div_4() - Rad::full_turn() * hours / HOURS as f32;
+        let (sin, cos) = angle.sin_cos();
+        let dir = vec3(cos, sin, 0.0);
+        let perp = vec3(-sin, cos, 0.0);
+        let center = center + Vector3::unit_z() * HAND_Z;
+        Some(StickerGeometry::new_double_polygon(
+            vec![
+                center + dir * HAND_LENGTH,
+                center + perp * HAND_WIDTH,
+                center - dir * HAND_WIDTH,
+                center - perp * HAND_WIDTH,
+            ],
+            twists,
+            true,
+            false,
+        ))
+    }
+
+    fn is_solved(&self) -> bool {
+        self.hours.iter().all(|&h| h == 0)
+    }
+
+    fn is_solved_in_original_orientation(&self) -> bool {
+        self.is_solved() && !self.flipped
+    }
+}
+#[delegate_to_methods]
+#[delegate(PuzzleType, target_ref = "desc")]
+impl Clock {
+    pub fn new() -> Self {
+        Self {
+            desc: &*DESCRIPTION,
+            hours: [0; DIAL_COUNT],
+            pins: [false; PIN_COUNT],
+            flipped: false,
+        }
+    }
+
+    fn desc(&self) -> &ClockDescription {
+        self.desc
+    }
+
+    /// Returns the original side (0 for front, 1 for back) whose wheel is
+    /// turned by a twist.
+    fn side_turned_by(&self, twist: Twist) -> usize {
+        (self.flipped != (twist.layers == BACK)) as usize
+    }
+    /// Returns each dial turned by turning the wheel on an original side with
+    /// some pins pushed up on that side, along with whether it turns in the
+    /// same direction (1) or the opposite direction (-1) as seen from its own
+    /// side.
+    fn turned_dials(&self, pins: u8, side: usize) -> Vec<(usize, i32)> {
+        let mut is_turned = [false; DIALS_PER_SIDE];
+        for (pin, dials) in PIN_DIALS.iter().enumerate() {
+            if pins & (1 << pin) != 0 {
+                for &pos in dials {
+                    is_turned[pos] = true;
+                }
+            }
+        }
+
+        let mut ret = vec![];
+        for pos in (0..DIALS_PER_SIDE).filter(|&pos| is_turned[pos]) {
+            ret.push((side * DIALS_PER_SIDE + pos, 1));
+            if is_corner(pos) {
+                // The dial behind a corner dial shares its gear, so it turns
+                // the opposite way as seen from its own side.
+                ret.push(((1 - side) * DIALS_PER_SIDE + mirror(pos), -1));
+            }
+        }
+        ret
+    }
+    /// Returns the center of an original side in the view. The side facing
+    /// the front is drawn on the left, and the side facing the back is drawn
+    /// on the right as seen from behind.
+    fn side_center(&self, side: usize) -> Point3<f32> {
+        if (side == 1) == self.flipped {
+            point3(-SIDE_OFFSET, 0.0, 0.0)
+        } else {
+            point3(SIDE_OFFSET, 0.0, 0.0)
+        }
+    }
+}
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the vertices of a regular polygon facing the camera,
+/// counterclockwise.
+fn regular_polygon(center: Point3<f32>, radius: f32, sides: usize) -> Vec<Point3<f32>> {
+    (0..sides)
+        .map(|i| {
+            let (sin, cos) = (Rad::full_turn() * i as f32 / sides as f32).sin_cos();
+            center + vec3(cos, sin, 0.0) * radius
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_twist_serialization() {
+        let p = Clock::new();
+        crate::puzzle::tests::test_twist_serialization(&p);
+    }
+
+    #[test]
+    fn test_clock_twist_period() {
+        let mut p = Clock::new();
+        let twist = p.notation_scheme().parse_twist("UR1+").unwrap();
+        for i in 1..=HOURS {
+            p.twist(twist).unwrap();
+            assert_eq!(p.is_solved(), i == HOURS);
+        }
+        assert!(p.pins[0] && !p.pins[1] && !p.pins[2] && !p.pins[3]);
+    }
+
+    #[test]
+    fn test_clock_wca_scramble() {
+        let mut p = Clock::new();
+        let notation = puzzle_type().notation_scheme();
+        let scramble = "UR3+ DR2- UL1- U5+ R4- D2+ L3- ALL2+ y2 U4- R6+ D1- L3+ ALL3-";
+        let twists = scramble
+            .split_whitespace()
+            .map(|s| notation.parse_twist(s).unwrap())
+            .collect_vec();
+        assert_eq!(notation.twist_to_string(twists[8]), "y2");
+
+        for &twist in &twists {
+            p.twist(twist).unwrap();
+        }
+        assert!(!p.is_solved());
+        for &twist in twists.iter().rev() {
+            p.twist(p.reverse_twist(twist)).unwrap();
+        }
+        assert!(p.is_solved_in_original_orientation());
+
+        // Back corners turn the opposite way as the front corners.
+        p.twist(notation.parse_twist("UR1+").unwrap()).unwrap();
+        assert_eq!(p.hours[2], 1);
+        assert_eq!(p.hours[DIALS_PER_SIDE], HOURS as u8 - 1);
+
+        // Flipping doesn't unsolve the puzzle.
+        let mut p = Clock::new();
+        p.twist(notation.parse_twist("y2").unwrap()).unwrap();
+        assert!(p.is_solved());
+        assert!(!p.is_solved_in_original_orientation());
+        assert!(p
+            .random_scramble(&mut rand::thread_rng())
+            .unwrap()
+            .iter()
+            .all(|&t| p.is_twist_legal(t)));
+    }
+}
@@ -1,6 +1,7 @@
 use cgmath::{One, Quaternion, Rotation};
 use enum_iterator::Sequence;
 use itertools::Itertools;
+use num_enum::FromPrimitive;
 use rand::Rng;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -12,6 +13,9 @@ use strum::{Display, EnumIter, EnumMessage};
 
 use super::*;
 
+/// Error returned when a twist cannot be applied in the current state.
+pub const BLOCKED_TWIST_ERROR: &str = "Twist is blocked";
+
 #[delegatable_trait]
 #[enum_dispatch]
 pub trait PuzzleType {
@@ -27,6 +31,11 @@ pub trait PuzzleType {
     /// Returns the maximum radius of the puzzle's 3D projection.
     fn projection_radius_3d(&self, p: StickerGeometryParams) -> f32;
     fn scramble_moves_count(&self) -> usize;
+    /// Returns a scramble in the puzzle's official format, or `None` if
+    /// scrambles should be made of random twists.
+    fn random_scramble(&self, _rng: &mut dyn rand::RngCore) -> Option<Vec<Twist>> {
+        None
+    }
 
     fn faces(&self) -> &[FaceInfo];
     fn pieces(&self) -> &[PieceInfo];
@@ -78,7 +87,11 @@ pub trait PuzzleType {
     fn canonicalize_twist(&self, twist: Twist) -> Twist;
 
     fn reverse_twist_direction(&self, direction: TwistDirection) -> TwistDirection;
-    fn chain_twist_directions(&self, dirs: &[TwistDirection]) -> Option<TwistDirection>;
+    fn chain_twist_directions(
+        &self,
+        axis: TwistAxis,
+        dirs: &[TwistDirection],
+    ) -> Option<TwistDirection>;
 
     fn notation_scheme(&self) -> &NotationScheme;
     fn split_twists_string<'s>(&self, string: &'s str) -> regex::Matches<'static, 's> {
@@ -133,7 +146,6 @@ impl<'a, P: PuzzleType> PuzzleTypeRefExt for &'a P {
     }
 }
 
-#[enum_dispatch]
 pub trait PuzzleState: PuzzleType {
     fn twist(&mut self, twist: Twist) -> Result<(), &'static str>;
     fn is_piece_affected_by_twist(&self, twist: Twist, piece: Piece) -> bool {
@@ -145,6 +157,11 @@ pub trait PuzzleState: PuzzleType {
             .filter(|&piece| self.is_piece_affected_by_twist(twist, piece))
             .collect()
     }
+    /// Returns whether a twist can be applied in the current state. Twists are
+    /// only blocked on puzzles with pieces fused together.
+    fn is_twist_legal(&self, _twist: Twist) -> bool {
+        true
+    }
     fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8;
 
     fn rotation_candidates(&self) -> Vec<(Vec<Twist>, Quaternion<f32>)>;
@@ -176,13 +193,86 @@ pub trait PuzzleState: PuzzleType {
         sticker: Sticker,
         p: StickerGeometryParams,
     ) -> Option<StickerGeometry>;
+    /// Returns the facet (the cell, on a 4D puzzle) that a sticker currently
+    /// lies on, or `None` if the puzzle doesn't track this.
+    fn sticker_facet(&self, _sticker: Sticker) -> Option<Face> {
+        None
+    }
+    /// Returns the layer that a piece currently occupies along each axis, or
+    /// `None` if the puzzle has no such coordinates.
+    fn piece_coordinates(&self, _piece: Piece) -> Option<Vec<u8>> {
+        None
+    }
+    /// Returns whether every sticker of a piece is on the face of its color.
+    /// This is always `false` on puzzles that don't implement
+    /// [`Self::sticker_facet()`].
+    fn is_piece_solved(&self, piece: Piece) -> bool {
+        self.info(piece)
+            .stickers
+            .iter()
+            .all(|&s| self.sticker_facet(s) == Some(self.info(s).color))
+    }
 
+    /// Returns whether each face of the puzzle is a single color, regardless
+    /// of the orientation of the whole puzzle.
     fn is_solved(&self) -> bool;
+    /// Returns whether each face of the puzzle is its original color.
+    fn is_solved_in_original_orientation(&self) -> bool;
+    /// Returns whether the puzzle is solved according to `definition`.
+    fn is_solved_by(&self, definition: SolvedStateDefinition) -> bool {
+        match definition {
+            SolvedStateDefinition::AnyOrientation => self.is_solved(),
+            SolvedStateDefinition::OriginalOrientation => self.is_solved_in_original_orientation(),
+        }
+    }
+    /// Returns whether the puzzle is solved according to `definition`, also
+    /// requiring every center piece to be turned the right way. Puzzles that
+    /// don't track the orientation of center pieces fall back to
+    /// [`Self::is_solved_by()`].
+    fn is_solved_as_supercube(&self, definition: SolvedStateDefinition) -> bool {
+        self.is_solved_by(definition)
+    }
 
     #[cfg(debug_assertions)]
     fn sticker_debug_info(&self, _s: &mut String, _sticker: Sticker) {}
 }
 
+/// Panics if any of `pieces`, which were just moved by `twist`, has two
+/// stickers on the same facet. This catches most bugs in twist
+/// implementations, so [`Puzzle`] calls it after every twist in debug builds.
+///
+/// Pieces are skipped if the puzzle doesn't implement
+/// [`PuzzleState::sticker_facet()`] or if the piece has two stickers of the
+/// same color.
+#[cfg(debug_assertions)]
+pub(crate) fn debug_check_twisted_pieces(
+    puzzle: &(impl PuzzleState + ?Sized),
+    twist: Twist,
+    pieces: &[Piece],
+) {
+    for &piece in pieces {
+        let stickers = &puzzle.info(piece).stickers;
+        if !stickers.iter().map(|&s| puzzle.info(s).color).all_unique() {
+            continue;
+        }
+        let facets: SmallVec<[Option<Face>; 8]> = stickers
+            .iter()
+            .map(|&sticker| puzzle.sticker_facet(sticker))
+            .collect();
+        if facets.contains(&None) {
+            continue;
+        }
+        assert!(
+            facets.iter().all_unique(),
+            "{:?} on {} put two stickers of {:?} on the same facet: {:?}",
+            twist,
+            puzzle.name(),
+            piece,
+            facets,
+        );
+    }
+}
+
 /// Enumeration of all puzzle types.
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum PuzzleTypeEnum {
@@ -196,6 +286,17 @@ pub enum PuzzleTypeEnum {
         #[serde(deserialize_with = "rubiks_4d::deserialize_layer_count")]
         layer_count: u8,
     },
+    /// 5D Rubik's cube.
+    Rubiks5D {
+        #[serde(deserialize_with = "rubiks_5d::deserialize_layer_count")]
+        layer_count: u8,
+    },
+    /// Face-turning 4D simplex (5-cell).
+    Simplex4D,
+    /// Rubik's Clock.
+    Clock,
+    /// Puzzle loaded from a definition file.
+    Generic { id: GenericPuzzleId },
 }
 #[delegate_to_methods]
 #[delegate(PuzzleType, target_ref = "as_dyn_type")]
@@ -204,6 +305,10 @@ impl PuzzleTypeEnum {
         match *self {
             PuzzleTypeEnum::Rubiks3D { layer_count } => rubiks_3d::puzzle_type(layer_count),
             PuzzleTypeEnum::Rubiks4D { layer_count } => rubiks_4d::puzzle_type(layer_count),
+            PuzzleTypeEnum::Rubiks5D { layer_count } => rubiks_5d::puzzle_type(layer_count),
+            PuzzleTypeEnum::Simplex4D => simplex_4d::puzzle_type(),
+            PuzzleTypeEnum::Clock => clock::puzzle_type(),
+            PuzzleTypeEnum::Generic { id } => generic::puzzle_type(id),
         }
     }
     pub fn validate(self) -> Result<(), String> {
@@ -222,6 +327,16 @@ impl PuzzleTypeEnum {
                     Err(format!("invalid layer count {layer_count} for this puzzle"))
                 }
             }
+            PuzzleTypeEnum::Rubiks5D { layer_count } => {
+                if rubiks_5d::LAYER_COUNT_RANGE.contains(&layer_count) {
+                    Ok(())
+                } else {
+                    Err(format!("invalid layer count {layer_count} for this puzzle"))
+                }
+            }
+            PuzzleTypeEnum::Simplex4D => Ok(()),
+            PuzzleTypeEnum::Clock => Ok(()),
+            PuzzleTypeEnum::Generic { .. } => Ok(()),
         }
     }
 
@@ -229,6 +344,10 @@ impl PuzzleTypeEnum {
         match *self {
             PuzzleTypeEnum::Rubiks3D { .. } => false,
             PuzzleTypeEnum::Rubiks4D { .. } => true,
+            PuzzleTypeEnum::Rubiks5D { .. } => false,
+            PuzzleTypeEnum::Simplex4D => false,
+            PuzzleTypeEnum::Clock => false,
+            PuzzleTypeEnum::Generic { .. } => false,
         }
     }
 }
@@ -282,8 +401,7 @@ impl FromStr for Twist {
     }
 }
 impl Twist {
-    pub fn from_rng(ty: PuzzleTypeEnum) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn from_rng(ty: PuzzleTypeEnum, rng: &mut impl Rng) -> Self {
         Self {
             axis: TwistAxis(rng.gen_range(0..ty.twist_axes().len()) as _),
             direction: TwistDirection(rng.gen_range(0..ty.twist_directions().len()) as _),
@@ -297,13 +415,21 @@ impl Twist {
 }
 
 /// Puzzle of any type.
-#[enum_dispatch(PuzzleType, PuzzleState)]
+#[enum_dispatch(PuzzleType)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Puzzle {
     /// 3D Rubik's cube.
     Rubiks3D(Rubiks3D),
     /// 4D Rubik's cube.
     Rubiks4D(Rubiks4D),
+    /// 5D Rubik's cube.
+    Rubiks5D(Rubiks5D),
+    /// Face-turning 4D simplex (5-cell).
+    Simplex4D(Simplex4D),
+    /// Rubik's Clock.
+    Clock(Clock),
+    /// Puzzle loaded from a definition file.
+    Generic(GenericPuzzle),
 }
 impl Default for Puzzle {
     fn default() -> Self {
@@ -320,10 +446,92 @@ impl Puzzle {
             PuzzleTypeEnum::Rubiks4D { layer_count } => {
                 Puzzle::Rubiks4D(Rubiks4D::new(layer_count))
             }
+            PuzzleTypeEnum::Rubiks5D { layer_count } => {
+                Puzzle::Rubiks5D(Rubiks5D::new(layer_count))
+            }
+            PuzzleTypeEnum::Simplex4D => Puzzle::Simplex4D(Simplex4D::new()),
+            PuzzleTypeEnum::Clock => Puzzle::Clock(Clock::new()),
+            PuzzleTypeEnum::Generic { id } => Puzzle::Generic(GenericPuzzle::new(id)),
         }
     }
 }
 
+/// Calls a method on the puzzle inside a [`Puzzle`].
+macro_rules! dispatch_puzzle {
+    ($self:ident, $puzzle:ident => $body:expr) => {
+        match $self {
+            Puzzle::Rubiks3D($puzzle) => $body,
+            Puzzle::Rubiks4D($puzzle) => $body,
+            Puzzle::Rubiks5D($puzzle) => $body,
+            Puzzle::Simplex4D($puzzle) => $body,
+            Puzzle::Clock($puzzle) => $body,
+            Puzzle::Generic($puzzle) => $body,
+        }
+    };
+}
+impl PuzzleState for Puzzle {
+    fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
+        dispatch_puzzle!(self, p => p.twist(twist))?;
+        #[cfg(debug_assertions)]
+        debug_check_twisted_pieces(self, twist, &self.pieces_affected_by_twist(twist));
+        Ok(())
+    }
+    fn is_piece_affected_by_twist(&self, twist: Twist, piece: Piece) -> bool {
+        dispatch_puzzle!(self, p => p.is_piece_affected_by_twist(twist, piece))
+    }
+    fn pieces_affected_by_twist(&self, twist: Twist) -> Vec<Piece> {
+        dispatch_puzzle!(self, p => p.pieces_affected_by_twist(twist))
+    }
+    fn is_twist_legal(&self, twist: Twist) -> bool {
+        dispatch_puzzle!(self, p => p.is_twist_legal(twist))
+    }
+    fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8 {
+        dispatch_puzzle!(self, p => p.layer_from_twist_axis(twist_axis, piece))
+    }
+
+    fn rotation_candidates(&self) -> Vec<(Vec<Twist>, Quaternion<f32>)> {
+        dispatch_puzzle!(self, p => p.rotation_candidates())
+    }
+    fn nearest_rotation(&self, rot: Quaternion<f32>) -> (Vec<Twist>, Quaternion<f32>) {
+        dispatch_puzzle!(self, p => p.nearest_rotation(rot))
+    }
+
+    fn sticker_geometry(
+        &self,
+        sticker: Sticker,
+        p: StickerGeometryParams,
+    ) -> Option<StickerGeometry> {
+        dispatch_puzzle!(self, puzzle => puzzle.sticker_geometry(sticker, p))
+    }
+    fn sticker_facet(&self, sticker: Sticker) -> Option<Face> {
+        dispatch_puzzle!(self, p => p.sticker_facet(sticker))
+    }
+    fn piece_coordinates(&self, piece: Piece) -> Option<Vec<u8>> {
+        dispatch_puzzle!(self, p => p.piece_coordinates(piece))
+    }
+    fn is_piece_solved(&self, piece: Piece) -> bool {
+        dispatch_puzzle!(self, p => p.is_piece_solved(piece))
+    }
+
+    fn is_solved(&self) -> bool {
+        dispatch_puzzle!(self, p => p.is_solved())
+    }
+    fn is_solved_in_original_orientation(&self) -> bool {
+        dispatch_puzzle!(self, p => p.is_solved_in_original_orientation())
+    }
+    fn is_solved_by(&self, definition: SolvedStateDefinition) -> bool {
+        dispatch_puzzle!(self, p => p.is_solved_by(definition))
+    }
+    fn is_solved_as_supercube(&self, definition: SolvedStateDefinition) -> bool {
+        dispatch_puzzle!(self, p => p.is_solved_as_supercube(definition))
+    }
+
+    #[cfg(debug_assertions)]
+    fn sticker_debug_info(&self, s: &mut String, sticker: Sticker) {
+        dispatch_puzzle!(self, p => p.sticker_debug_info(s, sticker))
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Piece(pub u16);
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
@@ -422,6 +630,18 @@ impl PieceTypeInfo {
     }
 }
 
+/// What counts as a solved puzzle.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SolvedStateDefinition {
+    /// Each face is a single color, regardless of the orientation of the
+    /// whole puzzle or which color is on which face.
+    #[default]
+    AnyOrientation,
+    /// Each face is its original color.
+    OriginalOrientation,
+}
+
 /// Convention for counting moves.
 #[derive(
     Serialize,
@@ -505,6 +725,18 @@ impl TwistMetric {
             Self::Qstm | Self::Qbtm | Self::Qobtm => Some(true),
         }
     }
+    /// Returns the next metric in the order shown to the user, respecting
+    /// whether quarter-turn metrics are enabled.
+    pub fn next(self, is_qtm: bool) -> Self {
+        let order = if is_qtm {
+            [Self::Atm, Self::Etm, Self::Qstm, Self::Qbtm, Self::Qobtm]
+        } else {
+            [Self::Atm, Self::Etm, Self::Stm, Self::Btm, Self::Obtm]
+        };
+        let index = order.iter().position(|&m| m == self).unwrap_or(0);
+        order[(index + 1) % order.len()]
+    }
+
     pub fn set_qtm(&mut self, is_qtm: bool) {
         *self = match self {
             Self::Stm | Self::Qstm => {
@@ -532,15 +764,28 @@ impl TwistMetric {
         };
     }
 
+    /// Returns the options equivalent to this metric, or `None` for metrics
+    /// that can't be expressed using [`TwistCountOptions`].
+    pub fn count_options(self) -> Option<TwistCountOptions> {
+        let slices = match self {
+            Self::Atm | Self::Etm => return None,
+            Self::Stm | Self::Qstm => SliceConvention::Slice,
+            Self::Btm | Self::Qbtm => SliceConvention::Block,
+            Self::Obtm | Self::Qobtm => SliceConvention::OuterBlock,
+        };
+        Some(TwistCountOptions {
+            slices,
+            quarter_turns: self.is_qtm() == Some(true),
+            rotations: false,
+        })
+    }
+
     /// Counts a sequence of twists using this metric.
     pub fn count_twists(
         self,
         puzzle: impl PuzzleType,
         twists: impl IntoIterator<Item = Twist>,
     ) -> usize {
-        #[allow(clippy::needless_late_init)]
-        let slice_multiplier: fn(LayerMask, u8) -> u32;
-
         match self {
             Self::Atm => {
                 let mut count = 0;
@@ -560,25 +805,68 @@ impl TwistMetric {
                     }
                 }
 
-                return count;
+                count
             }
-            Self::Etm => return twists.into_iter().count(),
+            Self::Etm => twists.into_iter().count(),
 
-            Self::Stm | Self::Qstm => slice_multiplier = |_, _| 1,
-            Self::Btm | Self::Qbtm => {
-                slice_multiplier = |layers, _| layers.count_contiguous_slices()
-            }
-            Self::Obtm | Self::Qobtm => slice_multiplier = LayerMask::count_outer_slices,
+            _ => self
+                .count_options()
+                .expect("metric has count options")
+                .count_twists(puzzle, twists),
         }
+    }
+}
 
-        let is_qtm = self.is_qtm().unwrap();
+/// Convention for counting twists of multiple layers.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SliceConvention {
+    /// Any twist counts as one move, regardless of which layers it twists.
+    #[default]
+    Slice,
+    /// Twists of noncontiguous layers are split into contiguous blocks.
+    Block,
+    /// Twists are split into contiguous blocks that include an outer layer.
+    OuterBlock,
+}
+impl SliceConvention {
+    /// Returns the number of moves that a twist of `layers` counts as.
+    fn moves_per_twist(self, layers: LayerMask, layer_count: u8) -> u32 {
+        match self {
+            Self::Slice => 1,
+            Self::Block => layers.count_contiguous_slices(),
+            Self::OuterBlock => layers.count_outer_slices(layer_count),
+        }
+    }
+}
 
+/// Options for counting twists, which generalize the slice, block, and
+/// outer-block turn metrics.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct TwistCountOptions {
+    /// How twists of multiple layers are counted.
+    pub slices: SliceConvention,
+    /// Whether double twists are split into quarter twists. If this is
+    /// `false`, consecutive twists of the same axis and layers are combined.
+    pub quarter_turns: bool,
+    /// Whether whole-puzzle rotations count as one move each.
+    pub rotations: bool,
+}
+impl TwistCountOptions {
+    /// Counts a sequence of twists using these options.
+    pub fn count_twists(
+        self,
+        puzzle: impl PuzzleType,
+        twists: impl IntoIterator<Item = Twist>,
+    ) -> usize {
         let mut count = 0;
 
         let mut prev_axis = None;
         let mut prev_layers = None;
         for twist in twists {
-            if twist.layers == puzzle.all_layers() {
+            let is_rotation = twist.layers == puzzle.all_layers();
+            if is_rotation && !self.rotations {
                 let opp = puzzle.opposite_twist_axis(twist.axis);
                 let is_same_axis =
                     prev_axis == Some(twist.axis) || opp.is_some() && prev_axis == opp;
@@ -591,7 +879,7 @@ impl TwistMetric {
                 continue;
             }
 
-            let direction_multiplier = if is_qtm {
+            let direction_multiplier = if self.quarter_turns {
                 puzzle.count_quarter_turns(twist)
             } else if prev_axis == Some(twist.axis) && prev_layers == Some(twist.layers) {
                 // Same axis and layers as previous twist! This twist is
@@ -604,8 +892,15 @@ impl TwistMetric {
             prev_axis = Some(twist.axis);
             prev_layers = Some(twist.layers);
 
-            count += direction_multiplier
-                * slice_multiplier(twist.layers, puzzle.layer_count()) as usize;
+            // A rotation is never split into slices.
+            let slice_multiplier = if is_rotation {
+                1
+            } else {
+                self.slices
+                    .moves_per_twist(twist.layers, puzzle.layer_count())
+            };
+
+            count += direction_multiplier * slice_multiplier as usize;
         }
 
         count
@@ -791,17 +1086,17 @@ impl FromStr for LayerMask {
     }
 }
 impl LayerMask {
-    pub(crate) fn slice_layers(total_layer_count: u8) -> Option<Self> {
+    pub fn slice_layers(total_layer_count: u8) -> Option<Self> {
         (total_layer_count >= 3).then(|| Self((Self::all_layers(total_layer_count).0 >> 1) & !1))
     }
-    pub(crate) fn all_layers(total_layer_count: u8) -> Self {
+    pub fn all_layers(total_layer_count: u8) -> Self {
         Self((1 << total_layer_count as u32) - 1)
     }
 
-    pub(crate) fn is_default(self) -> bool {
+    pub fn is_default(self) -> bool {
         self == Self::default()
     }
-    pub(crate) fn long_description(self) -> String {
+    pub fn long_description(self) -> String {
         match self.count() {
             0 => "no layers".to_owned(),
             1 => format!("layer {}", self.0.trailing_zeros() + 1),
@@ -811,10 +1106,10 @@ impl LayerMask {
             ),
         }
     }
-    pub(crate) fn count(self) -> u32 {
+    pub fn count(self) -> u32 {
         self.0.count_ones()
     }
-    pub(crate) fn count_contiguous_slices(self) -> u32 {
+    pub fn count_contiguous_slices(self) -> u32 {
         let mut n = self.0;
         let mut ret = 0;
         while n != 0 {
@@ -824,7 +1119,7 @@ impl LayerMask {
         }
         ret
     }
-    pub(crate) fn count_outer_slices(self, layer_count: u8) -> u32 {
+    pub fn count_outer_slices(self, layer_count: u8) -> u32 {
         let mut n = self.0;
         let mut ret = 0;
         while n != 0 {
@@ -840,10 +1135,10 @@ impl LayerMask {
         }
         ret
     }
-    pub(crate) fn is_contiguous_from_outermost(self) -> bool {
+    pub fn is_contiguous_from_outermost(self) -> bool {
         self.0 != 0 && self.0.count_ones() == self.0.trailing_ones()
     }
-    pub(crate) fn get_single_layer(self) -> Option<u32> {
+    pub fn get_single_layer(self) -> Option<u32> {
         (self.count() == 1).then(|| self.0.trailing_zeros())
     }
 }
@@ -868,3 +1163,29 @@ impl ClickTwists {
         }
     }
 }
+
+/// Whether the puzzle has been scrambled.
+#[derive(FromPrimitive, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ScrambleState {
+    /// Unscrambled.
+    #[default]
+    None = 0,
+    /// Some small number of scramble twists.
+    Partial = 1,
+    /// Fully scrambled.
+    Full = 2,
+    /// Was solved by user even if not currently solved.
+    Solved = 3,
+}
+impl ScrambleState {
+    /// Returns a short human-friendly description of the scramble state.
+    pub fn description(self) -> &'static str {
+        match self {
+            ScrambleState::None => "Unscrambled",
+            ScrambleState::Partial => "Partially scrambled",
+            ScrambleState::Full => "Fully scrambled",
+            ScrambleState::Solved => "Solved",
+        }
+    }
+}
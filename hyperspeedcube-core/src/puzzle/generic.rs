@@ -0,0 +1,966 @@
+//! Puzzles defined by data files loaded at runtime. A few definitions are also
+//! built in; see [`load_builtin()`].
+//!
+//! A puzzle definition is a YAML file with these fields:
+//!
+//! - `name`: display name of the puzzle, which must be unique.
+//! - `scramble_moves` (optional): number of random twists in a full scramble.
+//! - `faces`: list of faces (colors), each with a `symbol` and a `name`.
+//! - `twist_axes`: list of twist axes, each with a `name`, a `normal` vector,
+//!   a rotational `order`, and a list of `cuts`. Each cut is the distance of a
+//!   cutting plane from the origin along the normal.
+//! - `pieces`: list of pieces, each with an optional `type`, an optional
+//!   `bandage`, and a list of `stickers`. Each sticker has a `face` (symbol or
+//!   name) and a `polygon`, which is a list of 3D points. Pieces with the same
+//!   `bandage` name are fused together, so twists that would separate them are
+//!   blocked.
+//!
+//! Every twist must move each sticker onto the location of another sticker,
+//! which is checked when the puzzle is loaded. Pieces may be left out entirely
+//! to make void puzzles, such as a void cube with no centers or core.
+
+use cgmath::*;
+use itertools::Itertools;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use smallvec::SmallVec;
+use std::sync::Mutex;
+
+use super::*;
+
+const DEFAULT_SCRAMBLE_MOVES_COUNT: usize = 30;
+/// Puzzle definitions that are built into the program.
+const BUILTIN_PUZZLES: &[&str] = &[
+    include_str!("../../resources/puzzles/void_3x3x3.yaml"),
+    include_str!("../../resources/puzzles/bandaged_3x3x3.yaml"),
+];
+const DEFAULT_PIECE_TYPE_NAME: &str = "Piece";
+
+/// Maximum distance (relative to the size of the puzzle) between two points
+/// that are considered the same.
+const EPSILON: f32 = 0.001;
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Vec<&'static GenericDescription>> = Mutex::new(vec![]);
+}
+
+/// Identifier for a puzzle loaded from a definition file. This is serialized as
+/// the name of the puzzle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct GenericPuzzleId(u16);
+impl Serialize for GenericPuzzleId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(puzzle_description(*self).name())
+    }
+}
+impl<'de> Deserialize<'de> for GenericPuzzleId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        id_from_name(&name).ok_or_else(|| D::Error::custom(format!("unknown puzzle {name:?}")))
+    }
+}
+
+pub(super) fn puzzle_type(id: GenericPuzzleId) -> &'static dyn PuzzleType {
+    puzzle_description(id)
+}
+
+fn puzzle_description(id: GenericPuzzleId) -> &'static GenericDescription {
+    REGISTRY.lock().unwrap()[id.0 as usize]
+}
+
+fn id_from_name(name: &str) -> Option<GenericPuzzleId> {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .iter()
+        .position(|desc| desc.name == name)
+        .map(|i| GenericPuzzleId(i as u16))
+}
+
+/// Returns all puzzle types that have been loaded from definition files.
+pub fn puzzle_types() -> Vec<PuzzleTypeEnum> {
+    (0..REGISTRY.lock().unwrap().len())
+        .map(|i| PuzzleTypeEnum::Generic {
+            id: GenericPuzzleId(i as u16),
+        })
+        .collect()
+}
+
+/// Loads a puzzle definition, replacing any loaded puzzle with the same name.
+pub fn load(yaml: &str) -> Result<PuzzleTypeEnum, String> {
+    let spec: GenericPuzzleSpec = serde_yaml::from_str(yaml).map_err(|e| e.to_string())?;
+    let mut desc = GenericDescription::new(spec)?;
+
+    let mut registry = REGISTRY.lock().unwrap();
+    let index = match registry.iter().position(|d| d.name == desc.name) {
+        Some(i) => i,
+        None => registry.len(),
+    };
+    if index > u16::MAX as usize {
+        return Err("too many puzzles".to_string());
+    }
+    desc.id = GenericPuzzleId(index as u16);
+    // Puzzles may still reference an old description, so never free it.
+    let desc: &'static GenericDescription = Box::leak(Box::new(desc));
+    if index < registry.len() {
+        registry[index] = desc;
+    } else {
+        registry.push(desc);
+    }
+
+    Ok(desc.ty())
+}
+
+/// Loads the puzzle definitions that are built into the program. Custom
+/// puzzles loaded afterward with the same name replace them.
+pub fn load_builtin() {
+    for yaml in BUILTIN_PUZZLES {
+        load(yaml).expect("error loading built-in puzzle");
+    }
+}
+
+/// Loads all puzzle definitions (`*.yaml`) in a directory. Returns an error
+/// message for each file that could not be loaded.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_dir(dir: &std::path::Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![]; // No custom puzzles.
+    };
+    let paths = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |ext| ext == "yaml" || ext == "yml")
+        })
+        .sorted();
+    paths
+        .filter_map(|path| {
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|s| load(&s));
+            result.err().map(|e| format!("{}: {e}", path.display()))
+        })
+        .collect()
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct GenericPuzzleSpec {
+    name: String,
+    #[serde(default = "default_scramble_moves")]
+    scramble_moves: usize,
+    faces: Vec<FaceSpec>,
+    twist_axes: Vec<TwistAxisSpec>,
+    pieces: Vec<PieceSpec>,
+}
+fn default_scramble_moves() -> usize {
+    DEFAULT_SCRAMBLE_MOVES_COUNT
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct FaceSpec {
+    symbol: String,
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct TwistAxisSpec {
+    name: String,
+    normal: [f32; 3],
+    order: u8,
+    #[serde(default)]
+    cuts: Vec<f32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct PieceSpec {
+    #[serde(default, rename = "type")]
+    piece_type: Option<String>,
+    #[serde(default)]
+    bandage: Option<String>,
+    stickers: Vec<StickerSpec>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct StickerSpec {
+    face: String,
+    polygon: Vec<[f32; 3]>,
+}
+
+/// Location of a sticker on the solved puzzle. Sticker `i` starts in slot `i`.
+#[derive(Debug, Clone)]
+struct Slot {
+    /// Vertices, counterclockwise when seen from outside the puzzle.
+    verts: Vec<Point3<f32>>,
+    center: Point3<f32>,
+    /// Outward-facing unit normal vector.
+    normal: Vector3<f32>,
+}
+
+#[derive(Debug, Clone)]
+struct AxisGeometry {
+    /// Unit normal vector.
+    normal: Vector3<f32>,
+    order: u8,
+    /// Slot that each slot moves to after one clockwise step.
+    perm: Vec<u16>,
+    /// Layer of each slot.
+    layers: Vec<u8>,
+}
+impl AxisGeometry {
+    /// Returns the rotation for a number of clockwise steps.
+    fn rotation(&self, steps: f32) -> Quaternion<f32> {
+        let angle = -Rad::full_turn() * steps / self.order as f32;
+        Quaternion::from_axis_angle(self.normal, angle)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GenericDescription {
+    id: GenericPuzzleId,
+    name: String,
+
+    layer_count: u8,
+    scramble_moves_count: usize,
+
+    faces: Vec<FaceInfo>,
+    pieces: Vec<PieceInfo>,
+    stickers: Vec<StickerInfo>,
+    twist_axes: Vec<TwistAxisInfo>,
+    twist_directions: Vec<TwistDirectionInfo>,
+    piece_types: Vec<PieceTypeInfo>,
+    notation: NotationScheme,
+
+    /// Groups of pieces that are fused together.
+    bandages: Vec<Vec<Piece>>,
+    /// Index into `bandages` for each piece.
+    piece_bandages: Vec<Option<usize>>,
+
+    slots: Vec<Slot>,
+    axes: Vec<AxisGeometry>,
+    /// Maximum distance of any vertex from the origin.
+    radius: f32,
+}
+impl GenericDescription {
+    fn new(spec: GenericPuzzleSpec) -> Result<Self, String> {
+        if spec.faces.is_empty() || spec.twist_axes.is_empty() || spec.pieces.is_empty() {
+            return Err("puzzle must have faces, twist axes, and pieces".to_string());
+        }
+
+        // It's not like we'll ever unload a puzzle anyway, so just leak the
+        // strings and let us have the 'static lifetimes.
+        let leak = |s: &str| -> &'static str { Box::leak(s.to_owned().into_boxed_str()) };
+
+        let faces = spec
+            .faces
+            .iter()
+            .map(|f| FaceInfo::new(leak(&f.symbol), leak(&f.name)))
+            .collect_vec();
+        let face_from_str = |s: &str| {
+            faces
+                .iter()
+                .position(|f| f.symbol == s || f.name == s)
+                .map(|i| Face(i as u8))
+                .ok_or_else(|| format!("unknown face {s:?}"))
+        };
+
+        let mut piece_type_names = vec![];
+        let mut bandage_names = vec![];
+        let mut bandages: Vec<Vec<Piece>> = vec![];
+        let mut piece_bandages = vec![];
+        let mut pieces = vec![];
+        let mut stickers = vec![];
+        let mut slots = vec![];
+        let mut piece_centers = vec![];
+        for piece_spec in &spec.pieces {
+            if piece_spec.stickers.is_empty() {
+                return Err("every piece must have at least one sticker".to_string());
+            }
+            let piece = Piece(pieces.len() as _);
+            let type_name = piece_spec
+                .piece_type
+                .as_deref()
+                .unwrap_or(DEFAULT_PIECE_TYPE_NAME);
+            let piece_type = match piece_type_names.iter().position(|n| n == type_name) {
+                Some(i) => PieceType(i as _),
+                None => {
+                    piece_type_names.push(type_name.to_owned());
+                    PieceType((piece_type_names.len() - 1) as _)
+                }
+            };
+            let bandage = piece_spec.bandage.as_deref().map(|name| {
+                match bandage_names.iter().position(|n| n == name) {
+                    Some(i) => i,
+                    None => {
+                        bandage_names.push(name.to_owned());
+                        bandages.push(vec![]);
+                        bandages.len() - 1
+                    }
+                }
+            });
+            if let Some(i) = bandage {
+                bandages[i].push(piece);
+            }
+            piece_bandages.push(bandage);
+
+            let mut piece_stickers = SmallVec::new();
+            for sticker_spec in &piece_spec.stickers {
+                piece_stickers.push(Sticker(stickers.len() as _));
+                stickers.push(StickerInfo {
+                    piece,
+                    color: face_from_str(&sticker_spec.face)?,
+                });
+                slots.push(Slot::new(&sticker_spec.polygon)?);
+            }
+            let sticker_slots = &slots[slots.len() - piece_stickers.len()..];
+            let center = Point3::centroid(&sticker_slots.iter().map(|s| s.center).collect_vec());
+            piece_centers.push(center);
+
+            pieces.push(PieceInfo {
+                stickers: piece_stickers,
+                piece_type,
+            });
+        }
+        if stickers.len() > u16::MAX as usize {
+            return Err("too many stickers".to_string());
+        }
+
+        let radius = slots
+            .iter()
+            .flat_map(|slot| &slot.verts)
+            .map(|v| v.to_vec().magnitude())
+            .fold(0.0, f32::max);
+        let epsilon = EPSILON * radius;
+
+        let mut axes = vec![];
+        for axis_spec in &spec.twist_axes {
+            let normal = Vector3::from(axis_spec.normal);
+            if normal.magnitude() < EPSILON {
+                return Err(format!("twist axis {} has no normal", axis_spec.name));
+            }
+            let normal = normal.normalize();
+            if axis_spec.order < 2 {
+                return Err(format!(
+                    "twist axis {} has order less than 2",
+                    axis_spec.name
+                ));
+            }
+            if axis_spec.cuts.len() >= 32 {
+                return Err(format!("twist axis {} has too many cuts", axis_spec.name));
+            }
+            let cuts = axis_spec
+                .cuts
+                .iter()
+                .copied()
+                .sorted_by(|a, b| b.total_cmp(a))
+                .collect_vec();
+
+            let layers = stickers
+                .iter()
+                .map(|s| {
+                    let distance = piece_centers[s.piece.0 as usize].dot(normal);
+                    cuts.iter().filter(|&&cut| distance < cut).count() as u8
+                })
+                .collect_vec();
+
+            let mut axis = AxisGeometry {
+                normal,
+                order: axis_spec.order,
+                perm: vec![],
+                layers,
+            };
+            let rot = axis.rotation(1.0);
+            axis.perm = slots
+                .iter()
+                .map(|slot| {
+                    let center = rot.rotate_point(slot.center);
+                    let normal = rot.rotate_vector(slot.normal);
+                    slots
+                        .iter()
+                        .position(|other| {
+                            other.center.distance(center) < epsilon
+                                && other.normal.dot(normal) > 1.0 - EPSILON
+                        })
+                        .map(|i| i as u16)
+                        .ok_or_else(|| {
+                            format!(
+                                "twist axis {} does not move stickers onto other stickers",
+                                axis_spec.name,
+                            )
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+            axes.push(axis);
+        }
+
+        let layer_count = spec
+            .twist_axes
+            .iter()
+            .map(|a| a.cuts.len() + 1)
+            .max()
+            .unwrap_or(1);
+        let max_order = axes.iter().map(|a| a.order).max().unwrap_or(2);
+
+        let mut twist_directions = vec![];
+        for steps in 1..=max_order / 2 {
+            if steps == 1 {
+                twist_directions.push(TwistDirectionInfo::new("", "CW"));
+                twist_directions.push(TwistDirectionInfo::new("'", "CCW"));
+            } else {
+                twist_directions.push(TwistDirectionInfo::new(
+                    leak(&format!("{steps}")),
+                    leak(&format!("CW{steps}")),
+                ));
+                twist_directions.push(TwistDirectionInfo::new(
+                    leak(&format!("{steps}'")),
+                    leak(&format!("CCW{steps}")),
+                ));
+            }
+        }
+
+        let notation = NotationScheme {
+            axis_names: spec.twist_axes.iter().map(|a| a.name.clone()).collect(),
+            direction_names: twist_directions
+                .iter()
+                .map(|dir| TwistDirectionName::Same(dir.symbol.to_string()))
+                .collect(),
+            block_suffix: None,
+            aliases: vec![],
+        };
+
+        Ok(Self {
+            id: GenericPuzzleId(0), // assigned when registered
+            name: spec.name,
+
+            layer_count: layer_count as u8,
+            scramble_moves_count: spec.scramble_moves,
+
+            faces,
+            pieces,
+            stickers,
+            twist_axes: spec
+                .twist_axes
+                .iter()
+                .map(|a| TwistAxisInfo {
+                    name: leak(&a.name),
+                })
+                .collect(),
+            twist_directions,
+            piece_types: piece_type_names
+                .into_iter()
+                .map(PieceTypeInfo::new)
+                .collect(),
+            notation,
+
+            bandages,
+            piece_bandages,
+
+            slots,
+            axes,
+            radius,
+        })
+    }
+
+    /// Returns the signed number of clockwise steps for a twist direction.
+    fn direction_steps(&self, direction: TwistDirection) -> i32 {
+        let steps = direction.0 as i32 / 2 + 1;
+        if direction.0 % 2 == 0 {
+            steps
+        } else {
+            -steps
+        }
+    }
+    /// Returns the twist direction for a signed number of clockwise steps.
+    fn direction_from_steps(&self, steps: i32) -> Option<TwistDirection> {
+        let i = (steps.unsigned_abs() as usize).checked_sub(1)? * 2 + (steps < 0) as usize;
+        (i < self.twist_directions.len()).then(|| TwistDirection(i as _))
+    }
+}
+impl PuzzleType for GenericDescription {
+    fn ty(&self) -> PuzzleTypeEnum {
+        PuzzleTypeEnum::Generic { id: self.id }
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn family_display_name(&self) -> &'static str {
+        "Custom"
+    }
+    fn family_internal_name(&self) -> &'static str {
+        "Generic"
+    }
+    fn projection_type(&self) -> ProjectionType {
+        ProjectionType::_3D
+    }
+
+    fn layer_count(&self) -> u8 {
+        self.layer_count
+    }
+    fn family_max_layer_count(&self) -> u8 {
+        self.layer_count
+    }
+    fn projection_radius_3d(&self, _p: StickerGeometryParams) -> f32 {
+        self.radius
+    }
+    fn scramble_moves_count(&self) -> usize {
+        self.scramble_moves_count
+    }
+
+    fn faces(&self) -> &[FaceInfo] {
+        &self.faces
+    }
+    fn pieces(&self) -> &[PieceInfo] {
+        &self.pieces
+    }
+    fn stickers(&self) -> &[StickerInfo] {
+        &self.stickers
+    }
+    fn twist_axes(&self) -> &[TwistAxisInfo] {
+        &self.twist_axes
+    }
+    fn twist_directions(&self) -> &[TwistDirectionInfo] {
+        &self.twist_directions
+    }
+    fn piece_types(&self) -> &[PieceTypeInfo] {
+        &self.piece_types
+    }
+
+    fn opposite_twist_axis(&self, twist_axis: TwistAxis) -> Option<TwistAxis> {
+        let normal = self.axes[twist_axis.0 as usize].normal;
+        self.axes
+            .iter()
+            .position(|axis| axis.normal.dot(normal) < EPSILON - 1.0)
+            .map(|i| TwistAxis(i as _))
+    }
+    fn count_quarter_turns(&self, twist: Twist) -> usize {
+        self.direction_steps(twist.direction).unsigned_abs() as usize
+    }
+
+    fn make_recenter_twist(&self, _axis: TwistAxis) -> Result<Twist, String> {
+        Err("recentering is not supported for this puzzle".to_string())
+    }
+
+    fn canonicalize_twist(&self, twist: Twist) -> Twist {
+        twist
+    }
+
+    fn reverse_twist_direction(&self, direction: TwistDirection) -> TwistDirection {
+        TwistDirection(direction.0 ^ 1)
+    }
+    fn chain_twist_directions(
+        &self,
+        axis: TwistAxis,
+        dirs: &[TwistDirection],
+    ) -> Option<TwistDirection> {
+        let total: i32 = dirs.iter().map(|&dir| self.direction_steps(dir)).sum();
+        let order = self.axes.get(axis.0 as usize)?.order as i32;
+        let mut steps = total.rem_euclid(order);
+        if steps > order / 2 {
+            steps -= order;
+        }
+        self.direction_from_steps(steps)
+    }
+
+    fn notation_scheme(&self) -> &NotationScheme {
+        &self.notation
+    }
+}
+
+impl Slot {
+    fn new(polygon: &[[f32; 3]]) -> Result<Self, String> {
+        if polygon.len() < 3 {
+            return Err("sticker polygon must have at least 3 vertices".to_string());
+        }
+        let mut verts = polygon.iter().map(|&v| Point3::from(v)).collect_vec();
+        let center = Point3::centroid(&verts);
+
+        // Newell's method for the normal of a polygon
+        let normal: Vector3<f32> = verts
+            .iter()
+            .copied()
+            .circular_tuple_windows()
+            .fold(Vector3::zero(), |sum, (a, b)| {
+                sum + (a - center).cross(b - center)
+            });
+        if normal.magnitude() < EPSILON {
+            return Err("sticker polygon has no area".to_string());
+        }
+        let mut normal = normal.normalize();
+        if normal.dot(center.to_vec()) < 0.0 {
+            verts.reverse();
+            normal = -normal;
+        }
+
+        Ok(Self {
+            verts,
+            center,
+            normal,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GenericPuzzle {
+    desc: &'static GenericDescription,
+    /// Slot that each sticker is currently in.
+    sticker_slots: Box<[u16]>,
+}
+impl Eq for GenericPuzzle {}
+impl PartialEq for GenericPuzzle {
+    fn eq(&self, other: &Self) -> bool {
+        self.sticker_slots == other.sticker_slots
+    }
+}
+impl PuzzleState for GenericPuzzle {
+    fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
+        let desc = self.desc;
+        let axis = desc
+            .axes
+            .get(twist.axis.0 as usize)
+            .ok_or("invalid twist axis")?;
+        if twist.direction.0 as usize >= desc.twist_directions.len() {
+            return Err("invalid twist direction");
+        }
+        if !self.is_twist_legal(twist) {
+            return Err(BLOCKED_TWIST_ERROR);
+        }
+        let steps = desc
+            .direction_steps(twist.direction)
+            .rem_euclid(axis.order as i32);
+        for piece in self.pieces_affected_by_twist(twist) {
+            for &sticker in &desc.pieces[piece.0 as usize].stickers {
+                let slot = &mut self.sticker_slots[sticker.0 as usize];
+                for _ in 0..steps {
+                    *slot = axis.perm[*slot as usize];
+                }
+            }
+        }
+        Ok(())
+    }
+    fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8 {
+        let sticker = self.info(piece).stickers[0];
+        self.desc.axes[twist_axis.0 as usize].layers[self.slot(sticker)]
+    }
+    fn is_twist_legal(&self, twist: Twist) -> bool {
+        self.desc.bandages.iter().all(|bandage| {
+            bandage
+                .iter()
+                .map(|&piece| self.is_piece_affected_by_twist(twist, piece))
+                .all_equal()
+        })
+    }
+
+    fn rotation_candidates(&self) -> Vec<(Vec<Twist>, Quaternion<f32>)> {
+        let layers = self.all_layers();
+        let directions = (0..self.twist_directions().len() as u8).map(TwistDirection);
+        itertools::iproduct!(0..self.desc.axes.len(), directions)
+            .map(|(i, direction)| {
+                let axis = &self.desc.axes[i];
+                let steps = self.desc.direction_steps(direction) as f32;
+                let twist = Twist {
+                    axis: TwistAxis(i as _),
+                    direction,
+                    layers,
+                };
+                (vec![twist], axis.rotation(steps))
+            })
+            .collect()
+    }
+
+    fn sticker_geometry(
+        &self,
+        sticker: Sticker,
+        p: StickerGeometryParams,
+    ) -> Option<StickerGeometry> {
+        let piece = self.info(sticker).piece;
+        let slot_index = self.slot(sticker);
+        let slot = &self.desc.slots[slot_index];
+
+        let mut transform = p.view_transform;
+        if let Some((twist, progress)) = p.twist_animation {
+            if self.is_piece_affected_by_twist(twist, piece) {
+                let axis = &self.desc.axes[twist.axis.0 as usize];
+                let steps = self.desc.direction_steps(twist.direction) as f32;
+                let twist_transform: Matrix3<f32> = Quaternion::one()
+                    .slerp(axis.rotation(steps), progress)
+                    .into();
+                transform = transform * twist_transform;
+            }
+        }
+
+        // Shrink the sticker toward its center to leave space between
+        // stickers. Stickers of bandaged pieces shrink toward the center of
+        // the whole bandaged block on their face instead, so that there is no
+        // space between them. Front faces are wound clockwise when seen from
+        // outside.
+        let scale = (1.0 - p.sticker_spacing).max(0.0);
+        let center = self.bandaged_center(piece, slot);
+        let verts = slot
+            .verts
+            .iter()
+            .rev()
+            .map(|&v| transform.transform_point(center + (v - center) * scale))
+            .collect();
+
+        // Decide what twists should happen when the sticker is clicked. Use
+        // the twist axis closest to the sticker's normal.
+        let (axis_index, axis) = self.desc.axes.iter().enumerate().max_by(|(_, a), (_, b)| {
+            let a = a.normal.dot(slot.normal);
+            let b = b.normal.dot(slot.normal);
+            a.total_cmp(&b)
+        })?;
+        let mut twists = ClickTwists::default();
+        if axis.normal.dot(slot.normal) > 0.0 {
+            let cw_twist = Twist {
+                axis: TwistAxis(axis_index as _),
+                direction: TwistDirection(0),
+                layers: LayerMask(1 << axis.layers[slot_index]),
+            };
+            twists.cw = Some(cw_twist);
+            twists.ccw = Some(self.reverse_twist(cw_twist));
+        }
+
+        Some(StickerGeometry::new_double_polygon(
+            verts,
+            twists,
+            p.show_frontfaces,
+            p.show_backfaces,
+        ))
+    }
+    fn sticker_facet(&self, sticker: Sticker) -> Option<Face> {
+        // Sticker `i` starts in slot `i`, so each slot is on the face of the
+        // sticker that starts there.
+        Some(self.stickers()[self.slot(sticker)].color)
+    }
+
+    fn is_solved(&self) -> bool {
+        let mut color_per_facet = vec![None; self.faces().len()];
+        for (i, sticker) in self.stickers().iter().enumerate() {
+            let color = self.stickers()[self.slot(Sticker(i as _))].color;
+            let facet = sticker.color.0 as usize;
+            if color_per_facet[facet] == None {
+                color_per_facet[facet] = Some(color);
+            } else if color_per_facet[facet] != Some(color) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn is_solved_in_original_orientation(&self) -> bool {
+        (0..self.stickers().len() as _)
+            .map(Sticker)
+            .all(|sticker| self.stickers()[self.slot(sticker)].color == self.info(sticker).color)
+    }
+}
+#[delegate_to_methods]
+#[delegate(PuzzleType, target_ref = "desc")]
+impl GenericPuzzle {
+    pub fn new(id: GenericPuzzleId) -> Self {
+        let desc = puzzle_description(id);
+        let sticker_slots = (0..desc.stickers.len() as u16).collect();
+        Self {
+            desc,
+            sticker_slots,
+        }
+    }
+
+    fn desc(&self) -> &GenericDescription {
+        self.desc
+    }
+
+    fn slot(&self, sticker: Sticker) -> usize {
+        self.sticker_slots[sticker.0 as usize] as usize
+    }
+
+    /// Returns the center of the stickers in the same bandage as `piece` that
+    /// are on the same face as `slot`, or the center of `slot` if the piece is
+    /// not bandaged.
+    fn bandaged_center(&self, piece: Piece, slot: &Slot) -> Point3<f32> {
+        let Some(bandage) = self.desc.piece_bandages[piece.0 as usize] else {
+            return slot.center;
+        };
+        let centers = self.desc.bandages[bandage]
+            .iter()
+            .flat_map(|&piece| &self.info(piece).stickers)
+            .map(|&sticker| &self.desc.slots[self.slot(sticker)])
+            .filter(|other| other.normal.dot(slot.normal) > 1.0 - EPSILON)
+            .map(|other| other.center)
+            .collect_vec();
+        Point3::centroid(&centers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the definition of a 2x2x2 cube.
+    fn pocket_cube_yaml() -> String {
+        let mut pieces = String::new();
+        for corner in itertools::iproduct!([-1.0, 1.0], [-1.0, 1.0], [-1.0, 1.0]) {
+            let corner = [corner.0, corner.1, corner.2];
+            pieces += "  - stickers:\n";
+            for (i, face) in ["R", "L", "U", "D", "F", "B"].iter().enumerate() {
+                let axis = i / 2;
+                let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                if corner[axis] != sign {
+                    continue;
+                }
+                let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                let polygon = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+                    .iter()
+                    .map(|&(a, b)| {
+                        let mut p = [0.0; 3];
+                        p[axis] = sign;
+                        p[u] = a * corner[u];
+                        p[v] = b * corner[v];
+                        format!("[{}, {}, {}]", p[0], p[1], p[2])
+                    })
+                    .join(", ");
+                pieces += &format!("      - {{ face: {face}, polygon: [{polygon}] }}\n");
+            }
+        }
+
+        format!(
+            "name: Test 2x2x2\n\
+             faces:\n\
+             \x20 - {{ symbol: R, name: Right }}\n\
+             \x20 - {{ symbol: L, name: Left }}\n\
+             \x20 - {{ symbol: U, name: Up }}\n\
+             \x20 - {{ symbol: D, name: Down }}\n\
+             \x20 - {{ symbol: F, name: Front }}\n\
+             \x20 - {{ symbol: B, name: Back }}\n\
+             twist_axes:\n\
+             \x20 - {{ name: R, normal: [1, 0, 0], order: 4, cuts: [0] }}\n\
+             \x20 - {{ name: U, normal: [0, 1, 0], order: 4, cuts: [0] }}\n\
+             \x20 - {{ name: F, normal: [0, 0, 1], order: 4, cuts: [0] }}\n\
+             pieces:\n\
+             {pieces}",
+        )
+    }
+
+    #[test]
+    fn test_generic_pocket_cube() {
+        let ty = load(&pocket_cube_yaml()).unwrap();
+        assert_eq!(ty.name(), "Test 2x2x2");
+        assert_eq!(ty.stickers().len(), 24);
+        assert_eq!(ty.layer_count(), 2);
+
+        let mut puzzle = Puzzle::new(ty);
+        let notation = ty.notation_scheme();
+        let r = notation.parse_twist("R").unwrap();
+        let u = notation.parse_twist("U").unwrap();
+
+        puzzle.twist(r).unwrap();
+        assert!(!puzzle.is_solved());
+        puzzle.twist(ty.reverse_twist(r)).unwrap();
+        assert!(puzzle.is_solved());
+
+        for _ in 0..3 {
+            puzzle.twist(r).unwrap();
+            puzzle.twist(u).unwrap();
+            assert!(!puzzle.is_solved());
+            puzzle.twist(ty.reverse_twist(u)).unwrap();
+        }
+        puzzle.twist(r).unwrap();
+        assert!(puzzle.is_solved());
+
+        // Whole-puzzle rotations keep the puzzle solved.
+        puzzle
+            .twist(Twist {
+                layers: ty.all_layers(),
+                ..r
+            })
+            .unwrap();
+        assert!(puzzle.is_solved());
+    }
+
+    #[test]
+    fn test_generic_bandaged_cube() {
+        // Fuse the DLB and DLF corners.
+        let yaml = pocket_cube_yaml()
+            .replace("name: Test 2x2x2", "name: Test bandaged 2x2x2")
+            .replacen("  - stickers:", "  - bandage: a\n    stickers:", 2);
+        let ty = load(&yaml).unwrap();
+        let mut puzzle = Puzzle::new(ty);
+        let notation = ty.notation_scheme();
+        let r = notation.parse_twist("R").unwrap();
+        let f = notation.parse_twist("F").unwrap();
+
+        assert!(puzzle.is_twist_legal(r));
+        assert!(!puzzle.is_twist_legal(f));
+        assert!(puzzle.twist(f).is_err());
+        assert!(puzzle.is_solved());
+
+        // Whole-puzzle rotations are never blocked.
+        let f_rotation = Twist {
+            layers: ty.all_layers(),
+            ..f
+        };
+        assert!(puzzle.is_twist_legal(f_rotation));
+        puzzle.twist(f_rotation).unwrap();
+        assert!(puzzle.is_twist_legal(r));
+    }
+
+    #[test]
+    fn test_builtin_puzzles() {
+        load_builtin();
+        let find = |name| {
+            puzzle_types()
+                .into_iter()
+                .find(|ty| ty.name() == name)
+                .unwrap()
+        };
+
+        let void = find("Void 3x3x3");
+        assert_eq!(void.pieces().len(), 20);
+        assert_eq!(void.stickers().len(), 48);
+        assert_eq!(void.layer_count(), 3);
+
+        let bandaged = find("Bandaged 3x3x3");
+        assert_eq!(bandaged.stickers().len(), 54);
+        let mut puzzle = Puzzle::new(bandaged);
+        let notation = bandaged.notation_scheme();
+        let twist = |s| notation.parse_twist(s).unwrap();
+        for blocked in ["R", "B", "2R"] {
+            assert!(!puzzle.is_twist_legal(twist(blocked)), "{blocked}");
+        }
+        for legal in ["U", "F", "L", "D", "2U"] {
+            assert!(puzzle.is_twist_legal(twist(legal)), "{legal}");
+        }
+        puzzle.twist(twist("U")).unwrap();
+        puzzle.twist(twist("U'")).unwrap();
+        assert!(puzzle.is_solved());
+    }
+
+    #[test]
+    fn test_generic_chain_mixed_order() {
+        // Only allow half turns of R.
+        let yaml = pocket_cube_yaml()
+            .replace("name: Test 2x2x2", "name: Test mixed-order 2x2x2")
+            .replace(
+                "{ name: R, normal: [1, 0, 0], order: 4",
+                "{ name: R, normal: [1, 0, 0], order: 2",
+            );
+        let ty = load(&yaml).unwrap();
+        let notation = ty.notation_scheme();
+        let r = notation.parse_twist("R").unwrap();
+        let u = notation.parse_twist("U").unwrap();
+        let u2 = notation.parse_twist("U2").unwrap();
+
+        // Two half turns of R cancel out, even though U has order 4.
+        let chain = |twist: Twist| ty.chain_twist_directions(twist.axis, &[twist.direction; 2]);
+        assert_eq!(chain(r), None);
+        assert_eq!(chain(u), Some(u2.direction));
+
+        let mut puzzle = Puzzle::new(ty);
+        puzzle.twist(r).unwrap();
+        assert!(!puzzle.is_solved());
+        puzzle.twist(r).unwrap();
+        assert!(puzzle.is_solved());
+    }
+}
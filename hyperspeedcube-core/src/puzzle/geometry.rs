@@ -4,16 +4,33 @@
 use cgmath::*;
 use smallvec::{smallvec, SmallVec};
 use std::cmp::Ordering;
+use std::sync::Arc;
 
-use super::{ClickTwists, PuzzleType, PuzzleTypeEnum, Sticker, Twist};
-use crate::preferences::ViewPreferences;
+use super::{ClickTwists, PuzzleType, PuzzleTypeEnum, Sign, Sticker, Twist};
 use crate::util::{self, IterCyclicPairsExt};
+use crate::ViewPreferences;
 
 const W_NEAR_CLIPPING_DIVISOR: f32 = 0.1;
 const Z_NEAR_CLIPPING_DIVISOR: f32 = 0.0;
 
 const EPSILON: f32 = 0.000001;
 
+/// Maximum number of polygons that each sticker is tested against when culling
+/// occluded stickers.
+const MAX_OCCLUDERS: usize = 64;
+
+/// Vertex indices of each face of a cube, where vertex `i` is at the corner
+/// whose X, Y, and Z coordinates are positive iff bits 2, 1, and 0 of `i` are
+/// set respectively.
+const CUBE_POLYGON_INDICES: [[u16; 4]; 6] = [
+    [0, 2, 3, 1],
+    [4, 5, 7, 6],
+    [0, 1, 5, 4],
+    [2, 6, 7, 3],
+    [0, 4, 6, 2],
+    [1, 3, 7, 5],
+];
+
 /// Parameters for constructing sticker geometry.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct StickerGeometryParams {
@@ -36,6 +53,8 @@ pub struct StickerGeometryParams {
     pub fov_4d: f32,
     /// 3D FOV, in degrees.
     pub fov_3d: f32,
+    /// Distance of the 3D camera beyond its usual position.
+    pub camera_distance: f32,
 
     /// Factor of how much the W coordinate affects the XYZ coordinates. This is
     /// computed from the 4D FOV.
@@ -60,6 +79,11 @@ pub struct StickerGeometryParams {
     pub show_backfaces: bool,
     /// Whether to clip points behind the 4D camera.
     pub clip_4d: bool,
+    /// Whether to unfold the puzzle into a net, for puzzles that support it.
+    pub net: bool,
+    /// Whether to mark the orientation of center stickers, for puzzles that
+    /// support it.
+    pub supercube: bool,
 }
 impl StickerGeometryParams {
     /// Constructs sticker geometry parameters for a set of view preferences.
@@ -106,6 +130,7 @@ impl StickerGeometryParams {
 
             fov_4d: view_prefs.fov_4d,
             fov_3d: view_prefs.fov_3d,
+            camera_distance: view_prefs.camera_distance,
             w_factor_4d: (view_prefs.fov_4d.to_radians() / 2.0).tan(),
             w_factor_3d: (view_prefs.fov_3d.to_radians() / 2.0).tan(),
 
@@ -118,6 +143,8 @@ impl StickerGeometryParams {
             show_frontfaces: view_prefs.show_frontfaces,
             show_backfaces: view_prefs.show_backfaces,
             clip_4d: view_prefs.clip_4d,
+            net: view_prefs.net,
+            supercube: false,
         };
 
         ret.view_transform /= puzzle_type.projection_radius_3d(ret);
@@ -125,6 +152,23 @@ impl StickerGeometryParams {
         ret
     }
 
+    /// Projects a 5D point down to 3D, by first projecting it to 4D and then
+    /// projecting that down to 3D. Both projections use the 4D FOV.
+    pub fn project_5d(self, point: [f32; 5]) -> Option<Point3<f32>> {
+        let [x, y, z, w, v] = point;
+        let camera_v = self.face_scale;
+
+        // See `project_4d()`.
+        let divisor = 1.0 + (1.0 - v / camera_v) * self.w_factor_4d;
+
+        // Clip geometry that is behind the 5D camera.
+        if self.clip_4d && divisor < W_NEAR_CLIPPING_DIVISOR {
+            return None;
+        }
+
+        self.project_4d(cgmath::vec4(x, y, z, w) / divisor)
+    }
+
     /// Projects a 4D point down to 3D.
     pub fn project_4d(self, point: Vector4<f32>) -> Option<Point3<f32>> {
         let camera_w = self.face_scale;
@@ -154,7 +198,11 @@ impl StickerGeometryParams {
         // This Desmos graph shows how this divisor varies with respect to Z
         // (shown along the X axis) and the FOV (controlled by a slider):
         // https://www.desmos.com/calculator/ocztouh1h0
-        let divisor = 1.0 + (self.fov_3d.signum() - point.z) * self.w_factor_3d;
+        //
+        // Moving the camera further away moves that fixed plane with it, so
+        // the puzzle shrinks.
+        let camera_z = self.fov_3d.signum() * (1.0 + self.camera_distance);
+        let divisor = 1.0 + (camera_z - point.z) * self.w_factor_3d;
 
         // Clip geometry that is behind the 3D camera.
         if divisor < Z_NEAR_CLIPPING_DIVISOR {
@@ -201,6 +249,114 @@ impl StickerGeometry {
         }
         ret
     }
+    /// Constructs geometry for a flat polygon with a front face and a back
+    /// face. The vertices must be in the same winding order as the front face
+    /// of [`Self::new_double_quad()`].
+    pub(super) fn new_double_polygon(
+        verts: Vec<Point3<f32>>,
+        twists: ClickTwists,
+        front_face: bool,
+        back_face: bool,
+    ) -> Self {
+        let n = verts.len() as u16;
+        let mut ret = Self {
+            verts,
+            polygon_indices: vec![(0..n).collect(), (0..n).rev().collect()],
+            polygon_twists: vec![twists, twists.rev()],
+        };
+        if !back_face {
+            ret.polygon_indices.pop();
+            ret.polygon_twists.pop();
+        }
+        if !front_face {
+            ret.polygon_indices.remove(0);
+            ret.polygon_twists.remove(0);
+        }
+        ret
+    }
+    /// Constructs geometry for a tesseract projected to 3D. Vertex `i` is at
+    /// the corner whose coordinate along axis `j` is positive iff bit `3 - j`
+    /// of `i` is set.
+    ///
+    /// The cells of a projected tesseract tile its projection twice over, so
+    /// only the cells with positive orientation are included. `twists` is
+    /// called for each polygon with the two axes that are constant on it,
+    /// along with their signs.
+    pub(super) fn new_tesseract(
+        verts: [Point3<f32>; 16],
+        twists: impl Fn([(usize, Sign); 2]) -> ClickTwists,
+    ) -> Option<Self> {
+        let mut ret = Self {
+            verts: verts.to_vec(),
+            polygon_indices: vec![],
+            polygon_twists: vec![],
+        };
+        for fixed_axis in 0..4 {
+            let fixed_bit: u16 = 8 >> fixed_axis;
+            let mut cell_bits = [0_u16; 3];
+            for (bit, j) in cell_bits
+                .iter_mut()
+                .zip((0..4).filter(|&j| j != fixed_axis))
+            {
+                *bit = 8 >> j;
+            }
+
+            for fixed_sign in [Sign::Neg, Sign::Pos] {
+                let base = if fixed_sign == Sign::Pos {
+                    fixed_bit
+                } else {
+                    0
+                };
+                let cell_vert = |bits: [u16; 3], i: u16| {
+                    let mut ret = base;
+                    for (j, bit) in bits.into_iter().enumerate() {
+                        if i & (4 >> j) != 0 {
+                            ret |= bit;
+                        }
+                    }
+                    ret
+                };
+                let v = |i| verts[cell_vert(cell_bits, i) as usize];
+                let volume =
+                    Matrix3::from_cols(v(4) - v(0), v(2) - v(0), v(1) - v(0)).determinant();
+
+                // Each cell's orientation as part of the boundary of the
+                // tesseract alternates between axes and flips between opposite
+                // cells.
+                let is_positive_cell = (fixed_sign == Sign::Pos) == (fixed_axis % 2 == 0);
+                if volume == 0.0 || (volume > 0.0) != is_positive_cell {
+                    continue;
+                }
+                // Make sure the cube is right-handed.
+                let mut bits = cell_bits;
+                if volume < 0.0 {
+                    bits.swap(0, 1);
+                }
+
+                for cube_indices in CUBE_POLYGON_INDICES {
+                    let indices: Box<[u16]> =
+                        cube_indices.iter().map(|&i| cell_vert(bits, i)).collect();
+                    let all = indices.iter().fold(15, |a, &i| a & i);
+                    let any = indices.iter().fold(0, |a, &i| a | i);
+                    let fixed_axes = (0..4)
+                        .filter(|&j| all & (8 >> j) != 0 || any & (8 >> j) == 0)
+                        .map(|j| {
+                            let sign = if all & (8 >> j) != 0 {
+                                Sign::Pos
+                            } else {
+                                Sign::Neg
+                            };
+                            (j, sign)
+                        })
+                        .collect::<SmallVec<[_; 2]>>();
+                    ret.polygon_twists
+                        .push(twists([fixed_axes[0], fixed_axes[1]]));
+                    ret.polygon_indices.push(indices);
+                }
+            }
+        }
+        (!ret.polygon_indices.is_empty()).then_some(ret)
+    }
     pub(super) fn new_cube(verts: [Point3<f32>; 8], twists: [ClickTwists; 6]) -> Option<Self> {
         // Only show this sticker if the 3D volume is positive. (Cull it if its
         // 3D volume is negative.)
@@ -213,41 +369,64 @@ impl StickerGeometry {
         .is_sign_positive()
         .then(|| Self {
             verts: verts.to_vec(),
-            polygon_indices: vec![
-                Box::new([0, 2, 3, 1]),
-                Box::new([4, 5, 7, 6]),
-                Box::new([0, 1, 5, 4]),
-                Box::new([2, 6, 7, 3]),
-                Box::new([0, 4, 6, 2]),
-                Box::new([1, 3, 7, 5]),
-            ],
+            polygon_indices: CUBE_POLYGON_INDICES
+                .iter()
+                .map(|indices| Box::new(*indices) as Box<[u16]>)
+                .collect(),
             polygon_twists: twists.to_vec(),
         })
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct ProjectedStickerGeometry {
+/// Projected geometry of a single sticker.
+///
+/// The vertex and polygon data is reference-counted so that cached geometry
+/// can be cloned every frame without allocating.
+#[derive(Debug, Clone)]
+pub struct ProjectedStickerGeometry {
     pub sticker: Sticker,
 
-    pub verts: Box<[Point3<f32>]>,
+    pub verts: Arc<[Point3<f32>]>,
     pub min_bound: Point3<f32>,
     pub max_bound: Point3<f32>,
 
-    pub front_polygons: Box<[Polygon]>,
-    pub back_polygons: Box<[Polygon]>,
+    pub front_polygons: Arc<[Polygon]>,
+    pub back_polygons: Arc<[Polygon]>,
 }
 impl ProjectedStickerGeometry {
-    pub(crate) fn twists_for_point(&self, point: Point2<f32>) -> Option<ClickTwists> {
-        self.front_polygons
-            .iter()
-            .find(|polygon| polygon.contains_point(point))
-            .map(|polygon| polygon.twists)
+    pub fn twists_for_point(&self, point: Point2<f32>) -> Option<ClickTwists> {
+        self.pick(point, false).map(|picked| picked.polygon.twists)
     }
+
+    /// Returns the polygon nearest the camera that contains `point`. If
+    /// `include_back_polygons` is `true`, back-facing polygons are considered
+    /// too, which is useful when the sticker is transparent enough to see
+    /// them.
+    pub fn pick(&self, point: Point2<f32>, include_back_polygons: bool) -> Option<PickedPolygon> {
+        let front = self.front_polygons.iter().map(|p| (p, false));
+        let back = self.back_polygons.iter().map(|p| (p, true));
+        front
+            .chain(back.filter(|_| include_back_polygons))
+            .filter(|(polygon, _)| polygon.contains_point(point))
+            .max_by(|(a, _), (b, _)| a.depth_at_point(point).total_cmp(&b.depth_at_point(point)))
+            .map(|(polygon, is_back)| PickedPolygon {
+                polygon: polygon.clone(),
+                is_back,
+            })
+    }
+}
+
+/// Polygon of a sticker under the cursor.
+#[derive(Debug, Clone)]
+pub struct PickedPolygon {
+    pub polygon: Polygon,
+    /// Whether the polygon faces away from the camera, meaning that it is
+    /// seen through the sticker and appears mirrored.
+    pub is_back: bool,
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct Polygon {
+pub struct Polygon {
     pub verts: SmallVec<[Point3<f32>; 4]>,
     pub min_bound: Point3<f32>,
     pub max_bound: Point3<f32>,
@@ -300,21 +479,43 @@ impl Polygon {
         }
     }
 
-    fn contains_point(&self, point: Point2<f32>) -> bool {
-        self.min_bound.x <= point.x
-            && self.min_bound.y <= point.y
-            && point.x <= self.max_bound.x
-            && point.y <= self.max_bound.y
-            && self
-                .verts
-                .iter()
-                .map(|v| cgmath::point2(v.x, v.y))
-                .cyclic_pairs()
-                .all(|(a, b)| (b - a).perp_dot(point - a) <= 0.0)
+    /// Returns whether the polygon's projection onto the screen contains
+    /// `point`. This works regardless of winding order, even if the projected
+    /// polygon is concave.
+    pub fn contains_point(&self, point: Point2<f32>) -> bool {
+        if point.x < self.min_bound.x
+            || point.y < self.min_bound.y
+            || self.max_bound.x < point.x
+            || self.max_bound.y < point.y
+        {
+            return false;
+        }
+
+        // Count how many edges cross a ray from `point` in the +X direction.
+        let mut is_inside = false;
+        for (a, b) in self.verts.iter().cyclic_pairs() {
+            if (a.y > point.y) != (b.y > point.y) {
+                let x_intersect = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+                if point.x <= x_intersect {
+                    is_inside ^= true;
+                }
+            }
+        }
+        is_inside
+    }
+    /// Returns the Z coordinate of the polygon's plane at `point`. Larger
+    /// values are nearer the camera.
+    pub fn depth_at_point(&self, point: Point2<f32>) -> f32 {
+        let n = self.normal;
+        let v = self.verts[0];
+        if n.z == 0.0 {
+            return self.max_bound.z; // polygon is seen edge-on
+        }
+        v.z - (n.x * (point.x - v.x) + n.y * (point.y - v.y)) / n.z
     }
 }
 
-pub(crate) fn polygon_from_indices(
+pub fn polygon_from_indices(
     verts: &[Point3<f32>],
     indices: &[u16],
     illumination: f32,
@@ -336,7 +537,7 @@ pub(crate) fn polygon_from_indices(
     }
 }
 
-pub(crate) fn polygon_normal_from_indices(verts: &[Point3<f32>], indices: &[u16]) -> Vector3<f32> {
+pub fn polygon_normal_from_indices(verts: &[Point3<f32>], indices: &[u16]) -> Vector3<f32> {
     let a = verts[indices[0] as usize];
     let b = verts[indices[1] as usize];
     let c = verts[indices[2] as usize];
@@ -353,14 +554,72 @@ trait NewellObj: Sized {
     fn can_be_drawn_behind(&self, other: &Self) -> bool;
 }
 
+/// Removes stickers that are entirely covered by a single front-facing polygon
+/// of an opaque sticker in front of them. `objs` must already be sorted by
+/// depth.
+pub fn cull_occluded_stickers(
+    objs: &mut Vec<ProjectedStickerGeometry>,
+    is_opaque: impl Fn(Sticker) -> bool,
+) {
+    // Only keep the largest polygons as occluders, so that this stays fast even
+    // for puzzles with many stickers.
+    let area = |p: &Polygon| (p.max_bound.x - p.min_bound.x) * (p.max_bound.y - p.min_bound.y);
+    let mut occluders: Vec<&Polygon> = vec![];
+
+    let mut is_visible = vec![true; objs.len()];
+    // Iterate from front to back.
+    for (i, obj) in objs.iter().enumerate().rev() {
+        let (min, max) = (obj.min_bound, obj.max_bound);
+        let corners = [
+            cgmath::point2(min.x, min.y),
+            cgmath::point2(max.x, min.y),
+            cgmath::point2(max.x, max.y),
+            cgmath::point2(min.x, max.y),
+        ];
+        if occluders
+            .iter()
+            .any(|polygon| corners.iter().all(|&c| polygon.contains_point(c)))
+        {
+            is_visible[i] = false;
+            continue;
+        }
+
+        if is_opaque(obj.sticker) {
+            for polygon in &*obj.front_polygons {
+                if occluders.len() < MAX_OCCLUDERS {
+                    occluders.push(polygon);
+                } else if let Some(smallest) = occluders
+                    .iter_mut()
+                    .min_by(|a, b| area(a).total_cmp(&area(b)))
+                    .filter(|smallest| area(smallest) < area(polygon))
+                {
+                    *smallest = polygon;
+                }
+            }
+        }
+    }
+
+    log::trace!(
+        "Culled {} of {} stickers using {} occluders",
+        is_visible.iter().filter(|&&v| !v).count(),
+        objs.len(),
+        occluders.len(),
+    );
+
+    let mut is_visible = is_visible.into_iter();
+    objs.retain(|_| is_visible.next().unwrap_or(true));
+}
+
 /// Sort stickers by depth using to Newell's algorithm. Stickers are not split.
-pub(crate) fn sort_by_depth(objs: &mut [ProjectedStickerGeometry]) {
+pub fn sort_by_depth(objs: &mut [ProjectedStickerGeometry]) {
     // First, approximate the correct order.
     objs.sort_by(NewellObj::approx_depth_cmp);
 
     // This algorithm is basically selection sort. At every iteration, all the
     // objects before `i` are guaranteed to be in their final order, and we
     // search for the object that we can place in the `i`th index.
+    let mut total_swaps = 0;
+    let mut cycles = 0;
     let mut i = 0;
     while i < objs.len() {
         // Keep track of how many times we swap objects; if we swap objects too
@@ -379,6 +638,7 @@ pub(crate) fn sort_by_depth(objs: &mut [ProjectedStickerGeometry]) {
                 // must be a cycle, which can only be resolved by splitting one
                 // of the objects. Dealing with split polygons is complicated,
                 // so just give up and draw the polygons in the wrong order. :(
+                cycles += 1;
                 break;
             } else {
                 // Uh oh, `objs[j]` must be drawn behind `objs[i]`. Select
@@ -387,6 +647,7 @@ pub(crate) fn sort_by_depth(objs: &mut [ProjectedStickerGeometry]) {
                 objs[i..=j].rotate_right(1);
                 // Record that we swapped this polygon.
                 swaps += 1;
+                total_swaps += 1;
                 // Check all of `objs[(i+1)..]` again.
                 j = i + 1;
             }
@@ -398,6 +659,10 @@ pub(crate) fn sort_by_depth(objs: &mut [ProjectedStickerGeometry]) {
     }
 
     // Everything is (hopefully) sorted now! Yay!
+    log::trace!(
+        "Sorted {} stickers with {total_swaps} swaps and {cycles} unresolved cycles",
+        objs.len(),
+    );
 }
 
 impl NewellObj for ProjectedStickerGeometry {
@@ -584,3 +849,37 @@ impl PointRelativeToLine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concave_polygon_contains_point() {
+        // L-shaped polygon, missing its top right corner.
+        let verts: SmallVec<_> = [
+            (0.0, 0.0),
+            (2.0, 0.0),
+            (2.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 2.0),
+            (0.0, 2.0),
+        ]
+        .into_iter()
+        .map(|(x, y)| cgmath::point3(x, y, 0.0))
+        .collect();
+        let ccw = Polygon::new(verts.clone(), 1.0, ClickTwists::default());
+        let cw = Polygon::new(
+            verts.into_iter().rev().collect(),
+            1.0,
+            ClickTwists::default(),
+        );
+
+        for polygon in [ccw, cw] {
+            assert!(polygon.contains_point(cgmath::point2(0.5, 1.5)));
+            assert!(polygon.contains_point(cgmath::point2(1.5, 0.5)));
+            assert!(!polygon.contains_point(cgmath::point2(1.5, 1.5)));
+            assert!(!polygon.contains_point(cgmath::point2(2.5, 0.5)));
+        }
+    }
+}
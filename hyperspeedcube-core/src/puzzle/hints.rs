@@ -0,0 +1,200 @@
+//! Hints for solving a 3x3x3 using a beginner layer-by-layer method.
+
+use std::fmt;
+
+use super::{
+    solver, traits::*, Face, LayerMask, Piece, Rubiks3D, Twist, TwistAxis, TwistDirection,
+};
+
+/// Maximum number of twists in a hint. Longer searches take too long to run
+/// on request.
+pub const MAX_HINT_LENGTH: usize = 5;
+
+/// Step of the beginner method.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BeginnerStep {
+    Cross,
+    FirstLayerCorners,
+    SecondLayer,
+    LastLayer,
+    Solved,
+}
+impl fmt::Display for BeginnerStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BeginnerStep::Cross => write!(f, "Cross"),
+            BeginnerStep::FirstLayerCorners => write!(f, "First layer corners"),
+            BeginnerStep::SecondLayer => write!(f, "Second layer edges"),
+            BeginnerStep::LastLayer => write!(f, "Last layer"),
+            BeginnerStep::Solved => write!(f, "Solved"),
+        }
+    }
+}
+
+/// Suggestion for the next step of the beginner method.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    /// Step that the puzzle is on.
+    pub step: BeginnerStep,
+    /// Color of the first layer.
+    pub first_layer: Face,
+    /// Twists that solve one more piece of the step, or `None` if no short
+    /// enough sequence was found. The last layer needs algorithms, so it never
+    /// has a hint.
+    pub twists: Option<Vec<Twist>>,
+}
+
+/// Returns a hint for the next step of the beginner method on a 3x3x3, or
+/// `None` if the puzzle is a different size. Hints only use outer-layer
+/// twists, so they never move the centers.
+pub fn beginner_hint(puzzle: &Rubiks3D) -> Option<Hint> {
+    if puzzle.layer_count() != 3 {
+        return None;
+    }
+
+    let centers = center_facets(puzzle)?;
+    let is_solved = |p: &Rubiks3D, piece: Piece| {
+        p.info(piece)
+            .stickers
+            .iter()
+            .all(|&s| p.sticker_facet(s) == Some(centers[p.info(s).color.0 as usize]))
+    };
+    let has_color = |piece: Piece, color: Face| {
+        puzzle
+            .info(piece)
+            .stickers
+            .iter()
+            .any(|&s| puzzle.info(s).color == color)
+    };
+    let pieces_with_sticker_count = |n: usize| {
+        (0..puzzle.pieces().len() as _)
+            .map(Piece)
+            .filter(move |&piece| puzzle.info(piece).stickers.len() == n)
+    };
+
+    // Use whichever color has the most solved pieces as the first layer,
+    // preferring the one on the bottom.
+    let first_layer = (0..puzzle.faces().len() as _)
+        .map(Face)
+        .max_by_key(|&color| {
+            let solved_count = (0..puzzle.pieces().len() as _)
+                .map(Piece)
+                .filter(|&piece| has_color(piece, color) && is_solved(puzzle, piece))
+                .count();
+            (
+                solved_count,
+                puzzle.faces()[centers[color.0 as usize].0 as usize].symbol == "D",
+            )
+        })?;
+    let last_layer = Face(puzzle.opposite_twist_axis(TwistAxis(first_layer.0))?.0);
+
+    let steps: [(BeginnerStep, Vec<Piece>); 4] = [
+        (
+            BeginnerStep::Cross,
+            pieces_with_sticker_count(2)
+                .filter(|&p| has_color(p, first_layer))
+                .collect(),
+        ),
+        (
+            BeginnerStep::FirstLayerCorners,
+            pieces_with_sticker_count(3)
+                .filter(|&p| has_color(p, first_layer))
+                .collect(),
+        ),
+        (
+            BeginnerStep::SecondLayer,
+            pieces_with_sticker_count(2)
+                .filter(|&p| !has_color(p, first_layer) && !has_color(p, last_layer))
+                .collect(),
+        ),
+        (
+            BeginnerStep::LastLayer,
+            (0..puzzle.pieces().len() as _).map(Piece).collect(),
+        ),
+    ];
+
+    let mut done = vec![];
+    for (step, pieces) in steps {
+        let solved_count =
+            |p: &Rubiks3D| pieces.iter().filter(|&&piece| is_solved(p, piece)).count();
+        let initial_count = solved_count(puzzle);
+        if initial_count == pieces.len() {
+            done.extend(pieces);
+            continue;
+        }
+
+        // Solve one more piece of this step without breaking earlier steps.
+        let goal = |p: &Rubiks3D| {
+            done.iter().all(|&piece| is_solved(p, piece)) && solved_count(p) > initial_count
+        };
+        let twists = (step != BeginnerStep::LastLayer)
+            .then(|| solver::search(puzzle, &outer_layer_twists(puzzle), MAX_HINT_LENGTH, goal))
+            .flatten();
+        return Some(Hint {
+            step,
+            first_layer,
+            twists,
+        });
+    }
+
+    Some(Hint {
+        step: BeginnerStep::Solved,
+        first_layer,
+        twists: Some(vec![]),
+    })
+}
+
+/// Returns the facet that the center of each color is on.
+fn center_facets(puzzle: &Rubiks3D) -> Option<Vec<Face>> {
+    let mut ret = vec![Face(0); puzzle.faces().len()];
+    for piece in puzzle.pieces() {
+        if let [sticker] = &piece.stickers[..] {
+            let sticker = *sticker;
+            ret[puzzle.info(sticker).color.0 as usize] = puzzle.sticker_facet(sticker)?;
+        }
+    }
+    Some(ret)
+}
+
+/// Returns every quarter and half twist of a single outer layer.
+fn outer_layer_twists(puzzle: &Rubiks3D) -> Vec<Twist> {
+    let directions = puzzle
+        .twist_directions()
+        .iter()
+        .position(|dir| dir.symbol == "2'")
+        .unwrap_or(puzzle.twist_directions().len());
+    (0..puzzle.twist_axes().len() as _)
+        .flat_map(|axis| {
+            (0..directions as _).map(move |direction| Twist {
+                axis: TwistAxis(axis),
+                direction: TwistDirection(direction),
+                layers: LayerMask(1),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beginner_hint() {
+        let mut p = Rubiks3D::new(3);
+        assert_eq!(beginner_hint(&p).unwrap().step, BeginnerStep::Solved);
+
+        // After twisting two opposite side faces, each of the other colors
+        // has the same number of solved pieces, so the first layer is on the
+        // bottom. Undoing either twist fixes one more edge of the cross.
+        for s in ["F", "B"] {
+            let twist = p.notation_scheme().parse_twist(s).unwrap();
+            p.twist(twist).unwrap();
+        }
+        let hint = beginner_hint(&p).unwrap();
+        assert_eq!(p.faces()[hint.first_layer.0 as usize].symbol, "D");
+        assert_eq!(hint.step, BeginnerStep::Cross);
+        assert_eq!(hint.twists.unwrap().len(), 1);
+
+        assert!(beginner_hint(&Rubiks3D::new(4)).is_none());
+    }
+}
@@ -0,0 +1,183 @@
+//! Property-based tests that check invariants of twists on every built-in
+//! puzzle type.
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+use super::*;
+
+/// Maximum number of twists in a generated sequence.
+const MAX_TWISTS: usize = 40;
+
+/// Returns a strategy that picks a small built-in puzzle type. Puzzles loaded
+/// from definition files aren't included.
+fn puzzle_type() -> impl Strategy<Value = PuzzleTypeEnum> {
+    let mut types = vec![PuzzleTypeEnum::Simplex4D, PuzzleTypeEnum::Clock];
+    types.extend((1..=5).map(|layer_count| PuzzleTypeEnum::Rubiks3D { layer_count }));
+    types.extend((1..=4).map(|layer_count| PuzzleTypeEnum::Rubiks4D { layer_count }));
+    types.extend((1..=3).map(|layer_count| PuzzleTypeEnum::Rubiks5D { layer_count }));
+    prop::sample::select(types)
+}
+
+/// Returns a strategy for a twist on any puzzle type, which is turned into a
+/// twist on a particular puzzle type using [`make_twist()`].
+fn twist_indices() -> impl Strategy<Value = (Index, Index, Index)> {
+    (any::<Index>(), any::<Index>(), any::<Index>())
+}
+fn twist_indices_vec() -> impl Strategy<Value = Vec<(Index, Index, Index)>> {
+    prop::collection::vec(twist_indices(), 0..MAX_TWISTS)
+}
+
+fn make_twist(ty: PuzzleTypeEnum, (axis, direction, layers): (Index, Index, Index)) -> Twist {
+    Twist {
+        axis: TwistAxis(axis.index(ty.twist_axes().len()) as _),
+        direction: TwistDirection(direction.index(ty.twist_directions().len()) as _),
+        layers: LayerMask(layers.index(ty.all_layers().0 as usize) as u32 + 1),
+    }
+}
+fn make_twists(ty: PuzzleTypeEnum, indices: Vec<(Index, Index, Index)>) -> Vec<Twist> {
+    indices.into_iter().map(|i| make_twist(ty, i)).collect()
+}
+
+/// Applies each of `twists` that is legal and returns the ones that were
+/// applied.
+fn apply_legal_twists(puzzle: &mut Puzzle, twists: &[Twist]) -> Vec<Twist> {
+    twists
+        .iter()
+        .copied()
+        .filter(|&twist| puzzle.is_twist_legal(twist) && puzzle.twist(twist).is_ok())
+        .collect()
+}
+
+/// Returns whether undoing a twist is guaranteed to restore the whole state of
+/// the puzzle. Each twist on the clock sets its pins, so reversing a twist may
+/// leave the pins different than before.
+fn is_reversible(ty: PuzzleTypeEnum) -> bool {
+    ty != PuzzleTypeEnum::Clock
+}
+
+/// Returns whether a permutation is odd.
+fn is_odd_permutation(perm: &[usize]) -> bool {
+    let mut seen = vec![false; perm.len()];
+    let mut cycle_count = 0;
+    for start in 0..perm.len() {
+        if !seen[start] {
+            cycle_count += 1;
+            let mut i = start;
+            while !seen[i] {
+                seen[i] = true;
+                i = perm[i];
+            }
+        }
+    }
+    // A cycle of length `n` is made of `n - 1` transpositions.
+    (perm.len() - cycle_count) % 2 == 1
+}
+
+/// Returns the permutation that takes each piece's original location to its
+/// current location.
+fn location_permutation(puzzle: &Puzzle) -> Vec<usize> {
+    let original = Puzzle::new(puzzle.ty());
+    let original_locations: Vec<Vec<u8>> = (0..puzzle.pieces().len() as _)
+        .map(|i| original.piece_coordinates(Piece(i)).unwrap())
+        .collect();
+    (0..puzzle.pieces().len() as _)
+        .map(|i| {
+            let location = puzzle.piece_coordinates(Piece(i)).unwrap();
+            original_locations
+                .iter()
+                .position(|l| *l == location)
+                .expect("piece is not at any original location")
+        })
+        .collect()
+}
+
+/// Returns the sum of the orientations of the corners of a 3x3x3, modulo 3.
+/// Each corner's orientation is the number of clockwise turns from its U or D
+/// sticker to the U or D face.
+fn corner_orientation_sum(puzzle: &Puzzle) -> usize {
+    // Faces are ordered R, L, U, D, F, B, so each pair shares an axis.
+    let axis_of = |face: Face| face.0 as usize / 2;
+    const Y: usize = 1;
+
+    let mut sum = 0;
+    for piece in (0..puzzle.pieces().len() as _).map(Piece) {
+        let stickers = &puzzle.info(piece).stickers;
+        if stickers.len() != 3 {
+            continue; // not a corner
+        }
+        // Clockwise around a corner is X -> Y -> Z or Z -> Y -> X depending
+        // on the chirality of its location.
+        let location = puzzle.piece_coordinates(piece).unwrap();
+        let is_right_handed = location.iter().filter(|&&coord| coord == 0).count() % 2 == 0;
+        let ud_sticker = stickers
+            .iter()
+            .copied()
+            .find(|&sticker| axis_of(puzzle.info(sticker).color) == Y)
+            .unwrap();
+        let axis = axis_of(puzzle.sticker_facet(ud_sticker).unwrap());
+        sum += if is_right_handed {
+            (axis + 3 - Y) % 3
+        } else {
+            (Y + 3 - axis) % 3
+        };
+    }
+    sum % 3
+}
+
+proptest! {
+    #[test]
+    fn proptest_twist_then_reverse_is_identity(
+        ty in puzzle_type(),
+        setup in twist_indices_vec(),
+        twist in twist_indices(),
+    ) {
+        prop_assume!(is_reversible(ty));
+        let mut puzzle = Puzzle::new(ty);
+        apply_legal_twists(&mut puzzle, &make_twists(ty, setup));
+        let before = puzzle.clone();
+
+        let twist = make_twist(ty, twist);
+        prop_assume!(puzzle.is_twist_legal(twist));
+        prop_assume!(puzzle.twist(twist).is_ok());
+        puzzle.twist(ty.reverse_twist(twist)).unwrap();
+        prop_assert_eq!(puzzle, before);
+    }
+
+    #[test]
+    fn proptest_scramble_then_solution_solves(
+        ty in puzzle_type(),
+        scramble in twist_indices_vec(),
+    ) {
+        prop_assume!(is_reversible(ty));
+        let mut puzzle = Puzzle::new(ty);
+        let scramble = apply_legal_twists(&mut puzzle, &make_twists(ty, scramble));
+
+        let solution: Vec<Twist> =
+            scramble.iter().rev().map(|&twist| ty.reverse_twist(twist)).collect();
+        for twist in solution {
+            puzzle.twist(twist).unwrap();
+        }
+        prop_assert!(puzzle.is_solved());
+        prop_assert_eq!(puzzle, Puzzle::new(ty));
+    }
+
+    #[test]
+    fn proptest_rubiks_3d_parity_is_preserved(scramble in twist_indices_vec()) {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = Puzzle::new(ty);
+        apply_legal_twists(&mut puzzle, &make_twists(ty, scramble));
+
+        // Every quarter turn of a layer of a 3x3x3 is two 4-cycles.
+        prop_assert!(!is_odd_permutation(&location_permutation(&puzzle)));
+        prop_assert_eq!(corner_orientation_sum(&puzzle), 0);
+    }
+
+    #[test]
+    fn proptest_notation_round_trips(ty in puzzle_type(), twist in twist_indices()) {
+        let notation = ty.notation_scheme();
+        let twist = ty.canonicalize_twist(make_twist(ty, twist));
+        let string = notation.twist_to_string(twist);
+        prop_assert_eq!(notation.parse_twist(&string), Ok(twist), "{:?}", string);
+    }
+}
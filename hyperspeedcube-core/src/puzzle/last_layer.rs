@@ -0,0 +1,511 @@
+//! Recognition of last layer cases on 3D cubes, for learning algorithms and
+//! for comparing statistics between cases.
+
+use itertools::Itertools;
+use std::fmt;
+
+use super::{
+    progress, traits::*, Face, LayerMask, Piece, Rubiks3D, Twist, TwistAxis, TwistDirection,
+};
+
+/// Algorithms that solve each PLL case on a 3x3x3, used to recognize the
+/// cases.
+const PLL_ALGORITHMS: &[(&str, &str)] = &[
+    ("Aa", "x R' U R' D2 R U' R' D2 R2 x'"),
+    ("Ab", "x R2 D2 R U R' D2 R U' R x'"),
+    ("E", "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+    ("F", "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+    ("Ga", "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+    ("Gb", "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+    ("Gc", "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+    ("Gd", "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+    ("H", "M2 U M2 U2 M2 U M2"),
+    ("Ja", "R' U L' U2 R U' R' U2 R L"),
+    ("Jb", "R U R' F' R U R' U' R' F R2 U' R'"),
+    (
+        "Na",
+        "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'",
+    ),
+    ("Nb", "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+    ("Ra", "R U' R' U' R U R D R' U' R D' R' U2 R'"),
+    ("Rb", "R2 F R U R U' R' F' R U2 R' U2 R"),
+    ("T", "R U R' U' R' F R2 U' R' U' R U R' F'"),
+    ("Ua", "M2 U M U2 M' U M2"),
+    ("Ub", "M2 U' M U2 M' U' M2"),
+    ("V", "R' U R' U' y R' F' R2 U' R' U R' F R F"),
+    ("Y", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    ("Z", "M' U M2 U M2 U M' U2 M2"),
+];
+
+lazy_static! {
+    /// Permutation of each PLL case on a 3x3x3, as returned by
+    /// `LastLayer::pll_permutation()`.
+    static ref PLL_PERMUTATIONS: Vec<([u8; 8], &'static str)> = PLL_ALGORITHMS
+        .iter()
+        .filter_map(|&(name, algorithm)| Some((pll_permutation_of_algorithm(algorithm)?, name)))
+        .collect();
+}
+
+/// Case of the last layer of a cube on which every other layer is solved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastLayerCase {
+    /// Facet that the last layer is on.
+    pub facet: Face,
+    /// Name of the orientation case, or `None` if the last layer is oriented.
+    pub oll: Option<String>,
+    /// Name of the permutation case, or `None` if the last layer is oriented
+    /// and only needs a turn of the layer to be solved. This is always `None`
+    /// before the last layer is oriented.
+    pub pll: Option<String>,
+}
+impl fmt::Display for LastLayerCase {
+    /// Describes the case as it was when the last layer was reached, so
+    /// "OLL skip" means that the last layer was already oriented.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.oll, &self.pll) {
+            (Some(oll), _) => write!(f, "{oll}"),
+            (None, Some(pll)) => write!(f, "OLL skip, {pll}"),
+            (None, None) => write!(f, "LL skip"),
+        }
+    }
+}
+
+/// Returns the case of the last layer of a cube, or `None` if the cube is
+/// solved or has unsolved pieces outside of a single layer.
+///
+/// Parity is detected on cubes with an even number of layers. Cases are only
+/// named on the 3x3x3.
+pub fn last_layer_case(puzzle: &Rubiks3D) -> Option<LastLayerCase> {
+    let ll = LastLayer::of(puzzle)?;
+    let is_oriented = ll.pieces.iter().all(|&piece| ll.is_oriented(piece));
+
+    let oll = (!is_oriented).then(|| {
+        if ll.has_oll_parity() {
+            "OLL parity".to_owned()
+        } else {
+            ll.oll_name().unwrap_or("OLL").to_owned()
+        }
+    });
+    let pll = (is_oriented && !ll.is_permuted()).then(|| {
+        if ll.has_pll_parity() {
+            "PLL parity".to_owned()
+        } else {
+            match ll.pll_name() {
+                Some(name) => format!("{name} perm"),
+                None => "PLL".to_owned(),
+            }
+        }
+    });
+
+    Some(LastLayerCase {
+        facet: ll.facet,
+        oll,
+        pll,
+    })
+}
+
+/// Applies a solution to a scrambled cube one twist at a time and returns the
+/// case of the last layer when it was first reached, or `None` if it never
+/// was.
+pub fn first_last_layer_case(puzzle: &mut Rubiks3D, solution: &[Twist]) -> Option<LastLayerCase> {
+    for &twist in solution {
+        puzzle.twist(twist).ok()?;
+        if let Some(case) = last_layer_case(puzzle) {
+            return Some(case);
+        }
+    }
+    None
+}
+
+/// Returns the permutation of the PLL case that an algorithm solves.
+fn pll_permutation_of_algorithm(algorithm: &str) -> Option<[u8; 8]> {
+    let mut puzzle = Rubiks3D::new(3);
+    let twists: Vec<Twist> = puzzle
+        .split_twists_string(algorithm)
+        .map(|m| puzzle.notation_scheme().parse_twist(m.as_str()))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    for &twist in twists.iter().rev() {
+        let reverse = puzzle.reverse_twist(twist);
+        puzzle.twist(reverse).ok()?;
+    }
+    LastLayer::of(&puzzle)?.pll_permutation()
+}
+
+/// Pieces of the last layer of a cube.
+struct LastLayer<'a> {
+    puzzle: &'a Rubiks3D,
+    /// Facet that each color belongs on.
+    home_facets: Vec<Face>,
+    /// Facet that the last layer is on.
+    facet: Face,
+    /// Color of the last layer.
+    color: Face,
+    /// Pieces in the last layer.
+    pieces: Vec<Piece>,
+}
+impl<'a> LastLayer<'a> {
+    fn of(puzzle: &'a Rubiks3D) -> Option<Self> {
+        let home_facets = progress::home_facets(puzzle)?;
+        let all_pieces = (0..puzzle.pieces().len() as _).map(Piece);
+        let is_in_layer = |piece: Piece, facet: Face| {
+            puzzle
+                .info(piece)
+                .stickers
+                .iter()
+                .any(|&s| puzzle.sticker_facet(s) == Some(facet))
+        };
+
+        let unsolved = all_pieces
+            .clone()
+            .filter(|&piece| !is_solved(puzzle, &home_facets, piece))
+            .collect_vec();
+        if unsolved.is_empty() {
+            return None;
+        }
+        let facet = (0..puzzle.faces().len() as _)
+            .map(Face)
+            .find(|&facet| unsolved.iter().all(|&piece| is_in_layer(piece, facet)))?;
+        let color = Face(home_facets.iter().position(|&f| f == facet)? as _);
+        let pieces = all_pieces
+            .filter(|&piece| is_in_layer(piece, facet))
+            .collect();
+
+        Some(Self {
+            puzzle,
+            home_facets,
+            facet,
+            color,
+            pieces,
+        })
+    }
+
+    /// Returns whether the last layer color of a piece is on the last layer.
+    fn is_oriented(&self, piece: Piece) -> bool {
+        self.puzzle.info(piece).stickers.iter().any(|&s| {
+            self.puzzle.info(s).color == self.color
+                && self.puzzle.sticker_facet(s) == Some(self.facet)
+        })
+    }
+
+    /// Returns the facet that a piece's last layer color is on.
+    fn last_layer_sticker_facet(&self, piece: Piece) -> Option<Face> {
+        let stickers = &self.puzzle.info(piece).stickers;
+        let sticker = stickers
+            .iter()
+            .find(|&&s| self.puzzle.info(s).color == self.color)?;
+        self.puzzle.sticker_facet(*sticker)
+    }
+
+    /// Returns the facets around the last layer, in the order that a
+    /// clockwise turn of the last layer moves stickers between them.
+    fn sides(&self) -> Option<[Face; 4]> {
+        let mut turned = self.puzzle.clone();
+        turned.twist(self.turn()).ok()?;
+
+        let mut next = vec![None; self.puzzle.faces().len()];
+        for &piece in &self.pieces {
+            for &s in &self.puzzle.info(piece).stickers {
+                let from = self.puzzle.sticker_facet(s)?;
+                if from != self.facet {
+                    next[from.0 as usize] = turned.sticker_facet(s);
+                }
+            }
+        }
+
+        let first = self
+            .pieces
+            .iter()
+            .find_map(|&piece| self.side_facets(piece).first().copied())?;
+        let sides: [Face; 4] = std::iter::successors(Some(first), |f| next[f.0 as usize])
+            .take(4)
+            .collect_vec()
+            .try_into()
+            .ok()?;
+        sides.iter().all_unique().then_some(sides)
+    }
+
+    /// Returns the current facets of the stickers of a piece that are not on
+    /// the last layer.
+    fn side_facets(&self, piece: Piece) -> Vec<Face> {
+        self.puzzle
+            .info(piece)
+            .stickers
+            .iter()
+            .filter_map(|&s| self.puzzle.sticker_facet(s))
+            .filter(|&f| f != self.facet)
+            .collect()
+    }
+
+    /// Returns the index of the corner or edge position around the last layer
+    /// that is adjacent to `facets`. Corner `i` is between sides `i` and
+    /// `i + 1`.
+    fn slot(sides: &[Face; 4], facets: &[Face]) -> Option<usize> {
+        match facets {
+            [a] => sides.iter().position(|f| f == a),
+            [a, b] => (0..4).find(|&i| {
+                let pair = [sides[i], sides[(i + 1) % 4]];
+                pair.contains(a) && pair.contains(b)
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the position of a piece and the position where it belongs,
+    /// and whether it is a corner.
+    fn piece_slots(&self, sides: &[Face; 4], piece: Piece) -> Option<(usize, usize, bool)> {
+        let stickers = &self.puzzle.info(piece).stickers;
+        let current = self.side_facets(piece);
+        let home = stickers
+            .iter()
+            .map(|&s| self.home_facets[self.puzzle.info(s).color.0 as usize])
+            .filter(|&f| f != self.facet)
+            .collect_vec();
+        Some((
+            Self::slot(sides, &current)?,
+            Self::slot(sides, &home)?,
+            current.len() == 2,
+        ))
+    }
+
+    /// Returns a clockwise quarter turn of the last layer.
+    fn turn(&self) -> Twist {
+        Twist {
+            axis: TwistAxis(self.facet.0),
+            direction: TwistDirection(0),
+            layers: LayerMask(1),
+        }
+    }
+
+    /// Returns whether the last layer is solved except for a turn of the
+    /// layer.
+    fn is_permuted(&self) -> bool {
+        let mut puzzle = self.puzzle.clone();
+        for _ in 0..4 {
+            let is_layer_solved = self
+                .pieces
+                .iter()
+                .all(|&piece| is_solved(&puzzle, &self.home_facets, piece));
+            if is_layer_solved {
+                return true;
+            }
+            if puzzle.twist(self.turn()).is_err() {
+                return false;
+            }
+        }
+        false
+    }
+
+    /// Returns whether an odd number of edges are flipped, which can only
+    /// happen on cubes with an even number of layers.
+    fn has_oll_parity(&self) -> bool {
+        let n = self.puzzle.layer_count() as usize;
+        if n % 2 == 1 {
+            return false;
+        }
+        let flipped_wings = self
+            .pieces
+            .iter()
+            .filter(|&&piece| self.puzzle.info(piece).stickers.len() == 2)
+            .filter(|&&piece| !self.is_oriented(piece))
+            .count();
+        (flipped_wings / (n - 2)) % 2 == 1
+    }
+
+    /// Returns whether the corners and edges of an oriented last layer on a
+    /// cube with an even number of layers can't both be solved by a PLL
+    /// algorithm.
+    fn has_pll_parity(&self) -> bool {
+        if self.puzzle.layer_count() % 2 == 1 {
+            return false;
+        }
+        let Some(sides) = self.sides() else {
+            return false;
+        };
+        let mut corners = vec![];
+        let mut edges = vec![];
+        for &piece in &self.pieces {
+            match self.piece_slots(&sides, piece) {
+                Some((slot, home, true)) => corners.push((slot, home)),
+                // Each edge position has several wings, which are assumed to
+                // be paired.
+                Some((slot, home, false)) if !edges.iter().any(|&(s, _)| s == slot) => {
+                    edges.push((slot, home));
+                }
+                _ => (),
+            }
+        }
+        match (is_odd_permutation(&corners), is_odd_permutation(&edges)) {
+            (Some(corners_odd), Some(edges_odd)) => corners_odd != edges_odd,
+            _ => false,
+        }
+    }
+
+    /// Returns how many positions each corner and then each edge is from
+    /// where it belongs, going clockwise around the last layer, in a form that
+    /// doesn't depend on which side the last layer is viewed from or on the
+    /// turn of the last layer. Only supports the 3x3x3.
+    fn pll_permutation(&self) -> Option<[u8; 8]> {
+        if self.puzzle.layer_count() != 3 {
+            return None;
+        }
+        let sides = self.sides()?;
+        let mut offsets = [0_u8; 8];
+        for &piece in &self.pieces {
+            if self.puzzle.info(piece).stickers.len() < 2 {
+                continue; // center
+            }
+            let (slot, home, is_corner) = self.piece_slots(&sides, piece)?;
+            let i = if is_corner { slot } else { 4 + slot };
+            offsets[i] = ((home + 4 - slot) % 4) as u8;
+        }
+
+        // Rotating the view moves the offsets around the layer, and turning
+        // the layer also changes each offset by the same amount.
+        (0..4_usize)
+            .cartesian_product(0..4_u8)
+            .map(|(rotation, turn)| {
+                std::array::from_fn(|i| {
+                    let j = i / 4 * 4 + (i + rotation) % 4;
+                    (offsets[j] + turn) % 4
+                })
+            })
+            .min()
+    }
+
+    /// Returns the name of the PLL case of an oriented last layer on a
+    /// 3x3x3.
+    fn pll_name(&self) -> Option<&'static str> {
+        let permutation = self.pll_permutation()?;
+        PLL_PERMUTATIONS
+            .iter()
+            .find(|(p, _)| *p == permutation)
+            .map(|&(_, name)| name)
+    }
+
+    /// Returns the name of the OLL case of a last layer on a 3x3x3. If the
+    /// edges are not oriented, only their shape is named.
+    fn oll_name(&self) -> Option<&'static str> {
+        if self.puzzle.layer_count() != 3 {
+            return None;
+        }
+        let sides = self.sides()?;
+        let side_index = |f: Face| sides.iter().position(|&s| s == f);
+        let pieces_with_sticker_count = |n: usize| {
+            self.pieces
+                .iter()
+                .copied()
+                .filter(move |&piece| self.puzzle.info(piece).stickers.len() == n)
+        };
+
+        let oriented_edges = pieces_with_sticker_count(2)
+            .filter(|&piece| self.is_oriented(piece))
+            .map(|piece| side_index(*self.side_facets(piece).first()?))
+            .collect::<Option<Vec<usize>>>()?;
+        match oriented_edges[..] {
+            [] => return Some("Dot OLL"),
+            [a, b] if (a + 2) % 4 == b || (b + 2) % 4 == a => return Some("Line OLL"),
+            [_, _] => return Some("L shape OLL"),
+            [_, _, _, _] => (),
+            _ => return None,
+        }
+
+        // For each corner that is not oriented, which side its last layer
+        // color is on.
+        let twisted_corners = pieces_with_sticker_count(3)
+            .filter(|&piece| !self.is_oriented(piece))
+            .map(|piece| {
+                let slot = Self::slot(&sides, &self.side_facets(piece))?;
+                Some((slot, self.last_layer_sticker_facet(piece)?))
+            })
+            .collect::<Option<Vec<(usize, Face)>>>()?;
+        let is_on_second_side = |&(slot, facet): &(usize, Face)| facet == sides[(slot + 1) % 4];
+        match twisted_corners[..] {
+            [(a, _), (b, _)] if (a + 2) % 4 == b || (b + 2) % 4 == a => Some("Bowtie OLL"),
+            [(_, a), (_, b)] if a == b => Some("Headlights OLL"),
+            [_, _] => Some("Chameleon OLL"),
+            [_, _, _] if twisted_corners.iter().all(is_on_second_side) => Some("Sune"),
+            [_, _, _] => Some("Antisune"),
+            [_, _, _, _] => match twisted_corners.iter().map(|(_, f)| f).unique().count() {
+                2 => Some("H OLL"),
+                _ => Some("Pi OLL"),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Returns whether every sticker of a piece is on the facet that its color
+/// belongs on.
+fn is_solved(puzzle: &Rubiks3D, home_facets: &[Face], piece: Piece) -> bool {
+    puzzle
+        .info(piece)
+        .stickers
+        .iter()
+        .all(|&s| puzzle.sticker_facet(s) == Some(home_facets[puzzle.info(s).color.0 as usize]))
+}
+
+/// Returns whether a permutation, given as the position of each piece and
+/// the position where it belongs, is odd, or `None` if it is not a
+/// permutation.
+fn is_odd_permutation(mapping: &[(usize, usize)]) -> Option<bool> {
+    let mut visited = vec![false; mapping.len()];
+    let mut cycles = 0;
+    for start in 0..mapping.len() {
+        if visited[start] {
+            continue;
+        }
+        cycles += 1;
+        let mut i = start;
+        while !visited[i] {
+            visited[i] = true;
+            let home = mapping[i].1;
+            i = mapping.iter().position(|&(slot, _)| slot == home)?;
+        }
+    }
+    Some((mapping.len() - cycles) % 2 == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(p: &mut Rubiks3D, twists: &str) {
+        for s in twists.split_whitespace() {
+            let twist = p.notation_scheme().parse_twist(s).unwrap();
+            p.twist(twist).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_last_layer_case() {
+        let mut p = Rubiks3D::new(3);
+        assert_eq!(last_layer_case(&p), None);
+
+        apply(&mut p, "U");
+        assert_eq!(last_layer_case(&p).unwrap().to_string(), "LL skip");
+
+        // Every PLL case is recognized, no matter how the last layer is
+        // turned before and after.
+        assert_eq!(PLL_PERMUTATIONS.len(), PLL_ALGORITHMS.len());
+        assert!(PLL_PERMUTATIONS.iter().map(|(p, _)| p).all_unique());
+        let mut p = Rubiks3D::new(3);
+        apply(&mut p, "U R U R' U' R' F R2 U' R' U' R U R' F' U2");
+        let case = last_layer_case(&p).unwrap();
+        assert_eq!(case.pll.as_deref(), Some("T perm"));
+        assert_eq!(case.to_string(), "OLL skip, T perm");
+
+        // The inverse of F R U R' U' F' leaves two opposite edges oriented.
+        let mut p = Rubiks3D::new(3);
+        apply(&mut p, "F U R U' R' F'");
+        assert_eq!(
+            last_layer_case(&p).unwrap().oll.as_deref(),
+            Some("Line OLL")
+        );
+
+        // Cases are only named on the 3x3x3.
+        let mut p = Rubiks3D::new(4);
+        apply(&mut p, "R U R' U' R' F R2 U' R' U' R U R' F'");
+        assert_eq!(last_layer_case(&p).unwrap().pll.as_deref(), Some("PLL"));
+    }
+}
@@ -0,0 +1,270 @@
+//! Guessing which method was used to solve a 3x3x3 from its solution, for
+//! comparing statistics between methods.
+
+use std::fmt;
+
+use super::{traits::*, Face, LayerMask, Piece, Rubiks3D, Twist, TwistAxis, TwistDirection};
+
+/// Method for solving a 3x3x3.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SolveMethod {
+    /// Cross, first two layers, then orientation and permutation of the last
+    /// layer.
+    Cfop,
+    /// Two 1x2x3 blocks on opposite sides, corners of the last layer, then the
+    /// remaining edges using the M slice.
+    Roux,
+    /// Edge orientation and a line on the bottom, first two layers without
+    /// turning the front or back, then the last layer.
+    Zz,
+}
+impl fmt::Display for SolveMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveMethod::Cfop => write!(f, "CFOP"),
+            SolveMethod::Roux => write!(f, "Roux"),
+            SolveMethod::Zz => write!(f, "ZZ"),
+        }
+    }
+}
+
+/// Applies a solution to a scrambled 3x3x3 one twist at a time and guesses
+/// which method was used, based on which steps of each method were finished
+/// first. Returns `None` for other puzzle sizes or if the solution doesn't look
+/// like any known method.
+pub fn detect_method(puzzle: &mut Rubiks3D, solution: &[Twist]) -> Option<SolveMethod> {
+    if puzzle.layer_count() != 3 {
+        return None;
+    }
+
+    let mut cross_done = None;
+    let mut eo_line_done = None;
+    for (i, &twist) in solution.iter().enumerate() {
+        puzzle.twist(twist).ok()?;
+        let cube = Cube::of(puzzle)?;
+
+        if cube.has_roux_blocks() {
+            return Some(SolveMethod::Roux);
+        }
+        if eo_line_done.is_none() && cube.has_eo_line() {
+            eo_line_done = Some(i);
+        }
+        if cross_done.is_none() && cube.has_cross() {
+            cross_done = Some(i);
+        }
+        if cube.has_first_two_layers() {
+            return match (eo_line_done, cross_done) {
+                (Some(eo_line), Some(cross)) if eo_line < cross => Some(SolveMethod::Zz),
+                (_, Some(_)) => Some(SolveMethod::Cfop),
+                _ => None,
+            };
+        }
+    }
+    None
+}
+
+/// State of a 3x3x3, with each piece's solved state relative to the centers.
+struct Cube<'a> {
+    puzzle: &'a Rubiks3D,
+    /// Facet that the center of each color is on.
+    home_facets: Vec<Face>,
+}
+impl<'a> Cube<'a> {
+    fn of(puzzle: &'a Rubiks3D) -> Option<Self> {
+        let mut home_facets = vec![Face(0); puzzle.faces().len()];
+        for piece in puzzle.pieces() {
+            if let [sticker] = piece.stickers[..] {
+                let color = puzzle.info(sticker).color;
+                home_facets[color.0 as usize] = puzzle.sticker_facet(sticker)?;
+            }
+        }
+        Some(Self {
+            puzzle,
+            home_facets,
+        })
+    }
+
+    fn all_facets(&self) -> impl Iterator<Item = Face> {
+        (0..self.puzzle.faces().len() as _).map(Face)
+    }
+    fn opposite(&self, facet: Face) -> Face {
+        let axis = self.puzzle.opposite_twist_axis(TwistAxis(facet.0));
+        Face(axis.map_or(facet.0, |axis| axis.0))
+    }
+    /// Returns the facets perpendicular to a facet.
+    fn perpendicular(&self, facet: Face) -> Vec<Face> {
+        let opposite = self.opposite(facet);
+        self.all_facets()
+            .filter(|&f| f != facet && f != opposite)
+            .collect()
+    }
+
+    /// Returns the facets that a piece's stickers are currently on.
+    fn facets(&self, piece: Piece) -> Vec<Face> {
+        self.puzzle
+            .info(piece)
+            .stickers
+            .iter()
+            .filter_map(|&s| self.puzzle.sticker_facet(s))
+            .collect()
+    }
+    /// Returns the pieces with stickers on a facet.
+    fn layer(&self, facet: Face) -> impl '_ + Iterator<Item = Piece> {
+        (0..self.puzzle.pieces().len() as _)
+            .map(Piece)
+            .filter(move |&piece| self.facets(piece).contains(&facet))
+    }
+    fn edge(&self, a: Face, b: Face) -> Option<Piece> {
+        self.layer(a).find(|&piece| {
+            let facets = self.facets(piece);
+            facets.len() == 2 && facets.contains(&b)
+        })
+    }
+    fn is_solved(&self, piece: Piece) -> bool {
+        self.puzzle.info(piece).stickers.iter().all(|&s| {
+            let home = self.home_facets[self.puzzle.info(s).color.0 as usize];
+            self.puzzle.sticker_facet(s) == Some(home)
+        })
+    }
+    fn is_edge_solved(&self, a: Face, b: Face) -> bool {
+        self.edge(a, b).map_or(false, |piece| self.is_solved(piece))
+    }
+
+    /// Returns whether the center and edges of some face are solved.
+    fn has_cross(&self) -> bool {
+        self.all_facets().any(|facet| {
+            self.layer(facet)
+                .filter(|&piece| self.facets(piece).len() <= 2)
+                .all(|piece| self.is_solved(piece))
+        })
+    }
+
+    /// Returns whether every piece outside of one layer is solved.
+    fn has_first_two_layers(&self) -> bool {
+        let unsolved = (0..self.puzzle.pieces().len() as _)
+            .map(Piece)
+            .filter(|&piece| !self.is_solved(piece))
+            .collect::<Vec<_>>();
+        unsolved.is_empty()
+            || self
+                .all_facets()
+                .any(|facet| unsolved.iter().all(|p| self.facets(*p).contains(&facet)))
+    }
+
+    /// Returns whether there are solved 1x2x3 blocks on opposite sides while
+    /// the edges between them on the bottom are not both solved, which
+    /// happens in Roux but not in methods that start with a cross or line.
+    fn has_roux_blocks(&self) -> bool {
+        for left in self.all_facets() {
+            // The slice between the blocks may be turned relative to them.
+            let slice = Twist {
+                axis: TwistAxis(left.0),
+                direction: TwistDirection(0),
+                layers: LayerMask(0b010),
+            };
+            let mut puzzle = self.puzzle.clone();
+            for _ in 0..4 {
+                if Cube::of(&puzzle).map_or(false, |cube| cube.has_roux_blocks_beside(left)) {
+                    return true;
+                }
+                if puzzle.twist(slice).is_err() {
+                    break;
+                }
+            }
+        }
+        false
+    }
+    fn has_roux_blocks_beside(&self, left: Face) -> bool {
+        let right = self.opposite(left);
+        self.perpendicular(left).into_iter().any(|top| {
+            let bottom = self.opposite(top);
+            let blocks_solved = self
+                .layer(left)
+                .chain(self.layer(right))
+                .filter(|&piece| !self.facets(piece).contains(&top))
+                .all(|piece| self.is_solved(piece));
+            let line_solved = self
+                .perpendicular(left)
+                .into_iter()
+                .filter(|&f| f != top && f != bottom)
+                .all(|front| self.is_edge_solved(bottom, front));
+            blocks_solved && !line_solved
+        })
+    }
+
+    /// Returns whether every edge is oriented relative to some axis and the
+    /// two edges between the ends of that axis and some other face are
+    /// solved.
+    fn has_eo_line(&self) -> bool {
+        self.all_facets().any(|front| {
+            let back = self.opposite(front);
+            let is_line_solved =
+                |bottom| self.is_edge_solved(bottom, front) && self.is_edge_solved(bottom, back);
+            self.is_eo_solved(front) && self.perpendicular(front).into_iter().any(is_line_solved)
+        })
+    }
+
+    /// Returns whether every edge can be solved without quarter turns of the
+    /// front or back face.
+    fn is_eo_solved(&self, front: Face) -> bool {
+        let Some(&up) = self.perpendicular(front).first() else {
+            return false;
+        };
+        let (back, down) = (self.opposite(front), self.opposite(up));
+        // Each edge is checked using its sticker that belongs on the top or
+        // bottom, or else the one that belongs on the front or back.
+        let priority = |facet: Face| match facet {
+            f if f == up || f == down => 0,
+            f if f == front || f == back => 1,
+            _ => 2,
+        };
+
+        (0..self.puzzle.pieces().len() as _)
+            .map(Piece)
+            .filter(|&piece| self.facets(piece).len() == 2)
+            .all(|piece| {
+                let stickers = &self.puzzle.info(piece).stickers;
+                let reference_sticker = stickers.iter().min_by_key(|&&s| {
+                    priority(self.home_facets[self.puzzle.info(s).color.0 as usize])
+                });
+                let reference_facet = self.facets(piece).into_iter().min_by_key(|&f| priority(f));
+                match (reference_sticker, reference_facet) {
+                    (Some(&s), Some(f)) => self.puzzle.sticker_facet(s) == Some(f),
+                    _ => false,
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(scramble: &str, solution: &str) -> Option<SolveMethod> {
+        let mut p = Rubiks3D::new(3);
+        let parse = |s: &str| {
+            s.split_whitespace()
+                .map(|t| p.notation_scheme().parse_twist(t).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let (scramble, solution) = (parse(scramble), parse(solution));
+        for twist in scramble {
+            p.twist(twist).unwrap();
+        }
+        detect_method(&mut p, &solution)
+    }
+
+    #[test]
+    fn test_detect_method() {
+        assert_eq!(detect("R", "R'"), Some(SolveMethod::Cfop));
+        // After the first twist, the edge in DB is unsolved but both blocks
+        // are solved.
+        assert_eq!(detect("M U2 M' U", "U' M U2 M'"), Some(SolveMethod::Roux));
+        // After the first twist, every edge is oriented and UF and UB are
+        // solved, but no face has a cross until the next twist.
+        assert_eq!(detect("L2 R2 U2", "U2 R2 L2"), Some(SolveMethod::Zz));
+
+        assert_eq!(detect("R", ""), None);
+        assert_eq!(detect_method(&mut Rubiks3D::new(4), &[]), None);
+    }
+}
@@ -0,0 +1,223 @@
+//! Estimates of how close a puzzle is to being solved.
+
+use serde::{Deserialize, Serialize};
+
+use super::{traits::*, Face, Piece, Sticker, Twist};
+
+/// Number of solved pieces of each type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of solved pieces and total number of pieces, for each piece
+    /// type.
+    pub by_piece_type: Vec<(usize, usize)>,
+}
+impl Progress {
+    /// Counts the solved pieces in a puzzle, or returns `None` if the puzzle
+    /// doesn't track which face each sticker is on.
+    ///
+    /// Each color is assumed to belong on whichever face has the most
+    /// stickers of that color, so that rotating the whole puzzle doesn't
+    /// change its progress.
+    pub fn of(puzzle: &impl PuzzleState) -> Option<Self> {
+        let home_facets = home_facets(puzzle)?;
+
+        let mut by_piece_type = vec![(0, 0); puzzle.piece_types().len()];
+        for piece in (0..puzzle.pieces().len() as _).map(Piece) {
+            let piece_info = puzzle.info(piece);
+            let is_solved = piece_info.stickers.iter().all(|&s| {
+                puzzle.sticker_facet(s) == Some(home_facets[puzzle.info(s).color.0 as usize])
+            });
+            let (solved, total) = &mut by_piece_type[piece_info.piece_type.0 as usize];
+            *solved += is_solved as usize;
+            *total += 1;
+        }
+        Some(Self { by_piece_type })
+    }
+
+    /// Returns the number of solved pieces.
+    pub fn solved_pieces(&self) -> usize {
+        self.by_piece_type.iter().map(|(solved, _)| solved).sum()
+    }
+    /// Returns the total number of pieces.
+    pub fn total_pieces(&self) -> usize {
+        self.by_piece_type.iter().map(|(_, total)| total).sum()
+    }
+    /// Returns the fraction of pieces that are solved, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f32 {
+        match self.total_pieces() {
+            0 => 1.0,
+            total => self.solved_pieces() as f32 / total as f32,
+        }
+    }
+}
+
+/// Returns the facet that each color belongs on, which is whichever facet has
+/// the most stickers of that color, or `None` if the puzzle doesn't track which
+/// face each sticker is on.
+pub(super) fn home_facets(puzzle: &impl PuzzleState) -> Option<Vec<Face>> {
+    let face_count = puzzle.faces().len();
+
+    // Count the stickers of each color on each face.
+    let mut counts = vec![vec![0_usize; face_count]; face_count];
+    for sticker in (0..puzzle.stickers().len() as _).map(Sticker) {
+        let facet = puzzle.sticker_facet(sticker)?;
+        counts[puzzle.info(sticker).color.0 as usize][facet.0 as usize] += 1;
+    }
+    Some(
+        counts
+            .iter()
+            .map(|counts_per_facet| {
+                let i = (0..face_count)
+                    .max_by_key(|&i| counts_per_facet[i])
+                    .unwrap_or(0);
+                Face(i as _)
+            })
+            .collect(),
+    )
+}
+
+/// Number of twists spent on each piece type during a solve, for seeing which
+/// parts of a solve took the longest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PieceTypeBreakdown {
+    /// For each piece type, the number of twists made while it still had
+    /// unsolved pieces.
+    pub twists_while_unsolved: Vec<usize>,
+    /// For each piece type, the number of twists after which all of its
+    /// pieces were solved for the rest of the solve.
+    pub finished_after: Vec<usize>,
+    /// Total number of twists in the solution.
+    pub total_twists: usize,
+}
+impl PieceTypeBreakdown {
+    /// Applies a solution to a scrambled puzzle one twist at a time and
+    /// records when each piece type was solved.
+    pub fn of_solve(
+        puzzle: &mut impl PuzzleState,
+        solution: &[Twist],
+    ) -> Result<Self, &'static str> {
+        let solved_types = |puzzle: &_| -> Result<Vec<bool>, &'static str> {
+            let progress = Progress::of(puzzle).ok_or("Puzzle does not track solved pieces")?;
+            Ok(progress
+                .by_piece_type
+                .iter()
+                .map(|(solved, total)| solved == total)
+                .collect())
+        };
+
+        let mut was_solved = solved_types(&*puzzle)?;
+        let mut twists_while_unsolved = vec![0; was_solved.len()];
+        let mut finished_after = vec![0; was_solved.len()];
+        for (i, &twist) in solution.iter().enumerate() {
+            puzzle.twist(twist)?;
+            let is_solved = solved_types(&*puzzle)?;
+            for (t, (&was, &is)) in was_solved.iter().zip(&is_solved).enumerate() {
+                if !was {
+                    twists_while_unsolved[t] += 1;
+                    if is {
+                        finished_after[t] = i + 1;
+                    }
+                }
+            }
+            was_solved = is_solved;
+        }
+
+        Ok(Self {
+            twists_while_unsolved,
+            finished_after,
+            total_twists: solution.len(),
+        })
+    }
+
+    /// Returns each piece type that was unsolved at some point, in the order
+    /// they were finished, with the number of twists made since the previous
+    /// one was finished.
+    pub fn phases(&self) -> Vec<(usize, usize)> {
+        let mut piece_types = (0..self.finished_after.len())
+            .filter(|&t| self.twists_while_unsolved[t] > 0)
+            .collect::<Vec<usize>>();
+        piece_types.sort_by_key(|&t| self.finished_after[t]);
+
+        let mut last = 0;
+        piece_types
+            .into_iter()
+            .map(|t| {
+                let twists = self.finished_after[t].saturating_sub(last);
+                last = last.max(self.finished_after[t]);
+                (t, twists)
+            })
+            .collect()
+    }
+}
+
+/// Progress on a solve at some point in time, saved in the log file so that
+/// long solves can be tracked across sessions.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ProgressSnapshot {
+    /// Unix timestamp, in seconds.
+    pub time: i64,
+    /// Number of twists made since the scramble, in ETM.
+    pub twist_count: usize,
+    pub solved_pieces: usize,
+    pub total_pieces: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::Rubiks3D;
+
+    #[test]
+    fn test_progress() {
+        let mut p = Rubiks3D::new(3);
+        let twist = |p: &mut Rubiks3D, s| {
+            let twist = p.notation_scheme().parse_twist(s).unwrap();
+            p.twist(twist).unwrap();
+        };
+
+        let progress = Progress::of(&p).unwrap();
+        assert_eq!(progress.total_pieces(), p.pieces().len());
+        assert_eq!(progress.fraction(), 1.0);
+
+        twist(&mut p, "R");
+        let progress = Progress::of(&p).unwrap();
+        assert_eq!(progress.total_pieces() - progress.solved_pieces(), 8);
+
+        // Rotating the whole puzzle doesn't change progress.
+        twist(&mut p, "x");
+        twist(&mut p, "y");
+        assert_eq!(Progress::of(&p).unwrap(), progress);
+    }
+
+    #[test]
+    fn test_piece_type_breakdown() {
+        let mut p = Rubiks3D::new(3);
+        let parse = |p: &Rubiks3D, s: &str| {
+            s.split_whitespace()
+                .map(|t| p.notation_scheme().parse_twist(t).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let piece_type =
+            |p: &Rubiks3D, name: &str| p.piece_types().iter().position(|t| t.name == name).unwrap();
+        let corners = piece_type(&p, "corner");
+        let edges = piece_type(&p, "edge");
+        let centers = piece_type(&p, "center");
+
+        for twist in parse(&p, "R U") {
+            p.twist(twist).unwrap();
+        }
+        let solution = parse(&p, "U' R'");
+        let breakdown = PieceTypeBreakdown::of_solve(&mut p, &solution).unwrap();
+        assert_eq!(breakdown.total_twists, 2);
+        assert_eq!(breakdown.twists_while_unsolved[corners], 2);
+        assert_eq!(breakdown.twists_while_unsolved[edges], 2);
+        assert_eq!(breakdown.twists_while_unsolved[centers], 0);
+        assert_eq!(breakdown.finished_after[corners], 2);
+        assert_eq!(breakdown.finished_after[centers], 0);
+
+        // Centers were never unsolved, so they aren't a phase.
+        let phases = breakdown.phases();
+        assert_eq!(phases.len(), 2);
+        assert_eq!(phases.iter().map(|(_, twists)| twists).sum::<usize>(), 2);
+    }
+}
@@ -0,0 +1,239 @@
+//! Queries that pick out pieces of a puzzle by their colors, position, type,
+//! and state.
+//!
+//! A query is a list of terms, which are combined with `and` (the default
+//! when no operator is given), `or`, and `not`, and grouped with parentheses.
+//! The terms are:
+//!
+//! - a face symbol or name (e.g., `R` or `right`), matching pieces with a
+//!   sticker of that face's color
+//! - a face symbol or name prefixed with `@` (e.g., `@U`), matching pieces
+//!   with a sticker currently on that face
+//! - a piece type, optionally plural (e.g., `corners` or `x-center`), without
+//!   spaces or parentheses
+//! - a number of stickers followed by `c` (e.g., `3c`)
+//! - `solved` or `unsolved`, depending on whether each sticker of the piece
+//!   is on the face of its color
+//!
+//! For example, `R @U unsolved` matches unsolved pieces with a sticker of
+//! R's color and a sticker on the U face, and `(corners or edges) and not L`
+//! matches all corners and edges without a left sticker.
+
+use bitvec::vec::BitVec;
+use itertools::Itertools;
+
+use super::{traits::*, Face, Piece, PieceType};
+
+/// Query that matches some pieces of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PieceQuery {
+    /// Pieces with a sticker of the color of a face.
+    Color(Face),
+    /// Pieces with a sticker currently on a face.
+    On(Face),
+    /// Pieces of a type.
+    Type(PieceType),
+    /// Pieces with a number of stickers.
+    StickerCount(usize),
+    /// Pieces whose stickers are all on the faces of their colors.
+    Solved,
+
+    Not(Box<PieceQuery>),
+    And(Box<PieceQuery>, Box<PieceQuery>),
+    Or(Box<PieceQuery>, Box<PieceQuery>),
+}
+impl PieceQuery {
+    /// Parses a query for a puzzle type.
+    pub fn parse(ty: &impl PuzzleType, s: &str) -> Result<Self, String> {
+        let tokens = s
+            .replace('(', " ( ")
+            .replace(')', " ) ")
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect_vec();
+        if tokens.is_empty() {
+            return Err("empty query".to_owned());
+        }
+
+        let mut parser = Parser {
+            ty,
+            tokens: &tokens,
+            pos: 0,
+        };
+        let ret = parser.parse_or()?;
+        match parser.peek() {
+            Some(token) => Err(format!("unexpected {token:?}")),
+            None => Ok(ret),
+        }
+    }
+
+    /// Returns whether a piece matches the query.
+    pub fn matches(&self, puzzle: &impl PuzzleState, piece: Piece) -> bool {
+        let stickers = &puzzle.info(piece).stickers;
+        match self {
+            Self::Color(face) => stickers.iter().any(|&s| puzzle.info(s).color == *face),
+            Self::On(face) => stickers
+                .iter()
+                .any(|&s| puzzle.sticker_facet(s) == Some(*face)),
+            Self::Type(piece_type) => puzzle.info(piece).piece_type == *piece_type,
+            Self::StickerCount(n) => stickers.len() == *n,
+            Self::Solved => puzzle.is_piece_solved(piece),
+
+            Self::Not(q) => !q.matches(puzzle, piece),
+            Self::And(a, b) => a.matches(puzzle, piece) && b.matches(puzzle, piece),
+            Self::Or(a, b) => a.matches(puzzle, piece) || b.matches(puzzle, piece),
+        }
+    }
+
+    /// Returns the set of pieces that match the query.
+    pub fn matching_pieces(&self, puzzle: &impl PuzzleState) -> BitVec {
+        (0..puzzle.pieces().len() as _)
+            .map(|i| self.matches(puzzle, Piece(i)))
+            .collect()
+    }
+}
+
+struct Parser<'a, P> {
+    ty: &'a P,
+    tokens: &'a [String],
+    pos: usize,
+}
+impl<P: PuzzleType> Parser<'_, P> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|s| s.as_str())
+    }
+    fn next(&mut self) -> Option<&str> {
+        self.pos += 1;
+        self.tokens.get(self.pos - 1).map(|s| s.as_str())
+    }
+
+    fn parse_or(&mut self) -> Result<PieceQuery, String> {
+        let mut ret = self.parse_and()?;
+        while self.peek() == Some("or") {
+            self.next();
+            ret = PieceQuery::Or(Box::new(ret), Box::new(self.parse_and()?));
+        }
+        Ok(ret)
+    }
+    fn parse_and(&mut self) -> Result<PieceQuery, String> {
+        let mut ret = self.parse_not()?;
+        loop {
+            match self.peek() {
+                None | Some("or" | ")") => return Ok(ret),
+                Some("and") => {
+                    self.next();
+                }
+                // Terms next to each other are implicitly combined with `and`.
+                Some(_) => (),
+            }
+            ret = PieceQuery::And(Box::new(ret), Box::new(self.parse_not()?));
+        }
+    }
+    fn parse_not(&mut self) -> Result<PieceQuery, String> {
+        if self.peek() == Some("not") {
+            self.next();
+            Ok(PieceQuery::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_atom()
+        }
+    }
+    fn parse_atom(&mut self) -> Result<PieceQuery, String> {
+        match self.next() {
+            None => Err("unexpected end of query".to_owned()),
+            Some("(") => {
+                let ret = self.parse_or()?;
+                match self.next() {
+                    Some(")") => Ok(ret),
+                    _ => Err("missing closing parenthesis".to_owned()),
+                }
+            }
+            Some(token) => {
+                let token = token.to_owned();
+                self.parse_term(&token)
+            }
+        }
+    }
+    fn parse_term(&self, token: &str) -> Result<PieceQuery, String> {
+        match token {
+            "solved" => return Ok(PieceQuery::Solved),
+            "unsolved" => return Ok(PieceQuery::Not(Box::new(PieceQuery::Solved))),
+            _ => (),
+        }
+        if let Some(face_name) = token.strip_prefix('@') {
+            return match self.face(face_name) {
+                Some(face) => Ok(PieceQuery::On(face)),
+                None => Err(format!("unknown face {face_name:?}")),
+            };
+        }
+        if let Some(Ok(n)) = token.strip_suffix('c').map(str::parse) {
+            return Ok(PieceQuery::StickerCount(n));
+        }
+        if let Some(face) = self.face(token) {
+            return Ok(PieceQuery::Color(face));
+        }
+        if let Some(piece_type) = self.piece_type(token) {
+            return Ok(PieceQuery::Type(piece_type));
+        }
+        Err(format!("unknown term {token:?}"))
+    }
+
+    fn face(&self, name: &str) -> Option<Face> {
+        self.ty
+            .faces()
+            .iter()
+            .position(|f| f.symbol.eq_ignore_ascii_case(name) || f.name.eq_ignore_ascii_case(name))
+            .map(|i| Face(i as _))
+    }
+    fn piece_type(&self, name: &str) -> Option<PieceType> {
+        self.ty
+            .piece_types()
+            .iter()
+            .position(|t| {
+                let t = t.name.replace([' ', '(', ')'], "").to_lowercase();
+                name == t || name.strip_suffix('s') == Some(t.as_str())
+            })
+            .map(|i| PieceType(i as _))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::Rubiks3D;
+
+    fn count(p: &Rubiks3D, query: &str) -> usize {
+        PieceQuery::parse(p, query)
+            .unwrap()
+            .matching_pieces(p)
+            .count_ones()
+    }
+
+    #[test]
+    fn test_piece_query() {
+        let mut p = Rubiks3D::new(3);
+        assert_eq!(count(&p, "corners"), 8);
+        assert_eq!(count(&p, "2c"), 12);
+        assert_eq!(count(&p, "R U"), 3);
+        assert_eq!(count(&p, "R and U"), 3);
+        assert_eq!(count(&p, "R or L"), 18);
+        assert_eq!(count(&p, "not (R or L) and edge"), 4);
+        assert_eq!(count(&p, "solved"), p.pieces().len());
+
+        let twist = p.notation_scheme().parse_twist("R").unwrap();
+        p.twist(twist).unwrap();
+        assert_eq!(count(&p, "unsolved"), 8);
+        assert_eq!(count(&p, "@R"), 9);
+        assert_eq!(count(&p, "F @U"), 5);
+        assert_eq!(count(&p, "F @U corners"), 3);
+    }
+
+    #[test]
+    fn test_piece_query_errors() {
+        let p = Rubiks3D::new(3);
+        assert!(PieceQuery::parse(&p, "").is_err());
+        assert!(PieceQuery::parse(&p, "frobnicate").is_err());
+        assert!(PieceQuery::parse(&p, "(R or U").is_err());
+        assert!(PieceQuery::parse(&p, "R )").is_err());
+        assert!(PieceQuery::parse(&p, "@X").is_err());
+    }
+}
@@ -17,6 +17,10 @@ pub const MIN_LAYER_COUNT: u8 = 1;
 pub const MAX_LAYER_COUNT: u8 = 9;
 pub const LAYER_COUNT_RANGE: RangeInclusive<u8> = MIN_LAYER_COUNT..=MAX_LAYER_COUNT;
 
+/// Fraction of a sticker's half-width that is cut off the corners of center
+/// stickers to point them in one direction, when supercube mode is enabled.
+const SUPERCUBE_MARKER_CUT: f32 = 0.5;
+
 pub(super) fn deserialize_layer_count<'de, D>(deserializer: D) -> Result<u8, D::Error>
 where
     D: Deserializer<'de>,
@@ -306,7 +310,11 @@ impl PuzzleType for Rubiks3DDescription {
             CCW180 => CW180.into(),
         }
     }
-    fn chain_twist_directions(&self, dirs: &[TwistDirection]) -> Option<TwistDirection> {
+    fn chain_twist_directions(
+        &self,
+        _axis: TwistAxis,
+        dirs: &[TwistDirection],
+    ) -> Option<TwistDirection> {
         use TwistDirectionEnum::*;
 
         let total: i32 = dirs
@@ -358,7 +366,8 @@ impl IndexMut<Piece> for Rubiks3D {
 }
 impl PuzzleState for Rubiks3D {
     fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
-        for piece in self.pieces_affected_by_twist(twist) {
+        let pieces = self.pieces_affected_by_twist(twist);
+        for &piece in &pieces {
             self[piece] = self[piece].twist(twist.axis.into(), twist.direction.into());
         }
         Ok(())
@@ -442,6 +451,10 @@ impl PuzzleState for Rubiks3D {
         sticker: Sticker,
         p: StickerGeometryParams,
     ) -> Option<StickerGeometry> {
+        if p.net {
+            return Some(self.net_sticker_geometry(sticker, p));
+        }
+
         let piece = self.info(sticker).piece;
         let face = self.sticker_face(sticker);
 
@@ -468,14 +481,33 @@ impl PuzzleState for Rubiks3D {
             v_span_axis.unit_vec3() * p.sticker_scale,
         );
 
-        // Decide what twists should happen when the sticker is clicked.
-        let cw_twist = Twist {
-            axis: face.into(),
-            direction: TwistDirectionEnum::CW90.into(),
-            layers: LayerMask::default(),
-        };
-        let ccw_twist = self.reverse_twist(cw_twist);
-        let recenter = self.make_recenter_twist(face.into()).ok();
+        if p.supercube && self.info(piece).stickers.len() == 1 {
+            // Point center stickers in the direction that their original `v`
+            // axis now faces, so that their orientation is visible.
+            let original_face: FaceEnum = self.info(sticker).color.into();
+            let marker = self[piece][original_face.parallel_axes()[1]];
+            let (edge, direction) = match (marker.axis() == u_span_axis, marker.sign()) {
+                (false, Sign::Neg) => (0, -v),
+                (true, Sign::Pos) => (1, u),
+                (false, Sign::Pos) => (2, v),
+                (true, Sign::Neg) => (3, -u),
+            };
+            let mut verts = vec![
+                center - u - v,
+                center + u - v,
+                center + u + v,
+                center - u + v,
+            ];
+            verts[edge] -= direction * SUPERCUBE_MARKER_CUT;
+            verts[(edge + 1) % 4] -= direction * SUPERCUBE_MARKER_CUT;
+            verts.insert(edge + 1, center + direction);
+            return Some(StickerGeometry::new_double_polygon(
+                verts,
+                self.click_twists(face),
+                p.show_frontfaces,
+                p.show_backfaces,
+            ));
+        }
 
         Some(StickerGeometry::new_double_quad(
             [
@@ -484,15 +516,17 @@ impl PuzzleState for Rubiks3D {
                 center + u - v,
                 center + u + v,
             ],
-            ClickTwists {
-                cw: Some(cw_twist),
-                ccw: Some(ccw_twist),
-                recenter,
-            },
+            self.click_twists(face),
             p.show_frontfaces,
             p.show_backfaces,
         ))
     }
+    fn sticker_facet(&self, sticker: Sticker) -> Option<Face> {
+        Some(self.sticker_face(sticker).into())
+    }
+    fn piece_coordinates(&self, piece: Piece) -> Option<Vec<u8>> {
+        Some(self.piece_location(piece).to_vec())
+    }
 
     fn is_solved(&self) -> bool {
         let mut color_per_facet = vec![None; self.faces().len()];
@@ -507,6 +541,22 @@ impl PuzzleState for Rubiks3D {
         }
         true
     }
+
+    fn is_solved_in_original_orientation(&self) -> bool {
+        (0..self.stickers().len() as _)
+            .map(Sticker)
+            .all(|sticker| self.sticker_face(sticker) == FaceEnum::from(self.info(sticker).color))
+    }
+
+    fn is_solved_as_supercube(&self, definition: SolvedStateDefinition) -> bool {
+        match definition {
+            SolvedStateDefinition::AnyOrientation => self.piece_states.iter().all_equal(),
+            SolvedStateDefinition::OriginalOrientation => self
+                .piece_states
+                .iter()
+                .all(|&state| state == PieceState::default()),
+        }
+    }
 }
 #[delegate_to_methods]
 #[delegate(PuzzleType, target_ref = "desc")]
@@ -544,6 +594,81 @@ impl Rubiks3D {
         }
     }
 
+    /// Returns the stickers of the puzzle unfolded into a cross-shaped net,
+    /// `4 * layer_count` stickers wide and `3 * layer_count` stickers tall,
+    /// with U above and D below F, and L, F, R, B in a row.
+    pub fn net(&self) -> Vec<NetSticker> {
+        (0..self.stickers().len())
+            .map(|i| {
+                let sticker = Sticker(i as _);
+                let (x, y) = self.net_position(sticker);
+                let color = self.info(sticker).color;
+                NetSticker { x, y, color }
+            })
+            .collect()
+    }
+    /// Returns the column and row of a sticker in the net of the puzzle. See
+    /// [`Self::net()`].
+    fn net_position(&self, sticker: Sticker) -> (usize, usize) {
+        let n = self.layer_count() as usize;
+        let [x, y, z] = self
+            .piece_location(self.info(sticker).piece)
+            .map(|c| c as usize);
+        // Each face is viewed from outside the puzzle.
+        let (col, row, (face_col, face_row)) = match self.sticker_face(sticker) {
+            FaceEnum::U => (x, z, (1, 0)),
+            FaceEnum::L => (z, n - 1 - y, (0, 1)),
+            FaceEnum::F => (x, n - 1 - y, (1, 1)),
+            FaceEnum::R => (n - 1 - z, n - 1 - y, (2, 1)),
+            FaceEnum::B => (n - 1 - x, n - 1 - y, (3, 1)),
+            FaceEnum::D => (x, n - 1 - z, (1, 2)),
+        };
+        (face_col * n + col, face_row * n + row)
+    }
+    /// Returns flat geometry for a sticker in the net of the puzzle, facing
+    /// the camera regardless of the view angle.
+    fn net_sticker_geometry(&self, sticker: Sticker, p: StickerGeometryParams) -> StickerGeometry {
+        let n = self.layer_count() as f32;
+        let (col, row) = self.net_position(sticker);
+
+        // Scale the net, which is `4n` stickers wide and `3n` stickers tall, to
+        // a width of 2.
+        let cell_size = 2.0 / (4.0 * n);
+        let center = cgmath::point3(
+            (col as f32 + 0.5 - 2.0 * n) * cell_size,
+            (1.5 * n - row as f32 - 0.5) * cell_size,
+            0.0,
+        );
+        let half_size = cell_size / 2.0 * p.sticker_scale / p.sticker_grid_scale;
+        let u = Vector3::unit_x() * half_size;
+        let v = Vector3::unit_y() * half_size;
+
+        StickerGeometry::new_double_quad(
+            [
+                center - u - v,
+                center - u + v,
+                center + u - v,
+                center + u + v,
+            ],
+            self.click_twists(self.sticker_face(sticker)),
+            true,
+            false,
+        )
+    }
+    /// Returns the twists to do when clicking a sticker on a face.
+    fn click_twists(&self, face: FaceEnum) -> ClickTwists {
+        let cw_twist = Twist {
+            axis: face.into(),
+            direction: TwistDirectionEnum::CW90.into(),
+            layers: LayerMask::default(),
+        };
+        ClickTwists {
+            cw: Some(cw_twist),
+            ccw: Some(self.reverse_twist(cw_twist)),
+            recenter: self.make_recenter_twist(face.into()).ok(),
+        }
+    }
+
     fn piece_center_3d(&self, piece: Piece, p: StickerGeometryParams) -> Point3<f32> {
         let pos = self.piece_location(piece);
         cgmath::point3(
@@ -567,6 +692,17 @@ impl Rubiks3D {
     }
 }
 
+/// Sticker in a flat net diagram of the puzzle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NetSticker {
+    /// Column in the net, from left to right.
+    pub x: usize,
+    /// Row in the net, from top to bottom.
+    pub y: usize,
+    /// Color of the sticker.
+    pub color: Face,
+}
+
 /// The facing directions of the X+, Y+, and Z+ stickers on this piece (assuming
 /// it has those stickers).
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -954,6 +1090,101 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rubiks_3d_twist_count_options() {
+        let p = Rubiks3D::new(3);
+        let twists =
+            ["R", "M", "x", "R2", "U", "U"].map(|s| p.notation_scheme().parse_twist(s).unwrap());
+        let count = |slices, quarter_turns, rotations| {
+            let options = TwistCountOptions {
+                slices,
+                quarter_turns,
+                rotations,
+            };
+            options.count_twists(&p, twists)
+        };
+
+        assert_eq!(count(SliceConvention::Slice, false, false), 4);
+        assert_eq!(count(SliceConvention::OuterBlock, false, false), 5);
+        assert_eq!(count(SliceConvention::Slice, true, false), 6);
+        assert_eq!(count(SliceConvention::Slice, false, true), 5);
+        assert_eq!(TwistMetric::Stm.count_twists(&p, twists), 4);
+    }
+
+    #[test]
+    fn test_rubiks_3d_solved_state_definition() {
+        let mut p = Rubiks3D::new(3);
+        let twist = |p: &mut Rubiks3D, s| {
+            let twist = p.notation_scheme().parse_twist(s).unwrap();
+            p.twist(twist).unwrap();
+        };
+
+        twist(&mut p, "x");
+        assert!(p.is_solved_by(SolvedStateDefinition::AnyOrientation));
+        assert!(!p.is_solved_by(SolvedStateDefinition::OriginalOrientation));
+
+        twist(&mut p, "R");
+        assert!(!p.is_solved_by(SolvedStateDefinition::AnyOrientation));
+
+        twist(&mut p, "R'");
+        twist(&mut p, "x'");
+        assert!(p.is_solved_by(SolvedStateDefinition::OriginalOrientation));
+    }
+
+    #[test]
+    fn test_rubiks_3d_supercube() {
+        let mut p = Rubiks3D::new(3);
+        let twist = |p: &mut Rubiks3D, s| {
+            let twist = p.notation_scheme().parse_twist(s).unwrap();
+            p.twist(twist).unwrap();
+        };
+
+        twist(&mut p, "y");
+        assert!(p.is_solved_as_supercube(SolvedStateDefinition::AnyOrientation));
+        assert!(!p.is_solved_as_supercube(SolvedStateDefinition::OriginalOrientation));
+        twist(&mut p, "y'");
+
+        // Turn the U center without moving any other piece.
+        let u_center = p
+            .pieces()
+            .iter()
+            .position(|piece| {
+                piece.stickers.len() == 1
+                    && p.info(piece.stickers[0]).color == Face::from(FaceEnum::U)
+            })
+            .unwrap();
+        p.piece_states[u_center] =
+            p.piece_states[u_center].twist(FaceEnum::U, TwistDirectionEnum::CW90);
+        assert!(p.is_solved_by(SolvedStateDefinition::OriginalOrientation));
+        assert!(!p.is_solved_as_supercube(SolvedStateDefinition::AnyOrientation));
+    }
+
+    #[test]
+    fn test_rubiks_3d_net() {
+        let color_at = |p: &Rubiks3D, x, y| {
+            let sticker = p.net().into_iter().find(|s| s.x == x && s.y == y)?;
+            Some(FaceEnum::from(sticker.color))
+        };
+
+        let mut p = Rubiks3D::new(3);
+        assert_eq!(p.net().len(), 54);
+        assert_eq!(color_at(&p, 0, 0), None);
+        assert_eq!(color_at(&p, 4, 4), Some(FaceEnum::F));
+        assert_eq!(color_at(&p, 10, 4), Some(FaceEnum::B));
+
+        let r = Twist {
+            axis: FaceEnum::R.into(),
+            direction: TwistDirectionEnum::CW90.into(),
+            layers: LayerMask(1),
+        };
+        p.twist(r).unwrap();
+        // The right column of the front face moves onto the top face.
+        for y in 0..3 {
+            assert_eq!(color_at(&p, 5, y), Some(FaceEnum::F));
+            assert_eq!(color_at(&p, 3, y), Some(FaceEnum::U));
+        }
+    }
+
     fn twist_comparison_key(p: &Rubiks3D, twist: Twist) -> impl PartialEq {
         const SOME_PROGRESS: f32 = 0.1;
 
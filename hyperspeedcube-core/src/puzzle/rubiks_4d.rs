@@ -17,6 +17,10 @@ pub const MIN_LAYER_COUNT: u8 = 1;
 pub const MAX_LAYER_COUNT: u8 = 9;
 pub const LAYER_COUNT_RANGE: RangeInclusive<u8> = MIN_LAYER_COUNT..=MAX_LAYER_COUNT;
 
+/// Radius of a sphere containing the whole net of the puzzle. See
+/// `FaceEnum::unfold_into_net()`.
+const NET_RADIUS: f32 = 5.0;
+
 pub(super) fn deserialize_layer_count<'de, D>(deserializer: D) -> Result<u8, D::Error>
 where
     D: Deserializer<'de>,
@@ -358,7 +362,11 @@ impl PuzzleType for Rubiks4DDescription {
         direction.0 ^= 1;
         direction
     }
-    fn chain_twist_directions(&self, dirs: &[TwistDirection]) -> Option<TwistDirection> {
+    fn chain_twist_directions(
+        &self,
+        _axis: TwistAxis,
+        dirs: &[TwistDirection],
+    ) -> Option<TwistDirection> {
         match dirs {
             [] => None,
             [dir] => Some(*dir),
@@ -413,7 +421,8 @@ impl IndexMut<Piece> for Rubiks4D {
 }
 impl PuzzleState for Rubiks4D {
     fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
-        for piece in self.pieces_affected_by_twist(twist) {
+        let pieces = self.pieces_affected_by_twist(twist);
+        for &piece in &pieces {
             self[piece] = self[piece].twist(twist.axis.into(), twist.direction.into());
         }
         Ok(())
@@ -463,13 +472,20 @@ impl PuzzleState for Rubiks4D {
         let center = model_transform * self.sticker_center_4d(sticker, p);
 
         // Compute the vectors that span the volume of the sticker.
+        // Invert outer face, which is seen from the inside when projected.
+        let inverted = face == FaceEnum::O && !p.net;
         let Matrix4 { x, y, z, w: _ } = model_transform
             * face.basis_matrix()
             * p.sticker_scale
-            // Invert outer face.
-            * if face == FaceEnum::O { -1.0 } else { 1.0 };
-
-        let project = |point_4d| Some(p.view_transform.transform_point(p.project_4d(point_4d)?));
+            * if inverted { -1.0 } else { 1.0 };
+
+        // Keep the view angle but not the scale, which depends on the
+        // projection.
+        let net_transform = p.view_transform / p.view_transform.x.magnitude() / NET_RADIUS;
+        let project = |point_4d: Vector4<f32>| match p.net {
+            true => Some(net_transform.transform_point(face.unfold_into_net(point_4d))),
+            false => Some(p.view_transform.transform_point(p.project_4d(point_4d)?)),
+        };
 
         // Decide what twists should happen when the sticker is clicked.
         let mut twists: [ClickTwists; 6];
@@ -513,19 +529,42 @@ impl PuzzleState for Rubiks4D {
             }
         }
 
-        StickerGeometry::new_cube(
-            [
-                project(center + -x + -y + -z)?,
-                project(center + -x + -y + z)?,
-                project(center + -x + y + -z)?,
-                project(center + -x + y + z)?,
-                project(center + x + -y + -z)?,
-                project(center + x + -y + z)?,
-                project(center + x + y + -z)?,
-                project(center + x + y + z)?,
-            ],
-            twists,
-        )
+        // Coefficients of `x`, `y`, and `z` for each vertex of the cube.
+        let mut corners: [[i8; 3]; 8] =
+            std::array::from_fn(|i| [i >> 2, i >> 1, i].map(|bit| [-1, 1][bit & 1]));
+        if p.supercube && self.info(piece).stickers.len() == 1 {
+            // Shrink one side of center stickers to half its width, so that
+            // the directions that their first two original basis vectors now
+            // face are visible.
+            let original_face: FaceEnum = self.info(sticker).color.into();
+            let basis = face.basis_faces();
+            let [(i, sign_i), (j, sign_j)] = [0, 1].map(|k| {
+                let marker = self.piece_face(piece, original_face.basis_faces()[k]);
+                let index = basis
+                    .iter()
+                    .position(|b| b.axis() == marker.axis())
+                    .expect("marker is not parallel to sticker");
+                let same_sign = basis[index].sign() == marker.sign();
+                (index, if same_sign != inverted { 1 } else { -1 })
+            });
+            for corner in &mut corners {
+                if corner[i] == sign_i && corner[j] == -sign_j {
+                    corner[j] = 0;
+                }
+            }
+        }
+        let mut verts = [Point3::origin(); 8];
+        for (vert, [cx, cy, cz]) in verts.iter_mut().zip(corners) {
+            *vert = project(center + x * cx as f32 + y * cy as f32 + z * cz as f32)?;
+        }
+
+        StickerGeometry::new_cube(verts, twists)
+    }
+    fn sticker_facet(&self, sticker: Sticker) -> Option<Face> {
+        Some(self.sticker_face(sticker).into())
+    }
+    fn piece_coordinates(&self, piece: Piece) -> Option<Vec<u8>> {
+        Some(self.piece_location(piece).to_vec())
     }
 
     fn is_solved(&self) -> bool {
@@ -541,6 +580,22 @@ impl PuzzleState for Rubiks4D {
         }
         true
     }
+
+    fn is_solved_in_original_orientation(&self) -> bool {
+        (0..self.stickers().len() as _)
+            .map(Sticker)
+            .all(|sticker| self.sticker_face(sticker) == FaceEnum::from(self.info(sticker).color))
+    }
+
+    fn is_solved_as_supercube(&self, definition: SolvedStateDefinition) -> bool {
+        match definition {
+            SolvedStateDefinition::AnyOrientation => self.piece_states.iter().all_equal(),
+            SolvedStateDefinition::OriginalOrientation => self
+                .piece_states
+                .iter()
+                .all(|&state| state == PieceState::default()),
+        }
+    }
 }
 #[delegate_to_methods]
 #[delegate(PuzzleType, target_ref = "desc")]
@@ -610,8 +665,12 @@ impl Rubiks4D {
     }
     fn sticker_face(&self, sticker: Sticker) -> FaceEnum {
         let sticker_info = self.info(sticker);
-        let original_face: FaceEnum = sticker_info.color.into();
-        let current_face = self[sticker_info.piece][original_face.axis()];
+        self.piece_face(sticker_info.piece, sticker_info.color.into())
+    }
+    /// Returns the direction that the side of `piece` that originally faced
+    /// `original_face` now faces.
+    fn piece_face(&self, piece: Piece, original_face: FaceEnum) -> FaceEnum {
+        let current_face = self[piece][original_face.axis()];
         match original_face.sign() {
             Sign::Pos => current_face,
             Sign::Neg => current_face.opposite(),
@@ -943,6 +1002,23 @@ impl FaceEnum {
     fn basis(self) -> [Vector4<f32>; 3] {
         self.basis_faces().map(|f| f.vector())
     }
+    /// Maps a point on this cell to its position in a 3D net of the
+    /// tesseract, centered on the origin. The I cell is in the middle, each
+    /// side cell is attached to the corresponding face of the I cell, and the
+    /// O cell is attached to the far side of the D cell.
+    fn unfold_into_net(self, point: Vector4<f32>) -> Point3<f32> {
+        let mut ret = Point3::from_vec(point.truncate());
+        match self {
+            FaceEnum::I => (),
+            // Unfolding through the D cell mirrors the O cell.
+            FaceEnum::O => ret.y = -4.0 - ret.y,
+            _ => ret[self.axis() as usize] = self.sign().float() * (2.0 + point.w),
+        }
+        // The net spans from -5 to +3 along the Y axis.
+        ret.y += 1.0;
+        ret
+    }
+
     fn basis_matrix(self) -> Matrix4<f32> {
         let [x, y, z] = self.basis();
         let w = Vector4::zero();
@@ -0,0 +1,711 @@
+//! 5D Rubik's cube.
+//!
+//! Each twist rotates a face by 90 degrees within a plane spanned by two of the
+//! axes perpendicular to the face. Stickers are tesseracts, which are projected
+//! from 5D to 4D and then to 3D.
+
+use cgmath::*;
+use itertools::Itertools;
+use num_enum::FromPrimitive;
+use serde::{de::Error, Deserialize, Deserializer};
+use smallvec::SmallVec;
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut, RangeInclusive};
+use std::sync::Mutex;
+use strum::IntoEnumIterator;
+
+use super::*;
+
+pub const DEFAULT_LAYER_COUNT: u8 = 3;
+pub const MIN_LAYER_COUNT: u8 = 1;
+pub const MAX_LAYER_COUNT: u8 = 5;
+pub const LAYER_COUNT_RANGE: RangeInclusive<u8> = MIN_LAYER_COUNT..=MAX_LAYER_COUNT;
+
+/// Pairs of axes within a face (using the face's basis) that can be twisted.
+/// Each plane has two twist directions.
+const TWIST_PLANES: [(usize, usize); 6] = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)];
+/// Name of each twist direction, using the face's basis.
+const TWIST_DIRECTION_NAMES: [&str; 12] = [
+    "xy", "yx", "xz", "zx", "xw", "wx", "yz", "zy", "yw", "wy", "zw", "wz",
+];
+
+pub(super) fn deserialize_layer_count<'de, D>(deserializer: D) -> Result<u8, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let layer_count: u8 = Deserialize::deserialize(deserializer)?;
+    if !LAYER_COUNT_RANGE.contains(&layer_count) {
+        return Err(D::Error::custom(format!(
+            "invalid layer count {layer_count}"
+        )));
+    }
+    Ok(layer_count)
+}
+
+pub(super) fn puzzle_type(layer_count: u8) -> &'static dyn PuzzleType {
+    puzzle_description(layer_count)
+}
+
+fn puzzle_description(layer_count: u8) -> &'static Rubiks5DDescription {
+    lazy_static! {
+        static ref CACHE: Mutex<HashMap<u8, &'static Rubiks5DDescription>> =
+            Mutex::new(HashMap::new());
+    }
+
+    assert!(LAYER_COUNT_RANGE.contains(&layer_count));
+
+    CACHE.lock().unwrap().entry(layer_count).or_insert_with(|| {
+        let mut pieces = vec![];
+        let mut stickers = vec![];
+        let mut piece_locations = vec![];
+
+        for location in (0..5).map(|_| 0..layer_count).multi_cartesian_product() {
+            let location: [u8; 5] = location.try_into().unwrap();
+
+            let piece = Piece(pieces.len() as _);
+            let mut piece_stickers = SmallVec::new();
+            let mut outer_axes: u8 = 0;
+            for axis in Axis::iter() {
+                let coord = location[axis as usize];
+                let is_max = coord == layer_count - 1;
+                let is_min = coord == 0;
+                if is_max || is_min {
+                    outer_axes += 1;
+                }
+                for (condition, sign) in [(is_max, Sign::Pos), (is_min, Sign::Neg)] {
+                    if condition {
+                        piece_stickers.push(Sticker(stickers.len() as _));
+                        stickers.push(StickerInfo {
+                            piece,
+                            color: FaceEnum::new(axis, sign).into(),
+                        });
+                    }
+                }
+            }
+            if outer_axes == 0 {
+                continue; // Skip internal pieces.
+            }
+
+            piece_locations.push(location);
+            pieces.push(PieceInfo {
+                stickers: piece_stickers,
+                // Piece type is determined by how many faces the piece
+                // touches.
+                piece_type: PieceType(outer_axes - 1),
+            });
+        }
+
+        let notation = NotationScheme {
+            axis_names: FaceEnum::iter()
+                .map(|f| f.symbol_upper_str().to_string())
+                .collect(),
+            direction_names: (0..TWIST_DIRECTION_NAMES.len() as u8)
+                .map(|dir| {
+                    TwistDirectionName::PerAxis(
+                        FaceEnum::iter()
+                            .map(|f| f.direction_symbol(TwistDirection(dir)))
+                            .collect(),
+                    )
+                })
+                .collect(),
+            block_suffix: None,
+            aliases: vec![],
+        };
+
+        // It's not like we'll ever clear the cache anyway, so just leak it
+        // and let us have the 'static lifetimes.
+        Box::leak(Box::new(Rubiks5DDescription {
+            name: format!("{0}x{0}x{0}x{0}x{0}", layer_count),
+
+            layer_count,
+
+            faces: FaceEnum::iter().map(|f| f.info()).collect(),
+            pieces,
+            stickers,
+            twist_axes: FaceEnum::iter().map(|f| f.twist_axis_info()).collect(),
+            twist_directions: TWIST_DIRECTION_NAMES
+                .iter()
+                .map(|&name| TwistDirectionInfo::new(name, name))
+                .collect(),
+            piece_types: (1..=5)
+                .map(|n| PieceTypeInfo::new(format!("{n}c")))
+                .collect(),
+            notation,
+
+            piece_locations,
+        }))
+    })
+}
+
+#[derive(Debug, Clone)]
+struct Rubiks5DDescription {
+    name: String,
+
+    layer_count: u8,
+
+    faces: Vec<FaceInfo>,
+    pieces: Vec<PieceInfo>,
+    stickers: Vec<StickerInfo>,
+    twist_axes: Vec<TwistAxisInfo>,
+    twist_directions: Vec<TwistDirectionInfo>,
+    piece_types: Vec<PieceTypeInfo>,
+    notation: NotationScheme,
+
+    piece_locations: Vec<[u8; 5]>,
+}
+impl PuzzleType for Rubiks5DDescription {
+    fn ty(&self) -> PuzzleTypeEnum {
+        PuzzleTypeEnum::Rubiks5D {
+            layer_count: self.layer_count,
+        }
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn family_display_name(&self) -> &'static str {
+        "Rubik's 5D"
+    }
+    fn family_internal_name(&self) -> &'static str {
+        "Rubiks5D"
+    }
+    fn projection_type(&self) -> ProjectionType {
+        // Use the same view settings as 4D puzzles.
+        ProjectionType::_4D
+    }
+
+    fn layer_count(&self) -> u8 {
+        self.layer_count
+    }
+    fn family_max_layer_count(&self) -> u8 {
+        MAX_LAYER_COUNT
+    }
+    fn projection_radius_3d(&self, p: StickerGeometryParams) -> f32 {
+        let r = 1.0 - p.face_spacing;
+        let farthest_point = [1.0, r, r, r, r];
+        match p.project_5d(farthest_point) {
+            Some(farthest_point) => p
+                .view_transform
+                .transform_point(farthest_point)
+                .distance(Point3::origin()),
+            None => 3.0_f32.sqrt(), // shouldn't ever happen
+        }
+    }
+    fn scramble_moves_count(&self) -> usize {
+        20 * self.layer_count as usize // TODO pulled from thin air; probably insufficient for big cubes
+    }
+
+    fn faces(&self) -> &[FaceInfo] {
+        &self.faces
+    }
+    fn pieces(&self) -> &[PieceInfo] {
+        &self.pieces
+    }
+    fn stickers(&self) -> &[StickerInfo] {
+        &self.stickers
+    }
+    fn twist_axes(&self) -> &[TwistAxisInfo] {
+        &self.twist_axes
+    }
+    fn twist_directions(&self) -> &[TwistDirectionInfo] {
+        &self.twist_directions
+    }
+    fn piece_types(&self) -> &[PieceTypeInfo] {
+        &self.piece_types
+    }
+
+    fn opposite_twist_axis(&self, twist_axis: TwistAxis) -> Option<TwistAxis> {
+        Some(FaceEnum::from(twist_axis).opposite().into())
+    }
+    fn count_quarter_turns(&self, _twist: Twist) -> usize {
+        1
+    }
+
+    fn make_recenter_twist(&self, _axis: TwistAxis) -> Result<Twist, String> {
+        Err("recentering is not supported for this puzzle".to_string())
+    }
+
+    fn canonicalize_twist(&self, twist: Twist) -> Twist {
+        twist
+    }
+
+    fn reverse_twist_direction(&self, direction: TwistDirection) -> TwistDirection {
+        TwistDirection(direction.0 ^ 1)
+    }
+    fn chain_twist_directions(
+        &self,
+        _axis: TwistAxis,
+        dirs: &[TwistDirection],
+    ) -> Option<TwistDirection> {
+        // Only twists within the same plane can be combined into one twist.
+        let plane = dirs.first()?.0 / 2;
+        if dirs.iter().any(|dir| dir.0 / 2 != plane) {
+            return None;
+        }
+        let total: i32 = dirs
+            .iter()
+            .map(|dir| if dir.0 % 2 == 0 { 1 } else { -1 })
+            .sum();
+        match total.rem_euclid(4) {
+            1 => Some(TwistDirection(plane * 2)),
+            3 => Some(TwistDirection(plane * 2 + 1)),
+            _ => None,
+        }
+    }
+
+    fn notation_scheme(&self) -> &NotationScheme {
+        &self.notation
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rubiks5D {
+    desc: &'static Rubiks5DDescription,
+    piece_states: Box<[PieceState]>,
+}
+impl Eq for Rubiks5D {}
+impl PartialEq for Rubiks5D {
+    fn eq(&self, other: &Self) -> bool {
+        self.piece_states == other.piece_states
+    }
+}
+impl Index<Piece> for Rubiks5D {
+    type Output = PieceState;
+
+    fn index(&self, piece: Piece) -> &Self::Output {
+        &self.piece_states[piece.0 as usize]
+    }
+}
+impl IndexMut<Piece> for Rubiks5D {
+    fn index_mut(&mut self, piece: Piece) -> &mut Self::Output {
+        &mut self.piece_states[piece.0 as usize]
+    }
+}
+impl PuzzleState for Rubiks5D {
+    fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
+        if twist.direction.0 as usize >= TWIST_DIRECTION_NAMES.len() {
+            return Err("invalid twist direction");
+        }
+        let face: FaceEnum = twist.axis.into();
+        let (from, to) = face.twist_plane(twist.direction);
+        let pieces = self.pieces_affected_by_twist(twist);
+        for &piece in &pieces {
+            self[piece] = self[piece].rotate(from, to);
+        }
+        Ok(())
+    }
+    fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8 {
+        let face: FaceEnum = twist_axis.into();
+        let face_coord = match face.sign() {
+            Sign::Pos => self.layer_count() - 1,
+            Sign::Neg => 0,
+        };
+        let piece_coord = self.piece_location(piece)[face.axis() as usize];
+        u8::abs_diff(face_coord, piece_coord)
+    }
+
+    fn rotation_candidates(&self) -> Vec<(Vec<Twist>, Quaternion<f32>)> {
+        let layers = self.all_layers();
+
+        // Twisting the whole puzzle on the A face rotates only the XYZ axes.
+        (0..TWIST_DIRECTION_NAMES.len() as u8)
+            .map(TwistDirection)
+            .filter_map(|direction| {
+                let (from, to) = FaceEnum::A.twist_plane(direction);
+                let twist = Twist {
+                    axis: FaceEnum::A.into(),
+                    direction,
+                    layers,
+                };
+                let rot = Quaternion::from_arc(from.unit_vec3()?, to.unit_vec3()?, None);
+                Some((vec![twist], rot))
+            })
+            .collect()
+    }
+
+    fn sticker_geometry(
+        &self,
+        sticker: Sticker,
+        p: StickerGeometryParams,
+    ) -> Option<StickerGeometry> {
+        let piece = self.info(sticker).piece;
+        let face = self.sticker_face(sticker);
+
+        let mut animation = None;
+        if let Some((twist, progress)) = p.twist_animation {
+            if self.is_piece_affected_by_twist(twist, piece) {
+                let twist_face: FaceEnum = twist.axis.into();
+                let angle = Rad::turn_div_4() * progress;
+                animation = Some((twist_face.twist_plane(twist.direction), angle));
+            }
+        }
+
+        // Compute the vertices of the sticker.
+        let center = self.sticker_center_5d(sticker, p);
+        let basis = face.basis();
+        let mut verts = [Point3::origin(); 16];
+        for (i, vert) in verts.iter_mut().enumerate() {
+            let mut point = center;
+            for (j, axis) in basis.into_iter().enumerate() {
+                let sign = if i & (8 >> j) != 0 { 1.0 } else { -1.0 };
+                point[axis as usize] += sign * p.sticker_scale;
+            }
+            if let Some(((from, to), angle)) = animation {
+                let (sin, cos) = Rad::sin_cos(angle);
+                let (a, b) = (point[from as usize], point[to as usize]);
+                point[from as usize] = a * cos - b * sin;
+                point[to as usize] = a * sin + b * cos;
+            }
+            *vert = p.view_transform.transform_point(p.project_5d(point)?);
+        }
+
+        // Decide what twists should happen when the sticker is clicked. Each
+        // polygon twists the face in the plane that keeps that polygon fixed.
+        let twists = |fixed_axes: [(usize, Sign); 2]| {
+            let [(_, sign1), (_, sign2)] = fixed_axes;
+            let (i, j) = (0..4)
+                .filter(|&j| fixed_axes.iter().all(|&(k, _)| j != k))
+                .collect_tuple()
+                .unwrap_or((0, 1));
+            let plane = TWIST_PLANES.iter().position(|&p| p == (i, j)).unwrap_or(0);
+            let cw_twist = Twist {
+                axis: face.into(),
+                direction: TwistDirection((plane * 2 + (sign1 != sign2) as usize) as _),
+                layers: LayerMask::default(),
+            };
+            ClickTwists {
+                cw: Some(cw_twist),
+                ccw: Some(self.reverse_twist(cw_twist)),
+                recenter: None,
+            }
+        };
+
+        StickerGeometry::new_tesseract(verts, twists)
+    }
+    fn sticker_facet(&self, sticker: Sticker) -> Option<Face> {
+        Some(self.sticker_face(sticker).into())
+    }
+    fn piece_coordinates(&self, piece: Piece) -> Option<Vec<u8>> {
+        Some(self.piece_location(piece).to_vec())
+    }
+
+    fn is_solved(&self) -> bool {
+        let mut color_per_facet = vec![None; self.faces().len()];
+        for (i, sticker) in self.stickers().iter().enumerate() {
+            let color = self.sticker_face(Sticker(i as _));
+            let facet = sticker.color.0 as usize;
+            if color_per_facet[facet] == None {
+                color_per_facet[facet] = Some(color);
+            } else if color_per_facet[facet] != Some(color) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn is_solved_in_original_orientation(&self) -> bool {
+        (0..self.stickers().len() as _)
+            .map(Sticker)
+            .all(|sticker| self.sticker_face(sticker) == FaceEnum::from(self.info(sticker).color))
+    }
+}
+#[delegate_to_methods]
+#[delegate(PuzzleType, target_ref = "desc")]
+impl Rubiks5D {
+    pub fn new(layer_count: u8) -> Self {
+        let desc = puzzle_description(layer_count);
+        let piece_states = vec![PieceState::default(); desc.pieces().len()].into_boxed_slice();
+        Self { desc, piece_states }
+    }
+
+    fn desc(&self) -> &Rubiks5DDescription {
+        self.desc
+    }
+
+    fn piece_location(&self, piece: Piece) -> [u8; 5] {
+        let piece_state = self[piece];
+        let initial_location = self.desc.piece_locations[piece.0 as usize];
+        let mut ret = [0_u8; 5];
+        for (i, axis) in Axis::iter().enumerate() {
+            let r = piece_state[axis].axis() as usize;
+            ret[r] = initial_location[i];
+            if piece_state[axis].sign() == Sign::Neg {
+                ret[r] = self.layer_count() - 1 - ret[r];
+            }
+        }
+        ret
+    }
+    fn sticker_face(&self, sticker: Sticker) -> FaceEnum {
+        let sticker_info = self.info(sticker);
+        let original_face: FaceEnum = sticker_info.color.into();
+        let current_face = self[sticker_info.piece][original_face.axis()];
+        match original_face.sign() {
+            Sign::Pos => current_face,
+            Sign::Neg => current_face.opposite(),
+        }
+    }
+
+    fn sticker_center_5d(&self, sticker: Sticker, p: StickerGeometryParams) -> [f32; 5] {
+        let piece = self.info(sticker).piece;
+        let mut ret = self
+            .piece_location(piece)
+            .map(|x| self.piece_center_coordinate(x, p));
+
+        let sticker_face = self.sticker_face(sticker);
+        ret[sticker_face.axis() as usize] = sticker_face.sign().float();
+        ret
+    }
+
+    fn piece_center_coordinate(&self, x: u8, p: StickerGeometryParams) -> f32 {
+        (2.0 * x as f32 - (self.layer_count() - 1) as f32) * p.sticker_grid_scale
+    }
+}
+
+/// The facing directions of the X+, Y+, Z+, W+, and V+ stickers on this piece
+/// (assuming it has those stickers).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PieceState([FaceEnum; 5]);
+impl Default for PieceState {
+    fn default() -> Self {
+        use FaceEnum::*;
+
+        Self([R, U, F, O, A])
+    }
+}
+impl Index<Axis> for PieceState {
+    type Output = FaceEnum;
+
+    fn index(&self, axis: Axis) -> &Self::Output {
+        &self.0[axis as usize]
+    }
+}
+impl IndexMut<Axis> for PieceState {
+    fn index_mut(&mut self, axis: Axis) -> &mut Self::Output {
+        &mut self.0[axis as usize]
+    }
+}
+impl PieceState {
+    /// Rotates the piece 90 degrees so that `from` moves to `to`.
+    #[must_use]
+    fn rotate(mut self, from: Axis, to: Axis) -> Self {
+        for face in &mut self.0 {
+            if face.axis() == from {
+                *face = FaceEnum::new(to, face.sign());
+            } else if face.axis() == to {
+                *face = FaceEnum::new(from, -face.sign());
+            }
+        }
+        self
+    }
+}
+
+#[derive(EnumIter, FromPrimitive, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(u8)]
+enum FaceEnum {
+    #[default]
+    R = 0,
+    L = 1,
+    U = 2,
+    D = 3,
+    F = 4,
+    B = 5,
+    O = 6,
+    I = 7,
+    A = 8,
+    K = 9,
+}
+impl From<Face> for FaceEnum {
+    fn from(Face(i): Face) -> Self {
+        Self::from(i)
+    }
+}
+impl From<FaceEnum> for Face {
+    fn from(face: FaceEnum) -> Self {
+        Self(face as _)
+    }
+}
+impl From<TwistAxis> for FaceEnum {
+    fn from(TwistAxis(i): TwistAxis) -> Self {
+        Self::from(i)
+    }
+}
+impl From<FaceEnum> for TwistAxis {
+    fn from(face: FaceEnum) -> Self {
+        Self(face as _)
+    }
+}
+impl FaceEnum {
+    fn new(axis: Axis, sign: Sign) -> Self {
+        Self::from(axis as u8 * 2 + (sign == Sign::Neg) as u8)
+    }
+
+    fn info(self) -> FaceInfo {
+        FaceInfo {
+            symbol: self.symbol_upper_str(),
+            name: self.name(),
+        }
+    }
+    fn twist_axis_info(self) -> TwistAxisInfo {
+        TwistAxisInfo {
+            name: self.symbol_upper_str(),
+        }
+    }
+
+    fn axis(self) -> Axis {
+        use FaceEnum::*;
+
+        match self {
+            R | L => Axis::X,
+            U | D => Axis::Y,
+            F | B => Axis::Z,
+            O | I => Axis::W,
+            A | K => Axis::V,
+        }
+    }
+    fn sign(self) -> Sign {
+        use FaceEnum::*;
+
+        match self {
+            R | U | F | O | A => Sign::Pos,
+            L | D | B | I | K => Sign::Neg,
+        }
+    }
+    #[must_use]
+    fn opposite(self) -> Self {
+        Self::new(self.axis(), -self.sign())
+    }
+
+    fn symbol_upper_str(self) -> &'static str {
+        use FaceEnum::*;
+
+        match self {
+            R => "R",
+            L => "L",
+            U => "U",
+            D => "D",
+            F => "F",
+            B => "B",
+            O => "O",
+            I => "I",
+            A => "A",
+            K => "K",
+        }
+    }
+    fn name(self) -> &'static str {
+        use FaceEnum::*;
+
+        match self {
+            R => "Right",
+            L => "Left",
+            U => "Up",
+            D => "Down",
+            F => "Front",
+            B => "Back",
+            O => "Out",
+            I => "In",
+            A => "Ana",
+            K => "Kata",
+        }
+    }
+
+    /// Returns the axes perpendicular to the face.
+    fn basis(self) -> [Axis; 4] {
+        let mut ret = [Axis::X; 4];
+        for (b, axis) in ret
+            .iter_mut()
+            .zip(Axis::iter().filter(|&ax| ax != self.axis()))
+        {
+            *b = axis;
+        }
+        ret
+    }
+    /// Returns the axes that a twist in some direction on this face rotates
+    /// from and to.
+    fn twist_plane(self, direction: TwistDirection) -> (Axis, Axis) {
+        let basis = self.basis();
+        let (i, j) = TWIST_PLANES[direction.0 as usize / 2];
+        if direction.0 % 2 == 0 {
+            (basis[i], basis[j])
+        } else {
+            (basis[j], basis[i])
+        }
+    }
+    /// Returns the symbol for a twist direction on this face, which names the
+    /// axes that it rotates from and to.
+    fn direction_symbol(self, direction: TwistDirection) -> String {
+        let (from, to) = self.twist_plane(direction);
+        format!("{}{}", from.symbol_lower(), to.symbol_lower())
+    }
+}
+
+/// 5-dimensional axis.
+#[derive(EnumIter, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Axis {
+    /// X axis (right).
+    X = 0,
+    /// Y axis (up).
+    Y = 1,
+    /// Z axis (towards the 3D camera).
+    Z = 2,
+    /// W axis (towards the 4D camera).
+    W = 3,
+    /// V axis (towards the 5D camera).
+    V = 4,
+}
+impl Axis {
+    fn symbol_lower(self) -> char {
+        match self {
+            Axis::X => 'x',
+            Axis::Y => 'y',
+            Axis::Z => 'z',
+            Axis::W => 'w',
+            Axis::V => 'v',
+        }
+    }
+
+    /// Returns the unit vector along this axis, if it is within 3D space.
+    fn unit_vec3(self) -> Option<Vector3<f32>> {
+        match self {
+            Axis::X => Some(Vector3::unit_x()),
+            Axis::Y => Some(Vector3::unit_y()),
+            Axis::Z => Some(Vector3::unit_z()),
+            Axis::W | Axis::V => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rubiks_5d_twist_serialization() {
+        for layer_count in 1..=3 {
+            let p = Rubiks5D::new(layer_count);
+            crate::puzzle::tests::test_twist_serialization(&p);
+        }
+
+        for layer_count in LAYER_COUNT_RANGE {
+            let p = Rubiks5D::new(layer_count);
+            crate::puzzle::tests::test_layered_twist_serialization(&p);
+        }
+    }
+
+    #[test]
+    fn test_rubiks_5d_twist_period() {
+        let mut p = Rubiks5D::new(3);
+        assert_eq!(p.pieces().len(), 3_usize.pow(5) - 1);
+        assert_eq!(p.stickers().len(), 10 * 3_usize.pow(4));
+
+        for direction in (0..p.twist_directions().len() as u8).map(TwistDirection) {
+            let twist = Twist {
+                axis: FaceEnum::R.into(),
+                direction,
+                layers: LayerMask::default(),
+            };
+            for i in 1..=4 {
+                p.twist(twist).unwrap();
+                assert_eq!(p.is_solved(), i == 4);
+            }
+        }
+    }
+}
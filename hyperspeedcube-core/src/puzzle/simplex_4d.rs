@@ -0,0 +1,771 @@
+//! Face-turning 4D simplex (5-cell).
+//!
+//! The 5-cell has five tetrahedral facets, each of which touches all the
+//! others. Each facet is cut by a hyperplane parallel to it, so every twist
+//! moves the part of the puzzle on the facet's side of its cut. A twist
+//! rotates the facet by any rotation of its tetrahedron: 120 degrees around
+//! one of its vertices, or 180 degrees around an axis through the midpoints of
+//! two opposite edges.
+//!
+//! Stickers are convex polyhedra within each facet, computed by clipping the
+//! facet by the cuts.
+
+use cgmath::*;
+use itertools::Itertools;
+use smallvec::SmallVec;
+
+use super::*;
+
+/// Number of facets (and vertices) of the 5-cell.
+const FACE_COUNT: usize = 5;
+
+const FACE_SYMBOLS: [&str; FACE_COUNT] = ["A", "B", "C", "D", "E"];
+const FACE_NAMES: [&str; FACE_COUNT] = ["Alpha", "Beta", "Gamma", "Delta", "Epsilon"];
+const PIECE_TYPE_NAMES: [&str; FACE_COUNT - 1] = ["1c", "2c", "3c", "4c"];
+
+/// Symbol and name of each twist direction. Directions `2*i` and `2*i+1` turn
+/// around the `i`th vertex of the facet (counting only vertices on the facet),
+/// and the last three directions turn around an axis through the midpoint of
+/// the edge between the first vertex and another vertex.
+const TWIST_DIRECTIONS: [(&str, &str); 11] = [
+    ("1", "Vertex 1 CW"),
+    ("1'", "Vertex 1 CCW"),
+    ("2", "Vertex 2 CW"),
+    ("2'", "Vertex 2 CCW"),
+    ("3", "Vertex 3 CW"),
+    ("3'", "Vertex 3 CCW"),
+    ("4", "Vertex 4 CW"),
+    ("4'", "Vertex 4 CCW"),
+    ("12", "Edge 1-2"),
+    ("13", "Edge 1-3"),
+    ("14", "Edge 1-4"),
+];
+/// Number of twist directions that turn around a vertex.
+const VERTEX_TWIST_DIRECTIONS: u8 = 8;
+
+/// Distance of each cut from the center of the puzzle, as a fraction of the
+/// distance from the center to a facet.
+const CUT_DEPTH: f32 = 0.0;
+
+/// Maximum distance between two points that are considered the same.
+const EPSILON: f32 = 0.0001;
+
+pub(super) fn puzzle_type() -> &'static dyn PuzzleType {
+    &*DESCRIPTION
+}
+
+lazy_static! {
+    static ref DESCRIPTION: Simplex4DDescription = Simplex4DDescription::new();
+}
+
+/// Returns vertex `i` of the 5-cell, which is opposite facet `i`. The 5-cell
+/// has a circumradius of 1, with one vertex pointing toward the 4D camera.
+fn vertex(i: usize) -> Vector4<f32> {
+    let w = -1.0 / 5.0_f32.sqrt();
+    let v = match i {
+        0 => vec4(1.0, 1.0, 1.0, w),
+        1 => vec4(1.0, -1.0, -1.0, w),
+        2 => vec4(-1.0, 1.0, -1.0, w),
+        3 => vec4(-1.0, -1.0, 1.0, w),
+        _ => vec4(0.0, 0.0, 0.0, -4.0 * w),
+    };
+    v.normalize()
+}
+/// Returns the outward-facing unit normal vector of facet `f`.
+fn facet_normal(f: usize) -> Vector4<f32> {
+    -vertex(f)
+}
+/// Returns the center of facet `f`.
+fn facet_center(f: usize) -> Vector4<f32> {
+    // The inradius of a 4D simplex is 1/4 of its circumradius.
+    facet_normal(f) / 4.0
+}
+/// Returns the facets other than `f`, in order. The `i`th vertex of facet `f`
+/// is opposite the `i`th of these.
+fn other_facets(f: usize) -> impl Iterator<Item = usize> {
+    (0..FACE_COUNT).filter(move |&g| g != f)
+}
+
+#[derive(Debug, Clone)]
+struct Simplex4DDescription {
+    faces: Vec<FaceInfo>,
+    pieces: Vec<PieceInfo>,
+    stickers: Vec<StickerInfo>,
+    twist_axes: Vec<TwistAxisInfo>,
+    twist_directions: Vec<TwistDirectionInfo>,
+    piece_types: Vec<PieceTypeInfo>,
+    notation: NotationScheme,
+
+    slots: Vec<Slot>,
+    /// Rotation for each twist direction on each facet.
+    rotations: Vec<Vec<PlaneRotation>>,
+    /// Slot that each slot moves to after a twist in each direction on each
+    /// facet.
+    perms: Vec<Vec<Box<[u8]>>>,
+    /// Permutation of the vertices of a facet by each twist direction.
+    vertex_perms: Vec<[usize; 4]>,
+}
+impl Simplex4DDescription {
+    fn new() -> Self {
+        let mut pieces = vec![];
+        let mut stickers = vec![];
+        let mut slots = vec![];
+
+        // Each piece is the region of the puzzle beyond the cuts of some set
+        // of facets. There is one sticker for each of those facets.
+        for piece_faces in (1..FACE_COUNT).flat_map(|n| (0..FACE_COUNT).combinations(n)) {
+            let piece = Piece(pieces.len() as _);
+            let mut piece_stickers = SmallVec::new();
+            for &f in &piece_faces {
+                piece_stickers.push(Sticker(stickers.len() as _));
+                stickers.push(StickerInfo {
+                    piece,
+                    color: Face(f as _),
+                });
+                slots.push(Slot::new(f, &piece_faces));
+            }
+            pieces.push(PieceInfo {
+                stickers: piece_stickers,
+                piece_type: PieceType(piece_faces.len() as u8 - 1),
+            });
+        }
+
+        let rotations = (0..FACE_COUNT)
+            .map(|f| {
+                (0..TWIST_DIRECTIONS.len() as u8)
+                    .map(|d| PlaneRotation::new(f, TwistDirection(d)))
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        let perms = rotations
+            .iter()
+            .map(|facet_rotations| {
+                facet_rotations
+                    .iter()
+                    .map(|rot| {
+                        slots
+                            .iter()
+                            .map(|slot| {
+                                let center = rot.apply(slot.center, 1.0);
+                                slots
+                                    .iter()
+                                    .position(|other| other.center.distance(center) < EPSILON)
+                                    .expect("twist does not move stickers onto other stickers")
+                                    as u8
+                            })
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let facet_verts = other_facets(0).map(vertex).collect_vec();
+        let vertex_perms = rotations[0]
+            .iter()
+            .map(|rot| {
+                let mut perm = [0; 4];
+                for (i, &v) in facet_verts.iter().enumerate() {
+                    let v = rot.apply(v, 1.0);
+                    perm[i] = facet_verts
+                        .iter()
+                        .position(|other| other.distance(v) < EPSILON)
+                        .expect("twist does not move vertices onto other vertices");
+                }
+                perm
+            })
+            .collect();
+
+        let notation = NotationScheme {
+            axis_names: FACE_SYMBOLS.iter().map(|s| s.to_string()).collect(),
+            direction_names: (0..TWIST_DIRECTIONS.len() as u8)
+                .map(|d| {
+                    TwistDirectionName::PerAxis(
+                        (0..FACE_COUNT)
+                            .map(|f| direction_symbol(f, TwistDirection(d)))
+                            .collect(),
+                    )
+                })
+                .collect(),
+            block_suffix: None,
+            aliases: vec![],
+        };
+
+        Self {
+            faces: (0..FACE_COUNT)
+                .map(|f| FaceInfo::new(FACE_SYMBOLS[f], FACE_NAMES[f]))
+                .collect(),
+            pieces,
+            stickers,
+            twist_axes: FACE_SYMBOLS
+                .iter()
+                .map(|&name| TwistAxisInfo { name })
+                .collect(),
+            twist_directions: TWIST_DIRECTIONS
+                .iter()
+                .map(|&(symbol, name)| TwistDirectionInfo::new(symbol, name))
+                .collect(),
+            piece_types: PIECE_TYPE_NAMES
+                .iter()
+                .map(|&name| PieceTypeInfo::new(name.to_string()))
+                .collect(),
+            notation,
+
+            slots,
+            rotations,
+            perms,
+            vertex_perms,
+        }
+    }
+}
+impl PuzzleType for Simplex4DDescription {
+    fn ty(&self) -> PuzzleTypeEnum {
+        PuzzleTypeEnum::Simplex4D
+    }
+    fn name(&self) -> &str {
+        "5-cell"
+    }
+    fn family_display_name(&self) -> &'static str {
+        "Simplex 4D"
+    }
+    fn family_internal_name(&self) -> &'static str {
+        "Simplex4D"
+    }
+    fn projection_type(&self) -> ProjectionType {
+        ProjectionType::_4D
+    }
+
+    fn layer_count(&self) -> u8 {
+        2
+    }
+    fn family_max_layer_count(&self) -> u8 {
+        2
+    }
+    fn projection_radius_3d(&self, p: StickerGeometryParams) -> f32 {
+        (0..FACE_COUNT)
+            .flat_map(|f| other_facets(f).map(move |g| (f, g)))
+            .filter_map(|(f, g)| {
+                let point =
+                    facet_center(f) + (vertex(g) - facet_center(f)) * (1.0 - p.face_spacing);
+                Some(
+                    p.view_transform
+                        .transform_point(p.project_4d(point)?)
+                        .distance(Point3::origin()),
+                )
+            })
+            .fold(1.0, f32::max)
+    }
+    fn scramble_moves_count(&self) -> usize {
+        40 // TODO pulled from thin air
+    }
+
+    fn faces(&self) -> &[FaceInfo] {
+        &self.faces
+    }
+    fn pieces(&self) -> &[PieceInfo] {
+        &self.pieces
+    }
+    fn stickers(&self) -> &[StickerInfo] {
+        &self.stickers
+    }
+    fn twist_axes(&self) -> &[TwistAxisInfo] {
+        &self.twist_axes
+    }
+    fn twist_directions(&self) -> &[TwistDirectionInfo] {
+        &self.twist_directions
+    }
+    fn piece_types(&self) -> &[PieceTypeInfo] {
+        &self.piece_types
+    }
+
+    fn opposite_twist_axis(&self, _twist_axis: TwistAxis) -> Option<TwistAxis> {
+        None // Every facet is adjacent to every other facet.
+    }
+    fn count_quarter_turns(&self, _twist: Twist) -> usize {
+        1
+    }
+
+    fn make_recenter_twist(&self, _axis: TwistAxis) -> Result<Twist, String> {
+        Err("recentering is not supported for this puzzle".to_string())
+    }
+
+    fn canonicalize_twist(&self, twist: Twist) -> Twist {
+        twist
+    }
+
+    fn reverse_twist_direction(&self, direction: TwistDirection) -> TwistDirection {
+        if direction.0 < VERTEX_TWIST_DIRECTIONS {
+            TwistDirection(direction.0 ^ 1)
+        } else {
+            direction // 180-degree rotations are their own inverse.
+        }
+    }
+    fn chain_twist_directions(
+        &self,
+        _axis: TwistAxis,
+        dirs: &[TwistDirection],
+    ) -> Option<TwistDirection> {
+        // Compose the permutations of the facet's vertices.
+        let mut total = [0, 1, 2, 3];
+        for dir in dirs {
+            let perm = self.vertex_perms.get(dir.0 as usize)?;
+            total = total.map(|i| perm[i]);
+        }
+        self.vertex_perms
+            .iter()
+            .position(|&perm| perm == total)
+            .map(|i| TwistDirection(i as _))
+    }
+
+    fn notation_scheme(&self) -> &NotationScheme {
+        &self.notation
+    }
+}
+
+/// Returns the symbol for a twist direction on a facet, which names the facets
+/// opposite the vertices that the twist turns around.
+fn direction_symbol(f: usize, direction: TwistDirection) -> String {
+    let others = other_facets(f).collect_vec();
+    let letter = |i: usize| FACE_SYMBOLS[others[i]].to_lowercase();
+    let d = direction.0;
+    if d < VERTEX_TWIST_DIRECTIONS {
+        let prime = if d % 2 == 0 { "" } else { "'" };
+        format!("{}{prime}", letter(d as usize / 2))
+    } else {
+        format!(
+            "{}{}",
+            letter(0),
+            letter((d - VERTEX_TWIST_DIRECTIONS) as usize + 1)
+        )
+    }
+}
+
+/// Rotation of 4D space within a plane.
+#[derive(Debug, Copy, Clone)]
+struct PlaneRotation {
+    from: Vector4<f32>,
+    to: Vector4<f32>,
+    angle: Rad<f32>,
+}
+impl PlaneRotation {
+    /// Returns the rotation for a twist direction on facet `f`, which keeps
+    /// the facet's normal vector and the axis of the twist fixed.
+    fn new(f: usize, direction: TwistDirection) -> Self {
+        let others = other_facets(f).collect_vec();
+        let d = direction.0;
+        let (target, angle) = if d < VERTEX_TWIST_DIRECTIONS {
+            let angle = Rad::full_turn() / 3.0;
+            let angle = if d % 2 == 0 { -angle } else { angle };
+            (vertex(others[d as usize / 2]), angle)
+        } else {
+            let other = others[(d - VERTEX_TWIST_DIRECTIONS) as usize + 1];
+            ((vertex(others[0]) + vertex(other)) / 2.0, Rad::turn_div_2())
+        };
+        let normal = facet_normal(f);
+        let axis = (target - facet_center(f)).normalize();
+        let [from, to] = complete_basis([normal, axis]);
+        Self { from, to, angle }
+    }
+
+    /// Applies some fraction of the rotation to a vector.
+    fn apply(self, v: Vector4<f32>, t: f32) -> Vector4<f32> {
+        let (sin, cos) = Rad::sin_cos(self.angle * t);
+        let (a, b) = (v.dot(self.from), v.dot(self.to));
+        v + self.from * (a * cos - b * sin - a) + self.to * (a * sin + b * cos - b)
+    }
+}
+
+/// Returns an orthonormal basis for the subspace orthogonal to some orthonormal
+/// vectors, oriented so that the combined basis has a positive determinant.
+fn complete_basis<const N: usize, const M: usize>(vectors: [Vector4<f32>; N]) -> [Vector4<f32>; M] {
+    assert_eq!(N + M, 4);
+    let mut basis = vectors.to_vec();
+    for i in 0..4 {
+        let mut v = Vector4::zero();
+        v[i] = 1.0;
+        for &b in &basis {
+            v -= b * v.dot(b);
+        }
+        if basis.len() < 4 && v.magnitude() > 0.1 {
+            basis.push(v.normalize());
+        }
+    }
+    if Matrix4::from_cols(basis[0], basis[1], basis[2], basis[3]).determinant() < 0.0 {
+        basis[3] = -basis[3];
+    }
+    basis[N..].try_into().unwrap()
+}
+
+/// Location of a sticker on the solved puzzle. Sticker `i` starts in slot `i`.
+#[derive(Debug, Clone)]
+struct Slot {
+    /// Facet containing the slot.
+    facet: usize,
+    /// Bitmask of facets whose cuts the slot's piece is beyond.
+    piece_faces: u8,
+    verts: Vec<Vector4<f32>>,
+    /// Vertex indices of each polygon, counterclockwise when seen from outside
+    /// the sticker in the facet's basis.
+    polygons: Vec<Box<[u16]>>,
+    center: Vector4<f32>,
+    /// Index of the facet vertex closest to the slot.
+    nearest_vertex: usize,
+}
+impl Slot {
+    fn new(facet: usize, piece_faces: &[usize]) -> Self {
+        let center = facet_center(facet);
+        let basis: [Vector4<f32>; 3] = complete_basis([facet_normal(facet)]);
+        let to_local = |v: Vector4<f32>| {
+            let v = v - center;
+            vec3(v.dot(basis[0]), v.dot(basis[1]), v.dot(basis[2]))
+        };
+        let to_global = |v: Vector3<f32>| center + basis[0] * v.x + basis[1] * v.y + basis[2] * v.z;
+
+        let facet_verts = other_facets(facet)
+            .map(|g| to_local(vertex(g)))
+            .collect_vec();
+        let mut polyhedron = Polyhedron::tetrahedron(facet_verts.clone().try_into().unwrap());
+        for g in other_facets(facet) {
+            // Within the facet, the distance along the other facet's normal
+            // is `local.dot(m) + offset`.
+            let normal = facet_normal(g);
+            let m = vec3(
+                basis[0].dot(normal),
+                basis[1].dot(normal),
+                basis[2].dot(normal),
+            );
+            let offset = center.dot(normal);
+            let cut = CUT_DEPTH / 4.0;
+            polyhedron = if piece_faces.contains(&g) {
+                polyhedron.clip(-m, offset - cut)
+            } else {
+                polyhedron.clip(m, cut - offset)
+            };
+        }
+
+        let mut local_verts: Vec<Vector3<f32>> = vec![];
+        let polygons = polyhedron
+            .0
+            .iter()
+            .map(|polygon| {
+                polygon
+                    .iter()
+                    .map(|&v| {
+                        let i = local_verts
+                            .iter()
+                            .position(|&other| other.distance(v) < EPSILON)
+                            .unwrap_or_else(|| {
+                                local_verts.push(v);
+                                local_verts.len() - 1
+                            });
+                        i as u16
+                    })
+                    .collect()
+            })
+            .collect();
+        let local_center =
+            local_verts.iter().fold(Vector3::zero(), |a, &b| a + b) / local_verts.len() as f32;
+        let nearest_vertex = facet_verts
+            .iter()
+            .position_min_by(|a, b| {
+                a.distance(local_center)
+                    .total_cmp(&b.distance(local_center))
+            })
+            .unwrap_or(0);
+
+        Self {
+            facet,
+            piece_faces: piece_faces.iter().fold(0, |a, &f| a | 1 << f),
+            verts: local_verts.iter().map(|&v| to_global(v)).collect(),
+            polygons,
+            center: to_global(local_center),
+            nearest_vertex,
+        }
+    }
+}
+
+/// Convex polyhedron, stored as a list of polygons that are counterclockwise
+/// when seen from outside.
+#[derive(Debug, Clone)]
+struct Polyhedron(Vec<Vec<Vector3<f32>>>);
+impl Polyhedron {
+    fn tetrahedron(verts: [Vector3<f32>; 4]) -> Self {
+        Self(
+            (0..4)
+                .map(|i| {
+                    let mut polygon = (0..4).filter(|&j| j != i).map(|j| verts[j]).collect_vec();
+                    let normal = (polygon[1] - polygon[0]).cross(polygon[2] - polygon[0]);
+                    if normal.dot(polygon[0] - verts[i]) < 0.0 {
+                        polygon.reverse();
+                    }
+                    polygon
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the part of the polyhedron where `v.dot(normal) <= distance`.
+    fn clip(&self, normal: Vector3<f32>, distance: f32) -> Self {
+        let side = |v: Vector3<f32>| v.dot(normal) - distance;
+
+        let mut polygons = vec![];
+        let mut cap: Vec<Vector3<f32>> = vec![];
+        for polygon in &self.0 {
+            let mut new_polygon = vec![];
+            for (a, b) in polygon.iter().copied().circular_tuple_windows() {
+                let (da, db) = (side(a), side(b));
+                if da <= EPSILON {
+                    new_polygon.push(a);
+                }
+                if (da < -EPSILON && db > EPSILON) || (da > EPSILON && db < -EPSILON) {
+                    new_polygon.push(a + (b - a) * (da / (da - db)));
+                }
+            }
+            for &v in &new_polygon {
+                if side(v).abs() <= EPSILON && !cap.iter().any(|&c| c.distance(v) < EPSILON) {
+                    cap.push(v);
+                }
+            }
+            if new_polygon.len() >= 3 {
+                polygons.push(new_polygon);
+            }
+        }
+
+        // Add a polygon where the plane cut the polyhedron, sorted
+        // counterclockwise around the normal.
+        if cap.len() >= 3 {
+            let center = cap.iter().fold(Vector3::zero(), |a, &b| a + b) / cap.len() as f32;
+            let u = (cap[0] - center).normalize();
+            let v = normal.normalize().cross(u);
+            let angle = |p: Vector3<f32>| f32::atan2((p - center).dot(v), (p - center).dot(u));
+            cap.sort_by(|&a, &b| angle(a).total_cmp(&angle(b)));
+            polygons.push(cap);
+        }
+
+        Self(polygons)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Simplex4D {
+    desc: &'static Simplex4DDescription,
+    /// Slot that each sticker is currently in.
+    sticker_slots: Box<[u8]>,
+}
+impl Eq for Simplex4D {}
+impl PartialEq for Simplex4D {
+    fn eq(&self, other: &Self) -> bool {
+        self.sticker_slots == other.sticker_slots
+    }
+}
+impl PuzzleState for Simplex4D {
+    fn twist(&mut self, twist: Twist) -> Result<(), &'static str> {
+        let desc = self.desc;
+        let perm = desc
+            .perms
+            .get(twist.axis.0 as usize)
+            .ok_or("invalid twist axis")?
+            .get(twist.direction.0 as usize)
+            .ok_or("invalid twist direction")?;
+        for piece in self.pieces_affected_by_twist(twist) {
+            for &sticker in &desc.pieces[piece.0 as usize].stickers {
+                let slot = &mut self.sticker_slots[sticker.0 as usize];
+                *slot = perm[*slot as usize];
+            }
+        }
+        Ok(())
+    }
+    fn layer_from_twist_axis(&self, twist_axis: TwistAxis, piece: Piece) -> u8 {
+        let sticker = self.info(piece).stickers[0];
+        let piece_faces = self.slot(sticker).piece_faces;
+        if piece_faces & (1 << twist_axis.0) != 0 {
+            0
+        } else {
+            1
+        }
+    }
+
+    fn rotation_candidates(&self) -> Vec<(Vec<Twist>, Quaternion<f32>)> {
+        let layers = self.all_layers();
+
+        // Twisting the whole puzzle on the E facet rotates only the XYZ axes.
+        let axis = TwistAxis(4);
+        self.desc.rotations[axis.0 as usize]
+            .iter()
+            .enumerate()
+            .map(|(i, rot)| {
+                let twist = Twist {
+                    axis,
+                    direction: TwistDirection(i as _),
+                    layers,
+                };
+                let col = |v: Vector4<f32>| rot.apply(v, 1.0).truncate();
+                let matrix = Matrix3::from_cols(
+                    col(Vector4::unit_x()),
+                    col(Vector4::unit_y()),
+                    col(Vector4::unit_z()),
+                );
+                (vec![twist], Quaternion::from(matrix))
+            })
+            .collect()
+    }
+
+    fn sticker_geometry(
+        &self,
+        sticker: Sticker,
+        p: StickerGeometryParams,
+    ) -> Option<StickerGeometry> {
+        let piece = self.info(sticker).piece;
+        let slot = self.slot(sticker);
+
+        let mut animation = None;
+        if let Some((twist, progress)) = p.twist_animation {
+            if self.is_piece_affected_by_twist(twist, piece) {
+                let rot = self.desc.rotations[twist.axis.0 as usize][twist.direction.0 as usize];
+                animation = Some((rot, progress));
+            }
+        }
+
+        // Shrink the facet toward its center and the sticker toward its
+        // center to leave space between them.
+        let facet_center = facet_center(slot.facet);
+        let facet_scale = 1.0 - p.face_spacing;
+        let sticker_scale = (1.0 - p.sticker_spacing).max(0.0);
+        let verts: Vec<Point3<f32>> = slot
+            .verts
+            .iter()
+            .map(|&v| {
+                let v = slot.center + (v - slot.center) * sticker_scale;
+                let mut v = facet_center + (v - facet_center) * facet_scale;
+                if let Some((rot, progress)) = animation {
+                    v = rot.apply(v, progress);
+                }
+                Some(p.view_transform.transform_point(p.project_4d(v)?))
+            })
+            .collect::<Option<_>>()?;
+
+        // Only show this sticker if its projected volume is positive. (Cull it
+        // if its projected volume is negative.)
+        let mut volume = 0.0;
+        for polygon in &slot.polygons {
+            let v = |i: u16| verts[i as usize] - verts[0];
+            for (&b, &c) in polygon[1..].iter().tuple_windows() {
+                volume += v(polygon[0]).dot(v(b).cross(v(c)));
+            }
+        }
+        if volume <= 0.0 {
+            return None;
+        }
+
+        // Clicking a sticker twists its facet around the nearest vertex.
+        let cw_twist = Twist {
+            axis: TwistAxis(slot.facet as _),
+            direction: TwistDirection(slot.nearest_vertex as u8 * 2),
+            layers: LayerMask::default(),
+        };
+        let twists = ClickTwists {
+            cw: Some(cw_twist),
+            ccw: Some(self.reverse_twist(cw_twist)),
+            recenter: None,
+        };
+
+        // Front faces are wound clockwise when seen from outside.
+        Some(StickerGeometry {
+            verts,
+            polygon_indices: slot
+                .polygons
+                .iter()
+                .map(|polygon| polygon.iter().rev().copied().collect())
+                .collect(),
+            polygon_twists: vec![twists; slot.polygons.len()],
+        })
+    }
+    fn sticker_facet(&self, sticker: Sticker) -> Option<Face> {
+        Some(Face(self.slot(sticker).facet as _))
+    }
+
+    fn is_solved(&self) -> bool {
+        let mut color_per_facet = vec![None; FACE_COUNT];
+        for (i, sticker) in self.stickers().iter().enumerate() {
+            let facet = self.slot(Sticker(i as _)).facet;
+            let color = sticker.color.0 as usize;
+            if color_per_facet[color] == None {
+                color_per_facet[color] = Some(facet);
+            } else if color_per_facet[color] != Some(facet) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn is_solved_in_original_orientation(&self) -> bool {
+        (0..self.stickers().len() as _)
+            .map(Sticker)
+            .all(|sticker| self.slot(sticker).facet == self.info(sticker).color.0 as usize)
+    }
+}
+#[delegate_to_methods]
+#[delegate(PuzzleType, target_ref = "desc")]
+impl Simplex4D {
+    pub fn new() -> Self {
+        let desc = &*DESCRIPTION;
+        let sticker_slots = (0..desc.stickers.len() as u8).collect();
+        Self {
+            desc,
+            sticker_slots,
+        }
+    }
+
+    fn desc(&self) -> &Simplex4DDescription {
+        self.desc
+    }
+
+    fn slot(&self, sticker: Sticker) -> &Slot {
+        &self.desc.slots[self.sticker_slots[sticker.0 as usize] as usize]
+    }
+}
+impl Default for Simplex4D {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simplex_4d_twist_serialization() {
+        let p = Simplex4D::new();
+        crate::puzzle::tests::test_twist_serialization(&p);
+        crate::puzzle::tests::test_layered_twist_serialization(&p);
+    }
+
+    #[test]
+    fn test_simplex_4d_twist_period() {
+        let mut p = Simplex4D::new();
+        assert_eq!(p.pieces().len(), 30);
+        assert_eq!(p.stickers().len(), 75);
+
+        for direction in (0..p.twist_directions().len() as u8).map(TwistDirection) {
+            let period = if direction.0 < VERTEX_TWIST_DIRECTIONS {
+                3
+            } else {
+                2
+            };
+            let twist = Twist {
+                axis: TwistAxis(0),
+                direction,
+                layers: LayerMask::default(),
+            };
+            for i in 1..=period {
+                p.twist(twist).unwrap();
+                assert_eq!(p.is_solved(), i == period);
+            }
+        }
+
+        let dirs = [TwistDirection(0), TwistDirection(0)];
+        let chained = p.chain_twist_directions(TwistAxis(0), &dirs);
+        assert_eq!(chained, Some(TwistDirection(1)));
+    }
+}
@@ -0,0 +1,78 @@
+//! Search for short twist sequences that reach a goal.
+
+use super::{traits::*, Twist};
+
+/// Returns the shortest sequence of at most `max_depth` twists from `moves`
+/// that brings `puzzle` to a state satisfying `goal`, or `None` if there is
+/// none.
+///
+/// This is an iterative deepening search, so it is only practical for short
+/// sequences. Twists of the same axis are never repeated consecutively, and
+/// twists of opposite axes are only tried in one order.
+pub fn search<P: PuzzleState + Clone>(
+    puzzle: &P,
+    moves: &[Twist],
+    max_depth: usize,
+    goal: impl Fn(&P) -> bool,
+) -> Option<Vec<Twist>> {
+    let mut path = vec![];
+    (0..=max_depth).find_map(|depth| {
+        search_depth(puzzle, moves, depth, &goal, &mut path).then(|| path.clone())
+    })
+}
+
+fn search_depth<P: PuzzleState + Clone>(
+    puzzle: &P,
+    moves: &[Twist],
+    depth: usize,
+    goal: &impl Fn(&P) -> bool,
+    path: &mut Vec<Twist>,
+) -> bool {
+    if depth == 0 {
+        return goal(puzzle);
+    }
+    let prev = path.last().copied();
+    for &twist in moves {
+        if let Some(prev) = prev {
+            if prev.axis == twist.axis
+                || puzzle.opposite_twist_axis(twist.axis) == Some(prev.axis)
+                    && prev.axis.0 > twist.axis.0
+            {
+                continue;
+            }
+        }
+        let mut next = puzzle.clone();
+        if next.twist(twist).is_err() {
+            continue;
+        }
+        path.push(twist);
+        if search_depth(&next, moves, depth - 1, goal, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::Rubiks3D;
+
+    #[test]
+    fn test_search() {
+        let mut p = Rubiks3D::new(3);
+        let notation = p.notation_scheme().clone();
+        let moves: Vec<Twist> = ["R", "R'", "R2", "U", "U'", "U2", "F", "F'", "F2"]
+            .into_iter()
+            .map(|s| notation.parse_twist(s).unwrap())
+            .collect();
+        for s in ["R", "U2", "F'"] {
+            p.twist(notation.parse_twist(s).unwrap()).unwrap();
+        }
+
+        let solution = search(&p, &moves, 4, |p| p.is_solved()).unwrap();
+        assert_eq!(solution.len(), 3);
+        assert_eq!(search(&p, &moves, 2, |p| p.is_solved()), None);
+    }
+}
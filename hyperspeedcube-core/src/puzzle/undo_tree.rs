@@ -0,0 +1,385 @@
+//! Branching undo history.
+
+use itertools::Itertools;
+
+use super::{NotationScheme, Twist};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HistoryEntry {
+    Twist(Twist),
+}
+impl From<Twist> for HistoryEntry {
+    fn from(twist: Twist) -> Self {
+        Self::Twist(twist)
+    }
+}
+impl HistoryEntry {
+    pub fn twist(self) -> Option<Twist> {
+        match self {
+            HistoryEntry::Twist(twist) => Some(twist),
+        }
+    }
+    pub fn to_string(self, notation: &NotationScheme) -> String {
+        match self {
+            HistoryEntry::Twist(twist) => notation.twist_to_string(twist),
+        }
+    }
+}
+
+/// Index of a node in an [`UndoTree`].
+pub type UndoNodeId = usize;
+
+/// Branching undo history. Making a new move after undoing starts a new branch
+/// instead of discarding the moves that were undone, so any earlier state can
+/// be revisited.
+#[derive(Debug, Clone)]
+pub struct UndoTree {
+    /// All nodes in the tree. The first node is the root, which represents the
+    /// state before any entries were applied.
+    nodes: Vec<UndoNode>,
+    /// Current node.
+    current: UndoNodeId,
+    /// Entries from the root to the current node.
+    path: Vec<HistoryEntry>,
+}
+impl Default for UndoTree {
+    fn default() -> Self {
+        Self {
+            nodes: vec![UndoNode::default()],
+            current: Self::ROOT,
+            path: vec![],
+        }
+    }
+}
+impl UndoTree {
+    /// Root node, which has no entry.
+    pub const ROOT: UndoNodeId = 0;
+
+    /// Returns the entries from the root to the current node.
+    pub fn path(&self) -> &[HistoryEntry] {
+        &self.path
+    }
+    /// Consumes the tree and returns the entries from the root to the current
+    /// node.
+    pub fn into_path(self) -> Vec<HistoryEntry> {
+        self.path
+    }
+    /// Returns the nodes from the root to the current node, so that the node
+    /// at index `i` is the state after the first `i` entries of the path.
+    pub fn path_nodes(&self) -> Vec<UndoNodeId> {
+        let mut ret: Vec<UndoNodeId> =
+            std::iter::successors(Some(self.current), |&n| self.nodes[n].parent).collect();
+        ret.reverse();
+        ret
+    }
+    /// Returns the entries that would be reapplied by redoing repeatedly.
+    pub fn redo_path(&self) -> Vec<HistoryEntry> {
+        std::iter::successors(self.nodes[self.current].redo_child, |&n| {
+            self.nodes[n].redo_child
+        })
+        .filter_map(|n| self.nodes[n].entry)
+        .collect()
+    }
+
+    /// Returns the number of nodes in the tree, including the root. Nodes are
+    /// numbered in the order they were added.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+    /// Returns the current node.
+    pub fn current(&self) -> UndoNodeId {
+        self.current
+    }
+    /// Returns the entry that leads to a node, or `None` for the root.
+    pub fn entry(&self, node: UndoNodeId) -> Option<HistoryEntry> {
+        self.nodes[node].entry
+    }
+    /// Returns the parent of a node, or `None` for the root.
+    pub fn parent(&self, node: UndoNodeId) -> Option<UndoNodeId> {
+        self.nodes[node].parent
+    }
+    /// Returns the children of a node, oldest first.
+    pub fn children(&self, node: UndoNodeId) -> &[UndoNodeId] {
+        &self.nodes[node].children
+    }
+    /// Returns the child of a node that redo would move to.
+    pub fn redo_child(&self, node: UndoNodeId) -> Option<UndoNodeId> {
+        self.nodes[node].redo_child
+    }
+    /// Returns the annotation on a node, which is empty if there is none.
+    pub fn annotation(&self, node: UndoNodeId) -> &str {
+        &self.nodes[node].annotation
+    }
+    /// Sets the annotation on a node. An empty string removes it.
+    pub fn set_annotation(&mut self, node: UndoNodeId, annotation: String) {
+        self.nodes[node].annotation = annotation;
+    }
+    /// Returns whether `ancestor` is `node` or one of its ancestors.
+    pub fn is_ancestor(&self, ancestor: UndoNodeId, node: UndoNodeId) -> bool {
+        std::iter::successors(Some(node), |&n| self.nodes[n].parent).any(|n| n == ancestor)
+    }
+
+    /// Returns whether there is an entry to undo.
+    pub fn has_undo(&self) -> bool {
+        self.current != Self::ROOT
+    }
+    /// Returns whether there is an entry to redo.
+    pub fn has_redo(&self) -> bool {
+        self.nodes[self.current].redo_child.is_some()
+    }
+
+    /// Adds an entry after the current node and moves to it. If the same entry
+    /// was already made from this node, that branch is reused.
+    pub fn push(&mut self, entry: HistoryEntry) {
+        let child = self.add_child(self.current, entry);
+        self.nodes[self.current].redo_child = Some(child);
+        self.current = child;
+        self.path.push(entry);
+    }
+    /// Adds an entry after `parent` without moving to it and returns the new
+    /// node. If the same entry was already made from `parent`, that node is
+    /// returned instead. Redoing from `parent` only leads to the new node if
+    /// there was nothing to redo before.
+    pub fn add_child(&mut self, parent: UndoNodeId, entry: HistoryEntry) -> UndoNodeId {
+        let existing = self.nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .find(|&child| self.nodes[child].entry == Some(entry));
+        existing.unwrap_or_else(|| {
+            let id = self.nodes.len();
+            self.nodes.push(UndoNode {
+                entry: Some(entry),
+                parent: Some(parent),
+                children: vec![],
+                redo_child: None,
+                annotation: String::new(),
+            });
+            let parent = &mut self.nodes[parent];
+            parent.children.push(id);
+            parent.redo_child.get_or_insert(id);
+            id
+        })
+    }
+
+    /// Splits the parts of the tree that are not on the path to the current
+    /// node into branches. Each branch starts with the node that it leaves
+    /// from, which is on the path or on an earlier branch, followed by the
+    /// nodes along it. Branches follow the child that redo would move to, and
+    /// the branch that redo would follow from a node comes before any others
+    /// from that node.
+    pub fn side_branches(&self) -> Vec<Vec<UndoNodeId>> {
+        let path_nodes = self.path_nodes();
+        // Nodes to split branches off of, along with the child that continues
+        // the path or branch that they are on.
+        let mut pending = path_nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| (node, path_nodes.get(i + 1).copied()))
+            .collect_vec();
+
+        let mut ret = vec![];
+        let mut i = 0;
+        while let Some(&(node, next)) = pending.get(i) {
+            i += 1;
+            let children = self.nodes[node]
+                .redo_child
+                .into_iter()
+                .chain(self.nodes[node].children.iter().copied())
+                .unique()
+                .filter(|&child| Some(child) != next);
+            for child in children {
+                let mut branch = vec![node];
+                let mut n = Some(child);
+                while let Some(node) = n {
+                    branch.push(node);
+                    n = self.nodes[node].redo_child;
+                    pending.push((node, n));
+                }
+                ret.push(branch);
+            }
+        }
+        ret
+    }
+    /// Moves to the parent of the current node and returns the entry that
+    /// must be reversed, or `None` if already at the root.
+    pub fn undo(&mut self) -> Option<HistoryEntry> {
+        let node = &self.nodes[self.current];
+        let parent = node.parent?;
+        let entry = node.entry;
+        self.nodes[parent].redo_child = Some(self.current);
+        self.current = parent;
+        self.path.pop();
+        entry
+    }
+    /// Moves to the most recently visited child of the current node and
+    /// returns the entry that must be applied, or `None` if there is nothing to
+    /// redo.
+    pub fn redo(&mut self) -> Option<HistoryEntry> {
+        let child = self.nodes[self.current].redo_child?;
+        let entry = self.nodes[child].entry?;
+        self.current = child;
+        self.path.push(entry);
+        Some(entry)
+    }
+
+    /// Removes all nodes added after the first `len`. The current node must
+    /// not be one of them.
+    pub fn truncate(&mut self, len: usize) {
+        assert!(self.current < len, "cannot remove current undo node");
+        self.nodes.truncate(len);
+        for node in &mut self.nodes {
+            node.children.retain(|&child| child < len);
+            if node.redo_child.map_or(false, |child| child >= len) {
+                node.redo_child = node.children.last().copied();
+            }
+        }
+    }
+
+    /// Makes redo lead toward `node` from each of its ancestors.
+    pub fn select_branch(&mut self, node: UndoNodeId) {
+        let mut child = node;
+        while let Some(parent) = self.nodes[child].parent {
+            self.nodes[parent].redo_child = Some(child);
+            child = parent;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct UndoNode {
+    /// Entry that leads from the parent to this node.
+    entry: Option<HistoryEntry>,
+    parent: Option<UndoNodeId>,
+    children: Vec<UndoNodeId>,
+    /// Child to move to when redoing, which is the one most recently visited.
+    redo_child: Option<UndoNodeId>,
+    /// User comment on the state at this node, such as "start of OLL".
+    annotation: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::{LayerMask, Twist, TwistAxis, TwistDirection};
+
+    fn entry(axis: u8) -> HistoryEntry {
+        HistoryEntry::Twist(Twist {
+            axis: TwistAxis(axis),
+            direction: TwistDirection(0),
+            layers: LayerMask(1),
+        })
+    }
+
+    #[test]
+    fn test_undo_tree_keeps_branches() {
+        let mut tree = UndoTree::default();
+        tree.push(entry(0));
+        tree.push(entry(1));
+        let old_branch = tree.current();
+
+        assert_eq!(tree.undo(), Some(entry(1)));
+        tree.push(entry(2));
+        assert_eq!(tree.path(), &[entry(0), entry(2)]);
+        assert!(!tree.has_redo());
+
+        // The old branch still exists and can be redone after selecting it.
+        assert_eq!(tree.undo(), Some(entry(2)));
+        tree.select_branch(old_branch);
+        assert_eq!(tree.redo(), Some(entry(1)));
+        assert_eq!(tree.current(), old_branch);
+        assert_eq!(tree.path(), &[entry(0), entry(1)]);
+
+        // Pushing an existing entry reuses its node.
+        assert_eq!(tree.undo(), Some(entry(1)));
+        tree.push(entry(1));
+        assert_eq!(tree.current(), old_branch);
+    }
+
+    #[test]
+    fn test_undo_tree_root() {
+        let mut tree = UndoTree::default();
+        assert!(!tree.has_undo());
+        assert_eq!(tree.undo(), None);
+        assert_eq!(tree.redo(), None);
+
+        tree.push(entry(0));
+        assert!(tree.is_ancestor(UndoTree::ROOT, tree.current()));
+        assert!(!tree.is_ancestor(tree.current(), UndoTree::ROOT));
+        assert_eq!(tree.undo(), Some(entry(0)));
+        assert_eq!(tree.redo_path(), vec![entry(0)]);
+    }
+
+    #[test]
+    fn test_undo_tree_truncate() {
+        let mut tree = UndoTree::default();
+        tree.push(entry(0));
+        let len = tree.node_count();
+        tree.push(entry(1));
+        tree.undo();
+        tree.truncate(len);
+        assert!(!tree.has_redo());
+        assert!(tree.children(tree.current()).is_empty());
+
+        tree.push(entry(2));
+        assert_eq!(tree.path(), &[entry(0), entry(2)]);
+    }
+
+    #[test]
+    fn test_undo_tree_side_branches() {
+        let mut tree = UndoTree::default();
+        tree.push(entry(0));
+        tree.push(entry(1));
+        tree.push(entry(2));
+        let a = tree.current();
+        tree.undo();
+        tree.push(entry(3));
+        tree.undo();
+        tree.undo();
+        tree.push(entry(4));
+        tree.undo();
+        tree.undo();
+        assert!(tree.path().is_empty());
+
+        let root = UndoTree::ROOT;
+        let n0 = tree.children(root)[0];
+        let n1 = tree.children(n0)[0];
+        let n3 = tree.children(n1)[1];
+        let n4 = tree.children(n0)[1];
+        assert_eq!(
+            tree.side_branches(),
+            vec![vec![root, n0, n4], vec![n0, n1, n3], vec![n1, a]],
+        );
+
+        // Rebuild the tree from its branches.
+        let mut copy = UndoTree::default();
+        let mut copied_nodes = std::collections::HashMap::from([(root, root)]);
+        for branch in tree.side_branches() {
+            let mut parent = copied_nodes[&branch[0]];
+            for &node in &branch[1..] {
+                parent = copy.add_child(parent, tree.entry(node).unwrap());
+                copied_nodes.insert(node, parent);
+            }
+        }
+        assert_eq!(copy.node_count(), tree.node_count());
+        assert_eq!(copy.redo_path(), tree.redo_path());
+        assert_eq!(copy.side_branches().len(), 3);
+    }
+
+    #[test]
+    fn test_undo_tree_annotations() {
+        let mut tree = UndoTree::default();
+        tree.push(entry(0));
+        tree.push(entry(1));
+        let node = tree.current();
+        tree.set_annotation(node, "setup".to_string());
+        assert_eq!(tree.path_nodes().len(), 3);
+        assert_eq!(tree.path_nodes()[2], node);
+
+        // Annotations stay on their node when it is undone and redone.
+        tree.undo();
+        tree.push(entry(1));
+        assert_eq!(tree.annotation(tree.current()), "setup");
+        assert_eq!(tree.annotation(UndoTree::ROOT), "");
+    }
+}
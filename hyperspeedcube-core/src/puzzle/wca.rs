@@ -0,0 +1,95 @@
+//! Checks that scrambles of WCA puzzles follow the WCA regulations.
+
+use super::{
+    solver, traits::*, LayerMask, Puzzle, PuzzleTypeEnum, Twist, TwistAxis, TwistDirection,
+};
+
+/// Minimum number of twists needed to solve a scramble of a WCA puzzle.
+pub const MIN_SCRAMBLE_DISTANCE: usize = 2;
+
+/// Returns whether a puzzle is used in WCA competitions.
+pub fn is_wca_puzzle(ty: PuzzleTypeEnum) -> bool {
+    match ty {
+        PuzzleTypeEnum::Rubiks3D { layer_count } => (2..=7).contains(&layer_count),
+        PuzzleTypeEnum::Clock => true,
+        _ => false,
+    }
+}
+
+/// Returns whether `twist` trivially cancels with the twists before it,
+/// either because it is on the same axis as the previous twist or because it
+/// is on the same axis as the twist before that with only the opposite axis
+/// twisted in between.
+pub fn is_redundant_twist(ty: &impl PuzzleType, previous: &[Twist], twist: Twist) -> bool {
+    match previous {
+        [.., prev2, prev1] => {
+            prev1.axis == twist.axis
+                || prev2.axis == twist.axis
+                    && ty.opposite_twist_axis(twist.axis) == Some(prev1.axis)
+        }
+        [prev1] => prev1.axis == twist.axis,
+        [] => false,
+    }
+}
+
+/// Checks that a scramble has no trivially cancelling twists and can't be
+/// solved in fewer than [`MIN_SCRAMBLE_DISTANCE`] twists.
+pub fn verify_scramble(ty: PuzzleTypeEnum, scramble: &[Twist]) -> Result<(), &'static str> {
+    for i in 0..scramble.len() {
+        if is_redundant_twist(&ty, &scramble[..i], scramble[i]) {
+            return Err("Scramble has trivially cancelling twists");
+        }
+    }
+
+    let mut puzzle = Puzzle::new(ty);
+    for &twist in scramble {
+        puzzle.twist(twist)?;
+    }
+    let twists = all_twists(&puzzle);
+    let max_depth = MIN_SCRAMBLE_DISTANCE - 1;
+    if solver::search(&puzzle, &twists, max_depth, |p| p.is_solved()).is_some() {
+        return Err("Scramble is too close to solved");
+    }
+    Ok(())
+}
+
+/// Returns every twist of a puzzle, with any layer mask.
+fn all_twists(ty: &impl PuzzleType) -> Vec<Twist> {
+    let mut ret = vec![];
+    for axis in 0..ty.twist_axes().len() as _ {
+        for direction in 0..ty.twist_directions().len() as _ {
+            for layers in 1..1 << ty.layer_count() {
+                ret.push(Twist {
+                    axis: TwistAxis(axis),
+                    direction: TwistDirection(direction),
+                    layers: LayerMask(layers),
+                });
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let notation = ty.notation_scheme();
+        let parse = |s: &str| {
+            s.split_whitespace()
+                .map(|t| notation.parse_twist(t).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(verify_scramble(ty, &parse("R U F' L2 D B")).is_ok());
+        // Solvable in one twist.
+        assert!(verify_scramble(ty, &parse("R")).is_err());
+        // Same axis twice in a row.
+        assert!(verify_scramble(ty, &parse("R U U2 F")).is_err());
+        // Same axis on either side of the opposite axis.
+        assert!(verify_scramble(ty, &parse("R L R2 F")).is_err());
+    }
+}
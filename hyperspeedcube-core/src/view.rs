@@ -17,6 +17,10 @@ pub struct ViewPreferences {
     pub fov_3d: f32,
     /// 4D FOV, in degrees.
     pub fov_4d: f32,
+    /// Distance of the 3D camera beyond its usual position, relative to the
+    /// size of the puzzle. Larger values make the puzzle look smaller and
+    /// less distorted by perspective.
+    pub camera_distance: f32,
 
     /// Horizontal alignment, from -1.0 to +1.0.
     pub align_h: f32,
@@ -26,6 +30,10 @@ pub struct ViewPreferences {
     pub show_frontfaces: bool,
     pub show_backfaces: bool,
     pub clip_4d: bool,
+    /// Whether to unfold the puzzle into a net instead of projecting it.
+    pub net: bool,
+    /// Whether to draw a label near the center of each face.
+    pub face_labels: bool,
 
     pub face_spacing: f32,
     pub sticker_spacing: f32,
@@ -47,6 +55,7 @@ impl Default for ViewPreferences {
             scale: 1.0,
             fov_3d: 30_f32,
             fov_4d: 30_f32,
+            camera_distance: 0.0,
 
             align_h: 0.0,
             align_v: 0.0,
@@ -57,6 +66,8 @@ impl Default for ViewPreferences {
             show_frontfaces: true,
             show_backfaces: true,
             clip_4d: true,
+            net: false,
+            face_labels: false,
 
             outline_thickness: 1.0,
 
@@ -88,6 +99,7 @@ impl ViewPreferences {
             scale: crate::util::mix(self.scale, rhs.scale, t),
             fov_3d: crate::util::mix(self.fov_3d, rhs.fov_3d, t),
             fov_4d: crate::util::mix(self.fov_4d, rhs.fov_4d, t),
+            camera_distance: crate::util::mix(self.camera_distance, rhs.camera_distance, t),
             align_h: crate::util::mix(self.align_h, rhs.align_h, t),
             align_v: crate::util::mix(self.align_v, rhs.align_v, t),
             show_frontfaces: if t < 0.5 {
@@ -101,6 +113,12 @@ impl ViewPreferences {
                 rhs.show_backfaces
             },
             clip_4d: if t < 0.5 { self.clip_4d } else { rhs.clip_4d },
+            net: if t < 0.5 { self.net } else { rhs.net },
+            face_labels: if t < 0.5 {
+                self.face_labels
+            } else {
+                rhs.face_labels
+            },
             face_spacing: crate::util::mix(self.face_spacing, rhs.face_spacing, t),
             sticker_spacing: crate::util::mix(self.sticker_spacing, rhs.sticker_spacing, t),
             outline_thickness: crate::util::mix(self.outline_thickness, rhs.outline_thickness, t),
@@ -0,0 +1,318 @@
+## Menüleiste
+
+menu-file = Datei
+menu-file-open = Öffnen...
+menu-file-open-clipboard = Aus Zwischenablage öffnen
+menu-file-save = Speichern
+menu-file-save-as = Speichern unter...
+menu-file-screenshot = Bildschirmfoto
+menu-file-copy-hsc = Kopieren (.hsc)
+menu-file-copy-hsc-title = Hyperspeedcube-Logdatei (empfohlen)
+menu-file-copy-hsc-description = Enthält zusätzliche Metadaten wie die Zuganzahl
+menu-file-copy-mc4d = Kopieren (.log)
+menu-file-copy-mc4d-title = MC4D-kompatible Logdatei
+menu-file-copy-mc4d-description = Abwärtskompatibel mit Magic Cube 4D
+menu-file-exit = Beenden
+
+menu-edit = Bearbeiten
+menu-edit-undo = Drehung rückgängig machen
+menu-edit-redo = Drehung wiederholen
+menu-edit-add-checkpoint = Checkpoint hinzufügen
+menu-edit-revert-checkpoint = Zum letzten Checkpoint zurückkehren
+menu-edit-explore = Erkunden starten
+menu-edit-discard-exploration = Erkundung verwerfen
+menu-edit-keep-exploration = Erkundung behalten
+menu-edit-flush-queue = Wartende Drehungen abschließen
+menu-edit-pause-queue = Drehanimationen pausieren
+menu-edit-resume-queue = Drehanimationen fortsetzen
+menu-edit-clear-queue = Wartende Drehungen zurücknehmen
+menu-edit-reset = Puzzle zurücksetzen
+
+menu-scramble = Verdrehen
+menu-scramble-full = Vollständig
+menu-scramble-next = Nächste
+menu-scramble-redo = Letzte wiederholen
+menu-scramble-daily = Tägliche Herausforderung
+menu-scramble-settings = Einstellungen
+
+menu-puzzle = Puzzle
+
+menu-settings = Einstellungen
+menu-settings-clipboard-fallback = Ersatz-Zwischenablage verwenden
+
+menu-tools = Werkzeuge
+menu-tools-mini-mode = Minimodus
+
+menu-help = Hilfe
+menu-help-command-palette = Befehlspalette
+menu-help-tutorial = Einführung
+
+menu-download = Vollversion herunterladen
+
+## Fenster
+
+window-about = Über
+window-appearance = Darstellung
+window-camera-keyframes = Kamera-Keyframes
+window-command-palette = Befehlspalette
+window-debug-values = Debug-Werte
+window-external-tools = Externe Werkzeuge
+window-fewest-moves = Wenigste Züge
+window-global-keybinds = Globale Tastenbelegung
+window-hints = Hinweise
+window-history-tree = Verlaufsbaum
+window-interaction = Interaktion
+window-keybind-sets = Tastenbelegungssätze
+window-keybinds-reference = Tastenbelegungsübersicht
+window-log-console = Protokollkonsole
+window-metronome = Metronom
+window-modifier-keys = Modifikatortasten
+window-mousebinds = Mausbelegung
+window-move-count = Zuganzahl
+window-notifications = Benachrichtigungen
+window-piece-filters = Teilefilter
+window-progress = Fortschritt
+window-puzzle-controls = Puzzle-Steuerung
+window-puzzle-keybinds = Puzzle-Tastenbelegung
+window-race = Rennen
+window-scripts = Skripte
+window-solve-history = Lösungsverlauf
+window-spectate = Zuschauen
+window-statistics = Statistik
+window-sync = Synchronisierung
+window-timer = Timer
+window-upcoming-scrambles = Kommende Verdrehungen
+window-view = Ansicht
+window-welcome = Willkommen
+
+## Statusleiste
+
+status-blindfold = Blind
+status-blindfold-title = Blindmodus
+status-blindfold-description = Blendet die Stickerfarben aus
+status-exploring = Erkunden
+status-exploring-description = Drehungen beim Erkunden zählen nicht zur Lösung und können im Menü „Bearbeiten“ verworfen werden
+status-inverse = Invers
+status-inverse-title = Inverses Verdrehen
+status-inverse-description = Zeigt die Umkehrung der Verdrehung. Drehungen auf der anderen Seite werden als Vorzüge angewendet.
+status-queue = Warteschlange: { $count }
+status-queue-description = Anzahl der Drehungen, die noch animiert werden. Klicken zum Pausieren oder Fortsetzen, Rechtsklick zum Abschließen oder Zurücknehmen wartender Drehungen.
+status-fps = { $fps } FPS
+status-timer-description = Klicken, um das Timer-Fenster anzuzeigen
+
+## Einstellungen
+
+prefs-colors = Farben
+prefs-outlines = Umrisse
+prefs-opacity = Deckkraft
+prefs-performance = Leistung
+prefs-scramble = Verdrehen
+prefs-sounds = Klänge
+prefs-screenshots = Bildschirmfotos
+prefs-mini-mode = Minimodus
+prefs-layout = Layout
+prefs-hotkeys = Globale Tastenkürzel
+
+prefs-language = Sprache
+prefs-theme = Design
+prefs-theme-follow-os = Wie System
+prefs-theme-dark = Dunkel
+prefs-theme-light = Hell
+prefs-accent = Akzentfarbe
+prefs-faces = Seiten
+prefs-special = Sonstiges
+prefs-background = Hintergrund
+prefs-background-style-flat = Einfarbig
+prefs-background-style-gradient = Verlauf
+prefs-background-style-grid = Gitter
+prefs-background-secondary = Zweiter Hintergrund
+prefs-blindfolded-stickers = Sticker im Blindmodus
+prefs-blindfold-mode = Blindmodus
+
+prefs-theme-preview-selected = Ausgewählt
+prefs-theme-preview-unselected = Nicht ausgewählt
+prefs-theme-preview-button = Schaltfläche
+prefs-gfx-fps-limit = FPS-Grenze
+    .title = Bilder pro Sekunde
+    .tooltip = Begrenzt die Bildrate, um Energie zu sparen
+prefs-gfx-idle-mode = Ruhemodus
+    .tooltip = Senkt die Bildrate, wenn sich nichts bewegt und keine Eingabe erfolgt, um Energie zu sparen. Jede Eingabe beendet den Ruhemodus sofort.
+prefs-gfx-idle-fps-limit = FPS-Grenze im Ruhemodus
+prefs-gfx-ui-scale = Oberflächengröße
+    .tooltip = Größe von Text und Bedienelementen im Verhältnis zur Skalierung des Bildschirms
+prefs-gfx-msaa = MSAA
+    .title = Multisample-Kantenglättung
+    .tooltip = Macht Kanten glatter, kann aber die Leistung verschlechtern.
+    .disabled = Multisample-Kantenglättung wird im Web nicht unterstützt.
+prefs-gfx-show-fps = FPS in der Statusleiste anzeigen
+prefs-gfx-perf-hud = Leistungsanzeige einblenden
+    .title = Leistungsanzeige
+    .tooltip = Zeigt, wie lange jeder Schritt der Darstellung dauert, gemittelt über die letzten Bilder.
+prefs-gfx-lod = Winzige Sticker vereinfachen
+    .title = Detailstufe
+    .tooltip = Zeichnet Sticker, die nur wenige Pixel groß sind, als einfache Vierecke ohne Umrisse. Das verbessert die Leistung bei großen Puzzles.
+prefs-gfx-lod-min-size = Minimale Stickergröße
+prefs-gfx-stereo = Stereoskopische Darstellung
+prefs-gfx-stereo-off = Aus
+prefs-gfx-stereo-side-by-side = Nebeneinander
+prefs-gfx-stereo-anaglyph = Rot-Cyan
+prefs-gfx-eye-separation = Augenabstand
+    .tooltip = Wie weit die beiden Ansichten auseinanderliegen. Höhere Werte verstärken die Tiefe.
+prefs-screenshots-resolution = Auflösung
+    .tooltip = Größe der Bildschirmfotos im Verhältnis zur Größe der Puzzleansicht auf dem Bildschirm.
+prefs-screenshots-copy = In die Zwischenablage kopieren
+prefs-screenshots-no-folder = (keiner)
+prefs-screenshots-choose-folder = Ordner wählen …
+prefs-mini-mode-always-on-top = Immer im Vordergrund
+prefs-mini-mode-transparent = Transparenter Hintergrund
+prefs-mini-mode-background-opacity = Deckkraft des Hintergrunds
+prefs-layout-compact-width = Kompakte Breite
+    .tooltip = Wenn das Fenster schmaler ist, werden Seitenleisten und angedockte Fenster über dem Puzzle statt daneben angezeigt. Null schaltet das aus.
+prefs-interaction-confirm-discard = Verwerfen nur nach dem Verdrehen bestätigen
+    .tooltip = Wenn aktiviert, wird vor zerstörerischen Aktionen (wie dem Zurücksetzen des Puzzles) nur dann nachgefragt, wenn das Puzzle vollständig verdreht wurde.
+prefs-interaction-solved-in = Gelöst in
+    .tooltip = Ob ein verdrehtes Puzzle als gelöst gilt, wenn jede Seite einfarbig ist, egal in welcher Ausrichtung, oder nur, wenn jede Seite ihre ursprüngliche Farbe hat.
+prefs-interaction-solved-any-orientation = Beliebiger Ausrichtung
+prefs-interaction-solved-original-orientation = Ursprünglicher Ausrichtung
+prefs-interaction-supercube = Supercube
+    .tooltip = Markiert die Ausrichtung der Mittelsticker bei 3D- und 4D-Rubik-Puzzles. Ein verdrehtes Puzzle gilt erst als gelöst, wenn jede Mitte wieder richtig gedreht ist.
+prefs-interaction-drag-sensitivity = Ziehempfindlichkeit
+prefs-interaction-realign-on-release = Beim Loslassen neu ausrichten
+    .tooltip = Wenn aktiviert, springt das Puzzle sofort zurück, wenn die Maus nach dem Drehen des Puzzles losgelassen wird.
+prefs-interaction-realign-on-keypress = Bei Tastendruck neu ausrichten
+    .tooltip = Wenn aktiviert, springt das Puzzle sofort zurück, wenn mit der Tastatur gegriffen oder gedreht wird.
+prefs-interaction-smart-realign = Intelligent neu ausrichten
+    .tooltip = Wenn aktiviert, springt das Puzzle zur nächstgelegenen ähnlichen Ausrichtung statt zur ursprünglichen. Dabei wird eine Drehung des ganzen Puzzles zum Verlauf hinzugefügt.
+prefs-anim = Animationen
+prefs-anim-dynamic-twist-speed = Dynamische Drehgeschwindigkeit
+    .tooltip = Wenn aktiviert, dreht sich das Puzzle schneller, wenn viele Züge warten. Sobald alle wartenden Züge abgeschlossen sind, wird die Geschwindigkeit zurückgesetzt.
+prefs-anim-curve-exponential = Exponentiell
+prefs-anim-curve-linear = Linear
+prefs-anim-curve-queue-length = Länge der Warteschlange
+prefs-anim-speedup = Beschleunigung
+    .tooltip = Wie viel schneller sich das Puzzle für jeden wartenden Zug dreht.
+prefs-anim-simultaneous-twists = Gleichzeitige Drehungen
+    .tooltip = Wenn aktiviert, werden wartende Drehungen, die verschiedene Teile bewegen, wie Drehungen gegenüberliegender Seiten, gleichzeitig animiert.
+prefs-anim-merge-repeated-twists = Wiederholte Drehungen zusammenfassen
+    .tooltip = Wenn aktiviert, werden wartende Drehungen derselben Ebenen in dieselbe Richtung als eine Drehung animiert, etwa R R als R2.
+prefs-anim-skip-threshold = Überspringschwelle
+    .tooltip = Wenn mindestens so viel einer Drehung in einem einzigen Bild animiert würde, wird die Animation übersprungen, um Flackern zu vermeiden.
+prefs-anim-twist-duration = Drehdauer
+prefs-anim-other = Andere Animationen
+    .tooltip = Dauer anderer Animationen in Sekunden, etwa beim Verstecken eines Teils.
+prefs-anim-rewind-undo = Rückgängig zurückspulen
+    .tooltip = Wenn aktiviert, spielt Rückgängig die ursprüngliche Drehung rückwärts ab, und durch Rückgängig oder Wiederholen bewegte Teile werden in einer anderen Farbe umrandet.
+prefs-info-describe-state = Zustand des Puzzles beschreiben
+    .tooltip = Wenn aktiviert, beschreibt die Statusleiste die letzte Aktion, die Zugzahl, den Timer und den Verdrehungszustand als Text, den Bildschirmleser vorlesen können.
+prefs-info-sticker-tooltips = Sticker-Tooltips
+    .tooltip = Wenn aktiviert, zeigt ein Sticker unter dem Mauszeiger seine Seite, seine Teileart und seine Position.
+prefs-info-last-layer-case = Fall der letzten Ebene
+    .tooltip = Wenn aktiviert, nennt die Statusleiste die OLL- und PLL-Fälle und jede Parität, sobald ein 3D-Würfel die letzte Ebene erreicht.
+prefs-scramble-partial-max = Maximale Teilverdrehung
+    .tooltip = Größte Anzahl an Drehungen, die in Tastenbelegungen und der Befehlspalette für eine Teilverdrehung gewählt werden kann.
+prefs-scramble-queue-length = Vorbereitete Verdrehungen
+    .tooltip = Anzahl der vollen Verdrehungen, die für den Befehl „Nächste Verdrehung“ im Voraus erzeugt werden.
+prefs-scramble-full-length = Länge der vollen Verdrehung
+    .tooltip = Anzahl der zufälligen Drehungen einer vollen Verdrehung bei Puzzles, die nicht in einen zufälligen Zustand verdreht werden.
+prefs-scramble-outer-layers-only = Nur äußere Ebenen
+    .tooltip = Immer nur eine einzelne äußere Ebene drehen. Verdrehungen in einen zufälligen Zustand werden dann nicht verwendet.
+prefs-scramble-twist-axes = Drehachsen ({ $puzzle })
+prefs-hotkeys-enabled = Globale Tastenkürzel aktivieren
+    .tooltip = Diese Tastenkürzel auch dann erkennen, wenn das Fenster nicht im Fokus ist, etwa auf einem zweiten Bildschirm.
+prefs-hotkeys-toggle-timer = Timer starten/stoppen
+prefs-hotkeys-next-scramble = Nächste Verdrehung
+prefs-sounds-enabled = Klänge aktivieren
+prefs-sounds-master-volume = Gesamtlautstärke
+prefs-sounds-twists = Drehungen
+    .tooltip = Klicken, wenn eine Drehanimation abgeschlossen ist.
+prefs-sounds-timer = Timer
+    .tooltip = Piepton, wenn der Timer startet und stoppt.
+prefs-sounds-inspection = Inspektion
+    .tooltip = Warnung nach 8 und 12 Sekunden Inspektion, gezählt ab der Verdrehung bis zum Start des Timers.
+prefs-sounds-pb = Persönliche Bestzeit
+    .tooltip = Fanfare, wenn eine Lösung eine neue persönliche Bestzeit aufstellt.
+prefs-outlines-colors = Farben
+prefs-outlines-default = Standard
+prefs-outlines-hidden = Versteckt
+prefs-outlines-hovered = Unter dem Mauszeiger
+prefs-outlines-selected-sticker = Ausgew. Sticker
+prefs-outlines-selected-piece = Ausgew. Teil
+prefs-outlines-move-trail = Zugspur
+prefs-outlines-undo-redo = Rückgängig/Wiederholen
+    .tooltip = Farbe der durch Rückgängig oder Wiederholen bewegten Teile, wenn Zurückspulen aktiviert ist.
+prefs-outlines-face-colors = Seitenfarben
+prefs-outlines-face-default = Standard
+    .tooltip = Die Seitenfarbe jedes Stickers für seinen Umriss verwenden.
+prefs-outlines-selected = Ausgewählt
+prefs-outlines-shade = Schattierung
+    .tooltip = Wie stark die Seitenfarben für Umrisse abgedunkelt (negativ) oder aufgehellt (positiv) werden.
+prefs-outlines-sizes = Größen
+prefs-outlines-trail-length = Länge
+    .tooltip = Anzahl der letzten Drehungen, deren Teile umrandet werden. 0 schaltet die Spur aus.
+prefs-outlines-trail-length-unit = Drehungen
+prefs-outlines-trail-fade-time = Ausblendzeit
+prefs-opacity-base = Basis
+prefs-opacity-ungripped = Nicht gegriffen
+prefs-opacity-hidden = Versteckt
+prefs-opacity-selected = Ausgewählt
+prefs-opacity-solved = Gelöst
+prefs-view-presets = Voreinstellungen
+prefs-view-load = Laden
+prefs-view-position = Position
+prefs-view-align-h = Horizontale Ausrichtung
+prefs-view-align-v = Vertikale Ausrichtung
+prefs-view-angle = Blickwinkel
+prefs-view-pitch = Neigung
+prefs-view-yaw = Gierung
+prefs-view-roll = Rollen
+prefs-view-projection = Projektion
+prefs-view-scale = Größe
+prefs-view-fit = An Ansicht anpassen
+    .tooltip = Die Größe so einstellen, dass das ganze Puzzle ins Fenster passt
+prefs-view-fov-4d = 4D-Sichtfeld
+prefs-view-fov-3d = 3D-Sichtfeld
+prefs-view-camera-distance = Kameraabstand
+    .tooltip = Bewegt die Kamera vom Puzzle weg, sodass es kleiner und weniger perspektivisch verzerrt erscheint.
+prefs-view-geometry = Geometrie
+prefs-view-show-frontfaces = Vorderseiten anzeigen
+prefs-view-show-backfaces = Rückseiten anzeigen
+prefs-view-clip-4d = 4D abschneiden
+prefs-view-net = Als Netz aufklappen
+    .title = Netzansicht
+    .tooltip = Zeigt 3D-Würfel als flaches Netz und die Zellen von 4D-Würfeln in 3D aufgeklappt. Andere Puzzles sind nicht betroffen.
+prefs-view-face-spacing = Seitenabstand
+prefs-view-sticker-spacing = Stickerabstand
+prefs-view-lighting = Beleuchtung
+prefs-view-light-directional = Gerichtet
+prefs-view-light-ambient = Umgebung
+prefs-view-labels = Beschriftungen
+prefs-view-face-labels = Seitenbeschriftungen
+    .tooltip = Zeichnet eine Beschriftung nahe der Mitte jeder Seite. Die Beschriftungen können unten geändert werden.
+prefs-view-custom-face-labels = Eigene Seitenbeschriftungen
+prefs-view-guides = Hilfslinien
+prefs-view-axis-arrows = Achsenpfeile
+    .tooltip = Zeichnet einen Pfeil entlang jeder Achse, der zu ihrer positiven Zelle zeigt.
+prefs-view-highlight-cell = Zelle hervorheben
+    .tooltip = Lässt jeden Sticker in derselben Zelle wie der Sticker unter dem Mauszeiger pulsieren.
+prefs-opacity-dim-solved = Gelöste Teile abdunkeln
+    .tooltip = Wenn aktiviert, werden bereits gelöste Teile mit der Deckkraft „Gelöst“ gezeichnet, bis das ganze Puzzle gelöst ist.
+prefs-opacity-unhide-grip = Gegriffene Teile einblenden
+    .tooltip = Wenn aktiviert, schaltet das Greifen einer Seite die Teilefilter vorübergehend aus.
+
+## Drehrichtungen
+
+twist-cw = Im Uhrzeigersinn
+twist-ccw = Gegen den Uhrzeigersinn
+twist-180-cw = 180 im Uhrzeigersinn
+twist-180-ccw = 180 gegen den Uhrzeigersinn
+twist-vertex-1-cw = Ecke 1 im Uhrzeigersinn
+twist-vertex-1-ccw = Ecke 1 gegen den Uhrzeigersinn
+twist-vertex-2-cw = Ecke 2 im Uhrzeigersinn
+twist-vertex-2-ccw = Ecke 2 gegen den Uhrzeigersinn
+twist-vertex-3-cw = Ecke 3 im Uhrzeigersinn
+twist-vertex-3-ccw = Ecke 3 gegen den Uhrzeigersinn
+twist-vertex-4-cw = Ecke 4 im Uhrzeigersinn
+twist-vertex-4-ccw = Ecke 4 gegen den Uhrzeigersinn
+twist-edge-1-2 = Kante 1-2
+twist-edge-1-3 = Kante 1-3
+twist-edge-1-4 = Kante 1-4
@@ -0,0 +1,318 @@
+## Menu bar
+
+menu-file = File
+menu-file-open = Open...
+menu-file-open-clipboard = Open from clipboard
+menu-file-save = Save
+menu-file-save-as = Save as...
+menu-file-screenshot = Screenshot
+menu-file-copy-hsc = Copy (.hsc)
+menu-file-copy-hsc-title = Hyperspeedcube log file (recommended)
+menu-file-copy-hsc-description = Includes extra metadata such as move count
+menu-file-copy-mc4d = Copy (.log)
+menu-file-copy-mc4d-title = MC4D-compatible log file
+menu-file-copy-mc4d-description = Backwards-compatible with Magic Cube 4D
+menu-file-exit = Exit
+
+menu-edit = Edit
+menu-edit-undo = Undo twist
+menu-edit-redo = Redo twist
+menu-edit-add-checkpoint = Add checkpoint
+menu-edit-revert-checkpoint = Revert to last checkpoint
+menu-edit-explore = Start exploring
+menu-edit-discard-exploration = Discard exploration
+menu-edit-keep-exploration = Keep exploration
+menu-edit-flush-queue = Finish queued twists
+menu-edit-pause-queue = Pause twist animations
+menu-edit-resume-queue = Resume twist animations
+menu-edit-clear-queue = Take back queued twists
+menu-edit-reset = Reset puzzle
+
+menu-scramble = Scramble
+menu-scramble-full = Full
+menu-scramble-next = Next
+menu-scramble-redo = Redo last
+menu-scramble-daily = Daily challenge
+menu-scramble-settings = Settings
+
+menu-puzzle = Puzzle
+
+menu-settings = Settings
+menu-settings-clipboard-fallback = Use clipboard fallback
+
+menu-tools = Tools
+menu-tools-mini-mode = Mini mode
+
+menu-help = Help
+menu-help-command-palette = Command palette
+menu-help-tutorial = Tutorial
+
+menu-download = Download the full version
+
+## Windows
+
+window-about = About
+window-appearance = Appearance
+window-camera-keyframes = Camera keyframes
+window-command-palette = Command palette
+window-debug-values = Debug values
+window-external-tools = External tools
+window-fewest-moves = Fewest moves
+window-global-keybinds = Global keybinds
+window-hints = Hints
+window-history-tree = History tree
+window-interaction = Interaction
+window-keybind-sets = Keybind sets
+window-keybinds-reference = Keybinds reference
+window-log-console = Log console
+window-metronome = Metronome
+window-modifier-keys = Modifier keys
+window-mousebinds = Mousebinds
+window-move-count = Move count
+window-notifications = Notifications
+window-piece-filters = Piece filters
+window-progress = Progress
+window-puzzle-controls = Puzzle controls
+window-puzzle-keybinds = Puzzle keybinds
+window-race = Race
+window-scripts = Scripts
+window-solve-history = Solve history
+window-spectate = Spectate
+window-statistics = Statistics
+window-sync = Sync
+window-timer = Timer
+window-upcoming-scrambles = Upcoming scrambles
+window-view = View
+window-welcome = Welcome
+
+## Status bar
+
+status-blindfold = Blindfold
+status-blindfold-title = Blindfold mode
+status-blindfold-description = Hides sticker colors
+status-exploring = Exploring
+status-exploring-description = Twists made while exploring don't count toward the solve and can be discarded from the Edit menu
+status-inverse = Inverse
+status-inverse-title = Inverse scramble
+status-inverse-description = Shows the inverse of the scramble. Twists made on the other side are applied as premoves.
+status-queue = Queue: { $count }
+status-queue-description = Number of twists waiting to be animated. Click to pause or resume, or right-click to finish or take back queued twists.
+status-fps = { $fps } FPS
+status-timer-description = Click to show the timer window
+
+## Preferences
+
+prefs-colors = Colors
+prefs-outlines = Outlines
+prefs-opacity = Opacity
+prefs-performance = Performance
+prefs-scramble = Scramble
+prefs-sounds = Sounds
+prefs-screenshots = Screenshots
+prefs-mini-mode = Mini mode
+prefs-layout = Layout
+prefs-hotkeys = Global hotkeys
+
+prefs-language = Language
+prefs-theme = Theme
+prefs-theme-follow-os = Follow OS
+prefs-theme-dark = Dark
+prefs-theme-light = Light
+prefs-accent = Accent
+prefs-faces = Faces
+prefs-special = Special
+prefs-background = Background
+prefs-background-style-flat = Flat
+prefs-background-style-gradient = Gradient
+prefs-background-style-grid = Grid
+prefs-background-secondary = Secondary background
+prefs-blindfolded-stickers = Blindfolded stickers
+prefs-blindfold-mode = Blindfold mode
+
+prefs-theme-preview-selected = Selected
+prefs-theme-preview-unselected = Unselected
+prefs-theme-preview-button = Button
+prefs-gfx-fps-limit = FPS limit
+    .title = Frames Per Second
+    .tooltip = Limits framerate to save power
+prefs-gfx-idle-mode = Idle mode
+    .tooltip = Lowers the framerate when nothing is moving and there is no input, to save power. Input wakes it up immediately.
+prefs-gfx-idle-fps-limit = Idle FPS limit
+prefs-gfx-ui-scale = UI scale
+    .tooltip = Size of text and controls, relative to the display's scaling setting
+prefs-gfx-msaa = MSAA
+    .title = Multisample Anti-Aliasing
+    .tooltip = Makes edges less jagged, but may worsen performance.
+    .disabled = Multisample anti-aliasing is not supported on web.
+prefs-gfx-show-fps = Show FPS in status bar
+prefs-gfx-perf-hud = Show performance HUD
+    .title = Performance HUD
+    .tooltip = Shows how long each stage of rendering takes, averaged over recent frames.
+prefs-gfx-lod = Simplify tiny stickers
+    .title = Level of detail
+    .tooltip = Draws stickers that are only a few pixels across as simple quads without outlines, which improves performance on large puzzles.
+prefs-gfx-lod-min-size = Min sticker size
+prefs-gfx-stereo = Stereoscopic rendering
+prefs-gfx-stereo-off = Off
+prefs-gfx-stereo-side-by-side = Side-by-side
+prefs-gfx-stereo-anaglyph = Red-cyan
+prefs-gfx-eye-separation = Eye separation
+    .tooltip = How far apart the two views are. Higher values exaggerate depth.
+prefs-screenshots-resolution = Resolution
+    .tooltip = Size of screenshots, relative to the size of the puzzle view on screen.
+prefs-screenshots-copy = Copy to clipboard
+prefs-screenshots-no-folder = (none)
+prefs-screenshots-choose-folder = Choose folder...
+prefs-mini-mode-always-on-top = Always on top
+prefs-mini-mode-transparent = Transparent background
+prefs-mini-mode-background-opacity = Background opacity
+prefs-layout-compact-width = Compact width
+    .tooltip = When the window is narrower than this, side panels and docked panels are shown on top of the puzzle instead of beside it. Set to zero to disable.
+prefs-interaction-confirm-discard = Confirm discard only when scrambled
+    .tooltip = When enabled, a confirmation dialog before destructive actions (like resetting the puzzle) is only shown when the puzzle has been fully scrambled.
+prefs-interaction-solved-in = Solved in
+    .tooltip = Whether a scrambled puzzle counts as solved when each face is a single color in any orientation, or only when each face is its original color.
+prefs-interaction-solved-any-orientation = Any orientation
+prefs-interaction-solved-original-orientation = Original orientation
+prefs-interaction-supercube = Supercube
+    .tooltip = Marks the orientation of center stickers on 3D and 4D Rubik's puzzles. A scrambled puzzle only counts as solved once every center is turned back the right way.
+prefs-interaction-drag-sensitivity = Drag sensitivity
+prefs-interaction-realign-on-release = Realign puzzle on release
+    .tooltip = When enabled, the puzzle snaps back immediately when the mouse is released after dragging to rotate it.
+prefs-interaction-realign-on-keypress = Realign puzzle on keypress
+    .tooltip = When enabled, the puzzle snaps back immediately when the keyboard is used to grip or do a move.
+prefs-interaction-smart-realign = Smart realign
+    .tooltip = When enabled, the puzzle snaps to the nearest similar orientation, not the original. This adds a full-puzzle rotation to the undo history.
+prefs-anim = Animations
+prefs-anim-dynamic-twist-speed = Dynamic twist speed
+    .tooltip = When enabled, the puzzle twists faster when many moves are queued up. When all queued moves are complete, the twist speed resets.
+prefs-anim-curve-exponential = Exponential
+prefs-anim-curve-linear = Linear
+prefs-anim-curve-queue-length = Queue length
+prefs-anim-speedup = Speedup
+    .tooltip = How much faster the puzzle twists for each queued move.
+prefs-anim-simultaneous-twists = Simultaneous twists
+    .tooltip = When enabled, queued twists that move different pieces, such as twists of opposite faces, are animated at the same time.
+prefs-anim-merge-repeated-twists = Merge repeated twists
+    .tooltip = When enabled, queued twists of the same layers in the same direction are animated as one twist, such as R R as R2.
+prefs-anim-skip-threshold = Skip threshold
+    .tooltip = If at least this much of a twist would be animated in a single frame, the animation is skipped to reduce flashing.
+prefs-anim-twist-duration = Twist duration
+prefs-anim-other = Other animations
+    .tooltip = Number of seconds for other animations, such as hiding a piece.
+prefs-anim-rewind-undo = Rewind undo
+    .tooltip = When enabled, undo plays the original twist backwards, and pieces moved by undo or redo are outlined in a different color.
+prefs-info-describe-state = Describe puzzle state
+    .tooltip = When enabled, the status bar describes the last action, move count, timer, and scramble state in text that can be read by screen readers.
+prefs-info-sticker-tooltips = Sticker tooltips
+    .tooltip = When enabled, hovering over a sticker shows its face, piece type, and position.
+prefs-info-last-layer-case = Last layer case
+    .tooltip = When enabled, the status bar names the OLL and PLL cases and any parity when a 3D cube reaches the last layer.
+prefs-scramble-partial-max = Max partial scramble
+    .tooltip = Largest number of twists that can be chosen for a partial scramble in keybinds and the command palette.
+prefs-scramble-queue-length = Upcoming scrambles
+    .tooltip = Number of full scrambles to generate ahead of time for the "Next scramble" command.
+prefs-scramble-full-length = Full scramble length
+    .tooltip = Number of random twists in a full scramble of puzzles that aren't scrambled to a random state.
+prefs-scramble-outer-layers-only = Outer layers only
+    .tooltip = Only twist a single outer layer at a time. Random-state scrambles are not used when this is enabled.
+prefs-scramble-twist-axes = Twist axes ({ $puzzle })
+prefs-hotkeys-enabled = Enable global hotkeys
+    .tooltip = Listen for these hotkeys even when the window isn't focused, such as when it is on a second monitor.
+prefs-hotkeys-toggle-timer = Start/stop timer
+prefs-hotkeys-next-scramble = Next scramble
+prefs-sounds-enabled = Enable sounds
+prefs-sounds-master-volume = Master volume
+prefs-sounds-twists = Twists
+    .tooltip = Click when a twist animation completes.
+prefs-sounds-timer = Timer
+    .tooltip = Beep when the timer starts and stops.
+prefs-sounds-inspection = Inspection
+    .tooltip = Warning after 8 and 12 seconds of inspection, counted from the scramble until the timer starts.
+prefs-sounds-pb = Personal best
+    .tooltip = Fanfare when a solve sets a new personal best.
+prefs-outlines-colors = Colors
+prefs-outlines-default = Default
+prefs-outlines-hidden = Hidden
+prefs-outlines-hovered = Hovered
+prefs-outlines-selected-sticker = Sel. sticker
+prefs-outlines-selected-piece = Sel. piece
+prefs-outlines-move-trail = Move trail
+prefs-outlines-undo-redo = Undo/redo
+    .tooltip = Color of pieces moved by undo or redo when rewinding undo is enabled.
+prefs-outlines-face-colors = Face colors
+prefs-outlines-face-default = Default
+    .tooltip = Use each sticker's face color for its outline.
+prefs-outlines-selected = Selected
+prefs-outlines-shade = Shade
+    .tooltip = How much to darken (negative) or lighten (positive) face colors used for outlines.
+prefs-outlines-sizes = Sizes
+prefs-outlines-trail-length = Length
+    .tooltip = Number of recent twists whose pieces are outlined. Set to 0 to disable the trail.
+prefs-outlines-trail-length-unit = twists
+prefs-outlines-trail-fade-time = Fade time
+prefs-opacity-base = Base
+prefs-opacity-ungripped = Ungripped
+prefs-opacity-hidden = Hidden
+prefs-opacity-selected = Selected
+prefs-opacity-solved = Solved
+prefs-view-presets = Presets
+prefs-view-load = Load
+prefs-view-position = Position
+prefs-view-align-h = Horizontal align
+prefs-view-align-v = Vertical align
+prefs-view-angle = View angle
+prefs-view-pitch = Pitch
+prefs-view-yaw = Yaw
+prefs-view-roll = Roll
+prefs-view-projection = Projection
+prefs-view-scale = Scale
+prefs-view-fit = Fit to view
+    .tooltip = Set the scale so that the whole puzzle fits in the window
+prefs-view-fov-4d = 4D FOV
+prefs-view-fov-3d = 3D FOV
+prefs-view-camera-distance = Camera distance
+    .tooltip = Moves the camera away from the puzzle, making it smaller and less distorted by perspective.
+prefs-view-geometry = Geometry
+prefs-view-show-frontfaces = Show frontfaces
+prefs-view-show-backfaces = Show backfaces
+prefs-view-clip-4d = Clip 4D
+prefs-view-net = Unfold into net
+    .title = Net view
+    .tooltip = Shows 3D cubes as a flat net and 4D cubes as their cells unfolded in 3D. Other puzzles are unaffected.
+prefs-view-face-spacing = Face spacing
+prefs-view-sticker-spacing = Sticker spacing
+prefs-view-lighting = Lighting
+prefs-view-light-directional = Directional
+prefs-view-light-ambient = Ambient
+prefs-view-labels = Labels
+prefs-view-face-labels = Face labels
+    .tooltip = Draws a label near the center of each face. Labels can be changed below.
+prefs-view-custom-face-labels = Custom face labels
+prefs-view-guides = Guides
+prefs-view-axis-arrows = Axis arrows
+    .tooltip = Draws an arrow along each axis, pointing toward its positive cell.
+prefs-view-highlight-cell = Highlight cell
+    .tooltip = Pulses every sticker in the same cell as the hovered sticker.
+prefs-opacity-dim-solved = Dim solved pieces
+    .tooltip = When enabled, pieces that are already solved are drawn with the "Solved" opacity until the whole puzzle is solved.
+prefs-opacity-unhide-grip = Unhide grip
+    .tooltip = When enabled, gripping a face will temporarily disable piece filters.
+
+## Twist directions
+
+twist-cw = CW
+twist-ccw = CCW
+twist-180-cw = 180 CW
+twist-180-ccw = 180 CCW
+twist-vertex-1-cw = Vertex 1 CW
+twist-vertex-1-ccw = Vertex 1 CCW
+twist-vertex-2-cw = Vertex 2 CW
+twist-vertex-2-ccw = Vertex 2 CCW
+twist-vertex-3-cw = Vertex 3 CW
+twist-vertex-3-ccw = Vertex 3 CCW
+twist-vertex-4-cw = Vertex 4 CW
+twist-vertex-4-ccw = Vertex 4 CCW
+twist-edge-1-2 = Edge 1-2
+twist-edge-1-3 = Edge 1-3
+twist-edge-1-4 = Edge 1-4
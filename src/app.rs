@@ -10,12 +10,25 @@ use std::path::PathBuf;
 use winit::event::{ElementState, ModifiersState, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop, EventLoopProxy};
 
-use crate::commands::{Command, PuzzleCommand, PuzzleMouseCommand};
+use crate::audio::Sound;
+use crate::commands::{Command, GripGroupMode, PuzzleCommand, PuzzleMouseCommand};
+use crate::gui::{Severity, TutorialEvent, MAX_SCALE, MIN_SCALE};
+use crate::history::{daily_seed, Milestone, Solve, SolveHistory};
 use crate::logfile::LogFileFormat;
-use crate::preferences::{Key, Keybind, PieceFilter, Preferences, Preset};
+use crate::preferences::{Key, Keybind, MouseButton, PieceFilter, Preferences, Preset};
 use crate::puzzle::*;
 use crate::render::{GraphicsState, PuzzleRenderCache};
 
+/// Name of the directory, inside the config directory, containing custom
+/// puzzle definitions.
+#[cfg(not(target_arch = "wasm32"))]
+const CUSTOM_PUZZLES_DIR_NAME: &str = "puzzles";
+
+/// Smallest view scale that can be reached by zooming.
+const MIN_VIEW_SCALE: f32 = 0.1;
+/// Largest view scale that can be reached by zooming.
+const MAX_VIEW_SCALE: f32 = 5.0;
+
 #[cfg(target_arch = "wasm32")]
 macro_rules! unsupported_on_web {
     ($self:ident; $($tok:tt)*) => {
@@ -31,15 +44,71 @@ macro_rules! unsupported_on_web {
 
 pub struct App {
     pub(crate) timer: crate::gui::windows::Timer,
+    pub(crate) metronome: crate::gui::windows::Metronome,
+    pub(crate) latency_calibration: crate::gui::windows::LatencyCalibration,
+    pub(crate) relay: crate::gui::windows::Relay,
+    /// Day of the daily challenge that the puzzle was last scrambled with, so
+    /// that a solve finishing after midnight counts for the right day.
+    daily_challenge_day: Option<i64>,
+    pub(crate) fmc: crate::gui::windows::Fmc,
+    pub(crate) scramble_queue: crate::gui::windows::ScrambleQueue,
+    pub(crate) tutorial: crate::gui::Tutorial,
+    pub(crate) toasts: crate::gui::Toasts,
 
     pub(crate) prefs: Preferences,
+    /// Watcher for changes to the preferences file made outside the app.
+    #[cfg(not(target_arch = "wasm32"))]
+    prefs_watcher: Option<crate::preferences::PrefsWatcher>,
+    pub(crate) history: SolveHistory,
 
     events: EventLoopProxy<AppEvent>,
 
     pub(crate) puzzle: PuzzleController,
+    /// Connection to an online race lobby.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) race: Option<crate::net::RaceClient>,
+    /// Race that started while there were unsaved changes, waiting for the
+    /// player to join or skip it from the race window.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) pending_race: Option<crate::net::RaceStart>,
+    /// Race lobby hosted by this app.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) race_server: Option<crate::net::RaceServer>,
+    /// Broadcast of the local puzzle to spectators.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) spectate_host: Option<crate::net::SpectateHost>,
+    /// Read-only connection to another player's puzzle.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) spectating: Option<crate::net::SpectateClient>,
+    /// Sync of solve history and preferences with a server.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) sync: crate::net::SyncClient,
+    /// Local API for external tools.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) api: Option<crate::net::ApiServer>,
+    /// Live solve data file for stream overlays.
+    #[cfg(not(target_arch = "wasm32"))]
+    overlay: crate::overlay::OverlayWriter,
+    /// Hotkeys that work while the window is not focused.
+    #[cfg(not(target_arch = "wasm32"))]
+    hotkeys: crate::hotkeys::GlobalHotkeys,
+    /// User script.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) script: crate::scripting::ScriptEngine,
+    /// Sound effect output.
+    audio: crate::audio::Audio,
+    /// Whether the timer was running last frame, used to detect when it
+    /// starts or stops.
+    timer_was_running: bool,
     pub(crate) render_cache: PuzzleRenderCache,
     pub(crate) puzzle_texture_size: (u32, u32),
     force_redraw: bool,
+    /// Whether to take a screenshot after drawing the puzzle this frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    screenshot_requested: bool,
+    /// Whether to export the replay after drawing the puzzle this frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    replay_export_requested: bool,
 
     /// Mouse cursor position relative to the puzzle texture. Each axis ranges
     /// from -1.0 to +1.0.
@@ -56,24 +125,84 @@ pub struct App {
 
     /// Grips that are tied to a held key.
     transient_grips: HashMap<Key, Grip>,
+    /// Keybind layers that are tied to a held key, in the order they were
+    /// activated. The last one is active.
+    held_layers: Vec<(Key, String)>,
     /// Grip that is more permanent.
     pub(crate) toggle_grip: Grip,
 
+    /// Whether the window is showing only the timer and scramble.
+    pub(crate) mini_mode: bool,
+
+    /// Last layer case of the puzzle state that was last checked, for
+    /// showing changes in the status bar.
+    last_layer_case: Option<(UndoNodeId, Option<last_layer::LastLayerCase>)>,
+
     status_msg: String,
+    /// Human-readable description of the last action on the puzzle, for
+    /// screen readers.
+    last_action: String,
+
+    /// Number of frames rendered in the last second.
+    fps: u32,
 }
 impl App {
     pub(crate) fn new(event_loop: &EventLoop<AppEvent>, initial_file: Option<PathBuf>) -> Self {
+        // Load custom puzzles before preferences, which may refer to them.
+        generic::load_builtin();
+        #[cfg(not(target_arch = "wasm32"))]
+        let custom_puzzle_errors = load_custom_puzzles();
+
         let mut this = Self {
             timer: crate::gui::windows::Timer::new(),
+            metronome: crate::gui::windows::Metronome::default(),
+            latency_calibration: crate::gui::windows::LatencyCalibration::default(),
+            relay: crate::gui::windows::Relay::default(),
+            daily_challenge_day: None,
+            fmc: crate::gui::windows::Fmc::default(),
+            scramble_queue: crate::gui::windows::ScrambleQueue::default(),
+            tutorial: crate::gui::Tutorial::default(),
+            toasts: crate::gui::Toasts::default(),
 
             prefs: Preferences::load(None),
+            #[cfg(not(target_arch = "wasm32"))]
+            prefs_watcher: crate::preferences::PrefsWatcher::new()
+                .map_err(|e| log::warn!("Unable to watch preferences file: {}", e))
+                .ok(),
+            history: SolveHistory::load(),
 
             events: event_loop.create_proxy(),
 
             puzzle: PuzzleController::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            race: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            pending_race: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            race_server: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            spectate_host: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            spectating: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            sync: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            api: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            overlay: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            hotkeys: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            script: Default::default(),
+            audio: Default::default(),
+            timer_was_running: false,
             render_cache: PuzzleRenderCache::default(),
             puzzle_texture_size: (0, 0),
             force_redraw: true,
+            #[cfg(not(target_arch = "wasm32"))]
+            screenshot_requested: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            replay_export_requested: false,
 
             cursor_pos: None,
 
@@ -83,18 +212,42 @@ impl App {
             toggled_modifiers: ModifiersState::default(),
 
             transient_grips: HashMap::default(),
+            held_layers: vec![],
             toggle_grip: Grip::default(),
 
+            mini_mode: false,
+
+            last_layer_case: None,
+
             status_msg: String::default(),
+            last_action: String::default(),
+
+            fps: 0,
         };
 
         // Always save preferences after opening.
         this.prefs.needs_save = true;
 
+        #[cfg(not(target_arch = "wasm32"))]
+        for e in &custom_puzzle_errors {
+            log::error!("Error loading custom puzzle: {e}");
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(e) = custom_puzzle_errors.first() {
+            this.set_status_err(format!("Error loading custom puzzle: {e}"));
+        }
+
         if let Some(path) = initial_file {
             this.prefs.log_file = Some(path);
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if this.prefs.sync.on_startup {
+            if let Err(e) = this.start_sync() {
+                this.set_status_err(e);
+            }
+        }
+
         // Load last open file.
         #[cfg(target_arch = "wasm32")]
         this.try_load_from_local_storage();
@@ -103,9 +256,47 @@ impl App {
             this.try_load_puzzle(path);
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(dir) = crate::crash::take_pending_report() {
+            this.offer_crash_recovery(dir);
+        }
+
         this
     }
 
+    /// Tells the user about a crash report from the last run and offers to
+    /// restore the puzzle state saved in it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn offer_crash_recovery(&mut self, dir: PathBuf) {
+        let puzzle_file = dir.join(crate::crash::PUZZLE_FILE_NAME);
+        let restore = rfd::MessageDialog::new()
+            .set_title(&format!("{} crashed", crate::TITLE))
+            .set_description(&format!(
+                "{} crashed last time it was run. A crash report \
+                 has been saved to \"{}\"\n\n\
+                 Restore the puzzle from before the crash?",
+                crate::TITLE,
+                dir.display(),
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if !restore {
+            return;
+        }
+        match crate::logfile::load_file(&puzzle_file) {
+            Ok((puzzle, warnings)) => {
+                if self.confirm_load_puzzle(&warnings) {
+                    self.puzzle = puzzle;
+                    self.set_status_ok("Restored puzzle from crash report");
+                }
+            }
+            Err(e) => show_error_dialog(
+                "Unable to restore puzzle",
+                format!("Unable to restore puzzle from crash report:\n\n{e}"),
+            ),
+        }
+    }
+
     pub(crate) fn request_redraw_puzzle(&mut self) {
         self.force_redraw = true;
     }
@@ -145,33 +336,53 @@ impl App {
         #[cfg(target_arch = "wasm32")]
         let _ = control_flow;
 
+        if let AppEvent::Command(c) = &event {
+            self.tutorial.on_event(TutorialEvent::Command(c));
+        }
+
         match event {
             #[cfg(target_arch = "wasm32")]
             AppEvent::WebWorkaround(_) => {
                 panic!("web workaround event should not be handled by app")
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            AppEvent::AccessKitActionRequest(_) => {
+                panic!("accesskit action request should not be handled by app")
+            }
 
             AppEvent::Command(c) => match c {
                 Command::Open => {
-                    unsupported_on_web! {
-                        self;
-                        if self.confirm_discard_changes("open another file") {
+                    if self.confirm_discard_changes("open another file") {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        {
                             if let Some(path) = file_dialog().pick_file() {
                                 self.try_load_puzzle(path);
                             }
                         }
+                        #[cfg(target_arch = "wasm32")]
+                        crate::web_files::open_log_file(self.events.clone());
                     }
                 }
                 Command::Save => {
-                    unsupported_on_web! {
-                        self;
-                        match self.prefs.log_file.clone() {
-                            Some(path) => self.try_save_puzzle(&path),
-                            None => self.try_save_puzzle_as(),
-                        }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    match self.prefs.log_file.clone() {
+                        Some(path) => self.try_save_puzzle(&path),
+                        None => self.try_save_puzzle_as(),
                     }
+                    // There's no file to overwrite on web, so download a new
+                    // copy every time.
+                    #[cfg(target_arch = "wasm32")]
+                    self.try_download_puzzle();
+                }
+                Command::SaveAs => {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    self.try_save_puzzle_as();
+                    #[cfg(target_arch = "wasm32")]
+                    self.try_download_puzzle();
+                }
+                Command::Screenshot => {
+                    unsupported_on_web! { self; self.screenshot_requested = true }
                 }
-                Command::SaveAs => unsupported_on_web! { self; self.try_save_puzzle_as() },
 
                 Command::Exit => {
                     unsupported_on_web! {
@@ -187,20 +398,81 @@ impl App {
                 Command::PasteLog => response.request_paste = true,
 
                 Command::Undo => {
-                    self.puzzle.undo()?;
+                    self.check_not_spectating()?;
+                    self.puzzle
+                        .navigate_undo(self.prefs.interaction.rewind_undo)?;
+                    self.last_action = "Undid twist".to_owned();
                 }
                 Command::Redo => {
-                    self.puzzle.redo()?;
+                    self.check_not_spectating()?;
+                    self.puzzle
+                        .navigate_redo(self.prefs.interaction.rewind_undo)?;
+                    self.last_action = "Redid twist".to_owned();
+                }
+                Command::AddCheckpoint => {
+                    let name = self.puzzle.add_checkpoint("").name.clone();
+                    self.set_status_ok(format!("Added {name}"));
+                }
+                Command::RevertToCheckpoint => {
+                    self.check_not_spectating()?;
+                    match self.puzzle.checkpoints().len() {
+                        0 => return Err("No checkpoints".to_owned()),
+                        n => self.puzzle.revert_to_checkpoint(n - 1)?,
+                    }
+                }
+                Command::ToggleExplore => {
+                    self.check_not_spectating()?;
+                    if self.puzzle.is_exploring() {
+                        self.puzzle.discard_exploration()?;
+                        self.set_status_ok("Discarded exploration");
+                    } else {
+                        self.puzzle.start_exploring();
+                        self.set_status_ok("Exploring; twists will be discarded afterward");
+                    }
+                }
+                Command::KeepExploration => {
+                    if !self.puzzle.is_exploring() {
+                        return Err("Not exploring".to_owned());
+                    }
+                    self.puzzle.keep_exploration();
+                    self.set_status_ok("Kept twists from exploration");
+                }
+                Command::FlushTwistQueue => {
+                    self.puzzle.skip_twist_animations();
+                    self.request_redraw_puzzle();
+                }
+                Command::ToggleTwistQueuePaused => {
+                    self.puzzle.toggle_twist_queue_paused();
+                    if self.puzzle.is_twist_queue_paused() {
+                        self.set_status_ok("Paused twist animations");
+                    } else {
+                        self.set_status_ok("Resumed twist animations");
+                    }
+                }
+                Command::ClearTwistQueue => {
+                    self.check_not_spectating()?;
+                    match self.puzzle.cancel_queued_twists() {
+                        0 => return Err("No queued twists to take back".to_owned()),
+                        1 => self.set_status_ok("Took back 1 queued twist"),
+                        n => self.set_status_ok(format!("Took back {n} queued twists")),
+                    }
+                    self.request_redraw_puzzle();
                 }
                 Command::Reset => {
+                    self.check_not_spectating()?;
                     if self.confirm_discard_changes("reset puzzle") {
+                        self.relay.cancel();
                         self.puzzle.reset();
+                        self.last_action = "Reset puzzle".to_owned();
                     }
                 }
 
                 Command::ScrambleN(n) => {
+                    self.check_not_spectating()?;
                     if self.confirm_discard_changes("scramble") {
-                        self.puzzle.scramble_n(n)?;
+                        self.relay.cancel();
+                        let options = self.prefs.scramble.options(self.puzzle.ty());
+                        self.puzzle.scramble_n(n, &options)?;
                         self.set_status_ok(format!(
                             "Scrambled with {} random {}",
                             n,
@@ -210,15 +482,56 @@ impl App {
                     }
                 }
                 Command::ScrambleFull => {
+                    self.check_not_spectating()?;
                     if self.confirm_discard_changes("scramble") {
-                        self.puzzle.scramble_full()?;
+                        self.relay.cancel();
+                        let options = self.prefs.scramble.options(self.puzzle.ty());
+                        self.puzzle.scramble_full(&options)?;
                         self.set_status_ok("Scrambled fully");
+                        self.last_action = "Scrambled fully".to_owned();
+                        self.timer.on_scramble();
+                    }
+                }
+                Command::NextScramble => {
+                    self.check_not_spectating()?;
+                    if self.confirm_discard_changes("scramble") {
+                        self.relay.cancel();
+                        let options = self.prefs.scramble.options(self.puzzle.ty());
+                        let scramble = self.scramble_queue.pop(self.puzzle.ty(), &options)?;
+                        self.puzzle.scramble_with(&scramble)?;
+                        self.puzzle.mark_scramble_generated();
+                        self.set_status_ok("Scrambled with next scramble");
+                        self.last_action = "Scrambled fully".to_owned();
+                        self.timer.on_scramble();
+                    }
+                }
+                Command::RedoScramble => {
+                    self.check_not_spectating()?;
+                    if self.confirm_discard_changes("redo scramble") {
+                        self.relay.cancel();
+                        self.puzzle.rescramble()?;
+                        self.set_status_ok("Applied last scramble again");
+                        self.last_action = "Redid scramble".to_owned();
+                        self.timer.on_scramble();
+                    }
+                }
+
+                Command::DailyChallenge => {
+                    self.check_not_spectating()?;
+                    if self.confirm_discard_changes("scramble") {
+                        self.relay.cancel();
+                        let day = crate::util::unix_day();
+                        self.puzzle.scramble_full_seeded(daily_seed(day, self.puzzle.ty()))?;
+                        self.daily_challenge_day = Some(day);
+                        self.set_status_ok("Scrambled with today's daily challenge");
+                        self.last_action = "Scrambled with daily challenge".to_owned();
                         self.timer.on_scramble();
                     }
                 }
 
                 Command::NewPuzzle(puzzle_type) => {
                     if self.confirm_discard_changes("reset puzzle") {
+                        self.relay.cancel();
                         self.puzzle = PuzzleController::new(puzzle_type);
                         self.set_status_ok(format!("Loaded {}", puzzle_type));
                     }
@@ -228,27 +541,108 @@ impl App {
                     self.prefs.colors.blindfold ^= true;
                     if self.prefs.colors.blindfold {
                         self.puzzle.visible_pieces_mut().fill(true);
-                    } else {
-                        self.timer.on_blindfold_off();
+                    } else if let Some(duration) = self.timer.on_blindfold_off() {
+                        if self.puzzle.scramble_state() == ScrambleState::Solved {
+                            self.record_solve(duration);
+                        }
                     }
                     self.prefs.needs_save = true;
                     self.request_redraw_puzzle();
                 }
 
+                Command::ToggleNetView => {
+                    let view_prefs = self.prefs.view_mut(self.puzzle.ty());
+                    view_prefs.net ^= true;
+                    self.prefs.needs_save = true;
+                    self.request_redraw_puzzle();
+                }
+                Command::ToggleExplodedView => {
+                    let exploded = !self.puzzle.is_exploded();
+                    self.puzzle.set_exploded(exploded);
+                    self.request_redraw_puzzle();
+                }
+                Command::AutoFitView => {
+                    let scale = crate::render::auto_fit_scale(
+                        &self.puzzle,
+                        &self.prefs,
+                        self.puzzle_texture_size,
+                    )
+                    .ok_or_else(|| "Nothing to fit".to_owned())?;
+                    let view_prefs = self.prefs.view_mut(self.puzzle.ty());
+                    let old = view_prefs.clone();
+                    view_prefs.scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+                    self.puzzle.animate_from_view_settings(old);
+                    self.prefs.needs_save = true;
+                    self.request_redraw_puzzle();
+                }
+                Command::ToggleInverse => {
+                    self.check_not_spectating()?;
+                    self.puzzle.toggle_inverse()?;
+                    self.last_action = if self.puzzle.is_inverse() {
+                        "Switched to inverse scramble".to_owned()
+                    } else {
+                        "Switched to normal scramble".to_owned()
+                    };
+                    self.request_redraw_puzzle();
+                }
+
+                Command::MarkSplit => {
+                    let ty = self.puzzle.ty();
+                    let phases = self
+                        .history
+                        .active_session(ty)
+                        .map_or(&[][..], |session| &session.phases);
+                    let finished = self.timer.splits().len();
+                    if phases.is_empty() {
+                        return Err("Add phases in the timer window to mark splits".to_owned());
+                    }
+                    if finished + 1 >= phases.len() {
+                        return Err("The last phase ends when the puzzle is solved".to_owned());
+                    }
+                    let phase = phases[finished].clone();
+                    let split = self.timer.mark_split().ok_or("The timer is not running")?;
+                    let time = crate::gui::windows::duration_to_str(split);
+                    self.set_status_ok(format!("Finished {phase} at {time}"));
+                }
+
+                Command::ToggleTimer => match self.timer.toggle_manual() {
+                    Some(duration) => {
+                        let time = crate::gui::windows::duration_to_str(duration);
+                        self.set_status_ok(format!("Stopped timer at {time}"));
+                    }
+                    None => self.set_status_ok("Started timer"),
+                },
+
+                Command::ToggleCommandPalette => response.toggle_command_palette = true,
+                Command::ToggleMiniMode => self.mini_mode ^= true,
+
                 Command::None => (),
             },
 
             AppEvent::Twist(twist) => {
-                if self.puzzle.is_non_rotation(twist) {
+                self.check_not_spectating()?;
+                let is_non_rotation = self.puzzle.is_non_rotation(twist);
+                // Blocked twists don't start the timer.
+                self.puzzle.twist(twist)?;
+                self.tutorial.on_event(TutorialEvent::Twist);
+                if is_non_rotation && self.twists_start_timer() {
                     self.timer.on_non_rotation_twist();
+                    self.metronome.on_non_rotation_twist();
+                }
+                self.last_action = self.describe_twist(twist);
+
+                #[cfg(not(target_arch = "wasm32"))]
+                if self.script.is_loaded() {
+                    let twist_str = self.puzzle.notation_scheme().twist_to_string(twist);
+                    let result = self.script.on_twist(twist_str, &self.puzzle);
+                    self.apply_script_actions(result);
                 }
-                self.puzzle.twist(twist)?;
             }
 
             AppEvent::Click(mouse_button) => {
                 let modifiers_mask = self.modifiers_mask(None, None);
                 let matching_mousebind = self.prefs.mousebinds.iter().find(|bind| {
-                    egui::PointerButton::from(bind.button) == mouse_button
+                    bind.button == mouse_button
                         && bind.mods() & modifiers_mask == self.pressed_modifiers() & modifiers_mask
                 });
                 if let Some(bind) = matching_mousebind {
@@ -263,6 +657,22 @@ impl App {
                                 self.puzzle.deselect_all();
                             }
                         }
+
+                        PuzzleMouseCommand::Undo => self.event(Command::Undo),
+                        PuzzleMouseCommand::Redo => self.event(Command::Redo),
+                        PuzzleMouseCommand::NextGripAxis => self.cycle_grip_axis(1),
+                        PuzzleMouseCommand::PreviousGripAxis => self.cycle_grip_axis(-1),
+                        PuzzleMouseCommand::NextGripLayer => self.cycle_grip_layer(1),
+                        PuzzleMouseCommand::PreviousGripLayer => self.cycle_grip_layer(-1),
+                        PuzzleMouseCommand::HoverBehind => {
+                            self.puzzle.cycle_hover_depth(1);
+                            self.request_redraw_puzzle();
+                        }
+                        PuzzleMouseCommand::HoverInFront => {
+                            self.puzzle.cycle_hover_depth(-1);
+                            self.request_redraw_puzzle();
+                        }
+
                         PuzzleMouseCommand::None => (),
                     }
                 }
@@ -279,7 +689,33 @@ impl App {
                 }
             }
 
+            AppEvent::Zoom(factor) => {
+                let view = self.prefs.view_mut(self.puzzle.ty());
+                view.scale = (view.scale * factor).clamp(MIN_VIEW_SCALE, MAX_VIEW_SCALE);
+                self.prefs.needs_save = true;
+            }
+            AppEvent::TouchTap => {
+                let ty = self.puzzle.ty();
+                let twists = self.puzzle.hovered_twists();
+                if let Some(twist) = twists.and_then(|tw| tw.cw.or(tw.ccw)) {
+                    self.toggle_grip.toggle_axis(twist.axis, true);
+                    if self.toggle_grip.axes.is_empty() {
+                        self.set_status_ok("Released grip");
+                    } else {
+                        self.set_status_ok(format!("Gripped {} axis", ty.info(twist.axis).name));
+                    }
+                }
+            }
+            AppEvent::TouchSwipe { twists, clockwise } => {
+                self.apply_click_twist(if clockwise { twists.cw } else { twists.ccw })?;
+            }
+
             AppEvent::StatusError(msg) => return Err(msg),
+
+            #[cfg(target_arch = "wasm32")]
+            AppEvent::WebFileOpened { name, contents } => {
+                self.try_load_opened_file(&name, &contents);
+            }
         }
 
         Ok(response)
@@ -344,6 +780,14 @@ impl App {
                             held |= !self.pressed_keys.insert(Key::Vk(vk));
                         }
 
+                        // Key presses during calibration are only timed.
+                        if self.latency_calibration.is_running() {
+                            if !held {
+                                self.latency_calibration.on_key_press();
+                            }
+                            return;
+                        }
+
                         self.handle_key_press(sc, vk, held);
                     }
 
@@ -368,15 +812,23 @@ impl App {
         &mut self,
         get_twist: fn(ClickTwists) -> Option<Twist>,
     ) -> Result<(), &'static str> {
+        match self.puzzle.hovered_twists() {
+            Some(twists) => self.apply_click_twist(get_twist(twists)),
+            None => Ok(()),
+        }
+    }
+    /// Applies a twist from clicking or swiping a sticker, using the gripped
+    /// layers.
+    fn apply_click_twist(&mut self, twist: Option<Twist>) -> Result<(), &'static str> {
         if self.puzzle.current_twist().is_none() {
-            if let Some(twists) = self.puzzle.hovered_twists() {
-                if let Some(mut t) = get_twist(twists) {
-                    t.layers = self.gripped_layers(t.layers);
-                    if self.puzzle.is_non_rotation(t) {
-                        self.timer.on_non_rotation_twist();
-                    }
-                    self.puzzle.twist(t)?;
+            if let Some(mut t) = twist {
+                t.layers = self.gripped_layers(t.layers);
+                if self.puzzle.is_non_rotation(t) && self.twists_start_timer() {
+                    self.timer.on_non_rotation_twist();
+                    self.metronome.on_non_rotation_twist();
                 }
+                self.puzzle.twist(t)?;
+                self.tutorial.on_event(TutorialEvent::Twist);
             }
         }
         Ok(())
@@ -552,6 +1004,33 @@ impl App {
 
                     success = true;
                 }
+                PuzzleCommand::GripGroup {
+                    mode,
+                    grip_group_name,
+                } => {
+                    let ty = self.puzzle.ty();
+                    if grip_group_name == "Inverse" {
+                        self.toggle_grip.invert_layers(ty.layer_count());
+                    } else {
+                        let Some(preset) = self.prefs.grip_groups[ty]
+                            .iter()
+                            .find(|p| p.preset_name == *grip_group_name)
+                        else {
+                            self.set_status_err(format!(
+                                "Unable to find grip group {grip_group_name:?}"
+                            ));
+                            return;
+                        };
+                        let grip = preset.value.to_grip(ty);
+                        match mode {
+                            GripGroupMode::Select => self.toggle_grip = grip,
+                            GripGroupMode::Add => self.toggle_grip |= &grip,
+                            GripGroupMode::Intersect => self.toggle_grip &= &grip,
+                        }
+                    }
+
+                    success = true;
+                }
 
                 PuzzleCommand::KeybindSet { keybind_set_name } => {
                     let set_name = keybind_set_name.clone();
@@ -564,6 +1043,12 @@ impl App {
                     }
                     return; // Do not try to match other keybinds.
                 }
+                PuzzleCommand::Layer { layer_name } => {
+                    self.held_layers.retain(|&(k, _)| k != key);
+                    self.held_layers.push((key, layer_name.clone()));
+
+                    success = true;
+                }
                 PuzzleCommand::ViewPreset { view_preset_name } => {
                     let presets = match self.puzzle.ty().projection_type() {
                         ProjectionType::_3D => &mut self.prefs.view_3d,
@@ -621,7 +1106,7 @@ impl App {
 
         let modifiers_mask = self.modifiers_mask(sc, vk);
 
-        keybinds
+        let mut ret = keybinds
             .into_iter()
             .filter(move |bind| {
                 let key_combo = bind.key;
@@ -629,9 +1114,36 @@ impl App {
                 let key_matches = (sc.is_some() && sc == key) || (vk.is_some() && vk == key);
                 let mods_match =
                     key_combo.mods() & modifiers_mask == self.pressed_modifiers() & modifiers_mask;
-                key_matches && mods_match
+                let chord_matches = bind
+                    .chord
+                    .iter()
+                    .all(|k| self.pressed_keys.contains(k) || self.toggled_keys.contains(k));
+                key_matches && mods_match && chord_matches
             })
-            .collect()
+            .collect_vec();
+
+        // Keybinds in the active layer take priority. If the active layer
+        // doesn't bind this key, then fall back to the base layer.
+        let layer = self.active_keybind_layer();
+        if ret.iter().any(|bind| bind.layer == layer) {
+            ret.retain(|bind| bind.layer == layer);
+        } else {
+            ret.retain(|bind| bind.layer.is_empty());
+        }
+
+        // Chords with more keys take priority, so that pressing a chord
+        // doesn't also activate keybinds for its last key alone.
+        let longest_chord = ret.iter().map(|bind| bind.chord.len()).max();
+        ret.retain(|bind| Some(bind.chord.len()) == longest_chord);
+
+        ret
+    }
+    /// Returns the name of the active keybind layer, which is empty for the
+    /// base layer.
+    pub(crate) fn active_keybind_layer(&self) -> &str {
+        self.held_layers
+            .last()
+            .map_or("", |(_, layer_name)| layer_name.as_str())
     }
     fn modifiers_mask(&self, sc: Option<Key>, vk: Option<Key>) -> ModifiersState {
         // Sometimes, we want to ignore certain modifier keys when resolving a
@@ -645,14 +1157,19 @@ impl App {
         // A modifier is also ignored when matching its own key, hence
         // `.chain(&sc).chain(&vk)`. For example, the shift modifier is ignored
         // when matching the shift key.
-        let ignored_keys = self.transient_grips.keys().chain(&sc).chain(&vk);
+        let ignored_keys = self
+            .transient_grips
+            .keys()
+            .chain(self.held_layers.iter().map(|(k, _)| k))
+            .chain(&sc)
+            .chain(&vk);
         ignored_keys.fold(
             // Consider all modifiers, but don't distinguish left vs. right.
             ModifiersState::SHIFT
                 | ModifiersState::CTRL
                 | ModifiersState::ALT
                 | ModifiersState::LOGO,
-            // Ignore held greps and the key currently being pressed.
+            // Ignore held grips and layers, and the key currently being pressed.
             |mods, key_to_ignore| mods & !key_to_ignore.modifier_bit(),
         )
     }
@@ -720,6 +1237,9 @@ impl App {
     pub(crate) fn pressed_keys(&self) -> &HashSet<Key> {
         &self.pressed_keys
     }
+    pub(crate) fn toggled_keys(&self) -> &HashSet<Key> {
+        &self.toggled_keys
+    }
     pub(crate) fn pressed_modifiers(&self) -> ModifiersState {
         self.pressed_modifiers | self.toggled_modifiers
     }
@@ -758,18 +1278,768 @@ impl App {
         }
     }
 
+    /// Handles a click on a key of the on-screen keyboard. Keys that do
+    /// something while held, such as modifiers, grips, and layers, are toggled
+    /// on or off. Other keys are pressed and immediately released.
+    pub(crate) fn click_key(&mut self, sc: KeyMappingCode) {
+        let vk = key_names::key_to_winit_vkey(sc);
+
+        let is_held_key = !Key::Sc(sc).modifier_bit().is_empty()
+            || self.toggled_keys.contains(&Key::Sc(sc))
+            || self
+                .resolve_keypress(
+                    self.prefs.puzzle_keybinds[self.puzzle.ty()].get_active_keybinds(),
+                    Some(sc),
+                    vk,
+                )
+                .iter()
+                .any(|bind| {
+                    matches!(
+                        bind.command,
+                        PuzzleCommand::Grip { .. } | PuzzleCommand::Layer { .. }
+                    )
+                });
+
+        if is_held_key {
+            self.toggle_key(Some(sc), vk);
+        } else {
+            self.handle_key_press(Some(sc), vk, false);
+            self.handle_key_release(Some(sc), vk);
+        }
+    }
+
     pub(crate) fn frame(&mut self) {
-        self.puzzle.set_grip(self.grip(), &self.prefs.interaction);
+        #[cfg(not(target_arch = "wasm32"))]
+        crate::crash::update_snapshot(self);
+
+        let grip = self.grip();
+        if !grip.axes.is_empty() {
+            self.tutorial.on_event(TutorialEvent::Grip);
+        }
+        self.puzzle.set_grip(grip, &self.prefs.interaction);
+        self.timer
+            .set_input_latency(self.prefs.timer.input_latency());
+        if self.timer.is_running() {
+            self.tutorial.on_event(TutorialEvent::TimerStarted);
+        }
 
-        if self.puzzle.check_just_solved() {
+        if self.puzzle.check_just_solved(
+            self.prefs.interaction.solved_state,
+            self.prefs.interaction.supercube,
+        ) {
             if !self.prefs.colors.blindfold {
                 self.set_status_ok("Solved!");
             }
-            self.timer.on_solve();
+            let duration = if self.relay.is_running() {
+                self.on_relay_puzzle_solved()
+            } else {
+                let duration = self.timer.on_solve();
+                if let Some(duration) = duration {
+                    self.record_solve(duration);
+                }
+                duration
+            };
+            if let Some(analysis) = self.metronome.on_solve(&self.prefs.metronome) {
+                if self.prefs.metronome.flag_off_beat {
+                    self.toasts.push("Metronome", analysis.to_string());
+                }
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if self.script.is_loaded() {
+                let time_ms = duration.map(|d| d.as_millis() as u64);
+                let result = self.script.on_solve(time_ms, &self.puzzle);
+                self.apply_script_actions(result);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(api) = &mut self.api {
+                api.send_event(crate::net::ApiEvent::Solved {
+                    time_ms: duration.map(|d| d.as_millis() as u64),
+                    twist_count: self.puzzle.twist_count(self.prefs.info.metric),
+                });
+            }
+        }
+
+        self.update_sounds();
+        self.update_last_layer_case();
+
+        let options = self.prefs.scramble.options(self.puzzle.ty());
+        let queue_length = self.prefs.scramble.queue_length;
+        self.scramble_queue.refill(self.puzzle.ty(), &options, queue_length);
+
+        if let Some(keyframe) = self.prefs.camera_path.update_playback() {
+            keyframe.apply_to_view(self.prefs.view_mut(self.puzzle.ty()));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.reload_external_prefs_edit();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_race();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_spectate();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_sync();
+        #[cfg(not(target_arch = "wasm32"))]
+        self.update_api();
+        #[cfg(not(target_arch = "wasm32"))]
+        for e in self.hotkeys.update(&self.prefs.hotkeys, &self.events) {
+            self.toasts
+                .push_with_severity(Severity::Warning, "Global hotkeys", e);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut overlay = std::mem::take(&mut self.overlay);
+            overlay.update(self);
+            self.overlay = overlay;
+        }
+    }
+
+    /// Merges changes to the preferences file made outside the app.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_external_prefs_edit(&mut self) {
+        let Some(edit) = self.prefs_watcher.as_ref().and_then(|w| w.poll()) else {
+            return;
+        };
+        match self.prefs.merge_external_edit(edit) {
+            Ok(false) => (),
+            Ok(true) => {
+                self.set_status_ok("Reloaded preferences");
+                self.request_redraw_puzzle();
+            }
+            Err(e) => self.set_status_err(format!("Error reloading preferences: {e}")),
+        }
+    }
+
+    /// Sends the local puzzle to spectators and applies changes from the
+    /// puzzle being spectated.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_spectate(&mut self) {
+        if let Some(host) = &mut self.spectate_host {
+            host.update(&self.puzzle);
+        }
+
+        let messages = match &mut self.spectating {
+            Some(client) => client.poll(),
+            None => return,
+        };
+        for message in messages {
+            if let Err(e) = self.apply_spectate_message(message) {
+                self.set_status_err(e);
+            }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn apply_spectate_message(
+        &mut self,
+        message: crate::net::SpectateMessage,
+    ) -> Result<(), String> {
+        use crate::net::SpectateMessage;
+
+        let parse_twists = |twists: &[String]| -> Result<Vec<Twist>, String> {
+            twists
+                .iter()
+                .map(|s| s.parse().map_err(|()| format!("Invalid twist {s:?} from host")))
+                .collect()
+        };
+
+        match message {
+            SpectateMessage::Reset {
+                puzzle,
+                scramble,
+                twists,
+            } => {
+                // Discarding the local puzzle was confirmed when we started
+                // spectating, and it has been read-only since then.
+                if self.puzzle.ty() != puzzle {
+                    self.puzzle = PuzzleController::new(puzzle);
+                }
+                self.puzzle.scramble_with(&parse_twists(&scramble)?)?;
+                for twist in parse_twists(&twists)? {
+                    self.puzzle.twist_no_collapse(twist)?;
+                }
+                self.puzzle.skip_twist_animations();
+            }
+            SpectateMessage::Twist { twist, .. } => {
+                let twist = parse_twists(&[twist])?[0];
+                self.puzzle.twist_no_collapse(twist)?;
+            }
+            SpectateMessage::Undo { count, .. } => {
+                for _ in 0..count {
+                    self.puzzle.undo()?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Applies actions requested by a user script.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn apply_script_actions(
+        &mut self,
+        result: Result<Vec<crate::scripting::ScriptAction>, String>,
+    ) {
+        let actions = match result {
+            Ok(actions) => actions,
+            Err(e) => {
+                self.set_status_err(format!("Script error: {e}"));
+                return;
+            }
+        };
+        for action in actions {
+            if let Err(e) = self.apply_script_action(action) {
+                self.set_status_err(format!("Script error: {e}"));
+                break;
+            }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn apply_script_action(
+        &mut self,
+        action: crate::scripting::ScriptAction,
+    ) -> Result<(), String> {
+        use crate::scripting::ScriptAction;
+
+        self.check_not_spectating()?;
+        match action {
+            ScriptAction::Twist(s) => {
+                let twist = self.puzzle.notation_scheme().parse_twist(&s)?;
+                self.puzzle.twist(twist)?;
+            }
+            ScriptAction::ScrambleN(n) => {
+                let options = self.prefs.scramble.options(self.puzzle.ty());
+                self.puzzle.scramble_n(n, &options)?;
+                self.timer.on_scramble();
+            }
+            ScriptAction::ScrambleFull => {
+                let options = self.prefs.scramble.options(self.puzzle.ty());
+                self.puzzle.scramble_full(&options)?;
+                self.timer.on_scramble();
+            }
+            ScriptAction::Undo => self.puzzle.undo()?,
+            ScriptAction::Redo => self.puzzle.redo()?,
+            ScriptAction::Reset => self.puzzle.reset(),
+            ScriptAction::Select(sticker) => {
+                if sticker.0 as usize >= self.puzzle.stickers().len() {
+                    return Err(format!("No sticker with index {}", sticker.0));
+                }
+                self.puzzle.select(sticker);
+            }
+            ScriptAction::DeselectAll => self.puzzle.deselect_all(),
+        }
+        Ok(())
+    }
+
+    /// Returns whether twisting the puzzle should start the timer, which it
+    /// doesn't in fewest moves mode or while exploring.
+    fn twists_start_timer(&self) -> bool {
+        !self.fmc.enabled && !self.puzzle.is_exploring()
+    }
+    /// Starts watching another player's puzzle, which replaces the local one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn start_spectating(&mut self, address: &str, code: &str) {
+        // The host's state overwrites the local puzzle as soon as it arrives.
+        if self.confirm_discard_changes("watch another player's puzzle") {
+            self.spectating = Some(crate::net::SpectateClient::connect(address, code));
+        }
+    }
+    /// Returns an error if the puzzle is read-only because it is being
+    /// spectated.
+    fn check_not_spectating(&self) -> Result<(), String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.spectating.is_some() {
+            return Err("Puzzle is read-only while spectating".to_owned());
+        }
+        Ok(())
+    }
+
+    /// Handles messages from the race lobby and reports our progress.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_race(&mut self) {
+        let race_start = match &mut self.race {
+            Some(race) => race.poll(),
+            None => return,
+        };
+        if let Some(race_start) = race_start {
+            // Don't block the race on a dialog; let the player decide from the
+            // race window instead.
+            if self.has_unsaved_changes() {
+                self.pending_race = Some(race_start);
+                self.set_status_ok("Race started! Join it from the race window");
+            } else {
+                self.pending_race = None;
+                self.join_race(race_start);
+            }
+        }
+
+        let twist_count = self.puzzle.twist_count(self.prefs.info.metric);
+        if let Some(race) = &mut self.race {
+            if self.pending_race.is_none() {
+                race.report_progress(twist_count);
+            }
+        }
+    }
+    /// Loads the scramble of a race, discarding the current puzzle state.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn join_race(&mut self, race_start: crate::net::RaceStart) {
+        match self.load_race_scramble(race_start) {
+            Ok(()) => {
+                self.timer.on_race_start();
+                self.set_status_ok("Race started!");
+            }
+            Err(e) => self.set_status_err(e),
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_race_scramble(&mut self, race_start: crate::net::RaceStart) -> Result<(), String> {
+        let twists: Vec<Twist> = race_start
+            .scramble
+            .iter()
+            .map(|s| s.parse().map_err(|()| format!("Invalid twist {s:?} in race scramble")))
+            .collect::<Result<_, _>>()?;
+        self.prefs.log_file = None;
+        self.prefs.needs_save = true;
+        if self.puzzle.ty() != race_start.puzzle {
+            self.puzzle = PuzzleController::new(race_start.puzzle);
+        }
+        self.puzzle.scramble_with(&twists)?;
+        Ok(())
+    }
+    /// Starts a new race for everyone in the lobby with a random scramble of
+    /// the current puzzle.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn start_race(&mut self) -> Result<(), String> {
+        let race = self.race.as_ref().ok_or("Not connected to a race lobby")?;
+        let mut scrambled = PuzzleController::new(self.puzzle.ty());
+        scrambled.scramble_full(&self.prefs.scramble.options(self.puzzle.ty()))?;
+        race.start_race(scrambled.ty(), scrambled.scramble());
+        Ok(())
+    }
+
+    /// Starts or stops the local API for external tools to match the
+    /// preferences, sends it any changes, and applies commands from it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_api(&mut self) {
+        let prefs = &self.prefs.api;
+        if !prefs.enabled {
+            self.api = None;
+            return;
+        }
+        if self.api.as_ref().map(|api| api.port()) != Some(prefs.port) {
+            self.api = None;
+            match crate::net::ApiServer::start(prefs.port) {
+                Ok(api) => self.api = Some(api),
+                Err(e) => {
+                    // Don't keep trying every frame.
+                    self.prefs.api.enabled = false;
+                    self.prefs.needs_save = true;
+                    self.set_status_err(e);
+                    return;
+                }
+            }
+        }
+
+        let Some(api) = &mut self.api else {
+            return;
+        };
+        let commands = api.take_commands();
+        for command in commands {
+            if let Err(message) = self.apply_api_command(command) {
+                if let Some(api) = &mut self.api {
+                    api.send_event(crate::net::ApiEvent::Error { message });
+                }
+            }
+        }
+        if let Some(api) = &mut self.api {
+            api.update(&self.puzzle, self.timer.is_running());
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn apply_api_command(&mut self, command: crate::net::ApiCommand) -> Result<(), String> {
+        use crate::net::ApiCommand;
+
+        self.check_not_spectating()?;
+        match command {
+            ApiCommand::Twist { twist } => {
+                let twist = self.puzzle.notation_scheme().parse_twist(&twist)?;
+                self.event(AppEvent::Twist(twist));
+            }
+            ApiCommand::Scramble => {
+                self.relay.cancel();
+                let options = self.prefs.scramble.options(self.puzzle.ty());
+                self.puzzle.scramble_full(&options)?;
+                self.timer.on_scramble();
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts syncing solve history and preferences with the server.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn start_sync(&mut self) -> Result<(), String> {
+        if self.prefs.sync.url.trim().is_empty() {
+            return Err("No sync server URL set".to_owned());
+        }
+        if self.sync.is_busy() {
+            return Err("Already syncing".to_owned());
+        }
+        self.sync.download(&self.prefs.sync);
+        Ok(())
+    }
+    /// Merges files downloaded from the sync server and uploads the result.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_sync(&mut self) {
+        match self.sync.poll() {
+            Some(Ok(crate::net::SyncEvent::Downloaded(files))) => {
+                match self.merge_synced_files(files) {
+                    Ok(merged) => self.sync.upload(&self.prefs.sync, merged),
+                    Err(e) => {
+                        self.sync.fail(e.clone());
+                        self.set_status_err(e);
+                    }
+                }
+            }
+            Some(Ok(crate::net::SyncEvent::Uploaded)) => {
+                self.set_status_ok("Synced solve history and preferences");
+            }
+            Some(Err(e)) => self.set_status_err(e),
+            None => (),
+        }
+    }
+    /// Merges files downloaded from the sync server into the local solve
+    /// history and preferences, and returns the merged files.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn merge_synced_files(
+        &mut self,
+        files: crate::net::SyncFiles,
+    ) -> Result<crate::net::SyncFiles, String> {
+        if let Some(contents) = &files.history {
+            let history = serde_yaml::from_str(contents)
+                .map_err(|e| format!("Error reading synced solve history: {e}"))?;
+            self.history.merge(history);
+        }
+        if let Some(contents) = &files.prefs {
+            let changed = self
+                .prefs
+                .merge_synced(contents)
+                .map_err(|e| format!("Error reading synced preferences: {e}"))?;
+            if changed {
+                self.request_redraw_puzzle();
+            }
+        }
+
+        let error = |e: anyhow::Error| format!("Error preparing files to sync: {e}");
+        Ok(crate::net::SyncFiles {
+            history: Some(self.history.to_synced_string().map_err(error)?),
+            prefs: Some(self.prefs.to_synced_string().map_err(error)?),
+        })
+    }
+
+    /// Adds a solve to the active session, if the puzzle was fully scrambled.
+    fn record_solve(&mut self, duration: instant::Duration) {
+        if !self.puzzle.has_been_fully_scrambled() {
+            return;
+        }
+        let metric = self.prefs.info.metric;
+        let solve = Solve {
+            time_ms: duration.as_millis() as u64,
+            timestamp: crate::util::unix_timestamp(),
+            twist_count: self.puzzle.twist_count(metric),
+            metric,
+            blind: self.timer.is_blind(),
+            seed: self.puzzle.scramble_seed(),
+            scrambler: self.puzzle.scrambler_version().map(str::to_owned),
+            scramble: self.puzzle.scramble().iter().join(" "),
+            solution: self.puzzle.niss_solution().iter().join(" "),
+            splits_ms: vec![],
+            phase_splits_ms: self
+                .timer
+                .splits()
+                .iter()
+                .map(|split| split.as_millis() as u64)
+                .collect(),
+            milestones: vec![],
+            last_layer_case: self
+                .scrambled_rubiks_3d()
+                .and_then(|(mut puzzle, solution)| {
+                    last_layer::first_last_layer_case(&mut puzzle, &solution)
+                })
+                .map(|case| case.to_string())
+                .unwrap_or_default(),
+            method: self
+                .scrambled_rubiks_3d()
+                .and_then(|(mut puzzle, solution)| method::detect_method(&mut puzzle, &solution))
+                .map(|method| method.to_string())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(race) = &mut self.race {
+            race.report_finished(solve.time_ms, solve.twist_count);
+        }
+        let ty = self.puzzle.ty();
+        let is_daily = self.daily_challenge_day.map_or(false, |day| {
+            self.puzzle.scramble_seed() == Some(daily_seed(day, ty))
+        });
+        let milestones = if is_daily {
+            self.history.record_daily_solve(ty, solve)
+        } else {
+            self.history.record_solve(ty, solve)
+        };
+        self.celebrate_milestones(duration, &milestones);
+    }
+
+    /// Returns a new copy of the puzzle with only the scramble applied, along
+    /// with the solution, if the puzzle is a 3D cube.
+    fn scrambled_rubiks_3d(&self) -> Option<(Rubiks3D, Vec<Twist>)> {
+        let Puzzle::Rubiks3D(mut puzzle) = Puzzle::new(self.puzzle.ty()) else {
+            return None;
+        };
+        for &twist in self.puzzle.scramble() {
+            puzzle.twist(twist).ok()?;
+        }
+        Some((puzzle, self.puzzle.niss_solution()))
+    }
+
+    /// Shows the last layer case in the status bar when a 3D cube reaches the
+    /// last layer and again when the last layer is oriented.
+    fn update_last_layer_case(&mut self) {
+        if !self.prefs.info.last_layer_case || self.prefs.colors.blindfold {
+            return;
+        }
+        let current = self.puzzle.undo_tree().current();
+        if matches!(&self.last_layer_case, Some((node, _)) if *node == current) {
+            return;
+        }
+        let case = match self.puzzle.latest() {
+            Puzzle::Rubiks3D(puzzle) if self.puzzle.has_been_fully_scrambled() => {
+                last_layer::last_layer_case(puzzle)
+            }
+            _ => None,
+        };
+
+        let old_case = self
+            .last_layer_case
+            .replace((current, case.clone()))
+            .and_then(|(_, case)| case);
+        match (old_case, case) {
+            (None, Some(case)) => self.set_status_ok(format!("Last layer: {case}")),
+            (Some(old), Some(case)) if old.oll.is_some() && case.oll.is_none() => {
+                let pll = case.pll.unwrap_or_else(|| "PLL skip".to_owned());
+                self.set_status_ok(format!("Last layer: {pll}"));
+            }
+            _ => (),
+        }
+    }
+
+    /// Imports solves from a directory of MagicCube4D log files.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn import_mc4d_logs(&mut self, dir: &Path) {
+        let (solves, errors) = crate::logfile::import_mc4d_solves(dir, self.prefs.info.metric);
+        for e in &errors {
+            log::warn!("Error importing log file {e}");
+        }
+        let count = self.history.import_solves(solves);
+        let session = crate::history::IMPORTED_SESSION_NAME;
+        let msg = format!("Imported {count} solves into the {session:?} session");
+        if errors.is_empty() {
+            self.set_status_ok(msg);
+        } else {
+            self.set_status_err(format!(
+                "{msg}; unable to read {} files (see log for details)",
+                errors.len(),
+            ));
         }
     }
 
-    fn confirm_load_puzzle(&self, warnings: &[String]) -> bool {
+    /// Loads the scramble and solution of a recorded solve so that the
+    /// solution can be stepped through using redo.
+    pub(crate) fn load_replay(&mut self, ty: PuzzleTypeEnum, solve: &Solve) -> Result<(), String> {
+        if solve.scramble.is_empty() {
+            return Err("This solve has no recorded scramble".to_owned());
+        }
+        let parse = |s: &str| {
+            s.split_whitespace()
+                .map(|t| t.parse().map_err(|()| format!("Invalid twist {t:?} in replay")))
+                .collect::<Result<Vec<Twist>, String>>()
+        };
+        let scramble = parse(&solve.scramble)?;
+        let solution = parse(&solve.solution)?;
+        if !self.confirm_discard_changes("load replay") {
+            return Ok(());
+        }
+
+        let mut puzzle = PuzzleController::new(ty);
+        puzzle.scramble_with(&scramble)?;
+        for &twist in &solution {
+            puzzle.twist_no_collapse(twist)?;
+        }
+        // Mark the puzzle as solved so that replaying the solution doesn't
+        // record another solve.
+        puzzle.check_just_solved(
+            self.prefs.interaction.solved_state,
+            self.prefs.interaction.supercube,
+        );
+        while puzzle.has_undo() {
+            puzzle.undo()?;
+        }
+        puzzle.skip_twist_animations();
+
+        self.relay.cancel();
+        self.puzzle = puzzle;
+        self.set_status_ok("Loaded replay; redo to step through the solution");
+        Ok(())
+    }
+
+    /// Scrambles the puzzle from a seed or from a scramble written in the
+    /// puzzle's notation.
+    pub(crate) fn load_scramble(&mut self, s: &str) -> Result<(), String> {
+        self.check_not_spectating()?;
+        let s = s.trim();
+        let seed = s.parse::<u64>().ok();
+        let twists = match seed {
+            Some(_) => vec![],
+            None => {
+                let notation = self.puzzle.notation_scheme();
+                self.puzzle
+                    .split_twists_string(s)
+                    .map(|m| {
+                        notation
+                            .parse_twist(m.as_str())
+                            .map_err(|e| format!("Invalid twist {:?} in scramble: {e}", m.as_str()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+        };
+        if seed.is_none() && twists.is_empty() {
+            return Err("Enter a seed or a scramble".to_owned());
+        }
+
+        if self.confirm_discard_changes("scramble") {
+            self.relay.cancel();
+            match seed {
+                Some(seed) => self.puzzle.scramble_full_seeded(seed)?,
+                None => self.puzzle.scramble_with(&twists)?,
+            }
+            self.set_status_ok("Loaded scramble");
+            self.last_action = "Loaded scramble".to_owned();
+            self.timer.on_scramble();
+        }
+        Ok(())
+    }
+
+    /// Starts a relay of the puzzles chosen in the relay window, beginning
+    /// with the first one.
+    pub(crate) fn start_relay(&mut self) -> Result<(), String> {
+        self.check_not_spectating()?;
+        if self.timer.is_blind() {
+            return Err("Relays cannot be done in blind mode".to_owned());
+        }
+        if !self.confirm_discard_changes("start a relay") {
+            return Ok(());
+        }
+        self.relay.start()?;
+        self.load_relay_puzzle()?;
+        self.set_status_ok("Relay started");
+        self.timer.on_scramble();
+        Ok(())
+    }
+    /// Loads and scrambles the next puzzle in the relay.
+    fn load_relay_puzzle(&mut self) -> Result<(), String> {
+        if let Some((ty, scramble)) = self.relay.current_puzzle() {
+            let mut puzzle = PuzzleController::new(ty);
+            puzzle.scramble_with(scramble)?;
+            puzzle.mark_scramble_generated();
+            self.puzzle = puzzle;
+        }
+        Ok(())
+    }
+    /// Records a split for the puzzle that was just solved in a relay and
+    /// moves on to the next one. Returns the final time if that was the last
+    /// puzzle.
+    fn on_relay_puzzle_solved(&mut self) -> Option<instant::Duration> {
+        let split = self.timer.elapsed()?;
+        let twist_count = self.puzzle.twist_count(self.prefs.info.metric);
+        self.relay.record_split(split, twist_count);
+        if self.relay.is_running() {
+            if let Err(e) = self.load_relay_puzzle() {
+                self.relay.cancel();
+                self.set_status_err(e);
+            }
+            return None;
+        }
+
+        let duration = self.timer.on_solve()?;
+        let metric = self.prefs.info.metric;
+        let solve = Solve {
+            time_ms: duration.as_millis() as u64,
+            timestamp: crate::util::unix_timestamp(),
+            twist_count: self.relay.twist_count(),
+            metric,
+            blind: false,
+            seed: None,
+            scrambler: Some(crate::puzzle::SCRAMBLER_VERSION.to_owned()),
+            splits_ms: self
+                .relay
+                .splits()
+                .iter()
+                .map(|split| split.as_millis() as u64)
+                .collect(),
+            phase_splits_ms: vec![],
+            milestones: vec![],
+            ..Default::default()
+        };
+        let name = crate::gui::windows::relay_name(self.relay.attempt_puzzles());
+        let milestones = self.history.record_relay_solve(&name, solve);
+        self.celebrate_milestones(duration, &milestones);
+        Some(duration)
+    }
+
+    /// Plays a sound and shows a notification for a solve's milestones.
+    fn celebrate_milestones(&mut self, duration: instant::Duration, milestones: &[Milestone]) {
+        if milestones.iter().any(|m| m.is_personal_best()) {
+            self.play_sound(Sound::PersonalBest);
+        }
+        if !milestones.is_empty() {
+            let time = crate::gui::windows::duration_to_str(duration);
+            let message = milestones.iter().map(|m| m.description()).join("\n");
+            self.toasts.push(format!("🎉 {time}"), message);
+        }
+    }
+
+    fn update_sounds(&mut self) {
+        if self.puzzle.take_completed_twist_count() > 0 {
+            self.play_sound(Sound::Twist);
+        }
+
+        let timer_running = self.timer.is_running();
+        if timer_running != self.timer_was_running {
+            self.timer_was_running = timer_running;
+            self.play_sound(match timer_running {
+                true => Sound::TimerStart,
+                false => Sound::TimerStop,
+            });
+        }
+
+        if self.timer.take_inspection_call() {
+            self.play_sound(Sound::InspectionWarning);
+        }
+
+        if self.metronome.take_beat(&self.prefs.metronome) {
+            self.play_sound(Sound::MetronomeTick);
+        }
+        if self.latency_calibration.take_beat() {
+            self.play_sound(Sound::MetronomeTick);
+        }
+    }
+    fn play_sound(&mut self, sound: Sound) {
+        self.audio.play(sound, &self.prefs.sounds);
+    }
+
+    fn confirm_load_puzzle(&mut self, warnings: &[String]) -> bool {
+        for warning in warnings {
+            self.toasts
+                .log(Severity::Warning, "Error loading file", warning);
+        }
         warnings.is_empty()
             || rfd::MessageDialog::new()
                 .set_title("Errors loading file")
@@ -782,16 +2052,14 @@ impl App {
                 .show()
     }
 
+    /// Returns whether discarding the puzzle state needs confirmation.
+    fn has_unsaved_changes(&self) -> bool {
+        self.puzzle.is_unsaved()
+            && !(self.prefs.interaction.confirm_discard_only_when_scrambled
+                && !self.puzzle.has_been_fully_scrambled())
+    }
     fn confirm_discard_changes(&mut self, action: &str) -> bool {
-        let mut needs_save = self.puzzle.is_unsaved();
-
-        if self.prefs.interaction.confirm_discard_only_when_scrambled
-            && !self.puzzle.has_been_fully_scrambled()
-        {
-            needs_save = false;
-        }
-
-        let confirm = !needs_save
+        let confirm = !self.has_unsaved_changes()
             || rfd::MessageDialog::new()
                 .set_title("Unsaved changes")
                 .set_description(&format!("Discard puzzle state and {}?", action))
@@ -873,6 +2141,95 @@ impl App {
             self.try_save_puzzle(&path)
         }
     }
+    #[cfg(target_arch = "wasm32")]
+    fn try_load_opened_file(&mut self, file_name: &str, contents: &str) {
+        match crate::logfile::deserialize(contents) {
+            Ok((puzzle, warnings)) => {
+                if self.confirm_load_puzzle(&warnings) {
+                    self.puzzle = puzzle;
+
+                    self.set_status_ok(format!("Loaded log file {file_name}"));
+                }
+            }
+            Err(e) => self.set_status_err(format!("Unable to load log file: {e}")),
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    fn try_download_puzzle(&mut self) {
+        let file_name = format!("{}.hsc", self.puzzle.name());
+        let result = crate::logfile::serialize(&self.puzzle, LogFileFormat::Hsc)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| crate::web_files::download_file(&file_name, &contents));
+        match result {
+            Ok(()) => {
+                self.puzzle.mark_saved();
+                self.set_status_ok(format!("Downloaded log file {file_name}"));
+            }
+            Err(e) => self.set_status_err(format!("Unable to save log file: {e}")),
+        }
+    }
+
+    /// Takes a screenshot of the puzzle view, if one was requested this
+    /// frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn take_requested_screenshot(&mut self, gfx: &mut GraphicsState) {
+        if !std::mem::take(&mut self.screenshot_requested) {
+            return;
+        }
+        match self.try_take_screenshot(gfx) {
+            Ok(path) => self.set_status_ok(format!("Saved screenshot to {}", path.display())),
+            Err(e) => self.set_status_err(format!("Unable to take screenshot: {e}")),
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_take_screenshot(&mut self, gfx: &mut GraphicsState) -> anyhow::Result<PathBuf> {
+        let prefs = self.prefs.screenshots.clone();
+        let screenshot = crate::render::capture_puzzle(self, gfx, prefs.scale)?;
+
+        let mut path = prefs
+            .directory()
+            .ok_or_else(|| anyhow::anyhow!("no screenshot directory"))?;
+        std::fs::create_dir_all(&path)?;
+        path.push(format!("hyperspeedcube_{}.png", file_name_timestamp()));
+        screenshot.save_png(&path)?;
+
+        if prefs.copy_to_clipboard {
+            screenshot.copy_to_clipboard()?;
+        }
+
+        Ok(path)
+    }
+
+    /// Requests exporting the rest of the solution as a sequence of images
+    /// after drawing the puzzle this frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn request_replay_export(&mut self) {
+        self.replay_export_requested = true;
+    }
+    /// Exports the replay, if it was requested this frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn export_requested_replay(&mut self, gfx: &mut GraphicsState) {
+        if !std::mem::take(&mut self.replay_export_requested) {
+            return;
+        }
+        match self.try_export_replay(gfx) {
+            Ok((frame_count, path)) => self.set_status_ok(format!(
+                "Exported {frame_count} frames to {}",
+                path.display(),
+            )),
+            Err(e) => self.set_status_err(format!("Unable to export replay: {e}")),
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_export_replay(&mut self, gfx: &mut GraphicsState) -> anyhow::Result<(usize, PathBuf)> {
+        let prefs = self.prefs.screenshots.clone();
+        let mut path = prefs
+            .directory()
+            .ok_or_else(|| anyhow::anyhow!("no screenshot directory"))?;
+        path.push(format!("hyperspeedcube_replay_{}", file_name_timestamp()));
+        let frame_count = crate::render::export_replay_frames(self, gfx, &path, prefs.scale)?;
+        Ok((frame_count, path))
+    }
 
     #[cfg(target_arch = "wasm32")]
     const LOCAL_STORAGE_KEY: &str = "hyperspeedcube_puzzle_log";
@@ -912,14 +2269,61 @@ impl App {
     pub(crate) fn status_msg(&self) -> &str {
         &self.status_msg
     }
+
+    /// Returns a text description of the puzzle state and the last action,
+    /// such as `Twisted R CW; 23 moves; timer 1:42.000; Fully scrambled`.
+    pub(crate) fn accessibility_description(&self) -> String {
+        let mut parts = vec![];
+        if !self.last_action.is_empty() {
+            parts.push(self.last_action.clone());
+        }
+        parts.push(format!(
+            "{} moves",
+            self.puzzle.twist_count(self.prefs.info.metric),
+        ));
+        parts.push(format!("timer {}", self.timer.display_str()));
+        parts.push(self.puzzle.scramble_state().description().to_owned());
+        parts.join("; ")
+    }
+    fn describe_twist(&self, twist: Twist) -> String {
+        let ty = self.puzzle.ty();
+        let axis = ty.info(twist.axis).name;
+        let direction = match ty.info(twist.direction).name {
+            "CW" => "clockwise",
+            "CCW" => "counterclockwise",
+            other => other,
+        };
+        format!("Twisted {axis} {direction}")
+    }
+
+    pub(crate) fn fps(&self) -> u32 {
+        self.fps
+    }
+    pub(crate) fn set_fps(&mut self, fps: u32) {
+        self.fps = fps;
+    }
+    /// Returns whether anything is changing without user input, such as a
+    /// twist animation or a running timer, in which case the app shouldn't go
+    /// idle.
+    pub(crate) fn is_animating(&self) -> bool {
+        (self.puzzle.twist_queue_len() > 0 && !self.puzzle.is_twist_queue_paused())
+            || self.timer.is_running()
+            || self.timer.is_inspecting()
+            || self.metronome.is_running()
+            || self.latency_calibration.is_running()
+            || self.relay.is_running()
+    }
     fn clear_status(&mut self) {
         self.status_msg = String::new();
     }
     fn set_status_ok(&mut self, msg: impl fmt::Display) {
-        self.status_msg = msg.to_string()
+        self.status_msg = msg.to_string();
+        self.toasts.log(Severity::Info, &self.status_msg, "");
     }
     fn set_status_err(&mut self, msg: impl fmt::Display) {
-        self.status_msg = format!("Error: {}", msg)
+        self.status_msg = format!("Error: {}", msg);
+        self.toasts
+            .push_with_severity(Severity::Error, "Error", msg.to_string());
     }
 
     pub(crate) fn grip(&self) -> Grip {
@@ -933,8 +2337,34 @@ impl App {
         }
         ret
     }
+    /// Replaces the gripped twist axes with the next or previous twist axis.
+    fn cycle_grip_axis(&mut self, offset: isize) {
+        let ty = self.puzzle.ty();
+        let axis_count = ty.twist_axes().len() as isize;
+        if axis_count == 0 {
+            return;
+        }
+        let index = match self.toggle_grip.axes.iter().exactly_one() {
+            Ok(axis) => (axis.0 as isize + offset).rem_euclid(axis_count),
+            Err(_) if offset < 0 => axis_count - 1,
+            Err(_) => 0,
+        };
+        let axis = TwistAxis(index as _);
+        self.toggle_grip.axes = HashSet::from_iter([axis]);
+        self.set_status_ok(format!("Gripped {} axis", ty.info(axis).name));
+    }
+    /// Replaces the gripped layers with the next or previous single layer.
+    fn cycle_grip_layer(&mut self, offset: i32) {
+        let layer_count = self.puzzle.layer_count() as i32;
+        let current = self.toggle_grip.layers.unwrap_or_default().0.trailing_zeros() as i32;
+        let layer = (current + offset).clamp(0, layer_count - 1);
+        self.toggle_grip.layers = Some(LayerMask(1 << layer));
+        self.set_status_ok(format!("Gripped layer {}", layer + 1));
+    }
+    /// Removes grips and keybind layers tied to held keys.
     fn remove_held_grips(&mut self, mut remove_if: impl FnMut(Key) -> bool) {
         self.transient_grips.retain(|&k, _v| !remove_if(k));
+        self.held_layers.retain(|&(k, _)| !remove_if(k));
     }
 
     #[cfg(target_arch = "wasm32")]
@@ -949,16 +2379,32 @@ pub(crate) enum AppEvent {
 
     Twist(Twist),
 
-    Click(egui::PointerButton),
+    /// Mouse button click, or one step of the scroll wheel, over the puzzle.
+    Click(MouseButton),
     /// Drag event with a per-frame delta, sent every frame until the drag ends
     /// (even if the delta is zero).
     Drag(egui::Vec2),
     DragReleased,
+    /// Pinch gesture, which multiplies the view scale by a factor.
+    Zoom(f32),
+    /// Tap on the puzzle using a touchscreen.
+    TouchTap,
+    /// Swipe that started on a sticker, using a touchscreen.
+    TouchSwipe {
+        twists: ClickTwists,
+        clockwise: bool,
+    },
 
     StatusError(String),
 
+    #[cfg(not(target_arch = "wasm32"))]
+    AccessKitActionRequest(egui_winit::accesskit_winit::ActionRequestEvent),
+
     #[cfg(target_arch = "wasm32")]
     WebWorkaround(crate::web_workarounds::WebEvent),
+    /// File picked by the user to open on web.
+    #[cfg(target_arch = "wasm32")]
+    WebFileOpened { name: String, contents: String },
 }
 impl From<Command> for AppEvent {
     fn from(c: Command) -> Self {
@@ -970,12 +2416,32 @@ impl From<Twist> for AppEvent {
         Self::Twist(t)
     }
 }
+#[cfg(not(target_arch = "wasm32"))]
+impl From<egui_winit::accesskit_winit::ActionRequestEvent> for AppEvent {
+    fn from(e: egui_winit::accesskit_winit::ActionRequestEvent) -> Self {
+        Self::AccessKitActionRequest(e)
+    }
+}
 
 #[derive(Debug, Default, Clone)]
 #[must_use]
 pub(crate) struct AppEventResponse {
     pub(crate) copy_string: Option<String>,
     pub(crate) request_paste: bool,
+    pub(crate) toggle_command_palette: bool,
+}
+
+/// Loads custom puzzle definitions from the config directory. Returns an error
+/// message for each file that could not be loaded.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn load_custom_puzzles() -> Vec<String> {
+    match crate::preferences::config_dir() {
+        Ok(mut dir) => {
+            dir.push(CUSTOM_PUZZLES_DIR_NAME);
+            generic::load_dir(&dir)
+        }
+        Err(_) => vec![],
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -991,3 +2457,18 @@ fn show_error_dialog(title: &str, e: impl fmt::Display) {
         .set_description(&e.to_string())
         .show();
 }
+
+/// Returns the current local time formatted for use in a file name.
+#[cfg(not(target_arch = "wasm32"))]
+fn file_name_timestamp() -> String {
+    let now = time::OffsetDateTime::now_local().unwrap_or_else(|_| time::OffsetDateTime::now_utc());
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        now.year(),
+        now.month() as u8,
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second(),
+    )
+}
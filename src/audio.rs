@@ -0,0 +1,100 @@
+//! Synthesized sound effects for twists, the timer, and solves.
+
+use instant::Duration;
+use rodio::source::{SineWave, Source};
+use rodio::{OutputStream, OutputStreamHandle};
+
+use crate::preferences::SoundPreferences;
+
+/// Duration of the fade at the start of each note, to avoid clicks.
+const NOTE_FADE_IN: Duration = Duration::from_millis(4);
+
+/// Sound effect.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Sound {
+    /// A twist animation completed.
+    Twist,
+    /// The timer started.
+    TimerStart,
+    /// The timer stopped.
+    TimerStop,
+    /// 8 or 12 seconds of inspection have passed.
+    InspectionWarning,
+    /// The solve set a new personal best.
+    PersonalBest,
+    /// Metronome beat.
+    MetronomeTick,
+}
+impl Sound {
+    /// Returns the notes of the sound as `(frequency in Hz, duration in ms)`
+    /// pairs, played one after another.
+    fn notes(self) -> &'static [(f32, u64)] {
+        match self {
+            Sound::Twist => &[(1800.0, 12)],
+            Sound::TimerStart => &[(880.0, 80)],
+            Sound::TimerStop => &[(880.0, 60), (1318.5, 120)],
+            Sound::InspectionWarning => &[(659.3, 150), (659.3, 150)],
+            Sound::PersonalBest => &[(523.3, 110), (659.3, 110), (784.0, 110), (1046.5, 330)],
+            Sound::MetronomeTick => &[(1567.98, 25)],
+        }
+    }
+
+    fn volume(self, prefs: &SoundPreferences) -> f32 {
+        let volume = match self {
+            Sound::Twist => prefs.twist_volume,
+            Sound::TimerStart | Sound::TimerStop => prefs.timer_volume,
+            Sound::InspectionWarning => prefs.inspection_volume,
+            Sound::PersonalBest => prefs.pb_volume,
+            Sound::MetronomeTick => prefs.metronome_volume,
+        };
+        volume * prefs.master_volume
+    }
+}
+
+/// Audio output, opened the first time a sound is played.
+#[derive(Default)]
+pub(crate) struct Audio {
+    /// The stream must be kept alive for the handle to work.
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    /// Whether opening the audio device has failed, in which case we don't
+    /// try again.
+    failed: bool,
+}
+impl Audio {
+    /// Plays a sound, if sounds are enabled. The metronome is started
+    /// explicitly, so it ticks even when other sounds are disabled.
+    pub(crate) fn play(&mut self, sound: Sound, prefs: &SoundPreferences) {
+        let volume = sound.volume(prefs);
+        let enabled = prefs.enabled || sound == Sound::MetronomeTick;
+        if !enabled || volume <= 0.0 {
+            return;
+        }
+        let Some((_, handle)) = self.output() else {
+            return;
+        };
+
+        let notes = sound.notes().iter().map(|&(frequency, duration_ms)| {
+            let mut note =
+                SineWave::new(frequency).take_duration(Duration::from_millis(duration_ms));
+            note.set_filter_fadeout();
+            note.fade_in(NOTE_FADE_IN)
+        });
+        let source = rodio::source::from_iter(notes).amplify(volume);
+        if let Err(e) = handle.play_raw(source) {
+            log::warn!("Error playing sound {:?}: {}", sound, e);
+        }
+    }
+
+    fn output(&mut self) -> Option<&(OutputStream, OutputStreamHandle)> {
+        if self.output.is_none() && !self.failed {
+            match OutputStream::try_default() {
+                Ok(output) => self.output = Some(output),
+                Err(e) => {
+                    log::warn!("Unable to open audio output: {}", e);
+                    self.failed = true;
+                }
+            }
+        }
+        self.output.as_ref()
+    }
+}
@@ -0,0 +1,177 @@
+//! Keyframe-based camera animation.
+
+use instant::Instant;
+use serde::{Deserialize, Serialize};
+
+use crate::preferences::ViewPreferences;
+
+/// Camera orientation at a point on the timeline.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(default)]
+pub struct CameraKeyframe {
+    /// Position on the timeline, in seconds.
+    pub time: f32,
+
+    /// Puzzle angle around X axis, in degrees.
+    pub pitch: f32,
+    /// Puzzle angle around Y axis, in degrees.
+    pub yaw: f32,
+    /// Puzzle angle around Z axis, in degrees.
+    pub roll: f32,
+    /// Global puzzle scale.
+    pub scale: f32,
+}
+impl CameraKeyframe {
+    /// Returns a keyframe at `time` using the camera orientation from `view`.
+    pub fn from_view(time: f32, view: &ViewPreferences) -> Self {
+        Self {
+            time,
+            pitch: view.pitch,
+            yaw: view.yaw,
+            roll: view.roll,
+            scale: view.scale,
+        }
+    }
+    /// Sets the camera orientation in `view` to this keyframe.
+    pub fn apply_to_view(&self, view: &mut ViewPreferences) {
+        view.pitch = self.pitch;
+        view.yaw = self.yaw;
+        view.roll = self.roll;
+        view.scale = self.scale;
+    }
+
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        // Turn the shorter way around, such as from 170 to -170 degrees
+        // through 180 degrees.
+        let yaw_delta = (other.yaw - self.yaw + 180.0).rem_euclid(360.0) - 180.0;
+        let yaw = (self.yaw + yaw_delta * t + 180.0).rem_euclid(360.0) - 180.0;
+        Self {
+            time: lerp(self.time, other.time),
+            pitch: lerp(self.pitch, other.pitch),
+            yaw,
+            roll: lerp(self.roll, other.roll),
+            scale: lerp(self.scale, other.scale),
+        }
+    }
+}
+
+/// Sequence of camera keyframes that can be played back.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct CameraPath {
+    /// Keyframes, sorted by time.
+    keyframes: Vec<CameraKeyframe>,
+    /// Time when playback started, or `None` if not playing.
+    #[serde(skip)]
+    playback_start: Option<Instant>,
+}
+impl CameraPath {
+    /// Returns the keyframes, sorted by time.
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+    /// Adds a keyframe, keeping keyframes sorted by time.
+    pub fn add(&mut self, keyframe: CameraKeyframe) {
+        let i = self.keyframes.partition_point(|k| k.time <= keyframe.time);
+        self.keyframes.insert(i, keyframe);
+    }
+    /// Removes a keyframe.
+    pub fn remove(&mut self, index: usize) {
+        if index < self.keyframes.len() {
+            self.keyframes.remove(index);
+        }
+    }
+    /// Replaces a keyframe, keeping keyframes sorted by time.
+    pub fn set(&mut self, index: usize, keyframe: CameraKeyframe) {
+        self.remove(index);
+        self.add(keyframe);
+    }
+
+    /// Returns the time of the last keyframe, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+    /// Returns the camera orientation at a point on the timeline, or `None` if
+    /// there are no keyframes.
+    pub fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+        let i = self.keyframes.partition_point(|k| k.time <= time);
+        let prev = i.checked_sub(1).and_then(|i| self.keyframes.get(i));
+        let next = self.keyframes.get(i);
+        match (prev, next) {
+            (Some(a), Some(b)) => {
+                let t = (time - a.time) / (b.time - a.time);
+                // Ease in and out of each keyframe.
+                let t = t * t * (3.0 - 2.0 * t);
+                Some(a.lerp(b, t))
+            }
+            (Some(k), None) | (None, Some(k)) => Some(*k),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns whether the camera path is playing.
+    pub fn is_playing(&self) -> bool {
+        self.playback_start.is_some()
+    }
+    /// Starts playback from the beginning.
+    pub fn play(&mut self) {
+        self.playback_start = Some(Instant::now());
+    }
+    /// Stops playback.
+    pub fn stop(&mut self) {
+        self.playback_start = None;
+    }
+    /// Returns the camera orientation for the current playback position, or
+    /// `None` if not playing. Playback stops after the last keyframe.
+    pub fn update_playback(&mut self) -> Option<CameraKeyframe> {
+        let time = self.playback_start?.elapsed().as_secs_f32();
+        if time > self.duration() {
+            self.stop();
+        }
+        self.sample(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_path_sample() {
+        let mut path = CameraPath::default();
+        assert_eq!(path.sample(0.0), None);
+
+        let key = |time, yaw| CameraKeyframe {
+            time,
+            yaw,
+            ..Default::default()
+        };
+        path.add(key(2.0, 90.0));
+        path.add(key(0.0, 0.0));
+        assert_eq!(path.keyframes()[0].time, 0.0);
+
+        assert_eq!(path.sample(-1.0).unwrap().yaw, 0.0);
+        assert_eq!(path.sample(1.0).unwrap().yaw, 45.0);
+        assert_eq!(path.sample(2.0).unwrap().yaw, 90.0);
+        assert_eq!(path.sample(5.0).unwrap().yaw, 90.0);
+    }
+
+    #[test]
+    fn test_camera_path_yaw_wraparound() {
+        let mut path = CameraPath::default();
+        let key = |time, yaw| CameraKeyframe {
+            time,
+            yaw,
+            ..Default::default()
+        };
+        path.add(key(0.0, 170.0));
+        path.add(key(2.0, -170.0));
+
+        // Turn through 180 degrees, not through 0.
+        assert_eq!(path.sample(1.0).unwrap().yaw, -180.0);
+        assert!(path.sample(0.5).unwrap().yaw > 170.0);
+        assert!(path.sample(1.5).unwrap().yaw < -170.0);
+        assert_eq!(path.sample(2.0).unwrap().yaw, -170.0);
+    }
+}
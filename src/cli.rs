@@ -0,0 +1,70 @@
+//! Command-line subcommands that run without opening a window.
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+use crate::preferences::Preferences;
+
+const RENDER_USAGE: &str =
+    "usage: hyperspeedcube render <LOG FILE> <PNG FILE> [--size <WIDTH>[x<HEIGHT>]]";
+
+/// Default width and height of a rendered diagram, in pixels.
+const DEFAULT_RENDER_SIZE: (u32, u32) = (800, 800);
+
+/// Runs a subcommand, if one is given. Returns `None` if the app should start
+/// normally.
+pub(crate) fn run(args: &[String]) -> Option<Result<()>> {
+    match args.first()?.as_str() {
+        "render" => Some(render(&args[1..])),
+        _ => None,
+    }
+}
+
+/// Renders the puzzle from a log file to a PNG image, using the user's
+/// preferences for colors and view angle.
+fn render(args: &[String]) -> Result<()> {
+    let mut paths = vec![];
+    let mut size = DEFAULT_RENDER_SIZE;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let s = args.next().context(RENDER_USAGE)?;
+                size = parse_size(s).with_context(|| format!("invalid size {s:?}"))?;
+            }
+            _ if arg.starts_with("--") => bail!("unknown option {arg:?}\n{RENDER_USAGE}"),
+            _ => paths.push(Path::new(arg)),
+        }
+    }
+    let [log_path, png_path] = paths[..] else {
+        bail!(RENDER_USAGE);
+    };
+
+    crate::puzzle::generic::load_builtin();
+    for e in crate::app::load_custom_puzzles() {
+        log::warn!("{e}");
+    }
+    let prefs = Preferences::load(None);
+
+    let (mut puzzle, warnings) = crate::logfile::load_file(log_path)
+        .with_context(|| format!("error loading {}", log_path.display()))?;
+    for warning in warnings {
+        log::warn!("{warning}");
+    }
+
+    let (width, height) = size;
+    crate::render::render_puzzle_image(&mut puzzle, &prefs, width, height)?
+        .save_png(png_path)
+        .with_context(|| format!("error saving {}", png_path.display()))
+}
+
+/// Parses a size such as `800` or `1200x800`.
+fn parse_size(s: &str) -> Option<(u32, u32)> {
+    match s.split_once('x') {
+        Some((w, h)) => Some((w.parse().ok()?, h.parse().ok()?)),
+        None => {
+            let n = s.parse().ok()?;
+            Some((n, n))
+        }
+    }
+}
@@ -7,18 +7,18 @@ use std::str::FromStr;
 
 use crate::puzzle::*;
 
-/// Minimum number of moves for a partial scramble.
+/// Minimum number of moves for a partial scramble. The maximum is set in the
+/// scramble preferences.
 pub const PARTIAL_SCRAMBLE_MOVE_COUNT_MIN: usize = 1;
-/// Maximum number of moves for a partial scramble.
-pub const PARTIAL_SCRAMBLE_MOVE_COUNT_MAX: usize = 20;
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Default, EnumIter, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum Command {
     // File menu (local)
     Open,
     Save,
     SaveAs,
+    Screenshot,
     Exit,
 
     // File menu (web)
@@ -30,15 +30,35 @@ pub enum Command {
     Undo,
     Redo,
     Reset,
+    AddCheckpoint,
+    RevertToCheckpoint,
+    ToggleExplore,
+    KeepExploration,
+    FlushTwistQueue,
+    ToggleTwistQueuePaused,
+    ClearTwistQueue,
 
     // Scramble menu
     ScrambleN(usize),
     ScrambleFull,
+    NextScramble,
+    RedoScramble,
+    DailyChallenge,
 
     // Puzzle menu
     NewPuzzle(PuzzleTypeEnum),
 
     ToggleBlindfold,
+    ToggleNetView,
+    ToggleExplodedView,
+    AutoFitView,
+    ToggleInverse,
+
+    MarkSplit,
+    ToggleTimer,
+
+    ToggleCommandPalette,
+    ToggleMiniMode,
 
     #[default]
     #[serde(other)]
@@ -50,6 +70,7 @@ impl Command {
             Command::Open => "🗁".to_owned(),
             Command::Save => "💾".to_owned(),
             Command::SaveAs => "Save As".to_owned(),
+            Command::Screenshot => "📷".to_owned(),
             Command::Exit => "Exit".to_owned(),
 
             Command::CopyHscLog => "🗐".to_owned(),
@@ -59,17 +80,94 @@ impl Command {
             Command::Undo => "⮪".to_owned(),
             Command::Redo => "⮫".to_owned(),
             Command::Reset => "⟲".to_owned(),
+            Command::AddCheckpoint => "🚩".to_owned(),
+            Command::RevertToCheckpoint => "⮪🚩".to_owned(),
+            Command::ToggleExplore => "🔭".to_owned(),
+            Command::KeepExploration => "🔭✔".to_owned(),
+            Command::FlushTwistQueue => "⏩".to_owned(),
+            Command::ToggleTwistQueuePaused => "⏸".to_owned(),
+            Command::ClearTwistQueue => "⏹".to_owned(),
 
             Command::ScrambleN(n) => format!("🔀 {n}"),
             Command::ScrambleFull => "🔀".to_owned(),
+            Command::NextScramble => "🔀⏭".to_owned(),
+            Command::RedoScramble => "🔀⟲".to_owned(),
+            Command::DailyChallenge => "📅".to_owned(),
 
             Command::NewPuzzle(ty) => format!("New {}", ty.name()),
 
             Command::ToggleBlindfold => "BLD".to_owned(),
+            Command::ToggleNetView => "Net".to_owned(),
+            Command::ToggleExplodedView => "Expl".to_owned(),
+            Command::AutoFitView => "Fit".to_owned(),
+            Command::ToggleInverse => "Inv".to_owned(),
+
+            Command::MarkSplit => "⏱".to_owned(),
+            Command::ToggleTimer => "⏯".to_owned(),
+
+            Command::ToggleCommandPalette => "🔍".to_owned(),
+            Command::ToggleMiniMode => "Mini".to_owned(),
 
             Command::None => String::new(),
         }
     }
+
+    /// Returns the label for the command in the command palette, or `None` if
+    /// it isn't listed there.
+    pub(crate) fn palette_label(&self) -> Option<String> {
+        let label = match self {
+            Command::Open => "File: Open...",
+            Command::Save => "File: Save",
+            Command::SaveAs => "File: Save as...",
+            Command::Screenshot => "File: Screenshot",
+            Command::Exit => "File: Exit",
+
+            Command::CopyHscLog => "File: Copy puzzle log (.hsc)",
+            Command::CopyMc4dLog => "File: Copy puzzle log (.log)",
+            Command::PasteLog => "File: Open from clipboard",
+
+            Command::Undo => "Edit: Undo twist",
+            Command::Redo => "Edit: Redo twist",
+            Command::Reset => "Edit: Reset puzzle",
+            Command::AddCheckpoint => "Edit: Add checkpoint",
+            Command::RevertToCheckpoint => "Edit: Revert to last checkpoint",
+            Command::ToggleExplore => "Edit: Toggle explore mode",
+            Command::KeepExploration => "Edit: Keep twists from exploration",
+            Command::FlushTwistQueue => "Edit: Finish queued twist animations",
+            Command::ToggleTwistQueuePaused => "Edit: Pause/resume twist animations",
+            Command::ClearTwistQueue => "Edit: Take back queued twists",
+
+            Command::ScrambleN(n) => return Some(format!("Scramble: {n}")),
+            Command::ScrambleFull => "Scramble: Full",
+            Command::NextScramble => "Scramble: Next",
+            Command::RedoScramble => "Scramble: Redo last",
+            Command::DailyChallenge => "Scramble: Daily challenge",
+
+            Command::NewPuzzle(ty) => return Some(format!("Puzzle: New {}", ty.name())),
+
+            Command::ToggleBlindfold => "Toggle blindfold",
+            Command::ToggleNetView => "View: Toggle net view",
+            Command::ToggleExplodedView => "View: Toggle exploded view",
+            Command::AutoFitView => "View: Fit puzzle to window",
+            Command::ToggleInverse => "View: Toggle inverse scramble",
+
+            Command::MarkSplit => "Timer: Mark split",
+            Command::ToggleTimer => "Timer: Start/stop",
+
+            Command::ToggleCommandPalette => return None,
+            Command::ToggleMiniMode => "View: Toggle mini mode",
+
+            Command::None => return None,
+        };
+        Some(label.to_owned())
+    }
+    /// Returns whether the command only works in the desktop app.
+    pub(crate) fn is_local_only(&self) -> bool {
+        matches!(
+            self,
+            Command::Open | Command::Save | Command::SaveAs | Command::Screenshot | Command::Exit,
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq, Hash)]
@@ -80,6 +178,15 @@ pub enum PuzzleMouseCommand {
     Recenter,
     SelectPiece,
 
+    Undo,
+    Redo,
+    NextGripAxis,
+    PreviousGripAxis,
+    NextGripLayer,
+    PreviousGripLayer,
+    HoverBehind,
+    HoverInFront,
+
     #[default]
     #[serde(other)]
     None,
@@ -113,6 +220,12 @@ pub enum PuzzleCommand {
         #[serde(default)]
         filter_name: String,
     },
+    GripGroup {
+        #[serde(default)]
+        mode: GripGroupMode,
+        #[serde(default)]
+        grip_group_name: String,
+    },
 
     KeybindSet {
         #[serde(default)]
@@ -122,6 +235,10 @@ pub enum PuzzleCommand {
         #[serde(default)]
         view_preset_name: String,
     },
+    Layer {
+        #[serde(default)]
+        layer_name: String,
+    },
 
     #[default]
     #[serde(other)]
@@ -179,9 +296,21 @@ impl PuzzleCommand {
                     FilterMode::Toggle => "~".to_string(),
                 },
             },
+            PuzzleCommand::GripGroup {
+                mode,
+                grip_group_name,
+            } => match grip_group_name.as_str() {
+                "Inverse" => "⇄".to_string(),
+                _ => match mode {
+                    GripGroupMode::Select => format!("{grip_group_name}"),
+                    GripGroupMode::Add => format!("+{grip_group_name}"),
+                    GripGroupMode::Intersect => format!("&{grip_group_name}"),
+                },
+            },
 
             PuzzleCommand::KeybindSet { keybind_set_name } => format!("{keybind_set_name}"),
             PuzzleCommand::ViewPreset { view_preset_name } => format!("{view_preset_name}"),
+            PuzzleCommand::Layer { layer_name } => format!("⮉{layer_name}"),
 
             PuzzleCommand::None => String::new(),
         }
@@ -219,6 +348,20 @@ impl PuzzleCommand {
             _ => None,
         }
     }
+    pub fn grip_group_mode_mut(&mut self) -> Option<&mut GripGroupMode> {
+        match self {
+            Self::GripGroup { mode, .. } => Some(mode),
+            _ => None,
+        }
+    }
+    pub fn grip_group_name_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Self::GripGroup {
+                grip_group_name, ..
+            } => Some(grip_group_name),
+            _ => None,
+        }
+    }
     pub fn keybind_set_name_mut(&mut self) -> Option<&mut String> {
         match self {
             Self::KeybindSet {
@@ -235,6 +378,12 @@ impl PuzzleCommand {
             _ => None,
         }
     }
+    pub fn layer_name_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Self::Layer { layer_name } => Some(layer_name),
+            _ => None,
+        }
+    }
 }
 
 /// Mode in which to apply a piece filter.
@@ -275,6 +424,33 @@ pub enum FilterMode {
     Toggle,
 }
 
+/// Mode in which to apply a saved grip group.
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Display,
+    AsRefStr,
+    IntoStaticStr,
+    EnumIter,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum GripGroupMode {
+    #[default]
+    #[strum(serialize = "Select")]
+    Select,
+    #[strum(serialize = "Add to selection")]
+    Add,
+    #[strum(serialize = "Intersect with selection")]
+    Intersect,
+}
+
 /// Description of a layer mask that adjusts to the size of a puzzle.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct LayerMaskDesc {
@@ -0,0 +1,150 @@
+//! Diagnostic reports written when the app panics, so that bug reports
+//! contain the state that led to the crash and the puzzle can be recovered
+//! on the next start.
+
+use instant::{Duration, Instant};
+use itertools::Itertools;
+use std::fmt::Write;
+use std::panic::PanicInfo;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::app::App;
+use crate::logfile::LogFileFormat;
+
+/// Name of the directory inside the config directory that holds crash
+/// reports.
+const CRASH_REPORTS_DIR_NAME: &str = "crash-reports";
+/// Name of the file that records the latest crash report until the user has
+/// been told about it.
+const PENDING_FILE_NAME: &str = "pending";
+/// Name of the puzzle log file inside a crash report.
+pub const PUZZLE_FILE_NAME: &str = "puzzle.hsc";
+/// How often to take a new snapshot of the app state. Serializing the puzzle
+/// is too slow to do every frame for long solves.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    static ref SNAPSHOT: Mutex<Snapshot> = Mutex::new(Snapshot::default());
+}
+
+/// Copy of the app state, kept up to date so that it can be written from the
+/// panic hook.
+#[derive(Debug, Default)]
+struct Snapshot {
+    last_update: Option<Instant>,
+    puzzle_type: String,
+    scramble: String,
+    undo_buffer: String,
+    puzzle_log: Option<String>,
+    prefs: Option<String>,
+}
+
+/// Takes a new snapshot of the app state, if the last one is old enough.
+pub fn update_snapshot(app: &App) {
+    let mut snapshot = SNAPSHOT.lock().unwrap();
+    if snapshot
+        .last_update
+        .map_or(false, |t| t.elapsed() < SNAPSHOT_INTERVAL)
+    {
+        return;
+    }
+
+    let puzzle = &app.puzzle;
+    let notation = puzzle.notation_scheme();
+    *snapshot = Snapshot {
+        last_update: Some(Instant::now()),
+        puzzle_type: puzzle.ty().name().to_owned(),
+        scramble: puzzle
+            .scramble()
+            .iter()
+            .map(|&twist| notation.twist_to_string(twist))
+            .join(" "),
+        undo_buffer: puzzle
+            .undo_buffer()
+            .iter()
+            .map(|&entry| entry.to_string(notation))
+            .join(" "),
+        puzzle_log: crate::logfile::serialize(puzzle, LogFileFormat::Hsc).ok(),
+        // Leave out sync credentials.
+        prefs: app.prefs.to_synced_string().ok(),
+    };
+}
+
+/// Writes a crash report for a panic and returns the directory containing
+/// it. This is called from the panic hook, so it must not panic itself.
+pub fn write_report(info: &PanicInfo<'_>) -> Option<PathBuf> {
+    let mut reports_dir = crate::preferences::config_dir().ok()?;
+    reports_dir.push(CRASH_REPORTS_DIR_NAME);
+    let dir = reports_dir.join(format!("crash-{}", crate::util::unix_timestamp()));
+    std::fs::create_dir_all(&dir).ok()?;
+
+    let mut report = String::new();
+    let _ = writeln!(report, "{} v{}", crate::TITLE, env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "OS: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    );
+    let _ = writeln!(report, "Panic: {info}");
+    let _ = writeln!(report);
+
+    // The panic may have happened while taking a snapshot, in which case the
+    // lock is still held.
+    let snapshot = SNAPSHOT.try_lock().ok();
+    if let Some(snapshot) = &snapshot {
+        let _ = writeln!(report, "Puzzle: {}", snapshot.puzzle_type);
+        let _ = writeln!(report, "Scramble: {}", snapshot.scramble);
+        let _ = writeln!(report, "Undo buffer: {}", snapshot.undo_buffer);
+    } else {
+        let _ = writeln!(report, "App state unavailable");
+    }
+    let _ = writeln!(report);
+
+    let _ = writeln!(report, "Recent log records:");
+    for record in crate::log_buffer::try_records().unwrap_or_default() {
+        let _ = writeln!(
+            report,
+            "[{:>9.3}] {:<5} {}: {}",
+            record.time.as_secs_f32(),
+            record.level,
+            record.target,
+            record.message,
+        );
+    }
+    let _ = writeln!(report);
+
+    let _ = writeln!(report, "Backtrace:");
+    let _ = writeln!(report, "{}", std::backtrace::Backtrace::force_capture());
+
+    std::fs::write(dir.join("report.txt"), report).ok()?;
+    if let Some(snapshot) = &snapshot {
+        if let Some(puzzle_log) = &snapshot.puzzle_log {
+            let _ = std::fs::write(dir.join(PUZZLE_FILE_NAME), puzzle_log);
+        }
+        if let Some(prefs) = &snapshot.prefs {
+            let _ = std::fs::write(dir.join("preferences.yaml"), prefs);
+        }
+    }
+
+    let _ = std::fs::write(
+        reports_dir.join(PENDING_FILE_NAME),
+        dir.to_string_lossy().as_bytes(),
+    );
+
+    Some(dir)
+}
+
+/// Returns the directory of a crash report from a previous run that the user
+/// hasn't been told about yet, and marks it as seen.
+pub fn take_pending_report() -> Option<PathBuf> {
+    let mut pending_file = crate::preferences::config_dir().ok()?;
+    pending_file.push(CRASH_REPORTS_DIR_NAME);
+    pending_file.push(PENDING_FILE_NAME);
+    let dir = std::fs::read_to_string(&pending_file).ok()?;
+    if let Err(e) = std::fs::remove_file(&pending_file) {
+        log::warn!("Unable to remove {}: {e}", pending_file.display());
+    }
+    Some(PathBuf::from(dir.trim()))
+}
@@ -0,0 +1,249 @@
+//! Panels detached from the main window into their own OS windows, for
+//! multi-monitor setups.
+
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
+use winit::event::WindowEvent;
+use winit::event_loop::EventLoopWindowTarget;
+use winit::window::WindowId;
+
+use crate::app::{App, AppEvent};
+use crate::gui::windows::Window;
+use crate::preferences::DetachedWindowLayout;
+use crate::render::GraphicsState;
+
+/// Size of a newly detached window.
+const DEFAULT_SIZE: LogicalSize<f64> = LogicalSize {
+    width: 400.0,
+    height: 500.0,
+};
+
+/// OS windows for panels that have been detached from the main window.
+#[derive(Default)]
+pub(crate) struct DetachedWindows {
+    windows: Vec<DetachedWindow>,
+}
+impl DetachedWindows {
+    /// Opens and closes OS windows to match the layout preferences.
+    pub(crate) fn update(
+        &mut self,
+        ev_loop: &EventLoopWindowTarget<AppEvent>,
+        gfx: &GraphicsState,
+        app: &mut App,
+    ) {
+        let layout = &app.prefs.layout;
+        self.windows.retain(|w| layout.is_detached(w.panel.name));
+
+        let mut failed = vec![];
+        for window_layout in &app.prefs.layout.detached {
+            if self
+                .windows
+                .iter()
+                .any(|w| w.panel.name == window_layout.name)
+            {
+                continue;
+            }
+            // The panel may be from a newer version of the app.
+            let Some(panel) = Window::by_name(&window_layout.name) else {
+                continue;
+            };
+            match DetachedWindow::new(ev_loop, gfx, panel, window_layout) {
+                Ok(w) => self.windows.push(w),
+                Err(e) => {
+                    log::error!("Unable to open window for {:?}: {e}", panel.name);
+                    failed.push(panel);
+                }
+            }
+        }
+        for panel in failed {
+            app.prefs.layout.reattach(panel.name);
+            app.prefs.needs_save = true;
+        }
+    }
+
+    /// Handles an event for a window other than the main one.
+    pub(crate) fn handle_window_event(
+        &mut self,
+        window_id: WindowId,
+        event: &WindowEvent<'_>,
+        gfx: &GraphicsState,
+        main_ctx: &egui::Context,
+        app: &mut App,
+    ) {
+        let Some(w) = self.windows.iter_mut().find(|w| w.window.id() == window_id) else {
+            return;
+        };
+        let _ = w.egui_winit_state.on_event(&w.egui_ctx, event);
+
+        let layout = app
+            .prefs
+            .layout
+            .detached
+            .iter_mut()
+            .find(|l| l.name == w.panel.name);
+        match event {
+            WindowEvent::CloseRequested => {
+                // Closing the window puts the panel back in the main window.
+                app.prefs.layout.reattach(w.panel.name);
+                app.prefs.needs_save = true;
+                w.panel.set_open(main_ctx, true);
+            }
+            WindowEvent::Resized(new_size) => {
+                w.resize(gfx, *new_size);
+                if let Some(layout) = layout {
+                    layout.size = Some([new_size.width, new_size.height]);
+                    app.prefs.needs_save = true;
+                }
+            }
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                w.resize(gfx, **new_inner_size);
+            }
+            WindowEvent::Moved(new_position) => {
+                if let Some(layout) = layout {
+                    layout.position = Some([new_position.x, new_position.y]);
+                    app.prefs.needs_save = true;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Redraws all detached windows.
+    pub(crate) fn draw(&mut self, gfx: &GraphicsState, main_ctx: &egui::Context, app: &mut App) {
+        for w in &mut self.windows {
+            w.draw(gfx, main_ctx, app);
+        }
+    }
+}
+
+struct DetachedWindow {
+    panel: Window,
+    window: winit::window::Window,
+    surface: wgpu::Surface,
+    config: wgpu::SurfaceConfiguration,
+
+    egui_ctx: egui::Context,
+    egui_winit_state: egui_winit::State,
+    egui_renderer: egui_wgpu::Renderer,
+}
+impl DetachedWindow {
+    fn new(
+        ev_loop: &EventLoopWindowTarget<AppEvent>,
+        gfx: &GraphicsState,
+        panel: Window,
+        layout: &DetachedWindowLayout,
+    ) -> Result<Self, winit::error::OsError> {
+        let mut builder = winit::window::WindowBuilder::new()
+            .with_title(format!("{} - {}", panel.title(), crate::TITLE))
+            .with_window_icon(crate::icon::load_application_icon());
+        builder = match layout.size {
+            Some([w, h]) => builder.with_inner_size(PhysicalSize::new(w, h)),
+            None => builder.with_inner_size(DEFAULT_SIZE),
+        };
+        if let Some([x, y]) = layout.position {
+            builder = builder.with_position(PhysicalPosition::new(x, y));
+        }
+        let window = builder.build(ev_loop)?;
+
+        let (surface, config) = gfx.create_window_surface(&window);
+        let egui_renderer = egui_wgpu::Renderer::new(&gfx.device, config.format, None, 1);
+
+        Ok(Self {
+            panel,
+            window,
+            surface,
+            config,
+
+            egui_ctx: egui::Context::default(),
+            egui_winit_state: egui_winit::State::new(ev_loop),
+            egui_renderer,
+        })
+    }
+
+    fn resize(&mut self, gfx: &GraphicsState, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&gfx.device, &self.config);
+        }
+    }
+
+    fn draw(&mut self, gfx: &GraphicsState, main_ctx: &egui::Context, app: &mut App) {
+        // Match the theme of the main window.
+        self.egui_ctx.set_style(main_ctx.style());
+        let scale_factor = self.window.scale_factor() as f32;
+        let pixels_per_point = app.prefs.gfx.pixels_per_point(scale_factor);
+        self.egui_winit_state.set_pixels_per_point(pixels_per_point);
+
+        let egui_input = self.egui_winit_state.take_egui_input(&self.window);
+        let panel = self.panel;
+        let egui_output = self
+            .egui_ctx
+            .run(egui_input, |ctx| panel.show_detached(ctx, app));
+        self.egui_winit_state.handle_platform_output(
+            &self.window,
+            &self.egui_ctx,
+            egui_output.platform_output,
+        );
+
+        let output_frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&gfx.device, &self.config);
+                return;
+            }
+            Err(e) => {
+                log::warn!("Dropped frame with error: {:?}", e);
+                return;
+            }
+        };
+
+        let paint_jobs = self.egui_ctx.tessellate(egui_output.shapes);
+        let screen_descriptor = egui_wgpu::renderer::ScreenDescriptor {
+            size_in_pixels: [self.config.width, self.config.height],
+            pixels_per_point,
+        };
+        let mut encoder = gfx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("detached_window_command_encoder"),
+            });
+        for (id, image_delta) in &egui_output.textures_delta.set {
+            self.egui_renderer
+                .update_texture(&gfx.device, &gfx.queue, *id, image_delta);
+        }
+        self.egui_renderer.update_buffers(
+            &gfx.device,
+            &gfx.queue,
+            &mut encoder,
+            &paint_jobs,
+            &screen_descriptor,
+        );
+
+        {
+            let texture_view = output_frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &texture_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            self.egui_renderer
+                .render(&mut render_pass, &paint_jobs, &screen_descriptor);
+        }
+
+        for id in &egui_output.textures_delta.free {
+            self.egui_renderer.free_texture(id);
+        }
+
+        gfx.queue.submit(std::iter::once(encoder.finish()));
+        output_frame.present();
+    }
+}
@@ -7,8 +7,7 @@ use strum::IntoEnumIterator;
 
 use crate::app::App;
 use crate::commands::{
-    Command, FilterMode, PuzzleCommand, PARTIAL_SCRAMBLE_MOVE_COUNT_MAX,
-    PARTIAL_SCRAMBLE_MOVE_COUNT_MIN,
+    Command, FilterMode, GripGroupMode, PuzzleCommand, PARTIAL_SCRAMBLE_MOVE_COUNT_MIN,
 };
 use crate::gui::components::{
     big_icon_button, puzzle_type_menu, FancyComboBox, LayerMaskEdit, PlaintextYamlEditor,
@@ -20,6 +19,8 @@ use crate::preferences::{Keybind, KeybindSet, Preferences};
 use crate::puzzle::*;
 
 const KEY_BUTTON_SIZE: egui::Vec2 = egui::vec2(200.0, 22.0);
+/// Name shown for the base keybind layer.
+const BASE_LAYER_NAME: &str = "Base";
 
 pub struct KeybindSetsList<'a> {
     pub app: &'a mut App,
@@ -147,6 +148,23 @@ where
     fn ui(self, ui: &mut egui::Ui) -> egui::Response {
         let mut changed = false;
 
+        // Collect the layers activated by any puzzle keybind, along with any
+        // layers that keybinds in this set already belong to.
+        let prefs = &self.app.prefs;
+        let layer_names: BTreeSet<String> = prefs.puzzle_keybinds[self.app.puzzle.ty()]
+            .sets
+            .iter()
+            .flat_map(|set| &set.value.keybinds)
+            .filter_map(|bind| match &bind.command {
+                PuzzleCommand::Layer { layer_name } => Some(layer_name),
+                _ => None,
+            })
+            .chain(self.keybind_set.get(prefs).iter().map(|bind| &bind.layer))
+            .filter(|layer_name| !layer_name.is_empty())
+            .cloned()
+            .collect();
+        let show_layers = !layer_names.is_empty();
+
         let mut keybinds = std::mem::take(self.keybind_set.get_mut(&mut self.app.prefs));
 
         let yaml_editor = PlaintextYamlEditor {
@@ -171,6 +189,15 @@ where
                         |ui| ui.strong("Keybind"),
                     );
 
+                    if show_layers {
+                        ui.strong("Layer").on_hover_explanation(
+                            "",
+                            "Keybinds in a layer are only active while holding \
+                             down a key bound to that layer, and take priority \
+                             over keybinds in the base layer.",
+                        );
+                    }
+
                     ui.strong("Command");
                 });
 
@@ -179,17 +206,35 @@ where
                 egui::ScrollArea::new([false, true]).show(ui, |ui| {
                     let id = unique_id!(&self.keybind_set);
                     let r = ReorderableList::new(id, &mut keybinds).show(ui, |ui, idx, keybind| {
-                        let mut r = ui
-                            .add_sized(KEY_BUTTON_SIZE, egui::Button::new(keybind.key.to_string()));
+                        let mut r =
+                            ui.add_sized(KEY_BUTTON_SIZE, egui::Button::new(keybind.key_string()));
                         if r.clicked() {
                             key_combo_popup::open(
                                 ui.ctx(),
                                 Some(keybind.key),
+                                keybind.chord.clone(),
                                 self.keybind_set.clone(),
                                 idx,
                             )
                         }
 
+                        if show_layers {
+                            let mut options = vec![(String::new(), BASE_LAYER_NAME.into())];
+                            options.extend(
+                                layer_names
+                                    .iter()
+                                    .map(|name| (name.clone(), name.as_str().into())),
+                            );
+                            r |= ui.add(FancyComboBox {
+                                combo_box: egui::ComboBox::from_id_source(unique_id!(
+                                    &self.keybind_set,
+                                    idx
+                                )),
+                                selected: &mut keybind.layer,
+                                options,
+                            });
+                        }
+
                         r |= ui.add(CommandSelectWidget {
                             cmd: &mut keybind.command,
 
@@ -243,6 +288,7 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
                     "Open..." => Cmd::Open,
                     "Save" => Cmd::Save,
                     "Save as..." => Cmd::SaveAs,
+                    "Screenshot" => Cmd::Screenshot,
                     "Exit" => Cmd::Exit,
 
                     "Copy .hsc" => Cmd::CopyHscLog,
@@ -252,10 +298,28 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
                     "Undo" => Cmd::Undo,
                     "Redo" => Cmd::Redo,
                     "Reset" => Cmd::Reset,
+                    "Add checkpoint" => Cmd::AddCheckpoint,
+                    "Revert to checkpoint" => Cmd::RevertToCheckpoint,
+                    "Toggle explore mode" => Cmd::ToggleExplore,
+                    "Keep exploration" => Cmd::KeepExploration,
+                    "Flush twist queue" => Cmd::FlushTwistQueue,
+                    "Pause twist queue" => Cmd::ToggleTwistQueuePaused,
+                    "Clear twist queue" => Cmd::ClearTwistQueue,
 
                     "Scramble partially" => Cmd::ScrambleN(PARTIAL_SCRAMBLE_MOVE_COUNT_MIN),
                     "Scramble fully" => Cmd::ScrambleFull,
+                    "Next scramble" => Cmd::NextScramble,
+                    "Redo scramble" => Cmd::RedoScramble,
+                    "Daily challenge" => Cmd::DailyChallenge,
                     "Toggle blindfold" => Cmd::ToggleBlindfold,
+                    "Toggle net view" => Cmd::ToggleNetView,
+                    "Toggle exploded view" => Cmd::ToggleExplodedView,
+                    "Fit puzzle to view" => Cmd::AutoFitView,
+                    "Toggle inverse" => Cmd::ToggleInverse,
+                    "Mark split" => Cmd::MarkSplit,
+                    "Start/stop timer" => Cmd::ToggleTimer,
+                    "Command palette" => Cmd::ToggleCommandPalette,
+                    "Toggle mini mode" => Cmd::ToggleMiniMode,
                     "New puzzle" => Cmd::NewPuzzle(PuzzleTypeEnum::default()),
                 }
             );
@@ -264,7 +328,7 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
             match self.cmd {
                 Cmd::ScrambleN(n) => {
                     let r = ui.add(egui::DragValue::new(n).clamp_range(
-                        PARTIAL_SCRAMBLE_MOVE_COUNT_MIN..=PARTIAL_SCRAMBLE_MOVE_COUNT_MAX,
+                        PARTIAL_SCRAMBLE_MOVE_COUNT_MIN..=self.prefs.scramble.partial_max,
                     ));
                     changed |= r.changed();
                 }
@@ -323,6 +387,14 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
                         mode: self.cmd.filter_mode_mut().cloned().unwrap_or_default(),
                         filter_name: self.cmd.filter_name_mut().cloned().unwrap_or_default(),
                     },
+                    "Grip group" => Cmd::GripGroup {
+                        mode: self.cmd.grip_group_mode_mut().cloned().unwrap_or_default(),
+                        grip_group_name: self
+                            .cmd
+                            .grip_group_name_mut()
+                            .cloned()
+                            .unwrap_or_default(),
+                    },
 
                     "Keybind set" => Cmd::KeybindSet {
                         keybind_set_name: self
@@ -338,6 +410,9 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
                             .cloned()
                             .unwrap_or_default(),
                     },
+                    "Layer" => Cmd::Layer {
+                        layer_name: self.cmd.layer_name_mut().cloned().unwrap_or_default(),
+                    },
                 }
             );
             changed |= r.changed();
@@ -358,11 +433,18 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
                 changed |= r.changed();
             }
             if let Some(direction) = self.cmd.direction_mut() {
-                let r = ui.add(FancyComboBox::new(
-                    unique_id!(self.idx),
-                    direction,
-                    puzzle_type.twist_directions(),
-                ));
+                let r = ui.add(FancyComboBox {
+                    combo_box: egui::ComboBox::from_id_source(unique_id!(self.idx)),
+                    selected: direction,
+                    options: puzzle_type
+                        .twist_directions()
+                        .iter()
+                        .map(|dir| {
+                            let text = crate::i18n::twist_direction_name(dir.name);
+                            (dir.name.to_owned(), Cow::Owned(text))
+                        })
+                        .collect(),
+                });
                 changed |= r.changed();
             }
             if let Some(filter_mode) = self.cmd.filter_mode_mut() {
@@ -398,6 +480,33 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
                     );
                 changed |= r.changed();
             }
+            if let Some(grip_group_mode) = self.cmd.grip_group_mode_mut() {
+                let r = ui.add(FancyComboBox {
+                    combo_box: egui::ComboBox::from_id_source(unique_id!(self.idx)),
+                    selected: grip_group_mode,
+                    options: GripGroupMode::iter()
+                        .map(|mode| (mode, Cow::Borrowed(mode.into())))
+                        .collect(),
+                });
+                changed |= r.changed();
+            }
+            if let Some(grip_group_name) = self.cmd.grip_group_name_mut() {
+                let preset_names = self.prefs.grip_groups[puzzle_type]
+                    .iter()
+                    .map(|preset| &preset.preset_name);
+                let r = ui
+                    .add(FancyComboBox::new(
+                        unique_id!(self.idx),
+                        grip_group_name,
+                        ["Inverse".to_string()].iter().chain(preset_names),
+                    ))
+                    .on_hover_explanation(
+                        "",
+                        "You can manage grip groups \
+                         in the \"Puzzle controls\" tool.",
+                    );
+                changed |= r.changed();
+            }
             if let Some(keybind_set_name) = self.cmd.keybind_set_name_mut() {
                 let r = ui
                     .add(FancyComboBox::new(
@@ -433,6 +542,16 @@ impl egui::Widget for CommandSelectWidget<'_, PuzzleKeybindsAccessor> {
                     );
                 changed |= r.changed();
             }
+            if let Some(layer_name) = self.cmd.layer_name_mut() {
+                let r = ui
+                    .add(egui::TextEdit::singleline(layer_name).hint_text("Layer name"))
+                    .on_hover_explanation(
+                        "",
+                        "While this key is held, keybinds in this layer \
+                         take priority over keybinds in the base layer.",
+                    );
+                changed |= r.changed();
+            }
         });
 
         if changed {
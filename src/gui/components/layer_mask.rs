@@ -1,8 +1,16 @@
 use crate::commands::LayerMaskDesc;
 use crate::gui::ext::*;
+use crate::puzzle::LayerMask;
 
 const LAYER_DESCRIPTION_WIDTH: f32 = 50.0;
 
+/// Maximum width of each segment in a [`LayerMaskSegments`] widget.
+const LAYER_SEGMENT_MAX_WIDTH: f32 = 28.0;
+/// Height of a [`LayerMaskSegments`] widget.
+const LAYER_SEGMENT_HEIGHT: f32 = 22.0;
+/// Segments narrower than this don't show their layer number.
+const LAYER_SEGMENT_MIN_LABEL_WIDTH: f32 = 14.0;
+
 pub struct LayerMaskEdit<'a> {
     pub id: egui::Id,
     pub layers: &'a mut LayerMaskDesc,
@@ -62,3 +70,84 @@ impl<'a> egui::Widget for LayerMaskEdit<'a> {
         r
     }
 }
+
+/// Row of segments, one for each layer of a puzzle from the outermost layer
+/// on one side to the other. Pressing a segment toggles its layer, and
+/// dragging across segments sets them all to match the first one.
+pub struct LayerMaskSegments<'a> {
+    pub id: egui::Id,
+    pub layers: &'a mut LayerMask,
+    pub layer_count: u8,
+}
+impl egui::Widget for LayerMaskSegments<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let count = self.layer_count.max(1);
+        let segment_width = (ui.available_width() / count as f32).min(LAYER_SEGMENT_MAX_WIDTH);
+        let size = egui::vec2(segment_width * count as f32, LAYER_SEGMENT_HEIGHT);
+        let (rect, mut r) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+
+        let segment_rect = |layer: u8| {
+            egui::Rect::from_min_size(
+                rect.min + egui::vec2(segment_width * layer as f32, 0.0),
+                egui::vec2(segment_width, rect.height()),
+            )
+        };
+        let segment_at = |pos: egui::Pos2| {
+            let i = ((pos.x - rect.left()) / segment_width).floor() as i32;
+            i.clamp(0, count as i32 - 1) as u8
+        };
+
+        let old_layers = *self.layers;
+        let drag_value_id = self.id.with("drag_value");
+        if r.is_pointer_button_down_on() {
+            if let Some(pos) = r.interact_pointer_pos() {
+                let layer = segment_at(pos);
+                let value = *ui
+                    .data()
+                    .get_temp_mut_or_insert_with(drag_value_id, || !self.layers[layer]);
+                if value {
+                    *self.layers |= LayerMask(1 << layer);
+                } else {
+                    *self.layers &= !LayerMask(1 << layer);
+                }
+            }
+        } else {
+            ui.data().remove::<bool>(drag_value_id);
+        }
+        if *self.layers != old_layers {
+            r.mark_changed();
+        }
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.visuals();
+            let hovered_layer = r.hover_pos().map(segment_at);
+            for layer in 0..count {
+                let is_selected = self.layers[layer];
+                let (fill, text_color) = if is_selected {
+                    (visuals.selection.bg_fill, visuals.selection.stroke.color)
+                } else {
+                    (visuals.widgets.inactive.bg_fill, visuals.text_color())
+                };
+                let stroke = if hovered_layer == Some(layer) {
+                    visuals.widgets.hovered.bg_stroke
+                } else {
+                    visuals.widgets.noninteractive.bg_stroke
+                };
+                let segment = segment_rect(layer).shrink(1.0);
+                ui.painter()
+                    .rect(segment, visuals.widgets.inactive.rounding, fill, stroke);
+                if segment_width >= LAYER_SEGMENT_MIN_LABEL_WIDTH {
+                    ui.painter().text(
+                        segment.center(),
+                        egui::Align2::CENTER_CENTER,
+                        layer + 1,
+                        egui::TextStyle::Small.resolve(ui.style()),
+                        text_color,
+                    );
+                }
+            }
+        }
+
+        r.on_hover_text(self.layers.long_description())
+    }
+}
@@ -7,16 +7,18 @@ mod presets;
 mod puzzle_list;
 mod reorder;
 mod reset;
+mod solve_notes;
 mod yaml_editor;
 
 pub use combo_boxes::*;
 pub use keybinds::*;
 pub use layer_mask::*;
-pub use prefs::PrefsUi;
+pub use prefs::{PrefsUi, MAX_SCALE, MIN_SCALE};
 pub use presets::*;
 pub use puzzle_list::*;
 pub use reorder::*;
 pub use reset::*;
+pub use solve_notes::*;
 pub use yaml_editor::*;
 
 pub const BIG_ICON_BUTTON_SIZE: egui::Vec2 = egui::vec2(22.0, 22.0);
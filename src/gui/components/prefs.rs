@@ -1,13 +1,27 @@
 use egui::NumExt;
+use strum::IntoEnumIterator;
 
 use crate::app::App;
+use crate::commands::{Command, PARTIAL_SCRAMBLE_MOVE_COUNT_MIN};
 use crate::gui::components::{with_reset_button, PresetsUi, WidgetWithReset};
 use crate::gui::ext::*;
 use crate::gui::util::Access;
-use crate::preferences::{OpacityPreferences, DEFAULT_PREFS};
-use crate::puzzle::{traits::*, Face, ProjectionType};
+use crate::i18n::{tr_attr, Language};
+use crate::preferences::{
+    os_is_dark, BackgroundStyle, DynamicTwistCurve, OpacityPreferences, StereoMode, Theme,
+    DEFAULT_PREFS,
+};
+use crate::puzzle::{traits::*, Face, ProjectionType, SolvedStateDefinition, MAX_TRAIL_LENGTH};
 use crate::serde_impl::hex_color;
 
+/// Width of each text field for editing a custom face label.
+const FACE_LABEL_EDIT_WIDTH: f32 = 60.0;
+
+/// Minimum puzzle scale.
+pub const MIN_SCALE: f32 = 0.1;
+/// Maximum puzzle scale.
+pub const MAX_SCALE: f32 = 5.0;
+
 pub struct PrefsUi<'a, T> {
     pub ui: &'a mut egui::Ui,
     pub current: &'a mut T,
@@ -126,6 +140,24 @@ impl<T> PrefsUi<'_, T> {
     }
 }
 
+pub fn build_language_picker(ui: &mut egui::Ui, app: &mut App) {
+    let language = &mut app.prefs.language;
+    let r = egui::ComboBox::new(unique_id!(), tr!("prefs-language"))
+        .selected_text(language.native_name())
+        .show_ui(ui, |ui| {
+            let mut changed = false;
+            for option in Language::iter() {
+                changed |= ui
+                    .selectable_value(language, option, option.native_name())
+                    .changed();
+            }
+            changed
+        });
+    if r.inner == Some(true) {
+        app.prefs.needs_save = true;
+    }
+}
+
 pub fn build_colors_section(ui: &mut egui::Ui, app: &mut App) {
     let puzzle_type = app.puzzle.ty();
     let prefs = &mut app.prefs;
@@ -138,23 +170,87 @@ pub fn build_colors_section(ui: &mut egui::Ui, app: &mut App) {
         changed: &mut changed,
     };
 
-    prefs_ui.ui.strong("Faces");
+    prefs_ui.ui.strong(tr!("prefs-theme"));
+    let os_is_dark = os_is_dark();
+    let old_theme = prefs_ui.current.theme;
+    prefs_ui.ui.horizontal(|ui| {
+        for (theme, name) in [
+            (Theme::FollowOs, tr!("prefs-theme-follow-os")),
+            (Theme::Dark, tr!("prefs-theme-dark")),
+            (Theme::Light, tr!("prefs-theme-light")),
+        ] {
+            *prefs_ui.changed |= ui
+                .selectable_value(&mut prefs_ui.current.theme, theme, name)
+                .changed();
+        }
+    });
+    let new_theme = prefs_ui.current.theme;
+    prefs_ui.color(&tr!("prefs-accent"), access!(.accent));
+    let preview_visuals =
+        crate::gui::theme_visuals(new_theme.is_dark(os_is_dark), prefs_ui.current.accent);
+    build_theme_preview(prefs_ui.ui, preview_visuals, prefs_ui.current.background);
+
+    prefs_ui.ui.separator();
+
+    prefs_ui.ui.strong(tr!("prefs-faces"));
     for (i, &face) in puzzle_type.faces().iter().enumerate() {
         prefs_ui.color(face.name, access!([(puzzle_type, Face(i as _))]));
     }
 
     prefs_ui.ui.separator();
 
-    prefs_ui.ui.strong("Special");
-    prefs_ui.color("Background", access!(.background));
-    prefs_ui.color("Blindfolded stickers", access!(.blind_face));
-    prefs_ui.checkbox("Blindfold mode", access!(.blindfold));
+    prefs_ui.ui.strong(tr!("prefs-special"));
+    prefs_ui.color(&tr!("prefs-background"), access!(.background));
+    prefs_ui.ui.horizontal(|ui| {
+        for (style, name) in [
+            (BackgroundStyle::Flat, tr!("prefs-background-style-flat")),
+            (
+                BackgroundStyle::Gradient,
+                tr!("prefs-background-style-gradient"),
+            ),
+            (BackgroundStyle::Grid, tr!("prefs-background-style-grid")),
+        ] {
+            *prefs_ui.changed |= ui
+                .selectable_value(&mut prefs_ui.current.background_style, style, name)
+                .changed();
+        }
+    });
+    let is_flat = prefs_ui.current.background_style == BackgroundStyle::Flat;
+    prefs_ui.ui.add_enabled_ui(!is_flat, |ui| {
+        PrefsUi { ui, ..prefs_ui }.color(
+            &tr!("prefs-background-secondary"),
+            access!(.background_secondary),
+        );
+    });
+    prefs_ui.color(&tr!("prefs-blindfolded-stickers"), access!(.blind_face));
+    prefs_ui.checkbox(&tr!("prefs-blindfold-mode"), access!(.blindfold));
+
+    if new_theme != old_theme {
+        prefs.switch_theme_colors(old_theme.is_dark(os_is_dark), new_theme.is_dark(os_is_dark));
+    }
 
     prefs.needs_save |= changed;
     if changed {
         app.request_redraw_puzzle();
     }
 }
+fn build_theme_preview(ui: &mut egui::Ui, visuals: egui::Visuals, background: egui::Color32) {
+    ui.scope(|ui| {
+        *ui.visuals_mut() = visuals;
+        egui::Frame::group(ui.style())
+            .fill(ui.visuals().widgets.noninteractive.bg_fill)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let _ = ui.selectable_label(true, tr!("prefs-theme-preview-selected"));
+                    let _ = ui.selectable_label(false, tr!("prefs-theme-preview-unselected"));
+                    let _ = ui.button(tr!("prefs-theme-preview-button"));
+                });
+                let size = egui::vec2(ui.available_width(), ui.spacing().interact_size.y);
+                let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                ui.painter().rect_filled(rect, 0.0, background);
+            });
+    });
+}
 pub fn build_graphics_section(ui: &mut egui::Ui, app: &mut App) {
     let prefs = &mut app.prefs;
 
@@ -168,24 +264,85 @@ pub fn build_graphics_section(ui: &mut egui::Ui, app: &mut App) {
 
     let speed = prefs_ui.current.fps_limit as f64 / 1000.0; // logarithmic speed
     prefs_ui
-        .num("FPS limit", access!(.fps_limit), |dv| {
+        .num(&tr!("prefs-gfx-fps-limit"), access!(.fps_limit), |dv| {
             dv.fixed_decimals(0).clamp_range(30..=1000).speed(speed)
         })
-        .on_hover_explanation("Frames Per Second", "Limits framerate to save power");
+        .on_hover_tr("prefs-gfx-fps-limit");
+
+    prefs_ui
+        .checkbox(&tr!("prefs-gfx-idle-mode"), access!(.idle_mode))
+        .on_hover_tr("prefs-gfx-idle-mode");
+    let is_idle_mode_enabled = prefs_ui.current.idle_mode;
+    prefs_ui.ui.add_enabled_ui(is_idle_mode_enabled, |ui| {
+        PrefsUi { ui, ..prefs_ui }.num(
+            &tr!("prefs-gfx-idle-fps-limit"),
+            access!(.idle_fps_limit),
+            |dv| dv.fixed_decimals(0).clamp_range(1..=30),
+        );
+    });
+
+    prefs_ui
+        .num(&tr!("prefs-gfx-ui-scale"), access!(.ui_scale), |dv| {
+            dv.fixed_decimals(2)
+                .clamp_range(0.5..=3.0_f32)
+                .speed(0.01)
+                .suffix("x")
+        })
+        .on_hover_tr("prefs-gfx-ui-scale");
 
     let is_msaa_disabled = cfg!(target_arch = "wasm32");
     prefs_ui.ui.add_enabled_ui(!is_msaa_disabled, |ui| {
         PrefsUi { ui, ..prefs_ui }
-            .checkbox("MSAA", access!(.msaa))
-            .on_hover_explanation(
-                "Multisample Anti-Aliasing",
-                "Makes edges less jagged, \
-                 but may worsen performance.",
+            .checkbox(&tr!("prefs-gfx-msaa"), access!(.msaa))
+            .on_hover_tr("prefs-gfx-msaa")
+            .on_disabled_hover_text(tr_attr("prefs-gfx-msaa", "disabled"));
+    });
+
+    prefs_ui.checkbox(&tr!("prefs-gfx-show-fps"), access!(.show_fps));
+    prefs_ui
+        .checkbox(&tr!("prefs-gfx-perf-hud"), access!(.show_perf_hud))
+        .on_hover_tr("prefs-gfx-perf-hud");
+
+    prefs_ui
+        .checkbox(&tr!("prefs-gfx-lod"), access!(.lod))
+        .on_hover_tr("prefs-gfx-lod");
+    let lod = prefs_ui.current.lod;
+    prefs_ui.ui.add_enabled_ui(lod, |ui| {
+        PrefsUi { ui, ..prefs_ui }.num(
+            &tr!("prefs-gfx-lod-min-size"),
+            access!(.lod_min_size),
+            |dv| {
+                dv.fixed_decimals(1)
+                    .clamp_range(0.0..=32.0_f32)
+                    .speed(0.1)
+                    .suffix(" px")
+            },
+        );
+    });
+
+    prefs_ui.ui.separator();
+
+    prefs_ui.ui.label(tr!("prefs-gfx-stereo"));
+    prefs_ui.ui.horizontal(|ui| {
+        for (mode, name) in [
+            (StereoMode::Off, tr!("prefs-gfx-stereo-off")),
+            (StereoMode::SideBySide, tr!("prefs-gfx-stereo-side-by-side")),
+            (StereoMode::Anaglyph, tr!("prefs-gfx-stereo-anaglyph")),
+        ] {
+            *prefs_ui.changed |= ui
+                .selectable_value(&mut prefs_ui.current.stereo, mode, name)
+                .changed();
+        }
+    });
+    let stereo = prefs_ui.current.stereo != StereoMode::Off;
+    prefs_ui.ui.add_enabled_ui(stereo, |ui| {
+        PrefsUi { ui, ..prefs_ui }
+            .num(
+                &tr!("prefs-gfx-eye-separation"),
+                access!(.eye_separation),
+                |dv| dv.fixed_decimals(2).clamp_range(0.0..=0.5_f32).speed(0.002),
             )
-            .on_disabled_hover_text(
-                "Multisample anti-aliasing \
-                 is not supported on web.",
-            );
+            .on_hover_tr("prefs-gfx-eye-separation");
     });
 
     prefs.needs_save |= changed;
@@ -193,6 +350,96 @@ pub fn build_graphics_section(ui: &mut egui::Ui, app: &mut App) {
         app.request_redraw_puzzle();
     }
 }
+#[cfg(not(target_arch = "wasm32"))]
+pub fn build_screenshots_section(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.screenshots,
+        defaults: &DEFAULT_PREFS.screenshots,
+        changed: &mut changed,
+    };
+
+    prefs_ui
+        .num(
+            &tr!("prefs-screenshots-resolution"),
+            access!(.scale),
+            |dv| dv.clamp_range(1..=4).speed(0.05).suffix("x"),
+        )
+        .on_hover_tr("prefs-screenshots-resolution");
+    prefs_ui.checkbox(&tr!("prefs-screenshots-copy"), access!(.copy_to_clipboard));
+
+    prefs_ui.ui.horizontal(|ui| {
+        let directory = prefs_ui.current.directory();
+        let directory_text = match &directory {
+            Some(dir) => dir.display().to_string(),
+            None => tr!("prefs-screenshots-no-folder").to_owned(),
+        };
+        if ui.button(tr!("prefs-screenshots-choose-folder")).clicked() {
+            let mut dialog = rfd::FileDialog::new();
+            if let Some(dir) = &directory {
+                dialog = dialog.set_directory(dir);
+            }
+            if let Some(dir) = dialog.pick_folder() {
+                prefs_ui.current.directory = Some(dir);
+                *prefs_ui.changed = true;
+            }
+        }
+        ui.label(directory_text);
+    });
+
+    prefs.needs_save |= changed;
+}
+#[cfg(not(target_arch = "wasm32"))]
+pub fn build_mini_mode_section(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.mini_mode,
+        defaults: &DEFAULT_PREFS.mini_mode,
+        changed: &mut changed,
+    };
+
+    prefs_ui.checkbox(
+        &tr!("prefs-mini-mode-always-on-top"),
+        access!(.always_on_top),
+    );
+    prefs_ui.checkbox(&tr!("prefs-mini-mode-transparent"), access!(.transparent));
+    let is_transparent = prefs_ui.current.transparent;
+    prefs_ui.ui.add_enabled_ui(is_transparent, |ui| {
+        PrefsUi { ui, ..prefs_ui }.percent(
+            &tr!("prefs-mini-mode-background-opacity"),
+            access!(.background_opacity),
+        );
+    });
+
+    prefs.needs_save |= changed;
+}
+pub fn build_layout_section(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.layout,
+        defaults: &DEFAULT_PREFS.layout,
+        changed: &mut changed,
+    };
+
+    prefs_ui
+        .num(
+            &tr!("prefs-layout-compact-width"),
+            access!(.compact_width),
+            |dv| dv.fixed_decimals(0).clamp_range(0.0..=2000.0).speed(5.0),
+        )
+        .on_hover_tr("prefs-layout-compact-width");
+
+    prefs.needs_save |= changed;
+}
 pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
     let prefs = &mut app.prefs;
 
@@ -206,72 +453,315 @@ pub fn build_interaction_section(ui: &mut egui::Ui, app: &mut App) {
 
     prefs_ui
         .checkbox(
-            "Confirm discard only when scrambled",
+            &tr!("prefs-interaction-confirm-discard"),
             access!(.confirm_discard_only_when_scrambled),
         )
-        .on_hover_explanation(
-            "",
-            "When enabled, a confirmation dialog before \
-             destructive actions (like resetting the puzzle) \
-             is only shown when the puzzle has been fully \
-             scrambled.",
-        );
+        .on_hover_tr("prefs-interaction-confirm-discard");
+
+    prefs_ui.ui.horizontal(|ui| {
+        ui.label(tr!("prefs-interaction-solved-in"))
+            .on_hover_tr("prefs-interaction-solved-in");
+        for (solved_state, name) in [
+            (
+                SolvedStateDefinition::AnyOrientation,
+                tr!("prefs-interaction-solved-any-orientation"),
+            ),
+            (
+                SolvedStateDefinition::OriginalOrientation,
+                tr!("prefs-interaction-solved-original-orientation"),
+            ),
+        ] {
+            *prefs_ui.changed |= ui
+                .selectable_value(&mut prefs_ui.current.solved_state, solved_state, name)
+                .changed();
+        }
+    });
+    prefs_ui
+        .checkbox(&tr!("prefs-interaction-supercube"), access!(.supercube))
+        .on_hover_tr("prefs-interaction-supercube");
 
     prefs_ui.ui.separator();
 
-    prefs_ui.num("Drag sensitivity", access!(.drag_sensitivity), |dv| {
-        dv.fixed_decimals(2).clamp_range(0.0..=3.0_f32).speed(0.01)
-    });
+    prefs_ui.num(
+        &tr!("prefs-interaction-drag-sensitivity"),
+        access!(.drag_sensitivity),
+        |dv| dv.fixed_decimals(2).clamp_range(0.0..=3.0_f32).speed(0.01),
+    );
     prefs_ui
-        .checkbox("Realign puzzle on release", access!(.realign_on_release))
-        .on_hover_explanation(
-            "",
-            "When enabled, the puzzle snaps back immediately when \
-             the mouse is released after dragging to rotate it.",
-        );
+        .checkbox(
+            &tr!("prefs-interaction-realign-on-release"),
+            access!(.realign_on_release),
+        )
+        .on_hover_tr("prefs-interaction-realign-on-release");
     prefs_ui
-        .checkbox("Realign puzzle on keypress", access!(.realign_on_keypress))
-        .on_hover_explanation(
-            "",
-            "When enabled, the puzzle snaps back immediately when \
-             the keyboard is used to grip or do a move.",
-        );
+        .checkbox(
+            &tr!("prefs-interaction-realign-on-keypress"),
+            access!(.realign_on_keypress),
+        )
+        .on_hover_tr("prefs-interaction-realign-on-keypress");
     prefs_ui
-        .checkbox("Smart realign", access!(.smart_realign))
-        .on_hover_explanation(
-            "",
-            "When enabled, the puzzle snaps to the nearest \
-             similar orientation, not the original. This \
-             adds a full-puzzle rotation to the undo history.",
-        );
+        .checkbox(
+            &tr!("prefs-interaction-smart-realign"),
+            access!(.smart_realign),
+        )
+        .on_hover_tr("prefs-interaction-smart-realign");
 
     prefs_ui.ui.separator();
 
-    prefs_ui.collapsing("Animations", |mut prefs_ui| {
+    prefs_ui.collapsing(tr!("prefs-anim"), |mut prefs_ui| {
         prefs_ui
-            .checkbox("Dynamic twist speed", access!(.dynamic_twist_speed))
-            .on_hover_explanation(
-                "",
-                "When enabled, the puzzle twists faster when \
-                 many moves are queued up. When all queued \
-                 moves are complete, the twist speed resets.",
-            );
+            .checkbox(
+                &tr!("prefs-anim-dynamic-twist-speed"),
+                access!(.dynamic_twist_speed),
+            )
+            .on_hover_tr("prefs-anim-dynamic-twist-speed");
+        let dynamic = prefs_ui.current.dynamic_twist_speed;
+        prefs_ui.ui.add_enabled_ui(dynamic, |ui| {
+            let mut prefs_ui = PrefsUi {
+                ui,
+                current: &mut *prefs_ui.current,
+                defaults: prefs_ui.defaults,
+                changed: &mut *prefs_ui.changed,
+            };
+            prefs_ui.ui.horizontal(|ui| {
+                for (curve, name) in [
+                    (
+                        DynamicTwistCurve::Exponential,
+                        tr!("prefs-anim-curve-exponential"),
+                    ),
+                    (DynamicTwistCurve::Linear, tr!("prefs-anim-curve-linear")),
+                    (
+                        DynamicTwistCurve::QueueProportional,
+                        tr!("prefs-anim-curve-queue-length"),
+                    ),
+                ] {
+                    *prefs_ui.changed |= ui
+                        .selectable_value(&mut prefs_ui.current.dynamic_twist_curve, curve, name)
+                        .changed();
+                }
+            });
+            let has_factor =
+                prefs_ui.current.dynamic_twist_curve != DynamicTwistCurve::QueueProportional;
+            prefs_ui.ui.add_enabled_ui(has_factor, |ui| {
+                PrefsUi { ui, ..prefs_ui }
+                    .num(
+                        &tr!("prefs-anim-speedup"),
+                        access!(.dynamic_twist_factor),
+                        |dv| dv.fixed_decimals(2).clamp_range(0.0..=2.0_f32).speed(0.01),
+                    )
+                    .on_hover_tr("prefs-anim-speedup");
+            });
+        });
+        prefs_ui
+            .checkbox(
+                &tr!("prefs-anim-simultaneous-twists"),
+                access!(.simultaneous_twists),
+            )
+            .on_hover_tr("prefs-anim-simultaneous-twists");
+        prefs_ui
+            .checkbox(
+                &tr!("prefs-anim-merge-repeated-twists"),
+                access!(.merge_repeated_twists),
+            )
+            .on_hover_tr("prefs-anim-merge-repeated-twists");
+        prefs_ui
+            .percent(
+                &tr!("prefs-anim-skip-threshold"),
+                access!(.twist_skip_threshold),
+            )
+            .on_hover_tr("prefs-anim-skip-threshold");
 
         let speed = prefs_ui.current.twist_duration.at_least(0.1) / 100.0; // logarithmic speed
-        prefs_ui.num("Twist duration", access!(.twist_duration), |dv| {
-            dv.fixed_decimals(2).clamp_range(0.0..=5.0_f32).speed(speed)
-        });
+        prefs_ui.num(
+            &tr!("prefs-anim-twist-duration"),
+            access!(.twist_duration),
+            |dv| dv.fixed_decimals(2).clamp_range(0.0..=5.0_f32).speed(speed),
+        );
 
         let speed = prefs_ui.current.other_anim_duration.at_least(0.1) / 100.0; // logarithmic speed
         prefs_ui
-            .num("Other animations", access!(.other_anim_duration), |dv| {
-                dv.fixed_decimals(2).clamp_range(0.0..=1.0_f32).speed(speed)
-            })
-            .on_hover_explanation(
-                "",
-                "Number of seconds for other animations, \
-                 such as hiding a piece.",
-            );
+            .num(
+                &tr!("prefs-anim-other"),
+                access!(.other_anim_duration),
+                |dv| dv.fixed_decimals(2).clamp_range(0.0..=1.0_f32).speed(speed),
+            )
+            .on_hover_tr("prefs-anim-other");
+        prefs_ui
+            .checkbox(&tr!("prefs-anim-rewind-undo"), access!(.rewind_undo))
+            .on_hover_tr("prefs-anim-rewind-undo");
+    });
+
+    prefs_ui.ui.separator();
+
+    let mut prefs_ui = PrefsUi {
+        ui: prefs_ui.ui,
+        current: &mut prefs.info,
+        defaults: &DEFAULT_PREFS.info,
+        changed: &mut changed,
+    };
+    prefs_ui
+        .checkbox(
+            &tr!("prefs-info-describe-state"),
+            access!(.accessibility_descriptions),
+        )
+        .on_hover_tr("prefs-info-describe-state");
+    prefs_ui
+        .checkbox(
+            &tr!("prefs-info-sticker-tooltips"),
+            access!(.sticker_tooltips),
+        )
+        .on_hover_tr("prefs-info-sticker-tooltips");
+    prefs_ui
+        .checkbox(
+            &tr!("prefs-info-last-layer-case"),
+            access!(.last_layer_case),
+        )
+        .on_hover_tr("prefs-info-last-layer-case");
+
+    prefs.needs_save |= changed;
+}
+pub fn build_scramble_section(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle_type = app.puzzle.ty();
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.scramble,
+        defaults: &DEFAULT_PREFS.scramble,
+        changed: &mut changed,
+    };
+
+    prefs_ui
+        .num(
+            &tr!("prefs-scramble-partial-max"),
+            access!(.partial_max),
+            |dv| dv.clamp_range(PARTIAL_SCRAMBLE_MOVE_COUNT_MIN..=1000),
+        )
+        .on_hover_tr("prefs-scramble-partial-max");
+    prefs_ui
+        .num(
+            &tr!("prefs-scramble-queue-length"),
+            access!(.queue_length),
+            |dv| dv.clamp_range(0..=100),
+        )
+        .on_hover_tr("prefs-scramble-queue-length");
+
+    prefs_ui.ui.horizontal(|ui| {
+        let mut custom = prefs_ui.current.full_move_count.is_some();
+        let r = ui
+            .checkbox(&mut custom, tr!("prefs-scramble-full-length"))
+            .on_hover_tr("prefs-scramble-full-length");
+        if r.changed() {
+            prefs_ui.current.full_move_count = custom.then(|| puzzle_type.scramble_moves_count());
+            *prefs_ui.changed = true;
+        }
+        if let Some(n) = &mut prefs_ui.current.full_move_count {
+            *prefs_ui.changed |= ui
+                .add(egui::DragValue::new(n).clamp_range(1..=10_000))
+                .changed();
+        }
+    });
+
+    prefs_ui
+        .checkbox(
+            &tr!("prefs-scramble-outer-layers-only"),
+            access!(.outer_layers_only),
+        )
+        .on_hover_tr("prefs-scramble-outer-layers-only");
+
+    prefs_ui.ui.label(tr!(
+        "prefs-scramble-twist-axes",
+        puzzle = puzzle_type.family_display_name(),
+    ));
+    prefs_ui.ui.horizontal_wrapped(|ui| {
+        let disallowed = &mut prefs_ui.current.disallowed_axes[puzzle_type];
+        for axis in puzzle_type.twist_axes() {
+            let mut allowed = !disallowed.contains(axis.name);
+            if ui.checkbox(&mut allowed, axis.name).changed() {
+                if allowed {
+                    disallowed.remove(axis.name);
+                } else {
+                    disallowed.insert(axis.name.to_owned());
+                }
+                *prefs_ui.changed = true;
+            }
+        }
+    });
+
+    prefs.needs_save |= changed;
+}
+#[cfg(not(target_arch = "wasm32"))]
+pub fn build_hotkeys_section(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.hotkeys,
+        defaults: &DEFAULT_PREFS.hotkeys,
+        changed: &mut changed,
+    };
+
+    prefs_ui
+        .checkbox(&tr!("prefs-hotkeys-enabled"), access!(.enabled))
+        .on_hover_tr("prefs-hotkeys-enabled");
+
+    let enabled = prefs_ui.current.enabled;
+    prefs_ui.ui.add_enabled_ui(enabled, |ui| {
+        let current = &mut prefs_ui.current;
+        egui::Grid::new(unique_id!()).show(ui, |ui| {
+            for (label, key) in [
+                (tr!("prefs-hotkeys-toggle-timer"), &mut current.toggle_timer),
+                (
+                    tr!("prefs-hotkeys-next-scramble"),
+                    &mut current.next_scramble,
+                ),
+            ] {
+                ui.label(label);
+                *prefs_ui.changed |= ui
+                    .add(egui::TextEdit::singleline(key).hint_text("Ctrl+Shift+F9"))
+                    .changed();
+                ui.end_row();
+            }
+        });
+    });
+
+    prefs.needs_save |= changed;
+}
+pub fn build_sounds_section(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.sounds,
+        defaults: &DEFAULT_PREFS.sounds,
+        changed: &mut changed,
+    };
+
+    prefs_ui.checkbox(&tr!("prefs-sounds-enabled"), access!(.enabled));
+
+    let enabled = prefs_ui.current.enabled;
+    prefs_ui.ui.add_enabled_ui(enabled, |ui| {
+        let mut prefs_ui = PrefsUi { ui, ..prefs_ui };
+
+        prefs_ui.percent(&tr!("prefs-sounds-master-volume"), access!(.master_volume));
+
+        prefs_ui.ui.separator();
+
+        prefs_ui
+            .percent(&tr!("prefs-sounds-twists"), access!(.twist_volume))
+            .on_hover_tr("prefs-sounds-twists");
+        prefs_ui
+            .percent(&tr!("prefs-sounds-timer"), access!(.timer_volume))
+            .on_hover_tr("prefs-sounds-timer");
+        prefs_ui
+            .percent(&tr!("prefs-sounds-inspection"), access!(.inspection_volume))
+            .on_hover_tr("prefs-sounds-inspection");
+        prefs_ui
+            .percent(&tr!("prefs-sounds-pb"), access!(.pb_volume))
+            .on_hover_tr("prefs-sounds-pb");
     });
 
     prefs.needs_save |= changed;
@@ -287,16 +777,49 @@ pub fn build_outlines_section(ui: &mut egui::Ui, app: &mut App) {
         changed: &mut changed,
     };
 
-    prefs_ui.ui.strong("Colors");
-    prefs_ui.color("Default", access!(.default_color));
-    prefs_ui.color("Hidden", access!(.hidden_color));
-    prefs_ui.color("Hovered", access!(.hovered_color));
-    prefs_ui.color("Sel. sticker", access!(.selected_sticker_color));
-    prefs_ui.color("Sel. piece", access!(.selected_piece_color));
+    prefs_ui.ui.strong(tr!("prefs-outlines-colors"));
+    prefs_ui.color(&tr!("prefs-outlines-default"), access!(.default_color));
+    prefs_ui.color(&tr!("prefs-outlines-hidden"), access!(.hidden_color));
+    prefs_ui.color(&tr!("prefs-outlines-hovered"), access!(.hovered_color));
+    prefs_ui.color(
+        &tr!("prefs-outlines-selected-sticker"),
+        access!(.selected_sticker_color),
+    );
+    prefs_ui.color(
+        &tr!("prefs-outlines-selected-piece"),
+        access!(.selected_piece_color),
+    );
+    prefs_ui.color(&tr!("prefs-outlines-move-trail"), access!(.trail_color));
+    prefs_ui
+        .color(&tr!("prefs-outlines-undo-redo"), access!(.history_color))
+        .on_hover_tr("prefs-outlines-undo-redo");
+
+    prefs_ui.ui.separator();
+
+    prefs_ui.ui.strong(tr!("prefs-outlines-face-colors"));
+    prefs_ui
+        .checkbox(
+            &tr!("prefs-outlines-face-default"),
+            access!(.default_face_color),
+        )
+        .on_hover_tr("prefs-outlines-face-default");
+    prefs_ui.checkbox(&tr!("prefs-outlines-hidden"), access!(.hidden_face_color));
+    prefs_ui.checkbox(&tr!("prefs-outlines-hovered"), access!(.hovered_face_color));
+    prefs_ui.checkbox(
+        &tr!("prefs-outlines-selected"),
+        access!(.selected_face_color),
+    );
+    prefs_ui
+        .num(
+            &tr!("prefs-outlines-shade"),
+            access!(.face_color_shade),
+            |dv| dv.fixed_decimals(2).clamp_range(-1.0..=1.0_f32).speed(0.01),
+        )
+        .on_hover_tr("prefs-outlines-shade");
 
     prefs_ui.ui.separator();
 
-    prefs_ui.ui.strong("Sizes");
+    prefs_ui.ui.strong(tr!("prefs-outlines-sizes"));
 
     fn outline_size_dv(drag_value: egui::DragValue<'_>) -> egui::DragValue<'_> {
         drag_value
@@ -304,10 +827,55 @@ pub fn build_outlines_section(ui: &mut egui::Ui, app: &mut App) {
             .clamp_range(0.0..=5.0_f32)
             .speed(0.01)
     }
-    prefs_ui.num("Default", access!(.default_size), outline_size_dv);
-    prefs_ui.num("Hidden", access!(.hidden_size), outline_size_dv);
-    prefs_ui.num("Hovered", access!(.hovered_size), outline_size_dv);
-    prefs_ui.num("Selected", access!(.selected_size), outline_size_dv);
+    prefs_ui.num(
+        &tr!("prefs-outlines-default"),
+        access!(.default_size),
+        outline_size_dv,
+    );
+    prefs_ui.num(
+        &tr!("prefs-outlines-hidden"),
+        access!(.hidden_size),
+        outline_size_dv,
+    );
+    prefs_ui.num(
+        &tr!("prefs-outlines-hovered"),
+        access!(.hovered_size),
+        outline_size_dv,
+    );
+    prefs_ui.num(
+        &tr!("prefs-outlines-selected"),
+        access!(.selected_size),
+        outline_size_dv,
+    );
+    prefs_ui.num(
+        &tr!("prefs-outlines-move-trail"),
+        access!(.trail_size),
+        outline_size_dv,
+    );
+
+    prefs_ui.ui.separator();
+
+    prefs_ui.ui.strong(tr!("prefs-outlines-move-trail"));
+    prefs_ui
+        .num(
+            &tr!("prefs-outlines-trail-length"),
+            access!(.trail_length),
+            |dv| {
+                dv.clamp_range(0..=MAX_TRAIL_LENGTH)
+                    .suffix(format!(" {}", tr!("prefs-outlines-trail-length-unit")))
+            },
+        )
+        .on_hover_tr("prefs-outlines-trail-length");
+    prefs_ui.num(
+        &tr!("prefs-outlines-trail-fade-time"),
+        access!(.trail_duration),
+        |dv| {
+            dv.fixed_decimals(1)
+                .clamp_range(0.1..=30.0_f32)
+                .speed(0.05)
+                .suffix("s")
+        },
+    );
 
     prefs.needs_save |= changed;
     if changed {
@@ -325,10 +893,12 @@ pub fn build_opacity_section(ui: &mut egui::Ui, app: &mut App) {
         changed: &mut changed,
     };
 
-    prefs_ui.percent("Base", access!(.base));
-    prefs_ui.percent("Ungripped", access!(.ungripped));
-    prefs_ui.percent("Hidden", access!(.hidden));
-    prefs_ui.percent("Selected", access!(.selected));
+    prefs_ui.percent(&tr!("prefs-opacity-base"), access!(.base));
+    prefs_ui.percent(&tr!("prefs-opacity-ungripped"), access!(.ungripped));
+    prefs_ui.percent(&tr!("prefs-opacity-hidden"), access!(.hidden));
+    prefs_ui.percent(&tr!("prefs-opacity-selected"), access!(.selected));
+    build_dim_solved_checkbox(&mut prefs_ui);
+    prefs_ui.percent(&tr!("prefs-opacity-solved"), access!(.solved));
     build_unhide_grip_checkbox(&mut prefs_ui);
 
     prefs.needs_save |= changed;
@@ -344,7 +914,7 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
 
     let mut changed = false;
 
-    ui.collapsing("Presets", |ui| {
+    ui.collapsing(tr!("prefs-view-presets"), |ui| {
         let mut presets_ui = PresetsUi {
             id: unique_id!(),
             presets: &mut presets.presets,
@@ -363,7 +933,7 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
             let mut changed = false;
 
             let mut r = ui.scope(|ui| {
-                if ui.button("Load").clicked() {
+                if ui.button(tr!("prefs-view-load")).clicked() {
                     let old = std::mem::replace(&mut presets.current, preset.value.clone());
                     app.puzzle.animate_from_view_settings(old);
                     presets.active_preset = Some(preset.clone());
@@ -392,86 +962,174 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
         changed: &mut changed,
     };
 
-    prefs_ui.collapsing("Position", |mut prefs_ui| {
-        prefs_ui.num("Horizontal align", access!(.align_h), |dv| {
+    prefs_ui.collapsing(tr!("prefs-view-position"), |mut prefs_ui| {
+        prefs_ui.num(&tr!("prefs-view-align-h"), access!(.align_h), |dv| {
             dv.clamp_range(-1.0..=1.0).fixed_decimals(2).speed(0.01)
         });
-        prefs_ui.num("Vertical align", access!(.align_v), |dv| {
+        prefs_ui.num(&tr!("prefs-view-align-v"), access!(.align_v), |dv| {
             dv.clamp_range(-1.0..=1.0).fixed_decimals(2).speed(0.01)
         });
     });
 
-    prefs_ui.collapsing("View angle", |mut prefs_ui| {
-        prefs_ui.angle("Pitch", access!(.pitch), |dv| dv.clamp_range(-90.0..=90.0));
-        prefs_ui.angle("Yaw", access!(.yaw), |dv| dv.clamp_range(-180.0..=180.0));
-        prefs_ui.angle("Roll", access!(.roll), |dv| dv.clamp_range(-180.0..=180.0));
+    prefs_ui.collapsing(tr!("prefs-view-angle"), |mut prefs_ui| {
+        prefs_ui.angle(&tr!("prefs-view-pitch"), access!(.pitch), |dv| {
+            dv.clamp_range(-90.0..=90.0)
+        });
+        prefs_ui.angle(&tr!("prefs-view-yaw"), access!(.yaw), |dv| {
+            dv.clamp_range(-180.0..=180.0)
+        });
+        prefs_ui.angle(&tr!("prefs-view-roll"), access!(.roll), |dv| {
+            dv.clamp_range(-180.0..=180.0)
+        });
     });
 
-    prefs_ui.collapsing("Projection", |mut prefs_ui| {
+    let mut auto_fit = false;
+    prefs_ui.collapsing(tr!("prefs-view-projection"), |mut prefs_ui| {
         let speed = prefs_ui.current.scale / 100.0; // logarithmic speed
-        prefs_ui.num("Scale", access!(.scale), |dv| {
-            dv.fixed_decimals(2).clamp_range(0.1..=5.0_f32).speed(speed)
+        prefs_ui.num(&tr!("prefs-view-scale"), access!(.scale), |dv| {
+            dv.fixed_decimals(2)
+                .clamp_range(MIN_SCALE..=MAX_SCALE)
+                .speed(speed)
         });
+        auto_fit = prefs_ui
+            .ui
+            .button(tr!("prefs-view-fit"))
+            .on_hover_text(tr_attr("prefs-view-fit", "tooltip"))
+            .clicked();
 
         if proj_ty == ProjectionType::_4D {
-            prefs_ui.angle("4D FOV", access!(.fov_4d), |dv| {
+            prefs_ui.angle(&tr!("prefs-view-fov-4d"), access!(.fov_4d), |dv| {
                 dv.clamp_range(1.0..=120.0).speed(0.5)
             });
         }
 
         let label = if prefs_ui.current.fov_3d == 120.0 {
-            "QUAKE PRO"
+            "QUAKE PRO".to_owned()
         } else if prefs_ui.current.fov_3d == -120.0 {
-            "ORP EKAUQ"
+            "ORP EKAUQ".to_owned()
         } else {
-            "3D FOV"
+            tr!("prefs-view-fov-3d")
         };
-        prefs_ui.angle(label, access!(.fov_3d), |dv| {
+        prefs_ui.angle(&label, access!(.fov_3d), |dv| {
             dv.clamp_range(-120.0..=120.0).speed(0.5)
         });
+        prefs_ui
+            .num(
+                &tr!("prefs-view-camera-distance"),
+                access!(.camera_distance),
+                |dv| dv.fixed_decimals(2).clamp_range(0.0..=10.0_f32).speed(0.01),
+            )
+            .on_hover_tr("prefs-view-camera-distance");
     });
 
-    prefs_ui.collapsing("Geometry", |mut prefs_ui| {
+    prefs_ui.collapsing(tr!("prefs-view-geometry"), |mut prefs_ui| {
         if proj_ty == ProjectionType::_3D {
-            prefs_ui.checkbox("Show frontfaces", access!(.show_frontfaces));
-            prefs_ui.checkbox("Show backfaces", access!(.show_backfaces));
+            prefs_ui.checkbox(
+                &tr!("prefs-view-show-frontfaces"),
+                access!(.show_frontfaces),
+            );
+            prefs_ui.checkbox(&tr!("prefs-view-show-backfaces"), access!(.show_backfaces));
         }
         if proj_ty == ProjectionType::_4D {
-            prefs_ui.checkbox("Clip 4D", access!(.clip_4d));
+            prefs_ui.checkbox(&tr!("prefs-view-clip-4d"), access!(.clip_4d));
         }
+        prefs_ui
+            .checkbox(&tr!("prefs-view-net"), access!(.net))
+            .on_hover_tr("prefs-view-net");
 
-        prefs_ui.num("Face spacing", access!(.face_spacing), |dv| {
-            dv.fixed_decimals(2).clamp_range(0.0..=0.9_f32).speed(0.005)
-        });
+        prefs_ui.num(
+            &tr!("prefs-view-face-spacing"),
+            access!(.face_spacing),
+            |dv| dv.fixed_decimals(2).clamp_range(0.0..=0.9_f32).speed(0.005),
+        );
 
-        prefs_ui.num("Sticker spacing", access!(.sticker_spacing), |dv| {
-            dv.fixed_decimals(2).clamp_range(0.0..=0.9_f32).speed(0.005)
-        });
+        prefs_ui.num(
+            &tr!("prefs-view-sticker-spacing"),
+            access!(.sticker_spacing),
+            |dv| dv.fixed_decimals(2).clamp_range(0.0..=0.9_f32).speed(0.005),
+        );
     });
 
-    prefs_ui.collapsing("Lighting", |mut prefs_ui| {
-        prefs_ui.angle("Pitch", access!(.light_pitch), |dv| {
+    prefs_ui.collapsing(tr!("prefs-view-lighting"), |mut prefs_ui| {
+        prefs_ui.angle(&tr!("prefs-view-pitch"), access!(.light_pitch), |dv| {
             dv.clamp_range(-90.0..=90.0)
         });
-        prefs_ui.angle("Yaw", access!(.light_yaw), |dv| {
+        prefs_ui.angle(&tr!("prefs-view-yaw"), access!(.light_yaw), |dv| {
             dv.clamp_range(-180.0..=180.0)
         });
-        prefs_ui.percent("Directional", access!(.light_directional));
-        prefs_ui.percent("Ambient", access!(.light_ambient));
+        prefs_ui.percent(
+            &tr!("prefs-view-light-directional"),
+            access!(.light_directional),
+        );
+        prefs_ui.percent(&tr!("prefs-view-light-ambient"), access!(.light_ambient));
     });
 
+    prefs_ui.collapsing(tr!("prefs-view-labels"), |mut prefs_ui| {
+        prefs_ui
+            .checkbox(&tr!("prefs-view-face-labels"), access!(.face_labels))
+            .on_hover_tr("prefs-view-face-labels");
+    });
+
+    if prefs.view(puzzle_type).face_labels {
+        ui.collapsing(tr!("prefs-view-custom-face-labels"), |ui| {
+            egui::Grid::new(unique_id!()).num_columns(2).show(ui, |ui| {
+                for (i, face_info) in puzzle_type.faces().iter().enumerate() {
+                    let symbol = face_info.symbol;
+                    let mut label = prefs.face_label(puzzle_type, Face(i as _)).to_owned();
+                    ui.label(face_info.name);
+                    let r = ui.add(
+                        egui::TextEdit::singleline(&mut label)
+                            .hint_text(symbol)
+                            .desired_width(FACE_LABEL_EDIT_WIDTH),
+                    );
+                    if r.changed() {
+                        let labels = &mut prefs.face_labels[puzzle_type];
+                        if label.is_empty() || label == symbol {
+                            labels.remove(symbol);
+                        } else {
+                            labels.insert(symbol.to_owned(), label);
+                        }
+                        changed = true;
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    if proj_ty == ProjectionType::_4D {
+        let mut prefs_ui = PrefsUi {
+            ui,
+            current: &mut prefs.guides,
+            defaults: &DEFAULT_PREFS.guides,
+            changed: &mut changed,
+        };
+        prefs_ui.collapsing(tr!("prefs-view-guides"), |mut prefs_ui| {
+            prefs_ui
+                .checkbox(&tr!("prefs-view-axis-arrows"), access!(.axis_arrows))
+                .on_hover_tr("prefs-view-axis-arrows");
+            prefs_ui
+                .checkbox(&tr!("prefs-view-highlight-cell"), access!(.highlight_cell))
+                .on_hover_tr("prefs-view-highlight-cell");
+        });
+    }
+
     prefs.needs_save |= changed;
     if changed {
         app.request_redraw_puzzle();
     }
+    if auto_fit {
+        app.event(Command::AutoFitView);
+    }
 }
 
+pub fn build_dim_solved_checkbox(prefs_ui: &mut PrefsUi<OpacityPreferences>) {
+    prefs_ui
+        .checkbox(&tr!("prefs-opacity-dim-solved"), access!(.dim_solved))
+        .on_hover_tr("prefs-opacity-dim-solved");
+}
 pub fn build_unhide_grip_checkbox(prefs_ui: &mut PrefsUi<OpacityPreferences>) {
     prefs_ui
-        .checkbox("Unhide grip", access!(.unhide_grip))
-        .on_hover_explanation(
-            "",
-            "When enabled, gripping a face will temporarily \
-             disable piece filters.",
-        );
+        .checkbox(&tr!("prefs-opacity-unhide-grip"), access!(.unhide_grip))
+        .on_hover_tr("prefs-opacity-unhide-grip");
 }
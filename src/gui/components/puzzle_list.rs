@@ -1,4 +1,4 @@
-use crate::puzzle::{rubiks_3d, rubiks_4d, PuzzleType, PuzzleTypeEnum};
+use crate::puzzle::{generic, rubiks_3d, rubiks_4d, rubiks_5d, PuzzleType, PuzzleTypeEnum};
 
 pub fn puzzle_type_menu(ui: &mut egui::Ui) -> Option<PuzzleTypeEnum> {
     let mut ret = None;
@@ -37,5 +37,47 @@ pub fn puzzle_type_menu(ui: &mut egui::Ui) -> Option<PuzzleTypeEnum> {
         ret = Some(default);
     }
 
+    let default = PuzzleTypeEnum::Rubiks5D {
+        layer_count: rubiks_5d::DEFAULT_LAYER_COUNT,
+    };
+    let r = ui.menu_button(default.family_display_name(), |ui| {
+        for layer_count in rubiks_5d::LAYER_COUNT_RANGE {
+            let ty = PuzzleTypeEnum::Rubiks5D { layer_count };
+            if ui.button(ty.name()).clicked() {
+                ui.close_menu();
+                ret = Some(ty);
+            }
+        }
+    });
+    if r.response.clicked() {
+        ui.close_menu();
+        ret = Some(default);
+    }
+
+    let ty = PuzzleTypeEnum::Simplex4D;
+    if ui.button(ty.name()).clicked() {
+        ui.close_menu();
+        ret = Some(ty);
+    }
+
+    let ty = PuzzleTypeEnum::Clock;
+    if ui.button(ty.name()).clicked() {
+        ui.close_menu();
+        ret = Some(ty);
+    }
+
+    // Built-in variants and custom puzzles loaded from definition files
+    let generic_puzzles = generic::puzzle_types();
+    if !generic_puzzles.is_empty() {
+        ui.menu_button("Other", |ui| {
+            for ty in generic_puzzles {
+                if ui.button(ty.name()).clicked() {
+                    ui.close_menu();
+                    ret = Some(ty);
+                }
+            }
+        });
+    }
+
     ret
 }
@@ -0,0 +1,79 @@
+use crate::history::{Solve, SUGGESTED_TAGS};
+
+/// Shows a toggle for starring a solve. Returns whether it was changed.
+pub fn solve_star_toggle(ui: &mut egui::Ui, solve: &mut Solve) -> bool {
+    let text = if solve.starred { "★" } else { "☆" };
+    let r = ui
+        .selectable_label(solve.starred, text)
+        .on_hover_text("Star");
+    if r.clicked() {
+        solve.starred ^= true;
+    }
+    r.clicked()
+}
+
+/// Shows a menu button for editing the tags and comment on a solve. Returns
+/// whether they were changed.
+pub fn solve_notes_menu(ui: &mut egui::Ui, solve: &mut Solve) -> bool {
+    let mut changed = false;
+
+    let mut hover_text = solve.tags.iter().cloned().collect::<Vec<_>>().join(", ");
+    if !solve.comment.is_empty() {
+        if !hover_text.is_empty() {
+            hover_text += "\n";
+        }
+        hover_text += &solve.comment;
+    }
+    let icon = if hover_text.is_empty() {
+        "🏷"
+    } else {
+        "🏷…"
+    };
+
+    let r = ui.menu_button(icon, |ui| {
+        ui.strong("Tags");
+        let custom_tags = solve
+            .tags
+            .iter()
+            .filter(|tag| !SUGGESTED_TAGS.contains(&tag.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        let all_tags = SUGGESTED_TAGS
+            .iter()
+            .map(|&tag| tag.to_owned())
+            .chain(custom_tags);
+        for tag in all_tags {
+            let mut has_tag = solve.tags.contains(&tag);
+            if ui.checkbox(&mut has_tag, &tag).changed() {
+                if has_tag {
+                    solve.tags.insert(tag);
+                } else {
+                    solve.tags.remove(&tag);
+                }
+                changed = true;
+            }
+        }
+
+        let new_tag_id = unique_id!();
+        let mut new_tag = ui.data().get_temp::<String>(new_tag_id).unwrap_or_default();
+        let r = ui.add(egui::TextEdit::singleline(&mut new_tag).hint_text("New tag"));
+        if r.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+            let tag = std::mem::take(&mut new_tag);
+            if !tag.trim().is_empty() {
+                changed |= solve.tags.insert(tag.trim().to_owned());
+            }
+        }
+        ui.data().insert_temp(new_tag_id, new_tag);
+
+        ui.separator();
+        ui.strong("Comment");
+        changed |= ui
+            .add(egui::TextEdit::multiline(&mut solve.comment).desired_rows(2))
+            .changed();
+    });
+    if !hover_text.is_empty() {
+        r.response.on_hover_text(hover_text);
+    }
+
+    changed
+}
@@ -0,0 +1,70 @@
+//! Tabs in the main window that can be rearranged by dragging, for showing
+//! panels beside the puzzle view.
+
+use egui_dock::{DockArea, Style, TabViewer, Tree};
+
+use super::puzzle_view;
+use super::windows::Window;
+use crate::app::App;
+use crate::preferences::DockTab;
+
+struct Tabs<'a> {
+    app: &'a mut App,
+    puzzle_texture_id: egui::TextureId,
+}
+impl TabViewer for Tabs<'_> {
+    type Tab = DockTab;
+
+    fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
+        match tab {
+            DockTab::Puzzle => {
+                let background = self.app.prefs.colors.background;
+                ui.painter().rect_filled(ui.max_rect(), 0.0, background);
+                puzzle_view::build(ui, self.app, self.puzzle_texture_id);
+            }
+            DockTab::Panel(name) => match Window::by_name(name) {
+                Some(window) => window.show_contents(ui, self.app),
+                None => {
+                    ui.label(format!("Unknown panel {name:?}"));
+                }
+            },
+        }
+    }
+
+    fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
+        match tab {
+            DockTab::Puzzle => self.app.puzzle.ty().name().into(),
+            DockTab::Panel(name) => match Window::by_name(name) {
+                Some(window) => window.title().into(),
+                None => name.as_str().into(),
+            },
+        }
+    }
+
+    fn on_close(&mut self, tab: &mut Self::Tab) -> bool {
+        let can_close = *tab != DockTab::Puzzle;
+        self.app.prefs.needs_save |= can_close;
+        can_close
+    }
+}
+
+pub(super) fn build(ctx: &egui::Context, app: &mut App, puzzle_texture_id: egui::TextureId) {
+    app.prefs.layout.fix_dock();
+
+    // Rearranging tabs doesn't report any changes, so compare the whole tree.
+    let mut dock = std::mem::replace(&mut app.prefs.layout.dock, Tree::new(vec![]));
+    let old_dock = serde_yaml::to_string(&dock).ok();
+
+    DockArea::new(&mut dock)
+        .style(Style::from_egui(&ctx.style()))
+        .show(
+            ctx,
+            &mut Tabs {
+                app,
+                puzzle_texture_id,
+            },
+        );
+
+    app.prefs.needs_save |= serde_yaml::to_string(&dock).ok() != old_dock;
+    app.prefs.layout.dock = dock;
+}
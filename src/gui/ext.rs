@@ -4,6 +4,9 @@ pub const EXPLANATION_TOOLTIP_WIDTH: f32 = 200.0;
 
 pub trait ResponseExt {
     fn on_hover_explanation(self, strong_text: &str, detailed_message: &str) -> Self;
+    /// Shows an explanation using the `.title` and `.tooltip` attributes of a
+    /// translated message.
+    fn on_hover_tr(self, id: &str) -> Self;
 }
 impl ResponseExt for egui::Response {
     fn on_hover_explanation(self, strong_text: &str, detailed_message: &str) -> Self {
@@ -22,6 +25,11 @@ impl ResponseExt for egui::Response {
             );
         })
     }
+    fn on_hover_tr(self, id: &str) -> Self {
+        let title = crate::i18n::tr_attr(id, "title");
+        let tooltip = crate::i18n::tr_attr(id, "tooltip");
+        self.on_hover_explanation(&title, &tooltip)
+    }
 }
 
 pub trait ComboBoxExt {
@@ -5,7 +5,7 @@ use winit::event::{ElementState, ModifiersState, VirtualKeyCode, WindowEvent};
 use super::components::KeybindSetAccessor;
 use super::ext::*;
 use crate::app::App;
-use crate::preferences::{Key, KeyCombo};
+use crate::preferences::{chord_string, Key, KeyCombo};
 
 const KEYBIND_POPUP_SIZE: egui::Vec2 = egui::vec2(300.0, 200.0);
 
@@ -15,33 +15,72 @@ const SCANCODE_EXPLANATION: &str = "Scancodes are based on physical key position
 pub(super) struct State {
     /// Callback to set the new key combo. This is `None` to indicate that the
     /// popup is closed.
-    callback: Option<Arc<dyn Send + Sync + Fn(&mut App, KeyCombo)>>,
+    callback: Option<Arc<dyn Send + Sync + Fn(&mut App, KeyCombo, Vec<Key>)>>,
 
     key: Option<KeyCombo>,
+    chord: Vec<Key>,
 
     mods: ModifiersState,
     last_vk_pressed: Option<VirtualKeyCode>,
     last_sc_pressed: Option<KeyMappingCode>,
+    /// Non-modifier keys held down when the last key was pressed, in the
+    /// order they were pressed.
+    last_chord_pressed: Vec<(Option<KeyMappingCode>, Option<VirtualKeyCode>)>,
+    /// Non-modifier keys currently held down, in the order they were pressed.
+    held_keys: Vec<(Option<KeyMappingCode>, Option<VirtualKeyCode>)>,
 
     use_vk: bool,
     use_vk_id: Option<egui::Id>,
 }
 impl State {
     fn update_keybind(&mut self) {
-        let sc = self.last_sc_pressed.map(Key::Sc);
-        let vk = self.last_vk_pressed.map(Key::Vk);
-        let key = if self.use_vk { vk.or(sc) } else { sc.or(vk) };
+        let use_vk = self.use_vk;
+        let pick_key = |sc: Option<KeyMappingCode>, vk: Option<VirtualKeyCode>| {
+            let sc = sc.map(Key::Sc);
+            let vk = vk.map(Key::Vk);
+            if use_vk {
+                vk.or(sc)
+            } else {
+                sc.or(vk)
+            }
+        };
 
+        let key = pick_key(self.last_sc_pressed, self.last_vk_pressed);
         self.key = Some(KeyCombo::new(key, self.mods));
+        self.chord = self
+            .last_chord_pressed
+            .iter()
+            .filter_map(|&(sc, vk)| pick_key(sc, vk))
+            .collect();
     }
     fn set_key(&mut self, sc: KeyMappingCode, vk: VirtualKeyCode) {
         self.last_sc_pressed = Some(sc);
         self.last_vk_pressed = Some(vk);
+        self.last_chord_pressed.clear();
+        self.update_keybind();
+    }
+    fn press_key(&mut self, sc: Option<KeyMappingCode>, vk: Option<VirtualKeyCode>) {
+        self.last_sc_pressed = sc;
+        self.last_vk_pressed = vk;
+        self.held_keys.retain(|&k| k != (sc, vk));
+        self.last_chord_pressed = self.held_keys.clone();
+        let is_modifier = sc.map_or(false, |sc| !Key::Sc(sc).modifier_bit().is_empty())
+            || vk.map_or(false, |vk| !Key::Vk(vk).modifier_bit().is_empty());
+        if !is_modifier {
+            self.held_keys.push((sc, vk));
+        }
         self.update_keybind();
     }
+    fn release_key(&mut self, sc: Option<KeyMappingCode>, vk: Option<VirtualKeyCode>) {
+        self.held_keys.retain(|&k| k != (sc, vk));
+    }
     fn confirm(&mut self, app: &mut App) {
         if let Some(callback) = self.callback.take() {
-            callback(app, self.key.unwrap_or_default());
+            callback(
+                app,
+                self.key.unwrap_or_default(),
+                std::mem::take(&mut self.chord),
+            );
         }
     }
     fn cancel(&mut self) {
@@ -62,6 +101,7 @@ fn popup_state_id() -> egui::Id {
 pub(super) fn open<S: KeybindSetAccessor>(
     ctx: &egui::Context,
     key: Option<KeyCombo>,
+    chord: Vec<Key>,
     keybind_set: S,
     idx: usize,
 ) {
@@ -74,16 +114,21 @@ pub(super) fn open<S: KeybindSetAccessor>(
     let use_vk = data.get_temp(use_vk_id).unwrap_or(S::USE_VK_BY_DEFAULT);
 
     *popup_state_mut(&mut data) = State {
-        callback: Some(Arc::new(move |app, new_key_combo| {
-            keybind_set.get_mut(&mut app.prefs)[idx].key = new_key_combo;
+        callback: Some(Arc::new(move |app, new_key_combo, new_chord| {
+            let keybind = &mut keybind_set.get_mut(&mut app.prefs)[idx];
+            keybind.key = new_key_combo;
+            keybind.chord = new_chord;
             app.prefs.needs_save = true;
         })),
 
         key,
+        chord,
 
         mods: ModifiersState::empty(),
         last_vk_pressed: None,
         last_sc_pressed: None,
+        last_chord_pressed: vec![],
+        held_keys: vec![],
 
         use_vk,
         use_vk_id: Some(use_vk_id),
@@ -118,9 +163,15 @@ pub(super) fn build(ctx: &egui::Context, app: &mut App) -> Option<egui::Response
 
                                 ui.heading("Press a key combination");
 
-                                let key_combo = popup_state(ctx).key.unwrap_or_default();
+                                let state = popup_state(ctx);
+                                let key_combo = state.key.unwrap_or_default();
                                 if key_combo.key().is_some() {
-                                    ui.strong(key_combo.to_string());
+                                    ui.strong(chord_string(&state.chord, key_combo))
+                                        .on_hover_explanation(
+                                            "",
+                                            "Hold down other keys before pressing the \
+                                             last key to bind a chord.",
+                                        );
                                 } else {
                                     ui.strong("(press a key)");
                                 }
@@ -226,13 +277,16 @@ pub(crate) fn key_combo_popup_handle_event(
                 match input.virtual_keycode {
                     Some(VirtualKeyCode::Return) if popup.mods.is_empty() => popup.confirm(app),
                     Some(VirtualKeyCode::Escape) if popup.mods.is_empty() => popup.cancel(),
-                    _ => {
-                        popup.last_sc_pressed = key_names::sc_to_key(input.scancode as u16);
-                        popup.last_vk_pressed = input.virtual_keycode;
-                        popup.update_keybind();
-                    }
+                    _ => popup.press_key(
+                        key_names::sc_to_key(input.scancode as u16),
+                        input.virtual_keycode,
+                    ),
                 }
             }
+            winit::event::WindowEvent::KeyboardInput { input, .. } => popup.release_key(
+                key_names::sc_to_key(input.scancode as u16),
+                input.virtual_keycode,
+            ),
 
             winit::event::WindowEvent::ModifiersChanged(mods) => popup.mods = *mods,
 
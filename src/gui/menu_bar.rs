@@ -1,4 +1,4 @@
-use super::components::puzzle_type_menu;
+use super::components::{prefs, puzzle_type_menu};
 use super::ext::ResponseExt;
 use super::windows;
 use crate::app::App;
@@ -6,67 +6,133 @@ use crate::commands::Command;
 
 pub fn build(ui: &mut egui::Ui, app: &mut App) {
     egui::menu::bar(ui, |ui| {
-        ui.menu_button("File", |ui| {
+        ui.menu_button(tr!("menu-file"), |ui| {
             #[cfg(not(target_arch = "wasm32"))]
-            command_button(ui, app, "Open...", Command::Open);
-            command_button(ui, app, "Open from clipboard", Command::PasteLog);
+            command_button(ui, app, &tr!("menu-file-open"), Command::Open);
+            command_button(ui, app, &tr!("menu-file-open-clipboard"), Command::PasteLog);
             ui.separator();
             #[cfg(not(target_arch = "wasm32"))]
             {
-                command_button(ui, app, "Save", Command::Save);
-                command_button(ui, app, "Save as...", Command::SaveAs);
+                command_button(ui, app, &tr!("menu-file-save"), Command::Save);
+                command_button(ui, app, &tr!("menu-file-save-as"), Command::SaveAs);
+                command_button(ui, app, &tr!("menu-file-screenshot"), Command::Screenshot);
                 ui.separator();
             }
             command_button_with_explanation(
                 ui,
                 app,
-                "Copy (.hsc)",
+                &tr!("menu-file-copy-hsc"),
                 Command::CopyHscLog,
-                "Hyperspeedcube log file (recommended)",
-                "Includes extra metadata such as move count",
+                &tr!("menu-file-copy-hsc-title"),
+                &tr!("menu-file-copy-hsc-description"),
             );
             command_button_with_explanation(
                 ui,
                 app,
-                "Copy (.log)",
+                &tr!("menu-file-copy-mc4d"),
                 Command::CopyMc4dLog,
-                "MC4D-compatible log file",
-                "Backwards-compatible with Magic Cube 4D",
+                &tr!("menu-file-copy-mc4d-title"),
+                &tr!("menu-file-copy-mc4d-description"),
             );
 
             #[cfg(not(target_arch = "wasm32"))]
             {
                 ui.separator();
-                command_button(ui, app, "Exit", Command::Exit);
+                command_button(ui, app, &tr!("menu-file-exit"), Command::Exit);
             }
         });
 
-        ui.menu_button("Edit", |ui| {
+        ui.menu_button(tr!("menu-edit"), |ui| {
             ui.add_enabled_ui(app.puzzle.has_undo(), |ui| {
-                command_button(ui, app, "Undo twist", Command::Undo);
+                command_button(ui, app, &tr!("menu-edit-undo"), Command::Undo);
             });
             ui.add_enabled_ui(app.puzzle.has_redo(), |ui| {
-                command_button(ui, app, "Redo twist", Command::Redo);
+                command_button(ui, app, &tr!("menu-edit-redo"), Command::Redo);
             });
             ui.separator();
-            command_button(ui, app, "Reset puzzle", Command::Reset);
+            command_button(
+                ui,
+                app,
+                &tr!("menu-edit-add-checkpoint"),
+                Command::AddCheckpoint,
+            );
+            ui.add_enabled_ui(!app.puzzle.checkpoints().is_empty(), |ui| {
+                command_button(
+                    ui,
+                    app,
+                    &tr!("menu-edit-revert-checkpoint"),
+                    Command::RevertToCheckpoint,
+                );
+            });
+            ui.separator();
+            let explore_label = if app.puzzle.is_exploring() {
+                tr!("menu-edit-discard-exploration")
+            } else {
+                tr!("menu-edit-explore")
+            };
+            command_button(ui, app, &explore_label, Command::ToggleExplore);
+            ui.add_enabled_ui(app.puzzle.is_exploring(), |ui| {
+                command_button(
+                    ui,
+                    app,
+                    &tr!("menu-edit-keep-exploration"),
+                    Command::KeepExploration,
+                );
+            });
+            ui.separator();
+            ui.add_enabled_ui(app.puzzle.twist_queue_len() > 0, |ui| {
+                command_button(
+                    ui,
+                    app,
+                    &tr!("menu-edit-flush-queue"),
+                    Command::FlushTwistQueue,
+                );
+            });
+            let pause_label = if app.puzzle.is_twist_queue_paused() {
+                tr!("menu-edit-resume-queue")
+            } else {
+                tr!("menu-edit-pause-queue")
+            };
+            command_button(ui, app, &pause_label, Command::ToggleTwistQueuePaused);
+            ui.add_enabled_ui(app.puzzle.twist_queue_len() > 1, |ui| {
+                command_button(
+                    ui,
+                    app,
+                    &tr!("menu-edit-clear-queue"),
+                    Command::ClearTwistQueue,
+                );
+            });
+            ui.separator();
+            command_button(ui, app, &tr!("menu-edit-reset"), Command::Reset);
         });
 
-        ui.menu_button("Scramble", |ui| {
+        ui.menu_button(tr!("menu-scramble"), |ui| {
             for n in 1..=8 {
                 command_button(ui, app, &n.to_string(), Command::ScrambleN(n));
             }
             ui.separator();
-            command_button(ui, app, "Full", Command::ScrambleFull);
+            command_button(ui, app, &tr!("menu-scramble-full"), Command::ScrambleFull);
+            command_button(ui, app, &tr!("menu-scramble-next"), Command::NextScramble);
+            command_button(ui, app, &tr!("menu-scramble-redo"), Command::RedoScramble);
+            command_button(
+                ui,
+                app,
+                &tr!("menu-scramble-daily"),
+                Command::DailyChallenge,
+            );
+            ui.separator();
+            ui.menu_button(tr!("menu-scramble-settings"), |ui| {
+                prefs::build_scramble_section(ui, app);
+            });
         });
 
-        ui.menu_button("Puzzle", |ui| {
+        ui.menu_button(tr!("menu-puzzle"), |ui| {
             if let Some(ty) = puzzle_type_menu(ui) {
                 app.event(Command::NewPuzzle(ty));
             }
         });
 
-        ui.menu_button("Settings", |ui| {
+        ui.menu_button(tr!("menu-settings"), |ui| {
             windows::APPEARANCE_SETTINGS.menu_button_toggle(ui);
             windows::INTERACTION_SETTINGS.menu_button_toggle(ui);
             windows::VIEW_SETTINGS.menu_button_toggle(ui);
@@ -81,22 +147,62 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
                 app.prefs.needs_save |= ui
                     .checkbox(
                         &mut app.prefs.use_clipboard_fallback,
-                        "Use clipboard fallback",
+                        tr!("menu-settings-clipboard-fallback"),
                     )
                     .changed();
             }
         });
 
-        ui.menu_button("Tools", |ui| {
+        ui.menu_button(tr!("menu-tools"), |ui| {
+            command_button(
+                ui,
+                app,
+                &tr!("menu-tools-mini-mode"),
+                Command::ToggleMiniMode,
+            );
+            ui.separator();
             windows::PIECE_FILTERS.menu_button_toggle(ui);
             windows::PUZZLE_CONTROLS.menu_button_toggle(ui);
             windows::KEYBIND_SETS.menu_button_toggle(ui);
             windows::MODIFIER_KEYS.menu_button_toggle(ui);
             windows::TIMER.menu_button_toggle(ui);
+            windows::STATS.menu_button_toggle(ui);
+            windows::SOLVE_HISTORY.menu_button_toggle(ui);
+            windows::METRONOME.menu_button_toggle(ui);
+            windows::RELAY.menu_button_toggle(ui);
+            windows::SCRAMBLE_QUEUE.menu_button_toggle(ui);
+            windows::MOVE_COUNT.menu_button_toggle(ui);
+            windows::PROGRESS.menu_button_toggle(ui);
+            windows::FMC.menu_button_toggle(ui);
+            windows::HINTS.menu_button_toggle(ui);
+            windows::HISTORY_TREE.menu_button_toggle(ui);
+            windows::CAMERA_KEYFRAMES.menu_button_toggle(ui);
+            #[cfg(not(target_arch = "wasm32"))]
+            windows::RACE.menu_button_toggle(ui);
+            #[cfg(not(target_arch = "wasm32"))]
+            windows::SPECTATE.menu_button_toggle(ui);
+            #[cfg(not(target_arch = "wasm32"))]
+            windows::SCRIPTS.menu_button_toggle(ui);
+            #[cfg(not(target_arch = "wasm32"))]
+            windows::SYNC.menu_button_toggle(ui);
+            #[cfg(not(target_arch = "wasm32"))]
+            windows::API.menu_button_toggle(ui);
         });
 
-        ui.menu_button("Help", |ui| {
+        ui.menu_button(tr!("menu-help"), |ui| {
+            command_button(
+                ui,
+                app,
+                &tr!("menu-help-command-palette"),
+                Command::ToggleCommandPalette,
+            );
             windows::KEYBINDS_REFERENCE.menu_button_toggle(ui);
+            windows::NOTIFICATIONS.menu_button_toggle(ui);
+            windows::LOG_CONSOLE.menu_button_toggle(ui);
+            if ui.button(tr!("menu-help-tutorial")).clicked() {
+                ui.close_menu();
+                app.tutorial.start();
+            }
             ui.separator();
             windows::WELCOME.menu_button_toggle(ui);
             windows::ABOUT.menu_button_toggle(ui);
@@ -106,7 +212,7 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
 
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
             #[cfg(target_arch = "wasm32")]
-            ui.hyperlink_to("Download the full version", env!("CARGO_PKG_HOMEPAGE"));
+            ui.hyperlink_to(tr!("menu-download"), env!("CARGO_PKG_HOMEPAGE"));
 
             egui::warn_if_debug_build(ui);
         });
@@ -121,7 +227,7 @@ fn command_button(ui: &mut egui::Ui, app: &mut App, text: &str, command: Command
         .iter()
         .find(|keybind| keybind.command == command);
     if let Some(keybind) = matching_keybind {
-        button = button.shortcut_text(keybind.key.to_string());
+        button = button.shortcut_text(keybind.key_string());
     }
     if ui.add(button).clicked() {
         ui.close_menu();
@@ -144,7 +250,7 @@ fn command_button_with_explanation(
         .iter()
         .find(|keybind| keybind.command == command);
     if let Some(keybind) = matching_keybind {
-        button = button.shortcut_text(keybind.key.to_string());
+        button = button.shortcut_text(keybind.key_string());
     }
     let r = ui.add(button);
     if r.clicked() {
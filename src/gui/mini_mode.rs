@@ -0,0 +1,64 @@
+//! Compact layout that shows only the timer and scramble, for keeping the
+//! window in a corner of the screen while practicing on a physical puzzle.
+
+use itertools::Itertools;
+
+use crate::app::App;
+use crate::commands::Command;
+use crate::puzzle::traits::*;
+
+/// Size of the timer text.
+const TIMER_TEXT_SIZE: f32 = 40.0;
+/// Space around the contents of the window.
+const MARGIN: f32 = 8.0;
+
+/// Returns whether the window background should be see-through.
+pub(crate) fn is_transparent(app: &App) -> bool {
+    cfg!(not(target_arch = "wasm32")) && app.mini_mode && app.prefs.mini_mode.transparent
+}
+
+pub(super) fn build(ctx: &egui::Context, app: &mut App) {
+    let mut background = app.prefs.colors.background;
+    if is_transparent(app) {
+        let [r, g, b, _] = background.to_array();
+        let a = (app.prefs.mini_mode.background_opacity * 255.0) as u8;
+        background = egui::Color32::from_rgba_unmultiplied(r, g, b, a);
+    }
+
+    egui::CentralPanel::default()
+        .frame(egui::Frame::none().fill(background).inner_margin(MARGIN))
+        .show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(
+                    egui::RichText::new(app.timer.display_str())
+                        .size(TIMER_TEXT_SIZE)
+                        .monospace(),
+                );
+
+                let scramble = app.puzzle.scramble();
+                if scramble.is_empty() {
+                    ui.weak("Not scrambled");
+                } else {
+                    let notation = app.puzzle.notation_scheme();
+                    let text = scramble
+                        .iter()
+                        .map(|&twist| notation.twist_to_string(twist))
+                        .join(" ");
+                    ui.add(egui::Label::new(egui::RichText::new(text).strong()).wrap(true));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Next scramble")
+                        .on_hover_text("Scramble the puzzle with the upcoming scramble")
+                        .clicked()
+                    {
+                        app.event(Command::NextScramble);
+                    }
+                    if ui.button("Exit mini mode").clicked() {
+                        app.event(Command::ToggleMiniMode);
+                    }
+                });
+            });
+        });
+}
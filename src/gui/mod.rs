@@ -7,18 +7,43 @@ macro_rules! unique_id {
 #[macro_use]
 mod util;
 mod components;
+mod dock;
 mod ext;
 mod key_combo_popup;
 mod menu_bar;
+mod mini_mode;
 mod puzzle_view;
 mod side_bar;
 mod status_bar;
+mod toasts;
+mod tutorial;
 pub(super) mod windows;
 
 use crate::app::App;
+pub(crate) use components::{MAX_SCALE, MIN_SCALE};
 pub(super) use key_combo_popup::{key_combo_popup_captures_event, key_combo_popup_handle_event};
+pub(super) use mini_mode::is_transparent as is_window_transparent;
+pub(crate) use toasts::{Severity, Toasts};
+pub(crate) use tutorial::{Tutorial, TutorialEvent};
+
+/// Returns the visuals for a dark or light theme with an accent color.
+pub fn theme_visuals(dark: bool, accent: egui::Color32) -> egui::Visuals {
+    let mut visuals = if dark {
+        egui::Visuals::dark()
+    } else {
+        egui::Visuals::light()
+    };
+    visuals.selection.bg_fill = accent;
+    visuals
+}
 
 pub fn build(ctx: &egui::Context, app: &mut App, puzzle_texture_id: egui::TextureId) {
+    if app.mini_mode {
+        mini_mode::build(ctx, app);
+        toasts::build(ctx, app);
+        return;
+    }
+
     egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| menu_bar::build(ui, app));
 
     egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| status_bar::build(ui, app));
@@ -29,16 +54,38 @@ pub fn build(ctx: &egui::Context, app: &mut App, puzzle_texture_id: egui::Textur
         }
     }
 
-    egui::CentralPanel::default()
-        .frame(egui::Frame::none().fill(app.prefs.colors.background))
-        .show(ctx, |ui| {
-            for window in windows::ALL {
-                if window.location == windows::Location::Floating {
-                    window.show(ui.ctx(), app);
-                }
+    // In the compact layout, docked panels are shown as overlays so that the
+    // puzzle view gets the whole window.
+    if app.prefs.layout.has_docked_panels() && !is_compact_layout(ctx, app) {
+        dock::build(ctx, app, puzzle_texture_id);
+        for window in windows::ALL {
+            if window.location == windows::Location::Floating {
+                window.show(ctx, app);
             }
-            puzzle_view::build(ui, app, puzzle_texture_id);
-        });
+        }
+    } else {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none().fill(app.prefs.colors.background))
+            .show(ctx, |ui| {
+                for window in windows::ALL {
+                    if window.location == windows::Location::Floating {
+                        window.show(ui.ctx(), app);
+                    }
+                }
+                puzzle_view::build(ui, app, puzzle_texture_id);
+            });
+    }
 
     key_combo_popup::build(ctx, app);
+
+    toasts::build(ctx, app);
+
+    tutorial::build(ctx, app);
+}
+
+/// Returns whether the main window is narrow enough that side panels and
+/// docked panels should be shown on top of the puzzle view instead of
+/// beside it.
+pub(crate) fn is_compact_layout(ctx: &egui::Context, app: &App) -> bool {
+    app.prefs.layout.is_compact(ctx.screen_rect().width())
 }
@@ -1,10 +1,40 @@
+use cgmath::{EuclideanSpace, Point2, Point3};
+use itertools::Itertools;
 use winit::event::ModifiersState;
 
 use crate::app::{App, AppEvent};
+use crate::preferences::MouseButton;
+use crate::puzzle::{
+    traits::*, ClickTwists, Face, ProjectedStickerGeometry, ProjectionType, Puzzle, PuzzleTypeEnum,
+    Sticker,
+};
 
 // experimental
 const ENABLE_CONTEXT_MENU: bool = false;
 
+/// Scroll distance (in points) that counts as one step of the scroll wheel.
+const SCROLL_STEP: f32 = 50.0;
+
+/// Fraction of the time between metronome beats during which the border of
+/// the puzzle view is highlighted.
+const METRONOME_FLASH_FRACTION: f32 = 0.2;
+
+/// Font size of face labels.
+const FACE_LABEL_SIZE: f32 = 16.0;
+/// Space around the text of a face label.
+const FACE_LABEL_PADDING: f32 = 3.0;
+
+/// Number of times per second that the highlighted cell pulses.
+const CELL_HIGHLIGHT_PULSE_RATE: f64 = 1.5;
+/// Maximum opacity of the highlight over stickers in the hovered cell.
+const CELL_HIGHLIGHT_OPACITY: f32 = 0.5;
+/// Axis arrows shorter than this (in points) are not drawn, because the axis
+/// points almost directly toward the camera.
+const MIN_AXIS_ARROW_LENGTH: f32 = 16.0;
+/// Names of the axes of a 4D Rubik's cube, whose faces are listed in pairs
+/// with the positive face first.
+const AXIS_NAMES_4D: [&str; 4] = ["X", "Y", "Z", "W"];
+
 pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureId) {
     let dpi = ui.ctx().pixels_per_point();
 
@@ -39,6 +69,19 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
         cgmath::point2(p.x * 2.0 - 1.0, 1.0 - p.y * 2.0)
     });
 
+    if app.prefs.gfx.show_perf_hud {
+        build_perf_hud(ui, app, egui_rect);
+    }
+    if app.prefs.metronome.flash {
+        build_metronome_flash(ui, app, egui_rect);
+    }
+    if app.puzzle.view_prefs(&app.prefs).face_labels {
+        build_face_labels(ui, app, egui_rect);
+    }
+    if app.puzzle.ty().projection_type() == ProjectionType::_4D {
+        build_guides(ui, app, egui_rect);
+    }
+
     let popup_state_id = egui::Id::new("puzzle_context_menu_state");
     let mut popup_was_open = ui.data().get_temp(popup_state_id).unwrap_or(false);
     if popup_was_open || app.pressed_modifiers() == ModifiersState::SHIFT {
@@ -46,7 +89,7 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
         if ENABLE_CONTEXT_MENU {
             r = r.context_menu(|ui| {
                 if !popup_was_open && app.puzzle.hovered_sticker().is_some() {
-                    app.event(AppEvent::Click(egui::PointerButton::Secondary));
+                    app.event(AppEvent::Click(MouseButton::Right));
                 }
                 ui.data().insert_temp(popup_state_id, true);
                 popup_was_open |= true;
@@ -59,17 +102,41 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
         return; // Ignore click and drag events while the popup is open.
     }
 
+    if is_using_touch(ui) {
+        handle_touch_input(ui, app, &r, egui_rect);
+        return;
+    }
+
     // Submit click events.
     for button in [
         egui::PointerButton::Primary,
         egui::PointerButton::Secondary,
         egui::PointerButton::Middle,
+        egui::PointerButton::Extra1,
+        egui::PointerButton::Extra2,
     ] {
         if r.clicked_by(button) {
-            app.event(AppEvent::Click(button))
+            app.event(AppEvent::Click(button.into()))
         }
     }
 
+    // Submit scroll events, one for each step of the scroll wheel.
+    if r.hovered() {
+        let scroll_id = unique_id!();
+        let mut scroll = ui.data().get_temp::<f32>(scroll_id).unwrap_or(0.0);
+        scroll += ui.input().scroll_delta.y;
+        while scroll.abs() >= SCROLL_STEP {
+            if scroll > 0.0 {
+                app.event(AppEvent::Click(MouseButton::ScrollUp));
+                scroll -= SCROLL_STEP;
+            } else {
+                app.event(AppEvent::Click(MouseButton::ScrollDown));
+                scroll += SCROLL_STEP;
+            }
+        }
+        ui.data().insert_temp(scroll_id, scroll);
+    }
+
     // Submit drag events.
     if r.dragged() {
         app.event(AppEvent::Drag(r.drag_delta() / egui_rect.size().min_elem()))
@@ -78,18 +145,376 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
         app.event(AppEvent::DragReleased);
     }
 
-    // Show debug info for each sticker.
-    #[cfg(debug_assertions)]
     if let Some(sticker) = app.puzzle.hovered_sticker() {
-        use crate::puzzle::traits::*;
-
-        let mut s = String::new();
-        app.puzzle.displayed().sticker_debug_info(&mut s, sticker);
-        if !s.is_empty() {
-            egui::popup::show_tooltip_at_pointer(
-                ui.ctx(),
-                egui::Id::new("sticker_debug_info"),
-                |ui| ui.label(s),
+        build_sticker_tooltip(ui, app, sticker);
+    }
+}
+
+/// Returns whether the user is interacting using a touchscreen rather than a
+/// mouse. This stays true after the last finger is lifted, until the mouse
+/// moves.
+fn is_using_touch(ui: &egui::Ui) -> bool {
+    let touch_id = unique_id!();
+    let (any_touches, mouse_moved) = {
+        let input = ui.input();
+        // Touches never move the pointer without pressing it.
+        let mouse_moved = input.pointer.is_moving() && !input.pointer.any_down();
+        (input.any_touches(), mouse_moved)
+    };
+    let mut using_touch = ui.data().get_temp(touch_id).unwrap_or(false);
+    if any_touches {
+        using_touch = true;
+    } else if mouse_moved {
+        using_touch = false;
+    }
+    ui.data().insert_temp(touch_id, using_touch);
+    using_touch
+}
+
+/// Swipe that started on a sticker.
+#[derive(Debug, Copy, Clone)]
+struct Swipe {
+    twists: ClickTwists,
+    /// Whether the swipe started on a back face seen through a transparent
+    /// sticker, which appears mirrored.
+    is_back: bool,
+    start: egui::Pos2,
+    end: egui::Pos2,
+}
+
+/// Handles touch gestures on the puzzle view: tapping a sticker grips its
+/// axis, swiping from a sticker twists it, dragging elsewhere or with two
+/// fingers rotates the view, and pinching zooms.
+fn handle_touch_input(ui: &egui::Ui, app: &mut App, r: &egui::Response, egui_rect: egui::Rect) {
+    let size = egui_rect.size().min_elem();
+    let multi_touch_id = unique_id!();
+    let swipe_id = unique_id!();
+
+    let multi_touch = ui.input().multi_touch();
+    if let Some(multi_touch) = multi_touch {
+        if multi_touch.zoom_delta != 1.0 {
+            app.event(AppEvent::Zoom(multi_touch.zoom_delta));
+        }
+        app.event(AppEvent::Drag(multi_touch.translation_delta / size));
+        // A second finger cancels any swipe in progress.
+        ui.data().remove::<Swipe>(swipe_id);
+        ui.data().insert_temp(multi_touch_id, true);
+        return;
+    }
+    if ui.data().get_temp(multi_touch_id).unwrap_or(false) {
+        ui.data().insert_temp(multi_touch_id, false);
+        app.event(AppEvent::DragReleased);
+        return;
+    }
+
+    if r.clicked() {
+        app.event(AppEvent::TouchTap);
+    }
+
+    let pointer_pos = ui.input().pointer.interact_pos();
+    if r.drag_started() {
+        let start = ui.input().pointer.press_origin().or(pointer_pos);
+        match app.puzzle.hovered_twists().zip(start) {
+            Some((twists, start)) => {
+                let swipe = Swipe {
+                    twists,
+                    is_back: app.puzzle.hovered_polygon().map_or(false, |p| p.is_back),
+                    start,
+                    end: start,
+                };
+                ui.data().insert_temp(swipe_id, swipe);
+            }
+            None => ui.data().remove::<Swipe>(swipe_id),
+        }
+    }
+
+    let swipe = ui.data().get_temp::<Swipe>(swipe_id);
+    match swipe {
+        // Swipes are applied once the finger is lifted.
+        Some(mut swipe) => {
+            if let Some(pos) = pointer_pos {
+                swipe.end = pos;
+                ui.data().insert_temp(swipe_id, swipe);
+            }
+            if r.drag_released() {
+                ui.data().remove::<Swipe>(swipe_id);
+                // Whether the swipe goes clockwise around the center of the
+                // puzzle view. The Y axis points down in egui.
+                let from_center = swipe.start - egui_rect.center();
+                let delta = swipe.end - swipe.start;
+                let clockwise = from_center.x * delta.y - from_center.y * delta.x > 0.0;
+                let clockwise = clockwise != swipe.is_back;
+                app.event(AppEvent::TouchSwipe {
+                    twists: swipe.twists,
+                    clockwise,
+                });
+            }
+        }
+        None => {
+            if r.dragged() {
+                app.event(AppEvent::Drag(r.drag_delta() / size));
+            }
+            if r.drag_released() {
+                app.event(AppEvent::DragReleased);
+            }
+        }
+    }
+}
+
+/// Shows information about the hovered sticker, plus debug info in debug
+/// builds.
+fn build_sticker_tooltip(ui: &mut egui::Ui, app: &App, sticker: Sticker) {
+    let puzzle = app.puzzle.displayed();
+
+    #[allow(unused_mut)]
+    let mut debug_info = String::new();
+    #[cfg(debug_assertions)]
+    puzzle.sticker_debug_info(&mut debug_info, sticker);
+
+    let show_info = app.prefs.info.sticker_tooltips;
+    if !show_info && debug_info.is_empty() {
+        return;
+    }
+
+    egui::popup::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("sticker_tooltip"), |ui| {
+        if show_info {
+            let sticker_info = puzzle.info(sticker);
+            let piece_info = puzzle.info(sticker_info.piece);
+            egui::Grid::new(unique_id!()).num_columns(2).show(ui, |ui| {
+                let color = puzzle.info(sticker_info.color);
+                ui.label("Color:");
+                ui.label(format!("{} ({})", color.name, color.symbol));
+                ui.end_row();
+
+                if let Some(facet) = puzzle.sticker_facet(sticker) {
+                    let facet = puzzle.info(facet);
+                    ui.label("Face:");
+                    ui.label(format!("{} ({})", facet.name, facet.symbol));
+                    ui.end_row();
+                }
+
+                ui.label("Piece:");
+                ui.label(format!(
+                    "{} ({}C)",
+                    puzzle.info(piece_info.piece_type).name,
+                    piece_info.stickers.len(),
+                ));
+                ui.end_row();
+
+                if let Some(coords) = puzzle.piece_coordinates(sticker_info.piece) {
+                    ui.label("Position:");
+                    ui.label(format!("({})", coords.iter().join(", ")));
+                    ui.end_row();
+                }
+
+                let (depth, stack_size) = app.puzzle.hover_depth();
+                if stack_size > 1 {
+                    ui.label("Depth:");
+                    ui.label(format!("{} of {stack_size}", depth + 1));
+                    ui.end_row();
+                }
+            });
+        }
+        if !debug_info.is_empty() {
+            if show_info {
+                ui.separator();
+            }
+            ui.label(debug_info);
+        }
+    });
+}
+
+fn build_perf_hud(ui: &mut egui::Ui, app: &App, puzzle_rect: egui::Rect) {
+    let timings = app.render_cache.timings;
+    let stages = [
+        ("Geometry", timings.geometry),
+        ("Sort", timings.sort),
+        ("Upload", timings.upload),
+        ("UI", timings.ui),
+    ];
+
+    egui::Area::new(unique_id!())
+        .fixed_pos(puzzle_rect.min + egui::vec2(8.0, 8.0))
+        .interactable(false)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                egui::Grid::new(unique_id!()).num_columns(2).show(ui, |ui| {
+                    ui.label("FPS");
+                    ui.monospace(app.fps().to_string());
+                    ui.end_row();
+
+                    for (name, duration) in stages {
+                        ui.label(name);
+                        let ms = duration.get().as_secs_f64() * 1000.0;
+                        ui.monospace(format!("{ms:.2} ms"));
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+}
+
+fn build_metronome_flash(ui: &mut egui::Ui, app: &App, puzzle_rect: egui::Rect) {
+    let Some(phase) = app.metronome.beat_phase(&app.prefs.metronome) else {
+        return;
+    };
+    if phase < METRONOME_FLASH_FRACTION {
+        let strength = 1.0 - phase / METRONOME_FLASH_FRACTION;
+        let color = ui.visuals().selection.bg_fill.linear_multiply(strength);
+        ui.painter()
+            .rect_stroke(puzzle_rect.shrink(2.0), 0.0, (4.0, color));
+    }
+}
+
+/// Converts a point on the puzzle geometry drawn last frame to a position in
+/// the puzzle view.
+fn geometry_point_to_egui(
+    app: &App,
+    puzzle_rect: egui::Rect,
+    point: Point2<f32>,
+) -> Option<egui::Pos2> {
+    let p = app.render_cache.geometry_point_to_target(point)?;
+    let x = (p.x + 1.0) / 2.0 * puzzle_rect.width();
+    let y = (1.0 - p.y) / 2.0 * puzzle_rect.height();
+    Some(puzzle_rect.min + egui::vec2(x, y))
+}
+
+/// Returns the center of each facet of the puzzle, averaged from the vertices
+/// of its visible stickers.
+fn facet_centers(
+    puzzle: &Puzzle,
+    geometry: &[ProjectedStickerGeometry],
+) -> Vec<Option<Point3<f32>>> {
+    let mut sums = vec![(cgmath::vec3(0.0, 0.0, 0.0), 0_usize); puzzle.faces().len()];
+    for geom in geometry {
+        let Some(Face(i)) = puzzle.sticker_facet(geom.sticker) else {
+            continue;
+        };
+        let (sum, count) = &mut sums[i as usize];
+        for v in &*geom.verts {
+            *sum += v.to_vec();
+        }
+        *count += geom.verts.len();
+    }
+    sums.into_iter()
+        .map(|(sum, count)| match count {
+            0 => None,
+            _ => Some(Point3::from_vec(sum / count as f32)),
+        })
+        .collect()
+}
+
+/// Draws a label near the center of each face, hidden when it is behind
+/// stickers on other faces.
+fn build_face_labels(ui: &mut egui::Ui, app: &App, puzzle_rect: egui::Rect) {
+    let Some(geometry) = app.render_cache.last_puzzle_geometry() else {
+        return;
+    };
+    let puzzle = app.puzzle.displayed();
+    let painter = ui.painter().with_clip_rect(puzzle_rect);
+    let font = egui::FontId::proportional(FACE_LABEL_SIZE);
+    let text_color = ui.visuals().strong_text_color();
+    let background_color = ui.visuals().extreme_bg_color.linear_multiply(0.75);
+
+    for (i, center) in facet_centers(puzzle, &geometry).into_iter().enumerate() {
+        let face = Face(i as _);
+        let Some(center) = center else {
+            continue;
+        };
+        let point = cgmath::point2(center.x, center.y);
+        let is_occluded = geometry
+            .iter()
+            .filter(|geom| puzzle.sticker_facet(geom.sticker) != Some(face))
+            .flat_map(|geom| &*geom.front_polygons)
+            .any(|polygon| {
+                polygon.contains_point(point) && polygon.depth_at_point(point) > center.z
+            });
+        if is_occluded {
+            continue;
+        }
+        let Some(pos) = geometry_point_to_egui(app, puzzle_rect, point) else {
+            continue;
+        };
+
+        let text = app.prefs.face_label(puzzle.ty(), face).to_owned();
+        let galley = painter.layout_no_wrap(text, font.clone(), text_color);
+        let rect =
+            egui::Align2::CENTER_CENTER.anchor_rect(egui::Rect::from_min_size(pos, galley.size()));
+        painter.rect_filled(
+            rect.expand(FACE_LABEL_PADDING),
+            FACE_LABEL_PADDING,
+            background_color,
+        );
+        painter.galley(rect.min, galley);
+    }
+}
+
+/// Draws overlays that help map the 4D projection back to the cells of the
+/// puzzle.
+fn build_guides(ui: &mut egui::Ui, app: &App, puzzle_rect: egui::Rect) {
+    let guides = &app.prefs.guides;
+    if !(guides.axis_arrows || guides.highlight_cell) {
+        return;
+    }
+    let Some(geometry) = app.render_cache.last_puzzle_geometry() else {
+        return;
+    };
+    let puzzle = app.puzzle.displayed();
+    let to_egui = |p: Point2<f32>| geometry_point_to_egui(app, puzzle_rect, p);
+    let painter = ui.painter().with_clip_rect(puzzle_rect);
+
+    if guides.highlight_cell {
+        let hovered_cell = app
+            .puzzle
+            .hovered_sticker()
+            .and_then(|sticker| puzzle.sticker_facet(sticker));
+        if let Some(cell) = hovered_cell {
+            ui.ctx().request_repaint();
+            let t = ui.input().time * CELL_HIGHLIGHT_PULSE_RATE * std::f64::consts::TAU;
+            let strength = (1.0 - t.cos() as f32) / 2.0 * CELL_HIGHLIGHT_OPACITY;
+            let color = egui::Color32::WHITE.linear_multiply(strength);
+            for geom in geometry.iter() {
+                if puzzle.sticker_facet(geom.sticker) != Some(cell) {
+                    continue;
+                }
+                for polygon in &*geom.front_polygons {
+                    let points = polygon
+                        .verts
+                        .iter()
+                        .filter_map(|v| to_egui(cgmath::point2(v.x, v.y)))
+                        .collect();
+                    painter.add(egui::Shape::convex_polygon(
+                        points,
+                        color,
+                        egui::Stroke::none(),
+                    ));
+                }
+            }
+        }
+    }
+
+    if guides.axis_arrows && matches!(puzzle.ty(), PuzzleTypeEnum::Rubiks4D { .. }) {
+        let cell_centers = facet_centers(puzzle, &geometry)
+            .into_iter()
+            .map(|center| to_egui(cgmath::point2(center?.x, center?.y)))
+            .collect_vec();
+        let font = egui::FontId::proportional(16.0);
+        let stroke = egui::Stroke::new(2.0, ui.visuals().selection.bg_fill);
+        for (axis_name, pair) in AXIS_NAMES_4D.iter().zip(cell_centers.chunks(2)) {
+            let &[Some(pos), Some(neg)] = pair else {
+                continue;
+            };
+            let dir = pos - neg;
+            if dir.length() < MIN_AXIS_ARROW_LENGTH {
+                continue;
+            }
+            painter.arrow(neg, dir, stroke);
+            painter.text(
+                pos + dir.normalized() * font.size,
+                egui::Align2::CENTER_CENTER,
+                format!("+{axis_name}"),
+                font.clone(),
+                stroke.color,
             );
         }
     }
@@ -1,16 +1,41 @@
+/// Maximum fraction of the width of the screen that the side bar covers in
+/// the compact layout.
+const OVERLAY_MAX_FRACTION: f32 = 0.6;
+
+/// Builds a side bar on the left of the main window. In the compact layout
+/// (`is_overlay`), the side bar is drawn on top of the puzzle view instead of
+/// taking space from it.
 pub fn build(
     ctx: &egui::Context,
     title: &str,
     is_open: &mut bool,
+    is_overlay: bool,
     add_contents: impl FnOnce(&mut egui::Ui),
 ) {
     if !*is_open {
         return;
     }
 
-    egui::SidePanel::left(unique_id!(title)).show(ctx, |ui| {
-        ui.style_mut().wrap = Some(false);
+    if is_overlay {
+        let screen_width = ctx.available_rect().width();
+        egui::Window::new(title)
+            .id(unique_id!(title))
+            .open(is_open)
+            .anchor(egui::Align2::LEFT_TOP, [0.0, 0.0])
+            .collapsible(false)
+            .resizable(false)
+            .vscroll(true)
+            .default_width(screen_width * OVERLAY_MAX_FRACTION)
+            .show(ctx, |ui| {
+                // Wrap text instead of covering the whole puzzle.
+                ui.style_mut().wrap = Some(true);
+                ui.set_max_width(screen_width * OVERLAY_MAX_FRACTION);
+                add_contents(ui);
+            });
+        return;
+    }
 
+    egui::SidePanel::left(unique_id!(title)).show(ctx, |ui| {
         let heading_response = ui.heading(title);
 
         add_contents(ui);
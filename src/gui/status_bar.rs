@@ -1,12 +1,17 @@
+use itertools::Itertools;
 use key_names::KeyMappingCode;
 use strum::EnumMessage;
 use winit::event::VirtualKeyCode;
 
 use super::ext::*;
+use super::windows;
 use crate::app::App;
 use crate::commands::Command;
 use crate::preferences::Key;
-use crate::puzzle::TwistMetric;
+use crate::puzzle::{ScrambleState, TwistMetric};
+
+/// Maximum number of queued twists to show when hovering over the queue.
+const MAX_LISTED_QUEUED_TWISTS: usize = 20;
 
 pub fn build(ui: &mut egui::Ui, app: &mut App) {
     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -14,9 +19,32 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
         blindfold_toggle(ui, app);
         ui.separator();
 
+        inverse_toggle(ui, app);
+        ui.separator();
+
         twist_count(ui, app);
         ui.separator();
 
+        timer(ui, app);
+        ui.separator();
+
+        scramble_state(ui, app);
+        ui.separator();
+
+        if app.puzzle.is_exploring() {
+            ui.label(tr!("status-exploring"))
+                .on_hover_explanation("", &tr!("status-exploring-description"));
+            ui.separator();
+        }
+
+        twist_queue(ui, app);
+        ui.separator();
+
+        if app.prefs.gfx.show_fps {
+            ui.label(tr!("status-fps", fps = app.fps()));
+            ui.separator();
+        }
+
         // Left-aligned segments
         ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
             if app.prefs.info.modifier_toggles {
@@ -24,6 +52,11 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
                 ui.separator();
             }
 
+            if app.prefs.info.accessibility_descriptions {
+                ui.label(app.accessibility_description());
+                ui.separator();
+            }
+
             ui.label(app.status_msg());
         });
     });
@@ -71,20 +104,90 @@ pub(super) fn modifier_toggles(ui: &mut egui::Ui, app: &mut App, big: bool) {
 
 fn blindfold_toggle(ui: &mut egui::Ui, app: &mut App) {
     let r = ui
-        .selectable_label(app.prefs.colors.blindfold, "Blindfold")
-        .on_hover_explanation("Blindfold mode", "Hides sticker colors");
+        .selectable_label(app.prefs.colors.blindfold, tr!("status-blindfold"))
+        .on_hover_explanation(
+            &tr!("status-blindfold-title"),
+            &tr!("status-blindfold-description"),
+        );
     if r.clicked() {
         app.event(Command::ToggleBlindfold);
     }
 }
 
+fn inverse_toggle(ui: &mut egui::Ui, app: &mut App) {
+    let r = ui
+        .selectable_label(app.puzzle.is_inverse(), tr!("status-inverse"))
+        .on_hover_explanation(
+            &tr!("status-inverse-title"),
+            &tr!("status-inverse-description"),
+        );
+    if r.clicked() {
+        app.event(Command::ToggleInverse);
+    }
+}
+
+fn twist_queue(ui: &mut egui::Ui, app: &mut App) {
+    let notation = app.puzzle.notation_scheme();
+    let mut upcoming = app
+        .puzzle
+        .queued_twists()
+        .take(MAX_LISTED_QUEUED_TWISTS)
+        .map(|twist| notation.twist_to_string(twist))
+        .join(" ");
+    if app.puzzle.twist_queue_len() > MAX_LISTED_QUEUED_TWISTS {
+        upcoming += " ...";
+    }
+
+    let mut text = tr!("status-queue", count = app.puzzle.twist_queue_len());
+    if app.puzzle.is_twist_queue_paused() {
+        text = format!("⏸ {text}");
+    }
+    let r = ui
+        .add(egui::Label::new(text).sense(egui::Sense::click()))
+        .on_hover_explanation(&upcoming, &tr!("status-queue-description"));
+    if r.clicked() {
+        app.event(Command::ToggleTwistQueuePaused);
+    }
+    r.context_menu(|ui| {
+        for (label, command) in [
+            (tr!("menu-edit-flush-queue"), Command::FlushTwistQueue),
+            (tr!("menu-edit-clear-queue"), Command::ClearTwistQueue),
+        ] {
+            if ui.button(label).clicked() {
+                ui.close_menu();
+                app.event(command);
+            }
+        }
+    });
+}
+
+fn timer(ui: &mut egui::Ui, app: &mut App) {
+    let r = ui
+        .add(egui::Label::new(app.timer.display_str()).sense(egui::Sense::click()))
+        .on_hover_explanation("", &tr!("status-timer-description"));
+    if r.clicked() {
+        let is_open = windows::TIMER.is_open(ui.ctx());
+        windows::TIMER.set_open(ui.ctx(), !is_open);
+    }
+}
+
+fn scramble_state(ui: &mut egui::Ui, app: &mut App) {
+    let scramble_state = app.puzzle.scramble_state();
+    let mut text = egui::RichText::new(scramble_state.description());
+    if scramble_state == ScrambleState::Solved {
+        text = text.strong();
+    }
+    ui.label(text);
+}
+
 fn twist_count(ui: &mut egui::Ui, app: &mut App) {
     let mut changed = false;
 
     let metric = &mut app.prefs.info.metric;
     let twist_count = app.puzzle.twist_count(*metric);
     let r = ui
-        .add(egui::Label::new(format!("{}: {}", metric, twist_count)).sense(egui::Sense::click()));
+        .add(egui::Label::new(format!("{}: {}", metric, twist_count)).sense(egui::Sense::click()))
+        .on_hover_explanation("", "Click to choose a metric; right-click to cycle metrics");
     {
         let mut data = ui.data();
         let last_frame_metric = data.get_temp_mut_or_default(unique_id!());
@@ -167,6 +270,10 @@ fn twist_count(ui: &mut egui::Ui, app: &mut App) {
     if r.clicked() {
         ui.memory().open_popup(popup_id);
     }
+    if r.secondary_clicked() {
+        *metric = metric.next(app.prefs.info.qtm);
+        changed = true;
+    }
 
     app.prefs.needs_save |= changed;
 }
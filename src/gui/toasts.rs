@@ -0,0 +1,158 @@
+use instant::{Duration, Instant};
+
+use crate::app::App;
+
+/// How long each toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+/// How long a toast takes to fade out at the end of its duration.
+const TOAST_FADE_DURATION: Duration = Duration::from_millis(500);
+const TOAST_WIDTH: f32 = 240.0;
+/// Maximum number of notifications kept in the log. Older notifications are
+/// discarded first.
+const MAX_LOG_LEN: usize = 500;
+
+/// Queue of short-lived notifications shown in the corner of the window,
+/// along with a log of every notification since the app started.
+#[derive(Debug, Default)]
+pub(crate) struct Toasts {
+    queue: Vec<Toast>,
+    log: Vec<LogEntry>,
+}
+impl Toasts {
+    /// Adds an informational toast to the queue.
+    pub(crate) fn push(&mut self, title: impl Into<String>, message: impl Into<String>) {
+        self.push_with_severity(Severity::Info, title, message);
+    }
+    /// Adds a toast to the queue and records it in the log.
+    pub(crate) fn push_with_severity(
+        &mut self,
+        severity: Severity,
+        title: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        let title = title.into();
+        let message = message.into();
+        self.log(severity, title.clone(), message.clone());
+        // Repeated notifications, such as from holding down a key, restart
+        // the existing toast instead of stacking up.
+        if let Some(last) = self.queue.last_mut() {
+            if last.severity == severity && last.title == title && last.message == message {
+                last.created = Instant::now();
+                return;
+            }
+        }
+        self.queue.push(Toast {
+            severity,
+            title,
+            message,
+            created: Instant::now(),
+        });
+    }
+    /// Records a notification in the log without showing a toast.
+    pub(crate) fn log(
+        &mut self,
+        severity: Severity,
+        title: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        if self.log.len() >= MAX_LOG_LEN {
+            self.log.remove(0);
+        }
+        self.log.push(LogEntry {
+            severity,
+            title: title.into(),
+            message: message.into(),
+            timestamp: crate::util::unix_timestamp(),
+        });
+    }
+
+    /// Returns every notification in the log, oldest first.
+    pub(crate) fn log_entries(&self) -> &[LogEntry] {
+        &self.log
+    }
+    /// Clears the log.
+    pub(crate) fn clear_log(&mut self) {
+        self.log.clear();
+    }
+}
+
+/// How important a notification is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+impl Severity {
+    pub(crate) fn icon(self) -> &'static str {
+        match self {
+            Severity::Info => "ℹ",
+            Severity::Warning => "⚠",
+            Severity::Error => "❌",
+        }
+    }
+    pub(crate) fn color(self, visuals: &egui::Visuals) -> egui::Color32 {
+        match self {
+            Severity::Info => visuals.text_color(),
+            Severity::Warning => visuals.warn_fg_color,
+            Severity::Error => visuals.error_fg_color,
+        }
+    }
+}
+
+/// Notification recorded in the log.
+#[derive(Debug, Clone)]
+pub(crate) struct LogEntry {
+    pub(crate) severity: Severity,
+    pub(crate) title: String,
+    pub(crate) message: String,
+    /// Unix timestamp in seconds.
+    pub(crate) timestamp: i64,
+}
+
+#[derive(Debug)]
+struct Toast {
+    severity: Severity,
+    title: String,
+    message: String,
+    created: Instant,
+}
+
+pub(super) fn build(ctx: &egui::Context, app: &mut App) {
+    let queue = &mut app.toasts.queue;
+    queue.retain(|toast| toast.created.elapsed() < TOAST_DURATION);
+    if queue.is_empty() {
+        return;
+    }
+
+    egui::Area::new(unique_id!())
+        .anchor(egui::Align2::RIGHT_TOP, [-8.0, 32.0])
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            for toast in queue.iter() {
+                let remaining = TOAST_DURATION.saturating_sub(toast.created.elapsed());
+                let opacity =
+                    (remaining.as_secs_f32() / TOAST_FADE_DURATION.as_secs_f32()).clamp(0.0, 1.0);
+                egui::Frame::popup(ui.style())
+                    .multiply_with_opacity(opacity)
+                    .show(ui, |ui| {
+                        ui.set_width(TOAST_WIDTH);
+                        let title = match toast.severity {
+                            Severity::Info => egui::RichText::new(&toast.title),
+                            severity => {
+                                egui::RichText::new(format!("{} {}", severity.icon(), toast.title))
+                                    .color(severity.color(ui.visuals()))
+                            }
+                        };
+                        ui.label(title.strong());
+                        if !toast.message.is_empty() {
+                            ui.label(&toast.message);
+                        }
+                    });
+            }
+        });
+
+    // Keep repainting so that toasts fade out and disappear on time.
+    ctx.request_repaint();
+}
@@ -0,0 +1,155 @@
+use crate::app::App;
+use crate::commands::Command;
+
+const HINT_WIDTH: f32 = 320.0;
+
+/// Step of the interactive tutorial.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum TutorialStep {
+    Grip,
+    Twist,
+    Scramble,
+    Timer,
+    Done,
+}
+impl TutorialStep {
+    const ALL: [Self; 5] = [
+        Self::Grip,
+        Self::Twist,
+        Self::Scramble,
+        Self::Timer,
+        Self::Done,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Grip => "Grip a twist axis",
+            Self::Twist => "Make a twist",
+            Self::Scramble => "Scramble the puzzle",
+            Self::Timer => "Start the timer",
+            Self::Done => "You're ready!",
+        }
+    }
+
+    fn hint(self) -> &'static str {
+        match self {
+            Self::Grip => {
+                "Hold one of the grip keys listed in Help ➡ Keybinds reference to choose \
+                 which part of the puzzle to twist. Gripped pieces are highlighted."
+            }
+            Self::Twist => {
+                "Left-click a sticker to twist it clockwise, or right-click to twist it \
+                 counterclockwise. You can also press a twist key while holding a grip."
+            }
+            Self::Scramble => "Scramble the puzzle fully from the Scramble menu.",
+            Self::Timer => {
+                "The timer starts on your first twist after scrambling and stops when the \
+                 puzzle is solved. Open Tools ➡ Timer to see your times, then make a twist."
+            }
+            Self::Done => {
+                "That's everything you need to start solving. You can restart this \
+                 tutorial from Help ➡ Tutorial."
+            }
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Grip => Self::Twist,
+            Self::Twist => Self::Scramble,
+            Self::Scramble => Self::Timer,
+            Self::Timer | Self::Done => Self::Done,
+        }
+    }
+}
+
+/// Action taken by the user that may complete a step of the tutorial.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum TutorialEvent<'a> {
+    /// Some twist axis was gripped.
+    Grip,
+    /// A twist was made.
+    Twist,
+    /// The timer started.
+    TimerStarted,
+    /// A command was executed.
+    Command(&'a Command),
+}
+
+/// Guided introduction for new users, shown as a hint over the puzzle view
+/// that advances as the user does what it describes.
+#[derive(Debug, Default)]
+pub(crate) struct Tutorial {
+    step: Option<TutorialStep>,
+}
+impl Tutorial {
+    /// Starts the tutorial from the beginning.
+    pub(crate) fn start(&mut self) {
+        self.step = Some(TutorialStep::Grip);
+    }
+    /// Closes the tutorial.
+    pub(crate) fn exit(&mut self) {
+        self.step = None;
+    }
+
+    /// Advances the tutorial if the event completes the current step.
+    pub(crate) fn on_event(&mut self, event: TutorialEvent<'_>) {
+        let Some(step) = self.step else {
+            return;
+        };
+        let is_completed = match step {
+            TutorialStep::Grip => matches!(event, TutorialEvent::Grip),
+            TutorialStep::Twist => matches!(event, TutorialEvent::Twist),
+            TutorialStep::Scramble => {
+                matches!(event, TutorialEvent::Command(Command::ScrambleFull))
+            }
+            TutorialStep::Timer => matches!(event, TutorialEvent::TimerStarted),
+            TutorialStep::Done => false,
+        };
+        if is_completed {
+            self.step = Some(step.next());
+        }
+    }
+}
+
+pub(super) fn build(ctx: &egui::Context, app: &mut App) {
+    let Some(step) = app.tutorial.step else {
+        return;
+    };
+    let step_index = TutorialStep::ALL
+        .iter()
+        .position(|&s| s == step)
+        .unwrap_or(0);
+
+    egui::Area::new(unique_id!())
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -40.0])
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_width(HINT_WIDTH);
+                if step != TutorialStep::Done {
+                    ui.weak(format!(
+                        "Tutorial step {} of {}",
+                        step_index + 1,
+                        TutorialStep::ALL.len() - 1,
+                    ));
+                }
+                ui.strong(step.title());
+                ui.label(step.hint());
+                ui.horizontal(|ui| {
+                    if step == TutorialStep::Done {
+                        if ui.button("Finish").clicked() {
+                            app.tutorial.exit();
+                        }
+                    } else {
+                        if ui.button("Skip step").clicked() {
+                            app.tutorial.step = Some(step.next());
+                        }
+                        if ui.button("Exit tutorial").clicked() {
+                            app.tutorial.exit();
+                        }
+                    }
+                });
+            });
+        });
+}
@@ -0,0 +1,40 @@
+use super::Window;
+use crate::app::App;
+
+pub(crate) const API: Window = Window {
+    name: "External tools",
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 250.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    ui.label(
+        "External tools such as dashboards and bots can connect to a port on \
+         this computer to receive twists, scrambles, timer events, and solves \
+         as lines of JSON, and to send twists and scrambles.",
+    );
+    ui.separator();
+
+    let prefs = &mut app.prefs.api;
+    let mut changed = false;
+    changed |= ui.checkbox(&mut prefs.enabled, "Enable API").changed();
+    ui.horizontal(|ui| {
+        ui.label("Port");
+        changed |= ui
+            .add(egui::DragValue::new(&mut prefs.port).clamp_range(1024..=u16::MAX))
+            .changed();
+    });
+    app.prefs.needs_save |= changed;
+
+    if let Some(api) = &app.api {
+        ui.label(format!("Listening on port {}", api.port()));
+        ui.label(match api.client_count() {
+            1 => "1 tool connected".to_owned(),
+            n => format!("{n} tools connected"),
+        });
+    }
+}
@@ -0,0 +1,98 @@
+use super::Window;
+use crate::app::App;
+use crate::camera::CameraKeyframe;
+use crate::gui::components::big_icon_button;
+
+pub(crate) const CAMERA_KEYFRAMES: Window = Window {
+    name: "Camera keyframes",
+    vscroll: true,
+    build,
+    cleanup: |_, app| app.prefs.camera_path.stop(),
+    ..Window::DEFAULT
+};
+
+/// Time between a new keyframe and the previous one, in seconds.
+const DEFAULT_KEYFRAME_SPACING: f32 = 2.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let ty = app.puzzle.ty();
+
+    ui.horizontal(|ui| {
+        if big_icon_button(ui, "➕", "Add keyframe at the current view").clicked() {
+            let time = match app.prefs.camera_path.keyframes() {
+                [] => 0.0,
+                _ => app.prefs.camera_path.duration() + DEFAULT_KEYFRAME_SPACING,
+            };
+            let keyframe = CameraKeyframe::from_view(time, app.prefs.view(ty));
+            app.prefs.camera_path.add(keyframe);
+            app.prefs.needs_save = true;
+        }
+        ui.add_enabled_ui(app.prefs.camera_path.keyframes().len() >= 2, |ui| {
+            if app.prefs.camera_path.is_playing() {
+                if big_icon_button(ui, "⏹", "Stop").clicked() {
+                    app.prefs.camera_path.stop();
+                }
+            } else if big_icon_button(ui, "▶", "Play").clicked() {
+                app.prefs.camera_path.play();
+            }
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        if big_icon_button(ui, "🎞", "Export replay").clicked() {
+            app.request_replay_export();
+        }
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    ui.label(
+        "Export replay plays the rest of the solution from the redo history \
+         and saves each frame as an image in the screenshots folder.",
+    );
+
+    ui.separator();
+
+    if app.prefs.camera_path.keyframes().is_empty() {
+        ui.label("No keyframes");
+        return;
+    }
+
+    let mut go_to = None;
+    let mut update = None;
+    let mut remove = None;
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        for (i, keyframe) in app.prefs.camera_path.keyframes().iter().enumerate() {
+            let mut time = keyframe.time;
+            let r = ui.add(
+                egui::DragValue::new(&mut time)
+                    .clamp_range(0.0..=f32::MAX)
+                    .fixed_decimals(1)
+                    .speed(0.05)
+                    .suffix(" s"),
+            );
+            if r.changed() {
+                update = Some((i, CameraKeyframe { time, ..*keyframe }));
+            }
+            if big_icon_button(ui, "👁", "Go to keyframe").clicked() {
+                go_to = Some(*keyframe);
+            }
+            if big_icon_button(ui, "⟳", "Set keyframe to the current view").clicked() {
+                update = Some((i, CameraKeyframe::from_view(time, app.prefs.view(ty))));
+            }
+            if big_icon_button(ui, "🗑", "Delete keyframe").clicked() {
+                remove = Some(i);
+            }
+            ui.end_row();
+        }
+    });
+
+    if let Some(keyframe) = go_to {
+        app.prefs.camera_path.stop();
+        keyframe.apply_to_view(app.prefs.view_mut(ty));
+    }
+    if let Some((i, keyframe)) = update {
+        app.prefs.camera_path.set(i, keyframe);
+        app.prefs.needs_save = true;
+    }
+    if let Some(i) = remove {
+        app.prefs.camera_path.remove(i);
+        app.prefs.needs_save = true;
+    }
+}
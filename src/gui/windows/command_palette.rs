@@ -0,0 +1,305 @@
+use strum::IntoEnumIterator;
+
+use super::{Location, Window, ALL, COMMAND_PALETTE_WIDTH};
+use crate::app::App;
+use crate::commands::{Command, PuzzleCommand, PARTIAL_SCRAMBLE_MOVE_COUNT_MIN};
+use crate::puzzle::{
+    generic, rubiks_3d, rubiks_4d, rubiks_5d, traits::*, ProjectionType, PuzzleTypeEnum,
+};
+
+/// Maximum number of matching entries to show at once.
+const MAX_RESULTS: usize = 12;
+
+pub(crate) const COMMAND_PALETTE: Window = Window {
+    name: "Command palette",
+    location: Location::Centered,
+    fixed_width: Some(COMMAND_PALETTE_WIDTH),
+    build,
+    cleanup: |ctx, _app| {
+        ctx.data().remove::<State>(state_id());
+    },
+    ..Window::DEFAULT
+};
+
+#[derive(Debug, Default, Clone)]
+struct State {
+    query: String,
+    selected: usize,
+}
+
+fn state_id() -> egui::Id {
+    unique_id!()
+}
+
+#[derive(Clone)]
+enum Action {
+    Command(Command),
+    ToggleWindow(Window),
+    ViewPreset(String),
+    GripGroup(String),
+}
+
+struct Entry {
+    label: String,
+    keybind: Option<String>,
+    action: Action,
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let mut state: State = ui.data().get_temp(state_id()).unwrap_or_default();
+
+    let r = ui.add(
+        egui::TextEdit::singleline(&mut state.query)
+            .hint_text("Type to search commands...")
+            .desired_width(f32::INFINITY),
+    );
+    r.request_focus();
+    if r.changed() {
+        state.selected = 0;
+    }
+
+    let mut matches = all_entries(app)
+        .into_iter()
+        .filter_map(|entry| Some((fuzzy_match_score(&state.query, &entry.label)?, entry)))
+        .collect::<Vec<_>>();
+    // Stable sort, so entries with equal scores stay in their natural order.
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.truncate(MAX_RESULTS);
+
+    let (down, up, enter, escape) = {
+        let input = ui.input();
+        (
+            input.key_pressed(egui::Key::ArrowDown),
+            input.key_pressed(egui::Key::ArrowUp),
+            input.key_pressed(egui::Key::Enter),
+            input.key_pressed(egui::Key::Escape),
+        )
+    };
+    if down {
+        state.selected += 1;
+    }
+    if up {
+        state.selected = state.selected.saturating_sub(1);
+    }
+    state.selected = state.selected.min(matches.len().saturating_sub(1));
+
+    let mut chosen = None;
+    if enter {
+        chosen = matches.get(state.selected).map(|(_, entry)| entry.action.clone());
+    }
+
+    ui.separator();
+
+    if matches.is_empty() {
+        ui.label("No matching commands");
+    }
+    for (i, (_, entry)) in matches.iter().enumerate() {
+        let mut button = egui::Button::new(&entry.label).frame(i == state.selected);
+        if let Some(keybind) = &entry.keybind {
+            button = button.shortcut_text(keybind);
+        }
+        let r = ui.add_sized([ui.available_width(), 0.0], button);
+        if r.hovered() && ui.input().pointer.is_moving() {
+            state.selected = i;
+        }
+        if r.clicked() {
+            chosen = Some(entry.action.clone());
+        }
+    }
+
+    ui.data().insert_temp(state_id(), state);
+
+    if let Some(action) = chosen {
+        COMMAND_PALETTE.set_open(ui.ctx(), false);
+        match action {
+            Action::Command(command) => app.event(command),
+            Action::ToggleWindow(window) => {
+                let is_open = window.is_open(ui.ctx());
+                window.set_open(ui.ctx(), !is_open);
+            }
+            Action::ViewPreset(name) => {
+                let presets = match app.puzzle.ty().projection_type() {
+                    ProjectionType::_3D => &mut app.prefs.view_3d,
+                    ProjectionType::_4D => &mut app.prefs.view_4d,
+                };
+                if let Some(preset) = presets.presets.iter().find(|p| p.preset_name == name) {
+                    let old = std::mem::replace(&mut presets.current, preset.value.clone());
+                    app.puzzle.animate_from_view_settings(old);
+                    presets.active_preset = Some(preset.clone());
+                    app.prefs.needs_save = true;
+                }
+            }
+            Action::GripGroup(name) => {
+                let ty = app.puzzle.ty();
+                if let Some(preset) = app.prefs.grip_groups[ty]
+                    .iter()
+                    .find(|p| p.preset_name == name)
+                {
+                    app.toggle_grip = preset.value.to_grip(ty);
+                }
+            }
+        }
+    } else if escape {
+        COMMAND_PALETTE.set_open(ui.ctx(), false);
+    }
+}
+
+fn all_entries(app: &App) -> Vec<Entry> {
+    let global_keybind = |command: &Command| {
+        app.prefs
+            .global_keybinds
+            .iter()
+            .find(|keybind| keybind.command == *command)
+            .map(|keybind| keybind.key_string())
+    };
+
+    let command_entry = |command: Command| {
+        Some(Entry {
+            label: command.palette_label()?,
+            keybind: global_keybind(&command),
+            action: Action::Command(command),
+        })
+    };
+
+    // Commands with parameters are listed separately below.
+    let mut ret = Command::iter()
+        .filter(|command| !matches!(command, Command::ScrambleN(_) | Command::NewPuzzle(_)))
+        .filter(|command| cfg!(not(target_arch = "wasm32")) || !command.is_local_only())
+        .filter_map(&command_entry)
+        .collect::<Vec<_>>();
+
+    ret.extend(
+        (PARTIAL_SCRAMBLE_MOVE_COUNT_MIN..=app.prefs.scramble.partial_max)
+            .map(Command::ScrambleN)
+            .filter_map(&command_entry),
+    );
+
+    let puzzle_types = rubiks_3d::LAYER_COUNT_RANGE
+        .map(|layer_count| PuzzleTypeEnum::Rubiks3D { layer_count })
+        .chain(
+            rubiks_4d::LAYER_COUNT_RANGE.map(|layer_count| PuzzleTypeEnum::Rubiks4D { layer_count }),
+        )
+        .chain(
+            rubiks_5d::LAYER_COUNT_RANGE.map(|layer_count| PuzzleTypeEnum::Rubiks5D { layer_count }),
+        )
+        .chain([PuzzleTypeEnum::Simplex4D, PuzzleTypeEnum::Clock])
+        .chain(generic::puzzle_types());
+    ret.extend(
+        puzzle_types
+            .map(Command::NewPuzzle)
+            .filter_map(command_entry),
+    );
+
+    let view_presets = match app.puzzle.ty().projection_type() {
+        ProjectionType::_3D => &app.prefs.view_3d,
+        ProjectionType::_4D => &app.prefs.view_4d,
+    };
+    let puzzle_keybinds = &app.prefs.puzzle_keybinds[app.puzzle.ty()];
+    ret.extend(view_presets.presets.iter().map(|preset| {
+        let keybind = puzzle_keybinds
+            .get_active_keybinds()
+            .find(|keybind| {
+                matches!(
+                    &keybind.command,
+                    PuzzleCommand::ViewPreset { view_preset_name }
+                        if *view_preset_name == preset.preset_name
+                )
+            })
+            .map(|keybind| keybind.key_string());
+        Entry {
+            label: format!("View preset: {}", preset.preset_name),
+            keybind,
+            action: Action::ViewPreset(preset.preset_name.clone()),
+        }
+    }));
+    ret.extend(
+        app.prefs.grip_groups[app.puzzle.ty()]
+            .iter()
+            .map(|preset| Entry {
+                label: format!("Grip group: {}", preset.preset_name),
+                keybind: None,
+                action: Action::GripGroup(preset.preset_name.clone()),
+            }),
+    );
+
+    ret.extend(
+        ALL.iter()
+            .filter(|window| window.name != COMMAND_PALETTE.name)
+            .map(|&window| Entry {
+                label: format!("Window: {}", window.title()),
+                keybind: None,
+                action: Action::ToggleWindow(window),
+            }),
+    );
+
+    ret
+}
+
+/// Returns a score for how well `query` matches `text`, or `None` if it does
+/// not match at all. Every character of `query` must appear in `text` in
+/// order (case-insensitive), with bonuses for consecutive characters and
+/// characters at the start of a word.
+fn fuzzy_match_score(query: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut text_chars = text.chars().enumerate();
+    let mut last_match: Option<usize> = None;
+    let mut prev_char = None;
+
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        loop {
+            let (i, c) = text_chars.next()?;
+            let at_word_start = prev_char.map_or(true, |p: char| !p.is_alphanumeric());
+            prev_char = Some(c);
+            if c.to_lowercase().eq(q.to_lowercase()) {
+                score += 1;
+                if at_word_start {
+                    score += 8;
+                }
+                if last_match.map_or(false, |last| last + 1 == i) {
+                    score += 4;
+                }
+                last_match = Some(i);
+                break;
+            }
+        }
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_palette_fuzzy_match() {
+        assert_eq!(Some(0), fuzzy_match_score("", "Edit: Undo twist"));
+        assert!(fuzzy_match_score("undo", "Edit: Undo twist").is_some());
+        assert!(fuzzy_match_score("UNDO", "Edit: Undo twist").is_some());
+        assert!(fuzzy_match_score("eut", "Edit: Undo twist").is_some());
+        assert!(fuzzy_match_score("tsiwt", "Edit: Undo twist").is_none());
+        assert!(fuzzy_match_score("redo", "Edit: Undo twist").is_none());
+
+        // Word starts and consecutive characters should rank higher.
+        assert!(
+            fuzzy_match_score("undo", "Edit: Undo twist")
+                > fuzzy_match_score("undo", "Edit: Reset puzzle (un-do)")
+        );
+        assert!(
+            fuzzy_match_score("bl", "Toggle blindfold")
+                > fuzzy_match_score("bl", "Window: Keybinds reference (bl)")
+        );
+    }
+
+    #[test]
+    fn test_command_palette_covers_all_commands() {
+        for command in Command::iter() {
+            let expected = !matches!(command, Command::ToggleCommandPalette | Command::None);
+            assert_eq!(
+                expected,
+                command.palette_label().is_some(),
+                "wrong palette entry for {command:?}",
+            );
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use itertools::Itertools;
+
+use super::Window;
+use crate::app::{App, AppEvent};
+use crate::gui::components::big_icon_button;
+use crate::gui::ext::ResponseExt;
+use crate::puzzle::{NotationScheme, Twist};
+
+pub(crate) const FMC: Window = Window {
+    name: "Fewest moves",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 300.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    ui.checkbox(&mut app.fmc.enabled, "Fewest moves mode")
+        .on_hover_explanation(
+            "",
+            "When enabled, twisting the puzzle does not start the timer.",
+        );
+
+    let notation = app.puzzle.notation_scheme().clone();
+
+    ui.separator();
+    ui.strong("Scramble");
+    ui.label(twists_string(&notation, app.puzzle.scramble()));
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        let mut inverse = app.puzzle.is_inverse();
+        ui.selectable_value(&mut inverse, false, "Normal");
+        ui.selectable_value(&mut inverse, true, "Inverse")
+            .on_hover_explanation(
+                "NISS",
+                "Solve the inverse of the scramble. Twists made \
+                 on the other side are applied as premoves.",
+            );
+        if inverse != app.puzzle.is_inverse() {
+            if let Err(e) = app.puzzle.toggle_inverse() {
+                app.event(AppEvent::StatusError(e.to_owned()));
+            }
+        }
+    });
+    if !app.puzzle.premoves().is_empty() {
+        ui.label(format!(
+            "Premoves: {}",
+            twists_string(&notation, app.puzzle.premoves()),
+        ));
+    }
+
+    let solution = app.puzzle.niss_solution();
+    let count = app
+        .prefs
+        .info
+        .move_count
+        .count_twists(app.puzzle.ty(), solution.iter().copied());
+
+    ui.separator();
+    ui.strong(format!("Solution ({count} moves)"));
+    ui.label(solution_string(&notation, &solution, &app.fmc.insertions));
+    ui.horizontal(|ui| {
+        if ui.button("Copy solution").clicked() {
+            ui.output().copied_text = format!(
+                "Scramble: {}\nSolution: {} ({count} moves)",
+                twists_string(&notation, app.puzzle.scramble()),
+                twists_string(&notation, &solution),
+            );
+        }
+        if ui
+            .button("Add insertion")
+            .on_hover_text("Mark a point in the skeleton to insert twists")
+            .clicked()
+        {
+            app.fmc.insertions.push(Insertion {
+                position: solution.len(),
+                alg: String::new(),
+            });
+        }
+    });
+
+    if app.fmc.insertions.is_empty() {
+        return;
+    }
+    ui.separator();
+    ui.strong("Insertions");
+    let mut apply = None;
+    let mut remove = None;
+    egui::Grid::new(unique_id!()).num_columns(4).show(ui, |ui| {
+        for (i, insertion) in app.fmc.insertions.iter_mut().enumerate() {
+            ui.label(format!("[{}]", i + 1));
+            ui.add(egui::DragValue::new(&mut insertion.position).clamp_range(0..=solution.len()))
+                .on_hover_text("Number of twists before the insertion");
+            ui.add(
+                egui::TextEdit::singleline(&mut insertion.alg)
+                    .hint_text("Twists to insert")
+                    .desired_width(120.0),
+            );
+            ui.horizontal(|ui| {
+                if big_icon_button(ui, "✔", "Insert twists into the solution").clicked() {
+                    apply = Some(i);
+                }
+                if big_icon_button(ui, "🗑", "Delete insertion").clicked() {
+                    remove = Some(i);
+                }
+            });
+            ui.end_row();
+        }
+    });
+
+    if let Some(i) = apply {
+        let insertion = &app.fmc.insertions[i];
+        let result = insertion
+            .alg
+            .split_whitespace()
+            .map(|s| notation.parse_twist(s))
+            .collect::<Result<Vec<Twist>, String>>();
+        match result {
+            Ok(alg) => {
+                let position = insertion.position.min(solution.len());
+                let mut new_solution = solution[..position].to_vec();
+                new_solution.extend_from_slice(&alg);
+                new_solution.extend_from_slice(&solution[position..]);
+                match app.puzzle.replace_solution(&new_solution) {
+                    Ok(()) => {
+                        app.fmc.insertions.remove(i);
+                        for other in &mut app.fmc.insertions {
+                            if other.position > position {
+                                other.position += alg.len();
+                            }
+                        }
+                    }
+                    Err(e) => app.event(AppEvent::StatusError(e.to_owned())),
+                }
+            }
+            Err(e) => app.event(AppEvent::StatusError(e)),
+        }
+    } else if let Some(i) = remove {
+        app.fmc.insertions.remove(i);
+    }
+}
+
+fn twists_string(notation: &NotationScheme, twists: &[Twist]) -> String {
+    twists
+        .iter()
+        .map(|&twist| notation.twist_to_string(twist))
+        .join(" ")
+}
+
+/// Returns the solution with insertion points marked.
+fn solution_string(
+    notation: &NotationScheme,
+    solution: &[Twist],
+    insertions: &[Insertion],
+) -> String {
+    let mut words = vec![];
+    for i in 0..=solution.len() {
+        for (j, insertion) in insertions.iter().enumerate() {
+            if insertion.position == i {
+                words.push(format!("[{}]", j + 1));
+            }
+        }
+        if let Some(&twist) = solution.get(i) {
+            words.push(notation.twist_to_string(twist));
+        }
+    }
+    words.join(" ")
+}
+
+/// Fewest-moves solving state, including insertions into the skeleton
+/// solution.
+#[derive(Debug, Default)]
+pub(crate) struct Fmc {
+    /// Whether fewest moves mode is enabled, in which twists don't start the
+    /// timer.
+    pub(crate) enabled: bool,
+    /// Points in the solution at which to insert twists.
+    pub(crate) insertions: Vec<Insertion>,
+}
+
+/// Point in a skeleton solution at which to insert twists.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Insertion {
+    /// Number of twists in the solution before the insertion.
+    pub(crate) position: usize,
+    /// Twists to insert, in notation.
+    pub(crate) alg: String,
+}
@@ -0,0 +1,68 @@
+use itertools::Itertools;
+
+use super::Window;
+use crate::app::App;
+use crate::puzzle::hints::{beginner_hint, BeginnerStep, MAX_HINT_LENGTH};
+use crate::puzzle::{traits::*, Puzzle, UndoNodeId};
+
+pub(crate) const HINTS: Window = Window {
+    name: "Hints",
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 200.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    let Puzzle::Rubiks3D(puzzle) = app.puzzle.latest() else {
+        ui.label("Hints are only available for the 3x3x3.");
+        return;
+    };
+    if puzzle.layer_count() != 3 {
+        ui.label("Hints are only available for the 3x3x3.");
+        return;
+    }
+
+    ui.label("Shows twists for the next step of the beginner layer-by-layer method.");
+
+    // Remember the hint along with the state it was computed for.
+    let hint_id = unique_id!();
+    let current = app.puzzle.undo_tree().current();
+    if ui.button("Get hint").clicked() {
+        let text = match beginner_hint(puzzle) {
+            None => "No hint available".to_owned(),
+            Some(hint) => {
+                let notation = puzzle.notation_scheme();
+                let first_layer = puzzle.faces()[hint.first_layer.0 as usize].name;
+                match (hint.step, hint.twists) {
+                    (BeginnerStep::Solved, _) => "The puzzle is solved!".to_owned(),
+                    (BeginnerStep::LastLayer, _) => format!(
+                        "{}: use last layer algorithms (first layer: {first_layer})",
+                        hint.step,
+                    ),
+                    (step, None) => format!(
+                        "{step}: no hint within {MAX_HINT_LENGTH} twists \
+                         (first layer: {first_layer})",
+                    ),
+                    (step, Some(twists)) => format!(
+                        "{step}: {} (first layer: {first_layer})",
+                        twists
+                            .iter()
+                            .map(|&twist| notation.twist_to_string(twist))
+                            .join(" "),
+                    ),
+                }
+            }
+        };
+        ui.data().insert_temp(hint_id, (current, text));
+    }
+
+    let hint = ui.data().get_temp::<(UndoNodeId, String)>(hint_id);
+    if let Some((node, text)) = hint {
+        ui.separator();
+        ui.add_enabled(node == current, egui::Label::new(text).wrap(true))
+            .on_disabled_hover_text("The puzzle has changed since this hint was computed");
+    }
+}
@@ -0,0 +1,199 @@
+use super::Window;
+use crate::app::{App, AppEvent};
+use crate::gui::components::big_icon_button;
+use crate::gui::ext::ResponseExt;
+use crate::puzzle::{traits::*, NotationScheme, UndoNodeId, UndoTree};
+
+pub(crate) const HISTORY_TREE: Window = Window {
+    name: "History tree",
+    dockable: true,
+    vscroll: true,
+    build,
+    cleanup,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 250.0;
+
+fn cleanup(_ctx: &egui::Context, app: &mut App) {
+    app.puzzle.set_highlighted_undo_node(None);
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    checkpoints(ui, app);
+    ui.separator();
+    annotation(ui, app);
+    ui.separator();
+
+    let animate_id = unique_id!();
+    let mut animate = ui.data().get_temp(animate_id).unwrap_or(true);
+    ui.checkbox(&mut animate, "Animate jumps")
+        .on_hover_explanation(
+            "",
+            "When disabled, clicking a twist jumps to the \
+             state after it instantly.",
+        );
+    ui.data().insert_temp(animate_id, animate);
+
+    let tree = app.puzzle.undo_tree();
+    let notation = app.puzzle.notation_scheme();
+
+    let mut clicked = None;
+    let mut hovered = None;
+    ui.horizontal_wrapped(|ui| {
+        if ui
+            .selectable_label(tree.current() == UndoTree::ROOT, "(start)")
+            .clicked()
+        {
+            clicked = Some(UndoTree::ROOT);
+        }
+        annotation_label(ui, tree, UndoTree::ROOT);
+    });
+    build_branch(
+        ui,
+        tree,
+        notation,
+        UndoTree::ROOT,
+        &mut clicked,
+        &mut hovered,
+    );
+
+    app.puzzle.set_highlighted_undo_node(hovered);
+    if let Some(node) = clicked {
+        if let Err(e) = app.puzzle.jump_to_undo_node(node) {
+            app.event(AppEvent::StatusError(e.to_owned()));
+        }
+        if !animate {
+            app.puzzle.skip_twist_animations();
+        }
+    }
+}
+
+fn annotation(ui: &mut egui::Ui, app: &mut App) {
+    let node = app.puzzle.undo_tree().current();
+    let mut text = app.puzzle.undo_tree().annotation(node).to_owned();
+    ui.horizontal(|ui| {
+        ui.label("📝")
+            .on_hover_explanation("", "Comment on the current state, saved in the log file");
+        let r = ui.add(
+            egui::TextEdit::singleline(&mut text)
+                .hint_text("Annotation (e.g. \"start of OLL\")")
+                .desired_width(f32::INFINITY),
+        );
+        if r.changed() {
+            app.puzzle.set_annotation(node, &text);
+        }
+    });
+}
+
+fn checkpoints(ui: &mut egui::Ui, app: &mut App) {
+    let name_id = unique_id!();
+    let mut name = ui.data().get_temp::<String>(name_id).unwrap_or_default();
+    ui.horizontal(|ui| {
+        if big_icon_button(ui, "🚩", "Add checkpoint at the current state").clicked() {
+            app.puzzle.add_checkpoint(&name);
+            name.clear();
+        }
+        ui.add(
+            egui::TextEdit::singleline(&mut name)
+                .hint_text("Checkpoint name")
+                .desired_width(f32::INFINITY),
+        );
+    });
+    ui.data().insert_temp(name_id, name);
+
+    let mut revert = None;
+    let mut remove = None;
+    for (i, checkpoint) in app.puzzle.checkpoints().iter().enumerate() {
+        ui.horizontal(|ui| {
+            if big_icon_button(ui, "⮪", "Revert to this checkpoint").clicked() {
+                revert = Some(i);
+            }
+            if big_icon_button(ui, "🗑", "Delete checkpoint").clicked() {
+                remove = Some(i);
+            }
+            ui.label(&checkpoint.name);
+        });
+    }
+    if let Some(i) = revert {
+        if let Err(e) = app.puzzle.revert_to_checkpoint(i) {
+            app.event(AppEvent::StatusError(e.to_owned()));
+        }
+    }
+    if let Some(i) = remove {
+        app.puzzle.remove_checkpoint(i);
+    }
+}
+
+/// Shows the annotation on a node, if it has one.
+fn annotation_label(ui: &mut egui::Ui, tree: &UndoTree, node: UndoNodeId) {
+    let annotation = tree.annotation(node);
+    if !annotation.is_empty() {
+        ui.label(
+            egui::RichText::new(format!("// {annotation}"))
+                .italics()
+                .weak(),
+        );
+    }
+}
+
+/// Shows the line of twists that redo would follow from `start`, with other
+/// branches off that line in collapsible sections below it. Hovering over a
+/// twist highlights the pieces it moved.
+fn build_branch(
+    ui: &mut egui::Ui,
+    tree: &UndoTree,
+    notation: &NotationScheme,
+    start: UndoNodeId,
+    clicked: &mut Option<UndoNodeId>,
+    hovered: &mut Option<UndoNodeId>,
+) {
+    let line: Vec<UndoNodeId> =
+        std::iter::successors(tree.redo_child(start), |&n| tree.redo_child(n)).collect();
+
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 4.0;
+        for &node in &line {
+            let Some(entry) = tree.entry(node) else { continue };
+            let r = ui.selectable_label(tree.current() == node, entry.to_string(notation));
+            if r.clicked() {
+                *clicked = Some(node);
+            }
+            if r.hovered() {
+                *hovered = Some(node);
+            }
+            annotation_label(ui, tree, node);
+        }
+    });
+
+    // Show alternate branches that leave the line, in order.
+    let mut parent = start;
+    for (i, &node) in line.iter().enumerate() {
+        for &alt in tree.children(parent) {
+            if alt == node {
+                continue;
+            }
+            let Some(entry) = tree.entry(alt) else { continue };
+            ui.indent(unique_id!(alt), |ui| {
+                let title = format!("Branch after {} twists: {}", i, entry.to_string(notation));
+                egui::CollapsingHeader::new(title)
+                    .id_source(unique_id!(alt))
+                    .show(ui, |ui| {
+                        let r =
+                            ui.selectable_label(tree.current() == alt, entry.to_string(notation));
+                        if r.clicked() {
+                            *clicked = Some(alt);
+                        }
+                        if r.hovered() {
+                            *hovered = Some(alt);
+                        }
+                        annotation_label(ui, tree, alt);
+                        build_branch(ui, tree, notation, alt, clicked, hovered);
+                    });
+            });
+        }
+        parent = node;
+    }
+}
@@ -14,6 +14,8 @@ const MIN_KEY_PADDING: f32 = 4.0;
 
 pub(crate) const KEYBINDS_REFERENCE: Window = Window {
     name: "Keybinds reference",
+    detachable: true,
+    dockable: true,
     build,
     ..Window::DEFAULT
 };
@@ -26,6 +28,14 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
         let alpha = app.prefs.info.keybinds_reference.opacity;
         *bg_fill = bg_fill.linear_multiply(alpha);
 
+        let layer = app.active_keybind_layer();
+        if !layer.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label("Layer:");
+                ui.strong(layer);
+            });
+        }
+
         let mut areas = vec![MAIN_KEYS];
 
         if prefs.function {
@@ -156,12 +166,13 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
         app.prefs.info.keybinds_reference.max_font_size,
     );
 
-    let mut button = egui::Button::new(text).sense(egui::Sense::hover());
-    if app.pressed_keys().contains(&Key::Sc(key)) {
+    let mut button = egui::Button::new(text).sense(egui::Sense::click());
+    if app.pressed_keys().contains(&Key::Sc(key)) || app.toggled_keys().contains(&Key::Sc(key)) {
         button = button.fill(egui::Color32::DARK_GREEN);
         button = button.stroke(ui.style().noninteractive().fg_stroke);
     }
     let r = ui.put(rect, button);
+    let clicked = r.clicked();
     r.on_hover_ui(|ui| {
         ui.heading(get_key_name(key));
 
@@ -221,6 +232,14 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                     ui.strong(filter_name);
                     ui.label("preset");
                 }
+                PuzzleCommand::GripGroup {
+                    mode,
+                    grip_group_name,
+                } => {
+                    ui.label(mode.as_ref());
+                    ui.strong(grip_group_name);
+                    ui.label("grip group");
+                }
 
                 PuzzleCommand::KeybindSet { keybind_set_name } => {
                     ui.label("Switch to");
@@ -232,6 +251,11 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                     ui.strong(view_preset_name);
                     ui.label("view");
                 }
+                PuzzleCommand::Layer { layer_name } => {
+                    ui.label("Hold for");
+                    ui.strong(layer_name);
+                    ui.label("layer");
+                }
 
                 PuzzleCommand::None => unreachable!(),
             });
@@ -242,6 +266,7 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                 Command::Open => ui.label("Open"),
                 Command::Save => ui.label("Save"),
                 Command::SaveAs => ui.label("Save As"),
+                Command::Screenshot => ui.label("Screenshot"),
                 Command::Exit => ui.label("Exit"),
 
                 Command::CopyHscLog => ui.label("Copy puzzle log (.hsc)"),
@@ -251,12 +276,22 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                 Command::Undo => ui.label("Undo"),
                 Command::Redo => ui.label("Redo"),
                 Command::Reset => ui.label("Reset"),
+                Command::AddCheckpoint => ui.label("Add checkpoint"),
+                Command::RevertToCheckpoint => ui.label("Revert to last checkpoint"),
+                Command::ToggleExplore => ui.label("Toggle explore mode"),
+                Command::KeepExploration => ui.label("Keep exploration"),
+                Command::FlushTwistQueue => ui.label("Flush twist queue"),
+                Command::ToggleTwistQueuePaused => ui.label("Pause twist queue"),
+                Command::ClearTwistQueue => ui.label("Clear twist queue"),
 
                 Command::ScrambleN(n) => {
                     ui.label("Scramble");
                     ui.strong(n.to_string())
                 }
                 Command::ScrambleFull => ui.label("Scramble fully"),
+                Command::NextScramble => ui.label("Next scramble"),
+                Command::RedoScramble => ui.label("Redo last scramble"),
+                Command::DailyChallenge => ui.label("Daily challenge"),
 
                 Command::NewPuzzle(ty) => {
                     ui.label("Load new");
@@ -265,11 +300,25 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                 }
 
                 Command::ToggleBlindfold => ui.label("Toggle blindfold"),
+                Command::ToggleNetView => ui.label("Toggle net view"),
+                Command::ToggleExplodedView => ui.label("Toggle exploded view"),
+                Command::AutoFitView => ui.label("Fit puzzle to view"),
+                Command::ToggleInverse => ui.label("Toggle inverse"),
+
+                Command::MarkSplit => ui.label("Mark split"),
+                Command::ToggleTimer => ui.label("Start/stop timer"),
+
+                Command::ToggleCommandPalette => ui.label("Command palette"),
+                Command::ToggleMiniMode => ui.label("Toggle mini mode"),
 
                 Command::None => unreachable!(),
             });
         }
     });
+
+    if clicked {
+        app.click_key(key);
+    }
 }
 
 fn autosize_button_text(
@@ -0,0 +1,182 @@
+use instant::{Duration, Instant};
+
+use super::Window;
+use crate::app::App;
+use crate::gui::components::PrefsUi;
+use crate::gui::ext::ResponseExt;
+use crate::preferences::DEFAULT_PREFS;
+
+pub(crate) const LATENCY_CALIBRATION: Window = Window {
+    name: "Input latency",
+    build,
+    ..Window::DEFAULT
+};
+
+/// Time between beats during calibration.
+const BEAT_INTERVAL: Duration = Duration::from_millis(600);
+/// Number of key presses to measure.
+const TAP_COUNT: usize = 12;
+/// Number of key presses at the start to ignore while the user finds the
+/// rhythm.
+const WARMUP_TAPS: usize = 2;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.label(
+        "To measure input latency, press any key in time with \
+         the flashing circle and metronome sound.",
+    );
+
+    let button_text = match app.latency_calibration.is_running() {
+        true => "Cancel",
+        false => "Start",
+    };
+    if ui
+        .add_sized([120.0, 30.0], egui::Button::new(button_text))
+        .clicked()
+    {
+        app.latency_calibration.toggle();
+    }
+
+    let calibration = &app.latency_calibration;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(40.0, 40.0), egui::Sense::hover());
+    let is_flashing = calibration.beat_phase().map_or(false, |phase| phase < 0.15);
+    let color = match is_flashing {
+        true => ui.visuals().strong_text_color(),
+        false => ui.visuals().widgets.inactive.bg_fill,
+    };
+    ui.painter().circle_filled(rect.center(), 16.0, color);
+    if calibration.is_running() {
+        ui.label(format!(
+            "{} of {} key presses",
+            calibration.taps.len(),
+            TAP_COUNT,
+        ));
+    }
+
+    if let Some(latency) = calibration.result {
+        ui.label(format!("Measured latency: {} ms", latency.as_millis()));
+        if ui.button("Apply").clicked() {
+            app.prefs.timer.input_latency_ms = latency.as_millis() as u32;
+            app.prefs.needs_save = true;
+        }
+    }
+
+    ui.separator();
+
+    let mut changed = false;
+    PrefsUi {
+        ui,
+        current: &mut app.prefs.timer,
+        defaults: &DEFAULT_PREFS.timer,
+        changed: &mut changed,
+    }
+    .num("Timer offset (ms)", access!(.input_latency_ms), |dv| {
+        dv.clamp_range(0..=500_u32).speed(1.0)
+    })
+    .on_hover_explanation(
+        "",
+        "Time subtracted from each start or stop of the \
+         timer caused by a key press or click, to make up \
+         for slow keyboards.",
+    );
+    app.prefs.needs_save |= changed;
+}
+
+/// Tool for measuring the time between pressing a key and the app receiving
+/// it, by having the user press keys on a beat.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyCalibration {
+    /// Time of the first beat, or `None` if calibration is not running.
+    start: Option<Instant>,
+    /// Number of beats that have been ticked since calibration started.
+    beats_ticked: u64,
+    /// Times of key presses since calibration started, relative to `start`.
+    taps: Vec<Duration>,
+    /// Latency measured by the last calibration.
+    pub(crate) result: Option<Duration>,
+}
+impl LatencyCalibration {
+    pub(crate) fn is_running(&self) -> bool {
+        self.start.is_some()
+    }
+
+    pub(crate) fn toggle(&mut self) {
+        *self = Self {
+            start: match self.start {
+                Some(_) => None,
+                None => Some(Instant::now()),
+            },
+            result: self.result,
+            ..Self::default()
+        };
+    }
+
+    /// Returns `true` once for each beat that is due.
+    pub(crate) fn take_beat(&mut self) -> bool {
+        let Some(start) = self.start else {
+            return false;
+        };
+        let beats_due = start.elapsed().as_millis() as u64 / BEAT_INTERVAL.as_millis() as u64 + 1;
+        if self.beats_ticked < beats_due {
+            // If frames were skipped, don't tick several times at once.
+            self.beats_ticked = beats_due;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the time since the most recent beat, as a fraction of the time
+    /// between beats, or `None` if calibration is not running.
+    fn beat_phase(&self) -> Option<f32> {
+        let elapsed = self.start?.elapsed();
+        Some((elapsed.as_secs_f32() / BEAT_INTERVAL.as_secs_f32()).fract())
+    }
+
+    /// Records a key press, and finishes calibration once there are enough.
+    pub(crate) fn on_key_press(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+        self.taps.push(start.elapsed());
+        if self.taps.len() >= TAP_COUNT {
+            self.result = Some(estimate_latency(&self.taps[WARMUP_TAPS..]));
+            self.start = None;
+        }
+    }
+}
+
+/// Returns the median delay of key presses after the nearest beat, or zero if
+/// they tend to be early.
+fn estimate_latency(taps: &[Duration]) -> Duration {
+    let interval = BEAT_INTERVAL.as_secs_f32();
+    let mut offsets = taps
+        .iter()
+        .map(|t| {
+            let beats = t.as_secs_f32() / interval;
+            (beats - beats.round()) * interval
+        })
+        .collect::<Vec<f32>>();
+    offsets.sort_by(f32::total_cmp);
+    match offsets.get(offsets.len() / 2) {
+        Some(&median) if median > 0.0 => Duration::from_millis((median * 1000.0).round() as u64),
+        _ => Duration::ZERO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_latency() {
+        let ms = Duration::from_millis;
+        let taps = [ms(630), ms(1240), ms(1790), ms(2430), ms(3020)];
+        assert_eq!(estimate_latency(&taps).as_millis(), 30);
+
+        let early_taps = [ms(580), ms(1190), ms(1790)];
+        assert_eq!(estimate_latency(&early_taps), Duration::ZERO);
+
+        assert_eq!(estimate_latency(&[]), Duration::ZERO);
+    }
+}
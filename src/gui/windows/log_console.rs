@@ -0,0 +1,103 @@
+use super::Window;
+use crate::app::App;
+use crate::log_buffer;
+
+pub(crate) const LOG_CONSOLE: Window = Window {
+    name: "Log console",
+    dockable: true,
+    detachable: true,
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 480.0;
+const OUTPUT_HEIGHT: f32 = 320.0;
+
+fn build(ui: &mut egui::Ui, _app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    let min_level_id = unique_id!();
+    let module_filter_id = unique_id!();
+    let mut min_level = ui
+        .data()
+        .get_temp::<log::LevelFilter>(min_level_id)
+        .unwrap_or(log::LevelFilter::Trace);
+    let mut module_filter = ui
+        .data()
+        .get_temp::<String>(module_filter_id)
+        .unwrap_or_default();
+
+    let mut capture_level = log_buffer::capture_level();
+    ui.horizontal(|ui| {
+        ui.label("Record");
+        level_combo_box(ui, unique_id!(), &mut capture_level);
+        if capture_level != log_buffer::capture_level() {
+            log_buffer::set_capture_level(capture_level);
+        }
+    })
+    .response
+    .on_hover_text(
+        "Most verbose level that is recorded. \
+         Trace records are very frequent and \
+         may affect performance.",
+    );
+
+    ui.horizontal(|ui| {
+        ui.label("Show");
+        level_combo_box(ui, unique_id!(), &mut min_level);
+        ui.add(
+            egui::TextEdit::singleline(&mut module_filter)
+                .hint_text("Module")
+                .desired_width(120.0),
+        );
+    });
+
+    let records = log_buffer::records();
+    let filtered = records
+        .iter()
+        .filter(|r| r.level <= min_level && r.target.contains(module_filter.as_str()))
+        .map(|r| {
+            format!(
+                "[{:>9.3}] {:<5} {}: {}",
+                r.time.as_secs_f32(),
+                r.level,
+                r.target,
+                r.message,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    ui.horizontal(|ui| {
+        ui.label(format!("{} of {} records", filtered.len(), records.len()));
+        if ui.small_button("Copy").clicked() {
+            ui.output().copied_text = filtered.join("\n");
+        }
+        if ui.small_button("Clear").clicked() {
+            log_buffer::clear();
+        }
+    });
+
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    egui::ScrollArea::both()
+        .max_height(OUTPUT_HEIGHT)
+        .auto_shrink([false; 2])
+        .stick_to_bottom(true)
+        .show_rows(ui, row_height, filtered.len(), |ui, range| {
+            for line in &filtered[range] {
+                ui.add(egui::Label::new(egui::RichText::new(line).monospace()).wrap(false));
+            }
+        });
+
+    ui.data().insert_temp(min_level_id, min_level);
+    ui.data().insert_temp(module_filter_id, module_filter);
+}
+
+fn level_combo_box(ui: &mut egui::Ui, id: egui::Id, level: &mut log::LevelFilter) {
+    egui::ComboBox::from_id_source(id)
+        .selected_text(level.to_string())
+        .show_ui(ui, |ui| {
+            for option in log::LevelFilter::iter() {
+                ui.selectable_value(level, option, option.to_string());
+            }
+        });
+}
@@ -0,0 +1,256 @@
+use instant::{Duration, Instant};
+use itertools::Itertools;
+
+use super::Window;
+use crate::app::App;
+use crate::gui::components::PrefsUi;
+use crate::gui::ext::ResponseExt;
+use crate::preferences::{MetronomePreferences, DEFAULT_PREFS};
+
+pub(crate) const METRONOME: Window = Window {
+    name: "Metronome",
+    build,
+    ..Window::DEFAULT
+};
+
+/// Maximum number of off-beat moves to list individually.
+const MAX_LISTED_OFF_BEAT_MOVES: usize = 10;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let button_text = match app.metronome.is_running() {
+        true => "Stop",
+        false => "Start",
+    };
+    if ui
+        .add_sized([120.0, 30.0], egui::Button::new(button_text))
+        .clicked()
+    {
+        app.metronome.toggle();
+    }
+
+    ui.separator();
+
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.metronome,
+        defaults: &DEFAULT_PREFS.metronome,
+        changed: &mut changed,
+    };
+
+    prefs_ui
+        .num("Moves per second", access!(.tps), |dv| {
+            dv.fixed_decimals(1).clamp_range(0.5..=20.0_f32).speed(0.05)
+        })
+        .on_hover_explanation("", "Number of metronome beats per second.");
+    prefs_ui.checkbox("Flash on beat", access!(.flash));
+    prefs_ui
+        .checkbox("Flag off-beat moves", access!(.flag_off_beat))
+        .on_hover_explanation(
+            "",
+            "When enabled, after each solve, reports which \
+             moves were not executed on a beat.",
+        );
+    let flag_off_beat = prefs_ui.current.flag_off_beat;
+    prefs_ui.ui.add_enabled_ui(flag_off_beat, |ui| {
+        PrefsUi { ui, ..prefs_ui }
+            .percent("Tolerance", access!(.tolerance))
+            .on_hover_explanation(
+                "",
+                "Maximum distance from a beat for a move to \
+                 count as on-beat, relative to the time \
+                 between beats.",
+            );
+    });
+
+    PrefsUi {
+        ui,
+        current: &mut prefs.sounds,
+        defaults: &DEFAULT_PREFS.sounds,
+        changed: &mut changed,
+    }
+    .percent("Volume", access!(.metronome_volume));
+
+    prefs.needs_save |= changed;
+
+    let last_analysis = app.metronome.last_analysis.as_ref();
+    if let Some(analysis) = last_analysis.filter(|_| app.prefs.metronome.flag_off_beat) {
+        ui.separator();
+        ui.label(analysis.to_string());
+    }
+}
+
+/// Practice metronome for pacing moves.
+#[derive(Debug, Default)]
+pub(crate) struct Metronome {
+    /// Time of the first beat, or `None` if the metronome is stopped.
+    start: Option<Instant>,
+    /// Number of beats that have been ticked since `start`.
+    beats_ticked: u64,
+    /// Tempo that beats are being counted at, in beats per second.
+    tps: f32,
+    /// Times of non-rotation moves since the metronome started, the tempo
+    /// changed, or the last solve, relative to `start`.
+    twist_times: Vec<Duration>,
+    /// Off-beat analysis of the last solve.
+    pub(crate) last_analysis: Option<OffBeatAnalysis>,
+}
+impl Metronome {
+    pub(crate) fn is_running(&self) -> bool {
+        self.start.is_some()
+    }
+
+    pub(crate) fn toggle(&mut self) {
+        *self = Self {
+            start: match self.start {
+                Some(_) => None,
+                None => Some(Instant::now()),
+            },
+            last_analysis: self.last_analysis.take(),
+            ..Self::default()
+        };
+    }
+
+    /// Returns `true` once for each beat that is due.
+    pub(crate) fn take_beat(&mut self, prefs: &MetronomePreferences) -> bool {
+        let Some(mut start) = self.start else {
+            return false;
+        };
+        if self.tps != prefs.tps {
+            // Start counting again at the new tempo, so that the beat doesn't
+            // jump or skip.
+            start = Instant::now();
+            self.start = Some(start);
+            self.beats_ticked = 0;
+            self.tps = prefs.tps;
+            self.twist_times.clear();
+        }
+        let beats_due = (start.elapsed().as_secs_f32() * prefs.tps) as u64 + 1;
+        if self.beats_ticked < beats_due {
+            // If frames were skipped, don't tick several times at once.
+            self.beats_ticked = beats_due;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the time since the most recent beat, as a fraction of the time
+    /// between beats, or `None` if the metronome is stopped.
+    pub(crate) fn beat_phase(&self, prefs: &MetronomePreferences) -> Option<f32> {
+        let t = self.start?.elapsed().as_secs_f32() * prefs.tps;
+        Some(t.fract())
+    }
+
+    pub(crate) fn on_non_rotation_twist(&mut self) {
+        if let Some(start) = self.start {
+            self.twist_times.push(start.elapsed());
+        }
+    }
+
+    /// Analyzes the moves since the metronome started or since the last
+    /// solve, and then forgets them.
+    pub(crate) fn on_solve(&mut self, prefs: &MetronomePreferences) -> Option<&OffBeatAnalysis> {
+        if !self.is_running() {
+            return None;
+        }
+        let twist_times = std::mem::take(&mut self.twist_times);
+        self.last_analysis = Some(OffBeatAnalysis {
+            total: twist_times.len(),
+            off_beat: twist_times
+                .iter()
+                .positions(|&t| beat_offset(t, prefs.tps).abs() > prefs.tolerance)
+                .collect(),
+        });
+        self.last_analysis.as_ref()
+    }
+}
+
+/// Which moves of a solve were executed off-beat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OffBeatAnalysis {
+    /// Total number of moves.
+    pub(crate) total: usize,
+    /// Indices of moves that were off-beat.
+    pub(crate) off_beat: Vec<usize>,
+}
+impl std::fmt::Display for OffBeatAnalysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} moves off-beat",
+            self.off_beat.len(),
+            self.total
+        )?;
+        if !self.off_beat.is_empty() {
+            let listed = self.off_beat.iter().take(MAX_LISTED_OFF_BEAT_MOVES);
+            write!(f, ": {}", listed.map(|i| i + 1).join(", "))?;
+            if self.off_beat.len() > MAX_LISTED_OFF_BEAT_MOVES {
+                write!(f, ", ...")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the signed distance from time `t` to the nearest beat, as a
+/// fraction of the time between beats in the range `-0.5..0.5`.
+fn beat_offset(t: Duration, tps: f32) -> f32 {
+    let beats = t.as_secs_f32() * tps;
+    beats - beats.round()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beat_offset() {
+        let assert_offset = |ms, expected: f32| {
+            let offset = beat_offset(Duration::from_millis(ms), 2.0);
+            assert!((offset - expected).abs() < 1e-4, "{offset} != {expected}");
+        };
+        assert_offset(0, 0.0);
+        assert_offset(500, 0.0);
+        assert_offset(600, 0.2);
+        assert_offset(900, -0.2);
+        assert_offset(1240, 0.48);
+    }
+
+    #[test]
+    fn test_tempo_change_restarts_beats() {
+        let mut prefs = MetronomePreferences {
+            tps: 0.5,
+            ..Default::default()
+        };
+        let mut metronome = Metronome::default();
+        assert!(!metronome.take_beat(&prefs));
+
+        metronome.toggle();
+        assert!(metronome.take_beat(&prefs));
+        assert!(!metronome.take_beat(&prefs));
+
+        // The first beat at the new tempo is right away.
+        prefs.tps = 0.25;
+        assert!(metronome.take_beat(&prefs));
+        assert_eq!(metronome.beats_ticked, 1);
+        assert!(!metronome.take_beat(&prefs));
+    }
+
+    #[test]
+    fn test_off_beat_analysis_display() {
+        let analysis = OffBeatAnalysis {
+            total: 20,
+            off_beat: vec![],
+        };
+        assert_eq!(analysis.to_string(), "0 of 20 moves off-beat");
+
+        let analysis = OffBeatAnalysis {
+            total: 20,
+            off_beat: vec![2, 5],
+        };
+        assert_eq!(analysis.to_string(), "2 of 20 moves off-beat: 3, 6");
+    }
+}
@@ -1,25 +1,75 @@
 mod about;
+#[cfg(not(target_arch = "wasm32"))]
+mod api;
+mod camera_keyframes;
+mod command_palette;
+mod fmc;
+mod hints;
+mod history_tree;
 mod keybind_sets;
 mod keybinds_reference;
 mod keybinds_table;
+mod latency;
+mod log_console;
+mod metronome;
 mod modifier_keys;
 mod mousebinds_table;
+mod move_count;
+mod notifications;
 mod piece_filters;
+mod progress;
 mod puzzle_controls;
+#[cfg(not(target_arch = "wasm32"))]
+mod race;
+mod relay;
+mod scramble_queue;
+#[cfg(not(target_arch = "wasm32"))]
+mod scripts;
 mod settings;
+mod solve_history;
+mod stats;
+#[cfg(not(target_arch = "wasm32"))]
+mod spectate;
+#[cfg(not(target_arch = "wasm32"))]
+mod sync;
 mod timer;
 mod welcome;
 
 use crate::app::App;
 pub(crate) use about::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use api::*;
+pub(crate) use camera_keyframes::*;
+pub(crate) use command_palette::*;
+pub(crate) use fmc::*;
+pub(crate) use hints::*;
+pub(crate) use history_tree::*;
 pub(crate) use keybind_sets::*;
 pub(crate) use keybinds_reference::*;
 pub(crate) use keybinds_table::*;
+pub(crate) use latency::*;
+pub(crate) use log_console::*;
+pub(crate) use metronome::*;
 pub(crate) use modifier_keys::*;
 pub(crate) use mousebinds_table::*;
+pub(crate) use move_count::*;
+pub(crate) use notifications::*;
 pub(crate) use piece_filters::*;
+pub(crate) use progress::*;
 pub(crate) use puzzle_controls::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use race::*;
+pub(crate) use relay::*;
+pub(crate) use scramble_queue::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use scripts::*;
 pub(crate) use settings::*;
+pub(crate) use solve_history::*;
+pub(crate) use stats::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use spectate::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use sync::*;
 pub(crate) use timer::*;
 pub(crate) use welcome::*;
 
@@ -27,11 +77,15 @@ pub const FLOATING_WINDOW_OPACITY: f32 = 0.98;
 pub const PREFS_WINDOW_WIDTH: f32 = 240.0;
 pub const ABOUT_WINDOW_WIDTH: f32 = 360.0;
 pub const WELCOME_WINDOW_WIDTH: f32 = 540.0;
+pub const COMMAND_PALETTE_WIDTH: f32 = 400.0;
 
 pub const ALL: &[Window] = &[
     // Misc.
     WELCOME,
     ABOUT,
+    COMMAND_PALETTE,
+    NOTIFICATIONS,
+    LOG_CONSOLE,
     #[cfg(debug_assertions)]
     DEBUG,
     // Tools
@@ -40,6 +94,28 @@ pub const ALL: &[Window] = &[
     PIECE_FILTERS,
     MODIFIER_KEYS,
     TIMER,
+    STATS,
+    SOLVE_HISTORY,
+    METRONOME,
+    LATENCY_CALIBRATION,
+    RELAY,
+    SCRAMBLE_QUEUE,
+    MOVE_COUNT,
+    PROGRESS,
+    FMC,
+    HINTS,
+    HISTORY_TREE,
+    CAMERA_KEYFRAMES,
+    #[cfg(not(target_arch = "wasm32"))]
+    RACE,
+    #[cfg(not(target_arch = "wasm32"))]
+    SPECTATE,
+    #[cfg(not(target_arch = "wasm32"))]
+    SCRIPTS,
+    #[cfg(not(target_arch = "wasm32"))]
+    SYNC,
+    #[cfg(not(target_arch = "wasm32"))]
+    API,
     // Settings
     APPEARANCE_SETTINGS,
     INTERACTION_SETTINGS,
@@ -57,6 +133,8 @@ pub const DEBUG: Window = Window {
     location: Location::Floating,
     fixed_width: None,
     vscroll: true,
+    dockable: false,
+    detachable: false,
     build: |ui, _app| {
         let mut debug_info = std::mem::take(&mut *crate::debug::FRAME_DEBUG_INFO.lock().unwrap());
         ui.add(egui::TextEdit::multiline(&mut debug_info).code_editor());
@@ -70,6 +148,10 @@ pub struct Window {
     pub location: Location,
     fixed_width: Option<f32>,
     vscroll: bool,
+    /// Whether the window can be docked beside the puzzle view.
+    dockable: bool,
+    /// Whether the window can be moved into its own OS window.
+    detachable: bool,
     build: fn(&mut egui::Ui, &mut App),
     cleanup: fn(&egui::Context, &mut App),
 }
@@ -79,6 +161,8 @@ impl Window {
         location: Location::Floating,
         fixed_width: None,
         vscroll: false,
+        dockable: false,
+        detachable: false,
         build: |_, _| (),
         cleanup: |_, _| (),
     };
@@ -87,6 +171,16 @@ impl Window {
         unique_id!(self.name)
     }
 
+    /// Returns the window with a given name.
+    pub fn by_name(name: &str) -> Option<Self> {
+        ALL.iter().find(|w| w.name == name).copied()
+    }
+
+    /// Returns the window title in the current language.
+    pub fn title(self) -> String {
+        tr!(&crate::i18n::message_id("window", self.name))
+    }
+
     pub fn is_open(self, ctx: &egui::Context) -> bool {
         ctx.data().get_persisted(self.id()).unwrap_or(false)
     }
@@ -104,6 +198,13 @@ impl Window {
     }
 
     pub fn show(self, ctx: &egui::Context, app: &mut App) {
+        let is_compact = super::is_compact_layout(ctx, app);
+        let layout = &app.prefs.layout;
+        // Docked panels are shown as ordinary windows in the compact layout.
+        if (layout.is_docked(self.name) && !is_compact) || layout.is_detached(self.name) {
+            return;
+        }
+
         let opacity = if self.id() == KEYBINDS_REFERENCE.id() {
             app.prefs.info.keybinds_reference.opacity
         } else {
@@ -114,7 +215,9 @@ impl Window {
 
         match self.location {
             Location::Floating | Location::Centered => {
-                let mut w = egui::Window::new(self.name).open(&mut is_open);
+                let mut w = egui::Window::new(self.title())
+                    .id(self.id())
+                    .open(&mut is_open);
                 if self.location == Location::Centered {
                     w = w
                         .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
@@ -132,11 +235,16 @@ impl Window {
                         ui.set_min_width(w);
                         ui.set_max_width(w);
                     }
+                    self.build_layout_buttons(ui, app);
                     (self.build)(ui, app);
                 });
+                // The window may have closed itself.
+                is_open &= self.is_open(ctx);
             }
             Location::LeftSide => {
-                super::side_bar::build(ctx, self.name, &mut is_open, |ui| (self.build)(ui, app));
+                super::side_bar::build(ctx, &self.title(), &mut is_open, is_compact, |ui| {
+                    (self.build)(ui, app)
+                });
             }
         }
 
@@ -146,9 +254,54 @@ impl Window {
         }
     }
 
+    /// Draws buttons to move the window out of the floating layer.
+    fn build_layout_buttons(self, ui: &mut egui::Ui, app: &mut App) {
+        if !self.dockable && !self.detachable {
+            return;
+        }
+        let is_docked = app.prefs.layout.is_docked(self.name);
+        ui.horizontal(|ui| {
+            if self.dockable
+                && !is_docked
+                && ui
+                    .small_button("📌 Dock")
+                    .on_hover_text("Show as a tab beside the puzzle view")
+                    .clicked()
+            {
+                app.prefs.layout.dock(self.name);
+                app.prefs.needs_save = true;
+                self.set_open(ui.ctx(), false);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if self.detachable
+                && ui
+                    .small_button("⏏ Detach")
+                    .on_hover_text("Move into a separate window")
+                    .clicked()
+            {
+                app.prefs.layout.detach(self.name);
+                app.prefs.needs_save = true;
+                self.set_open(ui.ctx(), false);
+            }
+        });
+    }
+
+    /// Draws the contents of the window in a tab or another OS window.
+    pub fn show_contents(self, ui: &mut egui::Ui, app: &mut App) {
+        egui::ScrollArea::new([false, self.vscroll])
+            .auto_shrink([false; 2])
+            .show(ui, |ui| (self.build)(ui, app));
+    }
+
+    /// Draws the contents of the window into its own OS window.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn show_detached(self, ctx: &egui::Context, app: &mut App) {
+        egui::CentralPanel::default().show(ctx, |ui| self.show_contents(ui, app));
+    }
+
     pub fn menu_button_toggle(self, ui: &mut egui::Ui) {
         let mut is_open = self.is_open(ui.ctx());
-        if ui.checkbox(&mut is_open, self.name).changed() {
+        if ui.checkbox(&mut is_open, self.title()).changed() {
             self.set_open(ui.ctx(), is_open);
         }
     }
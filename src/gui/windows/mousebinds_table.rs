@@ -63,6 +63,10 @@ impl egui::Widget for MousebindsTable<'_> {
                                 (MouseButton::Left, "Left".into()),
                                 (MouseButton::Right, "Right".into()),
                                 (MouseButton::Middle, "Middle".into()),
+                                (MouseButton::Back, "Back (M4)".into()),
+                                (MouseButton::Forward, "Forward (M5)".into()),
+                                (MouseButton::ScrollUp, "Scroll up".into()),
+                                (MouseButton::ScrollDown, "Scroll down".into()),
                             ],
                         });
 
@@ -93,6 +97,26 @@ impl egui::Widget for MousebindsTable<'_> {
                                 ),
                                 (PuzzleMouseCommand::Recenter, "Recenter".into()),
                                 (PuzzleMouseCommand::SelectPiece, "Select piece".into()),
+                                (PuzzleMouseCommand::Undo, "Undo".into()),
+                                (PuzzleMouseCommand::Redo, "Redo".into()),
+                                (PuzzleMouseCommand::NextGripAxis, "Grip next axis".into()),
+                                (
+                                    PuzzleMouseCommand::PreviousGripAxis,
+                                    "Grip previous axis".into(),
+                                ),
+                                (PuzzleMouseCommand::NextGripLayer, "Grip next layer".into()),
+                                (
+                                    PuzzleMouseCommand::PreviousGripLayer,
+                                    "Grip previous layer".into(),
+                                ),
+                                (
+                                    PuzzleMouseCommand::HoverBehind,
+                                    "Hover sticker behind".into(),
+                                ),
+                                (
+                                    PuzzleMouseCommand::HoverInFront,
+                                    "Hover sticker in front".into(),
+                                ),
                             ],
                         });
 
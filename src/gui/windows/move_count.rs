@@ -0,0 +1,74 @@
+use strum::{EnumMessage, IntoEnumIterator};
+
+use super::Window;
+use crate::app::App;
+use crate::gui::components::PrefsUi;
+use crate::gui::ext::ResponseExt;
+use crate::preferences::DEFAULT_PREFS;
+use crate::puzzle::{SliceConvention, TwistMetric};
+
+pub(crate) const MOVE_COUNT: Window = Window {
+    name: "Move count",
+    dockable: true,
+    build,
+    ..Window::DEFAULT
+};
+
+/// Font size of the move count.
+const MOVE_COUNT_FONT_SIZE: f32 = 32.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let options = app.prefs.info.move_count;
+    let count = app.puzzle.twist_count_with_options(options);
+    ui.vertical_centered(|ui| {
+        ui.label(egui::RichText::new(count.to_string()).size(MOVE_COUNT_FONT_SIZE));
+    });
+
+    ui.separator();
+
+    let prefs = &mut app.prefs;
+
+    let mut changed = false;
+    let mut prefs_ui = PrefsUi {
+        ui,
+        current: &mut prefs.info.move_count,
+        defaults: &DEFAULT_PREFS.info.move_count,
+        changed: &mut changed,
+    };
+
+    prefs_ui.ui.horizontal(|ui| {
+        for (slices, name) in [
+            (SliceConvention::Slice, "Slice"),
+            (SliceConvention::Block, "Block"),
+            (SliceConvention::OuterBlock, "Outer block"),
+        ] {
+            *prefs_ui.changed |= ui
+                .selectable_value(&mut prefs_ui.current.slices, slices, name)
+                .changed();
+        }
+    });
+    prefs_ui
+        .checkbox("Quarter turns", access!(.quarter_turns))
+        .on_hover_explanation(
+            "",
+            "When enabled, double twists are split into \
+             quarters. Otherwise, consecutive twists of the \
+             same axis and layers are combined.",
+        );
+    prefs_ui
+        .checkbox("Count rotations", access!(.rotations))
+        .on_hover_explanation(
+            "",
+            "When enabled, each whole-puzzle rotation \
+             counts as one move.",
+        );
+
+    prefs.needs_save |= changed;
+
+    let equivalent_metric =
+        TwistMetric::iter().find(|m| m.count_options() == Some(prefs.info.move_count));
+    if let Some(metric) = equivalent_metric {
+        ui.label(format!("Same as {metric}"))
+            .on_hover_text(metric.get_message().unwrap_or(""));
+    }
+}
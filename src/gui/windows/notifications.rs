@@ -0,0 +1,62 @@
+use super::Window;
+use crate::app::App;
+use crate::gui::Severity;
+
+pub(crate) const NOTIFICATIONS: Window = Window {
+    name: "Notifications",
+    vscroll: true,
+    dockable: true,
+    build,
+    ..Window::DEFAULT
+};
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let min_severity_id = unique_id!();
+    let mut min_severity = ui
+        .data()
+        .get_temp::<Severity>(min_severity_id)
+        .unwrap_or(Severity::Info);
+
+    ui.horizontal(|ui| {
+        for (severity, label) in [
+            (Severity::Info, "All"),
+            (Severity::Warning, "Warnings"),
+            (Severity::Error, "Errors"),
+        ] {
+            ui.selectable_value(&mut min_severity, severity, label);
+        }
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.button("Clear").clicked() {
+                app.toasts.clear_log();
+            }
+        });
+    });
+    ui.data().insert_temp(min_severity_id, min_severity);
+
+    ui.separator();
+
+    let mut entries = app
+        .toasts
+        .log_entries()
+        .iter()
+        .rev()
+        .filter(|entry| entry.severity >= min_severity)
+        .peekable();
+    if entries.peek().is_none() {
+        ui.label("No notifications");
+        return;
+    }
+    for entry in entries {
+        ui.horizontal_wrapped(|ui| {
+            let color = entry.severity.color(ui.visuals());
+            ui.colored_label(color, entry.severity.icon())
+                .on_hover_text(format!("{:?}", entry.severity));
+            ui.weak(crate::util::format_unix_timestamp(entry.timestamp));
+            ui.strong(&entry.title);
+        });
+        if !entry.message.is_empty() {
+            ui.label(&entry.message);
+        }
+        ui.separator();
+    }
+}
@@ -4,7 +4,7 @@ use super::Window;
 use crate::app::App;
 use crate::gui::components::{prefs, small_icon_button, PrefsUi, PresetsUi};
 use crate::preferences::{PieceFilter, DEFAULT_PREFS};
-use crate::puzzle::{traits::*, Face, PieceInfo, PieceType};
+use crate::puzzle::{traits::*, Face, PieceInfo, PieceQuery, PieceType};
 
 pub(crate) const PIECE_FILTERS: Window = Window {
     name: "Piece filters",
@@ -59,6 +59,10 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
 
     prefs_ui.percent("Hidden", access!(.hidden));
     prefs::build_unhide_grip_checkbox(&mut prefs_ui);
+    prefs::build_dim_solved_checkbox(&mut prefs_ui);
+    if prefs_ui.current.dim_solved {
+        prefs_ui.percent("Solved", access!(.solved));
+    }
 
     prefs.needs_save |= changed;
     if changed {
@@ -141,6 +145,39 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
         ui.data().insert_temp(colors_selection_id, selected_colors);
     });
 
+    ui.collapsing("Query", |ui| {
+        let query_id = unique_id!();
+        let mut query_string: String = ui.data().get_temp(query_id).unwrap_or_default();
+        ui.add(
+            egui::TextEdit::singleline(&mut query_string)
+                .hint_text("e.g. R @U unsolved 3c")
+                .desired_width(f32::INFINITY),
+        )
+        .on_hover_text(
+            "Face names match pieces with that color, and \
+             @face matches pieces with a sticker on that face. \
+             Also accepts piece types, sticker counts like 3c, \
+             solved, unsolved, and, or, not, and parentheses.",
+        );
+
+        if !query_string.trim().is_empty() {
+            // Evaluate the query on the latest state, so that it is up to date
+            // even while twists are animating.
+            match PieceQuery::parse(&puzzle_type, &query_string) {
+                Ok(query) => {
+                    let piece_set = query.matching_pieces(app.puzzle.latest());
+                    let label = format!("{} matching pieces", piece_set.count_ones());
+                    PieceFilterWidget::new("matching pieces", &label, piece_set).show(ui, app);
+                }
+                Err(e) => {
+                    ui.colored_label(ui.visuals().error_fg_color, e);
+                }
+            }
+        }
+
+        ui.data().insert_temp(query_id, query_string);
+    });
+
     ui.collapsing("Presets", |ui| {
         ui.set_enabled(!app.prefs.colors.blindfold);
 
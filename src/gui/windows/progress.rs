@@ -0,0 +1,71 @@
+use super::Window;
+use crate::app::App;
+use crate::puzzle::traits::*;
+
+pub(crate) const PROGRESS: Window = Window {
+    name: "Progress",
+    build,
+    ..Window::DEFAULT
+};
+
+/// Number of saved snapshots listed under "History".
+const PROGRESS_HISTORY_COUNT: usize = 20;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    let puzzle = &app.puzzle;
+    let Some(progress) = puzzle.progress() else {
+        ui.label("Progress estimation is not supported for this puzzle.");
+        return;
+    };
+
+    ui.add(
+        egui::ProgressBar::new(progress.fraction())
+            .text(format!(
+                "{}/{} pieces solved",
+                progress.solved_pieces(),
+                progress.total_pieces(),
+            ))
+            .show_percentage(),
+    );
+
+    ui.collapsing("Piece types", |ui| {
+        egui::Grid::new(unique_id!()).num_columns(2).show(ui, |ui| {
+            let piece_types = puzzle.piece_types();
+            for (piece_type, &(solved, total)) in piece_types.iter().zip(&progress.by_piece_type) {
+                if total == 0 {
+                    continue;
+                }
+                ui.label(&piece_type.name);
+                ui.add(
+                    egui::ProgressBar::new(solved as f32 / total as f32)
+                        .text(format!("{solved}/{total}")),
+                );
+                ui.end_row();
+            }
+        });
+    });
+
+    ui.collapsing("History", |ui| {
+        let history = puzzle.progress_history();
+        if history.is_empty() {
+            ui.label("Progress is recorded each time the puzzle is saved.");
+            return;
+        }
+        egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+            ui.strong("Saved");
+            ui.strong("Twists (ETM)");
+            ui.strong("Solved");
+            ui.end_row();
+            for snapshot in history.iter().rev().take(PROGRESS_HISTORY_COUNT) {
+                ui.label(crate::util::format_unix_timestamp(snapshot.time));
+                ui.label(snapshot.twist_count.to_string());
+                let percent = match snapshot.total_pieces {
+                    0 => 100.0,
+                    total => snapshot.solved_pieces as f32 / total as f32 * 100.0,
+                };
+                ui.label(format!("{percent:.1}%"));
+                ui.end_row();
+            }
+        });
+    });
+}
@@ -1,6 +1,7 @@
 use super::Window;
 use crate::app::App;
-use crate::gui::components::reset_button;
+use crate::gui::components::{reset_button, small_icon_button, LayerMaskSegments, PresetsUi};
+use crate::preferences::GripGroup;
 use crate::puzzle::*;
 
 pub(crate) const PUZZLE_CONTROLS: Window = Window {
@@ -44,13 +45,24 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
     ui.strong("Layers");
     ui.with_layout(h_layout, |ui| {
         reset_button(ui, &mut app.toggle_grip.layers, Grip::default().layers, "");
-        for i in 0..puzzle_type.layer_count() {
-            let mut is_sel = grip.layers.unwrap_or_default()[i];
-            let r = ui.selectable_value(&mut is_sel, true, format!("{}", i + 1));
-            if r.changed() {
+        // Show layers gripped by held keys too, but only apply changes to
+        // the toggled grip.
+        let old_layers = grip.layers.unwrap_or_default();
+        let mut new_layers = old_layers;
+        let r = ui.add(LayerMaskSegments {
+            id: unique_id!(),
+            layers: &mut new_layers,
+            layer_count: puzzle_type.layer_count(),
+        });
+        if r.changed() {
+            let toggled = old_layers ^ new_layers;
+            for i in (0..puzzle_type.layer_count()).filter(|&i| toggled[i]) {
                 app.toggle_grip.toggle_layer(i, false);
             }
         }
+        if small_icon_button(ui, "⇄", "Invert layers").clicked() {
+            app.toggle_grip.invert_layers(puzzle_type.layer_count());
+        }
     });
 
     ui.separator();
@@ -62,7 +74,8 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
     ui.add_enabled_ui(can_twist, |ui| {
         ui.with_layout(h_layout, |ui| {
             for (i, twist_direction) in puzzle_type.twist_directions().iter().enumerate() {
-                if ui.button(twist_direction.name).clicked() {
+                let name = crate::i18n::twist_direction_name(twist_direction.name);
+                if ui.button(name).clicked() {
                     if let Ok(axis) = twist_axis {
                         // should always be `Ok`
                         app.event(Twist {
@@ -75,4 +88,66 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
             }
         });
     });
+
+    ui.separator();
+
+    ui.collapsing("Grip groups", |ui| {
+        let mut grip_groups = std::mem::take(&mut app.prefs.grip_groups[puzzle_type]);
+
+        let mut changed = false;
+
+        let mut presets_ui = PresetsUi {
+            id: unique_id!(),
+            presets: &mut grip_groups,
+            changed: &mut changed,
+            strings: Default::default(),
+            enable_yaml: true,
+        };
+
+        presets_ui.show_header(ui, || GripGroup::from_grip(puzzle_type, &app.toggle_grip));
+        ui.separator();
+        presets_ui.show_list(ui, |ui, _idx, preset| {
+            let grip = preset.value.to_grip(puzzle_type);
+            let name = &preset.preset_name;
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.spacing_mut().item_spacing.x /= 2.0;
+
+                let intersection = app.toggle_grip.clone() & &grip;
+                let union = app.toggle_grip.clone() | &grip;
+                let mut small_button = |new_grip: Grip, text: &str, hover_text: &str| {
+                    let r = ui.add_enabled(app.toggle_grip != new_grip, |ui: &mut egui::Ui| {
+                        small_icon_button(ui, text, hover_text)
+                    });
+                    if r.clicked() {
+                        app.toggle_grip = new_grip;
+                    }
+                };
+                small_button(
+                    intersection,
+                    "&",
+                    &format!("Intersect selection with {name}"),
+                );
+                small_button(union, "+", &format!("Add {name} to selection"));
+
+                ui.allocate_ui_with_layout(
+                    egui::vec2(ui.available_width(), ui.min_size().y),
+                    egui::Layout::centered_and_justified(egui::Direction::TopDown)
+                        .with_cross_align(egui::Align::LEFT),
+                    |ui| {
+                        let is_active = app.toggle_grip == grip;
+                        if ui.selectable_label(is_active, name.as_str()).clicked() {
+                            app.toggle_grip = grip.clone();
+                        }
+                    },
+                )
+                .response
+            })
+            .inner
+        });
+
+        app.prefs.grip_groups[puzzle_type] = grip_groups;
+
+        app.prefs.needs_save |= changed;
+    });
 }
@@ -0,0 +1,191 @@
+use super::Window;
+use crate::app::{App, AppEvent};
+use crate::net::{RaceClient, RaceServer};
+
+pub(crate) const RACE: Window = Window {
+    name: "Race",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 250.0;
+
+const DEFAULT_PORT: u16 = 8766;
+
+#[derive(Debug, Clone)]
+struct State {
+    url: String,
+    name: String,
+    port: u16,
+    allow_lan: bool,
+}
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            name: String::new(),
+            port: DEFAULT_PORT,
+            allow_lan: false,
+        }
+    }
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    // Keep updating peers' progress.
+    ui.ctx().request_repaint();
+
+    let Some(race) = &app.race else {
+        build_connect(ui, app);
+        return;
+    };
+
+    ui.horizontal(|ui| {
+        ui.label("Lobby:");
+        ui.strong(race.url());
+    });
+    if let Some(e) = race.error() {
+        ui.colored_label(ui.visuals().error_fg_color, e);
+    } else if !race.is_joined() {
+        ui.label("Connecting...");
+    }
+
+    let mut join = false;
+    let mut skip = false;
+    if app.pending_race.is_some() {
+        ui.separator();
+        ui.label("A race has started. Joining it discards the current puzzle state.");
+        ui.horizontal(|ui| {
+            join = ui.button("Join race").clicked();
+            skip = ui.button("Skip").clicked();
+        });
+        ui.separator();
+    }
+
+    let mut start = false;
+    let mut disconnect = false;
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(race.is_joined(), |ui| {
+            start = ui
+                .button("Start race")
+                .on_hover_text("Send a new scramble of the current puzzle to everyone")
+                .clicked();
+        });
+        disconnect = ui.button("Leave").clicked();
+    });
+
+    ui.separator();
+
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        ui.strong("Player");
+        ui.strong("Twists");
+        ui.strong("Time");
+        ui.end_row();
+
+        for peer in race.peers() {
+            if Some(peer.info.id) == race.id() {
+                ui.strong(&peer.info.name);
+            } else {
+                ui.label(&peer.info.name);
+            }
+            ui.label(peer.twist_count.to_string());
+            match (&peer.error, peer.time_ms) {
+                (Some(e), _) => ui
+                    .colored_label(ui.visuals().error_fg_color, "Error")
+                    .on_hover_text(e),
+                (None, Some(_)) => ui.label(super::time_ms_to_str(peer.time_ms)),
+                (None, None) => ui.label("—"),
+            };
+            ui.end_row();
+        }
+    });
+
+    if join {
+        if let Some(race_start) = app.pending_race.take() {
+            app.join_race(race_start);
+        }
+    }
+    if skip {
+        app.pending_race = None;
+        if let Some(race) = &mut app.race {
+            race.skip_race();
+        }
+    }
+    if start {
+        if let Err(e) = app.start_race() {
+            app.event(AppEvent::StatusError(e));
+        }
+    }
+    if disconnect {
+        app.race = None;
+        app.pending_race = None;
+    }
+}
+
+fn build_connect(ui: &mut egui::Ui, app: &mut App) {
+    let state_id = unique_id!();
+    let mut state: State = ui.data().get_temp(state_id).unwrap_or_default();
+
+    egui::Grid::new(unique_id!()).num_columns(2).show(ui, |ui| {
+        ui.label("Server");
+        ui.add(egui::TextEdit::singleline(&mut state.url).hint_text("ws://example.com:8080"));
+        ui.end_row();
+
+        ui.label("Name");
+        ui.add(egui::TextEdit::singleline(&mut state.name).hint_text("Your name"));
+        ui.end_row();
+    });
+
+    let can_connect = !state.url.trim().is_empty() && !state.name.trim().is_empty();
+    ui.add_enabled_ui(can_connect, |ui| {
+        if ui.button("Join lobby").clicked() {
+            app.race = Some(RaceClient::connect(state.url.trim(), state.name.trim()));
+        }
+    });
+
+    ui.separator();
+
+    ui.heading("Host");
+    match &app.race_server {
+        Some(server) => {
+            ui.label(format!("Hosting on port {}", server.port()));
+            ui.label(match server.player_count() {
+                1 => "1 player".to_owned(),
+                n => format!("{n} players"),
+            });
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!state.name.trim().is_empty(), |ui| {
+                    if ui.button("Join own lobby").clicked() {
+                        let url = server.local_url();
+                        app.race = Some(RaceClient::connect(&url, state.name.trim()));
+                    }
+                });
+                if ui.button("Stop hosting").clicked() {
+                    app.race_server = None;
+                }
+            });
+        }
+        None => {
+            ui.horizontal(|ui| {
+                ui.label("Port");
+                ui.add(egui::DragValue::new(&mut state.port).clamp_range(1024..=u16::MAX));
+            });
+            ui.checkbox(&mut state.allow_lan, "Allow other computers")
+                .on_hover_text("Let players on your local network join");
+            if ui
+                .button("Host lobby")
+                .on_hover_text("Run a race lobby that other players can join")
+                .clicked()
+            {
+                match RaceServer::start(state.port, state.allow_lan) {
+                    Ok(server) => app.race_server = Some(server),
+                    Err(e) => app.event(AppEvent::StatusError(e)),
+                }
+            }
+        }
+    }
+
+    ui.data().insert_temp(state_id, state);
+}
@@ -0,0 +1,249 @@
+use instant::Duration;
+use itertools::Itertools;
+
+use super::Window;
+use crate::app::{App, AppEvent};
+use crate::gui::components::{big_icon_button, puzzle_type_menu};
+use crate::puzzle::{PuzzleController, PuzzleType, PuzzleTypeEnum, ScrambleOptions, Twist};
+
+pub(crate) const RELAY: Window = Window {
+    name: "Relay",
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 250.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    if app.relay.is_running() {
+        build_running(ui, app);
+        return;
+    }
+
+    let mut remove = None;
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        for (i, ty) in app.relay.puzzles.iter().enumerate() {
+            ui.label(format!("{}.", i + 1));
+            ui.label(ty.name());
+            if big_icon_button(ui, "🗑", "Remove puzzle").clicked() {
+                remove = Some(i);
+            }
+            ui.end_row();
+        }
+    });
+    if let Some(i) = remove {
+        app.relay.puzzles.remove(i);
+    }
+
+    ui.horizontal(|ui| {
+        if let Some(ty) = ui
+            .menu_button("Add puzzle", puzzle_type_menu)
+            .inner
+            .flatten()
+        {
+            app.relay.puzzles.push(ty);
+        }
+        ui.menu_button("Presets", |ui| {
+            for (name, puzzles) in presets() {
+                if ui.button(name).clicked() {
+                    ui.close_menu();
+                    app.relay.puzzles = puzzles;
+                }
+            }
+        });
+    });
+
+    ui.add_enabled_ui(app.relay.puzzles.len() >= 2, |ui| {
+        let r = ui
+            .add_sized([120.0, 30.0], egui::Button::new("Start relay"))
+            .on_hover_text("Scramble each puzzle and time them all together");
+        if r.clicked() {
+            if let Err(e) = app.start_relay() {
+                app.event(AppEvent::StatusError(e));
+            }
+        }
+    });
+
+    if let Some(splits) = app.relay.finished_splits() {
+        ui.separator();
+        ui.strong("Last relay");
+        splits_grid(ui, app.relay.attempt_puzzles(), splits);
+    }
+
+    let name = relay_name(&app.relay.puzzles);
+    if let Some(session) = app.history.relay_session(&name) {
+        let stats = session.stats();
+        ui.separator();
+        egui::Grid::new(unique_id!()).num_columns(4).show(ui, |ui| {
+            ui.label("Solves:");
+            ui.label(stats.count.to_string());
+            ui.label("Mean:");
+            ui.label(super::time_ms_to_str(stats.mean));
+            ui.end_row();
+
+            ui.label("Best:");
+            ui.label(super::time_ms_to_str(stats.best));
+            ui.label("Best ao5:");
+            ui.label(super::time_ms_to_str(stats.best_ao5));
+            ui.end_row();
+        });
+    }
+}
+
+fn build_running(ui: &mut egui::Ui, app: &mut App) {
+    // Keep the current split updated.
+    ui.ctx().request_repaint();
+
+    let puzzles = app.relay.attempt_puzzles();
+    let current = app.relay.splits().len();
+    ui.label(format!(
+        "Puzzle {} of {}: {}",
+        current + 1,
+        puzzles.len(),
+        puzzles[current].name(),
+    ));
+    splits_grid(ui, puzzles, app.relay.splits());
+
+    if ui.button("Cancel relay").clicked() {
+        app.relay.cancel();
+    }
+}
+
+fn splits_grid(ui: &mut egui::Ui, puzzles: &[PuzzleTypeEnum], splits: &[Duration]) {
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        ui.strong("Puzzle");
+        ui.strong("Split");
+        ui.strong("Total");
+        ui.end_row();
+
+        let mut last = Duration::ZERO;
+        for (ty, &split) in puzzles.iter().zip(splits) {
+            ui.label(ty.name());
+            ui.label(super::duration_to_str(split - last));
+            ui.label(super::duration_to_str(split));
+            ui.end_row();
+            last = split;
+        }
+    });
+}
+
+/// Returns common relays.
+fn presets() -> Vec<(&'static str, Vec<PuzzleTypeEnum>)> {
+    vec![
+        (
+            "2x2x2 through 5x5x5",
+            (2..=5)
+                .map(|layer_count| PuzzleTypeEnum::Rubiks3D { layer_count })
+                .collect(),
+        ),
+        (
+            "3x3x3 and 3x3x3x3",
+            vec![
+                PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+                PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+            ],
+        ),
+    ]
+}
+
+/// Returns the name of a relay, which identifies its session in the solve
+/// history.
+pub(crate) fn relay_name(puzzles: &[PuzzleTypeEnum]) -> String {
+    puzzles.iter().map(|ty| ty.name()).join(" + ")
+}
+
+/// Sequence of puzzles that are scrambled and solved one after another, timed
+/// as a single solve.
+#[derive(Debug, Default)]
+pub(crate) struct Relay {
+    /// Puzzles in the next relay, in order.
+    pub(crate) puzzles: Vec<PuzzleTypeEnum>,
+    /// Current or most recent attempt.
+    attempt: Option<RelayAttempt>,
+}
+#[derive(Debug)]
+struct RelayAttempt {
+    puzzles: Vec<PuzzleTypeEnum>,
+    scrambles: Vec<Vec<Twist>>,
+    /// Time since the start of the relay at which each puzzle was solved.
+    splits: Vec<Duration>,
+    /// Total number of twists on all solved puzzles.
+    twist_count: usize,
+}
+impl Relay {
+    /// Returns whether there are puzzles left to solve in the current attempt.
+    pub(crate) fn is_running(&self) -> bool {
+        self.attempt
+            .as_ref()
+            .map_or(false, |a| a.splits.len() < a.puzzles.len())
+    }
+
+    /// Starts a new attempt, generating a scramble for each puzzle.
+    pub(crate) fn start(&mut self) -> Result<(), String> {
+        if self.puzzles.len() < 2 {
+            return Err("A relay needs at least two puzzles".to_owned());
+        }
+        let scrambles = self
+            .puzzles
+            .iter()
+            .map(|&ty| {
+                // Relays always use the default scramble options, so that
+                // times are comparable.
+                let mut puzzle = PuzzleController::new(ty);
+                puzzle.scramble_full(&ScrambleOptions::default())?;
+                Ok(puzzle.scramble().to_vec())
+            })
+            .collect::<Result<_, &str>>()?;
+        self.attempt = Some(RelayAttempt {
+            puzzles: self.puzzles.clone(),
+            scrambles,
+            splits: vec![],
+            twist_count: 0,
+        });
+        Ok(())
+    }
+    /// Abandons the current attempt.
+    pub(crate) fn cancel(&mut self) {
+        if self.is_running() {
+            self.attempt = None;
+        }
+    }
+
+    /// Returns the puzzle to solve next and its scramble.
+    pub(crate) fn current_puzzle(&self) -> Option<(PuzzleTypeEnum, &[Twist])> {
+        let a = self.attempt.as_ref()?;
+        let i = a.splits.len();
+        Some((*a.puzzles.get(i)?, a.scrambles.get(i)?))
+    }
+    /// Records that the current puzzle was solved.
+    pub(crate) fn record_split(&mut self, split: Duration, twist_count: usize) {
+        if let Some(a) = &mut self.attempt {
+            a.splits.push(split);
+            a.twist_count += twist_count;
+        }
+    }
+
+    /// Returns the splits of the current or most recent attempt.
+    pub(crate) fn splits(&self) -> &[Duration] {
+        self.attempt.as_ref().map_or(&[], |a| &a.splits)
+    }
+    /// Returns the splits of the most recent attempt, if it was finished.
+    pub(crate) fn finished_splits(&self) -> Option<&[Duration]> {
+        if self.is_running() {
+            return None;
+        }
+        self.attempt.as_ref().map(|a| &*a.splits)
+    }
+    /// Returns the puzzles in the current or most recent attempt.
+    pub(crate) fn attempt_puzzles(&self) -> &[PuzzleTypeEnum] {
+        self.attempt.as_ref().map_or(&[], |a| &a.puzzles)
+    }
+    /// Returns the total number of twists on all solved puzzles in the
+    /// current or most recent attempt.
+    pub(crate) fn twist_count(&self) -> usize {
+        self.attempt.as_ref().map_or(0, |a| a.twist_count)
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use itertools::Itertools;
+
+use super::Window;
+use crate::app::App;
+use crate::commands::Command;
+use crate::puzzle::{PuzzleController, PuzzleTypeEnum, ScrambleOptions, Twist};
+
+pub(crate) const SCRAMBLE_QUEUE: Window = Window {
+    name: "Upcoming scrambles",
+    detachable: true,
+    dockable: true,
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 250.0;
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    ui.horizontal(|ui| {
+        if ui
+            .button("Next scramble")
+            .on_hover_text("Scramble the puzzle with the upcoming scramble")
+            .clicked()
+        {
+            app.event(Command::NextScramble);
+        }
+        if ui
+            .button("Redo scramble")
+            .on_hover_text("Reset the puzzle and apply the last scramble again")
+            .clicked()
+        {
+            app.event(Command::RedoScramble);
+        }
+    });
+
+    let notation = app.puzzle.notation_scheme().clone();
+    let upcoming = app.scramble_queue.upcoming(app.puzzle.ty()).collect_vec();
+    ui.separator();
+    if upcoming.is_empty() {
+        ui.label("Generating scrambles…");
+        return;
+    }
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        for (i, scramble) in upcoming.into_iter().enumerate() {
+            ui.label(format!("{}.", i + 1));
+            let text = scramble
+                .iter()
+                .map(|&twist| notation.twist_to_string(twist))
+                .join(" ");
+            if i == 0 {
+                ui.add(egui::Label::new(egui::RichText::new(text).strong()).wrap(true));
+            } else {
+                ui.add(egui::Label::new(text).wrap(true));
+            }
+            ui.end_row();
+        }
+    });
+}
+
+/// Full scrambles generated ahead of time, so that there is no delay between
+/// solves.
+#[derive(Debug, Default)]
+pub(crate) struct ScrambleQueue {
+    /// Puzzle type and options that the queued scrambles were generated for.
+    key: Option<(PuzzleTypeEnum, ScrambleOptions)>,
+    upcoming: VecDeque<Vec<Twist>>,
+}
+impl ScrambleQueue {
+    /// Generates one more scramble if there are fewer than `len` queued.
+    /// This is called every frame so that generating scrambles is spread out
+    /// over time.
+    pub(crate) fn refill(&mut self, ty: PuzzleTypeEnum, options: &ScrambleOptions, len: usize) {
+        self.set_key(ty, options);
+        self.upcoming.truncate(len);
+        if self.upcoming.len() < len {
+            // Errors are reported when the scramble is actually needed.
+            if let Ok(scramble) = generate(ty, options) {
+                self.upcoming.push_back(scramble);
+            }
+        }
+    }
+
+    /// Returns the queued scrambles for a puzzle, in order.
+    pub(crate) fn upcoming(&self, ty: PuzzleTypeEnum) -> impl Iterator<Item = &[Twist]> {
+        let is_current = matches!(self.key, Some((queue_ty, _)) if queue_ty == ty);
+        self.upcoming
+            .iter()
+            .filter(move |_| is_current)
+            .map(|scramble| &scramble[..])
+    }
+
+    /// Removes and returns the next scramble, generating one if none are
+    /// queued.
+    pub(crate) fn pop(
+        &mut self,
+        ty: PuzzleTypeEnum,
+        options: &ScrambleOptions,
+    ) -> Result<Vec<Twist>, &'static str> {
+        self.set_key(ty, options);
+        match self.upcoming.pop_front() {
+            Some(scramble) => Ok(scramble),
+            None => generate(ty, options),
+        }
+    }
+
+    /// Clears the queue if the puzzle type or options have changed.
+    fn set_key(&mut self, ty: PuzzleTypeEnum, options: &ScrambleOptions) {
+        let key = (ty, options.clone());
+        if self.key.as_ref() != Some(&key) {
+            self.key = Some(key);
+            self.upcoming.clear();
+        }
+    }
+}
+
+fn generate(ty: PuzzleTypeEnum, options: &ScrambleOptions) -> Result<Vec<Twist>, &'static str> {
+    let mut puzzle = PuzzleController::new(ty);
+    puzzle.scramble_full(options)?;
+    Ok(puzzle.scramble().to_vec())
+}
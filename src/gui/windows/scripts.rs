@@ -0,0 +1,82 @@
+use super::Window;
+use crate::app::{App, AppEvent};
+
+pub(crate) const SCRIPTS: Window = Window {
+    name: "Scripts",
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 400.0;
+const OUTPUT_HEIGHT: f32 = 120.0;
+
+const EXAMPLE_SCRIPT: &str = "\
+// Called after each twist.
+fn on_twist(twist) {
+    print(`${twist} (${twist_count()} twists)`);
+}
+
+// Called when the puzzle is solved.
+fn on_solve(time_ms) {
+    print(`Solved ${puzzle()} in ${time_ms} ms`);
+}
+";
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    let source_id = unique_id!();
+    let mut source = ui
+        .data()
+        .get_temp::<String>(source_id)
+        .unwrap_or_else(|| EXAMPLE_SCRIPT.to_owned());
+
+    ui.horizontal(|ui| {
+        if ui.button("Open...").clicked() {
+            let file = rfd::FileDialog::new()
+                .add_filter("Rhai scripts", &["rhai"])
+                .add_filter("All files", &["*"])
+                .pick_file();
+            if let Some(path) = file {
+                match std::fs::read_to_string(&path) {
+                    Ok(s) => source = s,
+                    Err(e) => app.event(AppEvent::StatusError(format!("Error opening script: {e}"))),
+                }
+            }
+        }
+        if ui.button("Run").on_hover_text("Run the script and enable its event hooks").clicked() {
+            let result = app.script.load(&source, &app.puzzle);
+            app.apply_script_actions(result);
+        }
+        ui.add_enabled_ui(app.script.is_loaded(), |ui| {
+            if ui.button("Stop").on_hover_text("Disable the script's event hooks").clicked() {
+                app.script.unload();
+            }
+        });
+    });
+
+    ui.add(
+        egui::TextEdit::multiline(&mut source)
+            .code_editor()
+            .desired_rows(12)
+            .desired_width(f32::INFINITY),
+    );
+
+    ui.horizontal(|ui| {
+        ui.strong("Output");
+        if ui.small_button("Clear").clicked() {
+            app.script.clear_output();
+        }
+    });
+    egui::ScrollArea::vertical()
+        .max_height(OUTPUT_HEIGHT)
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+            for line in app.script.output() {
+                ui.monospace(line);
+            }
+        });
+
+    ui.data().insert_temp(source_id, source);
+}
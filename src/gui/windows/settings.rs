@@ -6,18 +6,30 @@ pub(crate) const APPEARANCE_SETTINGS: Window = Window {
     fixed_width: Some(PREFS_WINDOW_WIDTH),
     vscroll: true,
     build: |ui, app| {
-        ui.collapsing("Colors", |ui| {
+        prefs::build_language_picker(ui, app);
+        ui.collapsing(tr!("prefs-colors"), |ui| {
             prefs::build_colors_section(ui, app);
         });
-        ui.collapsing("Outlines", |ui| {
+        ui.collapsing(tr!("prefs-outlines"), |ui| {
             prefs::build_outlines_section(ui, app);
         });
-        ui.collapsing("Opacity", |ui| {
+        ui.collapsing(tr!("prefs-opacity"), |ui| {
             prefs::build_opacity_section(ui, app);
         });
-        ui.collapsing("Performance", |ui| {
+        ui.collapsing(tr!("prefs-performance"), |ui| {
             prefs::build_graphics_section(ui, app);
         });
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.collapsing(tr!("prefs-screenshots"), |ui| {
+            prefs::build_screenshots_section(ui, app);
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.collapsing(tr!("prefs-mini-mode"), |ui| {
+            prefs::build_mini_mode_section(ui, app);
+        });
+        ui.collapsing(tr!("prefs-layout"), |ui| {
+            prefs::build_layout_section(ui, app);
+        });
     },
     ..Window::DEFAULT
 };
@@ -25,7 +37,19 @@ pub(crate) const APPEARANCE_SETTINGS: Window = Window {
 pub(crate) const INTERACTION_SETTINGS: Window = Window {
     name: "Interaction",
     fixed_width: Some(PREFS_WINDOW_WIDTH),
-    build: prefs::build_interaction_section,
+    build: |ui, app| {
+        prefs::build_interaction_section(ui, app);
+        ui.collapsing(tr!("prefs-scramble"), |ui| {
+            prefs::build_scramble_section(ui, app);
+        });
+        ui.collapsing(tr!("prefs-sounds"), |ui| {
+            prefs::build_sounds_section(ui, app);
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.collapsing(tr!("prefs-hotkeys"), |ui| {
+            prefs::build_hotkeys_section(ui, app);
+        });
+    },
     ..Window::DEFAULT
 };
 
@@ -0,0 +1,386 @@
+use std::collections::BTreeSet;
+
+use itertools::Itertools;
+
+use super::{time_ms_to_str, Window};
+use crate::app::{App, AppEvent};
+use crate::gui::components::{solve_notes_menu, solve_star_toggle, FancyComboBox};
+use crate::history::{Penalty, Solve, DNF_MS};
+use crate::puzzle::{traits::*, PieceTypeBreakdown, ProjectionType, Puzzle, PuzzleTypeEnum, Twist};
+
+pub(crate) const SOLVE_HISTORY: Window = Window {
+    name: "Solve history",
+    dockable: true,
+    vscroll: true,
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 500.0;
+/// Name of the session option that includes every session.
+const ALL_SESSIONS: &str = "All sessions";
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum SortBy {
+    #[default]
+    Date,
+    Time,
+    Twists,
+}
+
+/// Filters, sorting, and selection in the solve history window.
+#[derive(Debug, Default, Clone)]
+struct BrowserState {
+    session: String,
+    tag: String,
+    search: String,
+    sort_by: SortBy,
+    ascending: bool,
+    /// Selected solves, as the name of the session and the index of the
+    /// solve in it.
+    selected: BTreeSet<(String, usize)>,
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    let ty = app.puzzle.ty();
+    let state_id = unique_id!();
+    let mut state = ui
+        .data()
+        .get_temp::<BrowserState>(state_id)
+        .unwrap_or_else(|| BrowserState {
+            session: ALL_SESSIONS.to_owned(),
+            tag: super::ANY_TAG.to_owned(),
+            ..Default::default()
+        });
+
+    let mut names = vec![ALL_SESSIONS.to_owned()];
+    names.extend(app.history.sessions(ty).iter().map(|s| s.name.clone()));
+    if !names.contains(&state.session) {
+        state.session = ALL_SESSIONS.to_owned();
+    }
+    ui.horizontal(|ui| {
+        ui.label("Session:");
+        ui.add(FancyComboBox::new(unique_id!(), &mut state.session, &names));
+        super::tag_filter(ui, app, &mut state.tag);
+    });
+    ui.horizontal(|ui| {
+        ui.label("Sort by:");
+        ui.selectable_value(&mut state.sort_by, SortBy::Date, "Date");
+        ui.selectable_value(&mut state.sort_by, SortBy::Time, "Time");
+        ui.selectable_value(&mut state.sort_by, SortBy::Twists, "Twists");
+        let arrow = if state.ascending { "⏶" } else { "⏷" };
+        if ui.button(arrow).on_hover_text("Reverse order").clicked() {
+            state.ascending ^= true;
+        }
+        ui.add(
+            egui::TextEdit::singleline(&mut state.search)
+                .hint_text("Search tags, comments, methods, and cases")
+                .desired_width(f32::INFINITY),
+        );
+    });
+
+    let search = state.search.trim().to_lowercase();
+    let mut rows = app
+        .history
+        .sessions(ty)
+        .iter()
+        .filter(|s| state.session == ALL_SESSIONS || s.name == state.session)
+        .flat_map(|s| {
+            s.solves
+                .iter()
+                .enumerate()
+                .map(move |(i, solve)| (&s.name, i, solve))
+        })
+        .filter(|(_, _, solve)| super::matches_tag_filter(solve, &state.tag))
+        .filter(|(_, _, solve)| {
+            search.is_empty()
+                || solve.comment.to_lowercase().contains(&search)
+                || solve.last_layer_case.to_lowercase().contains(&search)
+                || solve.method.to_lowercase().contains(&search)
+                || solve
+                    .tags
+                    .iter()
+                    .any(|t| t.to_lowercase().contains(&search))
+        })
+        .map(|(name, i, solve)| (name.clone(), i, solve.clone()))
+        .collect_vec();
+    rows.sort_by_key(|(_, _, solve)| match state.sort_by {
+        SortBy::Date => solve.timestamp as u64,
+        SortBy::Time => solve.timed_result_ms().unwrap_or(DNF_MS),
+        SortBy::Twists => solve.twist_count as u64,
+    });
+    if !state.ascending {
+        rows.reverse();
+    }
+
+    ui.separator();
+    bulk_operations(ui, app, &mut state, &rows);
+    ui.separator();
+
+    if rows.is_empty() {
+        ui.label("No solves");
+    }
+    let mut edited = None;
+    let mut replay = None;
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        if !rows.is_empty() {
+            for header in ["", "", "Time", "Penalty", "Twists", "Date", "Session", ""] {
+                ui.strong(header);
+            }
+            ui.end_row();
+        }
+
+        for (name, i, solve) in &rows {
+            let key = (name.clone(), *i);
+            let mut is_selected = state.selected.contains(&key);
+            if ui.checkbox(&mut is_selected, "").changed() {
+                if is_selected {
+                    state.selected.insert(key.clone());
+                } else {
+                    state.selected.remove(&key);
+                }
+            }
+
+            let mut new_solve = solve.clone();
+            let mut changed = solve_star_toggle(ui, &mut new_solve);
+            ui.label(time_ms_to_str(solve.timed_result_ms()));
+            ui.menu_button(new_solve.penalty.to_string(), |ui| {
+                for penalty in [Penalty::None, Penalty::PlusTwo, Penalty::Dnf] {
+                    let r =
+                        ui.selectable_value(&mut new_solve.penalty, penalty, penalty.to_string());
+                    if r.clicked() {
+                        changed = true;
+                        ui.close_menu();
+                    }
+                }
+            });
+            let r = ui.label(format!("{} {}", solve.twist_count, solve.metric));
+            let details = [
+                ("Method", &solve.method),
+                ("Last layer", &solve.last_layer_case),
+            ]
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| format!("{name}: {value}"))
+            .join("\n");
+            if !details.is_empty() {
+                r.on_hover_text(details);
+            }
+            ui.label(crate::util::format_unix_timestamp(solve.timestamp));
+            ui.label(name);
+            ui.horizontal(|ui| {
+                changed |= solve_notes_menu(ui, &mut new_solve);
+                let r = ui
+                    .add_enabled(!solve.scramble.is_empty(), egui::Button::new("▶"))
+                    .on_hover_text("Replay solve")
+                    .on_disabled_hover_text("This solve has no recorded scramble");
+                if r.clicked() {
+                    replay = Some(solve.clone());
+                }
+                piece_type_breakdown_menu(ui, ty, solve);
+            });
+            ui.end_row();
+
+            if changed {
+                edited = Some((key, new_solve));
+            }
+        }
+    });
+
+    if let Some(((name, i), new_solve)) = edited {
+        if let Some(solve) = app.history.solve_mut(ty, &name, i) {
+            *solve = new_solve;
+            app.history.needs_save = true;
+        }
+    }
+    if let Some(solve) = replay {
+        if let Err(e) = app.load_replay(ty, &solve) {
+            app.event(AppEvent::StatusError(e));
+        }
+    }
+
+    ui.data().insert_temp(state_id, state);
+}
+
+/// Shows a menu with the number of twists and estimated time spent on each
+/// piece type in a 4D solve.
+fn piece_type_breakdown_menu(ui: &mut egui::Ui, ty: PuzzleTypeEnum, solve: &Solve) {
+    let enabled = ty.projection_type() == ProjectionType::_4D && !solve.scramble.is_empty();
+    ui.add_enabled_ui(enabled, |ui| {
+        ui.menu_button("📊", |ui| {
+            // Replaying the solve is slow for big puzzles, so only do it once.
+            let id = unique_id!(&solve.scramble, &solve.solution);
+            let cached = ui.data().get_temp(id);
+            let breakdown = cached.unwrap_or_else(|| {
+                let result = piece_type_breakdown(ty, solve);
+                ui.data().insert_temp(id, result.clone());
+                result
+            });
+            match breakdown {
+                Ok(breakdown) => show_piece_type_breakdown(ui, ty, solve, &breakdown),
+                Err(e) => {
+                    ui.label(e);
+                }
+            }
+        })
+        .response
+        .on_hover_text("Piece type breakdown")
+        .on_disabled_hover_text("Only available for 4D solves with a recorded scramble");
+    });
+}
+
+fn piece_type_breakdown(ty: PuzzleTypeEnum, solve: &Solve) -> Result<PieceTypeBreakdown, String> {
+    let parse = |s: &str| {
+        s.split_whitespace()
+            .map(|t| {
+                t.parse()
+                    .map_err(|()| format!("Invalid twist {t:?} in solve"))
+            })
+            .collect::<Result<Vec<Twist>, String>>()
+    };
+    let scramble = parse(&solve.scramble)?;
+    let solution = parse(&solve.solution)?;
+
+    let mut puzzle = Puzzle::new(ty);
+    for twist in scramble {
+        puzzle.twist(twist)?;
+    }
+    Ok(PieceTypeBreakdown::of_solve(&mut puzzle, &solution)?)
+}
+
+fn show_piece_type_breakdown(
+    ui: &mut egui::Ui,
+    ty: PuzzleTypeEnum,
+    solve: &Solve,
+    breakdown: &PieceTypeBreakdown,
+) {
+    let piece_types = ty.piece_types();
+    // Twists aren't timestamped, so assume they were all equally fast.
+    let estimate_ms = |twists: usize| match breakdown.total_twists {
+        0 => None,
+        total => Some(solve.time_ms * twists as u64 / total as u64),
+    };
+
+    ui.strong("Twists while unsolved");
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        for (info, &twists) in piece_types.iter().zip(&breakdown.twists_while_unsolved) {
+            ui.label(&info.name);
+            ui.label(twists.to_string());
+            ui.end_row();
+        }
+    });
+
+    ui.separator();
+    ui.strong("Phases")
+        .on_hover_text("Twists and estimated time until each piece type was solved");
+    let phases = breakdown.phases();
+    if phases.is_empty() {
+        ui.label("No phases");
+    }
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        for (piece_type, twists) in phases {
+            ui.label(&piece_types[piece_type].name);
+            ui.label(twists.to_string());
+            ui.label(time_ms_to_str(estimate_ms(twists)));
+            ui.end_row();
+        }
+    });
+}
+
+/// Shows buttons to select solves and to delete, move, or export the
+/// selected ones.
+fn bulk_operations(
+    ui: &mut egui::Ui,
+    app: &mut App,
+    state: &mut BrowserState,
+    rows: &[(String, usize, Solve)],
+) {
+    let ty = app.puzzle.ty();
+    ui.horizontal(|ui| {
+        if ui.button("Select all").clicked() {
+            state.selected = rows.iter().map(|(name, i, _)| (name.clone(), *i)).collect();
+        }
+        if ui.button("Select none").clicked() {
+            state.selected.clear();
+        }
+
+        ui.add_enabled_ui(!state.selected.is_empty(), |ui| {
+            ui.label(format!("{} selected", state.selected.len()));
+
+            if ui.button("Delete").clicked() {
+                let confirm = rfd::MessageDialog::new()
+                    .set_title("Delete solves")
+                    .set_description(&format!("Delete {} solves?", state.selected.len()))
+                    .set_buttons(rfd::MessageButtons::YesNo)
+                    .show();
+                if confirm {
+                    app.history.delete_solves(ty, &state.selected);
+                    state.selected.clear();
+                }
+            }
+
+            ui.menu_button("Move to session", |ui| {
+                for session in app
+                    .history
+                    .sessions(ty)
+                    .iter()
+                    .map(|s| s.name.clone())
+                    .collect_vec()
+                {
+                    if ui.button(&session).clicked() {
+                        app.history.move_solves(ty, &state.selected, &session);
+                        state.selected.clear();
+                        ui.close_menu();
+                    }
+                }
+            });
+
+            if ui
+                .button("Copy as CSV")
+                .on_hover_text("Copy the selected solves as a spreadsheet")
+                .clicked()
+            {
+                let selected = rows
+                    .iter()
+                    .filter(|(name, i, _)| state.selected.contains(&(name.clone(), *i)));
+                ui.output().copied_text =
+                    solves_csv(selected.map(|(name, _, solve)| (name, solve)));
+            }
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui
+            .button("Import MC4D logs…")
+            .on_hover_text("Import solves from a folder of MagicCube4D log files")
+            .clicked()
+        {
+            if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                app.import_mc4d_logs(&dir);
+            }
+        }
+    });
+}
+
+/// Returns solves as comma-separated values, with a header row.
+fn solves_csv<'a>(solves: impl IntoIterator<Item = (&'a String, &'a Solve)>) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+    let mut lines = vec!["Session,Time (ms),Penalty,Twists,Metric,Date,Tags,Comment".to_owned()];
+    for (session, solve) in solves {
+        lines.push(
+            [
+                quote(session),
+                solve.time_ms.to_string(),
+                solve.penalty.to_string(),
+                solve.twist_count.to_string(),
+                solve.metric.to_string(),
+                quote(&crate::util::format_unix_timestamp(solve.timestamp)),
+                quote(&solve.tags.iter().join(", ")),
+                quote(&solve.comment),
+            ]
+            .join(","),
+        );
+    }
+    lines.join("\n")
+}
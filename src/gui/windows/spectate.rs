@@ -0,0 +1,115 @@
+use super::Window;
+use crate::app::{App, AppEvent};
+use crate::net::SpectateHost;
+
+pub(crate) const SPECTATE: Window = Window {
+    name: "Spectate",
+    build,
+    ..Window::DEFAULT
+};
+
+const DEFAULT_PORT: u16 = 8765;
+
+#[derive(Debug, Clone)]
+struct State {
+    port: u16,
+    allow_lan: bool,
+    address: String,
+    code: String,
+}
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            allow_lan: false,
+            address: String::new(),
+            code: String::new(),
+        }
+    }
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    // Keep applying twists from the host.
+    ui.ctx().request_repaint();
+
+    let state_id = unique_id!();
+    let mut state: State = ui.data().get_temp(state_id).unwrap_or_default();
+
+    ui.heading("Broadcast");
+    match &app.spectate_host {
+        Some(host) => {
+            ui.label(format!("Broadcasting on port {}", host.port()));
+            if !host.allows_lan() {
+                ui.label("Only this computer can connect");
+            }
+            ui.horizontal(|ui| {
+                ui.label("Code");
+                ui.strong(host.code());
+                if ui.small_button("Copy").clicked() {
+                    ui.output().copied_text = host.code().to_owned();
+                }
+            });
+            ui.label(match host.spectator_count() {
+                1 => "1 spectator".to_owned(),
+                n => format!("{n} spectators"),
+            });
+            if ui.button("Stop broadcasting").clicked() {
+                app.spectate_host = None;
+            }
+        }
+        None => {
+            ui.horizontal(|ui| {
+                ui.label("Port");
+                ui.add(egui::DragValue::new(&mut state.port).clamp_range(1024..=u16::MAX));
+            });
+            ui.checkbox(&mut state.allow_lan, "Allow other computers")
+                .on_hover_text("Let spectators on your local network connect");
+            if ui
+                .button("Start broadcasting")
+                .on_hover_text("Let other players watch your puzzle live")
+                .clicked()
+            {
+                match SpectateHost::start(state.port, state.allow_lan) {
+                    Ok(host) => app.spectate_host = Some(host),
+                    Err(e) => app.event(AppEvent::StatusError(e)),
+                }
+            }
+        }
+    }
+
+    ui.separator();
+
+    ui.heading("Watch");
+    match &app.spectating {
+        Some(client) => {
+            ui.horizontal(|ui| {
+                ui.label("Watching");
+                ui.strong(client.address());
+            });
+            if let Some(e) = client.error() {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+            } else if let Some(time_ms) = client.last_time_ms() {
+                ui.label(format!("Last twist at {}", super::time_ms_to_str(Some(time_ms))));
+            }
+            if ui.button("Stop watching").clicked() {
+                app.spectating = None;
+            }
+        }
+        None => {
+            ui.add(egui::TextEdit::singleline(&mut state.address).hint_text("host:port"));
+            ui.add(egui::TextEdit::singleline(&mut state.code).hint_text("code"));
+            let can_watch = !state.address.trim().is_empty() && !state.code.trim().is_empty();
+            ui.add_enabled_ui(can_watch, |ui| {
+                if ui
+                    .button("Start watching")
+                    .on_hover_text("Show another player's puzzle (read-only)")
+                    .clicked()
+                {
+                    app.start_spectating(state.address.trim(), state.code.trim());
+                }
+            });
+        }
+    }
+
+    ui.data().insert_temp(state_id, state);
+}
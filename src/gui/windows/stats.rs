@@ -0,0 +1,294 @@
+use egui::plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints};
+use itertools::Itertools;
+
+use super::Window;
+use crate::app::App;
+use crate::gui::components::FancyComboBox;
+use crate::history::{trimmed_average, Penalty, Solve, DNF_MS};
+
+pub(crate) const STATS: Window = Window {
+    name: "Statistics",
+    detachable: true,
+    dockable: true,
+    build,
+    ..Window::DEFAULT
+};
+
+const MIN_WIDTH: f32 = 400.0;
+const PLOT_HEIGHT: f32 = 200.0;
+/// Number of bars in each histogram.
+const HISTOGRAM_BIN_COUNT: usize = 20;
+/// Name of the session option that includes every session.
+const ALL_SESSIONS: &str = "All sessions";
+/// Name of the tag option that includes every solve.
+pub(crate) const ANY_TAG: &str = "Any";
+/// Name of the tag option that includes only starred solves.
+const STARRED: &str = "★ Starred";
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum Graph {
+    #[default]
+    Trend,
+    Histogram,
+    Tps,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum DateRange {
+    #[default]
+    All,
+    Day,
+    Week,
+    Month,
+}
+impl DateRange {
+    /// Returns the length of the range in seconds, or `None` if it includes
+    /// every solve.
+    fn seconds(self) -> Option<i64> {
+        const DAY: i64 = 60 * 60 * 24;
+        match self {
+            DateRange::All => None,
+            DateRange::Day => Some(DAY),
+            DateRange::Week => Some(DAY * 7),
+            DateRange::Month => Some(DAY * 30),
+        }
+    }
+}
+
+fn build(ui: &mut egui::Ui, app: &mut App) {
+    ui.set_min_width(MIN_WIDTH);
+
+    let ty = app.puzzle.ty();
+    let sessions = app.history.sessions(ty);
+
+    let session_id = unique_id!();
+    let mut session = ui
+        .data()
+        .get_temp::<String>(session_id)
+        .unwrap_or_else(|| app.history.active_session_name(ty).to_owned());
+    let mut names = vec![ALL_SESSIONS.to_owned()];
+    names.extend(sessions.iter().map(|s| s.name.clone()));
+    if !names.contains(&session) {
+        session = ALL_SESSIONS.to_owned();
+    }
+    ui.horizontal(|ui| {
+        ui.label("Session:");
+        ui.add(FancyComboBox::new(unique_id!(), &mut session, &names));
+    });
+
+    let tag_id = unique_id!();
+    let mut tag = ui
+        .data()
+        .get_temp::<String>(tag_id)
+        .unwrap_or_else(|| ANY_TAG.to_owned());
+    ui.horizontal(|ui| tag_filter(ui, app, &mut tag));
+
+    // Methods are only detected for some puzzles, so only show the filter if
+    // there are any.
+    let method_id = unique_id!();
+    let mut method = ui
+        .data()
+        .get_temp::<String>(method_id)
+        .unwrap_or_else(|| ANY_TAG.to_owned());
+    let mut methods = sessions
+        .iter()
+        .flat_map(|s| &s.solves)
+        .map(|solve| solve.method.clone())
+        .filter(|m| !m.is_empty())
+        .unique()
+        .sorted()
+        .collect_vec();
+    if !methods.contains(&method) {
+        method = ANY_TAG.to_owned();
+    }
+    if !methods.is_empty() {
+        let mut options = vec![ANY_TAG.to_owned()];
+        options.extend(methods.iter().cloned());
+        ui.horizontal(|ui| {
+            ui.label("Method:");
+            ui.add(FancyComboBox::new(unique_id!(), &mut method, &options));
+        });
+    }
+
+    let range_id = unique_id!();
+    let mut range = ui
+        .data()
+        .get_temp::<DateRange>(range_id)
+        .unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.selectable_value(&mut range, DateRange::All, "All time");
+        ui.selectable_value(&mut range, DateRange::Day, "Today");
+        ui.selectable_value(&mut range, DateRange::Week, "Past week");
+        ui.selectable_value(&mut range, DateRange::Month, "Past month");
+    });
+
+    let graph_id = unique_id!();
+    let mut graph = ui.data().get_temp::<Graph>(graph_id).unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.selectable_value(&mut graph, Graph::Trend, "Trend");
+        ui.selectable_value(&mut graph, Graph::Histogram, "Histogram");
+        ui.selectable_value(&mut graph, Graph::Tps, "TPS");
+    });
+
+    let earliest = range
+        .seconds()
+        .map_or(i64::MIN, |secs| crate::util::unix_timestamp() - secs);
+    let mut solves = sessions
+        .iter()
+        .filter(|s| session == ALL_SESSIONS || s.name == session)
+        .flat_map(|s| &s.solves)
+        .filter(|solve| solve.timestamp >= earliest)
+        .filter(|solve| matches_tag_filter(solve, &tag))
+        .filter(|solve| !solve.untimed)
+        .collect_vec();
+    solves.sort_by_key(|solve| solve.timestamp);
+
+    // Compare methods using every solve that matches the other filters.
+    methods.retain(|m| solves.iter().any(|solve| solve.method == *m));
+    if methods.len() > 1 {
+        ui.separator();
+        method_comparison(ui, &methods, &solves);
+    }
+    solves.retain(|solve| method == ANY_TAG || solve.method == method);
+
+    ui.separator();
+    if solves.is_empty() {
+        ui.label("No solves");
+    } else {
+        match graph {
+            Graph::Trend => trend_plot(ui, &solves),
+            Graph::Histogram => {
+                let times = solves
+                    .iter()
+                    .map(|s| s.result_ms())
+                    .filter(|&ms| ms != DNF_MS)
+                    .map(|ms| ms as f64 / 1000.0);
+                histogram_plot(ui, "Time (s)", &times.collect_vec());
+            }
+            Graph::Tps => {
+                let tps = solves
+                    .iter()
+                    .filter(|s| s.penalty != Penalty::Dnf && s.time_ms > 0)
+                    .map(|s| s.twist_count as f64 * 1000.0 / s.time_ms as f64);
+                histogram_plot(ui, "Twists per second", &tps.collect_vec());
+            }
+        }
+    }
+
+    let mut data = ui.data();
+    data.insert_temp(session_id, session);
+    data.insert_temp(tag_id, tag);
+    data.insert_temp(method_id, method);
+    data.insert_temp(range_id, range);
+    data.insert_temp(graph_id, graph);
+}
+
+/// Shows a combo box for filtering solves by tag.
+pub(crate) fn tag_filter(ui: &mut egui::Ui, app: &App, tag: &mut String) {
+    let mut tags = vec![ANY_TAG.to_owned(), STARRED.to_owned()];
+    tags.extend(app.history.all_tags(app.puzzle.ty()));
+    if !tags.contains(tag) {
+        *tag = ANY_TAG.to_owned();
+    }
+    ui.label("Tag:");
+    ui.add(FancyComboBox::new(unique_id!(), tag, &tags));
+}
+
+/// Returns whether a solve matches a tag chosen using [`tag_filter()`].
+pub(crate) fn matches_tag_filter(solve: &Solve, tag: &str) -> bool {
+    match tag {
+        ANY_TAG => true,
+        STARRED => solve.starred,
+        _ => solve.tags.contains(tag),
+    }
+}
+
+/// Shows the number of solves, mean, and best time using each method.
+fn method_comparison(ui: &mut egui::Ui, methods: &[String], solves: &[&Solve]) {
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        for header in ["Method", "Solves", "Mean", "Best"] {
+            ui.strong(header);
+        }
+        ui.end_row();
+
+        for method in methods {
+            let times = solves
+                .iter()
+                .filter(|solve| solve.method == *method)
+                .map(|solve| solve.result_ms())
+                .collect_vec();
+            let finished = times.iter().filter(|&&ms| ms != DNF_MS).collect_vec();
+            let mean = (!finished.is_empty())
+                .then(|| finished.iter().copied().sum::<u64>() / finished.len() as u64);
+            ui.label(method);
+            ui.label(times.len().to_string());
+            ui.label(super::time_ms_to_str(mean));
+            ui.label(super::time_ms_to_str(finished.into_iter().min().copied()));
+            ui.end_row();
+        }
+    });
+}
+
+/// Plots each solve time in order, with rolling averages. DNFs and DNF
+/// averages are left out.
+fn trend_plot(ui: &mut egui::Ui, solves: &[&Solve]) {
+    let times = solves.iter().map(|s| s.result_ms()).collect_vec();
+    let seconds = |ms: u64| ms as f64 / 1000.0;
+
+    let rolling_average = |n: usize| {
+        let points = times
+            .windows(n)
+            .enumerate()
+            .filter_map(|(i, window)| Some((i + n, trimmed_average(window)?)))
+            .filter(|&(_, ms)| ms != DNF_MS)
+            .map(|(i, ms)| [i as f64, seconds(ms)])
+            .collect::<PlotPoints>();
+        Line::new(points).name(format!("ao{n}"))
+    };
+
+    Plot::new(unique_id!())
+        .height(PLOT_HEIGHT)
+        .legend(Legend::default())
+        .show(ui, |plot_ui| {
+            let points = times
+                .iter()
+                .enumerate()
+                .filter(|&(_, &ms)| ms != DNF_MS)
+                .map(|(i, &ms)| [(i + 1) as f64, seconds(ms)])
+                .collect::<PlotPoints>();
+            plot_ui.line(Line::new(points).name("Time"));
+            plot_ui.line(rolling_average(5));
+            plot_ui.line(rolling_average(12));
+        });
+}
+
+/// Plots the distribution of `values`.
+fn histogram_plot(ui: &mut egui::Ui, name: &str, values: &[f64]) {
+    if values.is_empty() {
+        ui.label("No solves");
+        return;
+    }
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let bin_width = ((max - min) / HISTOGRAM_BIN_COUNT as f64).max(f64::EPSILON);
+
+    let mut counts = vec![0; HISTOGRAM_BIN_COUNT];
+    for &value in values {
+        let bin = ((value - min) / bin_width) as usize;
+        counts[bin.min(HISTOGRAM_BIN_COUNT - 1)] += 1;
+    }
+    let bars = counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| {
+            Bar::new(min + (i as f64 + 0.5) * bin_width, count as f64).width(bin_width)
+        })
+        .collect();
+
+    Plot::new(unique_id!())
+        .height(PLOT_HEIGHT)
+        .legend(Legend::default())
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(BarChart::new(bars).name(name));
+        });
+}
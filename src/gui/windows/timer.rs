@@ -1,22 +1,34 @@
 use instant::{Duration, Instant};
+use itertools::Itertools;
 
+use crate::app::{App, AppEvent};
+use crate::gui::components::{big_icon_button, solve_notes_menu, solve_star_toggle, FancyComboBox};
 use crate::gui::ext::ResponseExt;
+use crate::history::DNF_MS;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::preferences::OverlayFormat;
+use crate::puzzle::{traits::*, PuzzleTypeEnum, Rubiks3D};
 
 use super::Window;
 
 // TODO: resizing of timer text (eg keybind reference)
 // TODO: should Timer/Stopwatch be in components?
 
+/// Default target time when enabling one for a session.
+const DEFAULT_TARGET_MS: u64 = 60_000;
+/// Number of solves listed under "Recent solves".
+const RECENT_SOLVES_COUNT: usize = 10;
+/// Width and height of each face in the scramble preview.
+const SCRAMBLE_PREVIEW_FACE_SIZE: f32 = 36.0;
+/// Space between stickers in the scramble preview.
+const SCRAMBLE_PREVIEW_STICKER_GAP: f32 = 0.5;
+
 pub(crate) const TIMER: Window = Window {
     name: "Timer",
+    dockable: true,
     build: |ui, app| {
         ui.add(egui::Button::new(
-            egui::RichText::new(match app.timer.stopwatch {
-                Stopwatch::NotStarted => "Ready".into(),
-                Stopwatch::Running(start) => duration_to_str(start.elapsed()),
-                Stopwatch::Stopped(duration) => duration_to_str(duration),
-            })
-            .size(20.0),
+            egui::RichText::new(app.timer.display_str()).size(20.0),
         ));
         if ui
             .selectable_label(app.timer.is_blind, "Blind mode")
@@ -28,16 +40,402 @@ pub(crate) const TIMER: Window = Window {
         {
             app.timer.is_blind ^= true;
             app.timer.stopwatch.reset();
+            app.relay.cancel();
             app.puzzle.reset();
         }
+        ui.collapsing("Scramble preview", |ui| scramble_preview(ui, app));
+        ui.collapsing("Scramble seed", |ui| scramble_seed(ui, app));
+
+        ui.separator();
+        session_selector(ui, app);
+        session_target(ui, app);
+        session_stats(ui, app);
+        ui.collapsing("Phases", |ui| phases(ui, app));
+        ui.collapsing("Recent solves", |ui| recent_solves(ui, app));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            ui.separator();
+            stream_overlay(ui, app);
+        }
     },
     ..Window::DEFAULT
 };
 
+/// Draws a flat net of the scrambled state of a 3D Rubik's cube.
+fn scramble_preview(ui: &mut egui::Ui, app: &App) {
+    let ty = app.puzzle.ty();
+    let PuzzleTypeEnum::Rubiks3D { layer_count } = ty else {
+        ui.label("Only available for 3D Rubik's cubes");
+        return;
+    };
+    if app.puzzle.scramble().is_empty() {
+        ui.label("Not scrambled");
+        return;
+    }
+
+    let mut puzzle = Rubiks3D::new(layer_count);
+    for &twist in app.puzzle.scramble() {
+        if let Err(e) = puzzle.twist(twist) {
+            ui.label(format!("Error applying scramble: {e}"));
+            return;
+        }
+    }
+
+    let face_colors = app.prefs.colors.face_colors_list(ty);
+    let sticker_size = SCRAMBLE_PREVIEW_FACE_SIZE / layer_count as f32;
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(4.0, 3.0) * SCRAMBLE_PREVIEW_FACE_SIZE,
+        egui::Sense::hover(),
+    );
+    for sticker in puzzle.net() {
+        let min = rect.min + egui::vec2(sticker.x as f32, sticker.y as f32) * sticker_size;
+        let sticker_rect = egui::Rect::from_min_size(min, egui::Vec2::splat(sticker_size))
+            .shrink(SCRAMBLE_PREVIEW_STICKER_GAP);
+        ui.painter()
+            .rect_filled(sticker_rect, 0.0, face_colors[sticker.color.0 as usize]);
+    }
+}
+
+/// Shows the seed of the current scramble and lets the user load a scramble
+/// from a seed or from notation.
+fn scramble_seed(ui: &mut egui::Ui, app: &mut App) {
+    ui.horizontal(|ui| {
+        ui.label("Current seed:");
+        match app.puzzle.scramble_seed() {
+            Some(seed) => {
+                ui.strong(seed.to_string());
+                if big_icon_button(ui, "🗐", "Copy seed").clicked() {
+                    ui.output().copied_text = seed.to_string();
+                }
+            }
+            None => {
+                ui.label("None");
+            }
+        }
+    });
+
+    let text_id = unique_id!();
+    let mut text = ui.data().get_temp::<String>(text_id).unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(!text.trim().is_empty(), |ui| {
+            if ui.button("Load").clicked() {
+                match app.load_scramble(&text) {
+                    Ok(()) => text.clear(),
+                    Err(e) => app.event(AppEvent::StatusError(e)),
+                }
+            }
+        });
+        ui.add(
+            egui::TextEdit::singleline(&mut text)
+                .hint_text("Seed or scramble")
+                .desired_width(f32::INFINITY),
+        );
+    });
+    ui.data().insert_temp(text_id, text);
+}
+
+fn session_selector(ui: &mut egui::Ui, app: &mut App) {
+    let ty = app.puzzle.ty();
+
+    let mut active = app.history.active_session_name(ty).to_owned();
+    let mut names = app
+        .history
+        .sessions(ty)
+        .iter()
+        .map(|s| s.name.clone())
+        .collect_vec();
+    if !names.contains(&active) {
+        names.insert(0, active.clone());
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Session:");
+        let r = ui.add(FancyComboBox::new(unique_id!(), &mut active, &names));
+        if r.changed() {
+            app.history.set_active_session(ty, &active);
+        }
+    });
+
+    let name_id = unique_id!();
+    let mut name = ui.data().get_temp::<String>(name_id).unwrap_or_default();
+    ui.horizontal(|ui| {
+        let is_name_valid = !name.trim().is_empty();
+        ui.add_enabled_ui(is_name_valid, |ui| {
+            if big_icon_button(ui, "➕", "New session").clicked() {
+                match app.history.add_session(ty, &name) {
+                    Ok(()) => name.clear(),
+                    Err(e) => app.event(AppEvent::StatusError(e)),
+                }
+            }
+            if big_icon_button(ui, "✏", "Rename session").clicked() {
+                match app.history.rename_session(ty, &active, &name) {
+                    Ok(()) => name.clear(),
+                    Err(e) => app.event(AppEvent::StatusError(e)),
+                }
+            }
+        });
+        if big_icon_button(ui, "🗑", "Delete session").clicked() {
+            let confirm = rfd::MessageDialog::new()
+                .set_title("Delete session")
+                .set_description(&format!("Delete session {active:?} and all its solves?"))
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show();
+            if confirm {
+                app.history.delete_session(ty, &active);
+            }
+        }
+        ui.add(
+            egui::TextEdit::singleline(&mut name)
+                .hint_text("Session name")
+                .desired_width(f32::INFINITY),
+        );
+    });
+    ui.data().insert_temp(name_id, name);
+}
+
+fn session_target(ui: &mut egui::Ui, app: &mut App) {
+    let ty = app.puzzle.ty();
+    let current_target = app
+        .history
+        .active_session(ty)
+        .and_then(|session| session.target_ms);
+
+    let mut has_target = current_target.is_some();
+    let mut target_secs = current_target.unwrap_or(DEFAULT_TARGET_MS) as f64 / 1000.0;
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui.checkbox(&mut has_target, "Target time").changed();
+        ui.add_enabled_ui(has_target, |ui| {
+            let r = ui.add(
+                egui::DragValue::new(&mut target_secs)
+                    .clamp_range(0.0..=f64::MAX)
+                    .fixed_decimals(2)
+                    .speed(0.1)
+                    .suffix(" s"),
+            );
+            changed |= r.changed();
+        });
+    });
+
+    if changed {
+        let new_target = has_target.then(|| (target_secs * 1000.0).round() as u64);
+        app.history.active_session_mut(ty).target_ms = new_target;
+        app.history.needs_save = true;
+    }
+}
+
+fn session_stats(ui: &mut egui::Ui, app: &mut App) {
+    let stats = app
+        .history
+        .active_session(app.puzzle.ty())
+        .map(|session| session.stats())
+        .unwrap_or_default();
+
+    egui::Grid::new(unique_id!()).num_columns(4).show(ui, |ui| {
+        ui.label("Solves:");
+        ui.label(stats.count.to_string());
+        ui.label("Mean:");
+        ui.label(time_ms_to_str(stats.mean));
+        ui.end_row();
+
+        ui.label("Best:");
+        ui.label(time_ms_to_str(stats.best));
+        ui.label("Worst:");
+        ui.label(time_ms_to_str(stats.worst));
+        ui.end_row();
+
+        ui.label("ao5:");
+        ui.label(time_ms_to_str(stats.current_ao5));
+        ui.label("Best ao5:");
+        ui.label(time_ms_to_str(stats.best_ao5));
+        ui.end_row();
+
+        ui.label("ao12:");
+        ui.label(time_ms_to_str(stats.current_ao12));
+        ui.label("Best ao12:");
+        ui.label(time_ms_to_str(stats.best_ao12));
+        ui.end_row();
+    });
+}
+
+/// Lets the user define phases for the active session, and shows the splits
+/// of the current solve and the mean time of each phase.
+fn phases(ui: &mut egui::Ui, app: &mut App) {
+    let ty = app.puzzle.ty();
+    let (phases, means) = match app.history.active_session(ty) {
+        Some(session) => (session.phases.clone(), session.phase_means()),
+        None => (vec![], vec![]),
+    };
+
+    let mut remove = None;
+    if phases.is_empty() {
+        ui.label("No phases");
+    } else {
+        let splits = app.timer.splits();
+        egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+            ui.strong("Phase");
+            ui.strong("Split");
+            ui.strong("Mean");
+            ui.end_row();
+
+            for (i, phase) in phases.iter().enumerate() {
+                ui.label(phase);
+                match splits.get(i) {
+                    Some(&split) => {
+                        let start = i.checked_sub(1).map_or(Duration::ZERO, |j| splits[j]);
+                        ui.label(duration_to_str(split - start));
+                    }
+                    None => {
+                        ui.label("—");
+                    }
+                }
+                ui.label(time_ms_to_str(means.get(i).copied().flatten()));
+                if big_icon_button(ui, "🗑", "Delete phase").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+    }
+    if let Some(i) = remove {
+        app.history.active_session_mut(ty).phases.remove(i);
+        app.history.needs_save = true;
+    }
+
+    let name_id = unique_id!();
+    let mut name = ui.data().get_temp::<String>(name_id).unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(!name.trim().is_empty(), |ui| {
+            if big_icon_button(ui, "➕", "Add phase").clicked() {
+                let name = std::mem::take(&mut name);
+                app.history
+                    .active_session_mut(ty)
+                    .phases
+                    .push(name.trim().to_owned());
+                app.history.needs_save = true;
+            }
+        });
+        ui.add(
+            egui::TextEdit::singleline(&mut name)
+                .hint_text("Phase name")
+                .desired_width(f32::INFINITY),
+        );
+    });
+    ui.data().insert_temp(name_id, name);
+    ui.label("Bind a key to \"Mark split\" to finish each phase during a solve.");
+}
+
+/// Lists the most recent solves in the active session, with a button to
+/// practice each scramble again and buttons to star, tag, and comment on each
+/// solve.
+fn recent_solves(ui: &mut egui::Ui, app: &mut App) {
+    let ty = app.puzzle.ty();
+    let Some(session) = app.history.active_session(ty) else {
+        ui.label("No solves yet");
+        return;
+    };
+
+    let mut load_seed = None;
+    let mut edited = None;
+    egui::Grid::new(unique_id!()).striped(true).show(ui, |ui| {
+        ui.label("");
+        ui.strong("Time");
+        ui.strong("Twists");
+        ui.strong("Seed");
+        ui.label("");
+        ui.end_row();
+
+        let recent = session.solves.iter().enumerate().rev();
+        for (i, solve) in recent.take(RECENT_SOLVES_COUNT) {
+            let mut new_solve = solve.clone();
+            if solve_star_toggle(ui, &mut new_solve) {
+                edited = Some((i, new_solve.clone()));
+            }
+            ui.label(time_ms_to_str(solve.timed_result_ms()));
+            ui.label(format!("{} {}", solve.twist_count, solve.metric));
+            match solve.seed {
+                Some(seed) => {
+                    let r = ui
+                        .link(seed.to_string())
+                        .on_hover_text("Practice this scramble again");
+                    if r.clicked() {
+                        load_seed = Some(seed);
+                    }
+                }
+                None => {
+                    ui.label("—");
+                }
+            }
+            if solve_notes_menu(ui, &mut new_solve) {
+                edited = Some((i, new_solve));
+            }
+            ui.end_row();
+        }
+    });
+
+    if let Some((i, solve)) = edited {
+        app.history.active_session_mut(ty).solves[i] = solve;
+        app.history.needs_save = true;
+    }
+    if let Some(seed) = load_seed {
+        if let Err(e) = app.load_scramble(&seed.to_string()) {
+            app.event(AppEvent::StatusError(e));
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn stream_overlay(ui: &mut egui::Ui, app: &mut App) {
+    let prefs = &mut app.prefs.overlay;
+    let mut changed = false;
+
+    egui::CollapsingHeader::new("Stream overlay")
+        .id_source(unique_id!())
+        .show(ui, |ui| {
+            changed |= ui
+                .checkbox(&mut prefs.enabled, "Write live data to file")
+                .on_hover_text("Continuously write the current time, scramble, and stats to a file for use in OBS overlays")
+                .changed();
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .selectable_value(&mut prefs.format, OverlayFormat::Text, "Text")
+                    .changed();
+                changed |= ui
+                    .selectable_value(&mut prefs.format, OverlayFormat::Json, "JSON")
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Choose file...").clicked() {
+                    let extension = match prefs.format {
+                        OverlayFormat::Text => "txt",
+                        OverlayFormat::Json => "json",
+                    };
+                    let file = rfd::FileDialog::new()
+                        .add_filter("Overlay file", &[extension])
+                        .set_file_name(&format!("hyperspeedcube_overlay.{extension}"))
+                        .save_file();
+                    if let Some(path) = file {
+                        prefs.path = Some(path);
+                        changed = true;
+                    }
+                }
+                match &prefs.path {
+                    Some(path) => ui.label(path.display().to_string()),
+                    None => ui.label("No file selected"),
+                };
+            });
+        });
+
+    app.prefs.needs_save |= changed;
+}
+
 #[derive(Debug)]
 pub(crate) enum Stopwatch {
     NotStarted,
-    Running(Instant),
+    /// Time when the start event was received, and how long before that it
+    /// actually happened.
+    Running(Instant, Duration),
     Stopped(Duration),
 }
 impl Stopwatch {
@@ -45,66 +443,208 @@ impl Stopwatch {
         *self = Stopwatch::NotStarted;
     }
 
-    fn start(&mut self) {
+    /// Starts the stopwatch for an event that happened `latency` ago.
+    fn start(&mut self, latency: Duration) {
         if let Self::NotStarted = self {
-            *self = Self::Running(Instant::now());
+            *self = Self::Running(Instant::now(), latency);
         } else {
             debug_assert!(false, "Can only start a NotStarted timer. This is a horrible unrecoverable logic error in the scope of timer, but it's recoverable in the scope of the entire program.");
             self.reset();
         }
     }
 
-    fn stop(&mut self) {
-        if let Self::Running(beginning) = *self {
-            *self = Self::Stopped(beginning.elapsed());
+    /// Stops the stopwatch for an event that happened `latency` ago.
+    fn stop(&mut self, latency: Duration) {
+        if let Self::Running(..) = *self {
+            *self = Self::Stopped(self.elapsed().saturating_sub(latency));
         } else {
             debug_assert!(false, "Can only stop a Running timer. This is a horrible unrecoverable logic error in the scope of timer, but it's recoverable in the scope of the entire program.");
             self.reset();
         }
     }
+
+    fn elapsed(&self) -> Duration {
+        match *self {
+            Self::NotStarted => Duration::ZERO,
+            Self::Running(beginning, latency) => beginning.elapsed() + latency,
+            Self::Stopped(duration) => duration,
+        }
+    }
 }
 
+/// Times since the start of inspection at which to play a warning, as in WCA
+/// competitions.
+const INSPECTION_CALLS: [Duration; 2] = [Duration::from_secs(8), Duration::from_secs(12)];
+
 #[derive(Debug)]
 pub(crate) struct Timer {
     stopwatch: Stopwatch,
     is_blind: bool,
+    /// Time when inspection started and the number of inspection calls made
+    /// so far, or `None` if the solver is not inspecting.
+    inspection: Option<(Instant, usize)>,
+    /// Time since the start of the solve at which each phase was finished.
+    splits: Vec<Duration>,
+    /// Time between a key being pressed and the app receiving it, which is
+    /// subtracted from the time of each start or stop event caused by input.
+    input_latency: Duration,
 }
 impl Timer {
     pub(crate) fn new() -> Self {
         Self {
             stopwatch: Stopwatch::NotStarted,
             is_blind: false,
+            inspection: None,
+            splits: vec![],
+            input_latency: Duration::ZERO,
+        }
+    }
+
+    pub(crate) fn set_input_latency(&mut self, input_latency: Duration) {
+        self.input_latency = input_latency;
+    }
+
+    /// Returns the current time as a string, or "Ready" if the timer has not
+    /// started.
+    pub(crate) fn display_str(&self) -> String {
+        match self.stopwatch {
+            Stopwatch::NotStarted => "Ready".into(),
+            _ => duration_to_str(self.stopwatch.elapsed()),
         }
     }
 
+    /// Returns the time since the timer started, or `None` if it is not
+    /// running.
+    pub(crate) fn elapsed(&self) -> Option<Duration> {
+        match self.stopwatch {
+            Stopwatch::Running(..) => Some(self.stopwatch.elapsed()),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the timer is currently running.
+    pub(crate) fn is_running(&self) -> bool {
+        matches!(self.stopwatch, Stopwatch::Running(..))
+    }
+    /// Returns whether inspection is counting down.
+    pub(crate) fn is_inspecting(&self) -> bool {
+        self.inspection.is_some()
+    }
+
     pub(crate) fn on_scramble(&mut self) {
         self.stopwatch.reset();
+        self.splits.clear();
         if self.is_blind {
-            self.stopwatch.start();
+            self.stopwatch.start(self.input_latency);
+        } else {
+            self.inspection = Some((Instant::now(), 0));
         }
     }
 
+    /// Starts the timer immediately so that it stays in sync with other
+    /// players in a race.
+    pub(crate) fn on_race_start(&mut self) {
+        self.inspection = None;
+        self.stopwatch.reset();
+        self.splits.clear();
+        // The race start comes from the server, not from a key press.
+        self.stopwatch.start(Duration::ZERO);
+    }
+
+    /// Records that a phase of the solve was finished and returns the time of
+    /// the split, or `None` if the timer is not running.
+    pub(crate) fn mark_split(&mut self) -> Option<Duration> {
+        let split = self.elapsed()?;
+        self.splits.push(split);
+        Some(split)
+    }
+    /// Returns the time since the start of the solve at which each phase was
+    /// finished.
+    pub(crate) fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+
     pub(crate) fn on_non_rotation_twist(&mut self) {
         // check if the twist is the first one
         if !self.is_blind && matches!(self.stopwatch, Stopwatch::NotStarted) {
-            self.stopwatch.start();
+            self.inspection = None;
+            self.stopwatch.start(self.input_latency);
+        }
+    }
+
+    /// Returns `true` once for each inspection call (after 8 and 12 seconds
+    /// of inspection) that is due.
+    pub(crate) fn take_inspection_call(&mut self) -> bool {
+        let Some((start, calls_made)) = &mut self.inspection else {
+            return false;
+        };
+        match INSPECTION_CALLS.get(*calls_made) {
+            Some(&call_time) if start.elapsed() >= call_time => {
+                *calls_made += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn is_blind(&self) -> bool {
+        self.is_blind
+    }
+
+    /// Starts the timer if it is not running, or stops it and returns the
+    /// final time if it is. This is for timing solves on a physical puzzle.
+    pub(crate) fn toggle_manual(&mut self) -> Option<Duration> {
+        if self.is_running() {
+            self.stopwatch.stop(self.input_latency);
+            self.stopped_duration()
+        } else {
+            self.inspection = None;
+            self.stopwatch.reset();
+            self.splits.clear();
+            self.stopwatch.start(self.input_latency);
+            None
         }
     }
 
-    pub(crate) fn on_solve(&mut self) {
-        if !self.is_blind {
-            self.stopwatch.stop();
+    /// Returns the final time if the solve stopped the timer.
+    pub(crate) fn on_solve(&mut self) -> Option<Duration> {
+        // The timer may have been stopped manually before the solve.
+        if !self.is_blind && self.is_running() {
+            self.stopwatch.stop(self.input_latency);
+            self.stopped_duration()
+        } else {
+            None
         }
     }
 
-    pub(crate) fn on_blindfold_off(&mut self) {
+    /// Returns the final time if removing the blindfold stopped the timer.
+    pub(crate) fn on_blindfold_off(&mut self) -> Option<Duration> {
         if self.is_blind {
-            self.stopwatch.stop();
+            self.stopwatch.stop(self.input_latency);
+            self.stopped_duration()
+        } else {
+            None
         }
     }
+
+    fn stopped_duration(&self) -> Option<Duration> {
+        match self.stopwatch {
+            Stopwatch::Stopped(duration) => Some(duration),
+            _ => None,
+        }
+    }
+}
+
+/// Formats a time in milliseconds, or "-" if there is none.
+pub(crate) fn time_ms_to_str(time_ms: Option<u64>) -> String {
+    match time_ms {
+        Some(DNF_MS) => "DNF".to_owned(),
+        Some(ms) => duration_to_str(Duration::from_millis(ms)),
+        None => "-".to_owned(),
+    }
 }
 
-fn duration_to_str(duration: Duration) -> String {
+pub(crate) fn duration_to_str(duration: Duration) -> String {
     let milliseconds = duration.as_millis();
     let seconds = milliseconds / 1000;
     let minutes = seconds / 60;
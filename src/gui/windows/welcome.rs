@@ -34,6 +34,13 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
 
     ui.label("");
 
+    if ui.button("Start interactive tutorial").clicked() {
+        app.tutorial.start();
+        super::WELCOME.set_open(ui.ctx(), false);
+    }
+
+    ui.label("");
+
     ui.horizontal_wrapped(|ui| {
         ui.label("Nearly every aspect of this program can be customized from the");
         ui.strong("Settings");
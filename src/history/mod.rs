@@ -0,0 +1,699 @@
+//! Persistent solve history, grouped into named sessions for each puzzle.
+//!
+//! The history is stored separately from preferences so that a corrupted or
+//! reset preferences file never loses any solves.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod persist_local;
+#[cfg(target_arch = "wasm32")]
+mod persist_web;
+mod stats;
+
+use crate::preferences::PerPuzzle;
+use crate::puzzle::{PuzzleType, PuzzleTypeEnum, TwistMetric};
+#[cfg(not(target_arch = "wasm32"))]
+use persist_local as persist;
+#[cfg(target_arch = "wasm32")]
+use persist_web as persist;
+pub use stats::*;
+
+/// Solve history file format version.
+const LATEST_VERSION: u32 = 1;
+
+/// Name of the session used when none has been created yet.
+pub const DEFAULT_SESSION_NAME: &str = "Main";
+/// Name of the session that daily challenge solves are recorded in.
+pub const DAILY_SESSION_NAME: &str = "Daily";
+/// Name of the session that solves imported from other programs are added to.
+pub const IMPORTED_SESSION_NAME: &str = "Imported";
+
+/// Tags offered for every solve, in addition to ones the user has made up.
+pub const SUGGESTED_TAGS: &[&str] = &["PB", "lucky", "popped", "lockup", "misscramble"];
+
+/// Returns the scramble seed for the daily challenge on a puzzle, which is the
+/// same for everyone on the same day (in UTC).
+pub fn daily_seed(day: i64, ty: PuzzleTypeEnum) -> u64 {
+    stable_hash(day.to_le_bytes().into_iter().chain(ty.name().bytes()))
+}
+
+/// Hashes bytes using FNV-1a, which unlike the standard library's hasher is
+/// guaranteed to give the same result on every platform and version.
+pub fn stable_hash(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    bytes.into_iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Returns a new random version 4 UUID to identify a solve.
+fn new_solve_id() -> String {
+    let bits = rand::random::<u128>();
+    // Set the version and variant bits.
+    let bits = bits & !(0xf000 << 64) & !(0xc << 60) | (0x4000 << 64) | (0x8 << 60);
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        bits >> 96,
+        (bits >> 80) & 0xffff,
+        (bits >> 64) & 0xffff,
+        (bits >> 48) & 0xffff,
+        bits & 0xffff_ffff_ffff,
+    )
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SolveHistory {
+    #[serde(skip)]
+    pub needs_save: bool,
+
+    /// Solve history file format version.
+    #[serde(skip_deserializing)]
+    pub version: u32,
+
+    /// Name of the active session for each puzzle.
+    pub active_session: PerPuzzle<String>,
+    /// Sessions for each puzzle.
+    pub sessions: PerPuzzle<Vec<Session>>,
+    /// Sessions for relays, each named after the puzzles in the relay.
+    pub relays: Vec<Session>,
+    /// IDs of deleted solves, so that syncing doesn't bring them back.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub deleted_solves: BTreeSet<String>,
+}
+impl SolveHistory {
+    pub fn load() -> Self {
+        let result = persist::load_string().and_then(|s| match s {
+            Some(s) => Ok(serde_yaml::from_str(&s)?),
+            None => Ok(Self::default()),
+        });
+        match result {
+            Ok(mut history) => {
+                history.assign_missing_ids();
+                history
+            }
+            Err(e) => {
+                log::error!("Error loading solve history: {}", e);
+                persist::backup_history_file();
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&mut self) {
+        if self.needs_save {
+            self.needs_save = false;
+
+            self.version = LATEST_VERSION;
+
+            match persist::save(self) {
+                Ok(()) => log::debug!("Saved solve history"),
+                Err(e) => log::error!("Error saving solve history: {}", e),
+            }
+        }
+    }
+
+    /// Adds solves imported from other programs to the imported session for
+    /// each puzzle, skipping any that were already imported or have been
+    /// deleted. Returns the number of solves added.
+    pub fn import_solves(&mut self, solves: Vec<(PuzzleTypeEnum, Solve)>) -> usize {
+        let mut count = 0;
+        for (ty, solve) in solves {
+            let is_known = self.deleted_solves.contains(&solve.id)
+                || self.sessions[ty]
+                    .iter()
+                    .flat_map(|session| &session.solves)
+                    .any(|s| s.id == solve.id);
+            if !is_known {
+                let session = self.session_mut(ty, IMPORTED_SESSION_NAME);
+                session.solves.push(solve);
+                session.solves.sort_by_key(|solve| solve.timestamp);
+                count += 1;
+            }
+        }
+        self.needs_save |= count > 0;
+        count
+    }
+
+    /// Returns the solve history to upload when syncing.
+    pub fn to_synced_string(&self) -> anyhow::Result<String> {
+        let history = SolveHistory {
+            version: LATEST_VERSION,
+            ..self.clone()
+        };
+        Ok(serde_yaml::to_string(&history)?)
+    }
+
+    /// Merges solves from another copy of the solve history, such as one
+    /// synced from another machine. Solves are matched by ID, so solves in
+    /// both copies are kept once, and solves deleted from either copy are
+    /// deleted from both. Active sessions are not changed.
+    ///
+    /// Returns whether any solves or sessions were added or deleted.
+    pub fn merge(&mut self, mut other: SolveHistory) -> bool {
+        other.assign_missing_ids();
+
+        let deleted_count = self.deleted_solves.len();
+        self.deleted_solves.extend(other.deleted_solves);
+        let mut changed = self.deleted_solves.len() != deleted_count;
+
+        for (puzzle_name, sessions) in other.sessions {
+            let ours = self.sessions.get_mut_by_name(puzzle_name);
+            changed |= merge_sessions(ours, sessions, &self.deleted_solves);
+        }
+        changed |= merge_sessions(&mut self.relays, other.relays, &self.deleted_solves);
+
+        let deleted = &self.deleted_solves;
+        for session in self.sessions.values_mut().flatten().chain(&mut self.relays) {
+            let count = session.solves.len();
+            session.solves.retain(|solve| !deleted.contains(&solve.id));
+            changed |= session.solves.len() != count;
+        }
+
+        self.needs_save |= changed;
+        changed
+    }
+
+    /// Gives an ID to every solve that doesn't have one.
+    fn assign_missing_ids(&mut self) {
+        let sessions = self.sessions.values_mut().flatten().chain(&mut self.relays);
+        for solve in sessions.flat_map(|session| &mut session.solves) {
+            if solve.id.is_empty() {
+                solve.id = solve.legacy_id();
+                self.needs_save = true;
+            }
+        }
+    }
+
+    /// Returns all sessions for a puzzle.
+    pub fn sessions(&self, ty: PuzzleTypeEnum) -> &[Session] {
+        &self.sessions[ty]
+    }
+
+    /// Returns the name of the active session for a puzzle.
+    pub fn active_session_name(&self, ty: PuzzleTypeEnum) -> &str {
+        let name: &str = &self.active_session[ty];
+        if name.is_empty() {
+            DEFAULT_SESSION_NAME
+        } else {
+            name
+        }
+    }
+    /// Returns the active session for a puzzle, or `None` if it has no solves
+    /// yet.
+    pub fn active_session(&self, ty: PuzzleTypeEnum) -> Option<&Session> {
+        let name = self.active_session_name(ty);
+        self.sessions[ty].iter().find(|s| s.name == name)
+    }
+    /// Returns the active session for a puzzle, creating it if it does not
+    /// exist.
+    pub fn active_session_mut(&mut self, ty: PuzzleTypeEnum) -> &mut Session {
+        let name = self.active_session_name(ty).to_owned();
+        self.session_mut(ty, &name)
+    }
+    /// Switches to a different session for a puzzle.
+    pub fn set_active_session(&mut self, ty: PuzzleTypeEnum, name: &str) {
+        self.active_session[ty] = name.to_owned();
+        self.needs_save = true;
+    }
+
+    /// Creates a new session and makes it active.
+    pub fn add_session(&mut self, ty: PuzzleTypeEnum, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Session name cannot be empty".to_owned());
+        }
+        if self.sessions[ty].iter().any(|s| s.name == name) {
+            return Err(format!("There is already a session named {name:?}"));
+        }
+        self.session_mut(ty, name);
+        self.set_active_session(ty, name);
+        Ok(())
+    }
+    /// Renames a session.
+    pub fn rename_session(
+        &mut self,
+        ty: PuzzleTypeEnum,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), String> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err("Session name cannot be empty".to_owned());
+        }
+        if self.sessions[ty].iter().any(|s| s.name == new_name) {
+            return Err(format!("There is already a session named {new_name:?}"));
+        }
+        self.session_mut(ty, old_name).name = new_name.to_owned();
+        if self.active_session_name(ty) == old_name {
+            self.active_session[ty] = new_name.to_owned();
+        }
+        self.needs_save = true;
+        Ok(())
+    }
+    /// Deletes a session and all its solves.
+    pub fn delete_session(&mut self, ty: PuzzleTypeEnum, name: &str) {
+        self.sessions[ty].retain(|s| s.name != name);
+        if self.active_session_name(ty) == name {
+            self.active_session[ty] = match self.sessions[ty].first() {
+                Some(s) => s.name.clone(),
+                None => String::new(),
+            };
+        }
+        self.needs_save = true;
+    }
+
+    /// Adds a solve to the active session for a puzzle and returns the
+    /// milestones it achieved, which are also recorded on the solve.
+    pub fn record_solve(&mut self, ty: PuzzleTypeEnum, solve: Solve) -> Vec<Milestone> {
+        let milestones = self.active_session_mut(ty).record(solve);
+        self.needs_save = true;
+        milestones
+    }
+
+    /// Adds a daily challenge solve to the daily session for a puzzle and
+    /// returns the milestones it achieved, which are also recorded on the
+    /// solve.
+    pub fn record_daily_solve(&mut self, ty: PuzzleTypeEnum, solve: Solve) -> Vec<Milestone> {
+        let milestones = self.session_mut(ty, DAILY_SESSION_NAME).record(solve);
+        self.needs_save = true;
+        milestones
+    }
+
+    /// Returns every tag used on a solve of a puzzle, including the
+    /// suggested ones.
+    pub fn all_tags(&self, ty: PuzzleTypeEnum) -> BTreeSet<String> {
+        let used = self.sessions[ty]
+            .iter()
+            .flat_map(|session| &session.solves)
+            .flat_map(|solve| &solve.tags)
+            .cloned();
+        SUGGESTED_TAGS
+            .iter()
+            .map(|&tag| tag.to_owned())
+            .chain(used)
+            .collect()
+    }
+
+    /// Deletes solves, given as the name of the session and the index of the
+    /// solve in it.
+    pub fn delete_solves(&mut self, ty: PuzzleTypeEnum, solves: &BTreeSet<(String, usize)>) {
+        let removed = self.remove_solves(ty, solves);
+        self.deleted_solves
+            .extend(removed.into_iter().map(|solve| solve.id));
+    }
+    /// Removes solves without recording them as deleted, given as the name of
+    /// the session and the index of the solve in it. Returns the removed
+    /// solves.
+    fn remove_solves(
+        &mut self,
+        ty: PuzzleTypeEnum,
+        solves: &BTreeSet<(String, usize)>,
+    ) -> Vec<Solve> {
+        let mut removed = vec![];
+        for session in &mut self.sessions[ty] {
+            // Remove from the end so that indices stay valid.
+            let in_session = solves.iter().filter(|(name, _)| *name == session.name);
+            for &(_, i) in in_session.rev() {
+                if i < session.solves.len() {
+                    removed.push(session.solves.remove(i));
+                }
+            }
+        }
+        self.needs_save = true;
+        removed
+    }
+    /// Moves solves to another session, given as the name of the session and
+    /// the index of the solve in it.
+    pub fn move_solves(
+        &mut self,
+        ty: PuzzleTypeEnum,
+        solves: &BTreeSet<(String, usize)>,
+        to_session: &str,
+    ) {
+        let to_move = solves
+            .iter()
+            .filter(|(name, _)| name != to_session)
+            .cloned()
+            .collect();
+        let moved = self.remove_solves(ty, &to_move);
+
+        let session = self.session_mut(ty, to_session);
+        session.solves.extend(moved);
+        session.solves.sort_by_key(|solve| solve.timestamp);
+        self.needs_save = true;
+    }
+    /// Returns a solve, given the name of its session and its index in it.
+    pub fn solve_mut(&mut self, ty: PuzzleTypeEnum, session: &str, i: usize) -> Option<&mut Solve> {
+        let session = self.sessions[ty].iter_mut().find(|s| s.name == session)?;
+        session.solves.get_mut(i)
+    }
+
+    /// Returns the session for a relay, or `None` if it has no solves yet.
+    pub fn relay_session(&self, relay_name: &str) -> Option<&Session> {
+        self.relays.iter().find(|s| s.name == relay_name)
+    }
+    /// Adds a solve to the session for a relay and returns the milestones it
+    /// achieved, which are also recorded on the solve.
+    pub fn record_relay_solve(&mut self, relay_name: &str, solve: Solve) -> Vec<Milestone> {
+        let session = match self.relays.iter().position(|s| s.name == relay_name) {
+            Some(i) => &mut self.relays[i],
+            None => {
+                self.relays.push(Session {
+                    name: relay_name.to_owned(),
+                    ..Default::default()
+                });
+                self.relays.last_mut().unwrap()
+            }
+        };
+        let milestones = session.record(solve);
+        self.needs_save = true;
+        milestones
+    }
+
+    fn session_mut(&mut self, ty: PuzzleTypeEnum, name: &str) -> &mut Session {
+        let sessions = &mut self.sessions[ty];
+        match sessions.iter().position(|s| s.name == name) {
+            Some(i) => &mut sessions[i],
+            None => {
+                sessions.push(Session {
+                    name: name.to_owned(),
+                    ..Default::default()
+                });
+                self.needs_save = true;
+                sessions.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+/// Named group of solves on a single puzzle.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Session {
+    pub name: String,
+    /// Target time in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_ms: Option<u64>,
+    /// Names of the phases of each solve, which are split by pressing a key.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub phases: Vec<String>,
+    pub solves: Vec<Solve>,
+}
+impl Session {
+    pub fn stats(&self) -> SessionStats {
+        SessionStats::compute(&self.solves)
+    }
+    /// Returns the mean time in milliseconds spent on each phase, over solves
+    /// with a split for every phase.
+    pub fn phase_means(&self) -> Vec<Option<u64>> {
+        phase_means(&self.solves, self.phases.len())
+    }
+
+    /// Adds a solve to the session and returns the milestones it achieved,
+    /// which are also recorded on the solve.
+    fn record(&mut self, mut solve: Solve) -> Vec<Milestone> {
+        if solve.id.is_empty() {
+            solve.id = new_solve_id();
+        }
+        let old_stats = self.stats();
+
+        let mut milestones = vec![];
+        if self
+            .target_ms
+            .map_or(false, |target| solve.result_ms() < target)
+        {
+            milestones.push(Milestone::Target);
+        }
+
+        self.solves.push(solve.clone());
+        let new_stats = self.stats();
+
+        // Don't celebrate the first solve or the first average.
+        let is_pb = |old: Option<u64>, new: Option<u64>| {
+            matches!((old, new), (Some(old), Some(new)) if new < old)
+        };
+        if is_pb(old_stats.best, new_stats.best) {
+            milestones.push(Milestone::PbSingle);
+        }
+        if is_pb(old_stats.best_ao5, new_stats.best_ao5) {
+            milestones.push(Milestone::PbAo5);
+        }
+        if is_pb(old_stats.best_ao12, new_stats.best_ao12) {
+            milestones.push(Milestone::PbAo12);
+        }
+
+        solve.milestones = milestones.clone();
+        *self.solves.last_mut().unwrap() = solve;
+        milestones
+    }
+}
+
+/// Single completed solve.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Solve {
+    /// Unique ID of the solve, used to match up solves when syncing. Solves
+    /// recorded before IDs existed get one derived from their contents, so
+    /// that they get the same ID on every machine.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    /// Solve duration in milliseconds.
+    pub time_ms: u64,
+    /// Whether the solve has no recorded duration, such as one imported from a
+    /// log file. Untimed solves are left out of time statistics.
+    #[serde(skip_serializing_if = "is_false")]
+    pub untimed: bool,
+    /// Unix timestamp (in seconds) of when the solve was completed.
+    pub timestamp: i64,
+    /// Number of twists in the solution, counted using `metric`.
+    pub twist_count: usize,
+    pub metric: TwistMetric,
+    /// Whether the solve was done in blind mode.
+    #[serde(skip_serializing_if = "is_false")]
+    pub blind: bool,
+    /// Penalty that the user gave the solve.
+    #[serde(skip_serializing_if = "Penalty::is_none")]
+    pub penalty: Penalty,
+    /// Seed that the scramble was generated from, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Name and version of the program that generated the scramble, if it
+    /// was generated by this program.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scrambler: Option<String>,
+    /// Scramble twists, separated by spaces.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub scramble: String,
+    /// Twists of the solution, separated by spaces, which can be replayed
+    /// after the scramble.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub solution: String,
+    /// For a relay, time in milliseconds at which each puzzle was solved,
+    /// measured from the start of the relay.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub splits_ms: Vec<u64>,
+    /// Time in milliseconds at which each phase except the last was finished,
+    /// measured from the start of the solve.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub phase_splits_ms: Vec<u64>,
+    /// Notable achievements of this solve at the time it was completed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub milestones: Vec<Milestone>,
+    /// For a 3D cube, case of the last layer when it was reached, such as
+    /// "OLL skip, T perm".
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub last_layer_case: String,
+    /// For a 3x3x3, method that the solve seemed to use, such as "CFOP".
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub method: String,
+
+    /// Whether the user starred the solve.
+    #[serde(skip_serializing_if = "is_false")]
+    pub starred: bool,
+    /// Tags the user added to the solve, such as "lucky" or "popped".
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    /// Comment the user wrote on the solve.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub comment: String,
+}
+
+impl Solve {
+    /// Returns an ID derived from the contents of a solve that was recorded
+    /// before solves had IDs.
+    fn legacy_id(&self) -> String {
+        let bytes = self
+            .timestamp
+            .to_le_bytes()
+            .into_iter()
+            .chain(self.time_ms.to_le_bytes())
+            .chain((self.twist_count as u64).to_le_bytes())
+            .chain(self.scramble.bytes());
+        format!("legacy-{:016x}", stable_hash(bytes))
+    }
+
+    /// Returns the time in milliseconds with the penalty applied, which is
+    /// [`DNF_MS`] for a DNF.
+    pub fn result_ms(&self) -> u64 {
+        match self.penalty {
+            Penalty::None => self.time_ms,
+            Penalty::PlusTwo => self.time_ms + PLUS_TWO_MS,
+            Penalty::Dnf => DNF_MS,
+        }
+    }
+    /// Returns the time in milliseconds with the penalty applied, or `None`
+    /// if the solve is untimed.
+    pub fn timed_result_ms(&self) -> Option<u64> {
+        (!self.untimed).then(|| self.result_ms())
+    }
+}
+
+/// Penalty for a solve.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Penalty {
+    #[default]
+    None,
+    /// Two seconds are added to the time.
+    PlusTwo,
+    /// The solve did not finish.
+    Dnf,
+}
+impl fmt::Display for Penalty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Penalty::None => write!(f, "OK"),
+            Penalty::PlusTwo => write!(f, "+2"),
+            Penalty::Dnf => write!(f, "DNF"),
+        }
+    }
+}
+impl Penalty {
+    pub fn is_none(&self) -> bool {
+        *self == Penalty::None
+    }
+}
+
+/// Notable achievement of a solve.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Milestone {
+    /// Faster than the session's target time.
+    Target,
+    /// New personal best single in the session.
+    PbSingle,
+    /// New personal best average of 5 in the session.
+    PbAo5,
+    /// New personal best average of 12 in the session.
+    PbAo12,
+}
+impl Milestone {
+    pub fn description(self) -> &'static str {
+        match self {
+            Milestone::Target => "Beat target time",
+            Milestone::PbSingle => "New PB single",
+            Milestone::PbAo5 => "New PB ao5",
+            Milestone::PbAo12 => "New PB ao12",
+        }
+    }
+
+    /// Returns whether the milestone is a new personal best.
+    pub fn is_personal_best(self) -> bool {
+        matches!(
+            self,
+            Milestone::PbSingle | Milestone::PbAo5 | Milestone::PbAo12,
+        )
+    }
+}
+
+/// Adds sessions and solves from `theirs` that are not already in `ours` and
+/// have not been deleted, matching solves by ID and sessions by name. Returns
+/// whether anything was added.
+fn merge_sessions(
+    ours: &mut Vec<Session>,
+    theirs: Vec<Session>,
+    deleted_ids: &BTreeSet<String>,
+) -> bool {
+    let known_ids = ours
+        .iter()
+        .flat_map(|session| &session.solves)
+        .map(|solve| solve.id.clone())
+        .collect::<HashSet<_>>();
+
+    let mut changed = false;
+    for mut session in theirs {
+        let new_solves = std::mem::take(&mut session.solves)
+            .into_iter()
+            .filter(|solve| !known_ids.contains(&solve.id) && !deleted_ids.contains(&solve.id))
+            .collect::<Vec<_>>();
+        match ours.iter_mut().find(|s| s.name == session.name) {
+            Some(existing) => {
+                if !new_solves.is_empty() {
+                    existing.solves.extend(new_solves);
+                    existing.solves.sort_by_key(|solve| solve.timestamp);
+                    changed = true;
+                }
+            }
+            None => {
+                ours.push(Session {
+                    solves: new_solves,
+                    ..session
+                });
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+fn is_false(x: &bool) -> bool {
+    !x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let solve = |id: &str, timestamp| Solve {
+            id: id.to_owned(),
+            timestamp,
+            ..Default::default()
+        };
+        let session = |name: &str, solves: Vec<Solve>| Session {
+            name: name.to_owned(),
+            solves,
+            ..Default::default()
+        };
+
+        let mut ours = SolveHistory::default();
+        ours.sessions[ty] = vec![session("Main", vec![solve("a", 1), solve("c", 3)])];
+        ours.deleted_solves.insert("d".to_owned());
+
+        let mut theirs = SolveHistory::default();
+        theirs.sessions[ty] = vec![
+            session("Main", vec![solve("a", 1), solve("b", 2), solve("d", 4)]),
+            session("OH", vec![solve("e", 5)]),
+        ];
+        theirs.deleted_solves.insert("c".to_owned());
+
+        assert!(ours.merge(theirs.clone()));
+        let ids = |history: &SolveHistory, i: usize| -> Vec<String> {
+            let solves = &history.sessions(ty)[i].solves;
+            solves.iter().map(|s| s.id.clone()).collect()
+        };
+        assert_eq!(ids(&ours, 0), ["a", "b"]);
+        assert_eq!(ours.sessions(ty)[1].name, "OH");
+        assert_eq!(ids(&ours, 1), ["e"]);
+
+        // Merging again changes nothing.
+        assert!(!ours.merge(theirs));
+    }
+}
@@ -0,0 +1,58 @@
+use serde::Serialize;
+use std::path::PathBuf;
+
+const HISTORY_FILE_NAME: &str = "hyperspeedcube_history";
+const HISTORY_FILE_EXTENSION: &str = "yaml";
+
+fn history_file_path() -> anyhow::Result<PathBuf> {
+    let mut p = crate::preferences::config_dir()?;
+    p.push(format!("{}.{}", HISTORY_FILE_NAME, HISTORY_FILE_EXTENSION));
+    Ok(p)
+}
+
+/// Returns the contents of the solve history file, or `None` if it does not
+/// exist yet.
+pub fn load_string() -> anyhow::Result<Option<String>> {
+    let path = history_file_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(std::fs::read_to_string(path)?))
+}
+
+pub fn save(history_data: &impl Serialize) -> anyhow::Result<()> {
+    let path = history_file_path()?;
+    if let Some(p) = path.parent() {
+        std::fs::create_dir_all(p)?;
+    }
+    serde_yaml::to_writer(std::fs::File::create(path)?, history_data)?;
+    Ok(())
+}
+
+pub fn backup_history_file() {
+    if let Ok(history_path) = history_file_path() {
+        let mut backup_path = history_path.clone();
+        backup_path.pop();
+
+        let now =
+            time::OffsetDateTime::now_local().unwrap_or_else(|_| time::OffsetDateTime::now_utc());
+        backup_path.push(format!(
+            "{}_{:04}-{:02}-{:02}_{:02}-{:02}-{:02}_bak.{}",
+            HISTORY_FILE_NAME,
+            now.year(),
+            now.month() as u8,
+            now.day(),
+            now.hour(),
+            now.minute(),
+            now.second(),
+            HISTORY_FILE_EXTENSION,
+        ));
+
+        if std::fs::rename(history_path, &backup_path).is_ok() {
+            log::info!(
+                "Backup of old solve history stored at {}",
+                backup_path.display(),
+            );
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use anyhow::anyhow;
+use serde::Serialize;
+
+const HISTORY_KEY: &str = "hyperspeedcube_history";
+const HISTORY_BACKUP_KEY: &str = "hyperspeedcube_history_bak";
+
+/// Returns the saved solve history, or `None` if there is none yet.
+pub fn load_string() -> anyhow::Result<Option<String>> {
+    Ok(local_storage()?.get_item(HISTORY_KEY).ok().flatten())
+}
+
+pub fn save(history_data: &impl Serialize) -> anyhow::Result<()> {
+    let history_string = serde_yaml::to_string(history_data).map_err(|e| anyhow!(e))?;
+    local_storage()?
+        .set_item(HISTORY_KEY, &history_string)
+        .map_err(|e| anyhow!(format!("{e:?}")))
+}
+
+pub fn backup_history_file() {
+    if let Ok(storage) = local_storage() {
+        if let Ok(Some(s)) = storage.get_item(HISTORY_KEY) {
+            if storage.set_item(HISTORY_BACKUP_KEY, &s).is_ok() {
+                log::info!("Backup of old solve history stored in {HISTORY_BACKUP_KEY:?}");
+            }
+        }
+    }
+}
+
+fn local_storage() -> anyhow::Result<web_sys::Storage> {
+    web_sys::window()
+        .unwrap()
+        .local_storage()
+        .unwrap()
+        .ok_or_else(|| anyhow!("unable to access browser local storage"))
+}
@@ -0,0 +1,168 @@
+use super::{Penalty, Solve};
+
+/// Time in milliseconds that stands for a DNF, which is worse than any other
+/// time.
+pub const DNF_MS: u64 = u64::MAX;
+/// Time in milliseconds added by a +2 penalty.
+pub const PLUS_TWO_MS: u64 = 2000;
+
+/// Summary statistics for a group of solves. All times are in milliseconds,
+/// with penalties applied, and may be [`DNF_MS`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SessionStats {
+    pub count: usize,
+    pub best: Option<u64>,
+    pub worst: Option<u64>,
+    pub mean: Option<u64>,
+
+    pub current_ao5: Option<u64>,
+    pub best_ao5: Option<u64>,
+    pub current_ao12: Option<u64>,
+    pub best_ao12: Option<u64>,
+}
+impl SessionStats {
+    pub fn compute(solves: &[Solve]) -> Self {
+        let times = solves
+            .iter()
+            .filter_map(Solve::timed_result_ms)
+            .collect::<Vec<_>>();
+        let finished = times.iter().filter(|&&t| t != DNF_MS).collect::<Vec<_>>();
+
+        let (current_ao5, best_ao5) = rolling_average(&times, 5);
+        let (current_ao12, best_ao12) = rolling_average(&times, 12);
+
+        Self {
+            count: solves.len(),
+            best: times.iter().copied().min(),
+            worst: times.iter().copied().max(),
+            // The mean leaves out DNFs.
+            mean: (!finished.is_empty())
+                .then(|| finished.iter().copied().sum::<u64>() / finished.len() as u64),
+
+            current_ao5,
+            best_ao5,
+            current_ao12,
+            best_ao12,
+        }
+    }
+}
+
+/// Returns the mean time spent on each of `phase_count` phases, over solves
+/// with a split for every phase.
+pub fn phase_means(solves: &[Solve], phase_count: usize) -> Vec<Option<u64>> {
+    let mut sums = vec![0; phase_count];
+    let mut count = 0;
+    for solve in solves {
+        if phase_count == 0
+            || solve.phase_splits_ms.len() != phase_count - 1
+            || solve.penalty == Penalty::Dnf
+        {
+            continue;
+        }
+        let ends = solve.phase_splits_ms.iter().chain([&solve.time_ms]);
+        let mut start = 0;
+        for (sum, &end) in sums.iter_mut().zip(ends) {
+            *sum += end.saturating_sub(start);
+            start = end;
+        }
+        count += 1;
+    }
+    sums.into_iter()
+        .map(|sum| (count > 0).then(|| sum / count))
+        .collect()
+}
+
+/// Returns the most recent and best averages of `n` consecutive times.
+fn rolling_average(times: &[u64], n: usize) -> (Option<u64>, Option<u64>) {
+    if n == 0 || times.len() < n {
+        return (None, None);
+    }
+    let averages = times.windows(n).filter_map(trimmed_average);
+    let current = trimmed_average(&times[times.len() - n..]);
+    (current, averages.min())
+}
+
+/// Returns the average of `times` following the WCA regulations, after
+/// dropping the best and worst 5% of times (rounded up, and at least one
+/// each). DNFs count as the worst times, so the average is [`DNF_MS`] if there
+/// are more DNFs than dropped times. Returns `None` if there are fewer than
+/// three times.
+pub fn trimmed_average(times: &[u64]) -> Option<u64> {
+    if times.len() < 3 {
+        return None;
+    }
+    let trim = ((times.len() * 5 + 99) / 100).max(1);
+    let mut sorted = times.to_vec();
+    sorted.sort_unstable();
+    let counted = &sorted[trim..sorted.len() - trim];
+    if counted.contains(&DNF_MS) {
+        return Some(DNF_MS);
+    }
+    Some(counted.iter().sum::<u64>() / counted.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DNF: u64 = DNF_MS;
+
+    #[test]
+    fn test_trimmed_average() {
+        assert_eq!(trimmed_average(&[]), None);
+        assert_eq!(trimmed_average(&[1000, 2000]), None);
+        assert_eq!(trimmed_average(&[1000, 2000, 9000]), Some(2000));
+        assert_eq!(trimmed_average(&[5000, 1000, 4000, 2000, 3000]), Some(3000));
+
+        // A single DNF is dropped as the worst time.
+        assert_eq!(trimmed_average(&[1000, DNF, 3000, 4000, 5000]), Some(4000));
+        // Two DNFs make the average a DNF.
+        assert_eq!(trimmed_average(&[1000, DNF, 3000, DNF, 5000]), Some(DNF));
+        assert_eq!(trimmed_average(&[DNF; 5]), Some(DNF));
+
+        // ao12 drops one time from each end.
+        let mut times = vec![1000; 11];
+        times.push(DNF);
+        assert_eq!(trimmed_average(&times), Some(1000));
+        times[0] = DNF;
+        assert_eq!(trimmed_average(&times), Some(DNF));
+
+        // ao100 drops five times from each end.
+        let mut times = (1..=100).map(|i| i * 1000).collect::<Vec<_>>();
+        times[95..].fill(DNF);
+        assert_eq!(trimmed_average(&times), Some(50_500));
+        times[94] = DNF;
+        assert_eq!(trimmed_average(&times), Some(DNF));
+    }
+
+    #[test]
+    fn test_session_stats_penalties() {
+        let solve = |time_ms, penalty| Solve {
+            time_ms,
+            penalty,
+            ..Default::default()
+        };
+        let solves = [
+            solve(10_000, Penalty::None),
+            solve(9_000, Penalty::PlusTwo),
+            solve(8_000, Penalty::Dnf),
+            solve(12_000, Penalty::None),
+            solve(13_000, Penalty::None),
+        ];
+        let stats = SessionStats::compute(&solves);
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.best, Some(10_000));
+        assert_eq!(stats.worst, Some(DNF));
+        assert_eq!(stats.mean, Some(11_500));
+        // 11.000 (+2) and 12.000 and 13.000 remain after dropping 10.000 and
+        // the DNF.
+        assert_eq!(stats.current_ao5, Some(12_000));
+        assert_eq!(stats.best_ao5, Some(12_000));
+        assert_eq!(stats.current_ao12, None);
+
+        let mut solves = solves.to_vec();
+        solves[0].penalty = Penalty::Dnf;
+        let stats = SessionStats::compute(&solves);
+        assert_eq!(stats.current_ao5, Some(DNF));
+    }
+}
@@ -0,0 +1,112 @@
+//! Hotkeys that work even when the window is not focused, such as when the
+//! timer is running beside another app or on a second monitor.
+
+use livesplit_hotkey::{Hook, Hotkey, KeyCode, Modifiers};
+use winit::event_loop::EventLoopProxy;
+
+use crate::app::AppEvent;
+use crate::preferences::HotkeyPreferences;
+
+/// Hotkeys registered with the operating system.
+#[derive(Default)]
+pub(crate) struct GlobalHotkeys {
+    /// Hook that listens for hotkeys. Dropping it unregisters all of them.
+    hook: Option<Hook>,
+    /// Preferences that the current hotkeys were registered from.
+    registered: Option<HotkeyPreferences>,
+}
+impl GlobalHotkeys {
+    /// Registers hotkeys again if the preferences have changed. Returns an
+    /// error message for each hotkey that could not be registered.
+    pub(crate) fn update(
+        &mut self,
+        prefs: &HotkeyPreferences,
+        events: &EventLoopProxy<AppEvent>,
+    ) -> Vec<String> {
+        if self.registered.as_ref() == Some(prefs) {
+            return vec![];
+        }
+        self.registered = Some(prefs.clone());
+        self.hook = None;
+        if !prefs.enabled {
+            return vec![];
+        }
+
+        let hook = match Hook::new() {
+            Ok(hook) => hook,
+            Err(e) => return vec![format!("Unable to listen for global hotkeys: {e}")],
+        };
+        let mut errors = vec![];
+        for (key, command) in prefs.bindings() {
+            if key.trim().is_empty() {
+                continue;
+            }
+            let Some(hotkey) = parse_hotkey(key) else {
+                errors.push(format!("Invalid global hotkey {key:?}"));
+                continue;
+            };
+            let events = events.clone();
+            let result = hook.register(hotkey, move || {
+                // The event loop is gone if the app is closing.
+                let _ = events.send_event(AppEvent::Command(command.clone()));
+            });
+            if let Err(e) = result {
+                errors.push(format!("Unable to register global hotkey {key:?}: {e}"));
+            }
+        }
+        self.hook = Some(hook);
+        errors
+    }
+}
+
+/// Parses a hotkey such as `Ctrl+Shift+F9`. The key may be a single letter or
+/// digit, or a key code name such as `Space` or `Numpad1`.
+fn parse_hotkey(s: &str) -> Option<Hotkey> {
+    let mut parts = s.split('+').map(str::trim);
+    let key = parts.next_back()?;
+
+    let mut modifiers = Modifiers::empty();
+    for modifier in parts {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Modifiers::CONTROL,
+            "shift" => Modifiers::SHIFT,
+            "alt" => Modifiers::ALT,
+            "meta" | "super" | "win" | "cmd" => Modifiers::META,
+            _ => return None,
+        };
+    }
+
+    let key_code_name = match key.as_bytes() {
+        [c] if c.is_ascii_alphabetic() => format!("Key{}", c.to_ascii_uppercase() as char),
+        [c] if c.is_ascii_digit() => format!("Digit{}", *c as char),
+        _ => key.to_owned(),
+    };
+    let key_code = key_code_name.parse::<KeyCode>().ok()?;
+
+    Some(Hotkey {
+        key_code,
+        modifiers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hotkey() {
+        let hotkey = parse_hotkey("Ctrl + Shift+F9").unwrap();
+        assert_eq!(hotkey.key_code, KeyCode::F9);
+        assert_eq!(hotkey.modifiers, Modifiers::CONTROL | Modifiers::SHIFT);
+
+        let hotkey = parse_hotkey("a").unwrap();
+        assert_eq!(hotkey.key_code, KeyCode::KeyA);
+        assert_eq!(hotkey.modifiers, Modifiers::empty());
+
+        assert_eq!(parse_hotkey("Alt+5").unwrap().key_code, KeyCode::Digit5);
+
+        assert!(parse_hotkey("").is_none());
+        assert!(parse_hotkey("Hyper+X").is_none());
+        assert!(parse_hotkey("Ctrl+NotAKey").is_none());
+    }
+}
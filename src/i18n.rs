@@ -0,0 +1,231 @@
+//! Localization of user-facing strings using [Fluent](https://projectfluent.org/).
+//!
+//! Translations live in `resources/locales/<code>.ftl`. Messages missing from
+//! a translation fall back to English.
+//!
+//! Tooltips are attributes of the message for the control they explain:
+//! `.title` for the bold heading and `.tooltip` for the explanation.
+//!
+//! Translated so far: menus, window titles, the status bar, preferences
+//! (including their tooltips), and twist direction names. Other window
+//! contents are still English only.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+use strum::IntoEnumIterator;
+
+/// Returns the translation of a message in the current language, optionally
+/// with named arguments.
+///
+/// ```ignore
+/// tr!("status-fps", fps = 60)
+/// ```
+macro_rules! tr {
+    ($id:expr $(,)?) => {
+        $crate::i18n::tr($id)
+    };
+    ($id:expr, $($arg:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($arg), $value);)+
+        $crate::i18n::tr_args($id, &args)
+    }};
+}
+
+static CURRENT_LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+lazy_static! {
+    /// Bundle of messages for each language, indexed by `Language as usize`.
+    static ref BUNDLES: Vec<FluentBundle<FluentResource>> =
+        Language::iter().map(make_bundle).collect();
+}
+
+/// Language for the user interface.
+#[derive(Serialize, Deserialize, EnumIter, Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "de")]
+    German,
+}
+impl Language {
+    /// Returns the name of the language, written in that language.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    /// Returns the Unicode language identifier for the language.
+    fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    /// Returns the Fluent source for the language.
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../resources/locales/en.ftl"),
+            Language::German => include_str!("../resources/locales/de.ftl"),
+        }
+    }
+}
+
+fn make_bundle(language: Language) -> FluentBundle<FluentResource> {
+    let langid = language
+        .code()
+        .parse()
+        .expect("invalid language identifier");
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // Unicode isolation marks show up as boxes in egui.
+    bundle.set_use_isolating(false);
+
+    let resource = FluentResource::try_new(language.source().to_owned()).unwrap_or_else(
+        |(resource, errors)| {
+            log::error!("Error parsing {} translation: {:?}", language.code(), errors);
+            resource
+        },
+    );
+    if let Err(errors) = bundle.add_resource(resource) {
+        log::error!("Error loading {} translation: {:?}", language.code(), errors);
+    }
+
+    bundle
+}
+
+/// Sets the language used for translations.
+pub fn set_language(language: Language) {
+    CURRENT_LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+/// Returns the language used for translations.
+pub fn language() -> Language {
+    let index = CURRENT_LANGUAGE.load(Ordering::Relaxed) as usize;
+    Language::iter().nth(index).unwrap_or_default()
+}
+
+/// Returns the translation of a message in the current language.
+pub fn tr(id: &str) -> String {
+    translate(language(), id, None, None).unwrap_or_else(|| missing(id))
+}
+/// Returns the translation of a message with arguments in the current
+/// language.
+pub fn tr_args(id: &str, args: &FluentArgs<'_>) -> String {
+    translate(language(), id, None, Some(args)).unwrap_or_else(|| missing(id))
+}
+/// Returns the translation of an attribute of a message in the current
+/// language, or an empty string if the message doesn't have that attribute.
+pub fn tr_attr(id: &str, attr: &str) -> String {
+    translate(language(), id, Some(attr), None).unwrap_or_default()
+}
+
+/// Returns the translation of the name of a twist direction, such as "CW".
+/// Names without a translation are left as-is.
+pub fn twist_direction_name(name: &str) -> String {
+    translate(language(), &message_id("twist", name), None, None).unwrap_or_else(|| name.to_owned())
+}
+
+fn translate(
+    language: Language,
+    id: &str,
+    attr: Option<&str>,
+    args: Option<&FluentArgs<'_>>,
+) -> Option<String> {
+    [language, Language::English]
+        .into_iter()
+        .find_map(|language| {
+            let bundle = &BUNDLES[language as usize];
+            let message = bundle.get_message(id)?;
+            let pattern = match attr {
+                Some(attr) => message.get_attribute(attr)?.value(),
+                None => message.value()?,
+            };
+            let mut errors = vec![];
+            let s = bundle.format_pattern(pattern, args, &mut errors);
+            for e in errors {
+                log::warn!("Error formatting message {id:?}: {e}");
+            }
+            Some(s.into_owned())
+        })
+}
+fn missing(id: &str) -> String {
+    log::debug!("Missing message {id:?}");
+    id.to_owned()
+}
+
+/// Returns a message ID derived from English text, such as `window-piece-filters`
+/// for `"Piece filters"` with the prefix `window`.
+pub fn message_id(prefix: &str, english: &str) -> String {
+    let mut id = prefix.to_owned();
+    for word in english
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        id.push('-');
+        id.push_str(&word.to_ascii_lowercase());
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_id() {
+        assert_eq!(message_id("window", "Piece filters"), "window-piece-filters");
+        assert_eq!(message_id("window", "About"), "window-about");
+    }
+
+    #[test]
+    fn test_translations_parse() {
+        for language in Language::iter() {
+            if let Err((_, errors)) = FluentResource::try_new(language.source().to_owned()) {
+                panic!("error parsing {} translation: {errors:?}", language.code());
+            }
+        }
+    }
+
+    #[test]
+    fn test_fallback_to_english() {
+        let tr_de = |id| translate(Language::German, id, None, None);
+        assert_eq!(tr_de("menu-file").as_deref(), Some("Datei"));
+        assert_eq!(tr_de("nonexistent-message"), None);
+        let tr_en = |id| translate(Language::English, id, None, None);
+        assert_eq!(tr_en("menu-file").as_deref(), Some("File"));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let tooltip = |language| translate(language, "prefs-gfx-fps-limit", Some("tooltip"), None);
+        assert_eq!(
+            tooltip(Language::English).as_deref(),
+            Some("Limits framerate to save power"),
+        );
+        assert_ne!(tooltip(Language::German), tooltip(Language::English));
+        let missing = translate(
+            Language::English,
+            "prefs-gfx-show-fps",
+            Some("tooltip"),
+            None,
+        );
+        assert_eq!(missing, None);
+    }
+
+    #[test]
+    fn test_german_has_every_message() {
+        let german = &BUNDLES[Language::German as usize];
+        let ids = Language::English
+            .source()
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split_once(" =").map(|(id, _)| id));
+        for id in ids {
+            assert!(german.has_message(id), "missing German message {id:?}");
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Logger that keeps recent log records in memory so that they can be viewed
+//! in the app without a terminal.
+
+use instant::{Duration, Instant};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Maximum number of log records kept in memory. Older records are discarded
+/// first.
+const CAPACITY: usize = 2000;
+/// Prefix of the log targets that are recorded. Both the app and the core
+/// crate start with this.
+const TARGET_PREFIX: &str = "hyperspeedcube";
+
+lazy_static! {
+    static ref RECORDS: Mutex<VecDeque<LogRecord>> = Mutex::new(VecDeque::new());
+    static ref START: Instant = Instant::now();
+}
+
+/// Most verbose level that is recorded, as a `log::LevelFilter`.
+static CAPTURE_LEVEL: AtomicUsize = AtomicUsize::new(if cfg!(debug_assertions) {
+    log::LevelFilter::Debug as usize
+} else {
+    log::LevelFilter::Info as usize
+});
+/// Level at which records are passed on to the terminal or console, stored so
+/// that the maximum log level can be recomputed when the capture level
+/// changes.
+static INNER_LEVEL: AtomicUsize = AtomicUsize::new(log::LevelFilter::Off as usize);
+
+/// Log record kept in memory.
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub level: log::Level,
+    /// Module that emitted the record, such as `hyperspeedcube::app`.
+    pub target: String,
+    pub message: String,
+    /// Time since the logger was initialized.
+    pub time: Duration,
+}
+
+struct Logger {
+    /// Level at which records are passed on to the terminal or console.
+    inner_level: log::LevelFilter,
+    #[cfg(not(target_arch = "wasm32"))]
+    inner: env_logger::Logger,
+}
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= self.inner_level || is_captured(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.inner.log(record);
+        #[cfg(target_arch = "wasm32")]
+        if record.level() <= self.inner_level {
+            log_to_web_console(record);
+        }
+
+        if is_captured(record.metadata()) {
+            let mut records = RECORDS.lock().unwrap();
+            if records.len() >= CAPACITY {
+                records.pop_front();
+            }
+            records.push_back(LogRecord {
+                level: record.level(),
+                target: record.target().to_owned(),
+                message: record.args().to_string(),
+                time: START.elapsed(),
+            });
+        }
+    }
+
+    fn flush(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.inner.flush();
+    }
+}
+
+fn is_captured(metadata: &log::Metadata<'_>) -> bool {
+    metadata.level() <= capture_level() && metadata.target().starts_with(TARGET_PREFIX)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn log_to_web_console(record: &log::Record<'_>) {
+    let s = format!("{} {}: {}", record.level(), record.target(), record.args()).into();
+    match record.level() {
+        log::Level::Error => web_sys::console::error_1(&s),
+        log::Level::Warn => web_sys::console::warn_1(&s),
+        _ => web_sys::console::log_1(&s),
+    }
+}
+
+/// Installs the global logger.
+pub fn init() {
+    lazy_static::initialize(&START);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let logger = {
+        let inner = env_logger::builder()
+            .filter_module(
+                "hyperspeedcube",
+                if cfg!(debug_assertions) {
+                    log::LevelFilter::Debug
+                } else {
+                    log::LevelFilter::Warn
+                },
+            )
+            .build();
+        Logger {
+            inner_level: inner.filter(),
+            inner,
+        }
+    };
+    #[cfg(target_arch = "wasm32")]
+    let logger = Logger {
+        inner_level: log::LevelFilter::Warn,
+    };
+
+    let inner_level = logger.inner_level;
+    log::set_boxed_logger(Box::new(logger)).expect("logger already initialized");
+    log::set_max_level(std::cmp::max(inner_level, capture_level()));
+    INNER_LEVEL.store(inner_level as usize, Ordering::Relaxed);
+}
+
+/// Returns the most verbose level that is recorded.
+pub fn capture_level() -> log::LevelFilter {
+    level_filter_from_usize(CAPTURE_LEVEL.load(Ordering::Relaxed))
+}
+/// Sets the most verbose level that is recorded.
+pub fn set_capture_level(level: log::LevelFilter) {
+    CAPTURE_LEVEL.store(level as usize, Ordering::Relaxed);
+    let inner_level = level_filter_from_usize(INNER_LEVEL.load(Ordering::Relaxed));
+    log::set_max_level(std::cmp::max(inner_level, level));
+}
+fn level_filter_from_usize(n: usize) -> log::LevelFilter {
+    log::LevelFilter::iter()
+        .find(|&level| level as usize == n)
+        .unwrap_or(log::LevelFilter::Off)
+}
+
+/// Returns a copy of the recorded log records, oldest first.
+pub fn records() -> Vec<LogRecord> {
+    RECORDS.lock().unwrap().iter().cloned().collect()
+}
+/// Returns a copy of the recorded log records, or `None` if they are being
+/// modified. Unlike `records()`, this never blocks or panics, so it is safe to
+/// call while handling a panic.
+pub fn try_records() -> Option<Vec<LogRecord>> {
+    Some(RECORDS.try_lock().ok()?.iter().cloned().collect())
+}
+/// Discards all recorded log records.
+pub fn clear() {
+    RECORDS.lock().unwrap().clear();
+}
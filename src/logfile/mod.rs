@@ -1,30 +1,36 @@
+//! Saving and loading puzzle states using the log file formats from
+//! `hyperspeedcube_core`.
+
 use anyhow::{anyhow, Context, Result};
-use bitvec::vec::BitVec;
+use itertools::Itertools;
 use num_enum::FromPrimitive;
-use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
-use std::error::Error;
-use std::fmt;
+use std::collections::{BTreeMap, HashMap};
 #[cfg(not(target_arch = "wasm32"))]
 use std::path::Path;
 use std::str::FromStr;
 use strum::IntoEnumIterator;
 
 mod mc4d_compat;
+#[cfg(test)]
+mod replay_tests;
+
+pub use hyperspeedcube_core::logfile::LogFileFormat;
+use hyperspeedcube_core::logfile::{LogBranch, LogFile};
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::history::{stable_hash, Solve};
 use crate::puzzle::*;
 
 /// Loads a log file string and returns the puzzle state, along with any
 /// warnings.
 pub fn deserialize(log_file_contents: &str) -> anyhow::Result<(PuzzleController, Vec<String>)> {
     if mc4d_compat::is_mc4d_log_file(log_file_contents) {
-        let puzzle = mc4d_compat::Mc4dLogFile::from_str(log_file_contents)?
-            .to_puzzle()
-            .map_err(|e| anyhow!(e))?;
+        let log_file = mc4d_compat::Mc4dLogFile::from_str(log_file_contents)?;
+        let puzzle = mc4d_compat::to_puzzle(&log_file).map_err(|e| anyhow!(e))?;
         let warnings = vec![];
         Ok((puzzle, warnings))
     } else {
-        serde_yaml::from_str::<LogFile>(log_file_contents)?.to_puzzle()
+        log_file_to_puzzle(&serde_yaml::from_str(log_file_contents)?)
     }
 }
 
@@ -34,8 +40,8 @@ pub(crate) fn serialize(
     format: LogFileFormat,
 ) -> anyhow::Result<String> {
     match format {
-        LogFileFormat::Hsc => Ok(LogFile::new(puzzle).to_string()),
-        LogFileFormat::Mc4d => Ok(mc4d_compat::Mc4dLogFile::from_puzzle(puzzle)?.to_string()),
+        LogFileFormat::Hsc => Ok(log_file_from_puzzle(puzzle).to_string()),
+        LogFileFormat::Mc4d => Ok(mc4d_compat::from_puzzle(puzzle)?.to_string()),
     }
 }
 
@@ -57,201 +63,253 @@ pub fn save_file(path: &Path, puzzle: &mut PuzzleController) -> anyhow::Result<(
         }
     }
 
+    puzzle.record_progress_snapshot();
     std::fs::write(path, serialize(puzzle, format)?)?;
 
     Ok(())
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
-pub enum LogFileFormat {
-    #[default]
-    Hsc,
-    Mc4d,
-}
-impl LogFileFormat {
-    pub fn extension(self) -> &'static str {
-        match self {
-            LogFileFormat::Hsc => "hsc",
-            LogFileFormat::Mc4d => "log",
+/// Reads every MagicCube4D log file of a solved puzzle in a directory and its
+/// subdirectories, for importing into the solve history. Returns the solves,
+/// along with errors for files that could not be read. Log files of unsolved
+/// puzzles are skipped.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn import_mc4d_solves(
+    dir: &Path,
+    metric: TwistMetric,
+) -> (Vec<(PuzzleTypeEnum, Solve)>, Vec<String>) {
+    let mut solves = vec![];
+    let mut errors = vec![];
+
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                errors.push(format!("{}: {e}", dir.display()));
+                continue;
+            }
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .map_or(false, |ext| ext.eq_ignore_ascii_case("log"))
+            {
+                match import_mc4d_solve(&path, metric) {
+                    Ok(Some(solve)) => solves.push(solve),
+                    Ok(None) => (),
+                    Err(e) => errors.push(format!("{}: {e}", path.display())),
+                }
+            }
         }
     }
+
+    (solves, errors)
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct LogFile {
-    version: usize,
-    #[serde(default)]
-    puzzle: Option<PuzzleTypeEnum>,
-    #[serde(default)]
-    state: u8,
-    #[serde(
-        default,
-        skip_serializing_if = "Option::is_none",
-        with = "crate::serde_impl::hex_bitvec::opt"
-    )]
-    visible_pieces: Option<BitVec>,
-    #[serde(
-        default,
-        skip_serializing_if = "cgmath::Zero::is_zero",
-        skip_deserializing
-    )]
-    scramble_length: usize,
-    #[serde(default, skip_deserializing)]
-    twist_count: BTreeMap<TwistMetric, usize>,
-    #[serde(default, skip_serializing)] // manually serialized
-    scramble: String,
-    #[serde(default, skip_serializing)] // manually serialized
-    twists: String,
+/// Reads a MagicCube4D log file as a solve, or returns `None` if it is not a
+/// MagicCube4D log file or the puzzle was not solved.
+#[cfg(not(target_arch = "wasm32"))]
+fn import_mc4d_solve(
+    path: &Path,
+    metric: TwistMetric,
+) -> anyhow::Result<Option<(PuzzleTypeEnum, Solve)>> {
+    let contents = std::fs::read_to_string(path)?;
+    if !mc4d_compat::is_mc4d_log_file(&contents) {
+        return Ok(None);
+    }
+    let log_file = mc4d_compat::Mc4dLogFile::from_str(&contents)?;
+    let puzzle = mc4d_compat::to_puzzle(&log_file).map_err(|e| anyhow!(e))?;
+    if puzzle.scramble_state() != ScrambleState::Solved {
+        return Ok(None);
+    }
+
+    // MagicCube4D log files don't record when the solve happened or how long
+    // it took, so use the time that the file was last modified.
+    let modified = std::fs::metadata(path)?.modified()?;
+    let timestamp = modified.duration_since(std::time::UNIX_EPOCH)?.as_secs() as i64;
+
+    let solve = Solve {
+        // Derive the ID from the contents so that importing the same file
+        // again doesn't add it twice.
+        id: format!("mc4d-{:016x}", stable_hash(contents.bytes())),
+        untimed: true,
+        timestamp,
+        twist_count: puzzle.twist_count(metric),
+        metric,
+        scramble: puzzle.scramble().iter().join(" "),
+        solution: puzzle.niss_solution().iter().join(" "),
+        ..Default::default()
+    };
+    Ok(Some((puzzle.ty(), solve)))
 }
-impl fmt::Display for LogFile {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "{}", Self::COMMENT_STRING)?;
-        write!(
-            f,
-            "{}",
-            serde_yaml::to_string(self).map_err(|_| fmt::Error)?,
-        )?;
-        if !self.scramble.is_empty() {
-            writeln!(f, "scramble: >")?;
-            for line in self.scramble.lines() {
-                writeln!(f, "  {line}")?;
-            }
-        }
-        if !self.twists.is_empty() {
-            writeln!(f, "twists: >")?;
-            for line in self.twists.lines() {
-                writeln!(f, "  {line}")?;
-            }
-        }
-        Ok(())
+
+/// Returns the log file for a puzzle state.
+fn log_file_from_puzzle(puzzle: &PuzzleController) -> LogFile {
+    let notation = puzzle.notation_scheme();
+
+    LogFile {
+        version: LogFile::VERSION,
+        puzzle: Some(puzzle.ty()),
+        state: puzzle.scramble_state() as u8,
+        visible_pieces: puzzle
+            .is_any_piece_hidden()
+            .then(|| puzzle.visible_pieces().to_bitvec()),
+        scramble_length: puzzle.scramble().len(),
+        twist_count: TwistMetric::iter()
+            .map(|metric| (metric, puzzle.twist_count(metric)))
+            .collect(),
+        progress: puzzle.progress_history().to_vec(),
+        annotations: puzzle.path_annotations(),
+        branches: log_branches(puzzle),
+        inverse: puzzle.is_inverse(),
+        premoves: puzzle
+            .premoves()
+            .iter()
+            .map(|&twist| notation.twist_to_string(twist))
+            .join(" "),
+        scramble: crate::util::wrap_words(puzzle.scramble().iter().map(|twist| twist.to_string())),
+        twists: crate::util::wrap_words(
+            puzzle
+                .undo_buffer()
+                .iter()
+                .map(|&entry| entry.to_string(notation)),
+        ),
     }
 }
-impl LogFile {
-    const COMMENT_STRING: &'static str = "# Hyperspeedcube puzzle log";
-    const VERSION: usize = 1;
-
-    fn new(puzzle: &PuzzleController) -> Self {
-        let notation = puzzle.notation_scheme();
-
-        Self {
-            version: Self::VERSION,
-            puzzle: Some(puzzle.ty()),
-            state: puzzle.scramble_state() as u8,
-            visible_pieces: puzzle
-                .is_any_piece_hidden()
-                .then(|| puzzle.visible_pieces().to_bitvec()),
-            scramble_length: puzzle.scramble().len(),
-            twist_count: TwistMetric::iter()
-                .map(|metric| (metric, puzzle.twist_count(metric)))
-                .collect(),
-            scramble: crate::util::wrap_words(
-                puzzle.scramble().iter().map(|twist| twist.to_string()),
-            ),
-            twists: crate::util::wrap_words(
-                puzzle
-                    .undo_buffer()
-                    .iter()
-                    .map(|&entry| entry.to_string(notation)),
-            ),
-        }
+
+/// Returns the puzzle state from a log file, along with any warnings.
+fn log_file_to_puzzle(log_file: &LogFile) -> Result<(PuzzleController, Vec<String>)> {
+    log_file.validate().map_err(|e| anyhow!(e))?;
+
+    let mut warnings = vec![];
+
+    if log_file.version != LogFile::VERSION {
+        warnings.push(format!(
+            "This log file was saved using a \
+             different version of Hyperspeedcube \
+             (log file format v{:?}; expected v{:?})",
+            log_file.version,
+            LogFile::VERSION,
+        ));
     }
 
-    fn validate(&self) -> Result<()> {
-        if let Some(puzzle_ty) = self.puzzle {
-            puzzle_ty.validate().map_err(|e| anyhow!(e))?;
-        }
-        Ok(())
+    let puzzle_type = log_file.puzzle.context("unable to find puzzle type")?;
+    let mut ret = PuzzleController::new(puzzle_type);
+
+    let scramble_state = ScrambleState::from_primitive(log_file.state);
+
+    if let Some(visible_pieces) = &log_file.visible_pieces {
+        ret.set_visible_pieces(visible_pieces);
     }
 
-    fn scramble(&self) -> (Vec<Twist>, Vec<TwistParseError<'_>>) {
-        let mut ret_twists = vec![];
-        let mut ret_errors = vec![];
-        for twist_str in self.scramble.split_whitespace() {
-            match twist_str.parse() {
-                Ok(twist) => ret_twists.push(twist),
-                Err(()) => ret_errors.push(TwistParseError {
-                    twist_str,
-                    error_msg: "invalid twist".to_string(),
-                }),
-            }
+    let (twists, parse_errors) = log_file.scramble();
+    warnings.extend(parse_errors.iter().map(|e| e.to_string()));
+    for twist in twists {
+        if let Err(e) = ret.twist_no_collapse(twist) {
+            warnings.push(e.to_string());
         }
-        (ret_twists, ret_errors)
     }
+    ret.add_scramble_marker(scramble_state);
 
-    fn twists(&self, puzzle_type: &dyn PuzzleType) -> (Vec<Twist>, Vec<TwistParseError<'_>>) {
-        let mut ret_twists = vec![];
-        let mut ret_errors = vec![];
-        for twist_str in self.twists.split_whitespace() {
-            match puzzle_type.notation_scheme().parse_twist(twist_str) {
-                Ok(twist) => ret_twists.push(twist),
-                Err(error_msg) => ret_errors.push(TwistParseError {
-                    twist_str,
-                    error_msg,
-                }),
-            }
+    let (premoves, parse_errors) = log_file.premoves(&puzzle_type);
+    warnings.extend(parse_errors.iter().map(|e| e.to_string()));
+    if log_file.inverse || !premoves.is_empty() {
+        if let Err(e) = ret.set_inverse_and_premoves(log_file.inverse, premoves) {
+            warnings.push(e.to_string());
         }
-        (ret_twists, ret_errors)
     }
 
-    fn to_puzzle(&self) -> Result<(PuzzleController, Vec<String>)> {
-        self.validate()?;
-
-        let mut warnings = vec![];
-
-        if self.version != LogFile::VERSION {
-            warnings.push(format!(
-                "This log file was saved using a \
-                 different version of Hyperspeedcube \
-                 (log file format v{:?}; expected v{:?})",
-                self.version,
-                LogFile::VERSION,
-            ));
+    let (twists, parse_errors) = log_file.twists(&puzzle_type);
+    warnings.extend(parse_errors.iter().map(|e| e.to_string()));
+    for twist in twists {
+        if let Err(e) = ret.twist_no_collapse(twist) {
+            warnings.push(e.to_string());
         }
+    }
+    ret.set_progress_history(log_file.progress.clone());
+    ret.set_path_annotations(&log_file.annotations);
+    add_branches(log_file, &mut ret, &puzzle_type, &mut warnings);
+    ret.skip_twist_animations();
+    ret.mark_saved();
 
-        let puzzle_type = self.puzzle.context("unable to find puzzle type")?;
-        let mut ret = PuzzleController::new(puzzle_type);
+    Ok((ret, warnings))
+}
 
-        let scramble_state = ScrambleState::from_primitive(self.state);
+/// Returns the branches of the undo history that are not on the path to the
+/// current state.
+fn log_branches(puzzle: &PuzzleController) -> Vec<LogBranch> {
+    let tree = puzzle.undo_tree();
+    let notation = puzzle.notation_scheme();
 
-        if let Some(visible_pieces) = &self.visible_pieces {
-            ret.set_visible_pieces(visible_pieces);
-        }
+    // Branch and number of twists since the scramble for each node
+    let mut positions: HashMap<UndoNodeId, (usize, usize)> = tree
+        .path_nodes()
+        .into_iter()
+        .enumerate()
+        .map(|(i, node)| (node, (0, i)))
+        .collect();
 
-        let (twists, parse_errors) = self.scramble();
-        warnings.extend(parse_errors.iter().map(|e| e.to_string()));
-        for twist in twists {
-            if let Err(e) = ret.twist_no_collapse(twist) {
-                warnings.push(e.to_string());
+    let mut ret = vec![];
+    for (i, nodes) in tree.side_branches().into_iter().enumerate() {
+        let (from_branch, from_twist) = positions[&nodes[0]];
+        let mut annotations = BTreeMap::new();
+        for (j, &node) in nodes.iter().enumerate().skip(1) {
+            positions.insert(node, (i + 1, from_twist + j));
+            if !tree.annotation(node).is_empty() {
+                annotations.insert(from_twist + j, tree.annotation(node).to_owned());
             }
         }
-        ret.add_scramble_marker(scramble_state);
+        ret.push(LogBranch {
+            from_branch,
+            from_twist,
+            twists: crate::util::wrap_words(
+                nodes[1..]
+                    .iter()
+                    .filter_map(|&node| tree.entry(node))
+                    .map(|entry| entry.to_string(notation)),
+            ),
+            annotations,
+        });
+    }
+    ret
+}
 
-        let (twists, parse_errors) = self.twists(&puzzle_type);
+/// Adds the branches of the undo history from a log file to a puzzle.
+fn add_branches(
+    log_file: &LogFile,
+    puzzle: &mut PuzzleController,
+    puzzle_type: &dyn PuzzleType,
+    warnings: &mut Vec<String>,
+) {
+    // Number of twists since the scramble at the start of each branch, and the
+    // nodes along it
+    let mut branch_nodes = vec![(0, puzzle.undo_tree().path_nodes())];
+    for branch in &log_file.branches {
+        let (twists, parse_errors) = LogFile::parse_twists(&branch.twists, puzzle_type);
         warnings.extend(parse_errors.iter().map(|e| e.to_string()));
-        for twist in twists {
-            if let Err(e) = ret.twist_no_collapse(twist) {
-                warnings.push(e.to_string());
-            }
-        }
-        ret.skip_twist_animations();
-        ret.mark_saved();
 
-        Ok((ret, warnings))
-    }
-}
+        let from = branch_nodes
+            .get(branch.from_branch)
+            .and_then(|(start, nodes)| nodes.get(branch.from_twist.checked_sub(*start)?));
+        let Some(&from) = from else {
+            warnings.push(format!(
+                "Undo history branch leaves from missing twist {} of branch {}",
+                branch.from_twist, branch.from_branch,
+            ));
+            branch_nodes.push((0, vec![]));
+            continue;
+        };
 
-#[derive(Debug)]
-struct TwistParseError<'a> {
-    twist_str: &'a str,
-    error_msg: String,
-}
-impl fmt::Display for TwistParseError<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Error parsing twist {:?}: {}",
-            self.twist_str, self.error_msg,
-        )
+        let mut nodes = vec![from];
+        nodes.extend(puzzle.add_undo_branch(from, twists.into_iter().map(HistoryEntry::from)));
+        for (&i, annotation) in &branch.annotations {
+            if let Some(&node) = i.checked_sub(branch.from_twist).and_then(|j| nodes.get(j)) {
+                puzzle.set_annotation(node, annotation);
+            }
+        }
+        branch_nodes.push((branch.from_twist, nodes));
     }
 }
-impl Error for TwistParseError<'_> {}
@@ -0,0 +1,149 @@
+//! Regression tests that replay recorded solves from `tests/fixtures/solves`.
+//!
+//! Each fixture is an ordinary `.hsc` log file saved by the app. The scramble
+//! state, scramble length, and twist counts recorded in the file are what the
+//! app computed when the solve was saved, so replaying the file must reproduce
+//! them exactly. If a change to puzzle logic breaks one of these tests, either
+//! the change is wrong or every existing log file is now being misread.
+//!
+//! The fields in the file are written by the same code that is under test, so
+//! [`EXPECTED`] also pins the final state and move count of each fixture
+//! independently.
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::*;
+
+const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/solves");
+
+/// Expected outcome of replaying each fixture: file name, whether the puzzle
+/// ends up solved, and the number of twists in STM.
+const EXPECTED: &[(&str, bool, usize)] = &[
+    ("2x2x2_single_twist.hsc", true, 1),
+    ("3x3x3_branches.hsc", true, 2),
+    ("3x3x3_half_turns.hsc", true, 3),
+    ("3x3x3_sexy_move.hsc", true, 4),
+    ("3x3x3_t_perm.hsc", true, 15),
+    ("3x3x3x3_solve.hsc", true, 9),
+    ("3x3x3x3_unfinished.hsc", false, 3),
+];
+
+/// Fields of a log file that are written for reference but ignored when
+/// loading.
+#[derive(Deserialize, Debug)]
+struct Recorded {
+    state: u8,
+    scramble_length: usize,
+    twist_count: BTreeMap<TwistMetric, usize>,
+}
+
+fn fixture_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(FIXTURES_DIR)
+        .expect("error reading fixtures directory")
+        .map(|entry| entry.expect("error reading fixture").path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "hsc"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no fixtures found in {FIXTURES_DIR}");
+    paths
+}
+
+fn replay(path: &Path) -> (String, PuzzleController) {
+    let contents = std::fs::read_to_string(path).expect("error reading fixture");
+    let (puzzle, warnings) = deserialize(&contents)
+        .unwrap_or_else(|e| panic!("{}: error loading log file: {e}", path.display()));
+    assert!(warnings.is_empty(), "{}: {warnings:?}", path.display());
+    (contents, puzzle)
+}
+
+#[test]
+fn test_replay_recorded_solves() {
+    for path in fixture_paths() {
+        let (contents, puzzle) = replay(&path);
+        let recorded: Recorded = serde_yaml::from_str(&contents).unwrap();
+        let name = path.display();
+
+        let state = ScrambleState::from_primitive(recorded.state);
+        assert_eq!(puzzle.scramble_state(), state, "{name}");
+        assert_eq!(puzzle.scramble().len(), recorded.scramble_length, "{name}");
+        if state == ScrambleState::Solved {
+            assert!(puzzle.is_solved(), "{name}: replayed solve is not solved");
+        }
+
+        for metric in TwistMetric::iter() {
+            let expected = recorded.twist_count.get(&metric).copied();
+            assert_eq!(
+                Some(puzzle.twist_count(metric)),
+                expected,
+                "{name}: wrong {metric} twist count",
+            );
+        }
+    }
+}
+
+#[test]
+fn test_replay_final_state() {
+    let paths = fixture_paths();
+    assert_eq!(paths.len(), EXPECTED.len(), "every fixture needs an entry");
+    for (path, &(file_name, solved, stm)) in paths.iter().zip(EXPECTED) {
+        assert_eq!(path.file_name().unwrap(), file_name);
+        let (_, puzzle) = replay(path);
+        assert_eq!(puzzle.is_solved(), solved, "{file_name}: wrong final state");
+        assert_eq!(
+            puzzle.twist_count(TwistMetric::Stm),
+            stm,
+            "{file_name}: wrong move count",
+        );
+    }
+}
+
+#[test]
+fn test_replay_undo_branches() {
+    let (_, puzzle) = replay(&Path::new(FIXTURES_DIR).join("3x3x3_branches.hsc"));
+    let tree = puzzle.undo_tree();
+    let notation = puzzle.notation_scheme();
+    let branches = tree
+        .side_branches()
+        .into_iter()
+        .map(|nodes| {
+            nodes[1..]
+                .iter()
+                .map(|&node| tree.entry(node).unwrap().to_string(notation))
+                .join(" ")
+        })
+        .collect_vec();
+    assert_eq!(branches, ["R' U'", "U"]);
+
+    let wrong_order = tree.side_branches()[0][2];
+    assert_eq!(tree.annotation(wrong_order), "wrong order");
+    assert!(!puzzle.has_redo());
+}
+
+#[test]
+fn test_recorded_solves_round_trip() {
+    for path in fixture_paths() {
+        let (_, puzzle) = replay(&path);
+        let name = path.display();
+
+        let saved = serialize(&puzzle, LogFileFormat::Hsc).unwrap();
+        let (reloaded, warnings) = deserialize(&saved).unwrap();
+        assert!(warnings.is_empty(), "{name}: {warnings:?}");
+
+        assert_eq!(reloaded.scramble(), puzzle.scramble(), "{name}");
+        assert_eq!(reloaded.undo_buffer(), puzzle.undo_buffer(), "{name}");
+        assert_eq!(reloaded.redo_buffer(), puzzle.redo_buffer(), "{name}");
+        assert_eq!(
+            reloaded.undo_tree().node_count(),
+            puzzle.undo_tree().node_count(),
+            "{name}: wrong number of undo history nodes",
+        );
+        assert_eq!(reloaded.scramble_state(), puzzle.scramble_state(), "{name}");
+        assert_eq!(reloaded.is_solved(), puzzle.is_solved(), "{name}");
+        assert_eq!(
+            serialize(&reloaded, LogFileFormat::Hsc).unwrap(),
+            saved,
+            "{name}: saving is not stable",
+        );
+    }
+}
@@ -13,8 +13,6 @@
 #[macro_use]
 extern crate ambassador;
 #[macro_use]
-extern crate enum_dispatch;
-#[macro_use]
 extern crate lazy_static;
 #[macro_use]
 extern crate strum;
@@ -30,37 +28,71 @@ use winit::platform::web::WindowBuilderExtWebSys;
 
 #[macro_use]
 mod debug;
+#[macro_use]
+mod i18n;
 mod app;
+mod audio;
+mod camera;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
 mod commands;
+#[cfg(not(target_arch = "wasm32"))]
+mod crash;
+#[cfg(not(target_arch = "wasm32"))]
+mod detached;
 mod gui;
+mod history;
+#[cfg(not(target_arch = "wasm32"))]
+mod hotkeys;
 #[cfg(not(target_arch = "wasm32"))]
 mod icon;
+mod log_buffer;
 mod logfile;
+#[cfg(not(target_arch = "wasm32"))]
+mod net;
+#[cfg(not(target_arch = "wasm32"))]
+mod overlay;
 mod preferences;
 pub mod puzzle;
 mod render;
+#[cfg(not(target_arch = "wasm32"))]
+mod scripting;
 mod serde_impl;
 mod util;
 #[cfg(target_arch = "wasm32")]
+mod web_files;
+#[cfg(target_arch = "wasm32")]
 mod web_workarounds;
 
 use app::App;
 
 const TITLE: &str = "Hyperspeedcube";
 
+/// Time without input or animation after which the framerate is lowered, if
+/// idle mode is enabled.
+const IDLE_DELAY: Duration = Duration::from_secs(2);
+
+/// Initial size of the window in mini mode.
+#[cfg(not(target_arch = "wasm32"))]
+const MINI_MODE_WINDOW_SIZE: winit::dpi::LogicalSize<f64> = winit::dpi::LogicalSize {
+    width: 360.0,
+    height: 160.0,
+};
+
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
     // Initialize logging.
-    env_logger::builder()
-        .filter_module(
-            "hyperspeedcube",
-            if cfg!(debug_assertions) {
-                log::LevelFilter::Debug
-            } else {
-                log::LevelFilter::Warn
-            },
-        )
-        .init();
+    log_buffer::init();
+
+    // Run a subcommand without opening a window, if one is given.
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(result) = cli::run(&args) {
+        if let Err(e) = result {
+            eprintln!("error: {e:#}");
+            std::process::exit(1);
+        }
+        return;
+    }
 
     let human_panic_metadata = human_panic::Metadata {
         name: TITLE.into(),
@@ -71,13 +103,14 @@ fn main() {
 
     let std_panic_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
+        let crash_report_dir = crash::write_report(info);
         let file_path = human_panic::handle_dump(&human_panic_metadata, info);
         human_panic::print_msg(file_path.as_ref(), &human_panic_metadata)
             .expect("human-panic: printing error message to console failed");
 
         rfd::MessageDialog::new()
             .set_title(&format!("{TITLE} crashed"))
-            .set_description(&match file_path {
+            .set_description(&match crash_report_dir.or(file_path) {
                 Some(fp) => format!(
                     "A crash report has been saved to \"{}\"\n\n\
                      Please submit this to the developer",
@@ -97,7 +130,7 @@ fn main() {
 #[cfg(target_arch = "wasm32")]
 fn main() {
     // Initialize logging.
-    wasm_logger::init(wasm_logger::Config::new(log::Level::Warn));
+    log_buffer::init();
 
     // Log panics using `console.error`.
     console_error_panic_hook::set_once();
@@ -114,7 +147,9 @@ async fn run() {
     #[cfg(not(target_arch = "wasm32"))]
     let window_builder = winit::window::WindowBuilder::new()
         .with_title(crate::TITLE)
-        .with_window_icon(icon::load_application_icon());
+        .with_window_icon(icon::load_application_icon())
+        // Only see-through in mini mode, and only if enabled.
+        .with_transparent(true);
     #[cfg(target_arch = "wasm32")]
     let window_builder =
         winit::window::WindowBuilder::new().with_canvas(Some(find_canvas_element()));
@@ -133,11 +168,24 @@ async fn run() {
     // Initialize egui.
     let egui_ctx = egui::Context::default();
     let mut egui_winit_state = egui_winit::State::new(&event_loop);
-    match dark_light::detect() {
-        dark_light::Mode::Light => switch_to_light_mode(&egui_ctx),
-        dark_light::Mode::Dark => switch_to_dark_mode(&egui_ctx),
-        dark_light::Mode::Default => switch_to_dark_mode(&egui_ctx),
-    };
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let egui_ctx = egui_ctx.clone();
+        egui_winit_state.init_accesskit(&window, event_loop.create_proxy(), move || {
+            // Accessibility is only enabled once a screen reader asks for it.
+            egui_ctx.enable_accesskit();
+            egui_ctx.request_repaint();
+            egui_ctx.accesskit_placeholder_tree_update()
+        });
+    }
+    let mut os_is_dark = preferences::os_is_dark();
+    let mut current_theme = None;
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut was_mini_mode = false;
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut size_before_mini_mode = None;
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut is_always_on_top = false;
     let mut egui_renderer = egui_wgpu::Renderer::new(&gfx.device, gfx.config.format, None, 1);
     let puzzle_texture_id = egui_renderer.register_native_texture(
         &gfx.device,
@@ -160,11 +208,27 @@ async fn run() {
     #[cfg(not(target_arch = "wasm32"))]
     let mut request_paste = false;
 
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut detached_windows = detached::DetachedWindows::default();
+
     // Begin main loop.
     let mut next_frame_time = Instant::now();
-    event_loop.run(move |ev, _ev_loop, control_flow| {
+    let mut last_activity = Instant::now();
+    event_loop.run(move |ev, ev_loop, control_flow| {
+        #[cfg(target_arch = "wasm32")]
+        let _ = ev_loop;
+
         let mut event_has_been_captured = false;
 
+        // Wake up immediately on input.
+        if let Event::WindowEvent { .. } | Event::UserEvent(_) = &ev {
+            let now = Instant::now();
+            if now - last_activity > IDLE_DELAY {
+                next_frame_time = now;
+            }
+            last_activity = now;
+        }
+
         #[cfg(target_arch = "wasm32")]
         let ev = {
             web_workarounds.generate_modifiers_changed_event(&ev);
@@ -261,10 +325,9 @@ async fn run() {
                         gfx.set_scale_factor(*scale_factor as f32);
                         gfx.resize(**new_inner_size);
                     }
-                    WindowEvent::ThemeChanged(theme) => match theme {
-                        winit::window::Theme::Light => switch_to_light_mode(&egui_ctx),
-                        winit::window::Theme::Dark => switch_to_dark_mode(&egui_ctx),
-                    },
+                    WindowEvent::ThemeChanged(theme) => {
+                        os_is_dark = matches!(theme, winit::window::Theme::Dark);
+                    }
                     _ => {
                         if !event_has_been_captured {
                             app.handle_window_event(&event);
@@ -281,6 +344,17 @@ async fn run() {
                 }
             }
 
+            // Handle events for panels in other windows.
+            #[cfg(not(target_arch = "wasm32"))]
+            Event::WindowEvent { window_id, event } => {
+                detached_windows.handle_window_event(window_id, &event, &gfx, &egui_ctx, &mut app);
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            Event::UserEvent(app::AppEvent::AccessKitActionRequest(request)) => {
+                egui_winit_state.on_accesskit_action_request(request.request);
+            }
+
             // Handle application-specific events.
             Event::UserEvent(event) => {
                 let r = app.handle_app_event(event, control_flow);
@@ -292,6 +366,10 @@ async fn run() {
                         request_paste |= request_paste;
                     }
                 }
+                if r.toggle_command_palette {
+                    let palette = gui::windows::COMMAND_PALETTE;
+                    palette.set_open(&egui_ctx, !palette.is_open(&egui_ctx));
+                }
                 if let Some(copy_string) = r.copy_string {
                     #[cfg(target_arch = "wasm32")]
                     web_workarounds
@@ -303,8 +381,12 @@ async fn run() {
 
             Event::MainEventsCleared => {
                 // RedrawRequested will only trigger once unless we manually
-                // request it.
-                window.request_redraw();
+                // request it. Sleep until the next frame instead of spinning.
+                if Instant::now() >= next_frame_time {
+                    window.request_redraw();
+                } else {
+                    control_flow.set_wait_until(next_frame_time);
+                }
             }
 
             Event::RedrawRequested(window_id) if window_id == window.id() => {
@@ -312,7 +394,45 @@ async fn run() {
 
                 if next_frame_time <= now {
                     // Update scale factor.
-                    egui_winit_state.set_pixels_per_point(gfx.scale_factor);
+                    let pixels_per_point = app.prefs.gfx.pixels_per_point(gfx.scale_factor);
+                    egui_winit_state.set_pixels_per_point(pixels_per_point);
+
+                    crate::i18n::set_language(app.prefs.language);
+
+                    // Update theme.
+                    let colors = &app.prefs.colors;
+                    let theme = (colors.theme.is_dark(os_is_dark), colors.accent);
+                    if current_theme != Some(theme) {
+                        if current_theme.map(|(is_dark, _)| is_dark) != Some(theme.0) {
+                            // At startup or when the OS theme changes, switch
+                            // to the default colors for the theme unless the
+                            // user has customized them.
+                            app.prefs.switch_theme_colors(!theme.0, theme.0);
+                            app.request_redraw_puzzle();
+                        }
+                        current_theme = Some(theme);
+                        set_style(&egui_ctx, gui::theme_visuals(theme.0, theme.1));
+                    }
+
+                    // Update window for mini mode.
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        if app.mini_mode != was_mini_mode {
+                            was_mini_mode = app.mini_mode;
+                            if app.mini_mode {
+                                size_before_mini_mode = Some(window.inner_size());
+                                window.set_inner_size(MINI_MODE_WINDOW_SIZE);
+                            } else if let Some(size) = size_before_mini_mode.take() {
+                                window.set_inner_size(size);
+                            }
+                        }
+                        let always_on_top = app.mini_mode && app.prefs.mini_mode.always_on_top;
+                        if always_on_top != is_always_on_top {
+                            is_always_on_top = always_on_top;
+                            window.set_always_on_top(always_on_top);
+                        }
+                    }
+                    let is_transparent = gfx.set_transparent(gui::is_window_transparent(&app));
 
                     // Start egui frame.
                     #[allow(unused_mut)]
@@ -337,10 +457,12 @@ async fn run() {
                         }
                     }
 
+                    let ui_start = Instant::now();
                     let egui_output = egui_ctx.run(egui_input, |ctx| {
                         // Build all the UI.
                         gui::build(ctx, &mut app, puzzle_texture_id);
                     });
+                    app.render_cache.timings.ui.record(ui_start.elapsed());
 
                     // Handle cut & copy on web, which winit *should* do for us.
                     #[cfg(target_arch = "wasm32")]
@@ -360,6 +482,9 @@ async fn run() {
                     if app.prefs.needs_save {
                         app.prefs.save();
                     }
+                    if app.history.needs_save {
+                        app.history.save();
+                    }
 
                     #[cfg(target_arch = "wasm32")]
                     if app.puzzle.is_unsaved_in_local_storage() {
@@ -380,9 +505,21 @@ async fn run() {
 
                         // Request a repaint.
                         egui_ctx.request_repaint();
+
+                        last_activity = now;
                     }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    app.take_requested_screenshot(&mut gfx);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    app.export_requested_replay(&mut gfx);
 
-                    let frame_duration = app.prefs.gfx.frame_duration();
+                    // egui requests an immediate repaint while its UI is
+                    // animating.
+                    if app.is_animating() || egui_output.repaint_after.is_zero() {
+                        last_activity = now;
+                    }
+                    let is_idle = now - last_activity > IDLE_DELAY;
+                    let frame_duration = app.prefs.gfx.frame_duration(is_idle);
                     next_frame_time += frame_duration;
                     if next_frame_time < Instant::now() {
                         // Skip a frame (or several).
@@ -391,7 +528,8 @@ async fn run() {
                     // Update app state.
                     app.frame();
 
-                    let output_frame = match gfx.surface.get_current_texture() {
+                    let surface = gfx.surface.as_ref().expect("window has no surface");
+                    let output_frame = match surface.get_current_texture() {
                         Ok(tex) => tex,
                         // Log other errors to the console.
                         Err(e) => {
@@ -425,7 +563,7 @@ async fn run() {
                             });
                     let screen_descriptor = egui_wgpu::renderer::ScreenDescriptor {
                         size_in_pixels: [gfx.config.width, gfx.config.height],
-                        pixels_per_point: gfx.scale_factor,
+                        pixels_per_point,
                     };
 
                     for (id, image_delta) in &egui_output.textures_delta.set {
@@ -441,6 +579,11 @@ async fn run() {
 
                     // Record egui render passes.
                     {
+                        let clear_color = if is_transparent {
+                            wgpu::Color::TRANSPARENT
+                        } else {
+                            wgpu::Color::WHITE
+                        };
                         let texture_view = output_frame
                             .texture
                             .create_view(&wgpu::TextureViewDescriptor::default());
@@ -452,7 +595,7 @@ async fn run() {
                                     view: &texture_view,
                                     resolve_target: None,
                                     ops: wgpu::Operations {
-                                        load: wgpu::LoadOp::Clear(wgpu::Color::WHITE),
+                                        load: wgpu::LoadOp::Clear(clear_color),
                                         store: true,
                                     },
                                 })],
@@ -477,14 +620,21 @@ async fn run() {
                     // Present the frame.
                     output_frame.present();
 
+                    // Draw panels in other windows.
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        detached_windows.update(ev_loop, &gfx, &mut app);
+                        detached_windows.draw(&gfx, &egui_ctx, &mut app);
+                    }
+
                     // Update framerate.
                     frames_this_second += 1;
                     if (Instant::now() - last_second).as_secs() >= 1 {
                         last_fps = frames_this_second;
                         frames_this_second = 0;
                         last_second += Duration::from_secs(1);
+                        app.set_fps(last_fps);
                     }
-                    // TODO: display framerate somewhere
                     printlnd!("FPS: {}", last_fps);
                 }
             }
@@ -495,16 +645,9 @@ async fn run() {
     });
 }
 
-fn switch_to_dark_mode(ctx: &egui::Context) {
-    ctx.set_style(egui::Style {
-        visuals: egui::Visuals::dark(),
-        ..Default::default()
-    });
-    set_style_overrides(ctx);
-}
-fn switch_to_light_mode(ctx: &egui::Context) {
+fn set_style(ctx: &egui::Context, visuals: egui::Visuals) {
     ctx.set_style(egui::Style {
-        visuals: egui::Visuals::dark(),
+        visuals,
         ..Default::default()
     });
     set_style_overrides(ctx);
@@ -0,0 +1,197 @@
+//! Local API for external tools such as dashboards and bots.
+//!
+//! Tools connect over TCP to a port on the local machine. Each line sent to a
+//! tool is a JSON object: either a [`super::SpectateMessage`] describing a
+//! change to the puzzle, or an [`ApiEvent`]. Each line sent by a tool is a
+//! JSON [`ApiCommand`].
+
+use instant::Instant;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use super::spectate::SentState;
+use super::POLL_INTERVAL as ACCEPT_POLL_INTERVAL;
+use crate::puzzle::PuzzleController;
+
+/// Event sent to tools, other than changes to the puzzle.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApiEvent {
+    TimerStarted,
+    TimerStopped,
+    /// The puzzle was solved after being fully scrambled.
+    Solved {
+        /// Solve time in milliseconds, if the solve was timed.
+        time_ms: Option<u64>,
+        twist_count: usize,
+    },
+    /// A command from a tool failed.
+    Error {
+        message: String,
+    },
+}
+
+/// Command sent by a tool.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApiCommand {
+    /// Applies a twist, written in the puzzle's notation.
+    Twist { twist: String },
+    /// Scrambles the puzzle fully.
+    Scramble,
+}
+
+/// Server for the local API.
+#[derive(Debug)]
+pub struct ApiServer {
+    port: u16,
+    start: Instant,
+    /// Tools that have connected but not yet received the puzzle state.
+    new_clients: Arc<Mutex<Vec<mpsc::Sender<String>>>>,
+    clients: Vec<mpsc::Sender<String>>,
+    commands: mpsc::Receiver<ApiCommand>,
+
+    last_sent: Option<SentState>,
+    timer_was_running: bool,
+    /// Events to send after the next changes to the puzzle.
+    pending_events: Vec<ApiEvent>,
+}
+impl ApiServer {
+    /// Starts listening for tools on a port on the local machine.
+    pub fn start(port: u16) -> Result<Self, String> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .map_err(|e| format!("Unable to listen on port {port}: {e}"))?;
+        // Don't block so that the thread can stop when the server is dropped.
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let new_clients = Arc::new(Mutex::new(vec![]));
+        let (commands_tx, commands_rx) = mpsc::channel();
+
+        let new_clients_ref = Arc::downgrade(&new_clients);
+        thread::spawn(move || {
+            while let Some(new_clients) = new_clients_ref.upgrade() {
+                match listener.accept() {
+                    Ok((stream, _addr)) => {
+                        let _ = stream.set_nonblocking(false);
+                        match stream.try_clone() {
+                            Ok(reader) => {
+                                let (tx, rx) = mpsc::channel();
+                                new_clients.lock().unwrap().push(tx);
+                                let commands_tx = commands_tx.clone();
+                                thread::spawn(move || write_lines(stream, rx));
+                                thread::spawn(move || read_commands(reader, commands_tx));
+                            }
+                            Err(e) => log::warn!("Error accepting API client: {e}"),
+                        }
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        drop(new_clients);
+                        thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                    Err(e) => log::warn!("Error accepting API client: {e}"),
+                }
+            }
+        });
+
+        Ok(Self {
+            port,
+            start: Instant::now(),
+            new_clients,
+            clients: vec![],
+            commands: commands_rx,
+
+            last_sent: None,
+            timer_was_running: false,
+            pending_events: vec![],
+        })
+    }
+
+    /// Returns the port that tools can connect to.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+    /// Returns the number of connected tools.
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Returns all commands received since the last call.
+    pub fn take_commands(&mut self) -> Vec<ApiCommand> {
+        self.commands.try_iter().collect()
+    }
+    /// Sends an event after the next changes to the puzzle.
+    pub fn send_event(&mut self, event: ApiEvent) {
+        self.pending_events.push(event);
+    }
+
+    /// Sends any changes to the puzzle and timer, followed by other pending
+    /// events, to tools.
+    pub fn update(&mut self, puzzle: &PuzzleController, timer_running: bool) {
+        let current = SentState::new(puzzle);
+
+        let new_clients = std::mem::take(&mut *self.new_clients.lock().unwrap());
+        if !new_clients.is_empty() {
+            let line = encode(&current.reset_message());
+            for tx in new_clients {
+                if tx.send(line.clone()).is_ok() {
+                    self.clients.push(tx);
+                }
+            }
+        }
+
+        let time_ms = self.start.elapsed().as_millis() as u64;
+        let mut lines = current
+            .messages_since(self.last_sent.as_ref(), time_ms)
+            .iter()
+            .map(encode)
+            .collect::<Vec<_>>();
+        self.last_sent = Some(current);
+
+        if timer_running != self.timer_was_running {
+            self.timer_was_running = timer_running;
+            lines.push(encode(&match timer_running {
+                true => ApiEvent::TimerStarted,
+                false => ApiEvent::TimerStopped,
+            }));
+        }
+        lines.extend(self.pending_events.drain(..).map(|e| encode(&e)));
+
+        for line in lines {
+            // Forget tools that have disconnected.
+            self.clients.retain(|tx| tx.send(line.clone()).is_ok());
+        }
+    }
+}
+
+fn encode(message: &impl Serialize) -> String {
+    serde_json::to_string(message).expect("error serializing API message")
+}
+
+fn write_lines(mut stream: TcpStream, rx: mpsc::Receiver<String>) {
+    for line in rx {
+        if let Err(e) = writeln!(stream, "{line}") {
+            log::info!("API client disconnected: {e}");
+            return;
+        }
+    }
+}
+
+fn read_commands(stream: TcpStream, tx: mpsc::Sender<ApiCommand>) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { return };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(command) => {
+                if tx.send(command).is_err() {
+                    // The server was dropped.
+                    return;
+                }
+            }
+            Err(e) => log::warn!("Ignoring invalid command from API client: {e}"),
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Online racing, spectating, sync, and the local API for external tools.
+//!
+//! In a race lobby, everyone receives the same scramble at the same time, and
+//! each player's twist count and final time are shared with the others.
+
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::puzzle::{PuzzleTypeEnum, Twist};
+
+mod api;
+mod protocol;
+mod server;
+mod spectate;
+mod sync;
+
+pub use api::*;
+pub use protocol::*;
+pub use server::*;
+pub use spectate::*;
+pub use sync::*;
+
+/// How long the network thread waits for incoming messages before checking
+/// for outgoing ones.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Connection to a race lobby server. Network I/O happens on a separate
+/// thread.
+#[derive(Debug)]
+pub struct RaceClient {
+    url: String,
+    outgoing: mpsc::Sender<ClientMessage>,
+    incoming: mpsc::Receiver<Result<ServerMessage, String>>,
+
+    /// Our own ID, once the server has assigned one.
+    id: Option<PeerId>,
+    /// State of everyone in the lobby, including us.
+    peers: Vec<PeerState>,
+    /// Error that closed the connection, if any.
+    error: Option<String>,
+
+    /// Whether we are in a race that we have not finished yet.
+    in_race: bool,
+    /// Twist count last sent to the server.
+    reported_twist_count: usize,
+}
+impl RaceClient {
+    /// Connects to a lobby server and joins the lobby.
+    pub fn connect(url: &str, name: &str) -> Self {
+        let (outgoing_tx, outgoing_rx) = mpsc::channel();
+        let (incoming_tx, incoming_rx) = mpsc::channel();
+
+        let url_string = url.to_owned();
+        thread::spawn(move || {
+            if let Err(e) = run_connection(&url_string, outgoing_rx, &incoming_tx) {
+                let _ = incoming_tx.send(Err(e));
+            }
+        });
+
+        let _ = outgoing_tx.send(ClientMessage::Join {
+            name: name.to_owned(),
+        });
+
+        Self {
+            url: url.to_owned(),
+            outgoing: outgoing_tx,
+            incoming: incoming_rx,
+
+            id: None,
+            peers: vec![],
+            error: None,
+
+            in_race: false,
+            reported_twist_count: 0,
+        }
+    }
+
+    /// Returns the URL of the lobby server.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+    /// Returns whether the server has accepted us into the lobby.
+    pub fn is_joined(&self) -> bool {
+        self.id.is_some() && self.error.is_none()
+    }
+    /// Returns the error that closed the connection, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+    /// Returns our own ID, once the server has assigned one.
+    pub fn id(&self) -> Option<PeerId> {
+        self.id
+    }
+    /// Returns everyone in the lobby, including us.
+    pub fn peers(&self) -> &[PeerState] {
+        &self.peers
+    }
+
+    /// Returns whether we are in a race that we have not finished yet.
+    pub fn is_racing(&self) -> bool {
+        self.in_race
+    }
+
+    /// Starts a new race for everyone in the lobby.
+    pub fn start_race(&self, puzzle: PuzzleTypeEnum, scramble: &[Twist]) {
+        self.send(ClientMessage::Start {
+            puzzle,
+            scramble: scramble.iter().map(|twist| twist.to_string()).collect(),
+        });
+    }
+    /// Reports our twist count, if it has changed.
+    pub fn report_progress(&mut self, twist_count: usize) {
+        if self.in_race && twist_count != self.reported_twist_count {
+            self.reported_twist_count = twist_count;
+            self.send(ClientMessage::Progress { twist_count });
+        }
+    }
+    /// Stops reporting progress in the current race without finishing it.
+    pub fn skip_race(&mut self) {
+        self.in_race = false;
+    }
+    /// Reports that we completed the solve.
+    pub fn report_finished(&mut self, time_ms: u64, twist_count: usize) {
+        if self.in_race {
+            self.in_race = false;
+            self.send(ClientMessage::Finished {
+                time_ms,
+                twist_count,
+            });
+        }
+    }
+
+    fn send(&self, message: ClientMessage) {
+        // If the connection is closed, the error is reported by `poll()`.
+        let _ = self.outgoing.send(message);
+    }
+
+    /// Handles incoming messages and returns the most recent race start, if
+    /// any.
+    pub fn poll(&mut self) -> Option<RaceStart> {
+        let mut race_start = None;
+        while let Ok(result) = self.incoming.try_recv() {
+            let message = match result {
+                Ok(m) => m,
+                Err(e) => {
+                    self.error = Some(e);
+                    continue;
+                }
+            };
+            match message {
+                ServerMessage::Welcome { id } => self.id = Some(id),
+                ServerMessage::Peers { peers } => {
+                    let old_peers = std::mem::take(&mut self.peers);
+                    self.peers = peers
+                        .into_iter()
+                        .map(|info| {
+                            let old = old_peers.iter().find(|p| p.info.id == info.id);
+                            PeerState {
+                                info,
+                                ..old.cloned().unwrap_or_default()
+                            }
+                        })
+                        .collect();
+                }
+                ServerMessage::Started { puzzle, scramble } => {
+                    for peer in &mut self.peers {
+                        peer.twist_count = 0;
+                        peer.time_ms = None;
+                        peer.error = None;
+                    }
+                    self.in_race = true;
+                    self.reported_twist_count = 0;
+                    race_start = Some(RaceStart { puzzle, scramble });
+                }
+                ServerMessage::Progress { id, twist_count } => {
+                    if let Some(peer) = self.peer_mut(id) {
+                        peer.twist_count = twist_count;
+                    }
+                }
+                ServerMessage::Finished {
+                    id,
+                    time_ms,
+                    twist_count,
+                } => {
+                    if let Some(peer) = self.peer_mut(id) {
+                        peer.twist_count = twist_count;
+                        peer.time_ms = Some(time_ms);
+                    }
+                }
+                ServerMessage::Error { id, message } => {
+                    if let Some(peer) = self.peer_mut(id) {
+                        peer.error = Some(message);
+                    }
+                }
+            }
+        }
+        race_start
+    }
+
+    fn peer_mut(&mut self, id: PeerId) -> Option<&mut PeerState> {
+        self.peers.iter_mut().find(|p| p.info.id == id)
+    }
+}
+
+/// Race started by someone in the lobby.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaceStart {
+    pub puzzle: PuzzleTypeEnum,
+    /// Scramble twists, in the `Twist` string format from log files.
+    pub scramble: Vec<String>,
+}
+
+/// Live state of a player in the lobby.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PeerState {
+    pub info: PeerInfo,
+    /// Number of twists in the current race.
+    pub twist_count: usize,
+    /// Final time in the current race, in milliseconds.
+    pub time_ms: Option<u64>,
+    /// Error that kept the player from taking part in the current race.
+    pub error: Option<String>,
+}
+
+fn run_connection(
+    url: &str,
+    outgoing: mpsc::Receiver<ClientMessage>,
+    incoming: &mpsc::Sender<Result<ServerMessage, String>>,
+) -> Result<(), String> {
+    let (mut socket, _response) =
+        tungstenite::connect(url).map_err(|e| format!("Unable to connect: {e}"))?;
+    if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+        stream
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|e| e.to_string())?;
+    }
+
+    loop {
+        // Send outgoing messages.
+        loop {
+            match outgoing.try_recv() {
+                Ok(message) => send(&mut socket, &message)?,
+                Err(mpsc::TryRecvError::Empty) => break,
+                // The client was dropped, so close the connection.
+                Err(mpsc::TryRecvError::Disconnected) => return close(&mut socket),
+            }
+        }
+
+        // Receive incoming messages.
+        match socket.read_message() {
+            Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                Ok(message) => {
+                    if incoming.send(Ok(message)).is_err() {
+                        return close(&mut socket);
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Ignoring invalid message from race server: {e}");
+                    // Let the lobby know, for example if the race is on a
+                    // puzzle that we don't have.
+                    let message = e.to_string();
+                    send(&mut socket, &ClientMessage::Error { message })?;
+                }
+            },
+            Ok(Message::Close(_)) => return Err("Server closed the connection".to_owned()),
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut,
+                ) => {}
+            Err(e) => return Err(format!("Connection lost: {e}")),
+        }
+    }
+}
+
+fn send(
+    socket: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+    message: &ClientMessage,
+) -> Result<(), String> {
+    let text = serde_json::to_string(message).map_err(|e| e.to_string())?;
+    socket
+        .write_message(Message::Text(text))
+        .map_err(|e| format!("Connection lost: {e}"))
+}
+
+fn close(socket: &mut WebSocket<MaybeTlsStream<TcpStream>>) -> Result<(), String> {
+    let _ = socket.close(None);
+    Ok(())
+}
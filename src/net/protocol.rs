@@ -0,0 +1,66 @@
+//! Messages exchanged with a race lobby server.
+//!
+//! The server is a simple relay: it assigns each client an ID, keeps track of
+//! who is in the lobby, and forwards race updates to every client (including
+//! the one that sent them). Messages are JSON-encoded WebSocket text messages.
+
+use serde::{Deserialize, Serialize};
+
+use crate::puzzle::PuzzleTypeEnum;
+
+/// ID assigned to a client by the server.
+pub type PeerId = u32;
+
+/// Message sent from a client to the server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Joins the lobby.
+    Join { name: String },
+    /// Starts a new race for everyone in the lobby.
+    Start {
+        puzzle: PuzzleTypeEnum,
+        /// Scramble twists, each formatted using the `Twist` string format
+        /// from log files.
+        scramble: Vec<String>,
+    },
+    /// Reports the current twist count.
+    Progress { twist_count: usize },
+    /// Reports a completed solve.
+    Finished { time_ms: u64, twist_count: usize },
+    /// Reports a message from the server that couldn't be read, such as a
+    /// race on a puzzle that this client doesn't have.
+    Error { message: String },
+}
+
+/// Message sent from the server to a client.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Confirms that the client joined the lobby.
+    Welcome { id: PeerId },
+    /// Lists everyone in the lobby.
+    Peers { peers: Vec<PeerInfo> },
+    /// A race has started.
+    Started {
+        puzzle: PuzzleTypeEnum,
+        scramble: Vec<String>,
+    },
+    /// A peer's twist count changed.
+    Progress { id: PeerId, twist_count: usize },
+    /// A peer completed the solve.
+    Finished {
+        id: PeerId,
+        time_ms: u64,
+        twist_count: usize,
+    },
+    /// A message from or to a peer couldn't be read.
+    Error { id: PeerId, message: String },
+}
+
+/// Player in the lobby.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct PeerInfo {
+    pub id: PeerId,
+    pub name: String,
+}
@@ -0,0 +1,265 @@
+//! Race lobby server, so that one player can host a lobby for the others.
+//!
+//! The server relays messages as described in [`super::protocol`]. Each
+//! connection runs on its own thread, and the lobby state is shared between
+//! them.
+
+use std::net::{TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use tungstenite::{Message, WebSocket};
+
+use super::{ClientMessage, PeerId, PeerInfo, ServerMessage, POLL_INTERVAL};
+
+/// Race lobby server running on a local port.
+#[derive(Debug)]
+pub struct RaceServer {
+    port: u16,
+    lobby: Arc<Mutex<Lobby>>,
+}
+impl RaceServer {
+    /// Starts hosting a lobby on a port. If `allow_lan` is `false`, only
+    /// connections from this computer are accepted.
+    pub fn start(port: u16, allow_lan: bool) -> Result<Self, String> {
+        let ip = if allow_lan { "0.0.0.0" } else { "127.0.0.1" };
+        let listener = TcpListener::bind((ip, port))
+            .map_err(|e| format!("Unable to listen on port {port}: {e}"))?;
+        // Don't block so that the thread can stop when the server is dropped.
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let lobby = Arc::new(Mutex::new(Lobby::default()));
+
+        let lobby_ref = Arc::downgrade(&lobby);
+        thread::spawn(move || {
+            while let Some(lobby) = lobby_ref.upgrade() {
+                match listener.accept() {
+                    Ok((stream, _addr)) => {
+                        let _ = stream.set_nonblocking(false);
+                        // Do the handshake on the client's own thread so that a
+                        // slow client can't stall other connections.
+                        thread::spawn(move || match tungstenite::accept(stream) {
+                            Ok(socket) => run_connection(socket, &lobby),
+                            Err(e) => log::warn!("Error accepting race client: {e}"),
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        drop(lobby);
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                    Err(e) => log::warn!("Error accepting race client: {e}"),
+                }
+            }
+        });
+
+        Ok(Self { port, lobby })
+    }
+
+    /// Returns the port that players can connect to.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+    /// Returns the URL for connecting to the lobby from this computer.
+    pub fn local_url(&self) -> String {
+        format!("ws://127.0.0.1:{}", self.port)
+    }
+    /// Returns the number of players in the lobby.
+    pub fn player_count(&self) -> usize {
+        self.lobby.lock().unwrap().peers.len()
+    }
+}
+
+/// Players in a lobby.
+#[derive(Debug, Default)]
+struct Lobby {
+    next_id: PeerId,
+    peers: Vec<Peer>,
+}
+#[derive(Debug)]
+struct Peer {
+    info: PeerInfo,
+    tx: mpsc::Sender<ServerMessage>,
+}
+impl Lobby {
+    /// Adds a player to the lobby and returns their ID.
+    fn join(&mut self, name: String, tx: mpsc::Sender<ServerMessage>) -> PeerId {
+        let id = self.next_id;
+        self.next_id += 1;
+        let _ = tx.send(ServerMessage::Welcome { id });
+        self.peers.push(Peer {
+            info: PeerInfo { id, name },
+            tx,
+        });
+        self.broadcast_peers();
+        id
+    }
+    /// Removes a player from the lobby.
+    fn leave(&mut self, id: PeerId) {
+        self.peers.retain(|p| p.info.id != id);
+        self.broadcast_peers();
+    }
+    /// Handles a message from a player who has already joined.
+    fn handle(&mut self, id: PeerId, message: ClientMessage) {
+        let message = match message {
+            ClientMessage::Join { .. } => return,
+            ClientMessage::Start { puzzle, scramble } => {
+                ServerMessage::Started { puzzle, scramble }
+            }
+            ClientMessage::Progress { twist_count } => ServerMessage::Progress { id, twist_count },
+            ClientMessage::Finished {
+                time_ms,
+                twist_count,
+            } => ServerMessage::Finished {
+                id,
+                time_ms,
+                twist_count,
+            },
+            ClientMessage::Error { message } => ServerMessage::Error { id, message },
+        };
+        self.broadcast(message);
+    }
+    /// Sends a message to one player.
+    fn send(&mut self, id: PeerId, message: ServerMessage) {
+        if let Some(peer) = self.peers.iter().find(|p| p.info.id == id) {
+            let _ = peer.tx.send(message);
+        }
+    }
+
+    fn broadcast_peers(&mut self) {
+        let peers = self.peers.iter().map(|p| p.info.clone()).collect();
+        self.broadcast(ServerMessage::Peers { peers });
+    }
+    fn broadcast(&mut self, message: ServerMessage) {
+        // Forget players whose connection has closed.
+        self.peers.retain(|p| p.tx.send(message.clone()).is_ok());
+    }
+}
+
+fn run_connection(mut socket: WebSocket<TcpStream>, lobby: &Mutex<Lobby>) {
+    if let Err(e) = socket.get_ref().set_read_timeout(Some(POLL_INTERVAL)) {
+        log::warn!("Error configuring race client connection: {e}");
+        return;
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut tx = Some(tx);
+    let mut id = None;
+
+    'outer: loop {
+        // Send outgoing messages.
+        while let Ok(message) = rx.try_recv() {
+            let text = serde_json::to_string(&message).expect("error serializing message");
+            if let Err(e) = socket.write_message(Message::Text(text)) {
+                log::info!("Race client disconnected: {e}");
+                break 'outer;
+            }
+        }
+
+        // Receive incoming messages.
+        let message = match socket.read_message() {
+            Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                Ok(message) => message,
+                Err(e) => {
+                    log::warn!("Ignoring invalid message from race client: {e}");
+                    // Let the player know, for example if they started a race
+                    // on a puzzle that the server doesn't have.
+                    if let Some(id) = id {
+                        let message = format!("Lobby server couldn't read message: {e}");
+                        let error = ServerMessage::Error { id, message };
+                        lobby.lock().unwrap().send(id, error);
+                    }
+                    continue;
+                }
+            },
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut,
+                ) =>
+            {
+                continue
+            }
+            Err(e) => {
+                log::info!("Race client disconnected: {e}");
+                break;
+            }
+        };
+
+        let mut lobby = lobby.lock().unwrap();
+        match (id, message) {
+            (None, ClientMessage::Join { name }) => {
+                if let Some(tx) = tx.take() {
+                    id = Some(lobby.join(name, tx));
+                }
+            }
+            // Ignore everything else until the client has joined.
+            (None, _) => (),
+            (Some(id), message) => lobby.handle(id, message),
+        }
+    }
+
+    if let Some(id) = id {
+        lobby.lock().unwrap().leave(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::PuzzleTypeEnum;
+
+    #[test]
+    fn test_lobby() {
+        let mut lobby = Lobby::default();
+        let (tx_a, rx_a) = mpsc::channel();
+        let (tx_b, rx_b) = mpsc::channel();
+
+        let a = lobby.join("A".to_owned(), tx_a);
+        let b = lobby.join("B".to_owned(), tx_b);
+        assert_ne!(a, b);
+        assert_eq!(rx_a.try_recv(), Ok(ServerMessage::Welcome { id: a }));
+        assert!(matches!(rx_a.try_recv(), Ok(ServerMessage::Peers { peers }) if peers.len() == 1));
+        assert!(matches!(rx_a.try_recv(), Ok(ServerMessage::Peers { peers }) if peers.len() == 2));
+        assert_eq!(rx_b.try_recv(), Ok(ServerMessage::Welcome { id: b }));
+        assert!(matches!(rx_b.try_recv(), Ok(ServerMessage::Peers { peers }) if peers.len() == 2));
+
+        // Everyone receives the same scramble, including the sender.
+        let puzzle = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let scramble = vec!["0,0,1".to_owned()];
+        lobby.handle(
+            a,
+            ClientMessage::Start {
+                puzzle,
+                scramble: scramble.clone(),
+            },
+        );
+        let started = ServerMessage::Started { puzzle, scramble };
+        assert_eq!(rx_a.try_recv(), Ok(started.clone()));
+        assert_eq!(rx_b.try_recv(), Ok(started));
+
+        lobby.handle(b, ClientMessage::Progress { twist_count: 3 });
+        let progress = ServerMessage::Progress {
+            id: b,
+            twist_count: 3,
+        };
+        assert_eq!(rx_a.try_recv(), Ok(progress));
+
+        // Errors are relayed to everyone.
+        let message = "unknown puzzle \"Void 3x3x3\"".to_owned();
+        lobby.handle(
+            b,
+            ClientMessage::Error {
+                message: message.clone(),
+            },
+        );
+        let error = ServerMessage::Error { id: b, message };
+        assert_eq!(rx_a.try_recv(), Ok(error.clone()));
+        assert_eq!(rx_b.try_iter().last(), Some(error));
+
+        // Players whose connection closed are removed.
+        drop(rx_b);
+        lobby.handle(a, ClientMessage::Progress { twist_count: 1 });
+        assert_eq!(lobby.peers.len(), 1);
+    }
+}
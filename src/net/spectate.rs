@@ -0,0 +1,405 @@
+//! Broadcasting the local puzzle to read-only spectators.
+//!
+//! The host listens for WebSocket connections. Each spectator first receives
+//! the full puzzle state, followed by each twist and undo as it happens.
+//!
+//! By default the host only accepts connections from the same computer.
+//! Spectators must also know the host's code, which is randomly generated each
+//! time the host starts broadcasting.
+
+use instant::Instant;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::Message;
+
+use super::POLL_INTERVAL as ACCEPT_POLL_INTERVAL;
+
+use crate::puzzle::{HistoryEntry, PuzzleController, PuzzleTypeEnum, Twist};
+
+/// Message sent from the host to spectators. Twists are formatted using the
+/// `Twist` string format from log files.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpectateMessage {
+    /// Replaces the whole puzzle state.
+    Reset {
+        puzzle: PuzzleTypeEnum,
+        scramble: Vec<String>,
+        twists: Vec<String>,
+    },
+    /// Applies a twist.
+    Twist {
+        twist: String,
+        /// Milliseconds since the host started broadcasting.
+        time_ms: u64,
+    },
+    /// Undoes some number of twists.
+    Undo {
+        count: usize,
+        /// Milliseconds since the host started broadcasting.
+        time_ms: u64,
+    },
+}
+
+/// Puzzle state most recently sent to spectators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SentState {
+    puzzle: PuzzleTypeEnum,
+    scramble: Vec<Twist>,
+    twists: Vec<Twist>,
+}
+impl SentState {
+    pub(super) fn new(puzzle: &PuzzleController) -> Self {
+        Self {
+            puzzle: puzzle.ty(),
+            scramble: puzzle.scramble().to_vec(),
+            twists: puzzle
+                .undo_buffer()
+                .iter()
+                .copied()
+                .filter_map(HistoryEntry::twist)
+                .collect(),
+        }
+    }
+    pub(super) fn reset_message(&self) -> SpectateMessage {
+        SpectateMessage::Reset {
+            puzzle: self.puzzle,
+            scramble: self.scramble.iter().map(|t| t.to_string()).collect(),
+            twists: self.twists.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+    /// Returns the messages that change the state from `last` to this one.
+    pub(super) fn messages_since(
+        &self,
+        last: Option<&SentState>,
+        time_ms: u64,
+    ) -> Vec<SpectateMessage> {
+        let mut messages = vec![];
+        match last {
+            Some(last) if last == self => (),
+            Some(last) if last.puzzle == self.puzzle && last.scramble == self.scramble => {
+                let common = last
+                    .twists
+                    .iter()
+                    .zip(&self.twists)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                let count = last.twists.len() - common;
+                if count > 0 {
+                    messages.push(SpectateMessage::Undo { count, time_ms });
+                }
+                for twist in &self.twists[common..] {
+                    let twist = twist.to_string();
+                    messages.push(SpectateMessage::Twist { twist, time_ms });
+                }
+            }
+            _ => messages.push(self.reset_message()),
+        }
+        messages
+    }
+}
+
+/// Characters used in spectate codes, excluding ones that are easy to confuse.
+const CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+/// Number of characters in a spectate code.
+const CODE_LEN: usize = 6;
+
+/// Broadcasts the local puzzle to spectators.
+#[derive(Debug)]
+pub struct SpectateHost {
+    port: u16,
+    code: String,
+    allow_lan: bool,
+    start: Instant,
+    /// Spectators that have connected but not yet received the puzzle state.
+    new_spectators: Arc<Mutex<Vec<mpsc::Sender<String>>>>,
+    spectators: Vec<mpsc::Sender<String>>,
+    /// Number of spectators whose connection is still open.
+    connected: Arc<AtomicUsize>,
+    last_sent: Option<SentState>,
+}
+impl SpectateHost {
+    /// Starts listening for spectators on a port. If `allow_lan` is `false`,
+    /// only connections from this computer are accepted.
+    pub fn start(port: u16, allow_lan: bool) -> Result<Self, String> {
+        let ip = if allow_lan { "0.0.0.0" } else { "127.0.0.1" };
+        let listener = TcpListener::bind((ip, port))
+            .map_err(|e| format!("Unable to listen on port {port}: {e}"))?;
+        // Don't block so that the thread can stop when the host is dropped.
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let new_spectators = Arc::new(Mutex::new(vec![]));
+        let connected = Arc::new(AtomicUsize::new(0));
+
+        let mut rng = rand::thread_rng();
+        let code: String = (0..CODE_LEN)
+            .map(|_| CODE_CHARS[rng.gen_range(0..CODE_CHARS.len())] as char)
+            .collect();
+
+        let new_spectators_ref = Arc::downgrade(&new_spectators);
+        let expected_code = code.clone();
+        let connected_ref = Arc::clone(&connected);
+        thread::spawn(move || {
+            while let Some(new_spectators) = new_spectators_ref.upgrade() {
+                match listener.accept() {
+                    Ok((stream, _addr)) => {
+                        let _ = stream.set_nonblocking(false);
+                        // Do the handshake on the spectator's own thread so
+                        // that a slow client can't stall other connections.
+                        let new_spectators = Arc::downgrade(&new_spectators);
+                        let expected_code = expected_code.clone();
+                        let connected = Arc::clone(&connected_ref);
+                        thread::spawn(move || {
+                            let check_code = |request: &Request, response: Response| {
+                                check_spectate_code(request, response, &expected_code)
+                            };
+                            match tungstenite::accept_hdr(stream, check_code) {
+                                Ok(socket) => {
+                                    let Some(new_spectators) = new_spectators.upgrade() else {
+                                        return;
+                                    };
+                                    let (tx, rx) = mpsc::channel();
+                                    new_spectators.lock().unwrap().push(tx);
+                                    drop(new_spectators);
+                                    write_messages(socket, rx, connected);
+                                }
+                                Err(e) => log::warn!("Error accepting spectator: {e}"),
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        drop(new_spectators);
+                        thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                    Err(e) => log::warn!("Error accepting spectator: {e}"),
+                }
+            }
+        });
+
+        Ok(Self {
+            port,
+            code,
+            allow_lan,
+            start: Instant::now(),
+            new_spectators,
+            spectators: vec![],
+            connected,
+            last_sent: None,
+        })
+    }
+
+    /// Returns the port that spectators can connect to.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+    /// Returns the code that spectators must provide to connect.
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+    /// Returns whether spectators on other computers can connect.
+    pub fn allows_lan(&self) -> bool {
+        self.allow_lan
+    }
+    /// Returns the number of connected spectators.
+    pub fn spectator_count(&self) -> usize {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Sends any changes to the puzzle state to spectators.
+    pub fn update(&mut self, puzzle: &PuzzleController) {
+        let current = SentState::new(puzzle);
+
+        let new_spectators = std::mem::take(&mut *self.new_spectators.lock().unwrap());
+        if !new_spectators.is_empty() {
+            let message = encode(&current.reset_message());
+            for tx in new_spectators {
+                if tx.send(message.clone()).is_ok() {
+                    self.spectators.push(tx);
+                }
+            }
+        }
+
+        let time_ms = self.start.elapsed().as_millis() as u64;
+        if self.last_sent.as_ref() == Some(&current) {
+            return;
+        }
+        let messages = current.messages_since(self.last_sent.as_ref(), time_ms);
+        self.last_sent = Some(current);
+
+        for message in messages.iter().map(encode) {
+            // Forget spectators that have disconnected.
+            self.spectators.retain(|tx| tx.send(message.clone()).is_ok());
+        }
+    }
+}
+
+/// Read-only connection to another player's puzzle.
+#[derive(Debug)]
+pub struct SpectateClient {
+    address: String,
+    incoming: mpsc::Receiver<Result<SpectateMessage, String>>,
+    error: Option<String>,
+    /// Host time of the most recent twist or undo, in milliseconds.
+    last_time_ms: Option<u64>,
+}
+impl SpectateClient {
+    /// Connects to a host using the code that the host shows.
+    pub fn connect(address: &str, code: &str) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let url = if address.contains("://") {
+            address.to_owned()
+        } else {
+            format!("ws://{address}")
+        };
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let url = format!("{url}{separator}code={}", code.trim());
+        thread::spawn(move || {
+            if let Err(e) = read_messages(&url, &tx) {
+                let _ = tx.send(Err(e));
+            }
+        });
+        Self {
+            address: address.to_owned(),
+            incoming: rx,
+            error: None,
+            last_time_ms: None,
+        }
+    }
+
+    /// Returns the address of the host.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+    /// Returns the error that closed the connection, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+    /// Returns the host time of the most recent twist or undo, in
+    /// milliseconds.
+    pub fn last_time_ms(&self) -> Option<u64> {
+        self.last_time_ms
+    }
+
+    /// Returns all messages received since the last call.
+    pub fn poll(&mut self) -> Vec<SpectateMessage> {
+        let mut messages = vec![];
+        while let Ok(result) = self.incoming.try_recv() {
+            match result {
+                Ok(message) => {
+                    match &message {
+                        SpectateMessage::Reset { .. } => (),
+                        SpectateMessage::Twist { time_ms, .. }
+                        | SpectateMessage::Undo { time_ms, .. } => {
+                            self.last_time_ms = Some(*time_ms);
+                        }
+                    }
+                    messages.push(message);
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+        messages
+    }
+}
+
+fn encode(message: &SpectateMessage) -> String {
+    serde_json::to_string(message).expect("error serializing spectate message")
+}
+
+/// Rejects a spectator whose request does not include the right code.
+fn check_spectate_code(
+    request: &Request,
+    response: Response,
+    expected_code: &str,
+) -> Result<Response, ErrorResponse> {
+    let code = request
+        .uri()
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .find_map(|param| param.strip_prefix("code="));
+    if code.map_or(false, |code| code.eq_ignore_ascii_case(expected_code)) {
+        Ok(response)
+    } else {
+        let mut error = ErrorResponse::new(Some("Wrong spectate code".to_owned()));
+        *error.status_mut() = StatusCode::FORBIDDEN;
+        Err(error)
+    }
+}
+
+fn write_messages(
+    mut socket: tungstenite::WebSocket<TcpStream>,
+    rx: mpsc::Receiver<String>,
+    connected: Arc<AtomicUsize>,
+) {
+    connected.fetch_add(1, Ordering::Relaxed);
+    // Spectators never send anything, so reading only serves to notice when
+    // the connection closes.
+    let _ = socket
+        .get_ref()
+        .set_read_timeout(Some(ACCEPT_POLL_INTERVAL));
+    'outer: loop {
+        loop {
+            match rx.try_recv() {
+                Ok(text) => {
+                    if let Err(e) = socket.write_message(Message::Text(text)) {
+                        log::info!("Spectator disconnected: {e}");
+                        break 'outer;
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                // The host stopped broadcasting.
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    break 'outer;
+                }
+            }
+        }
+
+        match socket.read_message() {
+            Ok(Message::Close(_)) => break,
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut,
+                ) => {}
+            Err(e) => {
+                log::info!("Spectator disconnected: {e}");
+                break;
+            }
+        }
+    }
+    connected.fetch_sub(1, Ordering::Relaxed);
+}
+
+fn read_messages(
+    url: &str,
+    tx: &mpsc::Sender<Result<SpectateMessage, String>>,
+) -> Result<(), String> {
+    let (mut socket, _response) =
+        tungstenite::connect(url).map_err(|e| format!("Unable to connect: {e}"))?;
+    loop {
+        match socket.read_message() {
+            Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                Ok(message) => {
+                    if tx.send(Ok(message)).is_err() {
+                        // The client was dropped.
+                        let _ = socket.close(None);
+                        return Ok(());
+                    }
+                }
+                Err(e) => log::warn!("Ignoring invalid message from spectate host: {e}"),
+            },
+            Ok(Message::Close(_)) => return Err("Host stopped broadcasting".to_owned()),
+            Ok(_) => (),
+            Err(e) => return Err(format!("Connection lost: {e}")),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Sync of solve history and preferences with a server.
+//!
+//! The server can be any HTTP server that stores files uploaded with `PUT` and
+//! returns them with `GET`, such as a WebDAV folder.
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::preferences::SyncPreferences;
+
+/// Name of the synced solve history file on the server.
+const HISTORY_FILE_NAME: &str = "hyperspeedcube_history.yaml";
+/// Name of the synced preferences file on the server.
+const PREFS_FILE_NAME: &str = "hyperspeedcube.yaml";
+/// How long to wait for the server before giving up.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Contents of the synced files. Each is `None` if it does not exist yet.
+#[derive(Debug, Default, Clone)]
+pub struct SyncFiles {
+    pub history: Option<String>,
+    pub prefs: Option<String>,
+}
+
+/// Completed step of a sync.
+#[derive(Debug)]
+pub enum SyncEvent {
+    /// The files on the server have been downloaded and should be merged
+    /// with the local ones, which should then be uploaded.
+    Downloaded(SyncFiles),
+    /// The merged files have been uploaded.
+    Uploaded,
+}
+
+/// Client for syncing with a server. Network I/O happens on a separate
+/// thread.
+#[derive(Debug, Default)]
+pub struct SyncClient {
+    pending: Option<mpsc::Receiver<Result<SyncEvent, String>>>,
+
+    /// Unix timestamp of the last successful sync.
+    last_synced: Option<i64>,
+    /// Error from the last sync, if it failed.
+    error: Option<String>,
+}
+impl SyncClient {
+    /// Returns whether a sync is in progress.
+    pub fn is_busy(&self) -> bool {
+        self.pending.is_some()
+    }
+    /// Returns the Unix timestamp of the last successful sync.
+    pub fn last_synced(&self) -> Option<i64> {
+        self.last_synced
+    }
+    /// Returns the error from the last sync, if it failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Starts a sync by downloading the files from the server.
+    pub fn download(&mut self, prefs: &SyncPreferences) {
+        let prefs = prefs.clone();
+        self.start(move || {
+            Ok(SyncEvent::Downloaded(SyncFiles {
+                history: get(&prefs, HISTORY_FILE_NAME)?,
+                prefs: get(&prefs, PREFS_FILE_NAME)?,
+            }))
+        });
+    }
+    /// Finishes a sync by uploading the merged files to the server.
+    pub fn upload(&mut self, prefs: &SyncPreferences, files: SyncFiles) {
+        let prefs = prefs.clone();
+        self.start(move || {
+            if let Some(contents) = &files.history {
+                put(&prefs, HISTORY_FILE_NAME, contents)?;
+            }
+            if let Some(contents) = &files.prefs {
+                put(&prefs, PREFS_FILE_NAME, contents)?;
+            }
+            Ok(SyncEvent::Uploaded)
+        });
+    }
+    /// Cancels a sync after a download, for example because the downloaded
+    /// files could not be merged.
+    pub fn fail(&mut self, error: String) {
+        self.pending = None;
+        self.error = Some(error);
+    }
+
+    /// Returns the next completed step of the sync, if any.
+    pub fn poll(&mut self) -> Option<Result<SyncEvent, String>> {
+        let result = match self.pending.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return None,
+            Err(mpsc::TryRecvError::Disconnected) => Err("Sync stopped unexpectedly".to_owned()),
+        };
+        self.pending = None;
+        match &result {
+            Ok(SyncEvent::Downloaded(_)) => (),
+            Ok(SyncEvent::Uploaded) => {
+                self.last_synced = Some(crate::util::unix_timestamp());
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.clone()),
+        }
+        Some(result)
+    }
+
+    fn start(&mut self, f: impl 'static + Send + FnOnce() -> Result<SyncEvent, String>) {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(f());
+        });
+        self.pending = Some(rx);
+    }
+}
+
+/// Downloads a file from the server, returning `None` if it does not exist.
+fn get(prefs: &SyncPreferences, file_name: &str) -> Result<Option<String>, String> {
+    let request = request(prefs, "GET", file_name);
+    match request.call() {
+        Ok(response) => response
+            .into_string()
+            .map(Some)
+            .map_err(|e| format!("Error downloading {file_name}: {e}")),
+        Err(ureq::Error::Status(404, _)) => Ok(None),
+        Err(e) => Err(format!("Error downloading {file_name}: {e}")),
+    }
+}
+
+/// Uploads a file to the server, replacing it if it exists.
+fn put(prefs: &SyncPreferences, file_name: &str, contents: &str) -> Result<(), String> {
+    request(prefs, "PUT", file_name)
+        .set("Content-Type", "application/yaml")
+        .send_string(contents)
+        .map_err(|e| format!("Error uploading {file_name}: {e}"))?;
+    Ok(())
+}
+
+fn request(prefs: &SyncPreferences, method: &str, file_name: &str) -> ureq::Request {
+    let url = format!("{}/{}", prefs.url.trim_end_matches('/'), file_name);
+    let request = ureq::request(method, &url).timeout(TIMEOUT);
+    if prefs.token.is_empty() {
+        request
+    } else {
+        request.set("Authorization", &format!("Bearer {}", prefs.token))
+    }
+}
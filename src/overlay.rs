@@ -0,0 +1,129 @@
+//! Live solve data written to a file for use in stream overlays (such as OBS
+//! text sources).
+
+use instant::{Duration, Instant};
+use itertools::Itertools;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::app::App;
+use crate::gui::windows::time_ms_to_str;
+use crate::history::Solve;
+use crate::preferences::OverlayFormat;
+use crate::puzzle::traits::*;
+
+/// Minimum time between updates, to avoid rebuilding the data every frame and
+/// hammering the disk.
+const MIN_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Data written to the overlay file.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+struct OverlayData {
+    puzzle: String,
+    time: String,
+    scramble_state: String,
+    twist_count: usize,
+    metric: String,
+    scramble: String,
+
+    session: String,
+    last_solve: String,
+    best: String,
+    ao5: String,
+    ao12: String,
+}
+impl OverlayData {
+    fn new(app: &App) -> Self {
+        let ty = app.puzzle.ty();
+        let metric = app.prefs.info.metric;
+        let notation = app.puzzle.notation_scheme();
+        let session = app.history.active_session(ty);
+        let stats = session.map(|s| s.stats()).unwrap_or_default();
+
+        Self {
+            puzzle: ty.name().to_owned(),
+            time: app.timer.display_str(),
+            scramble_state: app.puzzle.scramble_state().description().to_owned(),
+            twist_count: app.puzzle.twist_count(metric),
+            metric: metric.to_string(),
+            scramble: app
+                .puzzle
+                .scramble()
+                .iter()
+                .map(|&twist| notation.twist_to_string(twist))
+                .join(" "),
+
+            session: app.history.active_session_name(ty).to_owned(),
+            last_solve: time_ms_to_str(
+                session
+                    .and_then(|s| s.solves.last())
+                    .and_then(Solve::timed_result_ms),
+            ),
+            best: time_ms_to_str(stats.best),
+            ao5: time_ms_to_str(stats.current_ao5),
+            ao12: time_ms_to_str(stats.current_ao12),
+        }
+    }
+
+    fn to_string(&self, format: OverlayFormat) -> String {
+        match format {
+            OverlayFormat::Text => [
+                ("Puzzle", self.puzzle.clone()),
+                ("Time", self.time.clone()),
+                ("State", self.scramble_state.clone()),
+                ("Twists", format!("{} {}", self.twist_count, self.metric)),
+                ("Session", self.session.clone()),
+                ("Last", self.last_solve.clone()),
+                ("Best", self.best.clone()),
+                ("Ao5", self.ao5.clone()),
+                ("Ao12", self.ao12.clone()),
+                ("Scramble", self.scramble.clone()),
+            ]
+            .iter()
+            .map(|(k, v)| format!("{k}: {v}\n"))
+            .collect(),
+            OverlayFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+        }
+    }
+}
+
+/// Continuously writes live solve data to a file.
+#[derive(Debug, Default)]
+pub(crate) struct OverlayWriter {
+    last_contents: Option<String>,
+    last_update: Option<Instant>,
+}
+impl OverlayWriter {
+    /// Writes the overlay file if it is enabled and its contents changed.
+    pub(crate) fn update(&mut self, app: &App) {
+        let prefs = &app.prefs.overlay;
+        let Some(path) = prefs.path.as_ref().filter(|_| prefs.enabled) else {
+            self.last_contents = None;
+            return;
+        };
+        if self
+            .last_update
+            .map_or(false, |t| t.elapsed() < MIN_UPDATE_INTERVAL)
+        {
+            return;
+        }
+        self.last_update = Some(Instant::now());
+
+        let contents = OverlayData::new(app).to_string(prefs.format);
+        if self.last_contents.as_ref() == Some(&contents) {
+            return;
+        }
+        if let Err(e) = write_atomic(path, &contents) {
+            log::error!("Error writing overlay file: {e}");
+        }
+        self.last_contents = Some(contents);
+    }
+}
+
+/// Writes to a temporary file and then renames it, so that other programs
+/// never see a partially-written file.
+fn write_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(tmp_path, path)
+}
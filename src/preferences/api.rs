@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ApiPreferences {
+    /// Whether to let external tools connect to the local API.
+    pub enabled: bool,
+    /// Port on the local machine that external tools connect to.
+    pub port: u16,
+}
+impl Default for ApiPreferences {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8766,
+        }
+    }
+}
@@ -6,11 +6,25 @@ use super::PerPuzzleFamily;
 use crate::puzzle::{traits::*, Face, PuzzleTypeEnum};
 use crate::serde_impl::hex_color;
 
+/// Background color used by default with a light theme.
+const LIGHT_THEME_BACKGROUND: egui::Color32 = egui::Color32::from_rgb(0xcc, 0xcc, 0xcc);
+/// Outline color used by default with a light theme.
+const LIGHT_THEME_OUTLINE: egui::Color32 = egui::Color32::from_rgb(0x55, 0x55, 0x55);
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default)]
 pub struct ColorPreferences {
+    pub theme: Theme,
+    /// Color of selected and highlighted UI elements.
+    #[serde(with = "hex_color")]
+    pub accent: egui::Color32,
+
     #[serde(with = "hex_color")]
     pub background: egui::Color32,
+    pub background_style: BackgroundStyle,
+    /// Color at the bottom of the gradient, or color of grid lines.
+    #[serde(with = "hex_color")]
+    pub background_secondary: egui::Color32,
     #[serde(with = "hex_color")]
     pub blind_face: egui::Color32,
     pub blindfold: bool,
@@ -40,6 +54,56 @@ impl IndexMut<(PuzzleTypeEnum, Face)> for ColorPreferences {
     }
 }
 
+/// How the background behind the puzzle is drawn.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum BackgroundStyle {
+    /// Solid background color.
+    #[default]
+    Flat,
+    /// Vertical gradient from the background color at the top to the
+    /// secondary color at the bottom.
+    Gradient,
+    /// Grid of lines in the secondary color on a floor below the horizon.
+    Grid,
+}
+
+/// Color theme for the UI.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Theme {
+    /// Match the operating system's theme.
+    #[default]
+    FollowOs,
+    Dark,
+    Light,
+}
+impl Theme {
+    /// Returns whether the theme is dark, given whether the operating system's
+    /// theme is dark.
+    pub fn is_dark(self, os_is_dark: bool) -> bool {
+        match self {
+            Theme::FollowOs => os_is_dark,
+            Theme::Dark => true,
+            Theme::Light => false,
+        }
+    }
+
+    /// Returns the default background and outline colors for a dark or light
+    /// theme.
+    pub fn default_colors(dark: bool) -> (egui::Color32, egui::Color32) {
+        if dark {
+            let defaults = &*super::DEFAULT_PREFS;
+            (defaults.colors.background, defaults.outlines.default_color)
+        } else {
+            (LIGHT_THEME_BACKGROUND, LIGHT_THEME_OUTLINE)
+        }
+    }
+}
+
+/// Returns whether the operating system is using a dark theme.
+pub fn os_is_dark() -> bool {
+    !matches!(dark_light::detect(), dark_light::Mode::Light)
+}
+
 // TODO: rename this type and use it for all colors. also impl display
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(transparent)]
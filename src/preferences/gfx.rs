@@ -5,20 +5,62 @@ use serde::{Deserialize, Serialize};
 #[serde(default)]
 pub struct GfxPreferences {
     pub fps_limit: usize,
+    /// Whether to lower the framerate when nothing is changing and there's no
+    /// input.
+    pub idle_mode: bool,
+    /// Framerate while idle.
+    pub idle_fps_limit: usize,
+    /// Scale factor for the UI, on top of the display's DPI scaling.
+    pub ui_scale: f32,
     pub msaa: bool,
+    pub show_fps: bool,
+    /// Whether to show a breakdown of frame time over the puzzle view.
+    pub show_perf_hud: bool,
+
+    /// Whether to draw tiny stickers as simple quads without outlines.
+    pub lod: bool,
+    /// Projected size (in pixels) below which stickers are simplified.
+    pub lod_min_size: f32,
+
+    /// Stereoscopic rendering mode.
+    pub stereo: StereoMode,
+    /// Horizontal shift per unit of depth between the two eyes.
+    pub eye_separation: f32,
 }
 impl Default for GfxPreferences {
     fn default() -> Self {
         Self {
             fps_limit: 60,
+            idle_mode: true,
+            idle_fps_limit: 5,
+            ui_scale: 1.0,
             msaa: true,
+            show_fps: false,
+            show_perf_hud: false,
+
+            lod: true,
+            lod_min_size: 4.0,
+
+            stereo: StereoMode::Off,
+            eye_separation: 0.06,
         }
     }
 }
 impl GfxPreferences {
-    /// Returns the duration of one frame based on the configured FPS value.
-    pub fn frame_duration(&self) -> Duration {
-        Duration::from_secs_f64(1.0 / self.fps_limit as f64)
+    /// Returns the duration of one frame based on the configured FPS value,
+    /// or the idle FPS value if the app is idle.
+    pub fn frame_duration(&self, is_idle: bool) -> Duration {
+        let fps_limit = match is_idle && self.idle_mode {
+            true => self.idle_fps_limit.min(self.fps_limit),
+            false => self.fps_limit,
+        };
+        Duration::from_secs_f64(1.0 / fps_limit.max(1) as f64)
+    }
+
+    /// Returns the number of physical pixels per UI point, given the display's
+    /// scale factor.
+    pub fn pixels_per_point(&self, scale_factor: f32) -> f32 {
+        scale_factor * self.ui_scale
     }
 
     /// Returns the MSAA sample count.
@@ -30,3 +72,18 @@ impl GfxPreferences {
         }
     }
 }
+
+/// Stereoscopic rendering mode, which draws the puzzle once for each eye.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StereoMode {
+    /// Draw the puzzle once.
+    #[default]
+    Off,
+    /// Draw the left eye on the left half of the view and the right eye on
+    /// the right half, for VR headsets and parallel viewing.
+    SideBySide,
+    /// Draw the left eye in red and the right eye in cyan, for red-cyan
+    /// glasses.
+    Anaglyph,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct GuidePreferences {
+    /// Whether to draw an arrow along each axis of a 4D puzzle, from the
+    /// negative cell to the positive cell.
+    pub axis_arrows: bool,
+    /// Whether to pulse all stickers in the same cell as the hovered sticker.
+    pub highlight_cell: bool,
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use crate::commands::Command;
+
+/// Hotkeys that work even when the window is not focused, written like
+/// `Ctrl+Shift+F9`. Empty strings are ignored.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct HotkeyPreferences {
+    /// Whether to listen for hotkeys while the window is not focused.
+    pub enabled: bool,
+    /// Hotkey to start or stop the timer.
+    pub toggle_timer: String,
+    /// Hotkey to scramble the puzzle with the next scramble.
+    pub next_scramble: String,
+}
+impl HotkeyPreferences {
+    /// Returns each hotkey with the command it runs.
+    pub fn bindings(&self) -> [(&str, Command); 2] {
+        [
+            (&self.toggle_timer, Command::ToggleTimer),
+            (&self.next_scramble, Command::NextScramble),
+        ]
+    }
+}
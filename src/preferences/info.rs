@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::puzzle::TwistMetric;
+use crate::puzzle::{TwistCountOptions, TwistMetric};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default)]
@@ -8,10 +8,22 @@ pub struct InfoPreferences {
     pub metric: TwistMetric,
     #[serde(skip)]
     pub qtm: bool,
+    /// Options for the move count window.
+    pub move_count: TwistCountOptions,
 
     pub keybinds_reference: KeybindsReferencePreferences,
 
     pub modifier_toggles: bool,
+
+    /// Whether to describe the puzzle state and last action in text for
+    /// screen readers.
+    pub accessibility_descriptions: bool,
+    /// Whether to show the face, piece type, and position of the hovered
+    /// sticker in a tooltip.
+    pub sticker_tooltips: bool,
+    /// Whether to show the last layer case in the status bar when a 3D cube
+    /// reaches the last layer.
+    pub last_layer_case: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Copy, Clone)]
@@ -1,9 +1,17 @@
 use serde::{Deserialize, Serialize};
 
+use crate::puzzle::SolvedStateDefinition;
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(default)]
 pub struct InteractionPreferences {
     pub confirm_discard_only_when_scrambled: bool,
+    /// What counts as a solved puzzle when a scrambled puzzle is checked for
+    /// completion.
+    pub solved_state: SolvedStateDefinition,
+    /// Whether center stickers show their orientation, which must also be
+    /// solved.
+    pub supercube: bool,
 
     pub drag_sensitivity: f32,
     pub realign_on_release: bool,
@@ -11,6 +19,49 @@ pub struct InteractionPreferences {
     pub smart_realign: bool,
 
     pub dynamic_twist_speed: bool,
+    /// How the twist speed increases with the number of queued twists.
+    pub dynamic_twist_curve: DynamicTwistCurve,
+    /// How quickly the twist speed increases with the number of queued
+    /// twists, for the exponential and linear curves.
+    pub dynamic_twist_factor: f32,
+    /// If at least this much of a twist would be animated in one frame, the
+    /// animation is skipped to reduce unnecessary flashing.
+    pub twist_skip_threshold: f32,
+    /// Whether twists that move different pieces, such as twists of opposite
+    /// faces, are animated at the same time.
+    pub simultaneous_twists: bool,
+    /// Whether consecutive queued twists of the same layers in the same
+    /// direction are animated as a single twist.
+    pub merge_repeated_twists: bool,
     pub twist_duration: f32,
     pub other_anim_duration: f32,
+    /// Whether undoing plays the original twist backwards instead of playing
+    /// the inverse twist, with the history outline color.
+    pub rewind_undo: bool,
+}
+
+/// Curve for increasing the twist speed when twists are queued.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DynamicTwistCurve {
+    /// Speed multiplies by a constant factor for each queued twist.
+    #[default]
+    Exponential,
+    /// Speed increases by a constant amount for each queued twist.
+    Linear,
+    /// Speed is proportional to the number of twists in the queue, so the
+    /// whole queue takes about as long as one twist.
+    QueueProportional,
+}
+impl DynamicTwistCurve {
+    /// Returns the multiplier for the twist speed when `queued` twists are
+    /// waiting behind the one being animated.
+    pub fn speed_multiplier(self, queued: usize, factor: f32) -> f32 {
+        let queued = queued as f32;
+        match self {
+            DynamicTwistCurve::Exponential => (queued * factor).exp(),
+            DynamicTwistCurve::Linear => 1.0 + queued * factor,
+            DynamicTwistCurve::QueueProportional => 1.0 + queued,
+        }
+    }
 }
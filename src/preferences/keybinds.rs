@@ -20,8 +20,34 @@ pub struct KeybindSet<C: Default> {
 pub struct Keybind<C> {
     #[serde(flatten, deserialize_with = "deser_valid_key_combo")]
     pub key: KeyCombo,
+    /// Other keys that must be held down when `key` is pressed, in the order
+    /// they were pressed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub chord: Vec<Key>,
+    /// Name of the layer that the keybind belongs to, which is empty for the
+    /// base layer. Layers are activated by holding down a key bound to a
+    /// layer command.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub layer: String,
     pub command: C,
 }
+impl<C> Keybind<C> {
+    /// Returns a string describing the keys that must be pressed to activate
+    /// the keybind, including the chord.
+    pub fn key_string(&self) -> String {
+        chord_string(&self.chord, self.key)
+    }
+}
+/// Returns a string describing a key combo pressed while holding down other
+/// keys.
+pub fn chord_string(chord: &[Key], key: KeyCombo) -> String {
+    let mut ret = String::new();
+    for k in chord {
+        ret += &format!("{k}+");
+    }
+    ret += &key.to_string();
+    ret
+}
 fn deser_valid_key_combo<'de, D: Deserializer<'de>>(deserializer: D) -> Result<KeyCombo, D::Error> {
     KeyCombo::deserialize(deserializer).map(KeyCombo::validate)
 }
@@ -47,10 +73,18 @@ impl fmt::Display for KeyCombo {
         write!(f, "{}", mods)?;
 
         match self.key {
-            Some(Key::Sc(sc)) => write!(f, "{}", key_names::key_name(sc)),
+            Some(key) => write!(f, "{key}"),
+            None => write!(f, "(no key set)"),
+        }
+    }
+}
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Key::Sc(sc) => write!(f, "{}", key_names::key_name(sc)),
             // TODO: virtual key code names aren't platform-aware and might not
             // match scancode names
-            Some(Key::Vk(vk)) => match vk {
+            Key::Vk(vk) => match vk {
                 VirtualKeyCode::Key1 => write!(f, "1"),
                 VirtualKeyCode::Key2 => write!(f, "2"),
                 VirtualKeyCode::Key3 => write!(f, "3"),
@@ -67,7 +101,6 @@ impl fmt::Display for KeyCombo {
                 VirtualKeyCode::Capital => write!(f, "CapsLock"),
                 other => write!(f, "{:?}", other),
             },
-            None => write!(f, "(no key set)"),
         }
     }
 }
@@ -0,0 +1,115 @@
+use egui_dock::{Node, NodeIndex, Tree};
+use serde::{Deserialize, Serialize};
+
+/// Fraction of the width of the main window taken by the puzzle view when a
+/// panel is first docked beside it.
+const DOCKED_PUZZLE_FRACTION: f32 = 0.7;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct LayoutPreferences {
+    /// Arrangement of the puzzle view and panels docked beside it.
+    pub dock: Tree<DockTab>,
+    /// Panels that are shown in their own OS window instead of inside the
+    /// main window.
+    pub detached: Vec<DetachedWindowLayout>,
+    /// Width of the main window (in points) below which side panels and
+    /// docked panels are shown as overlays on top of the puzzle view. Zero
+    /// disables the compact layout.
+    pub compact_width: f32,
+}
+impl Default for LayoutPreferences {
+    fn default() -> Self {
+        Self {
+            dock: Tree::new(vec![DockTab::Puzzle]),
+            detached: vec![],
+            compact_width: 700.0,
+        }
+    }
+}
+impl LayoutPreferences {
+    /// Returns whether a main window with the given width (in points) should
+    /// use the compact layout.
+    pub fn is_compact(&self, width: f32) -> bool {
+        width < self.compact_width
+    }
+
+    /// Returns whether any panels are docked beside the puzzle view.
+    pub fn has_docked_panels(&self) -> bool {
+        self.dock_tabs().any(|tab| tab != &DockTab::Puzzle)
+    }
+    /// Returns whether a panel is docked beside the puzzle view.
+    pub fn is_docked(&self, name: &str) -> bool {
+        self.dock_tabs()
+            .any(|tab| matches!(tab, DockTab::Panel(n) if n == name))
+    }
+    /// Docks a panel beside the puzzle view.
+    pub fn dock(&mut self, name: &str) {
+        if self.is_docked(name) {
+            return;
+        }
+        let tab = DockTab::Panel(name.to_owned());
+        if self.has_docked_panels() {
+            self.dock.push_to_focused_leaf(tab);
+        } else {
+            self.dock
+                .split_right(NodeIndex::root(), DOCKED_PUZZLE_FRACTION, vec![tab]);
+        }
+    }
+    /// Resets the dock if the puzzle view is missing from it, which can
+    /// happen if the preferences file was edited by hand.
+    pub fn fix_dock(&mut self) {
+        if !self.dock_tabs().any(|tab| tab == &DockTab::Puzzle) {
+            self.dock = Tree::new(vec![DockTab::Puzzle]);
+        }
+    }
+    fn dock_tabs(&self) -> impl Iterator<Item = &DockTab> {
+        self.dock.iter().flat_map(|node| match node {
+            Node::Leaf { tabs, .. } => &tabs[..],
+            _ => &[],
+        })
+    }
+
+    /// Returns whether a panel is in its own OS window.
+    pub fn is_detached(&self, name: &str) -> bool {
+        self.detached.iter().any(|w| w.name == name)
+    }
+    /// Moves a panel into its own OS window.
+    pub fn detach(&mut self, name: &str) {
+        if !self.is_detached(name) {
+            self.detached.push(DetachedWindowLayout {
+                name: name.to_owned(),
+                position: None,
+                size: None,
+            });
+        }
+    }
+    /// Moves a panel back into the main window.
+    pub fn reattach(&mut self, name: &str) {
+        self.detached.retain(|w| w.name != name);
+    }
+}
+
+/// Tab in the main window.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DockTab {
+    /// Puzzle view, which cannot be closed.
+    Puzzle,
+    /// Panel with the given name.
+    Panel(String),
+}
+
+/// Position and size of a panel in its own OS window.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct DetachedWindowLayout {
+    /// Name of the panel.
+    pub name: String,
+    /// Position of the window on the screen, in physical pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<[i32; 2]>,
+    /// Size of the window contents, in physical pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<[u32; 2]>,
+}
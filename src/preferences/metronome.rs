@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct MetronomePreferences {
+    /// Number of beats per second.
+    pub tps: f32,
+    /// Whether to flash the border of the puzzle view on each beat.
+    pub flash: bool,
+    /// Whether to report moves executed off-beat after each solve.
+    pub flag_off_beat: bool,
+    /// Maximum distance from a beat, as a fraction of the time between beats,
+    /// for a move to count as on-beat.
+    pub tolerance: f32,
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MiniModePreferences {
+    /// Whether to keep the window above other windows in mini mode.
+    pub always_on_top: bool,
+    /// Whether to make the window background see-through in mini mode.
+    pub transparent: bool,
+    /// Opacity of the window background when it is see-through.
+    pub background_opacity: f32,
+}
+impl Default for MiniModePreferences {
+    fn default() -> Self {
+        Self {
+            always_on_top: true,
+            transparent: false,
+            background_opacity: 0.75,
+        }
+    }
+}
@@ -10,36 +10,63 @@ use std::collections::{btree_map, BTreeMap};
 use std::ops::{Index, IndexMut};
 use std::path::PathBuf;
 
+mod api;
 mod colors;
 mod gfx;
+mod guides;
+mod hotkeys;
 mod info;
 mod interaction;
 mod keybinds;
+mod layout;
+mod metronome;
+mod mini_mode;
 mod migration;
 mod mousebinds;
 mod opacity;
 mod outlines;
+mod overlay;
 #[cfg(not(target_arch = "wasm32"))]
 mod persist_local;
 #[cfg(target_arch = "wasm32")]
 mod persist_web;
-mod view;
+mod scramble;
+mod screenshots;
+mod sounds;
+mod sync;
+mod timer;
 
+use crate::camera::CameraPath;
 use crate::commands::{Command, PuzzleCommand, PuzzleMouseCommand};
-use crate::puzzle::{traits::*, ProjectionType, PuzzleTypeEnum};
+use crate::i18n::Language;
+use crate::puzzle::{traits::*, Face, Grip, LayerMask, ProjectionType, PuzzleTypeEnum};
+pub use api::*;
 pub use colors::*;
 pub use gfx::*;
+pub use guides::*;
+pub use hotkeys::*;
+pub use hyperspeedcube_core::ViewPreferences;
 pub use info::*;
 pub use interaction::*;
 pub use keybinds::*;
+pub use layout::*;
+pub use metronome::*;
+pub use mini_mode::*;
 pub use mousebinds::*;
 pub use opacity::*;
 pub use outlines::*;
+pub use overlay::*;
 #[cfg(not(target_arch = "wasm32"))]
 use persist_local as persist;
+#[cfg(not(target_arch = "wasm32"))]
+pub use persist_local::{config_dir, ExternalEdit, PrefsWatcher};
 #[cfg(target_arch = "wasm32")]
 use persist_web as persist;
-pub use view::*;
+pub use scramble::*;
+pub use screenshots::*;
+pub use sounds::*;
+pub use sync::*;
+pub use timer::*;
 
 const PREFS_FILE_FORMAT: config::FileFormat = config::FileFormat::Yaml;
 const DEFAULT_PREFS_STR: &str = include_str!("default.yaml");
@@ -58,28 +85,54 @@ pub struct Preferences {
     /// Preferences file format version.
     #[serde(skip_deserializing)]
     pub version: u32,
+    /// Unix timestamp of when the preferences were last changed, used to
+    /// decide which preferences to keep when syncing.
+    pub modified: i64,
+    /// Contents of the preferences when they were last loaded or saved, not
+    /// including `modified`, used to tell whether they have changed since.
+    #[serde(skip)]
+    unmodified_contents: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub log_file: Option<PathBuf>,
 
     pub show_welcome_at_startup: bool,
+    pub language: Language,
 
     #[cfg(target_arch = "wasm32")]
     pub use_clipboard_fallback: bool,
 
     pub info: InfoPreferences,
+    pub overlay: OverlayPreferences,
+    pub sync: SyncPreferences,
+    pub api: ApiPreferences,
+    pub hotkeys: HotkeyPreferences,
 
+    pub layout: LayoutPreferences,
     pub gfx: GfxPreferences,
+    pub screenshots: ScreenshotPreferences,
     pub interaction: InteractionPreferences,
+    pub scramble: ScramblePreferences,
+    pub sounds: SoundPreferences,
+    pub metronome: MetronomePreferences,
+    pub timer: TimerPreferences,
+    pub mini_mode: MiniModePreferences,
     pub opacity: OpacityPreferences,
     pub outlines: OutlinePreferences,
+    pub guides: GuidePreferences,
 
     pub view_3d: WithPresets<ViewPreferences>,
     pub view_4d: WithPresets<ViewPreferences>,
+    /// Camera keyframes for animating the view during playback and replay
+    /// export.
+    pub camera_path: CameraPath,
 
     pub colors: ColorPreferences,
+    /// Custom labels for faces, by puzzle family and face symbol.
+    pub face_labels: PerPuzzleFamily<BTreeMap<String, String>>,
 
     pub piece_filters: PerPuzzle<Vec<Preset<PieceFilter>>>,
+    pub grip_groups: PerPuzzle<Vec<Preset<GripGroup>>>,
 
     pub global_keybinds: Vec<Keybind<Command>>,
     pub puzzle_keybinds: PerPuzzleFamily<PuzzleKeybindSets>,
@@ -99,7 +152,7 @@ impl Preferences {
             Err(e) => log::warn!("Error loading user preferences: {}", e),
         }
 
-        config
+        let mut prefs = config
             .build()
             .and_then(migration::try_deserialize)
             .unwrap_or_else(|e| {
@@ -120,7 +173,72 @@ impl Preferences {
                             .ok()
                     })
                     .unwrap_or_default()
-            })
+            });
+        prefs.unmodified_contents = prefs.contents_without_timestamp();
+
+        // Remember what was loaded, so that external edits made before the
+        // first save are merged against it.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(contents) = persist::read_prefs_file() {
+            persist::mark_synced(contents);
+        }
+
+        prefs
+    }
+
+    /// Parses the contents of a preferences file, using defaults for any
+    /// missing entries.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_user_str(contents: &str) -> Result<Self, config::ConfigError> {
+        config::Config::builder()
+            .add_source(config::File::from_str(DEFAULT_PREFS_STR, PREFS_FILE_FORMAT))
+            .add_source(config::File::from_str(contents, PREFS_FILE_FORMAT))
+            .build()
+            .and_then(migration::try_deserialize)
+    }
+
+    /// Merges a change to the preferences file made outside the app. Sections
+    /// that were also changed in the app since the file was last synced keep
+    /// their in-app values; all other sections are replaced with the values
+    /// from the file.
+    ///
+    /// Returns whether any preferences changed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn merge_external_edit(&mut self, edit: ExternalEdit) -> anyhow::Result<bool> {
+        let version = self.version;
+        let to_mapping = |prefs: Preferences| -> anyhow::Result<serde_yaml::Mapping> {
+            // Ignore the version number, which is only set when saving.
+            let prefs = Preferences { version, ..prefs };
+            match serde_yaml::to_value(prefs)? {
+                serde_yaml::Value::Mapping(m) => Ok(m),
+                _ => Ok(serde_yaml::Mapping::new()),
+            }
+        };
+
+        let ours = to_mapping(self.clone())?;
+        let theirs = to_mapping(Self::from_user_str(&edit.contents)?)?;
+        let base = match &edit.base {
+            Some(base) => to_mapping(Self::from_user_str(base)?)?,
+            None => ours.clone(),
+        };
+        persist::mark_synced(edit.contents);
+
+        let mut merged = theirs.clone();
+        for (key, value) in &ours {
+            if base.get(key) != Some(value) {
+                // This section has unsaved changes in the app.
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+        if merged == ours {
+            return Ok(false);
+        }
+
+        let needs_save = self.needs_save || merged != theirs;
+        *self = serde_yaml::from_value(serde_yaml::Value::Mapping(merged))?;
+        self.version = version;
+        self.needs_save = needs_save;
+        Ok(true)
     }
 
     pub fn save(&mut self) {
@@ -133,6 +251,12 @@ impl Preferences {
             // Set version number.
             self.version = migration::LATEST_VERSION;
 
+            let contents = self.contents_without_timestamp();
+            if contents != self.unmodified_contents {
+                self.modified = crate::util::unix_timestamp();
+                self.unmodified_contents = contents;
+            }
+
             let result = persist::save(self);
 
             match result {
@@ -142,6 +266,68 @@ impl Preferences {
         }
     }
 
+    /// Replaces the preferences with ones synced from another machine if those
+    /// were changed more recently. Sync settings and the log file are kept.
+    ///
+    /// Returns whether any preferences changed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn merge_synced(&mut self, contents: &str) -> anyhow::Result<bool> {
+        let theirs = Self::from_user_str(contents)?;
+        if theirs.modified <= self.modified {
+            return Ok(false);
+        }
+        *self = Preferences {
+            needs_save: true,
+            version: self.version,
+            unmodified_contents: theirs.contents_without_timestamp(),
+            log_file: self.log_file.take(),
+            sync: std::mem::take(&mut self.sync),
+            timer: std::mem::take(&mut self.timer),
+            ..theirs
+        };
+        Ok(true)
+    }
+
+    /// Returns the preferences to upload when syncing, without anything
+    /// specific to this machine.
+    pub fn to_synced_string(&self) -> anyhow::Result<String> {
+        let prefs = Preferences {
+            version: migration::LATEST_VERSION,
+            log_file: None,
+            sync: SyncPreferences::default(),
+            timer: TimerPreferences::default(),
+            ..self.clone()
+        };
+        Ok(serde_yaml::to_string(&prefs)?)
+    }
+
+    /// Returns the serialized preferences as they would be saved, but without
+    /// the modification timestamp.
+    fn contents_without_timestamp(&self) -> Option<String> {
+        let prefs = Preferences {
+            version: migration::LATEST_VERSION,
+            modified: 0,
+            ..self.clone()
+        };
+        serde_yaml::to_string(&prefs).ok()
+    }
+
+    /// Switches the background and outline colors to the defaults for a new
+    /// theme, unless they have been changed from the defaults for the old
+    /// theme.
+    pub fn switch_theme_colors(&mut self, was_dark: bool, is_dark: bool) {
+        let (old_background, old_outline) = Theme::default_colors(was_dark);
+        let (new_background, new_outline) = Theme::default_colors(is_dark);
+        if self.colors.background == old_background && old_background != new_background {
+            self.colors.background = new_background;
+            self.needs_save = true;
+        }
+        if self.outlines.default_color == old_outline && old_outline != new_outline {
+            self.outlines.default_color = new_outline;
+            self.needs_save = true;
+        }
+    }
+
     pub fn view(&self, ty: impl PuzzleType) -> &ViewPreferences {
         match ty.projection_type() {
             ProjectionType::_3D => &self.view_3d.current,
@@ -158,6 +344,16 @@ impl Preferences {
             ProjectionType::_4D => &mut self.view_4d,
         }
     }
+
+    /// Returns the label drawn on a face of the puzzle, which is the face's
+    /// symbol unless the user has chosen a different label.
+    pub fn face_label(&self, ty: PuzzleTypeEnum, face: Face) -> &str {
+        let symbol = ty.info(face).symbol;
+        self.face_labels
+            .get(ty)
+            .and_then(|labels| labels.get(symbol))
+            .map_or(symbol, |label| label.as_str())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
@@ -256,7 +452,19 @@ impl<T: Default> IndexMut<PuzzleTypeEnum> for PerPuzzle<T> {
         self.entry(puzzle_type).or_default()
     }
 }
+impl<T: Default> PerPuzzle<T> {
+    /// Returns the value for a puzzle given its name, inserting the default
+    /// value if there is none.
+    pub fn get_mut_by_name(&mut self, puzzle_name: String) -> &mut T {
+        self.map.entry(puzzle_name).or_default()
+    }
+}
 impl<T> PerPuzzle<T> {
+    /// Returns the values for every puzzle that has one.
+    pub fn values_mut(&mut self) -> btree_map::ValuesMut<'_, String, T> {
+        self.map.values_mut()
+    }
+
     fn entry(&mut self, puzzle_type: PuzzleTypeEnum) -> btree_map::Entry<'_, String, T> {
         self.map.entry(puzzle_type.name().to_owned())
     }
@@ -265,6 +473,15 @@ impl<T> PerPuzzle<T> {
     }
 }
 
+impl<T> IntoIterator for PerPuzzle<T> {
+    type Item = (String, T);
+    type IntoIter = btree_map::IntoIter<String, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(transparent)]
 pub struct PerPuzzleFamily<T> {
@@ -308,3 +525,77 @@ pub struct PieceFilter {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hidden_opacity: Option<f32>,
 }
+
+/// Saved twist selection.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct GripGroup {
+    /// Names of the selected twist axes.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub axes: Vec<String>,
+    /// Selected layers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layers: Option<LayerMask>,
+}
+impl GripGroup {
+    pub fn from_grip(ty: PuzzleTypeEnum, grip: &Grip) -> Self {
+        Self {
+            axes: grip
+                .axes
+                .iter()
+                .sorted_by_key(|axis| axis.0)
+                .map(|&axis| ty.info(axis).name.to_string())
+                .collect(),
+            layers: grip.layers,
+        }
+    }
+    /// Returns the grip for this group, ignoring any axes that the puzzle
+    /// doesn't have.
+    pub fn to_grip(&self, ty: PuzzleTypeEnum) -> Grip {
+        Grip {
+            axes: self
+                .axes
+                .iter()
+                .filter_map(|name| ty.twist_axis_from_name(name))
+                .collect(),
+            layers: self.layers.map(|l| l & ty.all_layers()),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_edit_before_first_save() {
+        let file_contents = |prefs: &Preferences| {
+            let prefs = Preferences {
+                version: migration::LATEST_VERSION,
+                ..prefs.clone()
+            };
+            serde_yaml::to_string(&prefs).unwrap()
+        };
+
+        // Load preferences, as at startup.
+        let loaded = file_contents(&DEFAULT_PREFS);
+        let mut prefs = Preferences::from_user_str(&loaded).unwrap();
+        persist::mark_synced(loaded);
+
+        // Change one section in the app and another in the file, without
+        // saving in between.
+        prefs.gfx.fps_limit = 30;
+        let mut edited = DEFAULT_PREFS.clone();
+        edited.show_welcome_at_startup = !edited.show_welcome_at_startup;
+        let edit = ExternalEdit::since_last_sync(file_contents(&edited)).unwrap();
+        assert!(edit.base.is_some());
+
+        assert!(prefs.merge_external_edit(edit).unwrap());
+        assert_eq!(prefs.gfx.fps_limit, 30);
+        assert_eq!(
+            prefs.show_welcome_at_startup,
+            edited.show_welcome_at_startup,
+        );
+        assert!(prefs.needs_save);
+    }
+}
@@ -45,13 +45,23 @@ pub enum MouseButton {
     Left,
     Right,
     Middle,
+    /// Extra mouse button, usually used for "back" (also called M4).
+    Back,
+    /// Extra mouse button, usually used for "forward" (also called M5).
+    Forward,
+    /// One step of the scroll wheel away from the user.
+    ScrollUp,
+    /// One step of the scroll wheel toward the user.
+    ScrollDown,
 }
-impl From<MouseButton> for egui::PointerButton {
-    fn from(b: MouseButton) -> Self {
+impl From<egui::PointerButton> for MouseButton {
+    fn from(b: egui::PointerButton) -> Self {
         match b {
-            MouseButton::Left => egui::PointerButton::Primary,
-            MouseButton::Right => egui::PointerButton::Secondary,
-            MouseButton::Middle => egui::PointerButton::Middle,
+            egui::PointerButton::Primary => MouseButton::Left,
+            egui::PointerButton::Secondary => MouseButton::Right,
+            egui::PointerButton::Middle => MouseButton::Middle,
+            egui::PointerButton::Extra1 => MouseButton::Back,
+            egui::PointerButton::Extra2 => MouseButton::Forward,
         }
     }
 }
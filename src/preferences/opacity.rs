@@ -1,12 +1,18 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 #[serde(default)]
 pub struct OpacityPreferences {
     pub base: f32,
     pub ungripped: f32,
     pub hidden: f32,
     pub selected: f32,
+    /// Opacity of solved pieces, when `dim_solved` is enabled.
+    pub solved: f32,
+
+    /// Whether to dim pieces that are already solved while the rest of the
+    /// puzzle is unsolved.
+    pub dim_solved: bool,
 
     pub unhide_grip: bool,
 
@@ -8,6 +8,7 @@ pub struct OutlinePreferences {
     pub hidden_size: f32,
     pub hovered_size: f32,
     pub selected_size: f32,
+    pub trail_size: f32,
 
     #[serde(with = "hex_color")]
     pub default_color: egui::Color32,
@@ -19,4 +20,39 @@ pub struct OutlinePreferences {
     pub selected_sticker_color: egui::Color32,
     #[serde(with = "hex_color")]
     pub selected_piece_color: egui::Color32,
+    #[serde(with = "hex_color")]
+    pub trail_color: egui::Color32,
+    /// Color of pieces being moved by undoing or redoing, if undo is
+    /// rewound.
+    #[serde(with = "hex_color")]
+    pub history_color: egui::Color32,
+
+    /// Whether to use the sticker's face color instead of `default_color`.
+    pub default_face_color: bool,
+    /// Whether to use the sticker's face color instead of `hidden_color`.
+    pub hidden_face_color: bool,
+    /// Whether to use the sticker's face color instead of `hovered_color`.
+    pub hovered_face_color: bool,
+    /// Whether to use the sticker's face color instead of
+    /// `selected_sticker_color` and `selected_piece_color`.
+    pub selected_face_color: bool,
+    /// Amount to darken (negative) or lighten (positive) face colors used for
+    /// outlines, from -1 to 1.
+    pub face_color_shade: f32,
+
+    /// Number of recent twists whose pieces are outlined in the move trail.
+    pub trail_length: usize,
+    /// Number of seconds for the move trail to fade after each twist.
+    pub trail_duration: f32,
+}
+impl OutlinePreferences {
+    /// Returns the outline color derived from a sticker's face color.
+    pub fn shaded_face_color(&self, face_color: egui::Rgba) -> egui::Rgba {
+        let shade = self.face_color_shade.clamp(-1.0, 1.0);
+        let target = match shade < 0.0 {
+            true => egui::Rgba::BLACK,
+            false => egui::Rgba::WHITE,
+        };
+        crate::util::mix(face_color, target, shade.abs())
+    }
 }
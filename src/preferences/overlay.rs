@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct OverlayPreferences {
+    /// Whether to continuously write live solve data to a file.
+    pub enabled: bool,
+    /// File to write live solve data to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    pub format: OverlayFormat,
+}
+
+/// File format for live solve data.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayFormat {
+    /// One `key: value` pair per line.
+    #[default]
+    Text,
+    /// JSON object.
+    Json,
+}
@@ -2,6 +2,7 @@ use directories::ProjectDirs;
 use serde::Serialize;
 use std::error::Error;
 use std::path::PathBuf;
+use std::sync::{mpsc, Mutex};
 
 const PREFS_FILE_NAME: &str = "hyperspeedcube";
 const PREFS_FILE_EXTENSION: &str = "yaml";
@@ -34,21 +35,26 @@ lazy_static! {
         }
     };
     static ref PROJECT_DIRS: Option<ProjectDirs> = ProjectDirs::from("", "", "Hyperspeedcube");
-    static ref PREFS_FILE_PATH: Result<PathBuf, PrefsError> = {
-        let mut p = if *NONPORTABLE {
+    static ref CONFIG_DIR: Result<PathBuf, PrefsError> = {
+        if *NONPORTABLE {
             log::info!("Using non-portable preferences path");
             match &*PROJECT_DIRS {
-                Some(proj_dirs) => proj_dirs.config_dir().to_owned(),
-                None => return Err(PrefsError::NoPreferencesPath),
+                Some(proj_dirs) => Ok(proj_dirs.config_dir().to_owned()),
+                None => Err(PrefsError::NoPreferencesPath),
             }
         } else {
             log::info!("Using portable preferences path");
-            LOCAL_DIR.clone()?
-        };
+            LOCAL_DIR.clone()
+        }
+    };
+    static ref PREFS_FILE_PATH: Result<PathBuf, PrefsError> = {
+        let mut p = CONFIG_DIR.clone()?;
         p.push(format!("{}.{}", PREFS_FILE_NAME, PREFS_FILE_EXTENSION));
         Ok(p)
     };
 
+    /// Contents of the preferences file when it was last saved or reloaded.
+    static ref LAST_SYNCED: Mutex<Option<String>> = Mutex::new(None);
 }
 
 #[derive(Display, Debug, Copy, Clone, PartialEq, Eq)]
@@ -60,6 +66,12 @@ pub enum PrefsError {
 }
 impl Error for PrefsError {}
 
+/// Returns the directory containing the preferences file, which is also used
+/// for other persistent data such as solve history.
+pub fn config_dir() -> Result<PathBuf, PrefsError> {
+    CONFIG_DIR.clone()
+}
+
 pub fn user_config_source() -> Result<impl config::Source, PrefsError> {
     PREFS_FILE_PATH
         .clone()
@@ -71,10 +83,89 @@ pub fn save(prefs_data: &impl Serialize) -> anyhow::Result<()> {
     if let Some(p) = path.parent() {
         std::fs::create_dir_all(p)?;
     }
-    serde_yaml::to_writer(std::fs::File::create(path)?, prefs_data)?;
+    let contents = serde_yaml::to_string(prefs_data)?;
+    std::fs::write(path, &contents)?;
+    mark_synced(contents);
     Ok(())
 }
 
+/// Returns the contents of the preferences file, if it exists.
+pub fn read_prefs_file() -> Option<String> {
+    std::fs::read_to_string(PREFS_FILE_PATH.as_ref().ok()?).ok()
+}
+
+/// Records the contents of the preferences file so that later changes can be
+/// compared against it.
+pub fn mark_synced(contents: String) {
+    *LAST_SYNCED.lock().unwrap() = Some(contents);
+}
+
+/// Change to the preferences file made outside the app.
+#[derive(Debug, Clone)]
+pub struct ExternalEdit {
+    /// Contents of the file when it was last saved or reloaded by the app.
+    pub base: Option<String>,
+    /// New contents of the file.
+    pub contents: String,
+}
+impl ExternalEdit {
+    /// Returns the change from the last synced contents of the preferences
+    /// file to `contents`, or `None` if they are the same.
+    pub fn since_last_sync(contents: String) -> Option<Self> {
+        let base = LAST_SYNCED.lock().unwrap().clone();
+        (base.as_ref() != Some(&contents)).then_some(Self { base, contents })
+    }
+}
+
+/// Watches the preferences file for changes made outside the app.
+pub struct PrefsWatcher {
+    _watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<()>,
+}
+impl PrefsWatcher {
+    pub fn new() -> anyhow::Result<Self> {
+        let path = PREFS_FILE_PATH.clone()?;
+        // Watch the whole directory, because many editors save files by
+        // replacing them.
+        let dir = path
+            .parent()
+            .ok_or(PrefsError::NoPreferencesPath)?
+            .to_owned();
+        std::fs::create_dir_all(&dir)?;
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(
+            move |result: notify::Result<notify::Event>| match result {
+                Ok(event) => {
+                    let is_write = event.kind.is_create() || event.kind.is_modify();
+                    if is_write
+                        && event
+                            .paths
+                            .iter()
+                            .any(|p| p.file_name() == path.file_name())
+                    {
+                        let _ = tx.send(());
+                    }
+                }
+                Err(e) => log::warn!("Error watching preferences file: {}", e),
+            },
+        )?;
+        notify::Watcher::watch(&mut watcher, &dir, notify::RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            events: rx,
+        })
+    }
+
+    /// Returns the new contents of the preferences file if it has been changed
+    /// by something other than this app.
+    pub fn poll(&self) -> Option<ExternalEdit> {
+        if self.events.try_iter().count() == 0 {
+            return None;
+        }
+        ExternalEdit::since_last_sync(read_prefs_file()?)
+    }
+}
+
 pub fn backup_prefs_file() {
     if let Ok(prefs_path) = &*PREFS_FILE_PATH {
         let mut backup_path = prefs_path.clone();
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use super::PerPuzzleFamily;
+use crate::puzzle::{traits::*, PuzzleTypeEnum, ScrambleOptions, TwistAxis};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct ScramblePreferences {
+    /// Maximum number of twists in a partial scramble.
+    pub partial_max: usize,
+    /// Number of upcoming scrambles to generate ahead of time.
+    pub queue_length: usize,
+    /// Number of twists in a full scramble of puzzles that are scrambled
+    /// using random twists, or `None` to use the default for each puzzle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_move_count: Option<usize>,
+    /// Whether to only twist a single outer layer at a time.
+    pub outer_layers_only: bool,
+    /// Names of twist axes that are never twisted, by puzzle family.
+    pub disallowed_axes: PerPuzzleFamily<BTreeSet<String>>,
+}
+impl ScramblePreferences {
+    /// Returns the scramble options for a puzzle.
+    pub fn options(&self, ty: PuzzleTypeEnum) -> ScrambleOptions {
+        let disallowed = &self.disallowed_axes[ty];
+        ScrambleOptions {
+            full_move_count: self.full_move_count,
+            outer_layers_only: self.outer_layers_only,
+            disallowed_axes: (0..ty.twist_axes().len() as _)
+                .map(TwistAxis)
+                .filter(|&axis| disallowed.contains(ty.info(axis).name))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ScreenshotPreferences {
+    /// Directory to save screenshots in. If this is `None`, screenshots are
+    /// saved in the user's pictures directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directory: Option<PathBuf>,
+    /// Resolution of screenshots, relative to the puzzle view.
+    pub scale: u32,
+    /// Whether to also copy screenshots to the clipboard.
+    pub copy_to_clipboard: bool,
+}
+impl Default for ScreenshotPreferences {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            scale: 1,
+            copy_to_clipboard: false,
+        }
+    }
+}
+impl ScreenshotPreferences {
+    /// Returns the directory to save screenshots in.
+    pub fn directory(&self) -> Option<PathBuf> {
+        self.directory.clone().or_else(|| {
+            let user_dirs = directories::UserDirs::new()?;
+            Some(user_dirs.picture_dir()?.to_owned())
+        })
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SoundPreferences {
+    /// Whether to play sound effects.
+    pub enabled: bool,
+
+    /// Volume multiplier applied to every sound.
+    pub master_volume: f32,
+
+    /// Volume for the click when a twist animation completes.
+    pub twist_volume: f32,
+    /// Volume for the beeps when the timer starts and stops.
+    pub timer_volume: f32,
+    /// Volume for the calls after 8 and 12 seconds of inspection.
+    pub inspection_volume: f32,
+    /// Volume for the fanfare on a new personal best.
+    pub pb_volume: f32,
+    /// Volume for metronome ticks.
+    pub metronome_volume: f32,
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SyncPreferences {
+    /// Whether to sync when the app starts.
+    pub on_startup: bool,
+    /// URL of the folder on the server to store synced files in, such as a
+    /// WebDAV folder. Credentials may be included in the URL.
+    pub url: String,
+    /// Token sent as a bearer token with each request, if not empty.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub token: String,
+}
@@ -0,0 +1,15 @@
+use instant::Duration;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct TimerPreferences {
+    /// Time between pressing a key and the app receiving it, in milliseconds.
+    /// This depends on the keyboard, so it is not synced between machines.
+    pub input_latency_ms: u32,
+}
+impl TimerPreferences {
+    pub fn input_latency(&self) -> Duration {
+        Duration::from_millis(self.input_latency_ms as u64)
+    }
+}
@@ -4,24 +4,42 @@ use anyhow::Result;
 use bitvec::bitvec;
 use bitvec::slice::BitSlice;
 use bitvec::vec::BitVec;
-use cgmath::{Deg, InnerSpace, One, Quaternion, Rotation, Rotation3};
-use instant::Duration;
-use num_enum::FromPrimitive;
+use cgmath::{Deg, InnerSpace, One, Point2, Quaternion, Rotation, Rotation3};
+use instant::{Duration, Instant};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use std::borrow::Cow;
-use std::collections::{HashSet, VecDeque};
-use std::ops::{BitOr, BitOrAssign};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
 use std::sync::Arc;
 
-/// If at least this much of a twist is animated in one frame, just skip the
-/// animation to reduce unnecessary flashing.
-const MIN_TWIST_DELTA: f32 = 1.0 / 3.0;
-
-/// Higher number means faster exponential increase in twist speed.
-const EXP_TWIST_FACTOR: f32 = 0.5;
+/// Maximum number of recent twists whose pieces are outlined in the move
+/// trail.
+pub const MAX_TRAIL_LENGTH: usize = 20;
 
 /// Higher number means slower exponential decay of view angle offset.
 const VIEW_ANGLE_OFFSET_DECAY_RATE: f32 = 0.02_f32;
 
+/// Duration of the shake animation when a twist is blocked, in seconds.
+const BLOCKED_TWIST_SHAKE_DURATION: f32 = 0.4;
+/// Maximum angle of the shake animation when a twist is blocked.
+const BLOCKED_TWIST_SHAKE_ANGLE: Deg<f32> = Deg(4.0);
+/// Number of times the puzzle shakes back and forth when a twist is blocked.
+const BLOCKED_TWIST_SHAKE_COUNT: f32 = 3.0;
+
+/// Higher number means slower exponential decay of face and sticker spacing
+/// toward their target values.
+const SPACING_DECAY_RATE: f32 = 0.0005_f32;
+/// Difference from the target spacing below which the spacing snaps to it.
+const SPACING_EPSILON: f32 = 0.0001;
+/// Amount by which exploding the puzzle increases face and sticker spacing.
+const EXPLODED_SPACING_INCREASE: f32 = 0.4;
+/// Maximum face and sticker spacing.
+const MAX_SPACING: f32 = 0.9;
+
+/// Opacity below which the back faces of a sticker can be hovered.
+const SEE_THROUGH_OPACITY: f32 = 0.5;
+
 /// Interpolation functions.
 pub mod interpolate {
     use std::f32::consts::PI;
@@ -39,8 +57,9 @@ pub mod interpolate {
 }
 
 use super::*;
-use crate::commands::PARTIAL_SCRAMBLE_MOVE_COUNT_MAX;
-use crate::preferences::{InteractionPreferences, Preferences, ViewPreferences};
+use crate::preferences::{
+    InteractionPreferences, OpacityPreferences, Preferences, ViewPreferences,
+};
 use crate::util;
 use interpolate::InterpolateFn;
 
@@ -54,8 +73,13 @@ pub struct PuzzleController {
     puzzle: Puzzle,
     /// Twist animation state.
     twist_anim: TwistAnimationState,
+    /// Number of twist animations completed since the last call to
+    /// `take_completed_twist_count()`.
+    completed_twists: usize,
     /// View settings animation state.
     view_settings_anim: ViewSettingsAnimState,
+    /// Face and sticker spacing animation state.
+    spacing_anim: SpacingAnimState,
     /// View angle animation state.
     view_angle: ViewAngleAnimState,
 
@@ -73,15 +97,43 @@ pub struct PuzzleController {
     scramble_state: ScrambleState,
     /// Scramble twists.
     scramble: Vec<Twist>,
-    /// Undo history.
-    undo_buffer: Vec<HistoryEntry>,
-    /// Redo history.
-    redo_buffer: Vec<HistoryEntry>,
+    /// Seed that the scramble was generated from, if any.
+    scramble_seed: Option<u64>,
+    /// Whether the puzzle was given a full scramble, which stays `true` after
+    /// it is solved.
+    fully_scrambled: bool,
+    /// Whether the scramble was generated by this program, as opposed to
+    /// being loaded from somewhere else.
+    generated_scramble: bool,
+    /// Undo/redo history.
+    undo_tree: UndoTree,
+    /// Named states in the undo history that can be reverted to.
+    checkpoints: Vec<Checkpoint>,
+    /// Whether the displayed state is the inverse of the scramble, for
+    /// solving on the inverse using NISS (normal-inverse scramble switch).
+    inverse: bool,
+    /// Twists made on the other side of the scramble (normal or inverse),
+    /// which are applied as premoves on this side.
+    premoves: Vec<Twist>,
+    /// State from which twists are being explored, and the number of nodes in
+    /// the undo tree at the time, or `None` if not exploring.
+    explore_start: Option<(UndoNodeId, usize)>,
+    /// Progress recorded each time the puzzle was saved, from oldest to
+    /// newest.
+    progress_history: Vec<ProgressSnapshot>,
 
     /// Sticker that the user is hovering over.
     hovered_sticker: Option<Sticker>,
     /// Twists from the hovered sticker.
     hovered_twists: Option<ClickTwists>,
+    /// Hoverable stickers under the cursor and the polygon of each one that
+    /// is under the cursor, in order from front to back.
+    hover_stack: Vec<(Sticker, PickedPolygon)>,
+    /// Index into `hover_stack` of the hovered sticker.
+    hover_depth: usize,
+    /// Node in the undo tree whose twist is highlighted, along with the
+    /// pieces moved by that twist.
+    highlighted_undo_node: Option<(UndoNodeId, BitVec)>,
 
     /// Grip, which controls which pieces will be twisted.
     grip: Grip,
@@ -101,10 +153,27 @@ pub struct PuzzleController {
     /// Piece states, such as whether a piece is hidden. All values are
     /// represented as `f32` for animation.
     visual_piece_states: Vec<VisualPieceState>,
+    /// Pieces affected by each recent twist, along with when the twist was
+    /// made, from oldest to newest.
+    recent_twists: VecDeque<(Instant, BitVec)>,
 
     /// Cached sticker geometry.
     cached_geometry: Option<Arc<Vec<ProjectedStickerGeometry>>>,
+    /// Allocation from previously cached sticker geometry, kept around so that
+    /// it can be reused instead of allocating a new `Vec` every frame.
+    recycled_geometry: Vec<ProjectedStickerGeometry>,
     cached_geometry_params: Option<StickerGeometryParams>,
+    /// Visible region used for the cached sticker geometry.
+    cached_geometry_bounds: Option<(Point2<f32>, Point2<f32>)>,
+    /// Opacity preferences used for the cached sticker geometry.
+    cached_geometry_opacity: Option<OpacityPreferences>,
+    /// Cached geometry for each sticker that isn't being animated, which is
+    /// `None` if the sticker must be projected again.
+    cached_sticker_geometries: Vec<Option<Option<ProjectedStickerGeometry>>>,
+    cached_sticker_geometry_params: Option<StickerGeometryParams>,
+    /// Time spent sorting and culling stickers during the last call to
+    /// `geometry()`.
+    last_sort_duration: Duration,
 }
 impl Default for PuzzleController {
     fn default() -> Self {
@@ -128,7 +197,9 @@ impl PuzzleController {
         Self {
             puzzle: Puzzle::new(ty),
             twist_anim: TwistAnimationState::default(),
+            completed_twists: 0,
             view_settings_anim: ViewSettingsAnimState::default(),
+            spacing_anim: SpacingAnimState::default(),
             view_angle: ViewAngleAnimState::default(),
 
             is_unsaved: false,
@@ -137,11 +208,21 @@ impl PuzzleController {
 
             scramble_state: ScrambleState::None,
             scramble: vec![],
-            undo_buffer: vec![],
-            redo_buffer: vec![],
+            scramble_seed: None,
+            fully_scrambled: false,
+            generated_scramble: false,
+            undo_tree: UndoTree::default(),
+            checkpoints: vec![],
+            inverse: false,
+            premoves: vec![],
+            explore_start: None,
+            progress_history: vec![],
 
             hovered_sticker: None,
             hovered_twists: None,
+            hover_stack: vec![],
+            hover_depth: 0,
+            highlighted_undo_node: None,
 
             grip: Grip::default(),
             selection: HashSet::new(),
@@ -151,9 +232,16 @@ impl PuzzleController {
             hidden_pieces_preview_opacity: None,
 
             visual_piece_states: vec![VisualPieceState::default(); ty.pieces().len()],
+            recent_twists: VecDeque::new(),
 
             cached_geometry: None,
+            recycled_geometry: vec![],
             cached_geometry_params: None,
+            cached_geometry_bounds: None,
+            cached_geometry_opacity: None,
+            cached_sticker_geometries: vec![],
+            cached_sticker_geometry_params: None,
+            last_sort_duration: Duration::ZERO,
         }
     }
     /// Resets the puzzle.
@@ -166,7 +254,15 @@ impl PuzzleController {
         self.scramble_state
     }
     /// Reset and then scramble some number of moves.
-    pub fn scramble_n(&mut self, n: usize) -> Result<(), &'static str> {
+    pub fn scramble_n(&mut self, n: usize, options: &ScrambleOptions) -> Result<(), &'static str> {
+        self.scramble_n_with_rng(n, &mut rand::thread_rng(), options)
+    }
+    fn scramble_n_with_rng(
+        &mut self,
+        n: usize,
+        rng: &mut impl Rng,
+        options: &ScrambleOptions,
+    ) -> Result<(), &'static str> {
         self.reset();
 
         // Set a reasonable limit on the number of moves.
@@ -175,30 +271,174 @@ impl PuzzleController {
             return Err("Cannot scramble more than 10,000 moves");
         }
 
-        // Use a `while` loop instead of a `for` loop because moves may cancel.
-        while self.undo_buffer.len() < n {
-            self.twist(Twist::from_rng(self.ty()))?;
+        let axes: Vec<TwistAxis> = (0..self.twist_axes().len() as _)
+            .map(TwistAxis)
+            .filter(|axis| !options.disallowed_axes.contains(axis))
+            .collect();
+        if axes.is_empty() {
+            return Err("Cannot scramble with every twist axis disallowed");
+        }
+        // With only two axes, every twist after the first two would cancel
+        // trivially, so allow it in that case.
+        let avoid_redundant_twists = axes.len() > 2;
+        let mut twists = vec![];
+
+        // Use a `while` loop instead of a `for` loop because moves may cancel
+        // or be blocked by bandaged pieces.
+        let mut attempts = 0;
+        while self.undo_tree.path().len() < n {
+            attempts += 1;
+            if attempts > MAX_SCRAMBLE_LEN * 100 {
+                return Err("Cannot find enough legal twists to scramble");
+            }
+            let mut twist = Twist::from_rng(self.ty(), rng);
+            twist.axis = axes[rng.gen_range(0..axes.len())];
+            if options.outer_layers_only {
+                twist.layers = LayerMask(1);
+            }
+            if avoid_redundant_twists && wca::is_redundant_twist(&self.puzzle, &twists, twist) {
+                continue;
+            }
+            if self.puzzle.is_twist_legal(twist) {
+                self.twist(twist)?;
+                // A twist that cancels the previous one undoes it instead, so
+                // keep only the twists that are still in the undo history.
+                let path = self.undo_tree.path();
+                twists.truncate(path.len());
+                if twists.len() < path.len() {
+                    twists.extend(path.last().copied().and_then(HistoryEntry::twist));
+                }
+            }
         }
+        log::debug!("Scrambled {n} moves in {attempts} attempts");
         self.add_scramble_marker(ScrambleState::Partial);
+        self.generated_scramble = true;
+        Ok(())
+    }
+    /// Scramble the puzzle completely, from a random seed. The seed is only
+    /// recorded if `options` are the defaults, since otherwise it isn't
+    /// enough to reproduce the scramble.
+    pub fn scramble_full(&mut self, options: &ScrambleOptions) -> Result<(), &'static str> {
+        if *options == ScrambleOptions::default() {
+            self.scramble_full_seeded(rand::thread_rng().gen())
+        } else {
+            self.scramble_full_with_rng(&mut rand::thread_rng(), options)
+        }
+    }
+    /// Scramble the puzzle completely, using random numbers generated from a
+    /// seed so that the same seed always gives the same scramble.
+    pub fn scramble_full_seeded(&mut self, seed: u64) -> Result<(), &'static str> {
+        let mut rng = seeded_rng(seed);
+        self.scramble_full_with_rng(&mut rng, &ScrambleOptions::default())?;
+        self.scramble_seed = Some(seed);
+        Ok(())
+    }
+    fn scramble_full_with_rng(
+        &mut self,
+        rng: &mut impl Rng,
+        options: &ScrambleOptions,
+    ) -> Result<(), &'static str> {
+        // Scrambles with custom options don't follow the WCA regulations
+        // anyway, so they aren't verified.
+        let verify = wca::is_wca_puzzle(self.ty()) && *options == ScrambleOptions::default();
+        const MAX_ATTEMPTS: usize = 100;
+        for _ in 0..MAX_ATTEMPTS {
+            self.generate_full_scramble(rng, options)?;
+            if !verify || wca::verify_scramble(self.ty(), &self.scramble).is_ok() {
+                self.generated_scramble = true;
+                return Ok(());
+            }
+        }
+        Err("Unable to generate a scramble that follows the WCA regulations")
+    }
+    fn generate_full_scramble(
+        &mut self,
+        rng: &mut impl Rng,
+        options: &ScrambleOptions,
+    ) -> Result<(), &'static str> {
+        // Random-state scrambles can't be restricted.
+        let random_scramble = if options.restricts_twists() {
+            None
+        } else {
+            self.random_scramble(rng)
+        };
+        if let Some(twists) = random_scramble {
+            self.scramble_with(&twists)?;
+        } else {
+            let n = options
+                .full_move_count
+                .unwrap_or_else(|| self.scramble_moves_count());
+            self.scramble_n_with_rng(n, rng, options)?;
+            self.scramble_state = ScrambleState::Full;
+            self.fully_scrambled = true;
+        }
+        Ok(())
+    }
+    /// Returns the seed that the scramble was generated from, if any.
+    pub fn scramble_seed(&self) -> Option<u64> {
+        self.scramble_seed
+    }
+    /// Returns the version of the program that generated the scramble, or
+    /// `None` if it was loaded from somewhere else.
+    pub fn scrambler_version(&self) -> Option<&'static str> {
+        self.generated_scramble.then_some(SCRAMBLER_VERSION)
+    }
+    /// Marks the scramble as generated by this program, for scrambles that
+    /// were generated ahead of time and then applied using
+    /// [`Self::scramble_with()`].
+    pub fn mark_scramble_generated(&mut self) {
+        self.generated_scramble = true;
+    }
+    /// Reset and then apply a specific full scramble.
+    pub fn scramble_with(&mut self, twists: &[Twist]) -> Result<(), &'static str> {
+        self.reset();
+        for &twist in twists {
+            self.twist_no_collapse(twist)?;
+        }
+        self.add_scramble_marker(ScrambleState::Full);
         Ok(())
     }
-    /// Scramble the puzzle completely.
-    pub fn scramble_full(&mut self) -> Result<(), &'static str> {
-        self.scramble_n(self.scramble_moves_count())?;
-        self.scramble_state = ScrambleState::Full;
+    /// Reset and then apply the same scramble again, keeping its seed.
+    pub fn rescramble(&mut self) -> Result<(), &'static str> {
+        if self.scramble_state == ScrambleState::None {
+            return Err("Puzzle has not been scrambled");
+        }
+        let scramble = std::mem::take(&mut self.scramble);
+        let scramble_seed = self.scramble_seed;
+        let generated_scramble = self.generated_scramble;
+        let new_scramble_state = if self.fully_scrambled {
+            ScrambleState::Full
+        } else {
+            ScrambleState::Partial
+        };
+        self.reset();
+        for twist in scramble {
+            self.twist_no_collapse(twist)?;
+        }
+        self.add_scramble_marker(new_scramble_state);
+        self.scramble_seed = scramble_seed;
+        self.generated_scramble = generated_scramble;
         Ok(())
     }
     /// Marks the puzzle as scrambled.
     pub fn add_scramble_marker(&mut self, new_scramble_state: ScrambleState) {
         self.skip_twist_animations();
+        let undo_tree = std::mem::take(&mut self.undo_tree);
         self.scramble
-            .extend(self.undo_buffer.drain(..).filter_map(HistoryEntry::twist));
+            .extend(undo_tree.into_path().into_iter().filter_map(HistoryEntry::twist));
+        self.checkpoints.clear();
+        self.explore_start = None;
+        self.inverse = false;
+        self.premoves.clear();
+        self.progress_history.clear();
+        self.highlighted_undo_node = None;
         if new_scramble_state == ScrambleState::None {
             // This is technically invalid? But I've seen some older MC4D log files that do this, so just assume it's a full scramble.
             self.scramble_state = ScrambleState::Full;
         } else {
             self.scramble_state = new_scramble_state;
         }
+        self.fully_scrambled = self.scramble_state == ScrambleState::Full;
     }
 
     pub fn is_non_rotation(&self, mut twist: Twist) -> bool {
@@ -224,18 +464,25 @@ impl PuzzleController {
         if twist.layers == LayerMask(0) {
             return Err("invalid layer mask");
         }
+        // Check the latest state, which may differ from the displayed state
+        // if there are twists in the animation queue.
+        if !self.puzzle.is_twist_legal(twist) {
+            log::trace!("Blocked twist {twist:?}");
+            self.view_angle.blocked_twist_shake = 1.0;
+            return Err(BLOCKED_TWIST_ERROR);
+        }
 
         self.mark_unsaved();
-        self.redo_buffer.clear();
         twist = self.canonicalize_twist(twist);
+        log::trace!("Twist {twist:?}");
 
-        if collapse && self.undo_buffer.last() == Some(&self.reverse_twist(twist).into()) {
+        if collapse && self.undo_tree.path().last() == Some(&self.reverse_twist(twist).into()) {
             // This twist is the reverse of the last one, so just undo the last
             // one.
             self.undo()
         } else {
             self.animate_twist(twist)?;
-            self.undo_buffer.push(twist.into());
+            self.undo_tree.push(twist.into());
             Ok(())
         }
     }
@@ -246,12 +493,11 @@ impl PuzzleController {
             for twist in twists {
                 self.mark_unsaved();
 
-                if self.undo_buffer.last() == Some(&self.reverse_twist(twist).into()) {
+                if self.undo_tree.path().last() == Some(&self.reverse_twist(twist).into()) {
                     // This twist is the reverse of the last one, so just undo the last one.
-                    self.redo_buffer.extend(self.undo_buffer.pop());
+                    self.undo_tree.undo();
                 } else {
-                    self.redo_buffer.clear();
-                    self.undo_buffer.push(twist.into());
+                    self.undo_tree.push(twist.into());
                 }
                 if self.puzzle.twist(twist).is_err() {
                     log::error!("error applying transient rotation twist {:?}", twist);
@@ -269,7 +515,7 @@ impl PuzzleController {
             }
 
             // Invalidate the cache.
-            self.cached_geometry = None;
+            self.invalidate_geometry_cache();
         }
     }
     /// Applies a twist to the puzzle and queues it for animation. Does _not_
@@ -277,14 +523,29 @@ impl PuzzleController {
     fn animate_twist(&mut self, twist: Twist) -> Result<(), &'static str> {
         let old_state = self.puzzle.clone();
         self.puzzle.twist(twist)?;
+
+        // Whole-puzzle rotations don't need a trail.
+        let affected: BitVec = (0..self.pieces().len() as _)
+            .map(|i| old_state.is_piece_affected_by_twist(twist, Piece(i)))
+            .collect();
+        if !affected.all() {
+            self.recent_twists.push_back((Instant::now(), affected));
+            if self.recent_twists.len() > MAX_TRAIL_LENGTH {
+                self.recent_twists.pop_front();
+            }
+        }
+
         self.twist_anim.queue.push_back(TwistAnimation {
             state: old_state,
             twist,
             view_angle_offset_delta: Quaternion::one(),
+            progress: 0.0,
+            reversed: false,
+            from_history: false,
         });
 
         // Invalidate the cache.
-        self.cached_geometry = None;
+        self.invalidate_geometry_cache();
 
         Ok(())
     }
@@ -294,7 +555,22 @@ impl PuzzleController {
         self.twist_anim
             .queue
             .get(0)
-            .map(|anim| (anim.twist, TWIST_INTERPOLATION_FN(self.twist_anim.progress)))
+            .map(TwistAnimation::twist_progress)
+    }
+    /// Returns the twists currently being animated, along with the progress
+    /// on each one. If `simultaneous` is true, this includes twists after the
+    /// first one that move different pieces.
+    pub fn current_twists(&self, simultaneous: bool) -> Vec<(Twist, f32)> {
+        let count = match simultaneous {
+            true => self.twist_anim.simultaneous_count(),
+            false => 1,
+        };
+        self.twist_anim
+            .queue
+            .iter()
+            .take(count)
+            .map(TwistAnimation::twist_progress)
+            .collect()
     }
 
     /// Returns the state of the cube that should be displayed, not including
@@ -372,9 +648,20 @@ impl PuzzleController {
         self.view_settings_anim.queue.push_back(view_prefs);
     }
 
+    /// Returns whether the puzzle is temporarily spaced out to show its
+    /// internals.
+    pub fn is_exploded(&self) -> bool {
+        self.spacing_anim.exploded
+    }
+    /// Sets whether the puzzle is temporarily spaced out to show its
+    /// internals.
+    pub fn set_exploded(&mut self, exploded: bool) {
+        self.spacing_anim.exploded = exploded;
+    }
+
     /// Returns whether this sticker can be hovered.
     fn is_sticker_hoverable(&self, sticker: Sticker) -> bool {
-        let less_than_halfway = TWIST_INTERPOLATION_FN(self.twist_anim.progress) < 0.5;
+        let less_than_halfway = self.current_twist().map_or(true, |(_, t)| t < 0.5);
         let puzzle_state = if less_than_halfway {
             self.displayed() // puzzle state before the twist
         } else {
@@ -389,14 +676,55 @@ impl PuzzleController {
     /// Sets the hovered stickers, in order from front to back.
     pub fn update_hovered_sticker(
         &mut self,
-        stickers_under_cursor: impl IntoIterator<Item = (Sticker, ClickTwists)>,
+        stickers_under_cursor: impl IntoIterator<Item = (Sticker, PickedPolygon)>,
     ) {
-        let hovered = stickers_under_cursor
+        let hover_stack = stickers_under_cursor
             .into_iter()
-            .find(|&(sticker, _twists)| self.is_sticker_hoverable(sticker));
+            .filter(|(sticker, _polygon)| self.is_sticker_hoverable(*sticker))
+            .collect::<Vec<_>>();
+
+        // Go back to the frontmost sticker whenever the cursor moves onto
+        // different stickers.
+        let old_stickers = self.hover_stack.iter().map(|(sticker, _)| sticker);
+        let new_stickers = hover_stack.iter().map(|(sticker, _)| sticker);
+        if !old_stickers.eq(new_stickers) {
+            self.hover_depth = 0;
+        }
 
-        self.hovered_sticker = hovered.map(|(sticker, _twists)| sticker);
-        self.hovered_twists = hovered.map(|(_sticker, twists)| twists);
+        self.hover_stack = hover_stack;
+        self.update_hovered_sticker_from_stack();
+    }
+    /// Hovers a sticker further behind (positive `offset`) or further in front
+    /// of (negative `offset`) the currently hovered one, wrapping around at
+    /// the ends of the stack of stickers under the cursor.
+    pub fn cycle_hover_depth(&mut self, offset: isize) {
+        let len = self.hover_stack.len() as isize;
+        if len > 0 {
+            self.hover_depth = (self.hover_depth as isize + offset).rem_euclid(len) as usize;
+            self.update_hovered_sticker_from_stack();
+        }
+    }
+    /// Returns the index of the hovered sticker in the stack of stickers under
+    /// the cursor, along with the size of the stack.
+    pub(crate) fn hover_depth(&self) -> (usize, usize) {
+        (self.hover_depth, self.hover_stack.len())
+    }
+    fn update_hovered_sticker_from_stack(&mut self) {
+        let hovered = self.hover_stack.get(self.hover_depth);
+        self.hovered_sticker = hovered.map(|(sticker, _polygon)| *sticker);
+        self.hovered_twists = hovered.map(|(_sticker, picked)| picked.polygon.twists);
+    }
+    /// Returns the polygon of the hovered sticker that is under the cursor.
+    pub(crate) fn hovered_polygon(&self) -> Option<&PickedPolygon> {
+        self.hover_stack
+            .get(self.hover_depth)
+            .map(|(_sticker, picked)| picked)
+    }
+    /// Returns whether a sticker is transparent enough that its back faces
+    /// should be pickable.
+    pub(crate) fn is_see_through(&self, sticker: Sticker, prefs: &Preferences) -> bool {
+        let piece = self.info(sticker).piece;
+        self.visual_piece_state(piece).opacity(prefs) < SEE_THROUGH_OPACITY
     }
     pub(crate) fn hovered_sticker(&self) -> Option<Sticker> {
         self.hovered_sticker
@@ -415,7 +743,7 @@ impl PuzzleController {
             // currently displaying;
             self.view_settings_anim.queue.pop_back();
         }
-        if let Some(old) = self.view_settings_anim.queue.get(0) {
+        let view_prefs = if let Some(old) = self.view_settings_anim.queue.get(0) {
             let new = self
                 .view_settings_anim
                 .queue
@@ -425,115 +753,229 @@ impl PuzzleController {
             Cow::Owned(ViewPreferences::interpolate(old, new, t))
         } else {
             Cow::Borrowed(old_view_prefs)
-        }
-    }
-    pub(crate) fn geometry(&mut self, prefs: &Preferences) -> Arc<Vec<ProjectedStickerGeometry>> {
+        };
+        // Use animated spacing.
+        self.spacing_anim.apply(view_prefs)
+    }
+    /// Returns the projected geometry of the stickers, sorted from back to
+    /// front. Stickers entirely outside `visible_bounds` (the minimum and
+    /// maximum visible coordinates) or entirely hidden behind opaque stickers
+    /// are omitted.
+    pub(crate) fn geometry(
+        &mut self,
+        prefs: &Preferences,
+        visible_bounds: (Point2<f32>, Point2<f32>),
+    ) -> Arc<Vec<ProjectedStickerGeometry>> {
         let view_prefs = self.view_prefs(prefs);
 
         self.update_transient_rotation(&prefs.interaction);
 
-        let params = StickerGeometryParams::new(
+        self.last_sort_duration = Duration::ZERO;
+
+        let mut params = StickerGeometryParams::new(
             &view_prefs,
             self.ty(),
             self.current_twist(),
-            self.view_angle.current * self.view_angle.queued_delta,
+            self.view_angle.current * self.view_angle.queued_delta * self.view_angle.shake(),
         );
+        params.supercube = prefs.interaction.supercube;
 
-        if self.cached_geometry_params != Some(params) {
+        if self.cached_geometry_params != Some(params)
+            || self.cached_geometry_bounds != Some(visible_bounds)
+            || self.cached_geometry_opacity.as_ref() != Some(&prefs.opacity)
+        {
             // Invalidate the cache.
-            self.cached_geometry = None;
+            self.invalidate_geometry_cache();
         }
 
         self.cached_geometry_params = Some(params);
+        self.cached_geometry_bounds = Some(visible_bounds);
+        self.cached_geometry_opacity = Some(prefs.opacity.clone());
 
-        let ret = self.cached_geometry.take().unwrap_or_else(|| {
-            log::trace!("Regenerating puzzle geometry");
+        if let Some(ret) = &self.cached_geometry {
+            return Arc::clone(ret);
+        }
 
-            // Project stickers.
-            let mut sticker_geometries: Vec<ProjectedStickerGeometry> = vec![];
-            for sticker in (0..self.stickers().len() as _).map(Sticker) {
-                let piece = self.info(sticker).piece;
-                let vis_piece = self.visual_piece_state(piece);
-                if !self.is_sticker_hoverable(sticker) && vis_piece.opacity(prefs) == 0.0 {
-                    continue;
-                }
+        log::trace!("Regenerating puzzle geometry");
 
-                // Compute geometry, including vertex positions before 3D
-                // perspective projection.
-                let sticker_geom = match self.displayed().sticker_geometry(sticker, params) {
-                    Some(s) => s,
-                    None => continue, // invisible; skip this sticker
-                };
+        // Stickers that aren't being animated don't depend on the twist
+        // animation, so their geometry can be reused between frames.
+        let static_params = StickerGeometryParams {
+            twist_animation: None,
+            ..params
+        };
+        if self.cached_sticker_geometry_params != Some(static_params) {
+            self.cached_sticker_geometries.clear();
+        }
+        self.cached_sticker_geometry_params = Some(static_params);
+        let mut cache = std::mem::take(&mut self.cached_sticker_geometries);
+        cache.resize_with(self.stickers().len(), || None);
+        let animations = self.current_twists(prefs.interaction.simultaneous_twists);
+
+        // Project stickers.
+        let mut sticker_geometries = std::mem::take(&mut self.recycled_geometry);
+        sticker_geometries.clear();
+        for sticker in (0..self.stickers().len() as _).map(Sticker) {
+            let piece = self.info(sticker).piece;
+            let vis_piece = self.visual_piece_state(piece);
+            if !self.is_sticker_hoverable(sticker) && vis_piece.opacity(prefs) == 0.0 {
+                continue;
+            }
 
-                // Compute vertex positions after 3D perspective projection.
-                let projected_verts = match sticker_geom
-                    .verts
-                    .iter()
-                    .map(|&v| params.project_3d(v))
-                    .collect::<Option<Vec<_>>>()
-                {
-                    Some(s) => s,
-                    None => continue, // behind camera; skip this sticker
-                };
-
-                let mut projected_front_polygons = vec![];
-                let mut projected_back_polygons = vec![];
-
-                for (indices, twists) in sticker_geom
-                    .polygon_indices
-                    .iter()
-                    .zip(sticker_geom.polygon_twists)
-                {
-                    let projected_normal =
-                        geometry::polygon_normal_from_indices(&projected_verts, indices);
-                    if projected_normal.z > 0.0 {
-                        // This polygon is front-facing.
-                        let lighting_normal =
-                            geometry::polygon_normal_from_indices(&sticker_geom.verts, indices)
-                                .normalize();
-                        let illumination =
-                            params.ambient_light + lighting_normal.dot(params.light_vector);
-                        projected_front_polygons.push(geometry::polygon_from_indices(
-                            &projected_verts,
-                            indices,
-                            illumination,
-                            twists,
-                        ));
-                    } else {
-                        // This polygon is back-facing.
-                        let illumination = 0.0; // don't care
-                        projected_back_polygons.push(geometry::polygon_from_indices(
-                            &projected_verts,
-                            indices,
-                            illumination,
-                            ClickTwists::default(), // don't care
-                        ));
-                    }
+            let animation = animations
+                .iter()
+                .find(|(twist, _)| self.displayed().is_piece_affected_by_twist(*twist, piece));
+            let sticker_geom = match animation {
+                Some(&animation) => {
+                    let params = StickerGeometryParams {
+                        twist_animation: Some(animation),
+                        ..params
+                    };
+                    self.project_sticker(sticker, params)
                 }
+                None => cache[sticker.0 as usize]
+                    .get_or_insert_with(|| self.project_sticker(sticker, static_params))
+                    .clone(),
+            };
+            let Some(sticker_geom) = sticker_geom else {
+                continue; // invisible or behind camera; skip this sticker
+            };
 
-                let (min_bound, max_bound) = util::min_and_max_bound(&projected_verts);
+            let (visible_min, visible_max) = visible_bounds;
+            if sticker_geom.max_bound.x < visible_min.x
+                || sticker_geom.max_bound.y < visible_min.y
+                || sticker_geom.min_bound.x > visible_max.x
+                || sticker_geom.min_bound.y > visible_max.y
+            {
+                continue; // outside viewport; skip this sticker
+            }
 
-                sticker_geometries.push(ProjectedStickerGeometry {
-                    sticker,
+            sticker_geometries.push(sticker_geom);
+        }
+        self.cached_sticker_geometries = cache;
 
-                    verts: projected_verts.into_boxed_slice(),
-                    min_bound,
-                    max_bound,
+        let sort_start = Instant::now();
 
-                    front_polygons: projected_front_polygons.into_boxed_slice(),
-                    back_polygons: projected_back_polygons.into_boxed_slice(),
-                });
-            }
+        // Sort stickers by depth.
+        geometry::sort_by_depth(&mut sticker_geometries);
 
-            // Sort stickers by depth.
-            geometry::sort_by_depth(&mut sticker_geometries);
-
-            Arc::new(sticker_geometries)
+        // Remove stickers hidden behind opaque stickers.
+        geometry::cull_occluded_stickers(&mut sticker_geometries, |sticker| {
+            let piece = self.info(sticker).piece;
+            self.visual_piece_state(piece).opacity(prefs) >= 1.0
         });
 
+        self.last_sort_duration = sort_start.elapsed();
+        log::trace!(
+            "Generated geometry for {} stickers; sorting took {:?}",
+            sticker_geometries.len(),
+            self.last_sort_duration,
+        );
+
+        let ret = Arc::new(sticker_geometries);
         self.cached_geometry = Some(Arc::clone(&ret));
         ret
     }
+    /// Returns the minimum and maximum X and Y coordinates of the whole puzzle
+    /// projected using `view_prefs`, ignoring twist animations and temporary
+    /// changes to the view angle, or `None` if no stickers are visible.
+    pub(crate) fn projected_bounds(
+        &self,
+        view_prefs: &ViewPreferences,
+    ) -> Option<(Point2<f32>, Point2<f32>)> {
+        let params = StickerGeometryParams::new(view_prefs, self.ty(), None, Quaternion::one());
+        (0..self.stickers().len() as _)
+            .filter_map(|sticker| self.project_sticker(Sticker(sticker), params))
+            .map(|geom| {
+                (
+                    cgmath::point2(geom.min_bound.x, geom.min_bound.y),
+                    cgmath::point2(geom.max_bound.x, geom.max_bound.y),
+                )
+            })
+            .reduce(|(min1, max1), (min2, max2)| {
+                (
+                    cgmath::point2(min1.x.min(min2.x), min1.y.min(min2.y)),
+                    cgmath::point2(max1.x.max(max2.x), max1.y.max(max2.y)),
+                )
+            })
+    }
+    /// Invalidates the cached sticker geometry. If nothing else is holding onto
+    /// it, its allocation is reused next time the geometry is generated.
+    fn invalidate_geometry_cache(&mut self) {
+        if let Some(old) = self.cached_geometry.take() {
+            if let Ok(old) = Arc::try_unwrap(old) {
+                self.recycled_geometry = old;
+            }
+        }
+    }
+    /// Returns the time spent sorting and culling stickers during the last call
+    /// to `geometry()`, which is zero if the geometry was cached.
+    pub(crate) fn last_sort_duration(&self) -> Duration {
+        self.last_sort_duration
+    }
+    /// Projects a sticker of the displayed puzzle state. Returns `None` if the
+    /// sticker is invisible or behind the camera.
+    fn project_sticker(
+        &self,
+        sticker: Sticker,
+        params: StickerGeometryParams,
+    ) -> Option<ProjectedStickerGeometry> {
+        // Compute geometry, including vertex positions before 3D perspective
+        // projection.
+        let sticker_geom = self.displayed().sticker_geometry(sticker, params)?;
+
+        // Compute vertex positions after 3D perspective projection.
+        let projected_verts = sticker_geom
+            .verts
+            .iter()
+            .map(|&v| params.project_3d(v))
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut projected_front_polygons = vec![];
+        let mut projected_back_polygons = vec![];
+
+        for (indices, twists) in sticker_geom
+            .polygon_indices
+            .iter()
+            .zip(sticker_geom.polygon_twists)
+        {
+            let projected_normal = geometry::polygon_normal_from_indices(&projected_verts, indices);
+            if projected_normal.z > 0.0 {
+                // This polygon is front-facing.
+                let lighting_normal =
+                    geometry::polygon_normal_from_indices(&sticker_geom.verts, indices).normalize();
+                let illumination = params.ambient_light + lighting_normal.dot(params.light_vector);
+                projected_front_polygons.push(geometry::polygon_from_indices(
+                    &projected_verts,
+                    indices,
+                    illumination,
+                    twists,
+                ));
+            } else {
+                // This polygon is back-facing.
+                let illumination = 0.0; // don't care
+                projected_back_polygons.push(geometry::polygon_from_indices(
+                    &projected_verts,
+                    indices,
+                    illumination,
+                    twists,
+                ));
+            }
+        }
+
+        let (min_bound, max_bound) = util::min_and_max_bound(&projected_verts);
+
+        Some(ProjectedStickerGeometry {
+            sticker,
+
+            verts: projected_verts.into(),
+            min_bound,
+            max_bound,
+
+            front_polygons: projected_front_polygons.into(),
+            back_polygons: projected_back_polygons.into(),
+        })
+    }
 
     /// Advances the puzzle geometry and internal state to the next frame, using
     /// the given time delta between this frame and the last.
@@ -544,6 +986,7 @@ impl PuzzleController {
 
         // Animate view settings.
         self.view_settings_anim.proceed(base_speed);
+        self.spacing_anim.proceed(delta);
 
         // Animate view angle offset.
         if !self.view_angle.is_frozen {
@@ -559,29 +1002,53 @@ impl PuzzleController {
             }
         }
 
+        // Animate shake from a blocked twist.
+        let shake = &mut self.view_angle.blocked_twist_shake;
+        *shake = (*shake - delta.as_secs_f32() / BLOCKED_TWIST_SHAKE_DURATION).max(0.0);
+
         // Animate twist.
         let anim = &mut self.twist_anim;
         if anim.queue.is_empty() {
             anim.queue_max = 0;
             self.view_angle.queued_delta = Quaternion::one();
-        } else {
+        } else if !anim.paused {
+            if prefs.merge_repeated_twists {
+                anim.merge_repeated(&self.puzzle);
+            }
             // Update queue_max.
             anim.queue_max = std::cmp::max(anim.queue_max, anim.queue.len());
-            // Twist exponentially faster if there are/were more twists in the
-            // queue.
+            // Twist faster if there are more twists in the queue.
             let speed_mod = match prefs.dynamic_twist_speed {
-                true => ((anim.queue.len() - 1) as f32 * EXP_TWIST_FACTOR).exp(),
+                true => prefs
+                    .dynamic_twist_curve
+                    .speed_multiplier(anim.queue.len() - 1, prefs.dynamic_twist_factor),
                 false => 1.0,
             };
             let mut twist_delta = base_speed * speed_mod;
             // Cap the twist delta at 1.0, and also handle the case where
             // something went wrong with the calculation (e.g., division by
             // zero).
-            if !(0.0..MIN_TWIST_DELTA).contains(&twist_delta) {
+            if !(0.0..prefs.twist_skip_threshold).contains(&twist_delta) {
                 twist_delta = 1.0; // Instantly complete the twist.
             }
-            if let Some(q) = self.twist_anim.proceed(twist_delta) {
-                self.view_angle.queued_delta = self.view_angle.queued_delta * q;
+            let simultaneous = match prefs.simultaneous_twists {
+                true => self.twist_anim.simultaneous_count(),
+                false => 1,
+            };
+            for done in self.twist_anim.proceed(twist_delta, simultaneous) {
+                self.completed_twists += 1;
+                self.view_angle.queued_delta =
+                    self.view_angle.queued_delta * done.view_angle_offset_delta;
+                // Only the pieces moved by the twist need to be projected again.
+                for piece in done.state.pieces_affected_by_twist(done.twist) {
+                    for &sticker in &done.state.info(piece).stickers {
+                        if let Some(entry) =
+                            self.cached_sticker_geometries.get_mut(sticker.0 as usize)
+                        {
+                            *entry = None;
+                        }
+                    }
+                }
             }
         }
     }
@@ -594,6 +1061,24 @@ impl PuzzleController {
 
         let delta = delta.as_secs_f32() / prefs.interaction.other_anim_duration;
 
+        let dim_solved = prefs.opacity.dim_solved && !self.puzzle.is_solved();
+        let history_anim = self
+            .twist_anim
+            .queue
+            .front()
+            .filter(|anim| anim.from_history);
+
+        // Forget twists that are too old to be in the trail.
+        let trail = &prefs.outlines;
+        let now = Instant::now();
+        while self.recent_twists.len() > trail.trail_length.min(MAX_TRAIL_LENGTH)
+            || self.recent_twists.front().map_or(false, |(time, _)| {
+                (now - *time).as_secs_f32() >= trail.trail_duration
+            })
+        {
+            self.recent_twists.pop_front();
+        }
+
         for piece in (0..self.pieces().len() as _).map(Piece) {
             let logical_state = self.logical_piece_state(piece);
 
@@ -604,8 +1089,23 @@ impl PuzzleController {
                 gripped: (gripped == Some(true)) as u8 as f32,
                 ungripped: (gripped == Some(false)) as u8 as f32,
                 hidden: hidden as u8 as f32,
+                solved: (dim_solved && self.puzzle.is_piece_solved(piece)) as u8 as f32,
                 selected: stickers.iter().any(|s| self.selection.contains(s)) as u8 as f32,
-                hovered: stickers.iter().any(|&s| Some(s) == self.hovered_sticker) as u8 as f32,
+                hovered: (stickers.iter().any(|&s| Some(s) == self.hovered_sticker)
+                    || self
+                        .highlighted_undo_node
+                        .as_ref()
+                        .map_or(false, |(_, pieces)| pieces[piece.0 as usize]))
+                    as u8 as f32,
+                trail: self
+                    .recent_twists
+                    .iter()
+                    .filter(|(_, pieces)| pieces[piece.0 as usize])
+                    .map(|(time, _)| 1.0 - (now - *time).as_secs_f32() / trail.trail_duration)
+                    .fold(0.0, f32::max),
+                history: history_anim.map_or(false, |anim| {
+                    anim.state.is_piece_affected_by_twist(anim.twist, piece)
+                }) as u8 as f32,
 
                 hidden_opacity_override: self.hidden_pieces_preview_opacity,
             };
@@ -631,15 +1131,27 @@ impl PuzzleController {
 
             let current = &mut self.visual_piece_states[piece.0 as usize];
             let was_visible = current.opacity(prefs) != 0.0;
+            let was_opaque = current.opacity(prefs) >= 1.0;
             changed |= approach_target(&mut current.gripped, target.gripped, delta);
             changed |= approach_target(&mut current.ungripped, target.ungripped, delta);
             changed |= approach_target(&mut current.hidden, target.hidden, delta);
+            changed |= approach_target(&mut current.solved, target.solved, delta);
             changed |= approach_target(&mut current.selected, target.selected, delta);
             changed |= approach_target(&mut current.hovered, target.hovered, delta);
             if current.hovered < target.hovered {
                 // Highlight hovered sticker instantly for better responsiveness.
                 changed |= approach_target(&mut current.hovered, target.hovered, f32::INFINITY);
             }
+            if current.trail != target.trail {
+                // The trail fades on its own schedule, so don't animate it.
+                current.trail = target.trail;
+                changed = true;
+            }
+            if current.history != target.history {
+                // The twist animation is already moving these pieces.
+                current.history = target.history;
+                changed = true;
+            }
             if current.hidden_opacity_override != target.hidden_opacity_override {
                 // I don't know how to animate this easily, so don't bother trying.
                 current.hidden_opacity_override = target.hidden_opacity_override;
@@ -649,7 +1161,13 @@ impl PuzzleController {
             if was_visible != is_visible {
                 // If a piece changes from invisible to visible, then it might need to be
                 // re-added to the geometry, so invalidate the cache.
-                self.cached_geometry = None;
+                self.invalidate_geometry_cache();
+            }
+            let is_opaque = current.opacity(prefs) >= 1.0;
+            if was_opaque != is_opaque {
+                // Pieces behind an opaque piece are culled, so they might need
+                // to be re-added to the geometry.
+                self.invalidate_geometry_cache();
             }
         }
 
@@ -737,32 +1255,100 @@ impl PuzzleController {
         self.selection = HashSet::new();
     }
 
+    /// Returns the number of twist animations that completed since the last
+    /// call to this function.
+    pub fn take_completed_twist_count(&mut self) -> usize {
+        std::mem::take(&mut self.completed_twists)
+    }
+    /// Returns the number of twists in the animation queue, including the one
+    /// currently being animated.
+    pub fn twist_queue_len(&self) -> usize {
+        self.twist_anim.queue.len()
+    }
+    /// Returns the twists in the animation queue, starting with the one
+    /// currently being animated.
+    pub fn queued_twists(&self) -> impl '_ + Iterator<Item = Twist> {
+        self.twist_anim.queue.iter().map(|anim| anim.twist)
+    }
+    /// Returns whether twist animations are paused.
+    pub fn is_twist_queue_paused(&self) -> bool {
+        self.twist_anim.paused
+    }
+    /// Pauses or resumes twist animations. Twists made while paused are
+    /// applied immediately but wait in the queue to be animated.
+    pub fn toggle_twist_queue_paused(&mut self) {
+        self.twist_anim.paused ^= true;
+    }
     /// Skips the animations for all twists in the queue.
     pub fn skip_twist_animations(&mut self) {
+        if !self.twist_anim.queue.is_empty() {
+            // The displayed puzzle state may change arbitrarily.
+            self.cached_sticker_geometries.clear();
+        }
         self.twist_anim.queue.clear();
+        self.twist_anim.paused = false;
+        // Twists that were never animated shouldn't leave a trail.
+        self.recent_twists.clear();
+    }
+    /// Takes back twists in the animation queue that haven't started
+    /// animating yet, and returns how many were taken back. They can be
+    /// redone afterward.
+    ///
+    /// Twists from undoing or redoing, and any twists queued before them, are
+    /// left in the queue.
+    pub fn cancel_queued_twists(&mut self) -> usize {
+        let mut cancelled = 0;
+        while self.twist_anim.queue.len() > 1 {
+            let Some(anim) = self.twist_anim.queue.back() else {
+                break;
+            };
+            if anim.reversed
+                || anim.progress > 0.0
+                || self.undo_tree.path().last() != Some(&anim.twist.into())
+            {
+                break;
+            }
+            let twist = anim.twist;
+            self.undo_tree.undo();
+            if let Some(anim) = self.twist_anim.queue.pop_back() {
+                self.puzzle = anim.state;
+            }
+            // Whole-puzzle rotations don't leave a trail.
+            if self.is_non_rotation(twist) {
+                self.recent_twists.pop_back();
+            }
+            cancelled += 1;
+        }
+        if cancelled > 0 {
+            self.mark_unsaved();
+            self.invalidate_geometry_cache();
+        }
+        cancelled
     }
 
     /// Returns whether there is a twist to undo.
     pub fn has_undo(&self) -> bool {
-        !self.undo_buffer.is_empty()
+        self.undo_tree.has_undo()
     }
     /// Returns whether there is a twist to redo.
     pub fn has_redo(&self) -> bool {
-        !self.redo_buffer.is_empty()
+        self.undo_tree.has_redo()
     }
 
     /// Undoes one twist. Returns an error if there was nothing to undo or the
     /// twist could not be applied to the puzzle.
     pub fn undo(&mut self) -> Result<(), &'static str> {
-        if let Some(entry) = self.undo_buffer.pop() {
-            self.mark_unsaved();
+        if let Some(&entry) = self.undo_tree.path().last() {
+            log::trace!("Undo {entry:?}");
+            // Only move in the undo tree once the twist has been applied.
             match entry {
                 HistoryEntry::Twist(twist) => {
                     let rev = self.reverse_twist(twist);
                     self.animate_twist(rev)?;
                 }
             }
-            self.redo_buffer.push(entry);
+            self.undo_tree.undo();
+            self.mark_unsaved();
             Ok(())
         } else {
             Err("Nothing to undo")
@@ -771,17 +1357,340 @@ impl PuzzleController {
     /// Redoes one twist. Returns an error if there was nothing to redo or the
     /// twist could not be applied to the puzzle.
     pub fn redo(&mut self) -> Result<(), &'static str> {
-        if let Some(entry) = self.redo_buffer.pop() {
-            self.mark_unsaved();
+        let current = self.undo_tree.current();
+        let next = self.undo_tree.redo_child(current);
+        if let Some(entry) = next.and_then(|node| self.undo_tree.entry(node)) {
+            log::trace!("Redo {entry:?}");
+            // Only move in the undo tree once the twist has been applied.
             match entry {
                 HistoryEntry::Twist(twist) => self.animate_twist(twist)?,
             }
-            self.undo_buffer.push(entry);
+            self.undo_tree.redo();
+            self.mark_unsaved();
             Ok(())
         } else {
             Err("Nothing to redo")
         }
     }
+    /// Undoes one twist to navigate the history rather than to solve. If
+    /// `rewind` is true, the original twist is animated backwards and its
+    /// pieces are outlined in the history color.
+    pub fn navigate_undo(&mut self, rewind: bool) -> Result<(), &'static str> {
+        let twist = self
+            .undo_tree
+            .path()
+            .last()
+            .copied()
+            .and_then(HistoryEntry::twist);
+        self.undo()?;
+        if let (true, Some(twist)) = (rewind, twist) {
+            if let Some(anim) = self.twist_anim.queue.back_mut() {
+                // Animate the original twist from the state after undoing it.
+                anim.state = self.puzzle.clone();
+                anim.twist = twist;
+                anim.reversed = true;
+                anim.from_history = true;
+            }
+        }
+        Ok(())
+    }
+    /// Redoes one twist to navigate the history rather than to solve. If
+    /// `rewind` is true, the twist's pieces are outlined in the history
+    /// color.
+    pub fn navigate_redo(&mut self, rewind: bool) -> Result<(), &'static str> {
+        self.redo()?;
+        if let (true, Some(anim)) = (rewind, self.twist_anim.queue.back_mut()) {
+            anim.from_history = true;
+        }
+        Ok(())
+    }
+    /// Undoes and redoes twists until reaching a node in the undo tree.
+    pub fn jump_to_undo_node(&mut self, node: UndoNodeId) -> Result<(), &'static str> {
+        while !self.undo_tree.is_ancestor(self.undo_tree.current(), node) {
+            self.undo()?;
+        }
+        self.undo_tree.select_branch(node);
+        while self.undo_tree.current() != node {
+            self.redo()?;
+        }
+        Ok(())
+    }
+
+    /// Highlights the pieces moved by the twist leading to a node in the undo
+    /// tree, or removes the highlight if `node` is `None`.
+    pub fn set_highlighted_undo_node(&mut self, node: Option<UndoNodeId>) {
+        if self.highlighted_undo_node.as_ref().map(|(n, _)| *n) == node {
+            return;
+        }
+        self.highlighted_undo_node =
+            node.and_then(|n| Some((n, self.pieces_twisted_at_undo_node(n)?)));
+    }
+    /// Returns the pieces moved by the twist leading to a node in the undo
+    /// tree, or `None` if the node is the root.
+    fn pieces_twisted_at_undo_node(&self, node: UndoNodeId) -> Option<BitVec> {
+        let tree = &self.undo_tree;
+        let twist = tree.entry(node)?.twist()?;
+        let before = tree.parent(node)?;
+
+        // Reconstruct the state just before the twist, starting from the
+        // current state: undo twists until reaching a common ancestor, then
+        // redo twists along the branch leading to `node`.
+        let mut puzzle = self.puzzle.clone();
+        let mut ancestor = tree.current();
+        while !tree.is_ancestor(ancestor, before) {
+            let rev = self.reverse_twist(tree.entry(ancestor)?.twist()?);
+            puzzle.twist(rev).ok()?;
+            ancestor = tree.parent(ancestor)?;
+        }
+        let mut redo_twists = vec![];
+        let mut n = before;
+        while n != ancestor {
+            redo_twists.push(tree.entry(n)?.twist()?);
+            n = tree.parent(n)?;
+        }
+        for t in redo_twists.into_iter().rev() {
+            puzzle.twist(t).ok()?;
+        }
+
+        Some(
+            (0..self.pieces().len() as _)
+                .map(|i| puzzle.is_piece_affected_by_twist(twist, Piece(i)))
+                .collect(),
+        )
+    }
+
+    /// Returns whether twists are being made in a sandbox that can be
+    /// discarded.
+    pub fn is_exploring(&self) -> bool {
+        self.explore_start.is_some()
+    }
+    /// Starts exploring from the current state. Twists made until the
+    /// exploration is discarded or kept do not count toward solving the
+    /// puzzle.
+    pub fn start_exploring(&mut self) {
+        self.explore_start = Some((self.undo_tree.current(), self.undo_tree.node_count()));
+    }
+    /// Stops exploring and returns to the state from before exploring,
+    /// removing all twists made since then from the undo tree.
+    pub fn discard_exploration(&mut self) -> Result<(), &'static str> {
+        let (node, node_count) = self.explore_start.ok_or("Not exploring")?;
+        self.jump_to_undo_node(node)?;
+        self.undo_tree.truncate(node_count);
+        self.checkpoints.retain(|c| c.node < node_count);
+        self.highlighted_undo_node = None;
+        self.explore_start = None;
+        Ok(())
+    }
+    /// Stops exploring and keeps the twists made since starting, as though
+    /// they had been made normally.
+    pub fn keep_exploration(&mut self) {
+        self.explore_start = None;
+    }
+
+    /// Returns whether the displayed state is the inverse of the scramble.
+    pub fn is_inverse(&self) -> bool {
+        self.inverse
+    }
+    /// Returns the twists made on the other side of the scramble, which are
+    /// applied as premoves on this side.
+    pub fn premoves(&self) -> &[Twist] {
+        &self.premoves
+    }
+    /// Switches between the normal and inverse scramble. Twists made on one
+    /// side become premoves on the other side, and the undo history is
+    /// replaced by the twists previously made on the new side.
+    pub fn toggle_inverse(&mut self) -> Result<(), &'static str> {
+        let moves = self.undo_buffer_twists();
+        let replay = std::mem::replace(&mut self.premoves, moves);
+        self.inverse = !self.inverse;
+        self.rebuild_from_scramble(&replay)
+    }
+    /// Sets which side of the scramble is displayed and the premoves made on
+    /// the other side, keeping the twists made on this side.
+    pub fn set_inverse_and_premoves(
+        &mut self,
+        inverse: bool,
+        premoves: Vec<Twist>,
+    ) -> Result<(), &'static str> {
+        let moves = self.undo_buffer_twists();
+        self.inverse = inverse;
+        self.premoves = premoves;
+        self.rebuild_from_scramble(&moves)
+    }
+    /// Returns the full solution, combining twists made on both sides of the
+    /// scramble: the normal twists followed by the inverse of the inverse
+    /// twists.
+    pub fn niss_solution(&self) -> Vec<Twist> {
+        let (normal, inverse) = if self.inverse {
+            (self.premoves.clone(), self.undo_buffer_twists())
+        } else {
+            (self.undo_buffer_twists(), self.premoves.clone())
+        };
+        let mut ret = normal;
+        ret.extend(self.invert_twists(&inverse));
+        ret
+    }
+    /// Switches to the normal scramble with no premoves and replaces the undo
+    /// history with `solution`.
+    pub fn replace_solution(&mut self, solution: &[Twist]) -> Result<(), &'static str> {
+        self.inverse = false;
+        self.premoves.clear();
+        self.rebuild_from_scramble(solution)
+    }
+    /// Returns the twists that undo a sequence of twists.
+    fn invert_twists(&self, twists: &[Twist]) -> Vec<Twist> {
+        twists.iter().rev().map(|&t| self.reverse_twist(t)).collect()
+    }
+    fn undo_buffer_twists(&self) -> Vec<Twist> {
+        self.undo_tree
+            .path()
+            .iter()
+            .copied()
+            .filter_map(HistoryEntry::twist)
+            .collect()
+    }
+    /// Resets the puzzle to the scramble on the current side with premoves
+    /// applied, and then applies `twists`. The undo tree, checkpoints, and
+    /// exploration are discarded.
+    fn rebuild_from_scramble(&mut self, twists: &[Twist]) -> Result<(), &'static str> {
+        let scramble = if self.inverse {
+            self.invert_twists(&self.scramble)
+        } else {
+            self.scramble.clone()
+        };
+        let mut puzzle = Puzzle::new(self.ty());
+        for twist in self.invert_twists(&self.premoves).into_iter().chain(scramble) {
+            puzzle.twist(twist)?;
+        }
+
+        self.skip_twist_animations();
+        self.puzzle = puzzle;
+        self.undo_tree = UndoTree::default();
+        self.checkpoints.clear();
+        self.explore_start = None;
+        self.highlighted_undo_node = None;
+        self.invalidate_geometry_cache();
+        self.cached_sticker_geometries.clear();
+        for &twist in twists {
+            self.twist_no_collapse(twist)?;
+        }
+        self.skip_twist_animations();
+        self.mark_unsaved();
+        Ok(())
+    }
+
+    /// Returns the checkpoints in the order they were added.
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+    /// Marks the current state as a checkpoint. If `name` is empty, a default
+    /// name is used.
+    pub fn add_checkpoint(&mut self, name: &str) -> &Checkpoint {
+        let name = match name.trim() {
+            "" => format!("Checkpoint {}", self.checkpoints.len() + 1),
+            s => s.to_owned(),
+        };
+        self.checkpoints.push(Checkpoint {
+            name,
+            node: self.undo_tree.current(),
+        });
+        self.checkpoints.last().unwrap()
+    }
+    /// Removes a checkpoint.
+    pub fn remove_checkpoint(&mut self, index: usize) {
+        if index < self.checkpoints.len() {
+            self.checkpoints.remove(index);
+        }
+    }
+    /// Undoes and redoes twists until reaching a checkpoint. Twists made after
+    /// the checkpoint are kept in the undo tree.
+    pub fn revert_to_checkpoint(&mut self, index: usize) -> Result<(), &'static str> {
+        let checkpoint = self.checkpoints.get(index).ok_or("No such checkpoint")?;
+        self.jump_to_undo_node(checkpoint.node)
+    }
+
+    /// Sets the annotation on a node in the undo tree. An empty string removes
+    /// it.
+    pub fn set_annotation(&mut self, node: UndoNodeId, annotation: &str) {
+        if self.undo_tree.annotation(node) != annotation.trim() {
+            self.undo_tree.set_annotation(node, annotation.trim().to_owned());
+            self.mark_unsaved();
+        }
+    }
+    /// Adds a branch to the undo history that leaves from `from`, without
+    /// applying it to the puzzle. Returns the nodes along the branch.
+    pub fn add_undo_branch(
+        &mut self,
+        from: UndoNodeId,
+        entries: impl IntoIterator<Item = HistoryEntry>,
+    ) -> Vec<UndoNodeId> {
+        let mut node = from;
+        entries
+            .into_iter()
+            .map(|entry| {
+                node = self.undo_tree.add_child(node, entry);
+                node
+            })
+            .collect()
+    }
+    /// Returns the annotations on the current line of the undo history, keyed
+    /// by the number of entries applied since the scramble.
+    pub fn path_annotations(&self) -> BTreeMap<usize, String> {
+        self.undo_tree
+            .path_nodes()
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, node)| !self.undo_tree.annotation(node).is_empty())
+            .map(|(i, node)| (i, self.undo_tree.annotation(node).to_owned()))
+            .collect()
+    }
+    /// Sets annotations on the current line of the undo history, keyed by the
+    /// number of entries applied since the scramble. Annotations past the
+    /// current node are ignored.
+    pub fn set_path_annotations(&mut self, annotations: &BTreeMap<usize, String>) {
+        let path_nodes = self.undo_tree.path_nodes();
+        for (&i, annotation) in annotations {
+            if let Some(&node) = path_nodes.get(i) {
+                self.undo_tree.set_annotation(node, annotation.trim().to_owned());
+            }
+        }
+    }
+
+    /// Returns the number of solved pieces of each type, or `None` if the
+    /// puzzle doesn't support progress estimation.
+    pub fn progress(&self) -> Option<Progress> {
+        Progress::of(&self.puzzle)
+    }
+    /// Returns the progress recorded each time the puzzle was saved, from
+    /// oldest to newest.
+    pub fn progress_history(&self) -> &[ProgressSnapshot] {
+        &self.progress_history
+    }
+    /// Sets the progress history, such as when loading a log file.
+    pub fn set_progress_history(&mut self, history: Vec<ProgressSnapshot>) {
+        self.progress_history = history;
+    }
+    /// Returns a snapshot of the current progress, or `None` if the puzzle
+    /// doesn't support progress estimation.
+    pub fn progress_snapshot(&self) -> Option<ProgressSnapshot> {
+        let progress = self.progress()?;
+        Some(ProgressSnapshot {
+            time: util::unix_timestamp(),
+            twist_count: self.twist_count(TwistMetric::Etm),
+            solved_pieces: progress.solved_pieces(),
+            total_pieces: progress.total_pieces(),
+        })
+    }
+    /// Appends the current progress to the progress history, unless no
+    /// twists have been made since the last snapshot.
+    pub fn record_progress_snapshot(&mut self) {
+        if let Some(snapshot) = self.progress_snapshot() {
+            match self.progress_history.last_mut() {
+                Some(last) if last.twist_count == snapshot.twist_count => *last = snapshot,
+                _ => self.progress_history.push(snapshot),
+            }
+        }
+    }
 
     /// Marks the puzzle as saved
     pub fn mark_saved(&mut self) {
@@ -819,8 +1728,7 @@ impl PuzzleController {
             ScrambleState::Partial => false,
             ScrambleState::Full => true,
             ScrambleState::Solved => {
-                self.scramble.len() >= self.scramble_moves_count()
-                    || self.scramble.len() > PARTIAL_SCRAMBLE_MOVE_COUNT_MAX
+                self.fully_scrambled || self.scramble.len() >= self.scramble_moves_count()
             }
         }
     }
@@ -833,13 +1741,23 @@ impl PuzzleController {
         self.puzzle.is_solved()
     }
     /// Checks whether the puzzle was scrambled and is now solved. If so,
-    /// updates the scramble state, and returns `true`.
-    pub fn check_just_solved(&mut self) -> bool {
+    /// updates the scramble state, and returns `true`. If `supercube` is
+    /// `true`, center pieces must also be turned the right way.
+    pub fn check_just_solved(
+        &mut self,
+        definition: SolvedStateDefinition,
+        supercube: bool,
+    ) -> bool {
         let has_been_scrambled = matches!(
             self.scramble_state,
             ScrambleState::Partial | ScrambleState::Full,
-        );
-        if has_been_scrambled && self.is_solved() {
+        ) && !self.is_exploring();
+        let is_solved = if supercube {
+            self.puzzle.is_solved_as_supercube(definition)
+        } else {
+            self.puzzle.is_solved_by(definition)
+        };
+        if has_been_scrambled && is_solved {
             self.scramble_state = ScrambleState::Solved;
             true
         } else {
@@ -851,7 +1769,20 @@ impl PuzzleController {
     pub fn twist_count(&self, metric: TwistMetric) -> usize {
         metric.count_twists(
             self,
-            self.undo_buffer
+            self.undo_tree
+                .path()
+                .iter()
+                .copied()
+                .filter_map(HistoryEntry::twist),
+        )
+    }
+    /// Returns the number of twists applied to the puzzle, not including the
+    /// scramble, counted using custom options.
+    pub fn twist_count_with_options(&self, options: TwistCountOptions) -> usize {
+        options.count_twists(
+            self,
+            self.undo_tree
+                .path()
                 .iter()
                 .copied()
                 .filter_map(HistoryEntry::twist),
@@ -864,11 +1795,16 @@ impl PuzzleController {
     /// Returns the twists and other actions applied to the puzzle, not
     /// including the scramble.
     pub fn undo_buffer(&self) -> &[HistoryEntry] {
-        &self.undo_buffer
+        self.undo_tree.path()
+    }
+    /// Returns the twists and other actions that would be reapplied by redoing
+    /// repeatedly.
+    pub fn redo_buffer(&self) -> Vec<HistoryEntry> {
+        self.undo_tree.redo_path()
     }
-    /// Returns the twists and other actions in the redo buffer.
-    pub fn redo_buffer(&self) -> &[HistoryEntry] {
-        &self.redo_buffer
+    /// Returns the full branching undo history.
+    pub fn undo_tree(&self) -> &UndoTree {
+        &self.undo_tree
     }
 }
 
@@ -878,24 +1814,115 @@ struct TwistAnimationState {
     queue: VecDeque<TwistAnimation>,
     /// Maximum number of animations in the queue (reset when queue is empty).
     queue_max: usize,
-    /// Progress of the animation in the current twist, from 0.0 to 1.0.
-    progress: f32,
+    /// Whether animations are paused, in which case the queue doesn't
+    /// advance.
+    paused: bool,
 }
 impl TwistAnimationState {
+    /// Advances the first `simultaneous` animations. Returns the animations
+    /// that finished.
     #[must_use]
-    fn proceed(&mut self, delta_t: f32) -> Option<Quaternion<f32>> {
-        self.progress += delta_t;
-        if self.progress >= 1.0 {
-            self.progress = 0.0;
-            self.queue
-                .pop_front()
-                .map(|anim| anim.view_angle_offset_delta)
-        } else {
-            None
+    fn proceed(&mut self, delta_t: f32, simultaneous: usize) -> Vec<TwistAnimation> {
+        for anim in self.queue.iter_mut().take(simultaneous.max(1)) {
+            anim.progress = (anim.progress + delta_t).min(1.0);
+        }
+        // A later twist may finish first, but it stays in the queue until the
+        // ones before it have finished.
+        let mut done = vec![];
+        while let Some(anim) = self.queue.front() {
+            if anim.progress < 1.0 {
+                break;
+            }
+            done.extend(self.queue.pop_front());
+        }
+        done
+    }
+
+    /// Merges consecutive animations that haven't started yet into a single
+    /// animation over the combined angle, such as `R R` into `R2`.
+    fn merge_repeated(&mut self, ty: &impl PuzzleType) {
+        let mut i = 1;
+        while i < self.queue.len() {
+            match merged_twist(ty, &self.queue[i - 1], &self.queue[i]) {
+                Some(twist) => {
+                    let Some(next) = self.queue.remove(i) else {
+                        break;
+                    };
+                    let anim = &mut self.queue[i - 1];
+                    anim.twist = twist;
+                    anim.view_angle_offset_delta =
+                        anim.view_angle_offset_delta * next.view_angle_offset_delta;
+                }
+                None => i += 1,
+            }
+        }
+    }
+
+    /// Returns the number of animations at the front of the queue that move
+    /// different pieces from each other, so they can be animated at the same
+    /// time.
+    fn simultaneous_count(&self) -> usize {
+        let Some(first) = self.queue.front() else {
+            return 0;
+        };
+        // Reversed twists end at their state instead of starting there, so
+        // the states of the twists after them can't be compared.
+        if first.reversed {
+            return 1;
+        }
+        let displayed = &first.state;
+        let pieces = (0..displayed.pieces().len() as _).map(Piece);
+        let mut moved = pieces
+            .clone()
+            .map(|piece| displayed.is_piece_affected_by_twist(first.twist, piece))
+            .collect::<Vec<bool>>();
+        let mut count = 1;
+        for anim in self.queue.iter().skip(1) {
+            if anim.reversed {
+                break;
+            }
+            // The twist is drawn on the displayed state, but applies to the
+            // pieces in its own state.
+            let affected = pieces
+                .clone()
+                .map(|piece| {
+                    displayed.is_piece_affected_by_twist(anim.twist, piece)
+                        || anim.state.is_piece_affected_by_twist(anim.twist, piece)
+                })
+                .collect::<Vec<bool>>();
+            if std::iter::zip(&moved, &affected).any(|(&a, &b)| a && b) {
+                break;
+            }
+            for (m, a) in std::iter::zip(&mut moved, affected) {
+                *m |= a;
+            }
+            count += 1;
         }
+        count
     }
 }
 
+/// Returns a single twist that does the same as two consecutive twist
+/// animations in the same direction, or `None` if they can't be merged.
+fn merged_twist(ty: &impl PuzzleType, a: &TwistAnimation, b: &TwistAnimation) -> Option<Twist> {
+    let unstarted = a.progress == 0.0 && b.progress == 0.0;
+    let same_kind = !a.reversed && !b.reversed && a.from_history == b.from_history;
+    let same_layers = a.twist.axis == b.twist.axis && a.twist.layers == b.twist.layers;
+    if !(unstarted && same_kind && same_layers) {
+        return None;
+    }
+    let direction =
+        ty.chain_twist_directions(a.twist.axis, &[a.twist.direction, b.twist.direction])?;
+    let merged = Twist {
+        direction,
+        ..a.twist
+    };
+    // Don't merge twists that would turn the other way, such as `R R2` into
+    // `R'`.
+    let quarter_turns = ty.count_quarter_turns(a.twist) + ty.count_quarter_turns(b.twist);
+    (ty.count_quarter_turns(merged) == quarter_turns).then_some(merged)
+}
+
 #[derive(Debug, Clone)]
 struct TwistAnimation {
     /// Puzzle state before twist.
@@ -904,6 +1931,23 @@ struct TwistAnimation {
     twist: Twist,
     /// Delta to apply to the view angle before animating.
     view_angle_offset_delta: Quaternion<f32>,
+    /// Progress of the animation, from 0.0 to 1.0.
+    progress: f32,
+    /// Whether the twist is animated backwards, ending at `state`.
+    reversed: bool,
+    /// Whether the twist comes from undoing or redoing rather than solving.
+    from_history: bool,
+}
+impl TwistAnimation {
+    /// Returns the twist along with a float between 0.0 and 1.0 indicating
+    /// how far it is displayed.
+    fn twist_progress(&self) -> (Twist, f32) {
+        let progress = match self.reversed {
+            true => 1.0 - self.progress,
+            false => self.progress,
+        };
+        (self.twist, TWIST_INTERPOLATION_FN(progress))
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -947,6 +1991,55 @@ impl ViewSettingsAnimState {
     }
 }
 
+/// Face and sticker spacing, which follow the view settings smoothly instead of
+/// snapping when they change.
+#[derive(Debug, Default, Clone)]
+struct SpacingAnimState {
+    /// Displayed face and sticker spacing, or `None` if nothing has been
+    /// displayed yet.
+    current: Option<[f32; 2]>,
+    /// Face and sticker spacing to animate toward.
+    target: [f32; 2],
+    /// Whether to temporarily increase the spacing to show the puzzle's
+    /// internals.
+    exploded: bool,
+}
+impl SpacingAnimState {
+    /// Updates the target spacing from `view_prefs` and returns `view_prefs`
+    /// with the displayed spacing.
+    fn apply<'a>(&mut self, mut view_prefs: Cow<'a, ViewPreferences>) -> Cow<'a, ViewPreferences> {
+        let mut target = [view_prefs.face_spacing, view_prefs.sticker_spacing];
+        if self.exploded {
+            for spacing in &mut target {
+                *spacing = (*spacing + EXPLODED_SPACING_INCREASE).min(MAX_SPACING);
+            }
+        }
+        self.target = target;
+
+        let current = *self.current.get_or_insert(target);
+        if current != [view_prefs.face_spacing, view_prefs.sticker_spacing] {
+            let [face_spacing, sticker_spacing] = current;
+            let view_prefs = view_prefs.to_mut();
+            view_prefs.face_spacing = face_spacing;
+            view_prefs.sticker_spacing = sticker_spacing;
+        }
+        view_prefs
+    }
+    fn proceed(&mut self, delta: Duration) {
+        let Some(current) = &mut self.current else {
+            return;
+        };
+        let decay_multiplier = SPACING_DECAY_RATE.powf(delta.as_secs_f32());
+        for (spacing, target) in std::iter::zip(current, self.target) {
+            *spacing = target + (*spacing - target) * decay_multiplier;
+            // Stop the animation once it's close enough.
+            if (*spacing - target).abs() < SPACING_EPSILON {
+                *spacing = target;
+            }
+        }
+    }
+}
+
 /// The following rotations are applied to the whole puzzle in order before
 /// rendering:
 ///
@@ -967,6 +2060,10 @@ pub struct ViewAngleAnimState {
     /// Whether to freeze the view angle offset, versus animating it back to
     /// zero.
     is_frozen: bool,
+
+    /// Remaining fraction of the shake animation played when a twist is
+    /// blocked, from 1.0 down to 0.0.
+    blocked_twist_shake: f32,
 }
 impl Default for ViewAngleAnimState {
     fn default() -> Self {
@@ -976,45 +2073,57 @@ impl Default for ViewAngleAnimState {
 
             transient_rotation: None,
             is_frozen: false,
+
+            blocked_twist_shake: 0.0,
         }
     }
 }
+impl ViewAngleAnimState {
+    /// Returns the rotation for the shake animation played when a twist is
+    /// blocked, which shakes back and forth around the Y axis and dies down
+    /// over time.
+    fn shake(&self) -> Quaternion<f32> {
+        let remaining = self.blocked_twist_shake;
+        let phase = (1.0 - remaining) * BLOCKED_TWIST_SHAKE_COUNT * std::f32::consts::TAU;
+        Quaternion::from_angle_y(BLOCKED_TWIST_SHAKE_ANGLE * remaining * phase.sin())
+    }
+}
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub enum HistoryEntry {
-    Twist(Twist),
+/// Name and version of the program that generates scrambles, which is
+/// recorded with each solve.
+pub const SCRAMBLER_VERSION: &str = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+
+/// Restrictions on random-move scrambles.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScrambleOptions {
+    /// Number of twists in a full random-move scramble, or `None` to use the
+    /// default for the puzzle.
+    pub full_move_count: Option<usize>,
+    /// Whether to only twist a single outer layer at a time.
+    pub outer_layers_only: bool,
+    /// Twist axes that are never twisted.
+    pub disallowed_axes: Vec<TwistAxis>,
 }
-impl From<Twist> for HistoryEntry {
-    fn from(twist: Twist) -> Self {
-        Self::Twist(twist)
+impl ScrambleOptions {
+    /// Returns whether some twists are not allowed, in which case random-state
+    /// scrambles cannot be used.
+    pub fn restricts_twists(&self) -> bool {
+        self.outer_layers_only || !self.disallowed_axes.is_empty()
     }
 }
-impl HistoryEntry {
-    pub fn twist(self) -> Option<Twist> {
-        match self {
-            HistoryEntry::Twist(twist) => Some(twist),
-        }
-    }
-    pub fn to_string(self, notation: &NotationScheme) -> String {
-        match self {
-            HistoryEntry::Twist(twist) => notation.twist_to_string(twist),
-        }
-    }
+
+/// Named state in the undo history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub name: String,
+    pub node: UndoNodeId,
 }
 
-/// Whether the puzzle has been scrambled.
-#[derive(FromPrimitive, Debug, Default, Copy, Clone, PartialEq, Eq)]
-#[repr(u8)]
-pub enum ScrambleState {
-    /// Unscrambled.
-    #[default]
-    None = 0,
-    /// Some small number of scramble twists.
-    Partial = 1,
-    /// Fully scrambled.
-    Full = 2,
-    /// Was solved by user even if not currently solved.
-    Solved = 3,
+/// Returns a random number generator for seeded scrambles. Unlike `StdRng`,
+/// ChaCha8 is guaranteed to produce the same values on every platform and
+/// version, so a seed always gives the same scramble.
+fn seeded_rng(seed: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(seed)
 }
 
 /// Which parts of the puzzle to twist.
@@ -1041,6 +2150,30 @@ impl BitOrAssign<&Grip> for Grip {
         }
     }
 }
+impl BitAnd<&Grip> for Grip {
+    type Output = Self;
+
+    fn bitand(mut self, rhs: &Grip) -> Self::Output {
+        self &= rhs;
+        self
+    }
+}
+impl BitAndAssign<&Grip> for Grip {
+    /// Intersects two grips. Axes or layers left unspecified by one grip are
+    /// taken from the other.
+    fn bitand_assign(&mut self, rhs: &Self) {
+        if self.axes.is_empty() {
+            self.axes = rhs.axes.clone();
+        } else if !rhs.axes.is_empty() {
+            self.axes.retain(|axis| rhs.axes.contains(axis));
+        }
+        self.layers = match (self.layers, rhs.layers) {
+            (None, None) => None,
+            (None, Some(l)) | (Some(l), None) => Some(l),
+            (Some(l1), Some(l2)) => Some(l1 & l2),
+        }
+    }
+}
 impl Grip {
     pub fn with_axis(axis: TwistAxis) -> Self {
         Self {
@@ -1078,6 +2211,12 @@ impl Grip {
             self.layers = None;
         }
     }
+    /// Selects every layer that is not selected, and deselects every layer
+    /// that is.
+    pub fn invert_layers(&mut self, layer_count: u8) {
+        let inverted = !self.layers.unwrap_or_default() & LayerMask::all_layers(layer_count);
+        self.layers = (inverted != LayerMask::default()).then_some(inverted);
+    }
 
     /// Returns whether the twist selection includes a particular piece.
     pub fn has_piece(&self, puzzle: &dyn PuzzleState, piece: Piece) -> Option<bool> {
@@ -1108,28 +2247,53 @@ pub struct VisualPieceState {
     pub gripped: f32,
     pub ungripped: f32,
     pub hidden: f32,
+    pub solved: f32,
     pub selected: f32,
     pub hovered: f32,
+    /// How recently the piece was twisted, from 1.0 (just now) to 0.0 (not
+    /// in the move trail).
+    pub trail: f32,
+    /// Whether the piece is being moved by undoing or redoing.
+    pub history: f32,
 
     hidden_opacity_override: Option<f32>,
 }
 impl VisualPieceState {
-    pub fn outline_color(self, prefs: &Preferences, is_sticker_selected: bool) -> egui::Rgba {
+    /// Returns the outline color of a sticker, given its face color.
+    pub fn outline_color(
+        self,
+        prefs: &Preferences,
+        is_sticker_selected: bool,
+        face_color: egui::Rgba,
+    ) -> egui::Rgba {
         let pr = &prefs.outlines;
 
-        let hidden_or_ungripped = f32::max(self.hidden, self.ungripped);
+        let dimmed = f32::max(self.hidden, self.ungripped).max(self.solved);
+
+        let face_color = pr.shaded_face_color(face_color);
+        let color = |fixed_color: egui::Color32, use_face_color: bool| match use_face_color {
+            true => face_color,
+            false => egui::Rgba::from(fixed_color),
+        };
+        let selected_color = if is_sticker_selected {
+            pr.selected_sticker_color
+        } else {
+            pr.selected_piece_color
+        };
 
-        let mut ret = egui::Rgba::from(pr.default_color);
+        let mut ret = color(pr.default_color, pr.default_face_color);
         // In order from lowest to highest priority:
-        ret = util::mix(ret, egui::Rgba::from(pr.hidden_color), hidden_or_ungripped);
-        ret = util::mix(ret, egui::Rgba::from(pr.hovered_color), self.hovered);
+        ret = util::mix(ret, color(pr.hidden_color, pr.hidden_face_color), dimmed);
+        ret = util::mix(ret, egui::Rgba::from(pr.trail_color), self.trail);
+        ret = util::mix(ret, egui::Rgba::from(pr.history_color), self.history);
         ret = util::mix(
             ret,
-            egui::Rgba::from(if is_sticker_selected {
-                pr.selected_sticker_color
-            } else {
-                pr.selected_piece_color
-            }),
+            color(pr.hovered_color, pr.hovered_face_color),
+            self.hovered,
+        );
+        ret = util::mix(
+            ret,
+            color(selected_color, pr.selected_face_color),
             self.selected,
         );
         ret
@@ -1137,11 +2301,12 @@ impl VisualPieceState {
     pub fn outline_size(self, prefs: &Preferences) -> f32 {
         let pr = &prefs.outlines;
 
-        let hidden_or_ungripped = f32::max(self.hidden, self.ungripped);
+        let dimmed = f32::max(self.hidden, self.ungripped).max(self.solved);
 
         let mut ret = pr.default_size;
         // In order from lowest to highest priority:
-        ret = util::mix(ret, pr.hidden_size, hidden_or_ungripped);
+        ret = util::mix(ret, pr.hidden_size, dimmed);
+        ret = util::mix(ret, pr.trail_size, self.trail.max(self.history));
         ret = util::mix(ret, pr.selected_size, self.selected);
         ret = util::mix(ret, pr.hovered_size, self.hovered);
         ret
@@ -1163,6 +2328,7 @@ impl VisualPieceState {
         let mut ret = 1.0;
         // In order from lowest to highest priority:
         ret = util::mix(ret, hidden_opacity, self.hidden);
+        ret = util::mix(ret, f32::min(ret, pr.solved), self.solved);
         ret *= pr.base;
         ret = util::mix(ret, pr.selected, self.selected);
         ret = util::mix(ret, 1.0, full_opacity);
@@ -1172,3 +2338,91 @@ impl VisualPieceState {
         ret
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    #[test]
+    fn test_seeded_rng_is_stable() {
+        // If these change, every shared seed and daily challenge gives a
+        // different scramble than it used to.
+        let mut rng = seeded_rng(42);
+        assert_eq!(rng.next_u64(), 0xae90bfb5395d5ba1);
+        assert_eq!(rng.next_u64(), 0xf3453fc625799188);
+        assert_eq!(rng.next_u64(), 0x6d71b708c5b6538c);
+    }
+
+    #[test]
+    fn test_seeded_scramble_is_reproducible() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let scramble = |seed| {
+            let mut puzzle = PuzzleController::new(ty);
+            puzzle.scramble_full_seeded(seed).unwrap();
+            assert_eq!(puzzle.scramble_seed(), Some(seed));
+            puzzle.scramble().to_vec()
+        };
+        assert_eq!(scramble(42), scramble(42));
+        assert_ne!(scramble(42), scramble(43));
+    }
+
+    fn twist(axis: u8) -> Twist {
+        Twist {
+            axis: TwistAxis(axis),
+            direction: TwistDirection(0),
+            layers: LayerMask(1),
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_names() {
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        assert_eq!(puzzle.add_checkpoint("").name, "Checkpoint 1");
+        assert_eq!(puzzle.add_checkpoint("  before PLL ").name, "before PLL");
+        assert_eq!(puzzle.add_checkpoint(" ").name, "Checkpoint 3");
+
+        puzzle.remove_checkpoint(0);
+        puzzle.remove_checkpoint(5);
+        let names: Vec<&str> = puzzle.checkpoints().iter().map(|c| &*c.name).collect();
+        assert_eq!(names, ["before PLL", "Checkpoint 3"]);
+    }
+
+    #[test]
+    fn test_revert_to_checkpoint() {
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        puzzle.add_checkpoint("solved");
+        puzzle.twist_no_collapse(twist(0)).unwrap();
+        puzzle.add_checkpoint("after R");
+        puzzle.twist_no_collapse(twist(2)).unwrap();
+        puzzle.twist_no_collapse(twist(4)).unwrap();
+
+        // Twists after the checkpoint stay available to redo.
+        puzzle.revert_to_checkpoint(1).unwrap();
+        assert_eq!(puzzle.undo_buffer(), [HistoryEntry::Twist(twist(0))]);
+        assert_eq!(
+            puzzle.redo_buffer(),
+            [HistoryEntry::Twist(twist(2)), HistoryEntry::Twist(twist(4))],
+        );
+
+        // Reverting works from a different branch too.
+        puzzle.twist_no_collapse(twist(3)).unwrap();
+        puzzle.revert_to_checkpoint(0).unwrap();
+        assert!(puzzle.undo_buffer().is_empty());
+        assert!(puzzle.is_solved());
+        puzzle.revert_to_checkpoint(1).unwrap();
+        assert_eq!(puzzle.undo_buffer(), [HistoryEntry::Twist(twist(0))]);
+
+        assert!(puzzle.revert_to_checkpoint(2).is_err());
+    }
+
+    #[test]
+    fn test_scramble_clears_checkpoints() {
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        puzzle.twist_no_collapse(twist(0)).unwrap();
+        puzzle.add_checkpoint("");
+        puzzle.add_scramble_marker(ScrambleState::Partial);
+        assert!(puzzle.checkpoints().is_empty());
+    }
+}
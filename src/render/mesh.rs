@@ -11,23 +11,92 @@ use crate::util::IterCyclicPairsExt;
 const OUTLINE_SCALE: f32 = 1.0 / 512.0;
 const OUTLINE_WEDGE_VERTS_PER_RADIAN: f32 = 3.0;
 
+/// Factor by which a simplified sticker must grow beyond the minimum size
+/// before it is drawn in full again, to prevent stickers from flickering
+/// between levels of detail.
+const LOD_HYSTERESIS: f32 = 1.25;
+
+/// Level-of-detail state for drawing tiny stickers as simple quads.
+pub(super) struct Lod<'a> {
+    /// Whether each sticker was simplified last frame.
+    pub simplified: &'a mut Vec<bool>,
+    /// Projected size (in pixels) below which stickers are simplified.
+    pub min_size: f32,
+    /// Number of pixels per unit along each axis.
+    pub pixels_per_unit: Vector2<f32>,
+}
+impl Lod<'_> {
+    /// Returns whether a sticker should be drawn as a simple quad.
+    fn update(&mut self, geom: &ProjectedStickerGeometry) -> bool {
+        let extent = geom.max_bound - geom.min_bound;
+        let size = f32::max(
+            extent.x * self.pixels_per_unit.x,
+            extent.y * self.pixels_per_unit.y,
+        );
+        let was_simplified = &mut self.simplified[geom.sticker.0 as usize];
+        let threshold = if *was_simplified {
+            self.min_size * LOD_HYSTERESIS
+        } else {
+            self.min_size
+        };
+        *was_simplified = size < threshold;
+        *was_simplified
+    }
+}
+
+/// Buffers for the puzzle mesh, which are kept between frames to avoid
+/// allocating new ones every time the mesh is rebuilt.
+#[derive(Debug, Default)]
+pub(super) struct MeshBuffers {
+    pub verts: Vec<RgbaVertex>,
+    pub indices: Vec<u32>,
+    /// Depth value for the next sticker.
+    z: f32,
+
+    /// Outline edges of the current sticker.
+    outlines: Vec<[Point2<f32>; 2]>,
+    /// Outline endpoints of the current sticker.
+    outline_ends: Vec<Point2<f32>>,
+}
+
+impl MeshBuffers {
+    pub fn clear(&mut self) {
+        self.verts.clear();
+        self.indices.clear();
+
+        // We already did depth sorting, so the GPU doesn't need to know the
+        // real depth values. It just needs some value between 0 and 1 that
+        // increases nearer to the camera. It's easy enough to start at 0.5 and
+        // do integer incrementation for each sticker to get the next-largest
+        // `f32` value.
+        self.z = 0.5;
+    }
+}
+
+/// Triangulates polygons and combines the whole puzzle into one mesh, which is
+/// appended to `buffers`. Call [`MeshBuffers::clear()`] first to start a new
+/// mesh.
 pub(super) fn make_puzzle_mesh(
+    buffers: &mut MeshBuffers,
     puzzle: &mut PuzzleController,
     prefs: &Preferences,
     sticker_geometries: &[ProjectedStickerGeometry],
-) -> (Vec<RgbaVertex>, Vec<u32>) {
-    // Triangulate polygons and combine the whole puzzle into one mesh.
-    let mut verts = vec![];
-    let mut indices = vec![];
-
-    // We already did depth sorting, so the GPU doesn't need to know the real
-    // depth values. It just needs some value between 0 and 1 that increases
-    // nearer to the camera. It's easy enough to start at 0.5 and do integer
-    // incrementation for each sticker to get the next-largest `f32` value.
-    let mut z = 0.5_f32;
+    mut lod: Option<Lod<'_>>,
+) {
+    let MeshBuffers {
+        verts,
+        indices,
+        z,
+        outlines,
+        outline_ends,
+    } = buffers;
 
     let face_colors = &prefs.colors.face_colors_list(puzzle.ty());
 
+    if let Some(lod) = &mut lod {
+        lod.simplified.resize(puzzle.stickers().len(), false);
+    }
+
     for geom in sticker_geometries {
         let sticker_info = puzzle.info(geom.sticker);
 
@@ -37,22 +106,58 @@ pub(super) fn make_puzzle_mesh(
         let alpha = visual_state.opacity(prefs);
 
         // Determine sticker fill color.
-        let sticker_color = egui::Rgba::from(if prefs.colors.blindfold {
+        let face_color = egui::Rgba::from(if prefs.colors.blindfold {
             prefs.colors.blind_face
         } else {
             face_colors[puzzle.info(geom.sticker).color.0 as usize]
-        })
-        .multiply(alpha);
+        });
+        let sticker_color = face_color.multiply(alpha);
+
+        // Draw tiny stickers as a single quad covering the sticker, with no
+        // outline.
+        if lod.as_mut().map_or(false, |lod| lod.update(geom)) {
+            let illumination = geom
+                .front_polygons
+                .iter()
+                .map(|polygon| polygon.illumination)
+                .fold(0.0, f32::max);
+            let base = verts.len() as u32;
+            let (min, max) = (geom.min_bound, geom.max_bound);
+            verts.extend(
+                [
+                    (min.x, min.y),
+                    (max.x, min.y),
+                    (max.x, max.y),
+                    (min.x, max.y),
+                ]
+                .map(|(x, y)| RgbaVertex {
+                    pos: [x, y, *z],
+                    color: [
+                        sticker_color.r() * illumination,
+                        sticker_color.g() * illumination,
+                        sticker_color.b() * illumination,
+                        sticker_color.a(),
+                    ],
+                }),
+            );
+            indices.extend([0, 1, 2, 0, 2, 3].map(|i| base + i));
+            *z = f32::from_bits(z.to_bits() + 1);
+            continue;
+        }
 
         // Determine outline appearance.
         let outline_color = visual_state
-            .outline_color(prefs, puzzle.selection().contains(&geom.sticker))
+            .outline_color(
+                prefs,
+                puzzle.selection().contains(&geom.sticker),
+                face_color,
+            )
             .multiply(alpha);
         let outline_size = visual_state.outline_size(prefs);
 
         // Generate outline vertices.
         if outline_size > 0.0 {
-            let mut outlines = vec![];
+            outlines.clear();
             for polygon in &*geom.front_polygons {
                 for (a, b) in polygon
                     .verts
@@ -68,12 +173,13 @@ pub(super) fn make_puzzle_mesh(
                 }
             }
             generate_outline_geometry(
-                &mut verts,
-                &mut indices,
-                &outlines,
+                verts,
+                indices,
+                outline_ends,
+                outlines,
                 outline_size,
                 |Point2 { x, y }| RgbaVertex {
-                    pos: [x, y, z],
+                    pos: [x, y, *z],
                     color: outline_color.to_array(),
                 },
             );
@@ -83,7 +189,7 @@ pub(super) fn make_puzzle_mesh(
         for polygon in &*geom.front_polygons {
             let base = verts.len() as u32;
             verts.extend(polygon.verts.iter().map(|v| RgbaVertex {
-                pos: [v.x, v.y, z],
+                pos: [v.x, v.y, *z],
                 color: [
                     sticker_color.r() * polygon.illumination,
                     sticker_color.g() * polygon.illumination,
@@ -97,22 +203,21 @@ pub(super) fn make_puzzle_mesh(
 
         // Increase the Z value very slightly. If this scares you, click this
         // link and try increasing the significand: https://float.exposed/0x3f000000
-        z = f32::from_bits(z.to_bits() + 1);
+        *z = f32::from_bits(z.to_bits() + 1);
     }
-
-    (verts, indices)
 }
 
 fn generate_outline_geometry(
     verts_out: &mut Vec<RgbaVertex>,
     indices_out: &mut Vec<u32>,
+    unique_line_ends: &mut Vec<Point2<f32>>,
     lines: &[[Point2<f32>; 2]],
     outline_size: f32,
     make_vert: impl Copy + Fn(Point2<f32>) -> RgbaVertex,
 ) {
     let outline_radius = outline_size * OUTLINE_SCALE;
 
-    let mut unique_line_ends: Vec<Point2<f32>> = vec![];
+    unique_line_ends.clear();
 
     // Generate simple lines.
     for &[a, b] in lines {
@@ -140,7 +245,7 @@ fn generate_outline_geometry(
     }
 
     // Generate line joins.
-    for p in unique_line_ends {
+    for &p in &*unique_line_ends {
         let max_angle_pair = {
             lines
                 .iter()
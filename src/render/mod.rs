@@ -1,17 +1,25 @@
 //! Rendering logic.
 
-use instant::Instant;
-use std::sync::Arc;
+use cgmath::Point2;
+use instant::{Duration, Instant};
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
 
 mod cache;
 mod mesh;
+#[cfg(not(target_arch = "wasm32"))]
+mod screenshot;
 mod shaders;
 mod state;
+mod stereo;
 mod structs;
 
 use crate::app::App;
-use crate::puzzle::ProjectedStickerGeometry;
+use crate::preferences::{BackgroundStyle, Preferences, StereoMode};
+use crate::puzzle::{ProjectedStickerGeometry, PuzzleController};
 use cache::{CachedDynamicBuffer, CachedUniformBuffer};
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use screenshot::{capture_puzzle, export_replay_frames, render_puzzle_image};
 pub(crate) use state::GraphicsState;
 use structs::*;
 
@@ -24,29 +32,79 @@ struct PuzzleRenderParams {
     scale: f32,
     align_h: f32,
     align_v: f32,
+
+    stereo: StereoMode,
+    eye_separation: f32,
+}
+
+/// Fraction of the viewport filled by the puzzle after auto-fitting its scale.
+const AUTO_FIT_MARGIN: f32 = 0.95;
+
+/// Weight of the newest sample in a [`SmoothedDuration`].
+const TIMING_SMOOTHING: f32 = 0.1;
+
+/// Exponential moving average of a duration, so that timings are readable
+/// when displayed every frame.
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct SmoothedDuration(Duration);
+impl SmoothedDuration {
+    pub(crate) fn record(&mut self, sample: Duration) {
+        self.0 = self.0.mul_f32(1.0 - TIMING_SMOOTHING) + sample.mul_f32(TIMING_SMOOTHING);
+    }
+    pub(crate) fn get(self) -> Duration {
+        self.0
+    }
+}
+
+/// Breakdown of time spent on each stage of a frame.
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct FrameTimings {
+    /// Projecting sticker geometry.
+    pub(crate) geometry: SmoothedDuration,
+    /// Sorting stickers by depth and culling hidden stickers.
+    pub(crate) sort: SmoothedDuration,
+    /// Generating the mesh and submitting it to the GPU.
+    pub(crate) upload: SmoothedDuration,
+    /// Building the UI.
+    pub(crate) ui: SmoothedDuration,
 }
 
 pub(crate) struct PuzzleRenderCache {
+    pub(crate) timings: FrameTimings,
+
     last_render_time: Instant,
     last_params: Option<PuzzleRenderParams>,
-    last_puzzle_geometry: Option<Arc<Vec<ProjectedStickerGeometry>>>,
+    /// Geometry drawn last frame. This is a weak reference so that the puzzle
+    /// controller can reuse the allocation once the geometry changes.
+    last_puzzle_geometry: Option<Weak<Vec<ProjectedStickerGeometry>>>,
+    /// Whether each sticker was drawn with a lower level of detail.
+    lod_simplified: Vec<bool>,
+    /// Scratch buffers for building the mesh, reused between frames.
+    mesh: mesh::MeshBuffers,
 
     vertex_buffer: CachedDynamicBuffer,
     index_buffer: CachedDynamicBuffer,
     uniform_buffer: CachedUniformBuffer<BasicUniform>,
+    background_uniform_buffer: CachedUniformBuffer<BackgroundUniform>,
 
     multisample_texture: Option<(wgpu::Texture, wgpu::TextureView)>,
     out_texture: Option<(wgpu::Texture, wgpu::TextureView)>,
     depth_texture: Option<(wgpu::Texture, wgpu::TextureView)>,
 
-    basic_pipeline: Option<wgpu::RenderPipeline>,
+    /// Pipeline for each set of color channels that an eye draws to.
+    basic_pipelines: HashMap<wgpu::ColorWrites, wgpu::RenderPipeline>,
+    background_pipeline: Option<wgpu::RenderPipeline>,
 }
 impl Default for PuzzleRenderCache {
     fn default() -> Self {
         Self {
+            timings: FrameTimings::default(),
+
             last_render_time: Instant::now(),
             last_params: None,
             last_puzzle_geometry: None,
+            lod_simplified: vec![],
+            mesh: mesh::MeshBuffers::default(),
 
             vertex_buffer: CachedDynamicBuffer::new::<RgbaVertex>(
                 Some("puzzle_vertex_buffer"),
@@ -57,16 +115,41 @@ impl Default for PuzzleRenderCache {
                 wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::INDEX,
             ),
             uniform_buffer: CachedUniformBuffer::new(Some("puzzle_uniform_buffer"), 0),
+            background_uniform_buffer: CachedUniformBuffer::new(
+                Some("background_uniform_buffer"),
+                0,
+            ),
 
             multisample_texture: None,
             out_texture: None,
             depth_texture: None,
 
-            basic_pipeline: None,
+            basic_pipelines: HashMap::new(),
+            background_pipeline: None,
         }
     }
 }
 impl PuzzleRenderCache {
+    /// Returns the geometry drawn last frame, if it is still current.
+    pub(crate) fn last_puzzle_geometry(&self) -> Option<Arc<Vec<ProjectedStickerGeometry>>> {
+        self.last_puzzle_geometry.as_ref()?.upgrade()
+    }
+    /// Converts a point on the puzzle geometry drawn last frame to a position
+    /// on the render target, with both coordinates ranging from -1 to 1. With
+    /// stereoscopic rendering, this gives the position seen by the left eye.
+    pub(crate) fn geometry_point_to_target(&self, point: Point2<f32>) -> Option<Point2<f32>> {
+        let params = self.last_params.as_ref()?;
+        let eye = stereo::eyes(params.stereo)[0];
+        let eye_w = params.target_w as f32 * eye.viewport_width();
+        let eye_h = params.target_h as f32;
+        let pixel_scale = f32::min(eye_w, eye_h) * params.scale;
+
+        let x_in_eye = point.x * pixel_scale / eye_w + params.align_h;
+        let y = point.y * pixel_scale / eye_h + params.align_v;
+        let x = eye.viewport.0 + (x_in_eye + 1.0) / 2.0 * eye.viewport_width();
+        Some(cgmath::point2(x * 2.0 - 1.0, y))
+    }
+
     fn set_params_and_invalidate(&mut self, new_params: PuzzleRenderParams) -> bool {
         let old = match self.last_params.take() {
             Some(p) => p,
@@ -88,7 +171,8 @@ impl PuzzleRenderCache {
             self.multisample_texture = None;
             self.depth_texture = None;
 
-            self.basic_pipeline = None;
+            self.basic_pipelines.clear();
+            self.background_pipeline = None;
         }
 
         self.last_params = Some(new);
@@ -97,12 +181,70 @@ impl PuzzleRenderCache {
     }
 }
 
+/// Returns the scale at which the whole puzzle fits in a viewport of the given
+/// size, or `None` if there is nothing to fit.
+pub(crate) fn auto_fit_scale(
+    puzzle: &PuzzleController,
+    prefs: &Preferences,
+    (width, height): (u32, u32),
+) -> Option<f32> {
+    let view_prefs = prefs.view(puzzle.ty());
+    let (min, max) = puzzle.projected_bounds(view_prefs)?;
+
+    let eyes = stereo::eyes(prefs.gfx.stereo);
+    let eye_size = cgmath::vec2(width as f32 * eyes[0].viewport_width(), height as f32);
+    let min_dimen = f32::min(eye_size.x, eye_size.y);
+
+    // See `draw_puzzle_to_texture()` for how the scale is applied. Each
+    // coordinate must end up between -1 and +1 after scaling and alignment.
+    let max_scale = |coord: f32, align: f32, eye_dimen: f32| {
+        (coord.signum() - align) * eye_dimen / (coord * min_dimen)
+    };
+    [
+        max_scale(min.x, view_prefs.align_h, eye_size.x),
+        max_scale(max.x, view_prefs.align_h, eye_size.x),
+        max_scale(min.y, view_prefs.align_v, eye_size.y),
+        max_scale(max.y, view_prefs.align_v, eye_size.y),
+    ]
+    .into_iter()
+    .filter(|scale| scale.is_finite() && *scale > 0.0)
+    .reduce(f32::min)
+    .map(|scale| scale * AUTO_FIT_MARGIN)
+}
+
 pub(crate) fn draw_puzzle(
     app: &mut App,
     gfx: &mut GraphicsState,
+    force_redraw: bool,
+) -> Option<wgpu::TextureView> {
+    // Disable MSAA on web.
+    #[cfg(target_arch = "wasm32")]
+    {
+        app.prefs.gfx.msaa = false;
+    }
+
+    draw_puzzle_to_texture(
+        &mut app.puzzle,
+        &app.prefs,
+        &mut app.render_cache,
+        app.puzzle_texture_size,
+        app.cursor_pos,
+        gfx,
+        force_redraw,
+    )
+}
+
+/// Draws the puzzle to the "out" texture of `cache`, independently of the
+/// rest of the app. Returns `None` if nothing changed since the last call.
+pub(crate) fn draw_puzzle_to_texture(
+    puzzle: &mut PuzzleController,
+    prefs: &Preferences,
+    cache: &mut PuzzleRenderCache,
+    (width, height): (u32, u32),
+    cursor_pos: Option<Point2<f32>>,
+    gfx: &mut GraphicsState,
     mut force_redraw: bool,
 ) -> Option<wgpu::TextureView> {
-    let (width, height) = app.puzzle_texture_size;
     let size = cgmath::vec2(width as f32, height as f32);
 
     // Avoid divide-by-zero errors.
@@ -110,16 +252,7 @@ pub(crate) fn draw_puzzle(
         return None;
     }
 
-    // Disable MSAA on web.
-    #[cfg(target_arch = "wasm32")]
-    {
-        app.prefs.gfx.msaa = false;
-    }
-
-    let puzzle = &mut app.puzzle;
-    let prefs = &app.prefs;
     let view_prefs = puzzle.view_prefs(prefs);
-    let cache = &mut app.render_cache;
 
     let now = Instant::now();
     let delta = now - cache.last_render_time;
@@ -137,36 +270,67 @@ pub(crate) fn draw_puzzle(
         scale: view_prefs.scale,
         align_h: view_prefs.align_h,
         align_v: view_prefs.align_v,
+
+        stereo: prefs.gfx.stereo,
+        eye_separation: prefs.gfx.eye_separation,
     });
 
+    // All eyes have viewports of the same size.
+    let eyes = stereo::eyes(prefs.gfx.stereo);
+    let eye_size = cgmath::vec2(size.x * eyes[0].viewport_width(), size.y);
+
     // Calculate scale.
     let scale = {
-        let min_dimen = f32::min(size.x, size.y);
+        let min_dimen = f32::min(eye_size.x, eye_size.y);
         let pixel_scale = min_dimen * view_prefs.scale;
-        cgmath::vec2(pixel_scale / size.x, pixel_scale / size.y)
+        cgmath::vec2(pixel_scale / eye_size.x, pixel_scale / eye_size.y)
     };
 
+    // Compute the region of the puzzle that is visible in the viewport.
+    let visible_bounds = (
+        cgmath::point2(
+            (-1.0 - view_prefs.align_h) / scale.x,
+            (-1.0 - view_prefs.align_v) / scale.y,
+        ),
+        cgmath::point2(
+            (1.0 - view_prefs.align_h) / scale.x,
+            (1.0 - view_prefs.align_v) / scale.y,
+        ),
+    );
+
     // If the puzzle geometry has changed, force a redraw.
-    let puzzle_geometry = puzzle.geometry(prefs);
+    let geometry_start = Instant::now();
+    let puzzle_geometry = puzzle.geometry(prefs, visible_bounds);
+    let sort_duration = puzzle.last_sort_duration();
+    let geometry_duration = geometry_start.elapsed().saturating_sub(sort_duration);
+    cache.timings.geometry.record(geometry_duration);
+    cache.timings.sort.record(sort_duration);
     if let Some(old_geom) = &cache.last_puzzle_geometry {
-        if !Arc::ptr_eq(&puzzle_geometry, old_geom) {
+        if old_geom.as_ptr() != Arc::as_ptr(&puzzle_geometry) {
             force_redraw = true;
         }
     } else {
         force_redraw = true;
     }
-    cache.last_puzzle_geometry = Some(Arc::clone(&puzzle_geometry));
+    cache.last_puzzle_geometry = Some(Arc::downgrade(&puzzle_geometry));
 
     // Determine which sticker(s) are at the mouse cursor, in order from front
     // to back.
-    if let Some(cursor_pos) = app.cursor_pos {
+    if let Some(cursor_pos) = cursor_pos {
+        let cursor_pos = stereo::cursor_pos_in_eye(prefs.gfx.stereo, cursor_pos);
         let transformed_cursor_pos = cgmath::point2(
             (cursor_pos.x - view_prefs.align_h) / scale.x,
             (cursor_pos.y - view_prefs.align_v) / scale.y,
         );
-        let hovered_stickers = puzzle_geometry.iter().rev().filter_map(move |geom| {
-            Some((geom.sticker, geom.twists_for_point(transformed_cursor_pos)?))
-        });
+        let hovered_stickers = puzzle_geometry
+            .iter()
+            .rev()
+            .filter_map(|geom| {
+                let include_back_polygons = puzzle.is_see_through(geom.sticker, prefs);
+                let picked = geom.pick(transformed_cursor_pos, include_back_polygons)?;
+                Some((geom.sticker, picked))
+            })
+            .collect::<Vec<_>>();
         puzzle.update_hovered_sticker(hovered_stickers);
     } else {
         puzzle.update_hovered_sticker([]);
@@ -181,8 +345,27 @@ pub(crate) fn draw_puzzle(
         return None; // No repaint needed.
     }
 
-    // Generate the mesh.
-    let (mut verts, mut indices) = mesh::make_puzzle_mesh(puzzle, prefs, &puzzle_geometry);
+    let upload_start = Instant::now();
+
+    // Generate the mesh, with one copy of the puzzle for each eye.
+    cache.mesh.clear();
+    let mut eye_index_ranges = vec![];
+    for &eye in eyes {
+        let lod = prefs.gfx.lod.then(|| mesh::Lod {
+            simplified: &mut cache.lod_simplified,
+            min_size: prefs.gfx.lod_min_size,
+            pixels_per_unit: cgmath::vec2(scale.x * eye_size.x, scale.y * eye_size.y) / 2.0,
+        });
+        let start = cache.mesh.indices.len() as u32;
+        if eye.parallax_sign == 0.0 {
+            mesh::make_puzzle_mesh(&mut cache.mesh, puzzle, prefs, &puzzle_geometry, lod);
+        } else {
+            let eye_geometry =
+                stereo::geometry_for_eye(&puzzle_geometry, eye, prefs.gfx.eye_separation);
+            mesh::make_puzzle_mesh(&mut cache.mesh, puzzle, prefs, &eye_geometry, lod);
+        }
+        eye_index_ranges.push(start..cache.mesh.indices.len() as u32);
+    }
 
     // Create "out" texture that will ultimately be returned.
     let (out_texture, out_texture_view) = cache.out_texture.get_or_insert_with(|| {
@@ -193,7 +376,10 @@ pub(crate) fn draw_puzzle(
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: gfx.config.format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            // Copied when taking a screenshot.
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC,
         })
     });
 
@@ -262,6 +448,61 @@ pub(crate) fn draw_puzzle(
         }
     };
 
+    // Create a pipeline for each eye.
+    for eye in eyes {
+        cache
+            .basic_pipelines
+            .entry(eye.color_writes)
+            .or_insert_with(|| {
+                gfx.device
+                    .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                        label: Some("basic_pipeline"),
+                        layout: Some(&gfx.device.create_pipeline_layout(
+                            &wgpu::PipelineLayoutDescriptor {
+                                label: Some("basic_pipeline_layout"),
+                                bind_group_layouts: &[cache.uniform_buffer.bind_group_layout(gfx)],
+                                push_constant_ranges: &[],
+                            },
+                        )),
+                        vertex: wgpu::VertexState {
+                            module: gfx.shaders.basic.get(gfx),
+                            entry_point: "vs_main",
+                            buffers: &[RgbaVertex::LAYOUT],
+                        },
+                        primitive: wgpu::PrimitiveState {
+                            topology: wgpu::PrimitiveTopology::TriangleList,
+                            strip_index_format: None,
+                            front_face: wgpu::FrontFace::Ccw,
+                            cull_mode: None,
+                            unclipped_depth: false,
+                            polygon_mode: wgpu::PolygonMode::Fill,
+                            conservative: false,
+                        },
+                        depth_stencil: Some(wgpu::DepthStencilState {
+                            format: wgpu::TextureFormat::Depth32Float,
+                            depth_write_enabled: true,
+                            depth_compare: wgpu::CompareFunction::Greater,
+                            stencil: wgpu::StencilState::default(),
+                            bias: wgpu::DepthBiasState::default(),
+                        }),
+                        multisample: wgpu::MultisampleState {
+                            count: prefs.gfx.sample_count(),
+                            ..Default::default()
+                        },
+                        fragment: Some(wgpu::FragmentState {
+                            module: gfx.shaders.basic.get(gfx),
+                            entry_point: "fs_main",
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format: gfx.config.format,
+                                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                                write_mask: eye.color_writes,
+                            })],
+                        }),
+                        multiview: None,
+                    })
+            });
+    }
+
     // Begin the render pass.
     let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
         label: Some("puzzle_stickers_render_pass"),
@@ -276,38 +517,36 @@ pub(crate) fn draw_puzzle(
         }),
     });
 
-    // Draw stickers, if there's anything to draw.
-    if !indices.is_empty() {
-        // Set pipeline.
-        render_pass.set_pipeline(cache.basic_pipeline.get_or_insert_with(|| {
+    // Draw the background, unless it is just the clear color.
+    let background_style = prefs.colors.background_style;
+    if background_style != BackgroundStyle::Flat {
+        render_pass.set_pipeline(cache.background_pipeline.get_or_insert_with(|| {
             gfx.device
                 .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("basic_pipeline"),
+                    label: Some("background_pipeline"),
                     layout: Some(&gfx.device.create_pipeline_layout(
                         &wgpu::PipelineLayoutDescriptor {
-                            label: Some("basic_pipeline_layout"),
-                            bind_group_layouts: &[cache.uniform_buffer.bind_group_layout(gfx)],
+                            label: Some("background_pipeline_layout"),
+                            bind_group_layouts: &[
+                                cache.background_uniform_buffer.bind_group_layout(gfx),
+                            ],
                             push_constant_ranges: &[],
                         },
                     )),
                     vertex: wgpu::VertexState {
-                        module: gfx.shaders.basic.get(gfx),
+                        module: gfx.shaders.background.get(gfx),
                         entry_point: "vs_main",
-                        buffers: &[RgbaVertex::LAYOUT],
+                        buffers: &[],
                     },
                     primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: None,
-                        unclipped_depth: false,
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        conservative: false,
+                        topology: wgpu::PrimitiveTopology::TriangleStrip,
+                        ..Default::default()
                     },
+                    // Don't write depth, so that stickers are drawn on top.
                     depth_stencil: Some(wgpu::DepthStencilState {
                         format: wgpu::TextureFormat::Depth32Float,
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::Greater,
+                        depth_write_enabled: false,
+                        depth_compare: wgpu::CompareFunction::Always,
                         stencil: wgpu::StencilState::default(),
                         bias: wgpu::DepthBiasState::default(),
                     }),
@@ -316,11 +555,11 @@ pub(crate) fn draw_puzzle(
                         ..Default::default()
                     },
                     fragment: Some(wgpu::FragmentState {
-                        module: gfx.shaders.basic.get(gfx),
+                        module: gfx.shaders.background.get(gfx),
                         entry_point: "fs_main",
                         targets: &[Some(wgpu::ColorTargetState {
                             format: gfx.config.format,
-                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            blend: None,
                             write_mask: wgpu::ColorWrites::ALL,
                         })],
                     }),
@@ -328,12 +567,27 @@ pub(crate) fn draw_puzzle(
                 })
         }));
 
+        let uniform = BackgroundUniform {
+            primary: egui::Rgba::from(prefs.colors.background).to_array(),
+            secondary: egui::Rgba::from(prefs.colors.background_secondary).to_array(),
+            style: background_style as u32,
+            aspect_ratio: size.x / size.y,
+            _padding: [0; 2],
+        };
+        cache.background_uniform_buffer.write(gfx, &uniform);
+        render_pass.set_bind_group(0, cache.background_uniform_buffer.bind_group(gfx), &[]);
+
+        render_pass.draw(0..4, 0..1);
+    }
+
+    // Draw stickers, if there's anything to draw.
+    if !cache.mesh.indices.is_empty() {
         // Populate vertex buffer.
-        let vertex_buffer = cache.vertex_buffer.write_all(gfx, &mut verts);
+        let vertex_buffer = cache.vertex_buffer.write_all(gfx, &mut cache.mesh.verts);
         render_pass.set_vertex_buffer(0, vertex_buffer);
 
         // Populate index buffer.
-        let index_buffer = cache.index_buffer.write_all(gfx, &mut indices);
+        let index_buffer = cache.index_buffer.write_all(gfx, &mut cache.mesh.indices);
         render_pass.set_index_buffer(index_buffer, wgpu::IndexFormat::Uint32);
 
         // Populate and bind uniform.
@@ -344,14 +598,28 @@ pub(crate) fn draw_puzzle(
         cache.uniform_buffer.write(gfx, &uniform);
         render_pass.set_bind_group(0, cache.uniform_buffer.bind_group(gfx), &[]);
 
-        // Draw stickers.
-        render_pass.draw_indexed(0..indices.len() as u32, 0, 0..1);
+        // Draw stickers for each eye.
+        for (eye, index_range) in eyes.iter().zip(eye_index_ranges) {
+            render_pass.set_pipeline(&cache.basic_pipelines[&eye.color_writes]);
+            let (viewport_start, viewport_end) = eye.viewport;
+            render_pass.set_viewport(
+                width as f32 * viewport_start,
+                0.0,
+                width as f32 * (viewport_end - viewport_start),
+                height as f32,
+                0.0,
+                1.0,
+            );
+            render_pass.draw_indexed(index_range, 0, 0..1);
+        }
     }
 
     drop(render_pass);
 
     gfx.queue.submit(std::iter::once(encoder.finish()));
 
+    cache.timings.upload.record(upload_start.elapsed());
+
     Some(out_texture.create_view(&wgpu::TextureViewDescriptor::default()))
 }
 
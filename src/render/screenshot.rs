@@ -0,0 +1,246 @@
+//! Offscreen rendering of the puzzle view for screenshots, diagrams, and
+//! replay videos.
+
+use anyhow::{anyhow, bail, Context, Result};
+use instant::{Duration, Instant};
+use std::path::Path;
+
+use super::{GraphicsState, PuzzleRenderCache};
+use crate::app::App;
+use crate::preferences::Preferences;
+use crate::puzzle::PuzzleController;
+
+/// Image of the puzzle view.
+pub(crate) struct Screenshot {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// Pixel data, in RGBA order with one byte per channel.
+    pub(crate) rgba: Vec<u8>,
+}
+impl Screenshot {
+    /// Encodes the screenshot as a PNG file.
+    pub(crate) fn save_png(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.rgba)?;
+        Ok(())
+    }
+
+    /// Copies the screenshot to the clipboard as an image.
+    pub(crate) fn copy_to_clipboard(&self) -> Result<()> {
+        arboard::Clipboard::new()?.set_image(arboard::ImageData {
+            width: self.width as usize,
+            height: self.height as usize,
+            bytes: (&self.rgba).into(),
+        })?;
+        Ok(())
+    }
+}
+
+/// Renders the puzzle view at `scale` times its size on screen and reads it
+/// back from the GPU.
+pub(crate) fn capture_puzzle(
+    app: &mut App,
+    gfx: &mut GraphicsState,
+    scale: u32,
+) -> Result<Screenshot> {
+    let (screen_width, screen_height) = app.puzzle_texture_size;
+    let (width, height) = capture_size(gfx, (screen_width * scale, screen_height * scale))?;
+
+    // Render at the screenshot resolution, then make sure the puzzle is
+    // rendered at its usual resolution next frame.
+    app.puzzle_texture_size = (width, height);
+    super::draw_puzzle(app, gfx, true);
+    app.puzzle_texture_size = (screen_width, screen_height);
+    app.request_redraw_puzzle();
+
+    let (texture, _) = app
+        .render_cache
+        .out_texture
+        .as_ref()
+        .context("puzzle was not rendered")?;
+    read_texture(gfx, texture, width, height)
+}
+
+/// Frame rate of exported replays.
+const REPLAY_FRAMES_PER_SECOND: u32 = 30;
+
+/// Plays the rest of the solution in the redo history and renders it to a
+/// numbered sequence of PNG files in `dir`, moving the camera along the
+/// keyframes in the preferences. Returns the number of frames written.
+///
+/// Twists are animated at the usual twist speed, one after another. Frames
+/// are rendered until both the solution and the camera path have finished.
+pub(crate) fn export_replay_frames(
+    app: &mut App,
+    gfx: &mut GraphicsState,
+    dir: &Path,
+    scale: u32,
+) -> Result<usize> {
+    let (screen_width, screen_height) = app.puzzle_texture_size;
+    let size = capture_size(gfx, (screen_width * scale, screen_height * scale))?;
+    std::fs::create_dir_all(dir)?;
+
+    let ty = app.puzzle.ty();
+    let frame_duration = Duration::from_secs(1) / REPLAY_FRAMES_PER_SECOND;
+    let camera_duration = app.prefs.camera_path.duration();
+    // Move the camera without changing the user's view settings.
+    let mut prefs = app.prefs.clone();
+    let mut cache = PuzzleRenderCache::default();
+
+    app.puzzle.skip_twist_animations();
+    let mut frame_count = 0;
+    loop {
+        let time = frame_count as f32 / REPLAY_FRAMES_PER_SECOND as f32;
+        if app.puzzle.twist_queue_len() == 0 {
+            if app.puzzle.has_redo() {
+                app.puzzle.redo().map_err(|e| anyhow!(e))?;
+            } else if time > camera_duration {
+                break;
+            }
+        }
+        if let Some(keyframe) = prefs.camera_path.sample(time) {
+            keyframe.apply_to_view(prefs.view_mut(ty));
+        }
+
+        // Advance animations by exactly one frame, no matter how long
+        // rendering takes.
+        app.puzzle.update_decorations(frame_duration, &prefs);
+        cache.last_render_time = Instant::now() - frame_duration;
+        super::draw_puzzle_to_texture(&mut app.puzzle, &prefs, &mut cache, size, None, gfx, true);
+        let (texture, _) = cache
+            .out_texture
+            .as_ref()
+            .context("puzzle was not rendered")?;
+        let path = dir.join(format!("frame_{frame_count:05}.png"));
+        read_texture(gfx, texture, size.0, size.1)?.save_png(&path)?;
+        frame_count += 1;
+    }
+
+    app.request_redraw_puzzle();
+    Ok(frame_count)
+}
+
+/// Returns the size to render at, shrinking `(width, height)` if necessary to
+/// fit the GPU's texture size limit without changing the aspect ratio.
+fn capture_size(gfx: &GraphicsState, (width, height): (u32, u32)) -> Result<(u32, u32)> {
+    let max_size = gfx.device.limits().max_texture_dimension_2d;
+    let scale = f64::min(1.0, max_size as f64 / width.max(height).max(1) as f64);
+    let width = (width as f64 * scale) as u32;
+    let height = (height as f64 * scale) as u32;
+    if width == 0 || height == 0 {
+        bail!("image is empty");
+    }
+    Ok((width, height))
+}
+
+/// Renders a puzzle to an image of the given size without a window, for
+/// generating diagrams such as scramble images.
+pub(crate) fn render_puzzle_image(
+    puzzle: &mut PuzzleController,
+    prefs: &Preferences,
+    width: u32,
+    height: u32,
+) -> Result<Screenshot> {
+    let mut gfx = pollster::block_on(GraphicsState::new_headless());
+    let (width, height) = capture_size(&gfx, (width, height))?;
+
+    // Draw the final state, rather than the first frame of any animations.
+    puzzle.skip_twist_animations();
+    puzzle.update_decorations(std::time::Duration::from_secs(60), prefs);
+
+    let mut cache = PuzzleRenderCache::default();
+    super::draw_puzzle_to_texture(
+        puzzle,
+        prefs,
+        &mut cache,
+        (width, height),
+        None,
+        &mut gfx,
+        true,
+    );
+    let (texture, _) = cache
+        .out_texture
+        .as_ref()
+        .context("puzzle was not rendered")?;
+    read_texture(&gfx, texture, width, height)
+}
+
+/// Copies the contents of a texture back from the GPU.
+fn read_texture(
+    gfx: &GraphicsState,
+    texture: &wgpu::Texture,
+    width: u32,
+    height: u32,
+) -> Result<Screenshot> {
+    // Rows of the copy must be padded to a multiple of 256 bytes.
+    let unpadded_bytes_per_row = width * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+    let buffer = gfx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("screenshot_buffer"),
+        size: padded_bytes_per_row as u64 * height as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = gfx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("screenshot_command_encoder"),
+        });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: std::num::NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        super::extent3d(width, height),
+    );
+    gfx.queue.submit(std::iter::once(encoder.finish()));
+
+    // Wait for the GPU to finish copying.
+    let slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    gfx.device.poll(wgpu::Maintain::Wait);
+    rx.recv()
+        .map_err(|_| anyhow!("screenshot buffer was never mapped"))??;
+
+    let is_bgra = matches!(
+        gfx.config.format,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb,
+    );
+    let mut rgba = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    for row in slice
+        .get_mapped_range()
+        .chunks(padded_bytes_per_row as usize)
+    {
+        for pixel in row[..unpadded_bytes_per_row as usize].chunks(4) {
+            let [r, g, b] = if is_bgra {
+                [pixel[2], pixel[1], pixel[0]]
+            } else {
+                [pixel[0], pixel[1], pixel[2]]
+            };
+            // Blending may leave the alpha channel partially transparent, but
+            // the puzzle view is always drawn opaque.
+            rgba.extend([r, g, b, u8::MAX]);
+        }
+    }
+    buffer.unmap();
+
+    Ok(Screenshot {
+        width,
+        height,
+        rgba,
+    })
+}
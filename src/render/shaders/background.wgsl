@@ -0,0 +1,58 @@
+struct VertexOutput {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+struct BackgroundUniform {
+    primary: vec4<f32>,
+    secondary: vec4<f32>,
+    style: u32,
+    aspect_ratio: f32,
+}
+
+let STYLE_GRADIENT: u32 = 1u;
+let STYLE_GRID: u32 = 2u;
+
+// Height of the camera above the grid floor, relative to the spacing between
+// grid lines.
+let GRID_CAMERA_HEIGHT: f32 = 0.25;
+
+@group(0) @binding(0)
+var<uniform> u: BackgroundUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) idx: u32) -> VertexOutput {
+    // Triangle strip covering the whole viewport.
+    let uv = vec2<f32>(f32(idx & 1u), f32(idx >> 1u));
+
+    var out: VertexOutput;
+    out.pos = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if u.style == STYLE_GRADIENT {
+        return mix(u.secondary, u.primary, in.uv.y);
+    }
+
+    // Project the pixel onto a floor below the horizon, which is at the
+    // vertical center of the viewport. Derivatives must be computed in uniform
+    // control flow, so this is done even for pixels above the horizon.
+    let below_horizon = 0.5 - in.uv.y;
+    let depth = GRID_CAMERA_HEIGHT / max(below_horizon, 0.001);
+    let floor_pos = vec2<f32>((in.uv.x - 0.5) * u.aspect_ratio * depth, depth);
+
+    // Antialiased distance to the nearest grid line, in pixels.
+    let line_dist = abs(fract(floor_pos - 0.5) - 0.5) / fwidth(floor_pos);
+    let line = 1.0 - min(min(line_dist.x, line_dist.y), 1.0);
+
+    // Fade out lines near the horizon, where they are too dense to see.
+    let fade = clamp(below_horizon * 4.0, 0.0, 1.0);
+
+    if u.style == STYLE_GRID && below_horizon > 0.0 {
+        return mix(u.primary, u.secondary, line * fade);
+    }
+    return u.primary;
+}
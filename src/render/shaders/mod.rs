@@ -4,11 +4,13 @@ use super::GraphicsState;
 
 pub(super) struct Shaders {
     pub(super) basic: CachedShaderModule,
+    pub(super) background: CachedShaderModule,
 }
 impl Shaders {
     pub(super) fn new() -> Self {
         Self {
             basic: CachedShaderModule::new(|| wgpu::include_wgsl!("basic.wgsl")),
+            background: CachedShaderModule::new(|| wgpu::include_wgsl!("background.wgsl")),
         }
     }
 }
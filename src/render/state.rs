@@ -3,10 +3,14 @@ use super::shaders::Shaders;
 /// Graphics state for the whole window.
 pub(crate) struct GraphicsState {
     pub(crate) size: winit::dpi::PhysicalSize<u32>,
-    pub(crate) surface: wgpu::Surface,
+    instance: wgpu::Instance,
+    /// Surface of the window, or `None` when rendering without a window.
+    pub(crate) surface: Option<wgpu::Surface>,
     pub(crate) device: wgpu::Device,
     pub(crate) queue: wgpu::Queue,
     pub(crate) config: wgpu::SurfaceConfiguration,
+    /// Ways the surface can be composited with what is behind the window.
+    alpha_modes: Vec<wgpu::CompositeAlphaMode>,
 
     pub(super) shaders: Shaders,
 
@@ -24,24 +28,10 @@ impl GraphicsState {
         let surface = unsafe { instance.create_surface(&window) };
 
         // Request adapter.
-        let adapter = request_adapter(&instance, &surface).await;
+        let adapter = request_adapter(&instance, Some(&surface)).await;
 
         // Request device.
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::default(),
-                    limits: if cfg!(target_arch = "wasm32") {
-                        wgpu::Limits::downlevel_webgl2_defaults()
-                    } else {
-                        wgpu::Limits::default()
-                    },
-                    label: None,
-                },
-                None,
-            )
-            .await
-            .unwrap();
+        let (device, queue) = request_device(&adapter).await;
 
         // Configure surface.
         let config = wgpu::SurfaceConfiguration {
@@ -56,11 +46,56 @@ impl GraphicsState {
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
         };
         surface.configure(&device, &config);
-
-        let shaders = Shaders::new();
+        let alpha_modes = surface.get_supported_alpha_modes(&adapter);
 
         let scale_factor = window.scale_factor() as f32;
 
+        let mut this = Self::with_device(
+            size,
+            instance,
+            Some(surface),
+            device,
+            queue,
+            config,
+            scale_factor,
+        );
+        this.alpha_modes = alpha_modes;
+        this
+    }
+
+    /// Creates graphics state for rendering offscreen, without a window.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) async fn new_headless() -> Self {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let adapter = request_adapter(&instance, None).await;
+        let (device, queue) = request_device(&adapter).await;
+
+        // There is no surface to present to, so the size only matters to code
+        // that draws the UI.
+        let size = winit::dpi::PhysicalSize::new(1, 1);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::AutoNoVsync,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        };
+
+        Self::with_device(size, instance, None, device, queue, config, 1.0)
+    }
+
+    fn with_device(
+        size: winit::dpi::PhysicalSize<u32>,
+        instance: wgpu::Instance,
+        surface: Option<wgpu::Surface>,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+        scale_factor: f32,
+    ) -> Self {
+        let shaders = Shaders::new();
+
         let dummy_texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("dummy_texture"),
             size: wgpu::Extent3d::default(),
@@ -73,10 +108,12 @@ impl GraphicsState {
 
         Self {
             size,
+            instance,
             surface,
             device,
             queue,
             config,
+            alpha_modes: vec![],
 
             shaders,
 
@@ -91,8 +128,56 @@ impl GraphicsState {
             self.size = new_size;
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+            if let Some(surface) = &self.surface {
+                surface.configure(&self.device, &self.config);
+            }
+        }
+    }
+
+    /// Creates a surface for another window, which is drawn using the same
+    /// device as the main window.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn create_window_surface(
+        &self,
+        window: &winit::window::Window,
+    ) -> (wgpu::Surface, wgpu::SurfaceConfiguration) {
+        let size = window.inner_size();
+        let surface = unsafe { self.instance.create_surface(window) };
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: self.config.format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::AutoNoVsync,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        };
+        surface.configure(&self.device, &config);
+        (surface, config)
+    }
+
+    /// Sets whether the window can be see-through where it is drawn with
+    /// partial opacity. Returns whether the window is now see-through, which
+    /// may be `false` even if requested when the platform does not support it.
+    pub(crate) fn set_transparent(&mut self, transparent: bool) -> bool {
+        let alpha_mode = if transparent {
+            let supported = [
+                wgpu::CompositeAlphaMode::PreMultiplied,
+                wgpu::CompositeAlphaMode::Inherit,
+            ];
+            match supported.into_iter().find(|m| self.alpha_modes.contains(m)) {
+                Some(m) => m,
+                None => return false,
+            }
+        } else {
+            wgpu::CompositeAlphaMode::Auto
+        };
+        if self.config.alpha_mode != alpha_mode {
+            self.config.alpha_mode = alpha_mode;
+            if let Some(surface) = &self.surface {
+                surface.configure(&self.device, &self.config);
+            }
         }
+        transparent
     }
 
     pub(crate) fn set_scale_factor(&mut self, new_scale_factor: f32) {
@@ -122,7 +207,7 @@ impl GraphicsState {
                     label: label.map(|s| format!("{s}_bind_group_layout")).as_deref(),
                     entries: &[wgpu::BindGroupLayoutEntry {
                         binding,
-                        visibility: wgpu::ShaderStages::VERTEX,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                         ty: wgpu::BindingType::Buffer {
                             ty: wgpu::BufferBindingType::Uniform,
                             has_dynamic_offset: false,
@@ -180,10 +265,13 @@ impl GraphicsState {
     }
 }
 
-async fn request_adapter(instance: &wgpu::Instance, surface: &wgpu::Surface) -> wgpu::Adapter {
+async fn request_adapter(
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface>,
+) -> wgpu::Adapter {
     let mut opts = wgpu::RequestAdapterOptions {
         power_preference: wgpu::PowerPreference::HighPerformance,
-        compatible_surface: Some(surface),
+        compatible_surface: surface,
         force_fallback_adapter: false,
     };
 
@@ -197,3 +285,21 @@ async fn request_adapter(instance: &wgpu::Instance, surface: &wgpu::Surface) ->
 
     panic!("unable to request graphics adapter")
 }
+
+async fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+    adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                features: wgpu::Features::default(),
+                limits: if cfg!(target_arch = "wasm32") {
+                    wgpu::Limits::downlevel_webgl2_defaults()
+                } else {
+                    wgpu::Limits::default()
+                },
+                label: None,
+            },
+            None,
+        )
+        .await
+        .unwrap()
+}
@@ -0,0 +1,123 @@
+//! Stereoscopic rendering, which draws the puzzle once for each eye.
+
+use cgmath::{Point2, Point3};
+use std::sync::Arc;
+
+use crate::preferences::StereoMode;
+use crate::puzzle::{Polygon, ProjectedStickerGeometry};
+use crate::util;
+
+/// One view of the puzzle.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(super) struct Eye {
+    /// Direction in which nearer geometry is shifted horizontally. This is
+    /// positive for the left eye and negative for the right eye.
+    pub parallax_sign: f32,
+    /// Color channels that the eye draws to.
+    pub color_writes: wgpu::ColorWrites,
+    /// Horizontal range of the render target that the eye draws to, as
+    /// fractions of its width.
+    pub viewport: (f32, f32),
+}
+impl Eye {
+    const MONO: Self = Self {
+        parallax_sign: 0.0,
+        color_writes: wgpu::ColorWrites::ALL,
+        viewport: (0.0, 1.0),
+    };
+
+    /// Returns the width of the eye's viewport, as a fraction of the width of
+    /// the render target.
+    pub fn viewport_width(self) -> f32 {
+        self.viewport.1 - self.viewport.0
+    }
+}
+
+/// Returns the eyes to draw, from left to right.
+pub(super) fn eyes(mode: StereoMode) -> &'static [Eye] {
+    match mode {
+        StereoMode::Off => &[Eye::MONO],
+        StereoMode::SideBySide => &[
+            Eye {
+                parallax_sign: 1.0,
+                viewport: (0.0, 0.5),
+                ..Eye::MONO
+            },
+            Eye {
+                parallax_sign: -1.0,
+                viewport: (0.5, 1.0),
+                ..Eye::MONO
+            },
+        ],
+        StereoMode::Anaglyph => &[
+            Eye {
+                parallax_sign: 1.0,
+                color_writes: wgpu::ColorWrites::RED,
+                ..Eye::MONO
+            },
+            Eye {
+                parallax_sign: -1.0,
+                color_writes: wgpu::ColorWrites::GREEN.union(wgpu::ColorWrites::BLUE),
+                ..Eye::MONO
+            },
+        ],
+    }
+}
+
+/// Converts a cursor position on the render target to a position within the
+/// viewport of whichever eye it is over. Both coordinates range from -1 to 1.
+///
+/// This ignores parallax, which is small enough not to matter for picking
+/// stickers.
+pub(super) fn cursor_pos_in_eye(mode: StereoMode, cursor_pos: Point2<f32>) -> Point2<f32> {
+    let x = (cursor_pos.x + 1.0) / 2.0;
+    let eyes = eyes(mode);
+    let eye = eyes
+        .iter()
+        .find(|eye| x < eye.viewport.1)
+        .unwrap_or(&eyes[eyes.len() - 1]);
+    let x_in_eye = (x - eye.viewport.0) / eye.viewport_width();
+    cgmath::point2(x_in_eye * 2.0 - 1.0, cursor_pos.y)
+}
+
+/// Returns the puzzle geometry as seen by `eye`, by shifting each vertex
+/// horizontally in proportion to its depth. Vertices at the center of the
+/// puzzle stay in place.
+pub(super) fn geometry_for_eye(
+    geometry: &[ProjectedStickerGeometry],
+    eye: Eye,
+    eye_separation: f32,
+) -> Vec<ProjectedStickerGeometry> {
+    let parallax = eye.parallax_sign * eye_separation / 2.0;
+    let shift = |v: &Point3<f32>| cgmath::point3(v.x + parallax * v.z, v.y, v.z);
+
+    geometry
+        .iter()
+        .map(|geom| {
+            let verts: Arc<[Point3<f32>]> = geom.verts.iter().map(shift).collect();
+            let (min_bound, max_bound) = util::min_and_max_bound(&verts);
+            let shift_polygons = |polygons: &[Polygon]| {
+                polygons
+                    .iter()
+                    .map(|p| {
+                        Polygon::new(
+                            p.verts.iter().map(shift).collect(),
+                            p.illumination,
+                            p.twists,
+                        )
+                    })
+                    .collect()
+            };
+            ProjectedStickerGeometry {
+                sticker: geom.sticker,
+
+                verts,
+                min_bound,
+                max_bound,
+
+                front_polygons: shift_polygons(&geom.front_polygons),
+                back_polygons: shift_polygons(&geom.back_polygons),
+            }
+        })
+        .collect()
+}
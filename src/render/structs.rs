@@ -23,3 +23,14 @@ pub(super) struct BasicUniform {
     pub scale: [f32; 2],
     pub align: [f32; 2],
 }
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub(super) struct BackgroundUniform {
+    pub primary: [f32; 4],
+    pub secondary: [f32; 4],
+    pub style: u32,
+    pub aspect_ratio: f32,
+    /// Uniforms must be a multiple of 16 bytes.
+    pub _padding: [u32; 2],
+}
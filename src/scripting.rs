@@ -0,0 +1,297 @@
+//! User scripts for custom trainers, labelers, and exporters.
+//!
+//! Scripts are written in [Rhai](https://rhai.rs/). Functions that modify the
+//! puzzle queue actions which are applied after the script returns, so scripts
+//! always see the puzzle state from when they were called.
+//!
+//! A script may define these functions to respond to events:
+//!
+//! - `on_twist(twist)` is called after each twist made by the user.
+//! - `on_solve(time_ms)` is called when the puzzle is solved.
+
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::puzzle::{traits::*, PuzzleController, Sticker};
+
+/// Maximum number of lines of script output to keep.
+const MAX_OUTPUT_LINES: usize = 500;
+/// Maximum number of operations a script may run per call, so that a script
+/// stuck in a loop can't freeze the app.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Action requested by a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptAction {
+    /// Twist written in the puzzle's notation.
+    Twist(String),
+    ScrambleN(usize),
+    ScrambleFull,
+    Undo,
+    Redo,
+    Reset,
+    Select(Sticker),
+    DeselectAll,
+}
+
+/// Puzzle state visible to scripts.
+#[derive(Debug, Default, Clone)]
+struct PuzzleSnapshot {
+    puzzle: String,
+    sticker_count: usize,
+    twists: Vec<String>,
+    is_solved: bool,
+    scramble_state: String,
+}
+impl PuzzleSnapshot {
+    fn new(puzzle: &PuzzleController) -> Self {
+        let notation = puzzle.notation_scheme();
+        Self {
+            puzzle: puzzle.ty().name().to_owned(),
+            sticker_count: puzzle.stickers().len(),
+            twists: puzzle
+                .undo_buffer()
+                .iter()
+                .map(|entry| entry.to_string(notation))
+                .collect(),
+            is_solved: puzzle.is_solved(),
+            scramble_state: puzzle.scramble_state().description().to_owned(),
+        }
+    }
+}
+
+/// State shared between the script engine and the functions it calls.
+#[derive(Debug, Default)]
+struct ScriptContext {
+    snapshot: PuzzleSnapshot,
+    actions: Vec<ScriptAction>,
+    output: Vec<String>,
+}
+
+/// Engine for running a user script.
+pub struct ScriptEngine {
+    engine: Engine,
+    ctx: Rc<RefCell<ScriptContext>>,
+    scope: Scope<'static>,
+    ast: Option<AST>,
+}
+impl Default for ScriptEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl ScriptEngine {
+    pub fn new() -> Self {
+        let ctx = Rc::new(RefCell::new(ScriptContext::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let c = Rc::clone(&ctx);
+        engine.on_print(move |s| c.borrow_mut().log(s.to_owned()));
+        let c = Rc::clone(&ctx);
+        engine.on_debug(move |s, _src, pos| c.borrow_mut().log(format!("[{pos}] {s}")));
+
+        macro_rules! register_action {
+            ($name:literal, |$($arg:ident: $arg_ty:ty),*| $action:expr) => {{
+                let c = Rc::clone(&ctx);
+                engine.register_fn($name, move |$($arg: $arg_ty),*| {
+                    c.borrow_mut().actions.push($action)
+                });
+            }};
+        }
+        register_action!("twist", |s: &str| ScriptAction::Twist(s.to_owned()));
+        register_action!("scramble", |n: i64| ScriptAction::ScrambleN(n.max(0) as usize));
+        register_action!("scramble_full", | | ScriptAction::ScrambleFull);
+        register_action!("undo", | | ScriptAction::Undo);
+        register_action!("redo", | | ScriptAction::Redo);
+        register_action!("reset", | | ScriptAction::Reset);
+        register_action!("deselect_all", | | ScriptAction::DeselectAll);
+
+        let c = Rc::clone(&ctx);
+        engine.register_fn("select", move |i: i64| -> Result<(), Box<EvalAltResult>> {
+            let mut ctx = c.borrow_mut();
+            // Check bounds before narrowing so that out-of-range indices can't
+            // wrap around to valid ones.
+            if !(0..ctx.snapshot.sticker_count as i64).contains(&i) {
+                return Err(format!("no sticker with index {i}").into());
+            }
+            ctx.actions.push(ScriptAction::Select(Sticker(i as u16)));
+            Ok(())
+        });
+
+        macro_rules! register_query {
+            ($name:literal, |$snapshot:ident| $value:expr) => {{
+                let c = Rc::clone(&ctx);
+                engine.register_fn($name, move || {
+                    let guard = c.borrow();
+                    let $snapshot = &guard.snapshot;
+                    $value
+                });
+            }};
+        }
+        register_query!("puzzle", |s| s.puzzle.clone());
+        register_query!("sticker_count", |s| s.sticker_count as i64);
+        register_query!("twist_count", |s| s.twists.len() as i64);
+        register_query!("twists", |s| {
+            s.twists.iter().cloned().map(Dynamic::from).collect::<rhai::Array>()
+        });
+        register_query!("is_solved", |s| s.is_solved);
+        register_query!("scramble_state", |s| s.scramble_state.clone());
+
+        Self {
+            engine,
+            ctx,
+            scope: Scope::new(),
+            ast: None,
+        }
+    }
+
+    /// Compiles and runs a script, replacing any previously loaded script.
+    ///
+    /// If the script fails, the error is also written to the output and any
+    /// actions it requested are discarded. A script that fails while running
+    /// stays loaded so that its hooks can still be called.
+    pub fn load(
+        &mut self,
+        source: &str,
+        puzzle: &PuzzleController,
+    ) -> Result<Vec<ScriptAction>, String> {
+        self.ast = None;
+        self.scope = Scope::new();
+        self.ctx.borrow_mut().snapshot = PuzzleSnapshot::new(puzzle);
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|e| self.fail(format!("Error: {e}")))?;
+        let result = self.engine.run_ast_with_scope(&mut self.scope, &ast);
+        self.ast = Some(ast);
+        result.map_err(|e| self.fail(format!("Error: {e}")))?;
+        Ok(self.take_actions())
+    }
+    /// Unloads the current script.
+    pub fn unload(&mut self) {
+        self.ast = None;
+        self.scope = Scope::new();
+    }
+    /// Returns whether a script is loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.ast.is_some()
+    }
+
+    /// Calls the script's `on_twist` hook, if it has one.
+    pub fn on_twist(
+        &mut self,
+        twist: String,
+        puzzle: &PuzzleController,
+    ) -> Result<Vec<ScriptAction>, String> {
+        self.call_hook("on_twist", (twist,), puzzle)
+    }
+    /// Calls the script's `on_solve` hook, if it has one.
+    pub fn on_solve(
+        &mut self,
+        time_ms: Option<u64>,
+        puzzle: &PuzzleController,
+    ) -> Result<Vec<ScriptAction>, String> {
+        let time_ms = time_ms.map_or(Dynamic::UNIT, |t| Dynamic::from(t as i64));
+        self.call_hook("on_solve", (time_ms,), puzzle)
+    }
+
+    /// Returns the lines printed by the script.
+    pub fn output(&self) -> Vec<String> {
+        self.ctx.borrow().output.clone()
+    }
+    /// Clears the lines printed by the script.
+    pub fn clear_output(&mut self) {
+        self.ctx.borrow_mut().output.clear();
+    }
+
+    fn call_hook(
+        &mut self,
+        name: &str,
+        args: impl rhai::FuncArgs,
+        puzzle: &PuzzleController,
+    ) -> Result<Vec<ScriptAction>, String> {
+        let Some(ast) = &self.ast else { return Ok(vec![]) };
+        if !ast.iter_functions().any(|f| f.name == name) {
+            return Ok(vec![]);
+        }
+        self.ctx.borrow_mut().snapshot = PuzzleSnapshot::new(puzzle);
+        let result = self.engine.call_fn::<Dynamic>(&mut self.scope, ast, name, args);
+        result.map_err(|e| self.fail(format!("Error in {name}: {e}")))?;
+        Ok(self.take_actions())
+    }
+
+    fn take_actions(&mut self) -> Vec<ScriptAction> {
+        std::mem::take(&mut self.ctx.borrow_mut().actions)
+    }
+    /// Logs an error, discards pending actions, and returns the error message.
+    fn fail(&self, msg: String) -> String {
+        let mut ctx = self.ctx.borrow_mut();
+        ctx.actions.clear();
+        ctx.log(msg.clone());
+        msg
+    }
+}
+
+impl ScriptContext {
+    fn log(&mut self, line: String) {
+        self.output.push(line);
+        if self.output.len() > MAX_OUTPUT_LINES {
+            self.output.remove(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::PuzzleTypeEnum;
+
+    fn puzzle() -> PuzzleController {
+        PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 })
+    }
+
+    #[test]
+    fn test_twist_and_select_bindings() {
+        let puzzle = puzzle();
+        let mut script = ScriptEngine::new();
+        let actions = script.load(r#"twist("R"); select(5); twist("U'");"#, &puzzle);
+        assert_eq!(
+            actions.unwrap(),
+            [
+                ScriptAction::Twist("R".to_owned()),
+                ScriptAction::Select(Sticker(5)),
+                ScriptAction::Twist("U'".to_owned()),
+            ],
+        );
+
+        let actions = script.load("fn on_twist(t) { twist(t); }", &puzzle);
+        assert!(actions.unwrap().is_empty());
+        let actions = script.on_twist("F2".to_owned(), &puzzle);
+        assert_eq!(actions.unwrap(), [ScriptAction::Twist("F2".to_owned())]);
+    }
+
+    #[test]
+    fn test_select_out_of_bounds() {
+        let puzzle = puzzle();
+        let sticker_count = puzzle.stickers().len();
+        let mut script = ScriptEngine::new();
+        for i in [-1, sticker_count as i64, 65536] {
+            let result = script.load(&format!("twist(\"R\"); select({i});"), &puzzle);
+            assert!(result.is_err(), "select({i}) should fail");
+        }
+        // Actions queued before the error are discarded.
+        assert!(script.take_actions().is_empty());
+    }
+
+    #[test]
+    fn test_operation_limit() {
+        let puzzle = puzzle();
+        let mut script = ScriptEngine::new();
+        assert!(script.load("loop {}", &puzzle).is_err());
+
+        script.load("fn on_twist(t) { loop {} }", &puzzle).unwrap();
+        assert!(script.on_twist("R".to_owned(), &puzzle).is_err());
+    }
+}
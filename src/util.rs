@@ -1,108 +1,34 @@
-use cgmath::Point3;
-use std::ops::{Add, Mul};
+pub use hyperspeedcube_core::util::{min_and_max_bound, mix, wrap_words, IterCyclicPairsExt};
 
 pub const INVALID_STR: &str = "<invalid>";
 
-pub struct CyclicPairsIter<I: Iterator> {
-    first: Option<I::Item>,
-    prev: Option<I::Item>,
-    rest: I,
+/// Returns the current time as a Unix timestamp in seconds.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_timestamp() -> i64 {
+    time::OffsetDateTime::now_utc().unix_timestamp()
 }
-impl<I> Iterator for CyclicPairsIter<I>
-where
-    I: Iterator,
-    I::Item: Clone,
-{
-    type Item = (I::Item, I::Item);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        Some(match self.rest.next() {
-            Some(curr) => (self.prev.replace(curr.clone())?, curr),
-            None => (self.prev.take()?, self.first.take()?),
-        })
-    }
-
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let (lo, hi) = self.rest.size_hint();
-        (lo.saturating_add(1), hi.and_then(|x| x.checked_add(1)))
-    }
-}
-
-pub trait IterCyclicPairsExt: Iterator + Sized {
-    fn cyclic_pairs(self) -> CyclicPairsIter<Self>;
-}
-impl<I> IterCyclicPairsExt for I
-where
-    I: Iterator,
-    I::Item: Clone,
-{
-    fn cyclic_pairs(mut self) -> CyclicPairsIter<Self> {
-        let first = self.next();
-        let prev = first.clone();
-        CyclicPairsIter {
-            first,
-            prev,
-            rest: self,
-        }
-    }
-}
-
-pub fn min_and_max_bound(verts: &[Point3<f32>]) -> (Point3<f32>, Point3<f32>) {
-    let mut min_bound = verts[0];
-    let mut max_bound = verts[0];
-
-    for v in &verts[1..] {
-        if v.x < min_bound.x {
-            min_bound.x = v.x;
-        }
-        if v.y < min_bound.y {
-            min_bound.y = v.y;
-        }
-        if v.z < min_bound.z {
-            min_bound.z = v.z;
-        }
-
-        if v.x > max_bound.x {
-            max_bound.x = v.x;
-        }
-        if v.y > max_bound.y {
-            max_bound.y = v.y;
-        }
-        if v.z > max_bound.z {
-            max_bound.z = v.z;
-        }
-    }
-
-    (min_bound, max_bound)
+/// Returns the current time as a Unix timestamp in seconds.
+#[cfg(target_arch = "wasm32")]
+pub fn unix_timestamp() -> i64 {
+    (js_sys::Date::now() / 1000.0) as i64
 }
-
-pub fn wrap_words<S: AsRef<str>>(words: impl Iterator<Item = S>) -> String {
-    const WORD_WRAP_WIDTH: usize = 70;
-    let mut ret = String::new();
-    let mut column = 0;
-    for word in words {
-        let word = word.as_ref();
-        if column == 0 {
-            column += word.len();
-            ret += word;
-        } else {
-            column += word.len() + 1;
-            if column <= WORD_WRAP_WIDTH {
-                ret += " ";
-            } else {
-                column = word.len();
-                ret += "\n";
-            }
-            ret += word;
-        }
-    }
-    ret
+/// Returns the number of whole days since the Unix epoch, in UTC.
+pub fn unix_day() -> i64 {
+    unix_timestamp().div_euclid(60 * 60 * 24)
 }
-
-pub fn mix<T>(a: T, b: T, t: f32) -> <T::Output as Add>::Output
-where
-    T: Mul<f32>,
-    T::Output: Add,
-{
-    a * (1.0 - t) + b * t
+/// Formats a Unix timestamp in seconds as a local date and time, falling
+/// back to UTC if the local time zone is unknown.
+pub fn format_unix_timestamp(timestamp: i64) -> String {
+    let Ok(t) = time::OffsetDateTime::from_unix_timestamp(timestamp) else {
+        return "?".to_owned();
+    };
+    let t = t.to_offset(time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC));
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        t.year(),
+        t.month() as u8,
+        t.day(),
+        t.hour(),
+        t.minute(),
+    )
 }
@@ -0,0 +1,55 @@
+//! Opening and saving files through the browser, since there is no file
+//! system on web.
+
+use wasm_bindgen::{JsCast, JsValue};
+use winit::event_loop::EventLoopProxy;
+
+use crate::app::AppEvent;
+
+/// Asks the user to pick a log file, then sends its contents to the app as an
+/// [`AppEvent::WebFileOpened`].
+pub(crate) fn open_log_file(events: EventLoopProxy<AppEvent>) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let Some(file) = rfd::AsyncFileDialog::new()
+            .add_filter("Hyperspeedcube Log Files", &["hsc", "log"])
+            .pick_file()
+            .await
+        else {
+            return;
+        };
+        let event = match String::from_utf8(file.read().await) {
+            Ok(contents) => AppEvent::WebFileOpened {
+                name: file.file_name(),
+                contents,
+            },
+            Err(e) => AppEvent::StatusError(format!("Unable to load log file: {e}")),
+        };
+        let _ = events.send_event(event);
+    });
+}
+
+/// Offers a text file to the user as a download.
+pub(crate) fn download_file(file_name: &str, contents: &str) -> Result<(), String> {
+    let js_err = |e: JsValue| format!("{e:?}");
+
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_("text/plain");
+    let blob =
+        web_sys::Blob::new_with_str_sequence_and_options(&parts, &options).map_err(js_err)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_err)?;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("unable to access document")?;
+    let anchor = document
+        .create_element("a")
+        .map_err(js_err)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| "unable to create link")?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url).map_err(js_err)
+}